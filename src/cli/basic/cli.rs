@@ -28,7 +28,7 @@ use crate::{
     },
     common::{infra::config::USERS, meta},
     migration,
-    service::{compact, db, file_list, users},
+    service::{compact, db, file_list, org_bundle, users},
 };
 
 pub async fn cli() -> Result<bool, anyhow::Error> {
@@ -50,6 +50,45 @@ pub async fn cli() -> Result<bool, anyhow::Error> {
                 .about("import openobserve data").args(dataArgs()),
             clap::Command::new("export")
                 .about("export openobserve data").args(dataArgs()),
+            clap::Command::new("export-org")
+                .about("export the metadata of an organization into a bundle directory")
+                .args([
+                    clap::Arg::new("org")
+                        .short('o')
+                        .long("org")
+                        .required(true)
+                        .help("organization to export"),
+                    clap::Arg::new("path")
+                        .short('p')
+                        .long("path")
+                        .required(true)
+                        .help("bundle directory"),
+                    clap::Arg::new("with-data")
+                        .long("with-data")
+                        .required(false)
+                        .action(clap::ArgAction::SetTrue)
+                        .help("also export the data files referenced from file_list"),
+                ]),
+            clap::Command::new("import-org")
+                .about("import an organization bundle")
+                .args([
+                    clap::Arg::new("path")
+                        .short('p')
+                        .long("path")
+                        .required(true)
+                        .help("bundle directory"),
+                    clap::Arg::new("org")
+                        .short('o')
+                        .long("org")
+                        .required(false)
+                        .help("target organization, default is the exported one"),
+                    clap::Arg::new("conflict")
+                        .short('c')
+                        .long("conflict")
+                        .required(false)
+                        .default_value("skip")
+                        .help("what to do with existing objects: skip, overwrite, fail"),
+                ]),
             clap::Command::new("view")
                 .about("view openobserve data")
                 .arg(
@@ -244,6 +283,62 @@ pub async fn cli() -> Result<bool, anyhow::Error> {
             ]),
             clap::Command::new("upgrade-db")
                 .about("upgrade db table schemas").args(dataArgs()),
+            clap::Command::new("wal").about("inspect and repair wal files").subcommands([
+                clap::Command::new("list").about("list wal files").args([
+                    clap::Arg::new("path")
+                        .short('p')
+                        .long("path")
+                        .required(false)
+                        .help("wal dir or file, default is the wal dir of the local node"),
+                ]),
+                clap::Command::new("dump").about("dump entries of a wal file as json").args([
+                    clap::Arg::new("file")
+                        .short('f')
+                        .long("file")
+                        .required(true)
+                        .help("wal file"),
+                    clap::Arg::new("limit")
+                        .short('l')
+                        .long("limit")
+                        .required(false)
+                        .default_value("0")
+                        .value_parser(clap::value_parser!(usize))
+                        .help("max number of entries to dump, 0 means all"),
+                ]),
+                clap::Command::new("verify").about("verify checksums of wal files").args([
+                    clap::Arg::new("path")
+                        .short('p')
+                        .long("path")
+                        .required(false)
+                        .help("wal dir or file, default is the wal dir of the local node"),
+                ]),
+                clap::Command::new("truncate").about("truncate the corrupted tail of a wal file").args([
+                    clap::Arg::new("file")
+                        .short('f')
+                        .long("file")
+                        .required(true)
+                        .help("wal file"),
+                    clap::Arg::new("dry-run")
+                        .long("dry-run")
+                        .required(false)
+                        .action(clap::ArgAction::SetTrue)
+                        .help("only show what would be truncated"),
+                ]),
+                clap::Command::new("reingest").about("re-ingest a wal file into the running node").args([
+                    clap::Arg::new("file")
+                        .short('f')
+                        .long("file")
+                        .required(true)
+                        .help("wal file"),
+                    clap::Arg::new("batch-size")
+                        .short('b')
+                        .long("batch-size")
+                        .required(false)
+                        .default_value("1000")
+                        .value_parser(clap::value_parser!(usize))
+                        .help("number of records per request"),
+                ]),
+            ]),
         ])
         .get_matches();
 
@@ -267,6 +362,41 @@ pub async fn cli() -> Result<bool, anyhow::Error> {
         }
         return Ok(true);
     }
+    if name == "wal" {
+        // wal commands work on local files, no need to init infra
+        match command.subcommand() {
+            Some(("list", args)) => {
+                super::wal::list(args.get_one::<String>("path"))?;
+            }
+            Some(("dump", args)) => {
+                let file = args.get_one::<String>("file").unwrap();
+                let limit = args.get_one::<usize>("limit").copied().unwrap_or_default();
+                super::wal::dump(file, limit)?;
+            }
+            Some(("verify", args)) => {
+                if !super::wal::verify(args.get_one::<String>("path"))? {
+                    return Err(anyhow::anyhow!("found corrupted wal files"));
+                }
+            }
+            Some(("truncate", args)) => {
+                let file = args.get_one::<String>("file").unwrap();
+                super::wal::truncate(file, args.get_flag("dry-run"))?;
+            }
+            Some(("reingest", args)) => {
+                let file = args.get_one::<String>("file").unwrap();
+                let batch_size = args
+                    .get_one::<usize>("batch-size")
+                    .copied()
+                    .unwrap_or(1000)
+                    .max(1);
+                super::wal::reingest(file, batch_size).await?;
+            }
+            _ => {
+                return Err(anyhow::anyhow!("unsupported sub command: {name}"));
+            }
+        }
+        return Ok(true);
+    }
 
     // init infra, create data dir & tables
     let cfg = config::get_config();
@@ -421,6 +551,41 @@ pub async fn cli() -> Result<bool, anyhow::Error> {
             crate::common::infra::cluster::register_and_keep_alive().await?;
             export::Export::operator(dataCli::arg_matches(command.clone())).await?;
         }
+        "export-org" => {
+            crate::common::infra::init().await?;
+            crate::common::infra::cluster::register_and_keep_alive().await?;
+            let org = command.get_one::<String>("org").unwrap();
+            let path = command.get_one::<String>("path").unwrap();
+            let bundle = org_bundle::export(
+                org,
+                std::path::Path::new(path),
+                command.get_flag("with-data"),
+            )
+            .await?;
+            println!(
+                "exported org {org} to {path}: {} dashboards, {} alerts, {} pipelines, {} streams, {} files",
+                bundle.dashboards.len(),
+                bundle.alerts.len(),
+                bundle.pipelines.len(),
+                bundle.streams.len(),
+                bundle.files.len()
+            );
+        }
+        "import-org" => {
+            crate::common::infra::init().await?;
+            crate::common::infra::cluster::register_and_keep_alive().await?;
+            let path = command.get_one::<String>("path").unwrap();
+            let org = command.get_one::<String>("org").map(|s| s.as_str());
+            let mode = command.get_one::<String>("conflict").unwrap().parse()?;
+            let report = org_bundle::import(std::path::Path::new(path), org, mode).await?;
+            println!("{}", config::utils::json::to_string(&report)?);
+            if !report.errors.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "failed to import {} objects",
+                    report.errors.len()
+                ));
+            }
+        }
         "migrate-schemas" => {
             println!("Running schema migration to row per schema version");
             #[allow(deprecated)]
@@ -292,6 +292,21 @@ pub async fn consistent_hash(files: Vec<String>) -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+pub async fn ingest_json(
+    org_id: &str,
+    stream_type: config::meta::stream::StreamType,
+    stream_name: &str,
+    data: Vec<serde_json::Value>,
+) -> Result<(), anyhow::Error> {
+    let url = match stream_type {
+        config::meta::stream::StreamType::Metrics => format!("/api/{org_id}/ingest/metrics/_json"),
+        _ => format!("/api/{org_id}/{stream_name}/_json"),
+    };
+    let body = serde_json::to_vec(&data)?;
+    request(&url, Some(body), reqwest::Method::POST).await?;
+    Ok(())
+}
+
 async fn request(
     url: &str,
     body: Option<Vec<u8>>,
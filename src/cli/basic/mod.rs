@@ -17,3 +17,4 @@ pub mod cli;
 mod http;
 mod load;
 mod test;
+mod wal;
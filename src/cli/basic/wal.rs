@@ -0,0 +1,356 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::path::{Path, PathBuf};
+
+use config::{
+    meta::stream::StreamType,
+    utils::{file::scan_files, size::bytes_to_human_readable},
+};
+use ingester::Entry;
+use prettytable::{Cell, Row, Table};
+
+/// The result of reading a wal file from the beginning to the end
+#[derive(Debug, Default)]
+pub struct WalScan {
+    pub entries: usize,
+    pub records: usize,
+    /// the length of the file which contains valid entries
+    pub valid_len: u64,
+    pub file_len: u64,
+    /// the error of the first corrupted entry
+    pub error: Option<String>,
+}
+
+impl WalScan {
+    pub fn is_corrupted(&self) -> bool {
+        self.error.is_some()
+    }
+}
+
+/// Reads all the entries of the wal file, the callback is called for every
+/// valid entry with the offset of the entry.
+pub fn scan(
+    path: &Path,
+    mut f: impl FnMut(u64, Entry) -> Result<(), anyhow::Error>,
+) -> Result<WalScan, anyhow::Error> {
+    let mut ret = WalScan {
+        file_len: std::fs::metadata(path)?.len(),
+        ..Default::default()
+    };
+    let mut reader = wal::Reader::from_path(path)?;
+    loop {
+        let offset = reader.current_position()?;
+        ret.valid_len = offset;
+        let entry = match reader.read_entry() {
+            Ok(Some(entry)) => entry,
+            Ok(None) => break,
+            Err(e) => {
+                ret.error = Some(e.to_string());
+                break;
+            }
+        };
+        if entry.is_empty() {
+            continue;
+        }
+        let entry = match Entry::from_bytes(&entry) {
+            Ok(v) => v,
+            Err(e) => {
+                ret.error = Some(format!("decode entry error: {e}"));
+                break;
+            }
+        };
+        ret.entries += 1;
+        ret.records += entry.data.len();
+        f(offset, entry)?;
+    }
+    if ret.error.is_none() {
+        ret.valid_len = reader.current_position()?;
+    }
+    Ok(ret)
+}
+
+fn get_wal_files(path: Option<&String>) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => PathBuf::from(&config::get_config().common.data_wal_dir).join("logs"),
+    };
+    if path.is_file() {
+        return Ok(vec![path]);
+    }
+    let mut files = scan_files(&path, "wal", None)?
+        .into_iter()
+        .map(PathBuf::from)
+        .collect::<Vec<_>>();
+    files.sort();
+    Ok(files)
+}
+
+// the wal file path is: {wal_dir}/logs/{idx}/{org}/{stype}/{id}.wal
+fn get_org_and_stream_type(path: &Path) -> Option<(String, StreamType)> {
+    let stream_type = path.parent()?;
+    let org_id = stream_type.parent()?;
+    Some((
+        org_id.file_name()?.to_str()?.to_string(),
+        StreamType::from(stream_type.file_name()?.to_str()?),
+    ))
+}
+
+pub fn list(path: Option<&String>) -> Result<(), anyhow::Error> {
+    let files = get_wal_files(path)?;
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("File"),
+        Cell::new("Size"),
+        Cell::new("Modified"),
+    ]));
+    for file in files.iter() {
+        let meta = std::fs::metadata(file)?;
+        let modified: chrono::DateTime<chrono::Utc> = meta.modified()?.into();
+        table.add_row(Row::new(vec![
+            Cell::new(&file.display().to_string()),
+            Cell::new(&bytes_to_human_readable(meta.len() as f64)),
+            Cell::new(&modified.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+        ]));
+    }
+    table.printstd();
+    println!("total: {}", files.len());
+    Ok(())
+}
+
+pub fn dump(path: &str, limit: usize) -> Result<(), anyhow::Error> {
+    let mut printed = 0;
+    let ret = scan(Path::new(path), |offset, entry| {
+        if limit > 0 && printed >= limit {
+            return Ok(());
+        }
+        printed += 1;
+        let value = serde_json::json!({
+            "offset": offset,
+            "stream": entry.stream,
+            "schema_key": entry.schema_key,
+            "partition_key": entry.partition_key,
+            "data": entry.data,
+        });
+        println!("{}", serde_json::to_string(&value)?);
+        Ok(())
+    })?;
+    if let Some(e) = ret.error {
+        eprintln!("wal file is corrupted at offset {}: {e}", ret.valid_len);
+    }
+    Ok(())
+}
+
+pub fn verify(path: Option<&String>) -> Result<bool, anyhow::Error> {
+    let files = get_wal_files(path)?;
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("File"),
+        Cell::new("Entries"),
+        Cell::new("Records"),
+        Cell::new("Valid Size"),
+        Cell::new("File Size"),
+        Cell::new("Error"),
+    ]));
+    let mut corrupted = 0;
+    for file in files.iter() {
+        let ret = match scan(file, |_, _| Ok(())) {
+            Ok(v) => v,
+            Err(e) => WalScan {
+                error: Some(e.to_string()),
+                ..Default::default()
+            },
+        };
+        if ret.is_corrupted() {
+            corrupted += 1;
+        }
+        table.add_row(Row::new(vec![
+            Cell::new(&file.display().to_string()),
+            Cell::new(&ret.entries.to_string()),
+            Cell::new(&ret.records.to_string()),
+            Cell::new(&ret.valid_len.to_string()),
+            Cell::new(&ret.file_len.to_string()),
+            Cell::new(ret.error.as_deref().unwrap_or_default()),
+        ]));
+    }
+    table.printstd();
+    println!("total: {}, corrupted: {corrupted}", files.len());
+    Ok(corrupted == 0)
+}
+
+/// Cuts the corrupted tail of the wal file, the original file is kept as
+/// `{file}.bak`.
+pub fn truncate(path: &str, dry_run: bool) -> Result<(), anyhow::Error> {
+    let path = Path::new(path);
+    let ret = scan(path, |_, _| Ok(()))?;
+    let Some(error) = ret.error else {
+        println!("wal file is valid, entries: {}", ret.entries);
+        return Ok(());
+    };
+    println!(
+        "wal file is corrupted at offset {}: {error}, valid entries: {}, truncate {} bytes",
+        ret.valid_len,
+        ret.entries,
+        ret.file_len - ret.valid_len
+    );
+    if dry_run {
+        return Ok(());
+    }
+    let backup = path.with_extension("wal.bak");
+    std::fs::copy(path, &backup)?;
+    let f = std::fs::OpenOptions::new().write(true).open(path)?;
+    f.set_len(ret.valid_len)?;
+    f.sync_all()?;
+    println!(
+        "wal file truncated to {} bytes, backup: {}",
+        ret.valid_len,
+        backup.display()
+    );
+    Ok(())
+}
+
+/// Sends the records of the wal file to the local node through the http api.
+pub async fn reingest(path: &str, batch_size: usize) -> Result<(), anyhow::Error> {
+    let path = Path::new(path);
+    let Some((org_id, stream_type)) = get_org_and_stream_type(path) else {
+        return Err(anyhow::anyhow!("invalid wal file path: {}", path.display()));
+    };
+    if !matches!(stream_type, StreamType::Logs | StreamType::Metrics) {
+        return Err(anyhow::anyhow!(
+            "re-ingest only supports logs and metrics, got {stream_type}"
+        ));
+    }
+    let mut batches: Vec<(String, Vec<serde_json::Value>)> = Vec::new();
+    let ret = scan(path, |_, entry| {
+        let records = entry.data.iter().map(|v| v.as_ref().clone());
+        push_to_batches(&mut batches, &entry.stream, records, batch_size);
+        Ok(())
+    })?;
+    if let Some(e) = ret.error.as_ref() {
+        println!(
+            "wal file is corrupted at offset {}: {e}, only re-ingest {} valid entries",
+            ret.valid_len, ret.entries
+        );
+    }
+    for (stream_name, data) in batches {
+        let num = data.len();
+        super::http::ingest_json(&org_id, stream_type, &stream_name, data).await?;
+        println!("re-ingest {num} records into {org_id}/{stream_type}/{stream_name}");
+    }
+    println!("total: {} entries, {} records", ret.entries, ret.records);
+    Ok(())
+}
+
+// appends the records to the last batch of the same stream, a batch never
+// holds more than `batch_size` records
+fn push_to_batches(
+    batches: &mut Vec<(String, Vec<serde_json::Value>)>,
+    stream_name: &str,
+    records: impl Iterator<Item = serde_json::Value>,
+    batch_size: usize,
+) {
+    let batch_size = batch_size.max(1);
+    for record in records {
+        match batches.last_mut() {
+            Some((stream, data)) if stream.as_str() == stream_name && data.len() < batch_size => {
+                data.push(record)
+            }
+            _ => batches.push((stream_name.to_string(), vec![record])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, sync::Arc};
+
+    use super::*;
+
+    fn write_wal(path: &Path, n: usize) {
+        let (mut writer, _) = wal::Writer::new(path.to_path_buf(), 0, 1024, None).unwrap();
+        for i in 0..n {
+            let mut entry = Entry {
+                stream: "default".into(),
+                schema: None,
+                schema_key: "key".into(),
+                partition_key: "2025/01/01/00".into(),
+                data: vec![Arc::new(serde_json::json!({"id": i}))],
+                data_size: 10,
+            };
+            writer.write(&entry.into_bytes().unwrap()).unwrap();
+        }
+        writer.close().unwrap();
+    }
+
+    #[test]
+    fn test_scan_and_truncate() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("0/default/logs/1.wal");
+        write_wal(&path, 3);
+
+        let mut offsets = Vec::new();
+        let ret = scan(&path, |offset, _| {
+            offsets.push(offset);
+            Ok(())
+        })
+        .unwrap();
+        assert!(!ret.is_corrupted());
+        assert_eq!(ret.entries, 3);
+        assert_eq!(ret.records, 3);
+        assert_eq!(ret.valid_len, ret.file_len);
+        assert_eq!(offsets.len(), 3);
+
+        // append a half-written entry
+        let mut f = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        f.write_all(&[0, 0, 0, 1, 0, 0, 0, 100, 1, 2]).unwrap();
+        drop(f);
+        let corrupted = scan(&path, |_, _| Ok(())).unwrap();
+        assert!(corrupted.is_corrupted());
+        assert_eq!(corrupted.entries, 3);
+        assert_eq!(corrupted.valid_len, ret.file_len);
+
+        truncate(path.to_str().unwrap(), false).unwrap();
+        let ret = scan(&path, |_, _| Ok(())).unwrap();
+        assert!(!ret.is_corrupted());
+        assert_eq!(ret.entries, 3);
+        assert!(path.with_extension("wal.bak").exists());
+    }
+
+    #[test]
+    fn test_get_org_and_stream_type() {
+        assert_eq!(
+            get_org_and_stream_type(Path::new("/data/wal/logs/0/default/logs/1.wal")),
+            Some(("default".to_string(), StreamType::Logs))
+        );
+        assert_eq!(get_org_and_stream_type(Path::new("1.wal")), None);
+    }
+
+    #[test]
+    fn test_push_to_batches() {
+        let mut batches = Vec::new();
+        let records = |n: usize| (0..n).map(|i| serde_json::json!({"id": i}));
+        push_to_batches(&mut batches, "a", records(3), 2);
+        push_to_batches(&mut batches, "a", records(2), 2);
+        push_to_batches(&mut batches, "b", records(1), 2);
+        let sizes = batches
+            .iter()
+            .map(|(s, d)| (s.as_str(), d.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, vec![("a", 2), ("a", 2), ("a", 1), ("b", 1)]);
+    }
+}
@@ -105,6 +105,9 @@ async fn register() -> Result<()> {
         if node.is_flatten_compactor() {
             super::add_node_to_consistent_hash(&node, &Role::FlattenCompactor, None).await;
         }
+        if node.is_ingester() {
+            super::add_node_to_consistent_hash(&node, &Role::Ingester, None).await;
+        }
         node_ids.push(node.id);
         w.insert(node.uuid.clone(), node);
     }
@@ -172,6 +175,9 @@ async fn register() -> Result<()> {
     if node.is_flatten_compactor() {
         super::add_node_to_consistent_hash(&node, &Role::FlattenCompactor, None).await;
     }
+    if node.is_ingester() {
+        super::add_node_to_consistent_hash(&node, &Role::Ingester, None).await;
+    }
 
     let mut w = super::NODES.write().await;
     w.insert(LOCAL_NODE.uuid.clone(), node.clone());
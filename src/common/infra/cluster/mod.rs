@@ -56,6 +56,7 @@ static QUERIER_BACKGROUND_CONSISTENT_HASH: Lazy<RwBTreeMap<u64, String>> =
 static COMPACTOR_CONSISTENT_HASH: Lazy<RwBTreeMap<u64, String>> = Lazy::new(Default::default);
 static FLATTEN_COMPACTOR_CONSISTENT_HASH: Lazy<RwBTreeMap<u64, String>> =
     Lazy::new(Default::default);
+static INGESTER_CONSISTENT_HASH: Lazy<RwBTreeMap<u64, String>> = Lazy::new(Default::default);
 static NODES_HEALTH_CHECK: Lazy<RwAHashMap<String, usize>> = Lazy::new(Default::default);
 
 pub async fn add_node_to_consistent_hash(node: &Node, role: &Role, group: Option<RoleGroup>) {
@@ -67,6 +68,7 @@ pub async fn add_node_to_consistent_hash(node: &Node, role: &Role, group: Option
         },
         Role::Compactor => COMPACTOR_CONSISTENT_HASH.write().await,
         Role::FlattenCompactor => FLATTEN_COMPACTOR_CONSISTENT_HASH.write().await,
+        Role::Ingester => INGESTER_CONSISTENT_HASH.write().await,
         _ => return,
     };
     let mut h = config::utils::hash::gxhash::new();
@@ -86,6 +88,7 @@ pub async fn remove_node_from_consistent_hash(node: &Node, role: &Role, group: O
         },
         Role::Compactor => COMPACTOR_CONSISTENT_HASH.write().await,
         Role::FlattenCompactor => FLATTEN_COMPACTOR_CONSISTENT_HASH.write().await,
+        Role::Ingester => INGESTER_CONSISTENT_HASH.write().await,
         _ => return,
     };
     let mut h = config::utils::hash::gxhash::new();
@@ -109,6 +112,7 @@ pub async fn get_node_from_consistent_hash(
         },
         Role::Compactor => COMPACTOR_CONSISTENT_HASH.read().await,
         Role::FlattenCompactor => FLATTEN_COMPACTOR_CONSISTENT_HASH.read().await,
+        Role::Ingester => INGESTER_CONSISTENT_HASH.read().await,
         _ => return None,
     };
     if nodes.is_empty() {
@@ -125,6 +129,34 @@ pub async fn get_node_from_consistent_hash(
     None
 }
 
+/// Returns up to `num` distinct nodes walking the hash ring clockwise from the
+/// key, the first one is the same as [`get_node_from_consistent_hash`].
+pub async fn get_nodes_from_consistent_hash(key: &str, role: &Role, num: usize) -> Vec<String> {
+    let nodes = match role {
+        Role::Compactor => COMPACTOR_CONSISTENT_HASH.read().await,
+        Role::FlattenCompactor => FLATTEN_COMPACTOR_CONSISTENT_HASH.read().await,
+        Role::Ingester => INGESTER_CONSISTENT_HASH.read().await,
+        _ => return vec![],
+    };
+    let mut names: Vec<String> = Vec::with_capacity(num);
+    if num == 0 || nodes.is_empty() {
+        return names;
+    }
+    let hash = config::utils::hash::gxhash::new().sum64(key);
+    let iter = nodes
+        .range((Bound::Included(&hash), Bound::Unbounded))
+        .chain(nodes.range((Bound::Unbounded, Bound::Excluded(&hash))));
+    for (_, name) in iter {
+        if !names.contains(name) {
+            names.push(name.clone());
+            if names.len() == num {
+                break;
+            }
+        }
+    }
+    names
+}
+
 pub async fn print_consistent_hash() -> HashMap<String, HashMap<String, Vec<u64>>> {
     let mut map = HashMap::new();
     let r = QUERIER_INTERACTIVE_CONSISTENT_HASH.read().await;
@@ -159,6 +191,14 @@ pub async fn print_consistent_hash() -> HashMap<String, HashMap<String, Vec<u64>
     }
     drop(r);
     map.insert("flatten_compactor".to_string(), node_map);
+    let r = INGESTER_CONSISTENT_HASH.read().await;
+    let mut node_map = HashMap::new();
+    for (k, v) in r.iter() {
+        let entry = node_map.entry(v.clone()).or_insert(Vec::new());
+        entry.push(*k);
+    }
+    drop(r);
+    map.insert("ingester".to_string(), node_map);
     map
 }
 
@@ -175,6 +215,9 @@ pub async fn count_consistent_hash() -> HashMap<String, usize> {
     drop(r);
     let r = FLATTEN_COMPACTOR_CONSISTENT_HASH.read().await;
     map.insert("flatten_compactor".to_string(), r.len());
+    drop(r);
+    let r = INGESTER_CONSISTENT_HASH.read().await;
+    map.insert("ingester".to_string(), r.len());
     map
 }
 
@@ -193,6 +236,7 @@ pub async fn register_and_keep_alive() -> Result<()> {
         add_node_to_consistent_hash(&node, &Role::Querier, Some(RoleGroup::Background)).await;
         add_node_to_consistent_hash(&node, &Role::Compactor, None).await;
         add_node_to_consistent_hash(&node, &Role::FlattenCompactor, None).await;
+        add_node_to_consistent_hash(&node, &Role::Ingester, None).await;
         NODES.write().await.insert(LOCAL_NODE.uuid.clone(), node);
         return Ok(());
     }
@@ -368,6 +412,9 @@ async fn watch_node_list() -> Result<()> {
                         )
                         .await;
                     }
+                    if item_value.is_ingester() {
+                        remove_node_from_consistent_hash(&item_value, &Role::Ingester, None).await;
+                    }
                     NODES.write().await.remove(item_key);
                     continue;
                 }
@@ -401,6 +448,9 @@ async fn watch_node_list() -> Result<()> {
                 if item_value.is_flatten_compactor() {
                     add_node_to_consistent_hash(&item_value, &Role::FlattenCompactor, None).await;
                 }
+                if item_value.is_ingester() {
+                    add_node_to_consistent_hash(&item_value, &Role::Ingester, None).await;
+                }
                 NODES.write().await.insert(item_key.to_string(), item_value);
             }
             Event::Delete(ev) => {
@@ -438,6 +488,9 @@ async fn watch_node_list() -> Result<()> {
                     remove_node_from_consistent_hash(&item_value, &Role::FlattenCompactor, None)
                         .await;
                 }
+                if item_value.is_ingester() {
+                    remove_node_from_consistent_hash(&item_value, &Role::Ingester, None).await;
+                }
                 NODES.write().await.remove(item_key);
             }
             Event::Empty => {}
@@ -508,6 +561,9 @@ async fn check_nodes_status(client: &reqwest::Client) -> Result<()> {
                 if node.is_flatten_compactor() {
                     remove_node_from_consistent_hash(&node, &Role::FlattenCompactor, None).await;
                 }
+                if node.is_ingester() {
+                    remove_node_from_consistent_hash(&node, &Role::Ingester, None).await;
+                }
                 NODES.write().await.remove(&node.uuid);
                 NODES_HEALTH_CHECK.write().await.remove(&node.uuid);
             }
@@ -761,6 +817,7 @@ mod tests {
         QUERIER_BACKGROUND_CONSISTENT_HASH.write().await.clear();
         COMPACTOR_CONSISTENT_HASH.write().await.clear();
         FLATTEN_COMPACTOR_CONSISTENT_HASH.write().await.clear();
+        INGESTER_CONSISTENT_HASH.write().await.clear();
 
         // Test consistent hash logic.
         let node = load_local_node();
@@ -781,6 +838,12 @@ mod tests {
             add_node_to_consistent_hash(&node_q, &Role::Querier, None).await;
             add_node_to_consistent_hash(&node_c, &Role::Compactor, None).await;
             add_node_to_consistent_hash(&node_c, &Role::FlattenCompactor, None).await;
+            let node_i = Node {
+                name: format!("node-i-{i}").to_string(),
+                role: [Role::Ingester].to_vec(),
+                ..node.clone()
+            };
+            add_node_to_consistent_hash(&node_i, &Role::Ingester, None).await;
         }
 
         for key in ["test", "test1", "test2", "test3", "test4", "test5", "test6"] {
@@ -813,6 +876,7 @@ mod tests {
         remove_node_from_consistent_hash(&node, &Role::Querier, Some(RoleGroup::Background)).await;
         remove_node_from_consistent_hash(&node, &Role::Compactor, None).await;
         remove_node_from_consistent_hash(&node, &Role::FlattenCompactor, None).await;
+        remove_node_from_consistent_hash(&node, &Role::Ingester, None).await;
         for key in data {
             assert_eq!(
                 get_node_from_consistent_hash(key.first().unwrap(), &Role::Querier, None).await,
@@ -824,11 +888,28 @@ mod tests {
             );
         }
 
+        for key in ["test", "test1", "test2"] {
+            let nodes = get_nodes_from_consistent_hash(key, &Role::Ingester, 3).await;
+            assert_eq!(nodes.len(), 3);
+            assert_eq!(
+                nodes.first().cloned(),
+                get_node_from_consistent_hash(key, &Role::Ingester, None).await
+            );
+            assert!(nodes.iter().all(|n| nodes.iter().filter(|v| *v == n).count() == 1));
+        }
+        assert_eq!(
+            get_nodes_from_consistent_hash("test", &Role::Ingester, 20)
+                .await
+                .len(),
+            10
+        );
+
         let ret = print_consistent_hash().await;
-        assert_eq!(ret.len(), 4);
+        assert_eq!(ret.len(), 5);
         assert_eq!(ret["querier_interactive"].len(), 10);
         assert_eq!(ret["querier_background"].len(), 10);
         assert_eq!(ret["compactor"].len(), 10);
         assert_eq!(ret["flatten_compactor"].len(), 10);
+        assert_eq!(ret["ingester"].len(), 10);
     }
 }
@@ -19,11 +19,15 @@ use config::{
     RwAHashMap, RwHashMap,
     meta::{
         alerts::alert::Alert,
+        api_token::ApiToken,
         destinations::{Destination, Template},
         folder::Folder,
         function::Transform,
+        grant::{Grant, GrantResource},
         promql::ClusterLeader,
+        quota::OrgQuota,
         ratelimit::CachedUserRoles,
+        session::SessionInfo,
         stream::StreamParams,
         user::User,
     },
@@ -76,6 +80,15 @@ pub static REALTIME_ALERT_TRIGGERS: Lazy<RwAHashMap<String, db_scheduler::Trigge
 pub static ALERTS_TEMPLATES: Lazy<RwHashMap<String, Template>> = Lazy::new(Default::default);
 pub static DESTINATIONS: Lazy<RwHashMap<String, Destination>> = Lazy::new(Default::default);
 pub static SYSLOG_ROUTES: Lazy<RwHashMap<String, SyslogRoute>> = Lazy::new(Default::default);
+// Key for grants cache is org/grant_id
+pub static GRANTS: Lazy<RwHashMap<String, Grant>> = Lazy::new(Default::default);
+// Key for grant restrictions cache is org/subject
+pub static GRANT_RESTRICTIONS: Lazy<RwHashMap<String, Vec<GrantResource>>> =
+    Lazy::new(Default::default);
+// Key for api tokens cache is org/token_id
+pub static API_TOKENS: Lazy<RwHashMap<String, ApiToken>> = Lazy::new(Default::default);
+// Key for org quotas cache is org
+pub static ORG_QUOTAS: Lazy<RwHashMap<String, OrgQuota>> = Lazy::new(Default::default);
 pub static SYSLOG_ENABLED: Lazy<Arc<RwLock<bool>>> = Lazy::new(|| Arc::new(RwLock::new(false)));
 pub static ENRICHMENT_TABLES: Lazy<RwHashMap<String, StreamTable>> = Lazy::new(Default::default);
 pub static ENRICHMENT_REGISTRY: Lazy<Arc<TableRegistry>> =
@@ -99,6 +112,7 @@ pub static STREAM_EXECUTABLE_PIPELINES: Lazy<RwAHashMap<StreamParams, Executable
 pub static PIPELINE_STREAM_MAPPING: Lazy<RwAHashMap<String, StreamParams>> =
     Lazy::new(Default::default);
 pub static USER_SESSIONS: Lazy<RwHashMap<String, String>> = Lazy::new(Default::default);
+pub static USER_SESSION_INFOS: Lazy<RwHashMap<String, SessionInfo>> = Lazy::new(Default::default);
 pub static SHORT_URLS: Lazy<RwHashMap<String, ShortUrlRecord>> = Lazy::new(DashMap::default);
 // TODO: Implement rate limiting for maximum number of sessions
 // Querier Connection Pool
@@ -102,6 +102,12 @@ pub enum LokiError {
         source: std::io::Error,
     },
 
+    #[error("Forbidden to ingest into stream: {stream_name}")]
+    Forbidden { stream_name: String },
+
+    #[error("Ingestion throttled: {message}")]
+    Throttled { message: String, retry_after: u64 },
+
     #[error("Ingestion failed: {source}")]
     Ingestion {
         #[from]
@@ -144,6 +150,18 @@ impl From<LokiError> for actix_web::HttpResponse {
                 .content_type("text/plain")
                 .body(format!("failed to decompress gzip: {source}")),
 
+            LokiError::Forbidden { stream_name } => HttpResponse::Forbidden()
+                .content_type("text/plain")
+                .body(format!("forbidden to ingest into stream: {stream_name}")),
+
+            LokiError::Throttled {
+                message,
+                retry_after,
+            } => HttpResponse::TooManyRequests()
+                .insert_header(("Retry-After", retry_after.to_string()))
+                .content_type("text/plain")
+                .body(message),
+
             // Server errors (500)
             LokiError::Ingestion { .. } => HttpResponse::InternalServerError()
                 .content_type("text/plain")
@@ -227,6 +227,12 @@ pub struct OrganizationSettingPayload {
     pub enable_streaming_search: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub min_auto_refresh_interval: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub red_metrics_dimensions: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_idle_timeout: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_sessions: Option<u32>,
 }
 
 #[derive(Serialize, ToSchema, Deserialize, Debug, Clone)]
@@ -247,6 +253,18 @@ pub struct OrganizationSetting {
     pub enable_streaming_search: bool,
     #[serde(default = "default_auto_refresh_interval")]
     pub min_auto_refresh_interval: u32,
+    /// Span attributes which are added as labels to the span metrics, in
+    /// addition to service name, span name, span kind and status code.
+    #[serde(default)]
+    pub red_metrics_dimensions: Vec<String>,
+    /// Login sessions idle for longer than this many seconds are logged out,
+    /// 0 disables the timeout.
+    #[serde(default)]
+    pub session_idle_timeout: u64,
+    /// Maximum number of login sessions of a user, the oldest ones are logged
+    /// out first. 0 means no limit.
+    #[serde(default)]
+    pub max_concurrent_sessions: u32,
 }
 
 impl Default for OrganizationSetting {
@@ -259,6 +277,9 @@ impl Default for OrganizationSetting {
             aggregation_cache_enabled: default_enable_aggregation_cache(),
             enable_streaming_search: default_enable_streaming_search(),
             min_auto_refresh_interval: default_auto_refresh_interval(),
+            red_metrics_dimensions: vec![],
+            session_idle_timeout: 0,
+            max_concurrent_sessions: 0,
         }
     }
 }
@@ -33,6 +33,9 @@ pub struct Stream {
     pub storage_type: String,
     pub stream_type: StreamType,
     pub stats: StreamStats,
+    /// Ratio of original size to compressed size, reflects the parquet settings
+    #[serde(default)]
+    pub compression_ratio: f64,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub schema: Vec<StreamProperty>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -145,6 +148,7 @@ mod tests {
             storage_type: "local".to_string(),
             stream_type: StreamType::Logs,
             stats: StreamStats::default(),
+            compression_ratio: 0.0,
             schema: vec![StreamProperty {
                 name: "field1".to_string(),
                 prop_type: "string".to_string(),
@@ -224,6 +228,7 @@ mod tests {
             storage_type: "local".to_string(),
             stream_type: StreamType::Logs,
             stats: StreamStats::default(),
+            compression_ratio: 0.0,
             schema: vec![],
             uds_schema: Some(vec![StreamProperty {
                 name: "uds_field".to_string(),
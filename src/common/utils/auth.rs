@@ -26,10 +26,7 @@ use once_cell::sync::Lazy;
 use regex::Regex;
 #[cfg(feature = "enterprise")]
 use {
-    crate::{
-        common::{infra::config::USER_SESSIONS, meta::ingestion::INGESTION_EP},
-        service::users::get_user,
-    },
+    crate::{common::meta::ingestion::INGESTION_EP, service::users::get_user},
     jsonwebtoken::TokenData,
     o2_dex::service::auth::get_dex_jwks,
     o2_openfga::config::get_config as get_openfga_config,
@@ -38,13 +35,16 @@ use {
     std::{collections::HashMap, str::FromStr},
 };
 
-use crate::common::{
-    infra::config::{ORG_USERS, PASSWORD_HASH},
-    meta::{
-        authz::Authz,
-        organization::DEFAULT_ORG,
-        user::{AuthTokens, UserOrgRole},
+use crate::{
+    common::{
+        infra::config::{ORG_USERS, PASSWORD_HASH, USER_SESSIONS},
+        meta::{
+            authz::Authz,
+            organization::DEFAULT_ORG,
+            user::{AuthTokens, UserOrgRole},
+        },
     },
+    service::session::is_native_session,
 };
 
 pub const V2_API_PREFIX: &str = "v2";
@@ -676,6 +676,10 @@ impl FromRequest for AuthExtractor {
                 || path.contains("/ws")
                 || path.contains("/_values_stream")
                 || (url_len > 1 && path_columns[1].eq("ai"))
+                || (url_len > 1 && path_columns[1].eq("api_tokens"))
+                || (url_len > 1 && path_columns[1].eq("quotas"))
+                || (url_len > 1 && path_columns[1].eq("encryption"))
+                || (url_len > 3 && path_columns[1].eq("users") && path_columns[3].eq("sessions"))
             {
                 return ready(Ok(AuthExtractor {
                     auth: auth_str.to_owned(),
@@ -793,6 +797,12 @@ impl FromRequest for AuthExtractor {
             let access_token = auth_tokens.access_token;
             if access_token.starts_with("Basic") || access_token.starts_with("Bearer") {
                 access_token
+            } else if let Some(session_key) = access_token.strip_prefix("session ") {
+                // the sessions of the native logins are resolved by the validator
+                match USER_SESSIONS.get(session_key) {
+                    Some(token) if !is_native_session(&token) => format!("Bearer {}", *token),
+                    _ => access_token,
+                }
             } else {
                 format!("Bearer {access_token}")
             }
@@ -849,10 +859,8 @@ pub fn extract_auth_str(req: &HttpRequest) -> String {
         } else if access_token.starts_with("session") {
             let session_key = access_token.strip_prefix("session ").unwrap().to_string();
             match USER_SESSIONS.get(&session_key) {
-                Some(token) => {
-                    format!("Bearer {}", *token)
-                }
-                None => access_token,
+                Some(token) if !is_native_session(&token) => format!("Bearer {}", *token),
+                _ => access_token,
             }
         } else {
             format!("Bearer {access_token}")
@@ -991,6 +999,10 @@ pub async fn extract_auth_expiry_and_user_id(
     } else if auth_str.starts_with("session ") {
         let session_key = auth_str.strip_prefix("session ").unwrap();
         let stripped_bearer_token = match crate::service::db::session::get(session_key).await {
+            Ok(token) if is_native_session(&token) => {
+                let user_id = crate::service::session::get_native_user(session_key).await;
+                return (None, user_id);
+            }
             Ok(bearer_token) => bearer_token,
             Err(e) => {
                 log::error!("Error getting session: {}", e);
@@ -2967,6 +2967,7 @@ mod tests {
                 cookie_secure_only: bool::default(),
                 ext_auth_salt: String::default(),
                 action_server_token: String::default(),
+                cross_org_search_users: String::default(),
             },
             report_server: config::ReportServer {
                 enable_report_server: bool::default(),
@@ -3061,6 +3062,8 @@ mod tests {
                 feature_query_skip_wal: bool::default(),
                 wal_write_queue_enabled: bool::default(),
                 wal_write_queue_full_reject: bool::default(),
+                wal_replication_enabled: bool::default(),
+                wal_replication_mode: String::default(),
                 ui_enabled: bool::default(),
                 ui_sql_base64_enabled: bool::default(),
                 metrics_dedup_enabled: bool::default(),
@@ -3092,6 +3095,14 @@ mod tests {
                 usage_reporting_creds: String::default(),
                 usage_batch_size: usize::default(),
                 usage_publish_interval: i64::default(),
+                audit_enabled: bool::default(),
+                audit_methods: String::default(),
+                audit_include_paths: String::default(),
+                audit_exclude_paths: String::default(),
+                audit_body_max_size: usize::default(),
+                audit_batch_size: usize::default(),
+                audit_publish_interval: u64::default(),
+                audit_queue_size: usize::default(),
                 mmdb_data_dir: String::default(),
                 mmdb_disable_download: bool::default(),
                 mmdb_update_duration_days: u64::default(),
@@ -3127,6 +3138,15 @@ mod tests {
                 traces_span_metrics_enabled: bool::default(),
                 traces_span_metrics_export_interval: u64::default(),
                 traces_span_metrics_channel_buffer: usize::default(),
+                traces_service_graph_enabled: bool::default(),
+                traces_service_graph_flush_interval: u64::default(),
+                traces_service_graph_wait_time: u64::default(),
+                traces_service_graph_max_pending_spans: usize::default(),
+                traces_red_metrics_enabled: bool::default(),
+                traces_red_metrics_flush_interval: u64::default(),
+                traces_red_metrics_max_series: usize::default(),
+                traces_tail_sampling_max_spans: usize::default(),
+                traces_tail_sampling_decision_ttl: u64::default(),
                 self_metrics_consumption_enabled: bool::default(),
                 self_metrics_consumption_interval: u64::default(),
                 self_metrics_consumption_whitelist: String::default(),
@@ -3175,6 +3195,10 @@ mod tests {
                 mem_persist_interval: u64::default(),
                 wal_write_buffer_size: usize::default(),
                 wal_write_queue_size: usize::default(),
+                wal_replication_factor: usize::default(),
+                wal_replication_queue_size: usize::default(),
+                wal_replication_retention: u64::default(),
+                wal_replication_check_interval: u64::default(),
                 file_push_interval: u64::default(),
                 file_push_limit: usize::default(),
                 file_move_fields_limit: usize::default(),
@@ -3194,6 +3218,10 @@ mod tests {
                 max_dashboard_series: usize::default(),
                 ingest_allowed_upto: i64::default(),
                 ingest_allowed_in_future: i64::default(),
+                ingest_org_rate_limit: usize::default(),
+                ingest_org_rate_burst: usize::default(),
+                ingest_retry_after_min: u64::default(),
+                ingest_retry_after_max: u64::default(),
                 ingest_flatten_level: u32::default(),
                 ignore_file_retention_by_stream: bool::default(),
                 logs_file_retention: String::default(),
@@ -3216,6 +3244,8 @@ mod tests {
                 job_runtime_shutdown_timeout: u64::default(),
                 calculate_stats_interval: u64::default(),
                 enrichment_table_max_size: usize::default(),
+                enrichment_table_index_max_size: usize::default(),
+                enrichment_table_source_allowed_hosts: String::default(),
                 http_request_timeout: u64::default(),
                 http_keep_alive: u64::default(),
                 http_slow_log_threshold: u64::default(),
@@ -3290,6 +3320,11 @@ mod tests {
                 job_clean_wait_time: i64::default(),
                 pending_jobs_metric_interval: u64::default(),
                 max_group_files: usize::default(),
+                verify_enabled: bool::default(),
+                verify_interval: u64::default(),
+                verify_lookback_hours: i64::default(),
+                verify_sample_partitions: usize::default(),
+                verify_auto_repair: bool::default(),
             },
             cache_latest_files: config::CacheLatestFiles {
                 enabled: bool::default(),
@@ -3471,6 +3506,27 @@ mod tests {
             encryption: config::Encryption {
                 algorithm: String::default(),
                 master_key: String::default(),
+                master_key_file: String::default(),
+                at_rest_enabled: bool::default(),
+                kms_url: String::default(),
+                kms_token: String::default(),
+            },
+            oidc: config::Oidc {
+                enabled: bool::default(),
+                issuer_url: String::default(),
+                client_id: String::default(),
+                client_secret: String::default(),
+                redirect_url: String::default(),
+                ui_redirect_url: String::default(),
+                scopes: String::default(),
+                email_claim: String::default(),
+                groups_claim: String::default(),
+                group_role_mapping: String::default(),
+                default_org: String::default(),
+                default_role: String::default(),
+                session_secret: String::default(),
+                link_native_users: bool::default(),
+                scim_enabled: bool::default(),
             },
         }
     }
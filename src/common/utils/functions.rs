@@ -140,11 +140,7 @@ mod tests {
         let test_org = "test_org";
         let test_stream = "test_stream";
         let table_name = "test_table";
-        let en_table = crate::service::enrichment::StreamTable {
-            org_id: test_org.to_string(),
-            stream_name: test_stream.to_string(),
-            data: vec![],
-        };
+        let en_table = crate::service::enrichment::StreamTable::new(test_org, test_stream, vec![]);
         ENRICHMENT_TABLES.insert(table_name.to_string(), en_table);
 
         // Test the function
@@ -424,6 +424,7 @@ pub struct Config {
     pub pipeline: Pipeline,
     pub health_check: HealthCheck,
     pub encryption: Encryption,
+    pub oidc: Oidc,
 }
 
 #[derive(EnvConfig)]
@@ -598,6 +599,12 @@ pub struct Auth {
     pub ext_auth_salt: String,
     #[env_config(name = "O2_ACTION_SERVER_TOKEN")]
     pub action_server_token: String,
+    #[env_config(
+        name = "ZO_CROSS_ORG_SEARCH_USERS",
+        default = "",
+        help = "Comma separated emails of the users who can search across organizations besides root"
+    )]
+    pub cross_org_search_users: String,
 }
 
 #[derive(EnvConfig)]
@@ -826,6 +833,18 @@ pub struct Common {
         help = "Reject write when write queue is full"
     )]
     pub wal_write_queue_full_reject: bool,
+    #[env_config(
+        name = "ZO_WAL_REPLICATION_ENABLED",
+        default = false,
+        help = "Replicate WAL entries to peer ingesters, a peer replays the WAL of a dead ingester"
+    )]
+    pub wal_replication_enabled: bool,
+    #[env_config(
+        name = "ZO_WAL_REPLICATION_MODE",
+        default = "async",
+        help = "WAL replication mode: sync, the write waits for all peers; async, the write returns after local WAL"
+    )]
+    pub wal_replication_mode: String,
     #[env_config(name = "ZO_TRACING_ENABLED", default = false)]
     pub tracing_enabled: bool,
     #[env_config(name = "ZO_TRACING_SEARCH_ENABLED", default = false)]
@@ -891,6 +910,54 @@ pub struct Common {
     )]
     // in seconds
     pub usage_publish_interval: i64,
+    #[env_config(
+        name = "ZO_AUDIT_ENABLED",
+        default = false,
+        help = "Record the API mutations into the audit stream of the _meta org"
+    )]
+    pub audit_enabled: bool,
+    #[env_config(
+        name = "ZO_AUDIT_METHODS",
+        default = "POST,PUT,PATCH,DELETE",
+        help = "Comma separated HTTP methods which are audited"
+    )]
+    pub audit_methods: String,
+    #[env_config(
+        name = "ZO_AUDIT_INCLUDE_PATHS",
+        default = "",
+        help = "Comma separated path prefixes after /api/ which are audited, `*` matches one path segment, e.g. `*/streams`. Empty audits all the paths"
+    )]
+    pub audit_include_paths: String,
+    #[env_config(
+        name = "ZO_AUDIT_EXCLUDE_PATHS",
+        default = "",
+        help = "Comma separated path prefixes after /api/ which are never audited, same format as ZO_AUDIT_INCLUDE_PATHS"
+    )]
+    pub audit_exclude_paths: String,
+    #[env_config(
+        name = "ZO_AUDIT_BODY_MAX_SIZE",
+        default = 1024,
+        help = "Maximum size in bytes of the request body summary kept in an audit record"
+    )]
+    pub audit_body_max_size: usize,
+    #[env_config(
+        name = "ZO_AUDIT_BATCH_SIZE",
+        default = 500,
+        help = "Number of audit records ingested into the audit stream at once"
+    )]
+    pub audit_batch_size: usize,
+    #[env_config(
+        name = "ZO_AUDIT_PUBLISH_INTERVAL",
+        default = 10,
+        help = "Seconds after which the pending audit records are ingested even if the batch isn't full"
+    )]
+    pub audit_publish_interval: u64,
+    #[env_config(
+        name = "ZO_AUDIT_QUEUE_SIZE",
+        default = 10000,
+        help = "Number of audit records which can wait to be ingested, the audited requests wait when the queue is full"
+    )]
+    pub audit_queue_size: usize,
     #[env_config(name = "ZO_MMDB_DATA_DIR")] // ./data/openobserve/mmdb/
     pub mmdb_data_dir: String,
     #[env_config(name = "ZO_MMDB_DISABLE_DOWNLOAD", default = false)]
@@ -1049,6 +1116,60 @@ pub struct Common {
         help = "traces span metrics channel send buffer"
     )]
     pub traces_span_metrics_channel_buffer: usize,
+    #[env_config(
+        name = "ZO_TRACES_SERVICE_GRAPH_ENABLED",
+        default = false,
+        help = "enable the service graph which is derived from the client and server spans"
+    )]
+    pub traces_service_graph_enabled: bool,
+    #[env_config(
+        name = "ZO_TRACES_SERVICE_GRAPH_FLUSH_INTERVAL",
+        default = 60,
+        help = "traces service graph edges flush interval, unit seconds"
+    )]
+    pub traces_service_graph_flush_interval: u64,
+    #[env_config(
+        name = "ZO_TRACES_SERVICE_GRAPH_WAIT_TIME",
+        default = 30,
+        help = "how long a span waits for the other side of the call, unit seconds"
+    )]
+    pub traces_service_graph_wait_time: u64,
+    #[env_config(
+        name = "ZO_TRACES_SERVICE_GRAPH_MAX_PENDING_SPANS",
+        default = 100000,
+        help = "max number of spans which are waiting for the other side of the call"
+    )]
+    pub traces_service_graph_max_pending_spans: usize,
+    #[env_config(
+        name = "ZO_TRACES_RED_METRICS_ENABLED",
+        default = false,
+        help = "generate calls_total and duration metrics from the ingested spans of each organization"
+    )]
+    pub traces_red_metrics_enabled: bool,
+    #[env_config(
+        name = "ZO_TRACES_RED_METRICS_FLUSH_INTERVAL",
+        default = 60,
+        help = "interval in seconds to write the span metrics into the metrics streams"
+    )]
+    pub traces_red_metrics_flush_interval: u64,
+    #[env_config(
+        name = "ZO_TRACES_RED_METRICS_MAX_SERIES",
+        default = 10000,
+        help = "max number of span metrics series of an organization, new series are dropped after reaching the limit"
+    )]
+    pub traces_red_metrics_max_series: usize,
+    #[env_config(
+        name = "ZO_TRACES_TAIL_SAMPLING_MAX_SPANS",
+        default = 1000000,
+        help = "max number of spans buffered by tail sampling, the pending traces are decided early after reaching the limit"
+    )]
+    pub traces_tail_sampling_max_spans: usize,
+    #[env_config(
+        name = "ZO_TRACES_TAIL_SAMPLING_DECISION_TTL",
+        default = 300,
+        help = "seconds to remember the tail sampling decision of a trace, late spans of the trace follow the decision"
+    )]
+    pub traces_tail_sampling_decision_ttl: u64,
     #[env_config(
         name = "ZO_SELF_METRIC_CONSUMPTION_ENABLED",
         default = false,
@@ -1206,6 +1327,26 @@ pub struct Limit {
     pub wal_write_buffer_size: usize,
     #[env_config(name = "ZO_WAL_WRITE_QUEUE_SIZE", default = 10000)] // 10k messages
     pub wal_write_queue_size: usize,
+    #[env_config(
+        name = "ZO_WAL_REPLICATION_FACTOR",
+        default = 1,
+        help = "Number of peer ingesters which receive a copy of the WAL"
+    )]
+    pub wal_replication_factor: usize,
+    #[env_config(name = "ZO_WAL_REPLICATION_QUEUE_SIZE", default = 10000)] // 10k messages
+    pub wal_replication_queue_size: usize,
+    #[env_config(
+        name = "ZO_WAL_REPLICATION_RETENTION",
+        default = 3600,
+        help = "Seconds to keep a WAL replica which was not released by its alive source ingester"
+    )]
+    pub wal_replication_retention: u64,
+    #[env_config(
+        name = "ZO_WAL_REPLICATION_CHECK_INTERVAL",
+        default = 30,
+        help = "Seconds between checks for WAL replicas of dead ingesters"
+    )]
+    pub wal_replication_check_interval: u64,
     #[env_config(name = "ZO_FILE_PUSH_INTERVAL", default = 10)] // seconds
     pub file_push_interval: u64,
     #[env_config(name = "ZO_FILE_PUSH_LIMIT", default = 0)] // files
@@ -1246,6 +1387,22 @@ pub struct Limit {
     pub ingest_allowed_upto: i64,
     #[env_config(name = "ZO_INGEST_ALLOWED_IN_FUTURE", default = 24)] // in hours - in future
     pub ingest_allowed_in_future: i64,
+    #[env_config(
+        name = "ZO_INGEST_ORG_RATE_LIMIT",
+        default = 0,
+        help = "Ingestion rate limit per organization on each ingester in MB/s, 0 means unlimited"
+    )]
+    pub ingest_org_rate_limit: usize,
+    #[env_config(
+        name = "ZO_INGEST_ORG_RATE_BURST",
+        default = 0,
+        help = "Ingestion burst size per organization in MB, default is the rate limit"
+    )]
+    pub ingest_org_rate_burst: usize,
+    #[env_config(name = "ZO_INGEST_RETRY_AFTER_MIN", default = 1)] // seconds
+    pub ingest_retry_after_min: u64,
+    #[env_config(name = "ZO_INGEST_RETRY_AFTER_MAX", default = 60)] // seconds
+    pub ingest_retry_after_max: u64,
     #[env_config(name = "ZO_INGEST_FLATTEN_LEVEL", default = 3)] // default flatten level
     pub ingest_flatten_level: u32,
     #[env_config(name = "ZO_IGNORE_FILE_RETENTION_BY_STREAM", default = false)]
@@ -1432,6 +1589,18 @@ pub struct Limit {
         help = "Maximum size of a single enrichment table in mb"
     )]
     pub enrichment_table_max_size: usize,
+    #[env_config(
+        name = "ZO_ENRICHMENT_TABLE_INDEX_LIMIT",
+        default = 512,
+        help = "Maximum memory of the lookup indexes of all the enrichment tables in mb, lookups scan the table when an index doesn't fit"
+    )]
+    pub enrichment_table_index_max_size: usize,
+    #[env_config(
+        name = "ZO_ENRICHMENT_TABLE_SOURCE_ALLOWED_HOSTS",
+        default = "",
+        help = "Comma separated hosts which the url sources of the enrichment tables can download from even if they resolve to a private, loopback or link local address"
+    )]
+    pub enrichment_table_source_allowed_hosts: String,
     #[env_config(name = "ZO_SHORT_URL_RETENTION_DAYS", default = 30)] // days
     pub short_url_retention_days: i64,
     #[env_config(
@@ -1539,6 +1708,32 @@ pub struct Compact {
     pub pending_jobs_metric_interval: u64,
     #[env_config(name = "ZO_COMPACT_MAX_GROUP_FILES", default = 10000)]
     pub max_group_files: usize,
+    #[env_config(
+        name = "ZO_COMPACT_VERIFY_ENABLED",
+        default = false,
+        help = "Periodically compare file_list with the object storage"
+    )]
+    pub verify_enabled: bool,
+    #[env_config(name = "ZO_COMPACT_VERIFY_INTERVAL", default = 3600)] // seconds
+    pub verify_interval: u64,
+    #[env_config(
+        name = "ZO_COMPACT_VERIFY_LOOKBACK_HOURS",
+        default = 24,
+        help = "The verifier checks the hours in this window, the recent hours which may still be merged are skipped"
+    )]
+    pub verify_lookback_hours: i64,
+    #[env_config(
+        name = "ZO_COMPACT_VERIFY_SAMPLE_PARTITIONS",
+        default = 0,
+        help = "Randomly verify this number of partitions (hours or days, by the stream partition time level) per stream in every run, 0 means all the partitions in the window"
+    )]
+    pub verify_sample_partitions: usize,
+    #[env_config(
+        name = "ZO_COMPACT_VERIFY_AUTO_REPAIR",
+        default = false,
+        help = "Add the orphan files to file_list and remove the records of the missing files"
+    )]
+    pub verify_auto_repair: bool,
 }
 
 #[derive(EnvConfig)]
@@ -1890,6 +2085,84 @@ pub struct Encryption {
     pub algorithm: String,
     #[env_config(name = "ZO_MASTER_ENCRYPTION_KEY", default = "")]
     pub master_key: String,
+    #[env_config(
+        name = "ZO_MASTER_ENCRYPTION_KEY_FILE",
+        default = "",
+        help = "File holding the base64 master key, used when ZO_MASTER_ENCRYPTION_KEY is empty"
+    )]
+    pub master_key_file: String,
+    #[env_config(
+        name = "ZO_ENCRYPTION_AT_REST_ENABLED",
+        default = false,
+        help = "Encrypt the stream files in the object storage with per-org data keys. It must stay enabled while encrypted files exist, the local caches keep plaintext"
+    )]
+    pub at_rest_enabled: bool,
+    #[env_config(
+        name = "ZO_ENCRYPTION_KMS_URL",
+        default = "",
+        help = "KMS plugin wrapping the data keys instead of the master key, it serves POST /wrap and POST /unwrap"
+    )]
+    pub kms_url: String,
+    #[env_config(name = "ZO_ENCRYPTION_KMS_TOKEN", default = "")]
+    pub kms_token: String,
+}
+
+#[derive(EnvConfig)]
+pub struct Oidc {
+    #[env_config(name = "ZO_OIDC_ENABLED", default = false)]
+    pub enabled: bool,
+    #[env_config(name = "ZO_OIDC_ISSUER_URL", default = "")]
+    pub issuer_url: String,
+    #[env_config(name = "ZO_OIDC_CLIENT_ID", default = "")]
+    pub client_id: String,
+    #[env_config(name = "ZO_OIDC_CLIENT_SECRET", default = "")]
+    pub client_secret: String,
+    #[env_config(
+        name = "ZO_OIDC_REDIRECT_URL",
+        default = "",
+        help = "Callback registered at the provider, default is ZO_WEB_URL + ZO_BASE_URI + /config/oidc_callback"
+    )]
+    pub redirect_url: String,
+    #[env_config(
+        name = "ZO_OIDC_UI_REDIRECT_URL",
+        default = "",
+        help = "Where the users land after login, default is ZO_WEB_URL + ZO_BASE_URI + /web/cb"
+    )]
+    pub ui_redirect_url: String,
+    #[env_config(name = "ZO_OIDC_SCOPES", default = "openid email profile")]
+    pub scopes: String,
+    #[env_config(name = "ZO_OIDC_EMAIL_CLAIM", default = "email")]
+    pub email_claim: String,
+    #[env_config(name = "ZO_OIDC_GROUPS_CLAIM", default = "groups")]
+    pub groups_claim: String,
+    #[env_config(
+        name = "ZO_OIDC_GROUP_ROLE_MAPPING",
+        default = "",
+        help = "Comma separated group=org:role entries, e.g. sre=default:admin,dev=default:editor. Also used for the SCIM groups"
+    )]
+    pub group_role_mapping: String,
+    #[env_config(name = "ZO_OIDC_DEFAULT_ORG", default = "default")]
+    pub default_org: String,
+    #[env_config(
+        name = "ZO_OIDC_DEFAULT_ROLE",
+        default = "viewer",
+        help = "Role in the default org of the users without a mapped group, empty denies their login"
+    )]
+    pub default_role: String,
+    #[env_config(
+        name = "ZO_OIDC_SESSION_SECRET",
+        default = "",
+        help = "Key signing the login sessions, default is the client secret"
+    )]
+    pub session_secret: String,
+    #[env_config(
+        name = "ZO_OIDC_LINK_NATIVE_USERS",
+        default = false,
+        help = "Lets a provider login take over the existing native user of the same email, the root user is never taken over"
+    )]
+    pub link_native_users: bool,
+    #[env_config(name = "ZO_SCIM_ENABLED", default = false)]
+    pub scim_enabled: bool,
 }
 
 #[derive(EnvConfig)]
@@ -1994,9 +2267,44 @@ pub fn init() -> Config {
         panic!("pipeline config error: {e}");
     }
 
+    // check oidc config
+    if let Err(e) = check_oidc_config(&mut cfg) {
+        panic!("oidc config error: {e}");
+    }
+
     cfg
 }
 
+fn check_oidc_config(cfg: &mut Config) -> Result<(), anyhow::Error> {
+    if !cfg.oidc.enabled {
+        return Ok(());
+    }
+    if cfg.oidc.issuer_url.is_empty() || cfg.oidc.client_id.is_empty() {
+        return Err(anyhow::anyhow!(
+            "ZO_OIDC_ISSUER_URL and ZO_OIDC_CLIENT_ID are required when oidc is enabled"
+        ));
+    }
+    cfg.oidc.issuer_url = cfg.oidc.issuer_url.trim_end_matches('/').to_string();
+    if cfg.oidc.redirect_url.is_empty() {
+        cfg.oidc.redirect_url = format!(
+            "{}{}/config/oidc_callback",
+            cfg.common.web_url, cfg.common.base_uri
+        );
+    }
+    if cfg.oidc.ui_redirect_url.is_empty() {
+        cfg.oidc.ui_redirect_url = format!("{}{}/web/cb", cfg.common.web_url, cfg.common.base_uri);
+    }
+    if cfg.oidc.session_secret.is_empty() {
+        cfg.oidc.session_secret = cfg.oidc.client_secret.clone();
+    }
+    if cfg.oidc.session_secret.is_empty() {
+        return Err(anyhow::anyhow!(
+            "ZO_OIDC_SESSION_SECRET is required for public clients without a client secret"
+        ));
+    }
+    Ok(())
+}
+
 fn check_limit_config(cfg: &mut Config) -> Result<(), anyhow::Error> {
     // set real cpu num
     cfg.limit.real_cpu_num = max(1, sysinfo::get_cpu_limit());
@@ -2111,6 +2419,18 @@ fn check_limit_config(cfg: &mut Config) -> Result<(), anyhow::Error> {
 }
 
 fn check_common_config(cfg: &mut Config) -> Result<(), anyhow::Error> {
+    if cfg.common.traces_service_graph_flush_interval == 0 {
+        cfg.common.traces_service_graph_flush_interval = 60;
+    }
+    if cfg.common.traces_service_graph_wait_time == 0 {
+        cfg.common.traces_service_graph_wait_time = 30;
+    }
+    if cfg.common.traces_red_metrics_flush_interval == 0 {
+        cfg.common.traces_red_metrics_flush_interval = 60;
+    }
+    if cfg.common.traces_tail_sampling_decision_ttl == 0 {
+        cfg.common.traces_tail_sampling_decision_ttl = 300;
+    }
     if cfg.limit.file_push_interval == 0 {
         cfg.limit.file_push_interval = 60;
     }
@@ -2453,6 +2773,34 @@ fn check_memory_config(cfg: &mut Config) -> Result<(), anyhow::Error> {
     if cfg.limit.wal_write_queue_size == 0 {
         cfg.limit.wal_write_queue_size = 10000;
     }
+    cfg.common.wal_replication_mode = cfg.common.wal_replication_mode.to_lowercase();
+    if cfg.common.wal_replication_mode != "sync" && cfg.common.wal_replication_mode != "async" {
+        return Err(anyhow::anyhow!(
+            "ZO_WAL_REPLICATION_MODE must be sync or async"
+        ));
+    }
+    if cfg.limit.wal_replication_factor == 0 {
+        cfg.limit.wal_replication_factor = 1;
+    }
+    if cfg.limit.wal_replication_queue_size == 0 {
+        cfg.limit.wal_replication_queue_size = 10000;
+    }
+    if cfg.limit.wal_replication_check_interval == 0 {
+        cfg.limit.wal_replication_check_interval = 30;
+    }
+
+    // ingestion flow control
+    if cfg.limit.ingest_org_rate_burst == 0 {
+        cfg.limit.ingest_org_rate_burst = cfg.limit.ingest_org_rate_limit;
+    }
+    cfg.limit.ingest_org_rate_limit *= 1024 * 1024;
+    cfg.limit.ingest_org_rate_burst *= 1024 * 1024;
+    if cfg.limit.ingest_retry_after_min == 0 {
+        cfg.limit.ingest_retry_after_min = 1;
+    }
+    if cfg.limit.ingest_retry_after_max < cfg.limit.ingest_retry_after_min {
+        cfg.limit.ingest_retry_after_max = cfg.limit.ingest_retry_after_min;
+    }
 
     // check query settings
     if cfg.limit.query_group_base_speed == 0 {
@@ -2644,6 +2992,12 @@ fn check_compact_config(cfg: &mut Config) -> Result<(), anyhow::Error> {
     if cfg.compact.pending_jobs_metric_interval == 0 {
         cfg.compact.pending_jobs_metric_interval = 300;
     }
+    if cfg.compact.verify_interval == 0 {
+        cfg.compact.verify_interval = 3600;
+    }
+    if cfg.compact.verify_lookback_hours < 1 {
+        cfg.compact.verify_lookback_hours = 24;
+    }
 
     Ok(())
 }
@@ -2764,6 +3118,33 @@ pub fn get_cluster_name() -> String {
 }
 
 fn check_encryption_config(cfg: &mut Config) -> Result<(), anyhow::Error> {
+    if cfg.encryption.master_key.is_empty() && !cfg.encryption.master_key_file.is_empty() {
+        cfg.encryption.master_key = std::fs::read_to_string(&cfg.encryption.master_key_file)
+            .map_err(|e| anyhow::anyhow!("failed to read the master encryption key file: {e}"))?
+            .trim()
+            .to_string();
+        if cfg.encryption.algorithm.is_empty() {
+            cfg.encryption.algorithm = "aes-256-siv".to_string();
+        }
+    }
+    if !cfg.encryption.kms_url.is_empty() {
+        cfg.encryption.kms_url = cfg.encryption.kms_url.trim_end_matches('/').to_string();
+    }
+    if cfg.encryption.at_rest_enabled
+        && cfg.encryption.master_key.is_empty()
+        && cfg.encryption.kms_url.is_empty()
+    {
+        return Err(anyhow::anyhow!(
+            "ZO_ENCRYPTION_AT_REST_ENABLED needs a master encryption key or ZO_ENCRYPTION_KMS_URL"
+        ));
+    }
+    // the master key wraps the data keys when there is no kms, it must be valid
+    if cfg.encryption.at_rest_enabled
+        && cfg.encryption.kms_url.is_empty()
+        && cfg.encryption.algorithm.is_empty()
+    {
+        cfg.encryption.algorithm = "aes-256-siv".to_string();
+    }
     if !cfg.encryption.algorithm.is_empty() {
         if cfg.encryption.algorithm != "aes-256-siv" {
             return Err(anyhow::anyhow!(
@@ -2817,10 +3198,75 @@ pub fn get_parquet_compression(compression: &str) -> parquet::basic::Compression
     }
 }
 
+/// Same as [`get_parquet_compression`] but uses the given level for the codecs
+/// which support it, invalid levels fall back to the codec default.
+pub fn get_parquet_compression_with_level(
+    compression: &str,
+    level: Option<i32>,
+) -> parquet::basic::Compression {
+    use parquet::basic::{BrotliLevel, Compression, GzipLevel, ZstdLevel};
+    let Some(level) = level else {
+        return get_parquet_compression(compression);
+    };
+    match get_parquet_compression(compression) {
+        Compression::GZIP(_) => {
+            Compression::GZIP(GzipLevel::try_new(level as u32).unwrap_or_default())
+        }
+        Compression::BROTLI(_) => {
+            Compression::BROTLI(BrotliLevel::try_new(level as u32).unwrap_or_default())
+        }
+        Compression::ZSTD(_) => Compression::ZSTD(ZstdLevel::try_new(level).unwrap_or_default()),
+        v => v,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_get_parquet_compression_with_level() {
+        use parquet::basic::{Compression, ZstdLevel};
+        assert_eq!(
+            get_parquet_compression_with_level("zstd", Some(9)),
+            Compression::ZSTD(ZstdLevel::try_new(9).unwrap())
+        );
+        assert_eq!(
+            get_parquet_compression_with_level("zstd", Some(99)),
+            Compression::ZSTD(Default::default())
+        );
+        assert_eq!(
+            get_parquet_compression_with_level("snappy", Some(3)),
+            Compression::SNAPPY
+        );
+        assert_eq!(
+            get_parquet_compression_with_level("gzip", None),
+            get_parquet_compression("gzip")
+        );
+    }
+
+    #[test]
+    fn test_check_encryption_config() {
+        let mut cfg = Config::init().unwrap();
+        cfg.encryption.at_rest_enabled = true;
+        cfg.encryption.algorithm = "".to_string();
+        cfg.encryption.kms_url = "".to_string();
+        cfg.encryption.master_key = "not a key".to_string();
+        assert!(check_encryption_config(&mut cfg).is_err());
+
+        cfg.encryption.algorithm = "".to_string();
+        cfg.encryption.master_key = BASE64_STANDARD.encode([7u8; 64]);
+        check_encryption_config(&mut cfg).unwrap();
+        assert_eq!(cfg.encryption.algorithm, "aes-256-siv");
+
+        // the kms wraps the data keys
+        cfg.encryption.algorithm = "".to_string();
+        cfg.encryption.master_key = "".to_string();
+        cfg.encryption.kms_url = "http://kms:8200/".to_string();
+        check_encryption_config(&mut cfg).unwrap();
+        assert!(cfg.encryption.algorithm.is_empty());
+    }
+
     #[test]
     fn test_get_config() {
         let mut cfg = Config::init().unwrap();
@@ -0,0 +1,155 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Named API tokens of users and service accounts. A token never has more
+//! access than its owner, its scope and stream patterns narrow it down further.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::meta::grant::wildcard_match;
+
+/// Prefix of the API tokens, the full token is `o2t_{id}_{secret}`.
+pub const API_TOKEN_PREFIX: &str = "o2t_";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiTokenScope {
+    /// Everything the owner can do
+    #[default]
+    Full,
+    /// Only the ingestion endpoints
+    Ingest,
+    /// Only searches and reads
+    Query,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ApiToken {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    /// Email of the user or service account owning the token
+    #[serde(default)]
+    pub user_email: String,
+    #[serde(default)]
+    pub scope: ApiTokenScope,
+    /// Stream name patterns, `*` matches any characters. Empty means all
+    /// the streams of the owner.
+    #[serde(default)]
+    pub streams: Vec<String>,
+    /// sha256 of the secret, never returned by the API
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub token_hash: String,
+    #[serde(default)]
+    pub created_at: i64,
+    /// Expiry in microseconds, the token never expires without it
+    #[serde(default)]
+    pub expires_at: Option<i64>,
+    #[serde(default)]
+    pub last_used_at: Option<i64>,
+    #[serde(default)]
+    pub revoked: bool,
+}
+
+impl ApiToken {
+    pub fn validate(&self, now: i64) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("token name is required".to_string());
+        }
+        if self.streams.iter().any(|s| s.trim().is_empty()) {
+            return Err("stream patterns can't be empty".to_string());
+        }
+        if self.expires_at.is_some_and(|e| e <= now) {
+            return Err("expires_at must be in the future".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn is_active(&self, now: i64) -> bool {
+        !self.revoked && self.expires_at.is_none_or(|e| e > now)
+    }
+
+    pub fn allows_stream(&self, stream_name: &str) -> bool {
+        self.streams.is_empty() || self.streams.iter().any(|p| wildcard_match(p, stream_name))
+    }
+
+    pub fn format(id: &str, secret: &str) -> String {
+        format!("{API_TOKEN_PREFIX}{id}_{secret}")
+    }
+
+    /// Splits a token into its id and secret.
+    pub fn parse(token: &str) -> Option<(&str, &str)> {
+        token
+            .strip_prefix(API_TOKEN_PREFIX)?
+            .split_once('_')
+            .filter(|(id, secret)| !id.is_empty() && !secret.is_empty())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, ToSchema)]
+pub struct CreateApiTokenRequest {
+    pub name: String,
+    /// Owner of the token, only admins create tokens for someone else, e.g. a
+    /// service account. Defaults to the caller.
+    #[serde(default)]
+    pub user_email: Option<String>,
+    #[serde(default)]
+    pub scope: ApiTokenScope,
+    #[serde(default)]
+    pub streams: Vec<String>,
+    #[serde(default)]
+    pub expires_at: Option<i64>,
+}
+
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct CreateApiTokenResponse {
+    #[serde(flatten)]
+    pub info: ApiToken,
+    /// The token itself, it is only shown once
+    pub token: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_token() {
+        let token = ApiToken {
+            id: "1".to_string(),
+            name: "agent".to_string(),
+            user_email: "agent@example.com".to_string(),
+            scope: ApiTokenScope::Ingest,
+            streams: vec!["k8s_*".to_string()],
+            token_hash: String::new(),
+            created_at: 0,
+            expires_at: Some(100),
+            last_used_at: None,
+            revoked: false,
+        };
+        assert!(token.is_active(99));
+        assert!(!token.is_active(100));
+        assert!(token.validate(50).is_ok());
+        assert!(token.validate(100).is_err());
+        assert!(token.allows_stream("k8s_logs"));
+        assert!(!token.allows_stream("payments"));
+
+        let formatted = ApiToken::format("7324", "s3cr_et");
+        assert_eq!(ApiToken::parse(&formatted), Some(("7324", "s3cr_et")));
+        assert_eq!(ApiToken::parse("o2t_7324"), None);
+        assert_eq!(ApiToken::parse("passcode"), None);
+    }
+}
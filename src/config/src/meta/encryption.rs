@@ -0,0 +1,82 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Encryption at rest of the stream files. Each org has versioned data keys,
+//! the files are encrypted with the active one and keep the version they were
+//! written with.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyProvider {
+    /// Wrapped by the master encryption key
+    #[default]
+    Local,
+    /// Wrapped by the KMS plugin
+    Kms,
+}
+
+/// A data key as stored, `key` is the raw key for the local provider, which
+/// the cipher table encrypts with the master key, and the wrapped key for the
+/// KMS provider, both base64 encoded.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DataKeyEntry {
+    pub version: u32,
+    pub provider: KeyProvider,
+    pub key: String,
+    pub created_at: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DataKeyInfo {
+    pub version: u32,
+    pub provider: KeyProvider,
+    pub created_at: i64,
+    pub created_by: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct EncryptionStatus {
+    pub enabled: bool,
+    /// Version of the key new files are encrypted with
+    pub active_version: Option<u32>,
+    pub keys: Vec<DataKeyInfo>,
+}
+
+impl DataKeyEntry {
+    /// The name of the entry in the cipher table.
+    pub fn name(version: u32) -> String {
+        format!("data_key_v{version}")
+    }
+
+    pub fn parse_name(name: &str) -> Option<u32> {
+        name.strip_prefix("data_key_v")?.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_key_name() {
+        assert_eq!(DataKeyEntry::name(3), "data_key_v3");
+        assert_eq!(DataKeyEntry::parse_name("data_key_v3"), Some(3));
+        assert_eq!(DataKeyEntry::parse_name("data_key_"), None);
+        assert_eq!(DataKeyEntry::parse_name("my_key"), None);
+    }
+}
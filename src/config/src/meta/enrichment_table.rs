@@ -0,0 +1,273 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::stream::StreamType;
+
+/// Minimum seconds between two scheduled refreshes of a source.
+pub const MIN_REFRESH_INTERVAL: u64 = 60;
+
+/// An enrichment table which is loaded from an external source and refreshed
+/// on a schedule. Every refresh replaces the whole table.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct EnrichmentTableSource {
+    #[serde(default)]
+    pub name: String,
+    pub source: SourceKind,
+    /// Inferred from the extension of the url or path if not set, the query
+    /// source doesn't use it.
+    #[serde(default)]
+    pub format: Option<SourceFormat>,
+    /// Seconds between two refreshes, 0 only refreshes on demand
+    #[serde(default = "default_refresh_interval")]
+    pub refresh_interval: u64,
+    /// The columns which every row of a refresh must have
+    #[serde(default)]
+    pub required_columns: Vec<String>,
+    #[serde(default)]
+    pub created_at: i64,
+    #[serde(default)]
+    pub updated_at: i64,
+    /// The user who saved the source last, the query source runs with the
+    /// access of this user
+    #[serde(default)]
+    pub updated_by: String,
+}
+
+fn default_refresh_interval() -> u64 {
+    86400
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SourceKind {
+    /// HTTP(S) url
+    Url {
+        url: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+    /// Path of a file under `enrichment_tables/{org_id}/` in the object
+    /// storage
+    ObjectStorage { path: String },
+    /// SQL query against another stream of the organization
+    Query {
+        sql: String,
+        #[serde(default)]
+        stream_type: StreamType,
+        /// Seconds before the refresh which the query covers
+        #[serde(default = "default_query_lookback")]
+        lookback: i64,
+    },
+}
+
+fn default_query_lookback() -> i64 {
+    3600
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceFormat {
+    Json,
+    Ndjson,
+    Csv,
+    Parquet,
+}
+
+impl EnrichmentTableSource {
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.source {
+            SourceKind::Url { url, .. } => {
+                if !url.starts_with("http://") && !url.starts_with("https://") {
+                    return Err("url must start with http:// or https://".to_string());
+                }
+            }
+            SourceKind::ObjectStorage { path } => {
+                if path.trim().is_empty() {
+                    return Err("object storage path can't be empty".to_string());
+                }
+                if path.starts_with('/')
+                    || path
+                        .split(['/', '\\'])
+                        .any(|part| part == "." || part == "..")
+                {
+                    return Err(
+                        "object storage path must be relative and can't contain . or .. segments"
+                            .to_string(),
+                    );
+                }
+            }
+            SourceKind::Query { sql, lookback, .. } => {
+                if !sql.trim().to_lowercase().starts_with("select") {
+                    return Err("query must be a SELECT statement".to_string());
+                }
+                if *lookback <= 0 {
+                    return Err("query lookback must be positive".to_string());
+                }
+            }
+        }
+        if !matches!(self.source, SourceKind::Query { .. }) && self.get_format().is_none() {
+            return Err(
+                "format can't be inferred from the extension, set one of json, ndjson, csv or parquet"
+                    .to_string(),
+            );
+        }
+        if self.refresh_interval > 0 && self.refresh_interval < MIN_REFRESH_INTERVAL {
+            return Err(format!(
+                "refresh_interval must be 0 or at least {MIN_REFRESH_INTERVAL} seconds"
+            ));
+        }
+        if self.required_columns.iter().any(|c| c.trim().is_empty()) {
+            return Err("required column can't be empty".to_string());
+        }
+        Ok(())
+    }
+
+    /// Returns the format of the data, the explicit one or the one of the
+    /// extension.
+    pub fn get_format(&self) -> Option<SourceFormat> {
+        if self.format.is_some() {
+            return self.format;
+        }
+        let location = match &self.source {
+            SourceKind::Url { url, .. } => url.split(['?', '#']).next().unwrap_or_default(),
+            SourceKind::ObjectStorage { path } => path.as_str(),
+            SourceKind::Query { .. } => return None,
+        };
+        let ext = location.rsplit_once('.')?.1.to_lowercase();
+        match ext.as_str() {
+            "json" => Some(SourceFormat::Json),
+            "ndjson" | "jsonl" => Some(SourceFormat::Ndjson),
+            "csv" => Some(SourceFormat::Csv),
+            "parquet" => Some(SourceFormat::Parquet),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RefreshStatus {
+    #[default]
+    Pending,
+    Success,
+    Failed,
+}
+
+/// The result of the last refresh of a source.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct EnrichmentTableSourceStatus {
+    pub status: RefreshStatus,
+    /// microseconds
+    pub last_refresh_at: i64,
+    /// microseconds
+    pub last_success_at: i64,
+    pub rows: usize,
+    /// milliseconds
+    pub took: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct EnrichmentTableSourceResponse {
+    pub source: EnrichmentTableSource,
+    pub status: EnrichmentTableSourceStatus,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url_source(url: &str) -> EnrichmentTableSource {
+        EnrichmentTableSource {
+            name: "assets".to_string(),
+            source: SourceKind::Url {
+                url: url.to_string(),
+                headers: HashMap::new(),
+            },
+            format: None,
+            refresh_interval: 3600,
+            required_columns: vec![],
+            created_at: 0,
+            updated_at: 0,
+            updated_by: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_get_format() {
+        let source = url_source("https://cmdb.local/export.CSV?token=x");
+        assert_eq!(source.get_format(), Some(SourceFormat::Csv));
+        assert_eq!(
+            url_source("http://cmdb.local/export.jsonl").get_format(),
+            Some(SourceFormat::Ndjson)
+        );
+        assert_eq!(url_source("http://cmdb.local/export").get_format(), None);
+        let mut source = url_source("http://cmdb.local/export");
+        source.format = Some(SourceFormat::Parquet);
+        assert_eq!(source.get_format(), Some(SourceFormat::Parquet));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(
+            url_source("https://cmdb.local/export.csv")
+                .validate()
+                .is_ok()
+        );
+        assert!(
+            url_source("ftp://cmdb.local/export.csv")
+                .validate()
+                .is_err()
+        );
+        assert!(url_source("https://cmdb.local/export").validate().is_err());
+
+        let mut source = url_source("https://cmdb.local/export.csv");
+        source.refresh_interval = 10;
+        assert!(source.validate().is_err());
+        source.refresh_interval = 0;
+        assert!(source.validate().is_ok());
+
+        let source: EnrichmentTableSource = serde_json::from_str(
+            r#"{"source":{"type":"query","sql":"SELECT host, owner FROM assets"}}"#,
+        )
+        .unwrap();
+        assert_eq!(source.refresh_interval, 86400);
+        assert!(source.validate().is_ok());
+        let source: EnrichmentTableSource =
+            serde_json::from_str(r#"{"source":{"type":"query","sql":"DELETE FROM assets"}}"#)
+                .unwrap();
+        assert!(source.validate().is_err());
+
+        let mut source = url_source("https://cmdb.local/export.csv");
+        for (path, ok) in [
+            ("assets/hosts.csv", true),
+            ("/files/other/hosts.csv", false),
+            ("../other/hosts.csv", false),
+            ("assets/../../other/hosts.csv", false),
+            ("assets\\..\\hosts.csv", false),
+        ] {
+            source.source = SourceKind::ObjectStorage {
+                path: path.to_string(),
+            };
+            assert_eq!(source.validate().is_ok(), ok, "{path}");
+        }
+    }
+}
@@ -0,0 +1,237 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Built-in access grants of the open source build. A user or service account
+//! which never had a grant on a kind of resource keeps the access given by its
+//! role. Once it gets one, it's restricted on that kind for good: only the
+//! granted objects of that kind are accessible, and none once its last grant
+//! is deleted. A `*` grant at the admin level gives the access of the role
+//! back.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::meta::stream::StreamType;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GrantResource {
+    /// Streams whose name matches the pattern
+    Stream,
+    /// Dashboards of the folder with the id in the pattern
+    DashboardFolder,
+    /// Alerts whose name matches the pattern
+    Alert,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GrantLevel {
+    Read,
+    /// Write data into streams, doesn't include reading them
+    Ingest,
+    /// Everything, including changing and deleting the object
+    Admin,
+}
+
+impl GrantLevel {
+    pub fn allows(&self, wanted: GrantLevel) -> bool {
+        *self == GrantLevel::Admin || *self == wanted
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Grant {
+    #[serde(default)]
+    pub id: String,
+    /// Email of the user or service account
+    pub subject: String,
+    pub resource: GrantResource,
+    /// Name pattern, `*` matches any characters, e.g. `app_*`
+    pub pattern: String,
+    /// Limits a stream grant to one stream type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_type: Option<StreamType>,
+    pub level: GrantLevel,
+}
+
+impl Grant {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.subject.trim().is_empty() {
+            return Err("grant subject is required".to_string());
+        }
+        if self.pattern.trim().is_empty() {
+            return Err("grant pattern is required".to_string());
+        }
+        if self.resource != GrantResource::Stream {
+            if self.level == GrantLevel::Ingest {
+                return Err("ingest level is only valid for streams".to_string());
+            }
+            if self.stream_type.is_some() {
+                return Err("stream_type is only valid for streams".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    fn matches(
+        &self,
+        resource: GrantResource,
+        stream_type: Option<StreamType>,
+        name: &str,
+    ) -> bool {
+        self.resource == resource
+            && (self.stream_type.is_none()
+                || stream_type.is_none()
+                || self.stream_type == stream_type)
+            && wildcard_match(&self.pattern, name)
+    }
+}
+
+/// Checks the access of a subject to an object given all the grants of the
+/// subject in the org and the kinds of resources it's restricted on.
+pub fn is_allowed(
+    grants: &[Grant],
+    restricted: &[GrantResource],
+    resource: GrantResource,
+    stream_type: Option<StreamType>,
+    name: &str,
+    level: GrantLevel,
+) -> bool {
+    let mut is_restricted = restricted.contains(&resource);
+    for grant in grants.iter().filter(|g| g.resource == resource) {
+        is_restricted = true;
+        if grant.level.allows(level) && grant.matches(resource, stream_type, name) {
+            return true;
+        }
+    }
+    !is_restricted
+}
+
+/// Matches `name` against `pattern` where `*` matches any sequence of
+/// characters.
+pub fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let parts = pattern.split('*').collect::<Vec<_>>();
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if name.len() < first.len() + last.len() || !name.starts_with(first) || !name.ends_with(last) {
+        return false;
+    }
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grant(resource: GrantResource, pattern: &str, level: GrantLevel) -> Grant {
+        Grant {
+            id: String::new(),
+            subject: "contractor@example.com".to_string(),
+            resource,
+            pattern: pattern.to_string(),
+            stream_type: None,
+            level,
+        }
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("app_*", "app_web"));
+        assert!(wildcard_match("*", "payments"));
+        assert!(wildcard_match("a*b*c", "a_x_b_y_c"));
+        assert!(!wildcard_match("a*b*c", "a_c"));
+        assert!(!wildcard_match("app_*", "payments"));
+        assert!(wildcard_match("payments", "payments"));
+        assert!(!wildcard_match("ab*ba", "aba"));
+    }
+
+    #[test]
+    fn test_is_allowed() {
+        let grants = vec![
+            grant(GrantResource::Stream, "app_*", GrantLevel::Read),
+            grant(GrantResource::Stream, "app_web", GrantLevel::Ingest),
+        ];
+        let allowed = |name, level| {
+            is_allowed(
+                &grants,
+                &[GrantResource::Stream],
+                GrantResource::Stream,
+                Some(StreamType::Logs),
+                name,
+                level,
+            )
+        };
+        assert!(allowed("app_web", GrantLevel::Read));
+        assert!(allowed("app_web", GrantLevel::Ingest));
+        assert!(!allowed("app_api", GrantLevel::Ingest));
+        assert!(!allowed("payments", GrantLevel::Read));
+        assert!(!allowed("app_web", GrantLevel::Admin));
+        // no grant on dashboards, the role decides
+        assert!(is_allowed(
+            &grants,
+            &[GrantResource::Stream],
+            GrantResource::DashboardFolder,
+            None,
+            "default",
+            GrantLevel::Admin
+        ));
+        // the last grant was deleted, nothing is accessible
+        assert!(!is_allowed(
+            &[],
+            &[GrantResource::Stream],
+            GrantResource::Stream,
+            Some(StreamType::Logs),
+            "app_web",
+            GrantLevel::Read
+        ));
+        assert!(is_allowed(
+            &[],
+            &[],
+            GrantResource::Stream,
+            Some(StreamType::Logs),
+            "app_web",
+            GrantLevel::Read
+        ));
+    }
+
+    #[test]
+    fn test_grant_validate() {
+        assert!(
+            grant(GrantResource::Stream, "app_*", GrantLevel::Ingest)
+                .validate()
+                .is_ok()
+        );
+        assert!(
+            grant(GrantResource::Alert, "a*", GrantLevel::Ingest)
+                .validate()
+                .is_err()
+        );
+        assert!(
+            grant(GrantResource::Stream, " ", GrantLevel::Read)
+                .validate()
+                .is_err()
+        );
+    }
+}
@@ -15,12 +15,16 @@
 
 pub mod actions;
 pub mod alerts;
+pub mod api_token;
 pub mod bitvec;
 pub mod cluster;
 pub mod dashboards;
 pub mod destinations;
+pub mod encryption;
+pub mod enrichment_table;
 pub mod folder;
 pub mod function;
+pub mod grant;
 pub mod inverted_index;
 pub mod logger;
 pub mod meta_store;
@@ -28,9 +32,12 @@ pub mod organization;
 pub mod otlp;
 pub mod pipeline;
 pub mod promql;
+pub mod quota;
 pub mod ratelimit;
+pub mod scim;
 pub mod search;
 pub mod self_reporting;
+pub mod session;
 pub mod short_url;
 pub mod sql;
 pub mod stream;
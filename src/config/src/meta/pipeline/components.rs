@@ -141,6 +141,25 @@ pub enum NodeData {
     Query(DerivedStream),
     Function(FunctionParams),
     Condition(ConditionParams),
+    Parse(ParseParams),
+    FieldTransform(FieldTransformParams),
+    Sample(SampleParams),
+    RateLimit(RateLimitParams),
+    HashRoute(HashRouteParams),
+}
+
+impl NodeData {
+    /// Validates the params of the built-in transform nodes.
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            NodeData::Parse(params) => params.validate(),
+            NodeData::FieldTransform(params) => params.validate(),
+            NodeData::Sample(params) => params.validate(),
+            NodeData::RateLimit(params) => params.validate(),
+            NodeData::HashRoute(params) => params.validate(),
+            _ => Ok(()),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, ToSchema)]
@@ -159,6 +178,175 @@ pub struct ConditionParams {
     pub conditions: Vec<RoutingCondition>,
 }
 
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ParseFormat {
+    Json,
+    Logfmt,
+    Grok,
+    Regex,
+}
+
+/// Parses a string field of the record and merges the result into the record.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ParseParams {
+    /// The (flattened) field which holds the text to parse
+    pub field: String,
+    pub format: ParseFormat,
+    /// The grok expression or the regex with named capture groups
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// Prefix added to every parsed field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_prefix: Option<String>,
+    /// Removes the source field after a successful parse
+    #[serde(default)]
+    pub drop_source: bool,
+    /// Doesn't report records which fail to parse as pipeline errors
+    #[serde(default)]
+    pub ignore_failure: bool,
+}
+
+impl ParseParams {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.field.trim().is_empty() {
+            return Err("ParseNode must have a source field".to_string());
+        }
+        match (self.format, self.pattern.as_deref()) {
+            (ParseFormat::Grok | ParseFormat::Regex, None | Some("")) => {
+                Err("ParseNode with grok or regex format must have a pattern".to_string())
+            }
+            (ParseFormat::Grok, Some(pattern)) => crate::utils::grok::Pattern::grok(pattern)
+                .map(|_| ())
+                .map_err(|e| format!("ParseNode {e}")),
+            (ParseFormat::Regex, Some(pattern)) => crate::utils::grok::Pattern::regex(pattern)
+                .map(|_| ())
+                .map_err(|e| format!("ParseNode {e}")),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldAction {
+    Drop,
+    Keep,
+    Rename,
+}
+
+/// Drops, keeps or renames fields of the record.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FieldTransformParams {
+    pub action: FieldAction,
+    /// The fields to drop or keep
+    #[serde(default)]
+    pub fields: Vec<String>,
+    /// old name -> new name, for the rename action
+    #[serde(default)]
+    pub renames: HashMap<String, String>,
+}
+
+impl FieldTransformParams {
+    pub fn validate(&self) -> Result<(), String> {
+        match self.action {
+            FieldAction::Drop | FieldAction::Keep => {
+                if self.fields.is_empty() || self.fields.iter().any(|f| f.trim().is_empty()) {
+                    return Err("FieldTransformNode must have non-empty fields".to_string());
+                }
+            }
+            FieldAction::Rename => {
+                if self.renames.is_empty()
+                    || self
+                        .renames
+                        .iter()
+                        .any(|(from, to)| from.trim().is_empty() || to.trim().is_empty())
+                {
+                    return Err("FieldTransformNode must have non-empty renames".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SampleMode {
+    /// Every record is kept with the given probability
+    #[default]
+    Random,
+    /// Records with the same key values are either all kept or all dropped
+    Hash,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SampleParams {
+    #[serde(default)]
+    pub mode: SampleMode,
+    /// Percentage of the records to keep, 0 - 100
+    pub percent: f64,
+    /// The fields whose values are hashed in the hash mode
+    #[serde(default)]
+    pub key_fields: Vec<String>,
+}
+
+impl SampleParams {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=100.0).contains(&self.percent) {
+            return Err("SampleNode percent must be between 0 and 100".to_string());
+        }
+        if self.mode == SampleMode::Hash && self.key_fields.is_empty() {
+            return Err("SampleNode in hash mode must have key fields".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Keeps at most `limit` records per `window` seconds for every distinct value
+/// of the key fields, the records above the limit are dropped.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RateLimitParams {
+    /// No key fields means a single limit for all the records
+    #[serde(default)]
+    pub key_fields: Vec<String>,
+    pub limit: u64,
+    #[serde(default = "default_rate_limit_window")]
+    pub window: u64,
+}
+
+fn default_rate_limit_window() -> u64 {
+    1
+}
+
+impl RateLimitParams {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.limit == 0 {
+            return Err("RateLimitNode limit must be greater than 0".to_string());
+        }
+        if self.window == 0 {
+            return Err("RateLimitNode window must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Sends every record to exactly one of the children, picked by the hash of
+/// the key fields.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HashRouteParams {
+    pub key_fields: Vec<String>,
+}
+
+impl HashRouteParams {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.key_fields.is_empty() || self.key_fields.iter().any(|f| f.trim().is_empty()) {
+            return Err("HashRouteNode must have non-empty key fields".to_string());
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 struct Position {
     x: f32,
@@ -275,4 +463,63 @@ mod tests {
         let node_data = json::from_value::<NodeData>(payload);
         assert!(node_data.is_ok());
     }
+
+    #[test]
+    fn test_transform_node_serialization() {
+        let payload = json::json!({
+            "node_type": "parse",
+            "field": "log",
+            "format": "grok",
+            "pattern": "%{IP:client} %{GREEDYDATA:message}",
+        });
+        let node_data: NodeData = json::from_value(payload).unwrap();
+        assert!(matches!(&node_data, NodeData::Parse(p) if p.format == ParseFormat::Grok));
+        assert!(node_data.validate().is_ok());
+
+        let payload = json::json!({
+            "node_type": "parse",
+            "field": "log",
+            "format": "regex",
+        });
+        let node_data: NodeData = json::from_value(payload).unwrap();
+        assert!(node_data.validate().is_err());
+
+        let payload = json::json!({
+            "node_type": "field_transform",
+            "action": "rename",
+            "renames": {"msg": "message"},
+        });
+        let node_data: NodeData = json::from_value(payload).unwrap();
+        assert!(node_data.validate().is_ok());
+
+        let payload = json::json!({
+            "node_type": "sample",
+            "mode": "hash",
+            "percent": 10.0,
+        });
+        let node_data: NodeData = json::from_value(payload).unwrap();
+        assert!(node_data.validate().is_err());
+
+        let payload = json::json!({
+            "node_type": "rate_limit",
+            "key_fields": ["service"],
+            "limit": 100,
+        });
+        let node_data: NodeData = json::from_value(payload).unwrap();
+        assert_eq!(
+            node_data,
+            NodeData::RateLimit(RateLimitParams {
+                key_fields: vec!["service".to_string()],
+                limit: 100,
+                window: 1,
+            })
+        );
+
+        let payload = json::json!({
+            "node_type": "hash_route",
+            "key_fields": [],
+        });
+        let node_data: NodeData = json::from_value(payload).unwrap();
+        assert!(node_data.validate().is_err());
+    }
 }
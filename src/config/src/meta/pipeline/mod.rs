@@ -58,6 +58,10 @@ pub struct Pipeline {
     pub source: PipelineSource,
     pub nodes: Vec<Node>,
     pub edges: Vec<Edge>,
+    /// Logs stream which receives the records failing in any node, together
+    /// with the node id and the error message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dead_letter_stream: Option<String>,
 }
 
 impl Pipeline {
@@ -84,12 +88,14 @@ impl Pipeline {
     /// 1. non-empty nodes list
     /// 2. non-empty edges list
     /// 3. 1st node in nodes list is either StreamNode or QueryNode
-    /// 4. non-empty `conditions` in all ConditionNode nodes in nodes list
+    /// 4. non-empty `conditions` in all ConditionNode nodes in nodes list, and valid params in all
+    ///    the built-in transform nodes
     /// 5. every node is reachable
     /// 6. all leaf nodes are of type StreamNode
     /// 7. In the same branch, unchecked `after_flattened` FunctionNode can't follow checked
     ///    `after_flattened` checked FunctionNode
     /// 8. EnrichmentTables can only be used in Scheduled pipelines
+    /// 9. the dead-letter stream, if any, isn't the source stream
     ///
     /// If all satisfies, populates the [Pipeline::source] with the first node in nodes list
     pub fn validate(&mut self) -> Result<()> {
@@ -134,6 +140,8 @@ impl Pipeline {
             if matches!(&node.data, NodeData::Condition(condition_params) if condition_params.conditions.is_empty())
             {
                 return Err(anyhow!("ConditionNode must have non-empty conditions"));
+            } else if let Err(e) = node.data.validate() {
+                return Err(anyhow!(e));
             } else if let NodeData::Stream(stream_params) = &mut node.data {
                 // ck 8
                 if stream_params.stream_type == StreamType::EnrichmentTables
@@ -150,6 +158,25 @@ impl Pipeline {
             }
         }
 
+        // ck 9
+        if let Some(dead_letter) = self.dead_letter_stream.as_mut() {
+            if dead_letter.trim().is_empty() {
+                self.dead_letter_stream = None;
+            } else {
+                if !cfg.common.skip_formatting_stream_name {
+                    *dead_letter = format_stream_name(dead_letter);
+                }
+                if matches!(&self.source, PipelineSource::Realtime(stream_params)
+                    if stream_params.stream_type == StreamType::Logs
+                        && stream_params.stream_name.as_str() == dead_letter.as_str())
+                {
+                    return Err(anyhow!(
+                        "Dead-letter stream can't be the source stream of the pipeline"
+                    ));
+                }
+            }
+        }
+
         // ck 5
         if self.edges.len() < self.nodes.len() - 1 {
             return Err(anyhow!(
@@ -308,6 +335,7 @@ where
     String: Type<R::Database> + Decode<'r, R::Database>,
     i32: Type<R::Database> + Decode<'r, R::Database>,
     bool: Type<R::Database> + Decode<'r, R::Database>,
+    Option<String>: Type<R::Database> + Decode<'r, R::Database>,
 {
     fn from_row(row: &'r R) -> Result<Self, Error> {
         let id: String = row.try_get("id")?;
@@ -339,6 +367,11 @@ where
             _ => return Err(sqlx::Error::ColumnNotFound("Invalid source type".into())),
         };
 
+        let dead_letter_stream: Option<String> = row
+            .try_get::<Option<String>, _>("dead_letter_stream")
+            .unwrap_or_default()
+            .filter(|s| !s.is_empty());
+
         let (nodes, edges) = {
             let nodes_raw: String = row.try_get("nodes")?;
             let edges_raw: String = row.try_get("edges")?;
@@ -358,6 +391,7 @@ where
             source,
             nodes,
             edges,
+            dead_letter_stream,
         })
     }
 }
@@ -365,6 +399,32 @@ where
 #[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
 pub struct PipelineList {
     pub list: Vec<Pipeline>,
+    /// pipeline_id -> node_id -> stats
+    #[serde(default)]
+    pub stats: HashMap<String, HashMap<String, PipelineNodeStats>>,
+}
+
+/// Record counters of a pipeline node, summed over all the nodes of the cluster.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct PipelineNodeStats {
+    /// Records received by the node
+    pub processed: u64,
+    /// Errors reported by the node
+    pub errors: u64,
+    /// Records which the node didn't pass on, filtered out or failed
+    pub dropped: u64,
+}
+
+impl PipelineNodeStats {
+    pub fn add(&mut self, other: &PipelineNodeStats) {
+        self.processed += other.processed;
+        self.errors += other.errors;
+        self.dropped += other.dropped;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.processed == 0 && self.errors == 0 && self.dropped == 0
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
@@ -378,6 +438,69 @@ pub struct PipelineDependencyResponse {
     pub list: Vec<PipelineDependencyItem>,
 }
 
+/// Maximum number of records a dry run reads from the pipeline source.
+pub const DRY_RUN_MAX_SIZE: usize = 1000;
+
+/// Runs a pipeline over sample records, or over the records its source
+/// produced in a past time range, without writing anything.
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct PipelineDryRunRequest {
+    pub pipeline: Pipeline,
+    /// Sample records, the source is queried when empty
+    #[serde(default)]
+    pub records: Vec<json::Value>,
+    /// Start of the replayed time range in microseconds
+    #[serde(default)]
+    pub start_time: i64,
+    /// End of the replayed time range in microseconds
+    #[serde(default)]
+    pub end_time: i64,
+    /// Maximum number of records read from the source
+    #[serde(default = "default_dry_run_size")]
+    pub size: usize,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct PipelineDryRunResponse {
+    pub input_count: usize,
+    /// node_id -> the records which reached the node
+    pub nodes: HashMap<String, DryRunNodeResult>,
+    pub destinations: Vec<DryRunDestination>,
+    pub errors: Vec<DryRunError>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct DryRunNodeResult {
+    pub node_type: String,
+    pub count: usize,
+    pub records: Vec<DryRunRecord>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct DryRunRecord {
+    /// Index of the input record, None for records generated by a function
+    pub input_index: Option<usize>,
+    pub record: json::Value,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct DryRunDestination {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream: Option<StreamParams>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_destination: Option<String>,
+    pub count: usize,
+    /// Indexes of the input records which reached the destination
+    pub input_indexes: Vec<usize>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct DryRunError {
+    pub node_id: String,
+    pub node_type: String,
+    pub error: String,
+}
+
 /// DFS traversal to check:
 /// 1. all leaf nodes are of StreamNode
 /// 2. No `After Flattened` unchecked FunctionNode follows `After Flatten` checked FunctionNode in
@@ -429,6 +552,10 @@ fn default_status() -> bool {
     true
 }
 
+fn default_dry_run_size() -> usize {
+    100
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -496,6 +623,77 @@ mod tests {
         assert!(new_nodes.is_ok());
     }
 
+    #[test]
+    fn test_pipeline_dead_letter_stream() {
+        let payload = json::json!({
+            "name": "dead letter test",
+            "dead_letter_stream": "default",
+            "nodes": [
+              {
+                "id": "1",
+                "data": {
+                  "node_type": "stream",
+                  "org_id": "default",
+                  "stream_name": "default",
+                  "stream_type": "logs"
+                },
+                "position": {"x": 100, "y": 100},
+                "io_type": "input"
+              },
+              {
+                "id": "2",
+                "data": {
+                  "node_type": "stream",
+                  "org_id": "default",
+                  "stream_name": "parsed",
+                  "stream_type": "logs"
+                },
+                "position": {"x": 300, "y": 100},
+                "io_type": "output"
+              }
+            ],
+            "edges": [{"id": "e1-2", "source": "1", "target": "2"}]
+        });
+        let mut pl = json::from_value::<Pipeline>(payload).unwrap();
+        // the source stream can't be its own dead-letter stream
+        assert!(pl.validate().is_err());
+        pl.dead_letter_stream = Some("pipeline_errors".to_string());
+        assert!(pl.validate().is_ok());
+        pl.dead_letter_stream = Some(" ".to_string());
+        assert!(pl.validate().is_ok());
+        assert!(pl.dead_letter_stream.is_none());
+    }
+
+    #[test]
+    fn test_pipeline_dry_run_request() {
+        let payload = json::json!({
+            "pipeline": {
+                "name": "dry run test",
+                "nodes": [],
+                "edges": []
+            },
+            "records": [{"log": "a"}, {"log": "b"}]
+        });
+        let req = json::from_value::<PipelineDryRunRequest>(payload).unwrap();
+        assert_eq!(req.records.len(), 2);
+        assert_eq!(req.size, 100);
+        assert_eq!(req.start_time, 0);
+
+        let resp = PipelineDryRunResponse {
+            input_count: 2,
+            destinations: vec![DryRunDestination {
+                stream: Some(StreamParams::new("default", "out", StreamType::Logs)),
+                remote_destination: None,
+                count: 1,
+                input_indexes: vec![1],
+            }],
+            ..Default::default()
+        };
+        let value = json::to_value(&resp).unwrap();
+        assert_eq!(value["destinations"][0]["stream"]["stream_name"], "out");
+        assert!(value["destinations"][0].get("remote_destination").is_none());
+    }
+
     #[test]
     fn test_query_pipeline_serialization() {
         let payload = json::json!(
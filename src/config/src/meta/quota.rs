@@ -0,0 +1,183 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Data volume quotas of an org. Each quota has a soft limit, above which the
+//! usage is reported and notified, and a hard limit, above which ingestion or
+//! searches are rejected.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct QuotaLimit {
+    #[serde(default)]
+    pub soft: Option<u64>,
+    #[serde(default)]
+    pub hard: Option<u64>,
+}
+
+impl QuotaLimit {
+    pub fn status(&self, usage: u64) -> QuotaStatus {
+        if self.hard.is_some_and(|hard| usage >= hard) {
+            QuotaStatus::Exceeded
+        } else if self.soft.is_some_and(|soft| usage >= soft) {
+            QuotaStatus::Warning
+        } else {
+            QuotaStatus::Ok
+        }
+    }
+}
+
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaStatus {
+    #[default]
+    Ok,
+    /// Above the soft limit
+    Warning,
+    /// At or above the hard limit
+    Exceeded,
+}
+
+impl std::fmt::Display for QuotaStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuotaStatus::Ok => write!(f, "ok"),
+            QuotaStatus::Warning => write!(f, "warning"),
+            QuotaStatus::Exceeded => write!(f, "exceeded"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct OrgQuota {
+    /// Bytes ingested per UTC day
+    #[serde(default)]
+    pub ingested_bytes_per_day: QuotaLimit,
+    /// Compressed bytes in the storage
+    #[serde(default)]
+    pub stored_bytes: QuotaLimit,
+    /// Bytes scanned by searches per UTC day
+    #[serde(default)]
+    pub query_scanned_bytes_per_day: QuotaLimit,
+    /// Searches running at the same time on a querier
+    #[serde(default)]
+    pub concurrent_queries: QuotaLimit,
+    /// Alert destinations notified when a limit is reached
+    #[serde(default)]
+    pub alert_destinations: Vec<String>,
+}
+
+impl OrgQuota {
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, limit) in self.limits() {
+            if let (Some(soft), Some(hard)) = (limit.soft, limit.hard)
+                && soft > hard
+            {
+                return Err(format!(
+                    "the soft limit of {name} can't be above its hard limit"
+                ));
+            }
+        }
+        if self.alert_destinations.iter().any(|d| d.trim().is_empty()) {
+            return Err("alert destinations can't be empty".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn limits(&self) -> [(&'static str, &QuotaLimit); 4] {
+        [
+            ("ingested_bytes_per_day", &self.ingested_bytes_per_day),
+            ("stored_bytes", &self.stored_bytes),
+            (
+                "query_scanned_bytes_per_day",
+                &self.query_scanned_bytes_per_day,
+            ),
+            ("concurrent_queries", &self.concurrent_queries),
+        ]
+    }
+}
+
+/// Usage of an org in one UTC day.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyUsage {
+    #[serde(default)]
+    pub ingested_bytes: u64,
+    #[serde(default)]
+    pub query_scanned_bytes: u64,
+}
+
+impl DailyUsage {
+    pub fn add(&mut self, other: &DailyUsage) {
+        self.ingested_bytes += other.ingested_bytes;
+        self.query_scanned_bytes += other.query_scanned_bytes;
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, ToSchema)]
+pub struct QuotaUsage {
+    pub usage: u64,
+    pub limit: QuotaLimit,
+    pub status: QuotaStatus,
+}
+
+impl QuotaUsage {
+    pub fn new(usage: u64, limit: &QuotaLimit) -> Self {
+        Self {
+            usage,
+            limit: *limit,
+            status: limit.status(usage),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, ToSchema)]
+pub struct OrgQuotaUsage {
+    /// The UTC day of the daily usage, e.g. 20250131
+    pub day: u32,
+    pub ingested_bytes_per_day: QuotaUsage,
+    pub stored_bytes: QuotaUsage,
+    pub query_scanned_bytes_per_day: QuotaUsage,
+    pub concurrent_queries: QuotaUsage,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quota_limit_status() {
+        let limit = QuotaLimit {
+            soft: Some(80),
+            hard: Some(100),
+        };
+        assert_eq!(limit.status(79), QuotaStatus::Ok);
+        assert_eq!(limit.status(80), QuotaStatus::Warning);
+        assert_eq!(limit.status(100), QuotaStatus::Exceeded);
+        assert_eq!(QuotaLimit::default().status(u64::MAX), QuotaStatus::Ok);
+
+        let quota = OrgQuota {
+            stored_bytes: QuotaLimit {
+                soft: Some(2),
+                hard: Some(1),
+            },
+            ..Default::default()
+        };
+        assert!(quota.validate().is_err());
+        assert!(OrgQuota::default().validate().is_ok());
+    }
+}
@@ -0,0 +1,224 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The SCIM 2.0 resources (RFC 7643) the identity providers push users and
+//! groups with. Only the attributes OpenObserve uses are kept.
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::json::Value;
+
+pub const USER_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+pub const GROUP_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:Group";
+pub const LIST_RESPONSE_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:ListResponse";
+pub const PATCH_OP_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:PatchOp";
+pub const ERROR_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:Error";
+
+/// Content type of the SCIM requests and responses.
+pub const SCIM_CONTENT_TYPE: &str = "application/scim+json";
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimUser {
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    /// The email of the user
+    #[serde(default)]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    pub user_name: String,
+    #[serde(default)]
+    pub name: ScimName,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub emails: Vec<ScimEmail>,
+    #[serde(default = "default_true")]
+    pub active: bool,
+    /// Read only, the groups of the org the user is a member of
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<ScimMember>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<ScimMeta>,
+}
+
+impl ScimUser {
+    /// The email of the user, the primary email or the user name.
+    pub fn email(&self) -> String {
+        self.emails
+            .iter()
+            .find(|e| e.primary)
+            .or(self.emails.first())
+            .map(|e| e.value.as_str())
+            .filter(|_| !self.user_name.contains('@'))
+            .unwrap_or(&self.user_name)
+            .trim()
+            .to_lowercase()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimName {
+    #[serde(default)]
+    pub given_name: String,
+    #[serde(default)]
+    pub family_name: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScimEmail {
+    pub value: String,
+    #[serde(default)]
+    pub primary: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScimMember {
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimGroup {
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    #[serde(default)]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    pub display_name: String,
+    #[serde(default)]
+    pub members: Vec<ScimMember>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<ScimMeta>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimMeta {
+    pub resource_type: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimListResponse<T> {
+    pub schemas: Vec<String>,
+    pub total_results: usize,
+    pub start_index: usize,
+    pub items_per_page: usize,
+    #[serde(rename = "Resources")]
+    pub resources: Vec<T>,
+}
+
+impl<T> ScimListResponse<T> {
+    /// Pages the resources, `start_index` is 1-based like in SCIM.
+    pub fn new(resources: Vec<T>, start_index: usize, count: usize) -> Self {
+        let total_results = resources.len();
+        let start_index = start_index.max(1);
+        let resources: Vec<T> = resources
+            .into_iter()
+            .skip(start_index - 1)
+            .take(count)
+            .collect();
+        Self {
+            schemas: vec![LIST_RESPONSE_SCHEMA.to_string()],
+            total_results,
+            start_index,
+            items_per_page: resources.len(),
+            resources,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ScimPatchOp {
+    #[serde(rename = "Operations", alias = "operations")]
+    pub operations: Vec<ScimPatchOperation>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ScimPatchOperation {
+    /// `add`, `remove` or `replace`, some providers capitalize it
+    pub op: String,
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub value: Option<Value>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimErrorResponse {
+    pub schemas: Vec<String>,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scim_type: Option<String>,
+    pub detail: String,
+}
+
+impl ScimErrorResponse {
+    pub fn new(status: u16, scim_type: Option<&str>, detail: impl ToString) -> Self {
+        Self {
+            schemas: vec![ERROR_SCHEMA.to_string()],
+            status: status.to_string(),
+            scim_type: scim_type.map(String::from),
+            detail: detail.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::json;
+
+    #[test]
+    fn test_scim_user_email() {
+        let user: ScimUser = json::from_value(json::json!({
+            "schemas": [USER_SCHEMA],
+            "userName": "jdoe",
+            "emails": [
+                {"value": "john@example.com"},
+                {"value": "John.Doe@Example.com", "primary": true},
+            ],
+        }))
+        .unwrap();
+        assert!(user.active);
+        assert_eq!(user.email(), "john.doe@example.com");
+
+        let user = ScimUser {
+            user_name: "Jane@Example.com".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(user.email(), "jane@example.com");
+    }
+
+    #[test]
+    fn test_scim_list_response() {
+        let list = ScimListResponse::new(vec![1, 2, 3, 4, 5], 2, 2);
+        assert_eq!(list.total_results, 5);
+        assert_eq!(list.resources, vec![2, 3]);
+        assert_eq!(list.items_per_page, 2);
+        let list = ScimListResponse::new(vec![1, 2], 0, 10);
+        assert_eq!(list.start_index, 1);
+        assert_eq!(list.resources, vec![1, 2]);
+    }
+}
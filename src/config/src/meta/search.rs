@@ -30,6 +30,9 @@ use crate::{
 pub const PARTIAL_ERROR_RESPONSE_MESSAGE: &str =
     "Please be aware that the response is based on partial data";
 
+/// The field the hits of a multi-org search are tagged with.
+pub const ORG_FIELD: &str = "_org";
+
 /// To represent the query start and end time based of partition or cache
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct TimeOffset {
@@ -1062,6 +1065,21 @@ pub struct MultiSearchPartitionRequest {
     pub histogram_interval: i64,
 }
 
+/// Runs the same query in several orgs, the hits are tagged with [`ORG_FIELD`].
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct MultiOrgSearchRequest {
+    pub orgs: Vec<String>,
+    #[serde(flatten)]
+    pub request: Request,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct MultiOrgSearchPartitionRequest {
+    pub orgs: Vec<String>,
+    #[serde(flatten)]
+    pub request: SearchPartitionRequest,
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
 pub struct MultiSearchPartitionResponse {
     pub success: hashbrown::HashMap<String, SearchPartitionResponse>,
@@ -1309,6 +1327,27 @@ mod tests {
         req.decode().unwrap();
         assert_eq!(req.query.sql, "select * from test");
     }
+
+    #[test]
+    fn test_multi_org_request() {
+        let req = json::json!(
+            {
+                "orgs": ["tenant_a", "tenant_b"],
+                "query": {
+                    "sql": "c2VsZWN0ICogZnJvbSB0ZXN0",
+                    "from": 0,
+                    "size": 10,
+                    "start_time": 0,
+                    "end_time": 0
+                },
+                "encoding": "base64"
+            }
+        );
+        let mut req: MultiOrgSearchRequest = json::from_value(req).unwrap();
+        req.request.decode().unwrap();
+        assert_eq!(req.orgs, vec!["tenant_a", "tenant_b"]);
+        assert_eq!(req.request.query.sql, "select * from test");
+    }
 }
 
 mod search_history_utils {
@@ -0,0 +1,42 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use serde::{Deserialize, Serialize};
+
+pub const AUDIT_STREAM: &str = "audit";
+
+/// One audited API request.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct AuditData {
+    pub _timestamp: i64,
+    pub user_email: String,
+    pub org_id: String,
+    pub http_method: String,
+    /// Path after `/api/`
+    pub http_path: String,
+    pub http_query_params: String,
+    /// Kind of the resource, e.g. `streams`, `alerts`
+    pub resource: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub resource_id: String,
+    pub http_response_code: u16,
+    /// Request body with the secrets redacted, truncated to
+    /// `ZO_AUDIT_BODY_MAX_SIZE`
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub http_body: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+}
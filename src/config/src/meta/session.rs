@@ -0,0 +1,77 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Login sessions of the users. The token of a session is kept apart, this is
+//! what the users and the admins see of it. The id of a session is the
+//! credential in the login cookie, so it's only known by its handle here.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SessionInfo {
+    /// Opaque handle of the session, the sha256 of its id
+    pub handle: String,
+    pub user_email: String,
+    pub created_at: i64,
+    /// The session is logged out after this time, zero never expires
+    #[serde(default)]
+    pub expires_at: i64,
+    /// Last request made with the session, updated at most once per minute
+    pub last_seen_at: i64,
+    /// Address of the client which opened the session
+    #[serde(default)]
+    pub ip: String,
+    #[serde(default)]
+    pub user_agent: String,
+}
+
+impl SessionInfo {
+    /// Returns true when the session has been idle for more than
+    /// `idle_timeout` seconds, a zero timeout never expires.
+    pub fn is_idle(&self, idle_timeout: u64, now: i64) -> bool {
+        idle_timeout > 0 && now - self.last_seen_at > idle_timeout as i64 * 1_000_000
+    }
+
+    /// Returns true when the session is past its expiry time.
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at > 0 && now > self.expires_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_is_idle() {
+        let info = SessionInfo {
+            last_seen_at: 1_000_000,
+            ..Default::default()
+        };
+        assert!(!info.is_idle(0, 100_000_000));
+        assert!(!info.is_idle(60, 61_000_000));
+        assert!(info.is_idle(60, 61_000_001));
+    }
+
+    #[test]
+    fn test_session_is_expired() {
+        let mut info = SessionInfo::default();
+        assert!(!info.is_expired(100));
+        info.expires_at = 100;
+        assert!(!info.is_expired(100));
+        assert!(info.is_expired(101));
+    }
+}
@@ -204,7 +204,7 @@ impl FileKey {
     }
 }
 
-#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, ToSchema)]
 pub struct FileMeta {
     pub min_ts: i64, // microseconds
     pub max_ts: i64, // microseconds
@@ -348,6 +348,15 @@ impl StreamStats {
         }
     }
 
+    /// Returns the ratio of original size to compressed size, 0 if nothing is
+    /// stored yet.
+    pub fn compression_ratio(&self) -> f64 {
+        if self.compressed_size <= 0.0 {
+            return 0.0;
+        }
+        self.storage_size / self.compressed_size
+    }
+
     pub fn format_by(&mut self, stats: &StreamStats) {
         self.file_num = stats.file_num;
         self.doc_num = stats.doc_num;
@@ -584,6 +593,12 @@ pub struct UpdateStreamSettings {
     pub index_original_data: Option<bool>,
     #[serde(default)]
     pub index_all_values: Option<bool>,
+    #[serde(default)]
+    pub parquet_settings: Option<ParquetSettings>,
+    #[serde(default)]
+    pub tail_sampling: Option<TailSamplingSettings>,
+    #[serde(default)]
+    pub masking: Option<MaskingSettings>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
@@ -715,6 +730,12 @@ pub struct StreamSettings {
     pub index_original_data: bool,
     #[serde(default)]
     pub index_all_values: bool,
+    #[serde(default)]
+    pub parquet_settings: Option<ParquetSettings>,
+    #[serde(default)]
+    pub tail_sampling: Option<TailSamplingSettings>,
+    #[serde(default)]
+    pub masking: Option<MaskingSettings>,
 }
 
 impl Serialize for StreamSettings {
@@ -768,6 +789,30 @@ impl Serialize for StreamSettings {
                 state.skip_field("flatten_level")?;
             }
         }
+        match self.parquet_settings.as_ref() {
+            Some(parquet_settings) if !parquet_settings.is_empty() => {
+                state.serialize_field("parquet_settings", parquet_settings)?;
+            }
+            _ => {
+                state.skip_field("parquet_settings")?;
+            }
+        }
+        match self.tail_sampling.as_ref() {
+            Some(tail_sampling) => {
+                state.serialize_field("tail_sampling", tail_sampling)?;
+            }
+            None => {
+                state.skip_field("tail_sampling")?;
+            }
+        }
+        match self.masking.as_ref() {
+            Some(masking) if !masking.rules.is_empty() => {
+                state.serialize_field("masking", masking)?;
+            }
+            _ => {
+                state.skip_field("masking")?;
+            }
+        }
         state.end()
     }
 }
@@ -907,6 +952,20 @@ impl From<&str> for StreamSettings {
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
 
+        let parquet_settings = settings
+            .get("parquet_settings")
+            .and_then(|v| json::from_value::<ParquetSettings>(v.clone()).ok())
+            .filter(|v| !v.is_empty());
+
+        let tail_sampling = settings
+            .get("tail_sampling")
+            .and_then(|v| json::from_value::<TailSamplingSettings>(v.clone()).ok());
+
+        let masking = settings
+            .get("masking")
+            .and_then(|v| json::from_value::<MaskingSettings>(v.clone()).ok())
+            .filter(|v| !v.rules.is_empty());
+
         Self {
             partition_time_level,
             partition_keys,
@@ -924,7 +983,253 @@ impl From<&str> for StreamSettings {
             extended_retention_days,
             index_original_data,
             index_all_values,
+            parquet_settings,
+            tail_sampling,
+            masking,
+        }
+    }
+}
+
+/// Parquet writer options of a stream, applied when the ingester persists and
+/// uploads files and when the compactor merges them. Unset values fall back to
+/// the global configuration.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ParquetSettings {
+    /// Compression codec: none, snappy, gzip, brotli, lz4, zstd
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<String>,
+    /// Compression level, only used by gzip (0-9), brotli (0-11) and zstd (1-22)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression_level: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dictionary_enabled: Option<bool>,
+    /// Data page size limit in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_page_size: Option<usize>,
+    /// Maximum number of rows in a row group
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_row_group_size: Option<usize>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[schema(value_type = Object)]
+    pub columns: HashMap<String, ParquetColumnSettings>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ParquetColumnSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression_level: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dictionary_enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bloom_filter_enabled: Option<bool>,
+}
+
+impl ParquetSettings {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        validate_parquet_compression(
+            self.compression.as_deref(),
+            self.compression_level,
+            "stream",
+        )?;
+        if self.data_page_size == Some(0) {
+            return Err("data_page_size must be greater than 0".to_string());
+        }
+        if let Some(size) = self.max_row_group_size
+            && (size < crate::PARQUET_BATCH_SIZE || size > crate::PARQUET_MAX_ROW_GROUP_SIZE)
+        {
+            return Err(format!(
+                "max_row_group_size must be between {} and {}",
+                crate::PARQUET_BATCH_SIZE,
+                crate::PARQUET_MAX_ROW_GROUP_SIZE
+            ));
+        }
+        for (name, column) in self.columns.iter() {
+            if name.is_empty() {
+                return Err("column name can't be empty".to_string());
+            }
+            validate_parquet_compression(
+                column.compression.as_deref(),
+                column.compression_level,
+                name,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn validate_parquet_compression(
+    compression: Option<&str>,
+    level: Option<i32>,
+    target: &str,
+) -> Result<(), String> {
+    let codec = match compression {
+        Some(v) => v.to_lowercase(),
+        None => get_config().common.parquet_compression.to_lowercase(),
+    };
+    let range = match codec.as_str() {
+        "none" | "uncompressed" | "snappy" | "lz4" | "lz4_raw" => None,
+        "gzip" => Some(0..=9),
+        "brotli" => Some(0..=11),
+        "zstd" => Some(1..=22),
+        _ => {
+            return Err(format!(
+                "[{target}] unsupported parquet compression: {codec}"
+            ));
+        }
+    };
+    match (level, range) {
+        (Some(level), Some(range)) if !range.contains(&level) => Err(format!(
+            "[{target}] compression level {level} out of range for {codec}: {}-{}",
+            range.start(),
+            range.end()
+        )),
+        (Some(_), None) => Err(format!(
+            "[{target}] compression {codec} doesn't support compression level"
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Tail sampling of a traces stream. The spans are buffered per trace for the
+/// decision wait, then the whole trace is kept or dropped: traces with an error
+/// span and slow traces are always kept, the rest is sampled by percentage and
+/// limited per root service.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TailSamplingSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Seconds to wait for the spans of a trace before the decision
+    #[serde(default = "default_tail_sampling_decision_wait")]
+    pub decision_wait: u64,
+    /// Keep the traces which have an error span
+    #[serde(default = "default_tail_sampling_keep_errors")]
+    pub keep_errors: bool,
+    /// Keep the traces which are slower than this, milliseconds, 0 disables it
+    #[serde(default)]
+    pub latency_threshold_ms: u64,
+    /// Percentage of the other traces to keep, 0-100
+    #[serde(default)]
+    pub sample_percent: f64,
+    /// Max traces per second kept by the percentage for each root service, 0
+    /// is unlimited
+    #[serde(default)]
+    pub max_traces_per_service: u64,
+    /// Overrides `max_traces_per_service` for the root services
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[schema(value_type = Object)]
+    pub service_rate_limits: HashMap<String, u64>,
+}
+
+fn default_tail_sampling_decision_wait() -> u64 {
+    10
+}
+
+fn default_tail_sampling_keep_errors() -> bool {
+    true
+}
+
+impl Default for TailSamplingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            decision_wait: default_tail_sampling_decision_wait(),
+            keep_errors: default_tail_sampling_keep_errors(),
+            latency_threshold_ms: 0,
+            sample_percent: 0.0,
+            max_traces_per_service: 0,
+            service_rate_limits: HashMap::new(),
+        }
+    }
+}
+
+impl TailSamplingSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.decision_wait == 0 || self.decision_wait > 300 {
+            return Err("decision_wait must be between 1 and 300 seconds".to_string());
+        }
+        if !(0.0..=100.0).contains(&self.sample_percent) {
+            return Err("sample_percent must be between 0 and 100".to_string());
+        }
+        if self.service_rate_limits.keys().any(|name| name.is_empty()) {
+            return Err("service name of service_rate_limits can't be empty".to_string());
+        }
+        Ok(())
+    }
+
+    /// Max traces per second kept by the percentage for the root service
+    pub fn service_rate_limit(&self, service_name: &str) -> u64 {
+        self.service_rate_limits
+            .get(service_name)
+            .copied()
+            .unwrap_or(self.max_traces_per_service)
+    }
+}
+
+/// Masking of the query results of a stream. Each rule applies to the users
+/// whose role it doesn't exempt, the alert notifications are always masked.
+/// The stored data is untouched so the exempted roles see the raw values.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct MaskingSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub rules: Vec<MaskingRule>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct MaskingRule {
+    /// Fields the rule applies to, an empty list applies the pattern to all
+    /// the string fields
+    #[serde(default)]
+    pub fields: Vec<String>,
+    /// Regex of the masked parts of the values, the whole value when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    pub action: MaskingAction,
+    /// Trailing characters left visible by the partial mask
+    #[serde(default)]
+    pub keep_last: usize,
+    /// Roles which see the raw values
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exempt_roles: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MaskingAction {
+    /// Replaces the value with its sha256 hex digest
+    Hash,
+    /// Replaces the characters with `*` except the last `keep_last`
+    Partial,
+    /// Removes the field, or the matched parts of the value with a pattern
+    Drop,
+}
+
+impl MaskingSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        for rule in self.rules.iter() {
+            match rule.pattern.as_ref() {
+                Some(pattern) => {
+                    if let Err(e) = regex::Regex::new(pattern) {
+                        return Err(format!("invalid pattern {pattern}: {e}"));
+                    }
+                }
+                None if rule.fields.is_empty() => {
+                    return Err("a masking rule needs fields or a pattern".to_string());
+                }
+                None => {}
+            }
+            if rule.fields.iter().any(|f| f.is_empty()) {
+                return Err("field name of a masking rule can't be empty".to_string());
+            }
         }
+        Ok(())
     }
 }
 
@@ -1333,6 +1638,96 @@ mod tests {
         assert_eq!(map.len(), 2);
     }
 
+    #[test]
+    fn test_parquet_settings() {
+        let mut settings = ParquetSettings {
+            compression: Some("zstd".to_string()),
+            compression_level: Some(3),
+            ..Default::default()
+        };
+        settings.columns.insert(
+            "body".to_string(),
+            ParquetColumnSettings {
+                compression: Some("gzip".to_string()),
+                compression_level: Some(9),
+                ..Default::default()
+            },
+        );
+        assert!(settings.validate().is_ok());
+
+        let stream_settings = StreamSettings {
+            parquet_settings: Some(settings.clone()),
+            ..Default::default()
+        };
+        let data = json::to_string(&stream_settings).unwrap();
+        let parsed = StreamSettings::from(data.as_str());
+        assert_eq!(parsed.parquet_settings, Some(settings.clone()));
+
+        settings.compression_level = Some(30);
+        assert!(settings.validate().is_err());
+        settings.compression_level = None;
+        settings.columns.get_mut("body").unwrap().compression = Some("snappy".to_string());
+        assert!(settings.validate().is_err());
+        settings.columns.get_mut("body").unwrap().compression = Some("foo".to_string());
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn test_masking_settings() {
+        let data = r#"{"masking":{"enabled":true,"rules":[{"fields":["email"],"action":"hash","exempt_roles":["admin"]}]}}"#;
+        let settings = StreamSettings::from(data).masking.unwrap();
+        assert!(settings.enabled);
+        assert_eq!(settings.rules[0].action, MaskingAction::Hash);
+        assert_eq!(settings.rules[0].exempt_roles, vec!["admin".to_string()]);
+        assert!(settings.validate().is_ok());
+
+        let stream_settings = StreamSettings {
+            masking: Some(settings.clone()),
+            ..Default::default()
+        };
+        let data = json::to_string(&stream_settings).unwrap();
+        assert_eq!(
+            StreamSettings::from(data.as_str()).masking,
+            Some(settings.clone())
+        );
+
+        let mut invalid = settings.clone();
+        invalid.rules[0].fields.clear();
+        assert!(invalid.validate().is_err());
+        invalid.rules[0].pattern = Some("[0-9".to_string());
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_tail_sampling_settings() {
+        let data = r#"{"tail_sampling":{"enabled":true,"sample_percent":10,"service_rate_limits":{"cart":5}}}"#;
+        let settings = StreamSettings::from(data).tail_sampling.unwrap();
+        assert!(settings.enabled);
+        assert_eq!(settings.decision_wait, 10);
+        assert!(settings.keep_errors);
+        assert_eq!(settings.sample_percent, 10.0);
+        assert_eq!(settings.service_rate_limit("cart"), 5);
+        assert_eq!(settings.service_rate_limit("frontend"), 0);
+        assert!(settings.validate().is_ok());
+
+        let stream_settings = StreamSettings {
+            tail_sampling: Some(settings.clone()),
+            ..Default::default()
+        };
+        let data = json::to_string(&stream_settings).unwrap();
+        assert_eq!(
+            StreamSettings::from(data.as_str()).tail_sampling,
+            Some(settings.clone())
+        );
+
+        let mut invalid = settings.clone();
+        invalid.sample_percent = 101.0;
+        assert!(invalid.validate().is_err());
+        let mut invalid = settings;
+        invalid.decision_wait = 0;
+        assert!(invalid.validate().is_err());
+    }
+
     #[test]
     fn test_split_ranges() {
         // contains
@@ -142,6 +142,30 @@ pub static INGEST_ERRORS: Lazy<IntCounterVec> = Lazy::new(|| {
     )
     .expect("Metric created")
 });
+pub static INGEST_THROTTLED_REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        Opts::new(
+            "ingest_throttled_requests",
+            "Ingestion requests rejected by flow control".to_owned() + HELP_SUFFIX,
+        )
+        .namespace(NAMESPACE)
+        .const_labels(create_const_labels()),
+        &["organization", "protocol", "reason"],
+    )
+    .expect("Metric created")
+});
+pub static INGEST_TAIL_SAMPLING_TRACES: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        Opts::new(
+            "ingest_tail_sampling_traces",
+            "Traces decided by tail sampling".to_owned() + HELP_SUFFIX,
+        )
+        .namespace(NAMESPACE)
+        .const_labels(create_const_labels()),
+        &["organization", "stream", "decision"],
+    )
+    .expect("Metric created")
+});
 pub static INGEST_WAL_USED_BYTES: Lazy<IntGaugeVec> = Lazy::new(|| {
     IntGaugeVec::new(
         Opts::new(
@@ -178,6 +202,30 @@ pub static INGEST_WAL_READ_BYTES: Lazy<IntCounterVec> = Lazy::new(|| {
     )
     .expect("Metric created")
 });
+pub static INGEST_WAL_REPLICATION_BYTES: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        Opts::new(
+            "ingest_wal_replication_bytes",
+            "Ingestor WAL bytes replicated to peers.".to_owned() + HELP_SUFFIX,
+        )
+        .namespace(NAMESPACE)
+        .const_labels(create_const_labels()),
+        &["organization", "stream_type"],
+    )
+    .expect("Metric created")
+});
+pub static INGEST_WAL_REPLICATION_ERRORS: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        Opts::new(
+            "ingest_wal_replication_errors",
+            "Ingestor WAL replication errors.".to_owned() + HELP_SUFFIX,
+        )
+        .namespace(NAMESPACE)
+        .const_labels(create_const_labels()),
+        &["organization", "stream_type"],
+    )
+    .expect("Metric created")
+});
 pub static INGEST_MEMTABLE_BYTES: Lazy<IntGaugeVec> = Lazy::new(|| {
     IntGaugeVec::new(
         Opts::new(
@@ -214,6 +262,18 @@ pub static INGEST_MEMTABLE_FILES: Lazy<IntGaugeVec> = Lazy::new(|| {
     )
     .expect("Metric created")
 });
+pub static ENRICHMENT_TABLE_INDEX_BYTES: Lazy<IntGaugeVec> = Lazy::new(|| {
+    IntGaugeVec::new(
+        Opts::new(
+            "enrichment_table_index_bytes",
+            "Enrichment table lookup index in memory bytes.".to_owned(),
+        )
+        .namespace(NAMESPACE)
+        .const_labels(create_const_labels()),
+        &["organization", "table"],
+    )
+    .expect("Metric created")
+});
 
 pub static INGEST_MEMTABLE_LOCK_TIME: Lazy<HistogramVec> = Lazy::new(|| {
     HistogramVec::new(
@@ -441,6 +501,30 @@ pub static COMPACT_PENDING_JOBS: Lazy<IntGaugeVec> = Lazy::new(|| {
     )
     .expect("Metric created")
 });
+pub static COMPACT_VERIFY_DISCREPANCIES: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        Opts::new(
+            "compact_verify_discrepancies",
+            "Compactor file_list verification discrepancies.".to_owned() + HELP_SUFFIX,
+        )
+        .namespace(NAMESPACE)
+        .const_labels(create_const_labels()),
+        &["organization", "stream_type", "kind"],
+    )
+    .expect("Metric created")
+});
+pub static COMPACT_VERIFY_REPAIRED: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        Opts::new(
+            "compact_verify_repaired",
+            "Compactor file_list verification repaired files.".to_owned() + HELP_SUFFIX,
+        )
+        .namespace(NAMESPACE)
+        .const_labels(create_const_labels()),
+        &["organization", "stream_type", "kind"],
+    )
+    .expect("Metric created")
+});
 // TODO deletion / archiving stats
 
 // storage stats
@@ -907,6 +991,19 @@ pub static PIPELINE_WAL_FILES: Lazy<IntGaugeVec> = Lazy::new(|| {
     .expect("Metric created")
 });
 
+pub static PIPELINE_NODE_RECORDS: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        Opts::new(
+            "pipeline_node_records",
+            "Pipeline node records by status: processed, error or dropped",
+        )
+        .namespace(NAMESPACE)
+        .const_labels(create_const_labels()),
+        &["organization", "pipeline", "node_id", "node_type", "status"],
+    )
+    .expect("Metric created")
+});
+
 pub static QUERY_AGGREGATION_CACHE_ITEMS: Lazy<IntGaugeVec> = Lazy::new(|| {
     IntGaugeVec::new(
         Opts::new(
@@ -960,6 +1057,12 @@ fn register_metrics(registry: &Registry) {
     registry
         .register(Box::new(INGEST_ERRORS.clone()))
         .expect("Metric registered");
+    registry
+        .register(Box::new(INGEST_THROTTLED_REQUESTS.clone()))
+        .expect("Metric registered");
+    registry
+        .register(Box::new(INGEST_TAIL_SAMPLING_TRACES.clone()))
+        .expect("Metric registered");
     registry
         .register(Box::new(INGEST_WAL_USED_BYTES.clone()))
         .expect("Metric registered");
@@ -969,6 +1072,12 @@ fn register_metrics(registry: &Registry) {
     registry
         .register(Box::new(INGEST_WAL_READ_BYTES.clone()))
         .expect("Metric registered");
+    registry
+        .register(Box::new(INGEST_WAL_REPLICATION_BYTES.clone()))
+        .expect("Metric registered");
+    registry
+        .register(Box::new(INGEST_WAL_REPLICATION_ERRORS.clone()))
+        .expect("Metric registered");
     registry
         .register(Box::new(INGEST_MEMTABLE_BYTES.clone()))
         .expect("Metric registered");
@@ -978,6 +1087,9 @@ fn register_metrics(registry: &Registry) {
     registry
         .register(Box::new(INGEST_MEMTABLE_FILES.clone()))
         .expect("Metric registered");
+    registry
+        .register(Box::new(ENRICHMENT_TABLE_INDEX_BYTES.clone()))
+        .expect("Metric registered");
     registry
         .register(Box::new(INGEST_MEMTABLE_LOCK_TIME.clone()))
         .expect("Metric registered");
@@ -1047,6 +1159,12 @@ fn register_metrics(registry: &Registry) {
     registry
         .register(Box::new(COMPACT_PENDING_JOBS.clone()))
         .expect("Metric registered");
+    registry
+        .register(Box::new(COMPACT_VERIFY_DISCREPANCIES.clone()))
+        .expect("Metric registered");
+    registry
+        .register(Box::new(COMPACT_VERIFY_REPAIRED.clone()))
+        .expect("Metric registered");
 
     // storage stats
     registry
@@ -1173,6 +1291,9 @@ fn register_metrics(registry: &Registry) {
     registry
         .register(Box::new(PIPELINE_WAL_FILES.clone()))
         .expect("Metric registered");
+    registry
+        .register(Box::new(PIPELINE_NODE_RECORDS.clone()))
+        .expect("Metric registered");
     registry
         .register(Box::new(QUERY_AGGREGATION_CACHE_ITEMS.clone()))
         .expect("Metric registered");
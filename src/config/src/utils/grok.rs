@@ -0,0 +1,260 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A small grok implementation which expands `%{PATTERN:field:type}` into a
+//! regular expression with named capture groups.
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::utils::json::{Map, Number, Value};
+
+/// Maximum depth of nested pattern references.
+const MAX_EXPAND_DEPTH: usize = 16;
+
+static GROK_REFERENCE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"%\{(?P<name>[A-Z0-9_]+)(?::(?P<field>[A-Za-z0-9_@.\-\[\]]+))?(?::(?P<type>int|float|string))?\}").unwrap()
+});
+
+static BUILTIN_PATTERNS: Lazy<HashMap<&'static str, &'static str>> = Lazy::new(|| {
+    HashMap::from([
+        ("USERNAME", r"[a-zA-Z0-9._-]+"),
+        ("USER", r"%{USERNAME}"),
+        ("INT", r"(?:[+-]?(?:[0-9]+))"),
+        ("BASE10NUM", r"(?:[+-]?(?:[0-9]+(?:\.[0-9]+)?|\.[0-9]+))"),
+        ("NUMBER", r"(?:%{BASE10NUM})"),
+        ("POSINT", r"\b(?:[1-9][0-9]*)\b"),
+        ("NONNEGINT", r"\b(?:[0-9]+)\b"),
+        ("WORD", r"\b\w+\b"),
+        ("NOTSPACE", r"\S+"),
+        ("SPACE", r"\s*"),
+        ("DATA", r".*?"),
+        ("GREEDYDATA", r".*"),
+        ("QUOTEDSTRING", r#"(?:"(?:\\.|[^\\"])*"|'(?:\\.|[^\\'])*')"#),
+        (
+            "UUID",
+            r"[A-Fa-f0-9]{8}-(?:[A-Fa-f0-9]{4}-){3}[A-Fa-f0-9]{12}",
+        ),
+        (
+            "IPV4",
+            r"(?:(?:25[0-5]|2[0-4][0-9]|[01]?[0-9][0-9]?)\.){3}(?:25[0-5]|2[0-4][0-9]|[01]?[0-9][0-9]?)",
+        ),
+        (
+            "IPV6",
+            r"(?:[A-Fa-f0-9]{0,4}:){2,7}(?:[A-Fa-f0-9]{0,4}|%{IPV4})",
+        ),
+        ("IP", r"(?:%{IPV6}|%{IPV4})"),
+        (
+            "HOSTNAME",
+            r"\b(?:[0-9A-Za-z][0-9A-Za-z-]{0,62})(?:\.(?:[0-9A-Za-z][0-9A-Za-z-]{0,62}))*(?:\.?|\b)",
+        ),
+        ("IPORHOST", r"(?:%{IP}|%{HOSTNAME})"),
+        ("HOSTPORT", r"%{IPORHOST}:%{POSINT}"),
+        ("PATH", r"(?:/[^\s?#]*)+"),
+        ("URIPATH", r"(?:/[A-Za-z0-9$.+!*'(){},~:;=@#%&_\-]*)+"),
+        ("URIPARAM", r"\?[A-Za-z0-9$.+!*'|(){},~@#%&/=:;_?\-\[\]<>]*"),
+        ("URIPATHPARAM", r"%{URIPATH}(?:%{URIPARAM})?"),
+        ("URI", r"[A-Za-z][A-Za-z0-9+\-.]*://\S+"),
+        (
+            "MONTH",
+            r"\b(?:[Jj]an(?:uary)?|[Ff]eb(?:ruary)?|[Mm]ar(?:ch)?|[Aa]pr(?:il)?|[Mm]ay|[Jj]un(?:e)?|[Jj]ul(?:y)?|[Aa]ug(?:ust)?|[Ss]ep(?:tember)?|[Oo]ct(?:ober)?|[Nn]ov(?:ember)?|[Dd]ec(?:ember)?)\b",
+        ),
+        ("MONTHNUM", r"(?:0?[1-9]|1[0-2])"),
+        ("MONTHDAY", r"(?:(?:0[1-9])|(?:[12][0-9])|(?:3[01])|[1-9])"),
+        ("YEAR", r"(?:\d\d){1,2}"),
+        ("HOUR", r"(?:2[0123]|[01]?[0-9])"),
+        ("MINUTE", r"(?:[0-5][0-9])"),
+        ("SECOND", r"(?:(?:[0-5]?[0-9]|60)(?:[:.,][0-9]+)?)"),
+        ("TIME", r"%{HOUR}:%{MINUTE}(?::%{SECOND})?"),
+        ("ISO8601_TIMEZONE", r"(?:Z|[+-]%{HOUR}(?::?%{MINUTE}))"),
+        (
+            "TIMESTAMP_ISO8601",
+            r"%{YEAR}-%{MONTHNUM}-%{MONTHDAY}[T ]%{HOUR}:?%{MINUTE}(?::?%{SECOND})?%{ISO8601_TIMEZONE}?",
+        ),
+        (
+            "HTTPDATE",
+            r"%{MONTHDAY}/%{MONTH}/%{YEAR}:%{TIME} [+-]?\d{4}",
+        ),
+        ("SYSLOGTIMESTAMP", r"%{MONTH} +%{MONTHDAY} %{TIME}"),
+        (
+            "LOGLEVEL",
+            r"(?:[Aa]lert|ALERT|[Tt]race|TRACE|[Dd]ebug|DEBUG|[Nn]otice|NOTICE|[Ii]nfo|INFO|[Ww]arn(?:ing)?|WARN(?:ING)?|[Ee]rr(?:or)?|ERR(?:OR)?|[Cc]rit(?:ical)?|CRIT(?:ICAL)?|[Ff]atal|FATAL|[Ss]evere|SEVERE|[Ee]merg(?:ency)?|EMERG(?:ENCY)?)",
+        ),
+    ])
+});
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Conversion {
+    Int,
+    Float,
+}
+
+/// A compiled grok or plain regex pattern. Every named capture group becomes a
+/// field of the parsed output.
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    regex: Regex,
+    /// capture group name -> output field name
+    fields: Vec<(String, String)>,
+    conversions: HashMap<String, Conversion>,
+}
+
+impl Pattern {
+    /// Compiles a grok expression, e.g. `%{IP:client} %{WORD:method}`.
+    pub fn grok(pattern: &str) -> Result<Self, String> {
+        let mut fields = Vec::new();
+        let mut conversions = HashMap::new();
+        let expanded = expand(pattern, 0, &mut fields, &mut conversions)?;
+        let regex = Regex::new(&format!("^{expanded}$"))
+            .map_err(|e| format!("invalid grok pattern: {e}"))?;
+        Ok(Self {
+            regex,
+            fields,
+            conversions,
+        })
+    }
+
+    /// Compiles a regular expression, named capture groups are the fields.
+    pub fn regex(pattern: &str) -> Result<Self, String> {
+        let regex = Regex::new(pattern).map_err(|e| format!("invalid regex pattern: {e}"))?;
+        let fields: Vec<_> = regex
+            .capture_names()
+            .flatten()
+            .map(|name| (name.to_string(), name.to_string()))
+            .collect();
+        if fields.is_empty() {
+            return Err("regex pattern must have at least one named capture group".to_string());
+        }
+        Ok(Self {
+            regex,
+            fields,
+            conversions: HashMap::new(),
+        })
+    }
+
+    /// Returns the captured fields, or None if the text doesn't match.
+    pub fn parse(&self, text: &str) -> Option<Map<String, Value>> {
+        let caps = self.regex.captures(text)?;
+        let mut out = Map::with_capacity(self.fields.len());
+        for (group, field) in self.fields.iter() {
+            let Some(m) = caps.name(group) else {
+                continue;
+            };
+            let value = match self.conversions.get(group) {
+                Some(Conversion::Int) => match m.as_str().parse::<i64>() {
+                    Ok(v) => Value::Number(v.into()),
+                    Err(_) => Value::String(m.as_str().to_string()),
+                },
+                Some(Conversion::Float) => {
+                    match m.as_str().parse::<f64>().ok().and_then(Number::from_f64) {
+                        Some(v) => Value::Number(v),
+                        None => Value::String(m.as_str().to_string()),
+                    }
+                }
+                None => Value::String(m.as_str().to_string()),
+            };
+            out.insert(field.to_string(), value);
+        }
+        Some(out)
+    }
+}
+
+fn expand(
+    pattern: &str,
+    depth: usize,
+    fields: &mut Vec<(String, String)>,
+    conversions: &mut HashMap<String, Conversion>,
+) -> Result<String, String> {
+    if depth > MAX_EXPAND_DEPTH {
+        return Err("grok pattern is nested too deep".to_string());
+    }
+    let mut out = String::with_capacity(pattern.len());
+    let mut last = 0;
+    for caps in GROK_REFERENCE.captures_iter(pattern) {
+        let whole = caps.get(0).unwrap();
+        out.push_str(&pattern[last..whole.start()]);
+        last = whole.end();
+
+        let name = &caps["name"];
+        let Some(definition) = BUILTIN_PATTERNS.get(name) else {
+            return Err(format!("unknown grok pattern: {name}"));
+        };
+        // only the outermost references produce fields
+        let inner = expand(definition, depth + 1, &mut Vec::new(), &mut HashMap::new())?;
+        match caps.name("field") {
+            Some(field) if depth == 0 => {
+                let group = format!("f{}", fields.len());
+                match caps.name("type").map(|t| t.as_str()) {
+                    Some("int") => {
+                        conversions.insert(group.clone(), Conversion::Int);
+                    }
+                    Some("float") => {
+                        conversions.insert(group.clone(), Conversion::Float);
+                    }
+                    _ => {}
+                }
+                out.push_str(&format!("(?P<{group}>{inner})"));
+                fields.push((group, field.as_str().to_string()));
+            }
+            _ => out.push_str(&format!("(?:{inner})")),
+        }
+    }
+    out.push_str(&pattern[last..]);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::json;
+
+    #[test]
+    fn test_grok_parse() {
+        let pattern =
+            Pattern::grok(r#"%{IP:client} \[%{HTTPDATE:ts}\] "%{WORD:method} %{URIPATHPARAM:path}" %{INT:status:int} %{NUMBER:took:float}"#)
+                .unwrap();
+        let parsed = pattern
+            .parse(r#"10.1.2.3 [10/Oct/2024:13:55:36 -0700] "GET /api/v1?x=1" 200 0.25"#)
+            .unwrap();
+        assert_eq!(
+            Value::Object(parsed),
+            json::json!({
+                "client": "10.1.2.3",
+                "ts": "10/Oct/2024:13:55:36 -0700",
+                "method": "GET",
+                "path": "/api/v1?x=1",
+                "status": 200,
+                "took": 0.25,
+            })
+        );
+        assert!(pattern.parse("not an access log").is_none());
+    }
+
+    #[test]
+    fn test_grok_errors() {
+        assert!(Pattern::grok("%{NOPE:x}").is_err());
+        assert!(Pattern::grok("%{LOGLEVEL:level} %{GREEDYDATA:message}").is_ok());
+    }
+
+    #[test]
+    fn test_regex_parse() {
+        assert!(Pattern::regex(r"\d+").is_err());
+        let pattern = Pattern::regex(r"user=(?P<user>\w+)").unwrap();
+        let parsed = pattern.parse("login user=alice ok").unwrap();
+        assert_eq!(parsed.get("user").unwrap(), "alice");
+    }
+}
@@ -19,6 +19,7 @@ pub mod base64;
 pub mod download_utils;
 pub mod file;
 pub mod flatten;
+pub mod grok;
 pub mod hash;
 pub mod inverted_index;
 pub mod json;
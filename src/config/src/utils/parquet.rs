@@ -32,7 +32,11 @@ use parquet::{
     file::{metadata::KeyValue, properties::WriterProperties},
 };
 
-use crate::{config::*, ider, meta::stream::FileMeta};
+use crate::{
+    config::*,
+    ider,
+    meta::stream::{FileMeta, ParquetSettings},
+};
 
 pub fn new_parquet_writer<'a>(
     buf: &'a mut Vec<u8>,
@@ -41,13 +45,51 @@ pub fn new_parquet_writer<'a>(
     metadata: &'a FileMeta,
     write_metadata: bool,
     compression: Option<&str>,
+    parquet_settings: Option<&ParquetSettings>,
 ) -> AsyncArrowWriter<&'a mut Vec<u8>> {
+    let writer_props = new_parquet_writer_properties(
+        bloom_filter_fields,
+        metadata,
+        write_metadata,
+        compression,
+        parquet_settings,
+    );
+    AsyncArrowWriter::try_new(buf, schema.clone(), Some(writer_props)).unwrap()
+}
+
+pub fn new_parquet_writer_properties(
+    bloom_filter_fields: &[String],
+    metadata: &FileMeta,
+    write_metadata: bool,
+    compression: Option<&str>,
+    parquet_settings: Option<&ParquetSettings>,
+) -> WriterProperties {
     let cfg = get_config();
-    let compression = compression.unwrap_or(&cfg.common.parquet_compression);
+    let default_settings = ParquetSettings::default();
+    let settings = parquet_settings.unwrap_or(&default_settings);
+    // the explicit compression has the highest priority, it is used by the ingester to write
+    // uncompressed wal parquet files
+    let compression_forced = compression.is_some();
+    let (compression, compression_level) = match compression {
+        Some(v) => (v, None),
+        None => (
+            settings
+                .compression
+                .as_deref()
+                .unwrap_or(&cfg.common.parquet_compression),
+            settings.compression_level,
+        ),
+    };
+    let max_row_group_size = settings
+        .max_row_group_size
+        .unwrap_or(PARQUET_MAX_ROW_GROUP_SIZE);
     let mut writer_props = WriterProperties::builder()
         .set_write_batch_size(PARQUET_BATCH_SIZE) // in bytes
-        .set_max_row_group_size(PARQUET_MAX_ROW_GROUP_SIZE) // maximum number of rows in a row group
-        .set_compression(get_parquet_compression(compression))
+        .set_max_row_group_size(max_row_group_size) // maximum number of rows in a row group
+        .set_compression(get_parquet_compression_with_level(
+            compression,
+            compression_level,
+        ))
         .set_column_dictionary_enabled(
             TIMESTAMP_COL_NAME.into(),
             false,
@@ -56,6 +98,12 @@ pub fn new_parquet_writer<'a>(
             TIMESTAMP_COL_NAME.into(),
             Encoding::DELTA_BINARY_PACKED,
         );
+    if let Some(dictionary_enabled) = settings.dictionary_enabled {
+        writer_props = writer_props.set_dictionary_enabled(dictionary_enabled);
+    }
+    if let Some(data_page_size) = settings.data_page_size {
+        writer_props = writer_props.set_data_page_size_limit(data_page_size);
+    }
     if cfg.common.timestamp_compression_disabled {
         writer_props = writer_props
             .set_column_compression(TIMESTAMP_COL_NAME.into(), Compression::UNCOMPRESSED);
@@ -71,27 +119,55 @@ pub fn new_parquet_writer<'a>(
             ),
         ]));
     }
+    // column level settings override the stream level settings, the column compression is
+    // ignored when the compression is forced by the caller
+    let mut bloom_filter_fields = bloom_filter_fields.to_vec();
+    for (name, column) in settings.columns.iter() {
+        if let Some(codec) = column.compression.as_deref()
+            && !compression_forced
+        {
+            writer_props = writer_props.set_column_compression(
+                name.as_str().into(),
+                get_parquet_compression_with_level(codec, column.compression_level),
+            );
+        }
+        if let Some(dictionary_enabled) = column.dictionary_enabled {
+            writer_props = writer_props
+                .set_column_dictionary_enabled(name.as_str().into(), dictionary_enabled);
+        }
+        match column.bloom_filter_enabled {
+            Some(true) => bloom_filter_fields.push(name.to_string()),
+            Some(false) => bloom_filter_fields.retain(|f| f != name),
+            None => {}
+        }
+    }
     // Bloom filter stored by row_group, set NDV to reduce the memory usage.
     // In this link, it says that the optimal number of NDV is 1000, here we use rg_size / NDV_RATIO
     // refer: https://www.influxdata.com/blog/using-parquets-bloom-filters/
-    let mut bf_ndv = min(metadata.records as u64, PARQUET_MAX_ROW_GROUP_SIZE as u64);
+    let mut bf_ndv = min(metadata.records as u64, max_row_group_size as u64);
     if bf_ndv > 1000 {
         bf_ndv = max(1000, bf_ndv / cfg.common.bloom_filter_ndv_ratio);
     }
     if cfg.common.bloom_filter_enabled {
-        let mut fields = bloom_filter_fields.to_vec();
+        let mut fields = bloom_filter_fields;
         fields.extend(BLOOM_FILTER_DEFAULT_FIELDS.clone());
         fields.sort();
         fields.dedup();
         for field in fields {
+            if settings
+                .columns
+                .get(&field)
+                .is_some_and(|c| c.bloom_filter_enabled == Some(false))
+            {
+                continue;
+            }
             writer_props = writer_props
                 .set_column_bloom_filter_enabled(field.as_str().into(), true)
                 .set_column_bloom_filter_fpp(field.as_str().into(), DEFAULT_BLOOM_FILTER_FPP)
                 .set_column_bloom_filter_ndv(field.into(), bf_ndv); // take the field ownership
         }
     }
-    let writer_props = writer_props.build();
-    AsyncArrowWriter::try_new(buf, schema.clone(), Some(writer_props)).unwrap()
+    writer_props.build()
 }
 
 pub async fn write_recordbatch_to_parquet(
@@ -99,10 +175,18 @@ pub async fn write_recordbatch_to_parquet(
     record_batches: &[RecordBatch],
     bloom_filter_fields: &[String],
     metadata: &FileMeta,
+    parquet_settings: Option<&ParquetSettings>,
 ) -> Result<Vec<u8>, anyhow::Error> {
     let mut buf = Vec::new();
-    let mut writer =
-        new_parquet_writer(&mut buf, &schema, bloom_filter_fields, metadata, true, None);
+    let mut writer = new_parquet_writer(
+        &mut buf,
+        &schema,
+        bloom_filter_fields,
+        metadata,
+        true,
+        None,
+        parquet_settings,
+    );
     for batch in record_batches {
         writer.write(batch).await?;
     }
@@ -269,6 +353,7 @@ mod tests {
             &[batch.clone()],
             &["name".to_string()],
             &metadata,
+            None,
         )
         .await
         .unwrap();
@@ -299,9 +384,10 @@ mod tests {
         };
 
         // Write to parquet
-        let data = write_recordbatch_to_parquet(schema, &[batch], &["name".to_string()], &metadata)
-            .await
-            .unwrap();
+        let data =
+            write_recordbatch_to_parquet(schema, &[batch], &["name".to_string()], &metadata, None)
+                .await
+                .unwrap();
 
         // Read metadata
         let read_metadata = read_metadata_from_bytes(&bytes::Bytes::from(data))
@@ -314,6 +400,64 @@ mod tests {
         assert_eq!(read_metadata.original_size, metadata.original_size);
     }
 
+    #[test]
+    fn test_new_parquet_writer_properties_with_settings() {
+        use parquet::{basic::ZstdLevel, schema::types::ColumnPath};
+
+        use crate::meta::stream::ParquetColumnSettings;
+
+        let metadata = FileMeta {
+            records: 100,
+            ..Default::default()
+        };
+        let mut settings = ParquetSettings {
+            compression: Some("zstd".to_string()),
+            compression_level: Some(9),
+            data_page_size: Some(64 * 1024),
+            max_row_group_size: Some(64 * 1024),
+            ..Default::default()
+        };
+        settings.columns.insert(
+            "trace_id".to_string(),
+            ParquetColumnSettings {
+                dictionary_enabled: Some(false),
+                bloom_filter_enabled: Some(true),
+                ..Default::default()
+            },
+        );
+        settings.columns.insert(
+            "body".to_string(),
+            ParquetColumnSettings {
+                compression: Some("zstd".to_string()),
+                compression_level: Some(19),
+                ..Default::default()
+            },
+        );
+        let props = new_parquet_writer_properties(&[], &metadata, false, None, Some(&settings));
+        let trace_id = ColumnPath::from("trace_id");
+        let body = ColumnPath::from("body");
+        assert_eq!(props.max_row_group_size(), 64 * 1024);
+        assert_eq!(props.data_page_size_limit(), 64 * 1024);
+        assert_eq!(
+            props.compression(&trace_id),
+            Compression::ZSTD(ZstdLevel::try_new(9).unwrap())
+        );
+        assert_eq!(
+            props.compression(&body),
+            Compression::ZSTD(ZstdLevel::try_new(19).unwrap())
+        );
+        assert!(!props.dictionary_enabled(&trace_id));
+        assert!(props.dictionary_enabled(&body));
+        if get_config().common.bloom_filter_enabled {
+            assert!(props.bloom_filter_properties(&trace_id).is_some());
+        }
+
+        // the forced compression ignores the stream and column compression
+        let props =
+            new_parquet_writer_properties(&[], &metadata, false, Some("none"), Some(&settings));
+        assert_eq!(props.compression(&body), Compression::UNCOMPRESSED);
+    }
+
     #[test]
     fn test_parse_file_key_columns() {
         let key = "files/default/logs/olympics/2022/10/03/10/6982652937134804993_1.parquet";
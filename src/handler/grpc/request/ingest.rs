@@ -21,7 +21,8 @@ use config::{
 };
 use infra::errors::{Error, Result};
 use proto::cluster_rpc::{
-    IngestionRequest, IngestionResponse, IngestionType, ingest_server::Ingest,
+    IngestionRequest, IngestionResponse, IngestionType, WalReplicationRequest,
+    WalReplicationResponse, ingest_server::Ingest,
 };
 use tonic::{Request, Response, Status};
 
@@ -73,7 +74,7 @@ impl Ingest for Ingester {
                     )))
                 } else {
                     let data = bytes::Bytes::from(in_data.data);
-                    crate::service::metrics::json::ingest(&org_id, data)
+                    crate::service::metrics::json::ingest(&org_id, "", data)
                         .await
                         .map(|_| ()) // we don't care about success response
                         .map_err(|e| Error::IngestionError(format!("error in ingesting metrics {e}")))
@@ -89,6 +90,17 @@ impl Ingest for Ingester {
                     Err(Error::IngestionError(format!(
                         "Internal gRPC trace ingestion only supports json type data, got {log_ingestion_type:?}"
                     )))
+                } else if req.metadata.as_ref().is_some_and(|m| {
+                    m.data
+                        .contains_key(crate::service::traces::tail_sampling::FORWARD_METADATA_KEY)
+                }) {
+                    crate::service::traces::tail_sampling::receive_forwarded(
+                        &org_id,
+                        &stream_name,
+                        &in_data.data,
+                    )
+                    .await
+                    .map_err(|e| Error::IngestionError(format!("error in ingesting traces {e}")))
                 } else {
                     let data = bytes::Bytes::from(in_data.data);
                     crate::service::traces::ingest_json(&org_id, data, OtlpRequestType::Grpc, &stream_name)
@@ -172,4 +184,47 @@ impl Ingest for Ingester {
 
         Ok(Response::new(reply))
     }
+    async fn replicate_wal(
+        &self,
+        request: Request<WalReplicationRequest>,
+    ) -> Result<Response<WalReplicationResponse>, Status> {
+        let start = std::time::Instant::now();
+        let req = request.into_inner();
+        let ret = if req.persisted {
+            ingester::release_wal_replica(&req.source_node, &req.file_path).await
+        } else {
+            ingester::write_wal_replica(&req.source_node, &req.file_path, req.entries, req.sealed)
+                .await
+        };
+        let reply = match ret {
+            Ok(_) => WalReplicationResponse {
+                status_code: 200,
+                message: "OK".to_string(),
+            },
+            Err(e) => {
+                log::error!(
+                    "[WAL_REPLICATION] write replica {}/{} error: {}",
+                    req.source_node,
+                    req.file_path,
+                    e
+                );
+                WalReplicationResponse {
+                    status_code: 500,
+                    message: e.to_string(),
+                }
+            }
+        };
+
+        // metrics
+        let time = start.elapsed().as_secs_f64();
+        let status_code = reply.status_code.to_string();
+        metrics::GRPC_RESPONSE_TIME
+            .with_label_values(&["/ingest/replicate_wal", &status_code, "", "", "", ""])
+            .observe(time);
+        metrics::GRPC_INCOMING_REQUESTS
+            .with_label_values(&["/ingest/replicate_wal", &status_code, "", "", "", ""])
+            .inc();
+
+        Ok(Response::new(reply))
+    }
 }
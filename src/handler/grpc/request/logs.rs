@@ -19,6 +19,8 @@ use opentelemetry_proto::tonic::collector::logs::v1::{
 };
 use tonic::{Response, Status};
 
+use crate::service::ingestion::flow_control;
+
 #[derive(Default)]
 pub struct LogsServer;
 
@@ -57,6 +59,18 @@ impl LogsService for LogsServer {
             user_email = user_id.to_str().unwrap();
         };
 
+        #[cfg(not(feature = "enterprise"))]
+        if !crate::service::grants::check_otlp_ingest(
+            org_id.unwrap().to_str().unwrap(),
+            user_email,
+            config::meta::stream::StreamType::Logs,
+            in_stream_name,
+        )
+        .await
+        {
+            return Err(Status::permission_denied("Unauthorized Access"));
+        }
+
         match crate::service::logs::otlp::handle_request(
             0,
             org_id.unwrap().to_str().unwrap(),
@@ -81,7 +95,10 @@ impl LogsService for LogsServer {
                     partial_success: None,
                 }))
             }
-            Err(e) => Err(Status::internal(e.to_string())),
+            Err(e) => Err(
+                flow_control::grpc_status(org_id.unwrap().to_str().unwrap(), &e)
+                    .unwrap_or_else(|| Status::internal(e.to_string())),
+            ),
         }
     }
 }
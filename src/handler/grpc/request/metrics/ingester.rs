@@ -20,6 +20,8 @@ use opentelemetry_proto::tonic::collector::metrics::v1::{
 };
 use tonic::{Response, Status};
 
+use crate::service::ingestion::flow_control;
+
 #[derive(Default)]
 pub struct MetricsIngester;
 
@@ -47,12 +49,31 @@ impl MetricsService for MetricsIngester {
             return Err(Status::invalid_argument(msg));
         }
 
+        #[cfg(not(feature = "enterprise"))]
+        if let Err(e) = crate::service::metrics::otlp::check_grants(
+            org_id.unwrap().to_str().unwrap(),
+            metadata
+                .get("user_id")
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default(),
+            &in_req,
+        )
+        .await
+        {
+            return Err(Status::permission_denied(e.to_string()));
+        }
+
         let resp = crate::service::metrics::otlp::handle_otlp_request(
             org_id.unwrap().to_str().unwrap(),
             in_req,
             OtlpRequestType::Grpc,
         )
         .await;
+        if let Ok(resp) = resp.as_ref()
+            && let Some(status) = flow_control::http_response_to_grpc_status(resp)
+        {
+            return Err(status);
+        }
         if resp.is_ok() {
             // metrics
             let time = start.elapsed().as_secs_f64();
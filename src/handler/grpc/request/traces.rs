@@ -19,7 +19,7 @@ use opentelemetry_proto::tonic::collector::trace::v1::{
 };
 use tonic::{Response, Status};
 
-use crate::service::traces::handle_otlp_request;
+use crate::service::{ingestion::flow_control, traces::handle_otlp_request};
 
 #[derive(Default)]
 pub struct TraceServer;
@@ -54,6 +54,21 @@ impl TraceService for TraceServer {
             in_stream_name = Some(stream_name.to_str().unwrap());
         };
 
+        #[cfg(not(feature = "enterprise"))]
+        if !crate::service::grants::check_otlp_ingest(
+            org_id.unwrap().to_str().unwrap(),
+            metadata
+                .get("user_id")
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default(),
+            config::meta::stream::StreamType::Traces,
+            in_stream_name,
+        )
+        .await
+        {
+            return Err(Status::permission_denied("Unauthorized Access"));
+        }
+
         let resp = handle_otlp_request(
             org_id.unwrap().to_str().unwrap(),
             in_req,
@@ -61,6 +76,11 @@ impl TraceService for TraceServer {
             in_stream_name,
         )
         .await;
+        if let Ok(resp) = resp.as_ref()
+            && let Some(status) = flow_control::http_response_to_grpc_status(resp)
+        {
+            return Err(status);
+        }
         if resp.is_ok() {
             // metrics
             let time = start.elapsed().as_secs_f64();
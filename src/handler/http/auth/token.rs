@@ -95,6 +95,12 @@ pub async fn token_validator(
                 };
                 match user {
                     Some(user) => {
+                        if let Err(e) =
+                            super::validator::check_session_policies(&req, path, user_id).await
+                        {
+                            return Err((e, req));
+                        }
+
                         // / Hack for prometheus, need support POST and check the header
                         let mut req = req;
 
@@ -154,12 +160,40 @@ pub async fn get_user_name_from_token(auth_str: &str) -> Option<String> {
     }
 }
 
+/// Bearer tokens of the open source build are the named API tokens and the
+/// sessions of the users logged in with OIDC, both are checked like passwords
+/// of their owner.
 #[cfg(not(feature = "enterprise"))]
 pub async fn token_validator(
     req: ServiceRequest,
-    _token: AuthExtractor,
+    auth_info: AuthExtractor,
 ) -> Result<ServiceRequest, (Error, ServiceRequest)> {
-    use actix_web::error::ErrorForbidden;
+    use actix_web::error::ErrorUnauthorized;
+    use config::meta::api_token::API_TOKEN_PREFIX;
+
+    use crate::service::{api_tokens, oidc};
 
-    Err((ErrorForbidden("Not Supported"), req))
+    let path_prefix = "/api/";
+    let path = req
+        .request()
+        .path()
+        .strip_prefix(format!("{}{path_prefix}", config::get_config().common.base_uri).as_str())
+        .unwrap_or(req.request().path());
+    let token = auth_info
+        .auth
+        .strip_prefix("Bearer")
+        .unwrap()
+        .trim()
+        .to_string();
+    let user_id = if token.starts_with(API_TOKEN_PREFIX) {
+        api_tokens::from_request(path, &token).map(|token| token.user_email)
+    } else {
+        oidc::verify_session_token(&token)
+    };
+    match user_id {
+        Some(user_id) => {
+            super::validator::validator(req, &user_id, &token, auth_info, path_prefix).await
+        }
+        None => Err((ErrorUnauthorized("Unauthorized Access"), req)),
+    }
 }
@@ -25,7 +25,10 @@ use actix_web::{
 };
 use config::{
     get_config,
-    meta::user::{DBUser, UserRole},
+    meta::{
+        api_token::API_TOKEN_PREFIX,
+        user::{DBUser, UserRole},
+    },
     utils::base64,
 };
 #[cfg(feature = "enterprise")]
@@ -50,7 +53,7 @@ use crate::{
             redirect_response::RedirectResponseBuilder,
         },
     },
-    service::{db, users},
+    service::{api_tokens, db, session, users},
 };
 
 pub const PKCE_STATE_ORG: &str = "o2_pkce_state";
@@ -79,16 +82,21 @@ pub async fn validator(
             config::utils::json::from_str(&auth_info.auth).unwrap_or_default();
         validate_credentials_ext(user_id, password, path, auth_token).await
     } else {
-        validate_credentials(user_id, password.trim(), path).await
+        validate_credentials(user_id, password.trim(), path, req.method()).await
     } {
         Ok(res) => {
             if res.is_valid {
+                if let Err(e) = check_session_policies(&req, path, &res.user_email).await {
+                    return Err((e, req));
+                }
+
                 // Check and create organization if needed
                 if let Err(e) = check_and_create_org(user_id, req.method(), path).await {
                     return Err((e, req));
                 }
 
-                #[cfg(feature = "enterprise")]
+                let api_token = api_tokens::from_request(path, password.trim());
+
                 let path = path.to_owned();
 
                 // / Hack for prometheus, need support POST and check the header
@@ -103,6 +111,10 @@ pub async fn validator(
                     header::HeaderName::from_static("user_id"),
                     header::HeaderValue::from_str(&res.user_email).unwrap(),
                 );
+                // the handlers check the streams in the body against the token
+                if let Some(token) = api_token {
+                    req.extensions_mut().insert(token);
+                }
 
                 #[cfg(feature = "enterprise")]
                 if let Some(role) = &res.user_role
@@ -114,6 +126,21 @@ pub async fn validator(
                     return Ok(req);
                 }
 
+                #[cfg(not(feature = "enterprise"))]
+                if !crate::service::grants::check_request(
+                    &res.user_email,
+                    req.method(),
+                    &path,
+                    req.query_string(),
+                    req.headers()
+                        .get(&get_config().grpc.stream_header_key)
+                        .and_then(|v| v.to_str().ok()),
+                )
+                .await
+                {
+                    return Err((ErrorForbidden("Unauthorized Access"), req));
+                }
+
                 if auth_info.bypass_check
                     || check_permissions(
                         user_id,
@@ -138,6 +165,27 @@ pub async fn validator(
     }
 }
 
+/// Enforces the idle timeout and the concurrent sessions limit of the org on
+/// the requests made with a login session.
+pub(crate) async fn check_session_policies(
+    req: &ServiceRequest,
+    path: &str,
+    user_email: &str,
+) -> Result<(), Error> {
+    let Some(session_id) = session::session_id_from_request(req.request()) else {
+        return Ok(());
+    };
+    let mut path_columns = path.split('/');
+    let org_id = match path_columns.next() {
+        Some(V2_API_PREFIX) => path_columns.next(),
+        org_id => org_id,
+    }
+    .unwrap_or_default();
+    session::check_session(&session_id, user_email, org_id)
+        .await
+        .map_err(|e| ErrorUnauthorized(e.to_string()))
+}
+
 /// `validate_token` validates the endpoints which are token only.
 /// This includes endpoints like `rum` etc.
 ///
@@ -155,6 +203,7 @@ pub async fn validate_credentials(
     user_id: &str,
     user_password: &str,
     path: &str,
+    method: &Method,
 ) -> Result<TokenValidationResponse, Error> {
     let mut path_columns = path.split('/').collect::<Vec<&str>>();
     if let Some(v) = path_columns.last()
@@ -238,6 +287,57 @@ pub async fn validate_credentials(
         }
     }
 
+    // named api tokens carry their own scope
+    if user_password.starts_with(API_TOKEN_PREFIX) {
+        if !api_tokens::validate(&user.email, user_password, method, path).await {
+            return Ok(TokenValidationResponse::default());
+        }
+        return Ok(TokenValidationResponse {
+            is_valid: true,
+            user_email: user.email,
+            is_internal_user: !user.is_external,
+            user_role: Some(user.role),
+            user_name: user.first_name.to_owned(),
+            family_name: user.last_name,
+            given_name: user.first_name,
+        });
+    }
+
+    // sessions of the users logged in with a password
+    if let Some(session_id) = user_password.strip_prefix("session ")
+        && !user.role.eq(&UserRole::ServiceAccount)
+        && session::get_native_user(session_id)
+            .await
+            .is_some_and(|email| email == user.email)
+    {
+        return Ok(TokenValidationResponse {
+            is_valid: true,
+            user_email: user.email,
+            is_internal_user: !user.is_external,
+            user_role: Some(user.role),
+            user_name: user.first_name.to_owned(),
+            family_name: user.last_name,
+            given_name: user.first_name,
+        });
+    }
+
+    // sessions of the users logged in with OIDC
+    #[cfg(not(feature = "enterprise"))]
+    if !user.role.eq(&UserRole::ServiceAccount)
+        && crate::service::oidc::verify_session_token(user_password)
+            .is_some_and(|email| email == user.email)
+    {
+        return Ok(TokenValidationResponse {
+            is_valid: true,
+            user_email: user.email,
+            is_internal_user: !user.is_external,
+            user_role: Some(user.role),
+            user_name: user.first_name.to_owned(),
+            family_name: user.last_name,
+            given_name: user.first_name,
+        });
+    }
+
     if user.role.eq(&UserRole::ServiceAccount) && user.token.eq(&user_password) {
         return Ok(TokenValidationResponse {
             is_valid: true,
@@ -549,9 +649,21 @@ pub async fn validator_aws(
                     .map(|s| s.to_string())
                     .collect::<Vec<String>>();
 
-                match validate_credentials(&creds[0], &creds[1], path).await {
+                match validate_credentials(&creds[0], &creds[1], path, req.method()).await {
                     Ok(res) => {
                         if res.is_valid {
+                            #[cfg(not(feature = "enterprise"))]
+                            if !crate::service::grants::check_request(
+                                &res.user_email,
+                                req.method(),
+                                path,
+                                req.query_string(),
+                                None,
+                            )
+                            .await
+                            {
+                                return Err((ErrorForbidden("Unauthorized Access"), req));
+                            }
                             let mut req = req;
                             req.headers_mut().insert(
                                 header::HeaderName::from_static("user_id"),
@@ -593,9 +705,21 @@ pub async fn validator_gcp(
                 .map(|s| s.to_string())
                 .collect::<Vec<String>>();
 
-            match validate_credentials(&creds[0], &creds[1], path).await {
+            match validate_credentials(&creds[0], &creds[1], path, req.method()).await {
                 Ok(res) => {
                     if res.is_valid {
+                        #[cfg(not(feature = "enterprise"))]
+                        if !crate::service::grants::check_request(
+                            &res.user_email,
+                            req.method(),
+                            path,
+                            req.query_string(),
+                            None,
+                        )
+                        .await
+                        {
+                            return Err((ErrorForbidden("Unauthorized Access"), req));
+                        }
                         let mut req = req;
                         req.headers_mut().insert(
                             header::HeaderName::from_static("user_id"),
@@ -682,6 +806,12 @@ async fn oo_validator_internal(
     } else if auth_info.auth.starts_with("Bearer") {
         log::debug!("Bearer token found");
         super::token::token_validator(req, auth_info).await
+    } else if let Some(session_id) = auth_info.auth.strip_prefix("session ") {
+        let Some(user_id) = session::get_native_user(session_id).await else {
+            return Err((ErrorUnauthorized("Unauthorized Access"), req));
+        };
+        let password = auth_info.auth.clone();
+        validator(req, &user_id, &password, auth_info, path_prefix).await
     } else if auth_info.auth.starts_with("{\"auth_ext\":") {
         log::debug!("Auth ext token found");
         let auth_tokens: AuthTokensExt =
@@ -726,6 +856,8 @@ pub async fn get_user_email_from_auth_str(auth_str: &str) -> Option<String> {
         }
     } else if auth_str.starts_with("Bearer") {
         super::token::get_user_name_from_token(auth_str).await
+    } else if let Some(session_id) = auth_str.strip_prefix("session ") {
+        session::get_native_user(session_id).await
     } else if auth_str.starts_with("{\"auth_ext\":") {
         let auth_tokens: AuthTokensExt =
             config::utils::json::from_str(auth_str).unwrap_or_default();
@@ -1154,20 +1286,25 @@ mod tests {
         .unwrap();
 
         assert!(
-            validate_credentials(init_user, pwd, "default/_bulk")
+            validate_credentials(init_user, pwd, "default/_bulk", &Method::POST)
+                .await
+                .unwrap()
+                .is_valid
+        );
+        assert!(
+            !validate_credentials("", pwd, "default/_bulk", &Method::POST)
                 .await
                 .unwrap()
                 .is_valid
         );
         assert!(
-            !validate_credentials("", pwd, "default/_bulk")
+            !validate_credentials("", pwd, "/", &Method::GET)
                 .await
                 .unwrap()
                 .is_valid
         );
-        assert!(!validate_credentials("", pwd, "/").await.unwrap().is_valid);
         assert!(
-            !validate_credentials(user_id, pwd, "/")
+            !validate_credentials(user_id, pwd, "/", &Method::GET)
                 .await
                 .unwrap()
                 .is_valid
@@ -1175,13 +1312,13 @@ mod tests {
         // TODO: In these unit tests, is_root_user function does not work,
         // So, the below test case will not work, move these tests to integration tests
         // assert!(
-        //     validate_credentials(user_id, pwd, "default/user")
+        //     validate_credentials(user_id, pwd, "default/user", &Method::GET)
         //         .await
         //         .unwrap()
         //         .is_valid
         // );
         assert!(
-            !validate_credentials(user_id, "x", "default/user")
+            !validate_credentials(user_id, "x", "default/user", &Method::GET)
                 .await
                 .unwrap()
                 .is_valid
@@ -0,0 +1,136 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io::Error;
+
+use actix_web::{HttpRequest, HttpResponse, delete, get, http, post, web};
+use config::meta::api_token::{CreateApiTokenRequest, CreateApiTokenResponse};
+
+use crate::{
+    common::meta::http::HttpResponse as MetaHttpResponse,
+    service::api_tokens::{self, ApiTokenError},
+};
+
+impl From<ApiTokenError> for HttpResponse {
+    fn from(value: ApiTokenError) -> Self {
+        match value {
+            ApiTokenError::InfraError(err) => MetaHttpResponse::internal_error(err),
+            ApiTokenError::NotFound(_) => MetaHttpResponse::not_found(value),
+            ApiTokenError::Forbidden(_) => MetaHttpResponse::forbidden(value),
+            error => MetaHttpResponse::bad_request(error),
+        }
+    }
+}
+
+/// ListApiTokens
+///
+/// #{"ratelimit_module":"ApiTokens", "ratelimit_module_operation":"list"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "ApiTokens",
+    operation_id = "ListApiTokens",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = Vec<ApiToken>),
+    )
+)]
+#[get("/{org_id}/api_tokens")]
+pub async fn list(path: web::Path<String>, req: HttpRequest) -> Result<HttpResponse, Error> {
+    let org_id = path.into_inner();
+    match api_tokens::list(&org_id, get_user_id(&req)).await {
+        Ok(tokens) => Ok(HttpResponse::Ok().json(tokens)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+/// CreateApiToken
+///
+/// #{"ratelimit_module":"ApiTokens", "ratelimit_module_operation":"create"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "ApiTokens",
+    operation_id = "CreateApiToken",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    request_body(content = CreateApiTokenRequest, description = "Token data", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = CreateApiTokenResponse),
+        (status = 400, description = "Failure", content_type = "application/json", body = HttpResponse),
+        (status = 403, description = "Forbidden", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[post("/{org_id}/api_tokens")]
+pub async fn create(
+    path: web::Path<String>,
+    body: web::Json<CreateApiTokenRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let org_id = path.into_inner();
+    match api_tokens::create(&org_id, get_user_id(&req), body.into_inner()).await {
+        Ok((info, token)) => Ok(HttpResponse::Ok().json(CreateApiTokenResponse { info, token })),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+/// RevokeApiToken
+///
+/// #{"ratelimit_module":"ApiTokens", "ratelimit_module_operation":"delete"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "ApiTokens",
+    operation_id = "RevokeApiToken",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("token_id" = String, Path, description = "Token ID"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = HttpResponse),
+        (status = 404, description = "NotFound", content_type = "application/json", body = HttpResponse),
+        (status = 403, description = "Forbidden", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[delete("/{org_id}/api_tokens/{token_id}")]
+pub async fn revoke(
+    path: web::Path<(String, String)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let (org_id, token_id) = path.into_inner();
+    match api_tokens::revoke(&org_id, get_user_id(&req), &token_id).await {
+        Ok(()) => Ok(HttpResponse::Ok().json(MetaHttpResponse::message(
+            http::StatusCode::OK,
+            "Token revoked successfully",
+        ))),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+fn get_user_id(req: &HttpRequest) -> &str {
+    req.headers()
+        .get("user_id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+}
@@ -0,0 +1,95 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io::Error;
+
+use actix_web::{HttpRequest, HttpResponse, get, post, web};
+
+use crate::{
+    common::meta::http::HttpResponse as MetaHttpResponse,
+    service::encryption::{self, EncryptionError},
+};
+
+impl From<EncryptionError> for HttpResponse {
+    fn from(value: EncryptionError) -> Self {
+        match value {
+            EncryptionError::InfraError(err) => MetaHttpResponse::internal_error(err),
+            EncryptionError::Forbidden(_) => MetaHttpResponse::forbidden(value),
+            error => MetaHttpResponse::bad_request(error),
+        }
+    }
+}
+
+/// GetEncryptionKeys
+///
+/// #{"ratelimit_module":"Encryption", "ratelimit_module_operation":"get"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Encryption",
+    operation_id = "GetEncryptionKeys",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = EncryptionStatus),
+        (status = 403, description = "Forbidden", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[get("/{org_id}/encryption/keys")]
+pub async fn status(path: web::Path<String>, req: HttpRequest) -> Result<HttpResponse, Error> {
+    let org_id = path.into_inner();
+    match encryption::status(&org_id, get_user_id(&req)).await {
+        Ok(status) => Ok(HttpResponse::Ok().json(status)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+/// RotateEncryptionKey
+///
+/// #{"ratelimit_module":"Encryption", "ratelimit_module_operation":"update"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Encryption",
+    operation_id = "RotateEncryptionKey",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = EncryptionStatus),
+        (status = 400, description = "Failure", content_type = "application/json", body = HttpResponse),
+        (status = 403, description = "Forbidden", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[post("/{org_id}/encryption/keys/rotate")]
+pub async fn rotate(path: web::Path<String>, req: HttpRequest) -> Result<HttpResponse, Error> {
+    let org_id = path.into_inner();
+    match encryption::rotate(&org_id, get_user_id(&req)).await {
+        Ok(status) => Ok(HttpResponse::Ok().json(status)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+fn get_user_id(req: &HttpRequest) -> &str {
+    req.headers()
+        .get("user_id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+}
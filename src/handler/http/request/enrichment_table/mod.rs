@@ -16,13 +16,21 @@
 use std::io::Error;
 
 use actix_multipart::Multipart;
-use actix_web::{HttpRequest, HttpResponse, post, web};
-use config::SIZE_IN_MB;
+use actix_web::{HttpRequest, HttpResponse, delete, get, post, put, web};
+use config::{
+    SIZE_IN_MB,
+    meta::enrichment_table::{EnrichmentTableSource, EnrichmentTableSourceResponse},
+    utils::time::now_micros,
+};
 use hashbrown::HashMap;
 
 use crate::{
     common::meta::http::HttpResponse as MetaHttpResponse,
-    service::enrichment_table::{extract_multipart, save_enrichment_data},
+    service::{
+        db,
+        enrichment_table::{extract_multipart, save_enrichment_data, source},
+        format_stream_name,
+    },
 };
 
 /// CreateEnrichmentTable
@@ -102,3 +110,172 @@ pub async fn save_enrichment_table(
         )),
     }
 }
+
+/// SaveEnrichmentTableSource
+///
+/// Defines the external source of the enrichment table and loads it, the
+/// table is then refreshed on the schedule of the source.
+///
+/// #{"ratelimit_module":"Enrichment Table", "ratelimit_module_operation":"create"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Functions",
+    operation_id = "SaveEnrichmentTableSource",
+    security(
+        ("Authorization" = [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("table_name" = String, Path, description = "Table name"),
+    ),
+    request_body(content = EnrichmentTableSource, description = "Enrichment table source", content_type = "application/json"),
+    responses(
+        (status = StatusCode::OK, description = "Saved source and the status of the first load", body = EnrichmentTableSourceResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Bad Request", body = HttpResponse),
+    ),
+)]
+#[put("/{org_id}/enrichment_tables/{table_name}/source")]
+pub async fn save_enrichment_table_source(
+    path: web::Path<(String, String)>,
+    body: web::Json<EnrichmentTableSource>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let (org_id, table_name) = path.into_inner();
+    let mut table_source = body.into_inner();
+    table_source.name = format_stream_name(table_name.trim());
+    table_source.updated_by = req
+        .headers()
+        .get("user_id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    if let Err(e) = table_source.validate() {
+        return Ok(MetaHttpResponse::bad_request(e));
+    }
+    let existing = match db::enrichment_table::get_source(&org_id, &table_source.name).await {
+        Ok(v) => v,
+        Err(e) => return Ok(MetaHttpResponse::internal_error(e)),
+    };
+    table_source.updated_at = now_micros();
+    table_source.created_at = existing.map_or(table_source.updated_at, |s| s.created_at);
+    if let Err(e) = db::enrichment_table::set_source(&org_id, &table_source).await {
+        return Ok(MetaHttpResponse::internal_error(e));
+    }
+    let status = source::refresh(&org_id, &table_source).await;
+    Ok(MetaHttpResponse::json(EnrichmentTableSourceResponse {
+        source: table_source,
+        status,
+    }))
+}
+
+/// GetEnrichmentTableSource
+///
+/// #{"ratelimit_module":"Enrichment Table", "ratelimit_module_operation":"get"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Functions",
+    operation_id = "GetEnrichmentTableSource",
+    security(
+        ("Authorization" = [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("table_name" = String, Path, description = "Table name"),
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Source and the status of the last refresh", body = EnrichmentTableSourceResponse),
+        (status = StatusCode::NOT_FOUND, description = "Source not found", body = HttpResponse),
+    ),
+)]
+#[get("/{org_id}/enrichment_tables/{table_name}/source")]
+pub async fn get_enrichment_table_source(
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, Error> {
+    let (org_id, table_name) = path.into_inner();
+    let name = format_stream_name(table_name.trim());
+    match db::enrichment_table::get_source(&org_id, &name).await {
+        Ok(Some(table_source)) => {
+            let status = db::enrichment_table::get_source_status(&org_id, &name).await;
+            Ok(MetaHttpResponse::json(EnrichmentTableSourceResponse {
+                source: table_source,
+                status,
+            }))
+        }
+        Ok(None) => Ok(MetaHttpResponse::not_found(format!(
+            "enrichment table [{name}] has no source"
+        ))),
+        Err(e) => Ok(MetaHttpResponse::internal_error(e)),
+    }
+}
+
+/// DeleteEnrichmentTableSource
+///
+/// Stops refreshing the enrichment table, the loaded data is kept.
+///
+/// #{"ratelimit_module":"Enrichment Table", "ratelimit_module_operation":"delete"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Functions",
+    operation_id = "DeleteEnrichmentTableSource",
+    security(
+        ("Authorization" = [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("table_name" = String, Path, description = "Table name"),
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Source deleted", body = HttpResponse),
+    ),
+)]
+#[delete("/{org_id}/enrichment_tables/{table_name}/source")]
+pub async fn delete_enrichment_table_source(
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, Error> {
+    let (org_id, table_name) = path.into_inner();
+    let name = format_stream_name(table_name.trim());
+    match db::enrichment_table::delete_source(&org_id, &name).await {
+        Ok(_) => Ok(MetaHttpResponse::ok("Enrichment table source deleted")),
+        Err(e) => Ok(MetaHttpResponse::internal_error(e)),
+    }
+}
+
+/// RefreshEnrichmentTableSource
+///
+/// #{"ratelimit_module":"Enrichment Table", "ratelimit_module_operation":"update"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Functions",
+    operation_id = "RefreshEnrichmentTableSource",
+    security(
+        ("Authorization" = [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("table_name" = String, Path, description = "Table name"),
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Source and the status of the refresh", body = EnrichmentTableSourceResponse),
+        (status = StatusCode::NOT_FOUND, description = "Source not found", body = HttpResponse),
+    ),
+)]
+#[post("/{org_id}/enrichment_tables/{table_name}/source/refresh")]
+pub async fn refresh_enrichment_table_source(
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, Error> {
+    let (org_id, table_name) = path.into_inner();
+    let name = format_stream_name(table_name.trim());
+    match db::enrichment_table::get_source(&org_id, &name).await {
+        Ok(Some(table_source)) => {
+            let status = source::refresh(&org_id, &table_source).await;
+            Ok(MetaHttpResponse::json(EnrichmentTableSourceResponse {
+                source: table_source,
+                status,
+            }))
+        }
+        Ok(None) => Ok(MetaHttpResponse::not_found(format!(
+            "enrichment table [{name}] has no source"
+        ))),
+        Err(e) => Ok(MetaHttpResponse::internal_error(e)),
+    }
+}
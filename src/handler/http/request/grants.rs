@@ -0,0 +1,181 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io::Error;
+
+use actix_web::{HttpRequest, HttpResponse, delete, get, post, web};
+#[cfg(not(feature = "enterprise"))]
+use {
+    crate::service::grants::{self, GrantError},
+    actix_web::http,
+};
+
+use crate::common::meta::http::HttpResponse as MetaHttpResponse;
+
+#[cfg(not(feature = "enterprise"))]
+impl From<GrantError> for HttpResponse {
+    fn from(value: GrantError) -> Self {
+        match value {
+            GrantError::InfraError(err) => MetaHttpResponse::internal_error(err),
+            GrantError::NotFound(_) => MetaHttpResponse::not_found(value),
+            GrantError::ForbiddenStream(_) => MetaHttpResponse::forbidden(value),
+            error => MetaHttpResponse::bad_request(error),
+        }
+    }
+}
+
+/// ListGrants
+///
+/// #{"ratelimit_module":"Grants", "ratelimit_module_operation":"list"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Grants",
+    operation_id = "ListGrants",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = Vec<Grant>),
+        (status = 403, description = "Forbidden", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[get("/{org_id}/grants")]
+pub async fn list(path: web::Path<String>, req: HttpRequest) -> Result<HttpResponse, Error> {
+    #[cfg(not(feature = "enterprise"))]
+    {
+        let org_id = path.into_inner();
+        if !grants::can_manage(&org_id, get_user_id(&req)).await {
+            return Ok(MetaHttpResponse::forbidden("Only admins can manage grants"));
+        }
+        match grants::list(&org_id).await {
+            Ok(grants) => Ok(HttpResponse::Ok().json(grants)),
+            Err(e) => Ok(e.into()),
+        }
+    }
+    #[cfg(feature = "enterprise")]
+    {
+        drop(path);
+        drop(req);
+        Ok(MetaHttpResponse::forbidden("not supported"))
+    }
+}
+
+/// CreateGrant
+///
+/// #{"ratelimit_module":"Grants", "ratelimit_module_operation":"create"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Grants",
+    operation_id = "CreateGrant",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    request_body(content = Grant, description = "Grant data", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = Grant),
+        (status = 400, description = "Failure", content_type = "application/json", body = HttpResponse),
+        (status = 403, description = "Forbidden", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[post("/{org_id}/grants")]
+pub async fn save(
+    path: web::Path<String>,
+    body: web::Bytes,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    #[cfg(not(feature = "enterprise"))]
+    {
+        let org_id = path.into_inner();
+        if !grants::can_manage(&org_id, get_user_id(&req)).await {
+            return Ok(MetaHttpResponse::forbidden("Only admins can manage grants"));
+        }
+        let grant = match config::utils::json::from_slice(&body) {
+            Ok(grant) => grant,
+            Err(e) => return Ok(MetaHttpResponse::bad_request(e)),
+        };
+        match grants::save(&org_id, grant).await {
+            Ok(grant) => Ok(HttpResponse::Ok().json(grant)),
+            Err(e) => Ok(e.into()),
+        }
+    }
+    #[cfg(feature = "enterprise")]
+    {
+        drop(path);
+        drop(body);
+        drop(req);
+        Ok(MetaHttpResponse::forbidden("not supported"))
+    }
+}
+
+/// DeleteGrant
+///
+/// #{"ratelimit_module":"Grants", "ratelimit_module_operation":"delete"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Grants",
+    operation_id = "DeleteGrant",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("grant_id" = String, Path, description = "Grant ID"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = HttpResponse),
+        (status = 404, description = "NotFound", content_type = "application/json", body = HttpResponse),
+        (status = 403, description = "Forbidden", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[delete("/{org_id}/grants/{grant_id}")]
+pub async fn delete(
+    path: web::Path<(String, String)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    #[cfg(not(feature = "enterprise"))]
+    {
+        let (org_id, grant_id) = path.into_inner();
+        if !grants::can_manage(&org_id, get_user_id(&req)).await {
+            return Ok(MetaHttpResponse::forbidden("Only admins can manage grants"));
+        }
+        match grants::delete(&org_id, &grant_id).await {
+            Ok(()) => Ok(HttpResponse::Ok().json(MetaHttpResponse::message(
+                http::StatusCode::OK,
+                "Grant deleted successfully",
+            ))),
+            Err(e) => Ok(e.into()),
+        }
+    }
+    #[cfg(feature = "enterprise")]
+    {
+        drop(path);
+        drop(req);
+        Ok(MetaHttpResponse::forbidden("not supported"))
+    }
+}
+
+#[cfg(not(feature = "enterprise"))]
+fn get_user_id(req: &HttpRequest) -> &str {
+    req.headers()
+        .get("user_id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+}
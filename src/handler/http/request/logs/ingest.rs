@@ -16,7 +16,7 @@
 use std::io::Error;
 
 use actix_web::{HttpRequest, HttpResponse, http, http::header, post, web};
-use config::meta::otlp::OtlpRequestType;
+use config::meta::{api_token::ApiToken, otlp::OtlpRequestType};
 use opentelemetry_proto::tonic::collector::logs::v1::ExportLogsServiceRequest;
 use prost::Message;
 
@@ -29,7 +29,10 @@ use crate::{
         },
     },
     handler::http::request::{CONTENT_TYPE_JSON, CONTENT_TYPE_PROTO},
-    service::logs::{self, otlp::handle_request},
+    service::{
+        ingestion::flow_control::{self, PROTOCOL_HTTP},
+        logs::{self, otlp::handle_request},
+    },
 };
 
 /// _bulk ES compatible ingestion API
@@ -58,6 +61,7 @@ pub async fn bulk(
 ) -> Result<HttpResponse, Error> {
     let org_id = org_id.into_inner();
     let user_email = in_req.headers().get("user_id").unwrap().to_str().unwrap();
+    let token = in_req.extensions().get::<ApiToken>().cloned();
 
     // log start processing time
     let process_time = if config::get_config().limit.http_slow_log_threshold > 0 {
@@ -66,21 +70,24 @@ pub async fn bulk(
         0
     };
 
-    let mut resp = match logs::bulk::ingest(**thread_id, &org_id, body, user_email).await {
-        Ok(v) => MetaHttpResponse::json(v),
-        Err(e) => {
-            log::error!("Error processing request {org_id}/_bulk: {e}");
-            if matches!(e, infra::errors::Error::ResourceError(_)) {
-                HttpResponse::ServiceUnavailable().json(MetaHttpResponse::error(
-                    http::StatusCode::SERVICE_UNAVAILABLE,
-                    e,
-                ))
-            } else {
-                HttpResponse::BadRequest()
-                    .json(MetaHttpResponse::error(http::StatusCode::BAD_REQUEST, e))
+    let mut resp =
+        match logs::bulk::ingest(**thread_id, &org_id, body, user_email, token.as_ref()).await {
+            Ok(v) => MetaHttpResponse::json(v),
+            Err(e) => {
+                log::error!("Error processing request {org_id}/_bulk: {e}");
+                if let Some(resp) = flow_control::http_response(&org_id, PROTOCOL_HTTP, &e) {
+                    resp
+                } else if matches!(e, infra::errors::Error::ResourceError(_)) {
+                    HttpResponse::ServiceUnavailable().json(MetaHttpResponse::error(
+                        http::StatusCode::SERVICE_UNAVAILABLE,
+                        e,
+                    ))
+                } else {
+                    HttpResponse::BadRequest()
+                        .json(MetaHttpResponse::error(http::StatusCode::BAD_REQUEST, e))
+                }
             }
-        }
-    };
+        };
 
     if process_time > 0 {
         resp.headers_mut().insert(
@@ -143,7 +150,9 @@ pub async fn multi(
         },
         Err(e) => {
             log::error!("Error processing request {org_id}/{stream_name}/_multi: {e}");
-            if matches!(e, infra::errors::Error::ResourceError(_)) {
+            if let Some(resp) = flow_control::http_response(&org_id, PROTOCOL_HTTP, &e) {
+                resp
+            } else if matches!(e, infra::errors::Error::ResourceError(_)) {
                 HttpResponse::ServiceUnavailable().json(MetaHttpResponse::error(
                     http::StatusCode::SERVICE_UNAVAILABLE,
                     e,
@@ -216,7 +225,9 @@ pub async fn json(
         },
         Err(e) => {
             log::error!("Error processing request {org_id}/{stream_name}/_json: {e}");
-            if matches!(e, infra::errors::Error::ResourceError(_)) {
+            if let Some(resp) = flow_control::http_response(&org_id, PROTOCOL_HTTP, &e) {
+                resp
+            } else if matches!(e, infra::errors::Error::ResourceError(_)) {
                 HttpResponse::ServiceUnavailable().json(MetaHttpResponse::error(
                     http::StatusCode::SERVICE_UNAVAILABLE,
                     e,
@@ -287,7 +298,9 @@ pub async fn handle_kinesis_request(
             }),
             Err(e) => {
                 log::error!("Error processing kinesis request: {e}");
-                if matches!(e, infra::errors::Error::ResourceError(_)) {
+                if let Some(resp) = flow_control::http_response(&org_id, PROTOCOL_HTTP, &e) {
+                    resp
+                } else if matches!(e, infra::errors::Error::ResourceError(_)) {
                     HttpResponse::ServiceUnavailable().json(KinesisFHIngestionResponse {
                         request_id,
                         timestamp: request_time,
@@ -331,7 +344,9 @@ pub async fn handle_gcp_request(
                     "Error processing request {org_id}/{stream_name}/_gcp: {:?}",
                     e
                 );
-                if matches!(e, infra::errors::Error::ResourceError(_)) {
+                if let Some(resp) = flow_control::http_response(&org_id, PROTOCOL_HTTP, &e) {
+                    resp
+                } else if matches!(e, infra::errors::Error::ResourceError(_)) {
                     HttpResponse::ServiceUnavailable().json(MetaHttpResponse::error(
                         http::StatusCode::SERVICE_UNAVAILABLE,
                         e,
@@ -420,7 +435,9 @@ pub async fn otlp_logs_write(
                 in_stream_name,
                 e
             );
-            if matches!(e, infra::errors::Error::ResourceError(_)) {
+            if let Some(resp) = flow_control::http_response(&org_id, PROTOCOL_HTTP, &e) {
+                resp
+            } else if matches!(e, infra::errors::Error::ResourceError(_)) {
                 HttpResponse::ServiceUnavailable().json(MetaHttpResponse::error(
                     http::StatusCode::SERVICE_UNAVAILABLE,
                     e,
@@ -480,7 +497,9 @@ pub async fn hec(
         Err(e) => {
             log::error!("Error processing request {org_id}/_hec: {e}");
             let res = HecResponse::from(HecStatus::Custom(e.to_string(), 400));
-            if matches!(e, infra::errors::Error::ResourceError(_)) {
+            if let Some(resp) = flow_control::http_response(&org_id, PROTOCOL_HTTP, &e) {
+                resp
+            } else if matches!(e, infra::errors::Error::ResourceError(_)) {
                 HttpResponse::ServiceUnavailable().json(res)
             } else {
                 HttpResponse::BadRequest().json(res)
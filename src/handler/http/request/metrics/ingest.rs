@@ -20,7 +20,10 @@ use actix_web::{HttpRequest, HttpResponse, http, post, web};
 use crate::{
     common::meta::http::HttpResponse as MetaHttpResponse,
     handler::http::request::{CONTENT_TYPE_JSON, CONTENT_TYPE_PROTO},
-    service::metrics,
+    service::{
+        ingestion::flow_control::{self, PROTOCOL_HTTP},
+        metrics,
+    },
 };
 
 /// _json ingestion API
@@ -41,16 +44,31 @@ use crate::{
     )
 )]
 #[post("/{org_id}/ingest/metrics/_json")]
-pub async fn json(org_id: web::Path<String>, body: web::Bytes) -> Result<HttpResponse, Error> {
+pub async fn json(
+    org_id: web::Path<String>,
+    req: HttpRequest,
+    body: web::Bytes,
+) -> Result<HttpResponse, Error> {
     let org_id = org_id.into_inner();
-    Ok(match metrics::json::ingest(&org_id, body).await {
-        Ok(v) => HttpResponse::Ok().json(v),
-        Err(e) => {
-            log::error!("Error processing request {org_id}/metrics/_json: {e}");
-            HttpResponse::BadRequest()
-                .json(MetaHttpResponse::error(http::StatusCode::BAD_REQUEST, e))
-        }
-    })
+    let user_email = req.headers().get("user_id").unwrap().to_str().unwrap();
+    Ok(
+        match metrics::json::ingest(&org_id, user_email, body).await {
+            Ok(v) => HttpResponse::Ok().json(v),
+            #[cfg(not(feature = "enterprise"))]
+            Err(e) if e.is::<crate::service::grants::GrantError>() => {
+                MetaHttpResponse::forbidden(e)
+            }
+            Err(e) => {
+                log::error!("Error processing request {org_id}/metrics/_json: {e}");
+                e.downcast_ref::<infra::errors::Error>()
+                    .and_then(|err| flow_control::http_response(&org_id, PROTOCOL_HTTP, err))
+                    .unwrap_or_else(|| {
+                        HttpResponse::BadRequest()
+                            .json(MetaHttpResponse::error(http::StatusCode::BAD_REQUEST, e))
+                    })
+            }
+        },
+    )
 }
 
 /// MetricsIngest
@@ -73,10 +91,11 @@ pub async fn otlp_metrics_write(
 ) -> Result<HttpResponse, Error> {
     let org_id = org_id.into_inner();
     let content_type = req.headers().get("Content-Type").unwrap().to_str().unwrap();
+    let user_email = req.headers().get("user_id").unwrap().to_str().unwrap();
     if content_type.eq(CONTENT_TYPE_PROTO) {
-        metrics::otlp::otlp_proto(&org_id, body).await
+        metrics::otlp::otlp_proto(&org_id, user_email, body).await
     } else if content_type.starts_with(CONTENT_TYPE_JSON) {
-        metrics::otlp::otlp_json(&org_id, body).await
+        metrics::otlp::otlp_json(&org_id, user_email, body).await
     } else {
         Ok(HttpResponse::BadRequest().json(MetaHttpResponse::error(
             http::StatusCode::BAD_REQUEST,
@@ -17,15 +17,18 @@ pub mod actions;
 #[cfg(feature = "enterprise")]
 pub mod ai;
 pub mod alerts;
+pub mod api_tokens;
 pub mod authz;
 #[cfg(feature = "cloud")]
 pub mod billings;
 pub mod clusters;
 pub mod dashboards;
+pub mod encryption;
 pub mod enrichment_table;
 #[allow(deprecated)]
 pub mod folders;
 pub mod functions;
+pub mod grants;
 pub mod keys;
 pub mod kv;
 pub mod logs;
@@ -33,8 +36,11 @@ pub mod metrics;
 pub mod organization;
 pub mod pipeline;
 pub mod promql;
+pub mod quotas;
 pub mod ratelimit;
 pub mod rum;
+#[cfg(not(feature = "enterprise"))]
+pub mod scim;
 #[cfg(feature = "enterprise")]
 pub mod script_server;
 pub mod search;
@@ -107,6 +107,24 @@ async fn create(
         data.enable_streaming_search = enable_streaming_search;
     }
 
+    if let Some(red_metrics_dimensions) = settings.red_metrics_dimensions {
+        field_found = true;
+        data.red_metrics_dimensions = red_metrics_dimensions
+            .into_iter()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .collect();
+    }
+
+    if let Some(session_idle_timeout) = settings.session_idle_timeout {
+        field_found = true;
+        data.session_idle_timeout = session_idle_timeout;
+    }
+    if let Some(max_concurrent_sessions) = settings.max_concurrent_sessions {
+        field_found = true;
+        data.max_concurrent_sessions = max_concurrent_sessions;
+    }
+
     if !field_found {
         return Ok(MetaHttpResponse::bad_request("No valid field found"));
     }
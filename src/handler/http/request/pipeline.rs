@@ -17,7 +17,10 @@ use std::io::Error;
 
 use actix_web::{HttpRequest, HttpResponse, delete, get, http, post, put, web};
 use ahash::HashMap;
-use config::{ider, meta::pipeline::Pipeline};
+use config::{
+    ider,
+    meta::pipeline::{Pipeline, PipelineDryRunRequest},
+};
 
 use crate::{
     common::meta::http::HttpResponse as MetaHttpResponse,
@@ -30,6 +33,7 @@ impl From<PipelineError> for HttpResponse {
             PipelineError::InfraError(err) => MetaHttpResponse::internal_error(err),
             PipelineError::NotFound(_) => MetaHttpResponse::not_found(value),
             PipelineError::Modified(_) => MetaHttpResponse::conflict(value),
+            PipelineError::DryRunForbidden(_) => MetaHttpResponse::forbidden(value),
             error => MetaHttpResponse::bad_request(error),
         }
     }
@@ -74,6 +78,43 @@ pub async fn save_pipeline(
     }
 }
 
+/// DryRunPipeline
+///
+/// Runs a pipeline over sample records, or over the records its source produced
+/// in a past time range, and returns what reached every node and destination.
+/// Nothing is written.
+///
+/// #{"ratelimit_module":"Pipeline", "ratelimit_module_operation":"create"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Pipeline",
+    operation_id = "dryRunPipeline",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    request_body(content = PipelineDryRunRequest, description = "Pipeline and records to run it on", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = PipelineDryRunResponse),
+        (status = 400, description = "Failure", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[post("/{org_id}/pipelines/dry_run")]
+pub async fn dry_run_pipeline(
+    path: web::Path<String>,
+    req: web::Json<PipelineDryRunRequest>,
+    in_req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let org_id = path.into_inner();
+    let user_email = in_req.headers().get("user_id").unwrap().to_str().unwrap();
+    match pipeline::dry_run_pipeline(&org_id, user_email, req.into_inner()).await {
+        Ok(resp) => Ok(HttpResponse::Ok().json(resp)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
 /// ListPipelines
 ///
 /// #{"ratelimit_module":"Pipeline", "ratelimit_module_operation":"list"}#
@@ -24,7 +24,10 @@ use {config::meta::stream::StreamType, o2_openfga::meta::mapping::OFGA_MODELS};
 
 use crate::{
     common::{meta::http::HttpResponse as MetaHttpResponse, utils::http::get_or_create_trace_id},
-    service::{metrics, promql},
+    service::{
+        ingestion::flow_control::{self, PROTOCOL_HTTP},
+        metrics, promql,
+    },
 };
 
 /// prometheus remote-write endpoint for metrics
@@ -53,11 +56,23 @@ pub async fn remote_write(
     let org_id = org_id.into_inner();
     let content_type = req.headers().get("Content-Type").unwrap().to_str().unwrap();
     if content_type == "application/x-protobuf" {
-        Ok(match metrics::prom::remote_write(&org_id, body).await {
-            Ok(_) => HttpResponse::Ok().into(),
-            Err(e) => HttpResponse::BadRequest()
-                .json(MetaHttpResponse::error(http::StatusCode::BAD_REQUEST, e)),
-        })
+        let user_email = req.headers().get("user_id").unwrap().to_str().unwrap();
+        Ok(
+            match metrics::prom::remote_write(&org_id, user_email, body).await {
+                Ok(_) => HttpResponse::Ok().into(),
+                #[cfg(not(feature = "enterprise"))]
+                Err(e) if e.is::<crate::service::grants::GrantError>() => {
+                    MetaHttpResponse::forbidden(e)
+                }
+                Err(e) => e
+                    .downcast_ref::<errors::Error>()
+                    .and_then(|err| flow_control::http_response(&org_id, PROTOCOL_HTTP, err))
+                    .unwrap_or_else(|| {
+                        HttpResponse::BadRequest()
+                            .json(MetaHttpResponse::error(http::StatusCode::BAD_REQUEST, e))
+                    }),
+            },
+        )
     } else {
         Ok(HttpResponse::BadRequest().json(MetaHttpResponse::error(
             http::StatusCode::BAD_REQUEST,
@@ -0,0 +1,156 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io::Error;
+
+use actix_web::{HttpRequest, HttpResponse, delete, get, http, put, web};
+use config::meta::quota::OrgQuota;
+
+use crate::{
+    common::meta::http::HttpResponse as MetaHttpResponse,
+    service::quotas::{self, QuotaError},
+};
+
+impl From<QuotaError> for HttpResponse {
+    fn from(value: QuotaError) -> Self {
+        match value {
+            QuotaError::InfraError(err) => MetaHttpResponse::internal_error(err),
+            QuotaError::NotFound(_) => MetaHttpResponse::not_found(value),
+            QuotaError::Forbidden(_) => MetaHttpResponse::forbidden(value),
+            error => MetaHttpResponse::bad_request(error),
+        }
+    }
+}
+
+/// GetOrgQuota
+///
+/// #{"ratelimit_module":"Quotas", "ratelimit_module_operation":"get"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Quotas",
+    operation_id = "GetOrgQuota",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = OrgQuota),
+        (status = 404, description = "NotFound", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[get("/{org_id}/quotas")]
+pub async fn get(path: web::Path<String>) -> Result<HttpResponse, Error> {
+    let org_id = path.into_inner();
+    match quotas::get(&org_id).await {
+        Ok(quota) => Ok(HttpResponse::Ok().json(quota)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+/// SetOrgQuota
+///
+/// #{"ratelimit_module":"Quotas", "ratelimit_module_operation":"update"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Quotas",
+    operation_id = "SetOrgQuota",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    request_body(content = OrgQuota, description = "Quota data", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = OrgQuota),
+        (status = 400, description = "Failure", content_type = "application/json", body = HttpResponse),
+        (status = 403, description = "Forbidden", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[put("/{org_id}/quotas")]
+pub async fn set(
+    path: web::Path<String>,
+    body: web::Json<OrgQuota>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let org_id = path.into_inner();
+    match quotas::set(&org_id, get_user_id(&req), body.into_inner()).await {
+        Ok(quota) => Ok(HttpResponse::Ok().json(quota)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+/// DeleteOrgQuota
+///
+/// #{"ratelimit_module":"Quotas", "ratelimit_module_operation":"delete"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Quotas",
+    operation_id = "DeleteOrgQuota",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = HttpResponse),
+        (status = 404, description = "NotFound", content_type = "application/json", body = HttpResponse),
+        (status = 403, description = "Forbidden", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[delete("/{org_id}/quotas")]
+pub async fn delete(path: web::Path<String>, req: HttpRequest) -> Result<HttpResponse, Error> {
+    let org_id = path.into_inner();
+    match quotas::delete(&org_id, get_user_id(&req)).await {
+        Ok(()) => Ok(HttpResponse::Ok().json(MetaHttpResponse::message(
+            http::StatusCode::OK,
+            "Quota deleted successfully",
+        ))),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+/// GetOrgQuotaUsage
+///
+/// #{"ratelimit_module":"Quotas", "ratelimit_module_operation":"get"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Quotas",
+    operation_id = "GetOrgQuotaUsage",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = OrgQuotaUsage),
+    )
+)]
+#[get("/{org_id}/quotas/usage")]
+pub async fn usage(path: web::Path<String>) -> Result<HttpResponse, Error> {
+    let org_id = path.into_inner();
+    Ok(HttpResponse::Ok().json(quotas::usage(&org_id)))
+}
+
+fn get_user_id(req: &HttpRequest) -> &str {
+    req.headers()
+        .get("user_id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+}
@@ -0,0 +1,296 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! SCIM 2.0 endpoints of the open source build. They follow RFC 7644 rather
+//! than the conventions of the other APIs, so they aren't in the OpenAPI docs.
+
+use std::io::Error;
+
+use actix_web::{HttpRequest, HttpResponse, delete, get, http::StatusCode, patch, post, put, web};
+use config::{
+    meta::scim::{SCIM_CONTENT_TYPE, ScimErrorResponse},
+    utils::json,
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::service::scim::{self, ScimError};
+
+impl From<ScimError> for HttpResponse {
+    fn from(value: ScimError) -> Self {
+        let (status, scim_type) = match &value {
+            ScimError::Disabled | ScimError::NotFound(_) => (StatusCode::NOT_FOUND, None),
+            ScimError::Forbidden => (StatusCode::FORBIDDEN, None),
+            ScimError::Conflict(_) => (StatusCode::CONFLICT, Some("uniqueness")),
+            ScimError::InvalidFilter(_) => (StatusCode::BAD_REQUEST, Some("invalidFilter")),
+            ScimError::InvalidValue(_) => (StatusCode::BAD_REQUEST, Some("invalidValue")),
+            ScimError::InfraError(_) => (StatusCode::INTERNAL_SERVER_ERROR, None),
+        };
+        scim_response(
+            status,
+            &ScimErrorResponse::new(status.as_u16(), scim_type, value),
+        )
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListQuery {
+    filter: Option<String>,
+    start_index: Option<usize>,
+    count: Option<usize>,
+}
+
+#[get("/{org_id}/scim/v2/Users")]
+pub async fn list_users(
+    path: web::Path<String>,
+    query: web::Query<ListQuery>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let org_id = path.into_inner();
+    if let Err(e) = scim::check_access(&org_id, get_user_id(&req)).await {
+        return Ok(e.into());
+    }
+    match scim::list_users(
+        &org_id,
+        query.filter.as_deref(),
+        query.start_index,
+        query.count,
+    )
+    .await
+    {
+        Ok(users) => Ok(scim_response(StatusCode::OK, &users)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+#[post("/{org_id}/scim/v2/Users")]
+pub async fn create_user(
+    path: web::Path<String>,
+    body: web::Bytes,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let org_id = path.into_inner();
+    if let Err(e) = scim::check_access(&org_id, get_user_id(&req)).await {
+        return Ok(e.into());
+    }
+    let user = match parse_body(&body) {
+        Ok(user) => user,
+        Err(e) => return Ok(e.into()),
+    };
+    match scim::create_user(&org_id, user).await {
+        Ok(user) => Ok(scim_response(StatusCode::CREATED, &user)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+#[get("/{org_id}/scim/v2/Users/{id}")]
+pub async fn get_user(
+    path: web::Path<(String, String)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let (org_id, id) = path.into_inner();
+    if let Err(e) = scim::check_access(&org_id, get_user_id(&req)).await {
+        return Ok(e.into());
+    }
+    match scim::get_user(&org_id, &id).await {
+        Ok(user) => Ok(scim_response(StatusCode::OK, &user)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+#[put("/{org_id}/scim/v2/Users/{id}")]
+pub async fn replace_user(
+    path: web::Path<(String, String)>,
+    body: web::Bytes,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let (org_id, id) = path.into_inner();
+    if let Err(e) = scim::check_access(&org_id, get_user_id(&req)).await {
+        return Ok(e.into());
+    }
+    let user = match parse_body(&body) {
+        Ok(user) => user,
+        Err(e) => return Ok(e.into()),
+    };
+    match scim::replace_user(&org_id, &id, user).await {
+        Ok(user) => Ok(scim_response(StatusCode::OK, &user)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+#[patch("/{org_id}/scim/v2/Users/{id}")]
+pub async fn patch_user(
+    path: web::Path<(String, String)>,
+    body: web::Bytes,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let (org_id, id) = path.into_inner();
+    if let Err(e) = scim::check_access(&org_id, get_user_id(&req)).await {
+        return Ok(e.into());
+    }
+    let patch = match parse_body(&body) {
+        Ok(patch) => patch,
+        Err(e) => return Ok(e.into()),
+    };
+    match scim::patch_user(&org_id, &id, patch).await {
+        Ok(user) => Ok(scim_response(StatusCode::OK, &user)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+#[delete("/{org_id}/scim/v2/Users/{id}")]
+pub async fn delete_user(
+    path: web::Path<(String, String)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let (org_id, id) = path.into_inner();
+    if let Err(e) = scim::check_access(&org_id, get_user_id(&req)).await {
+        return Ok(e.into());
+    }
+    match scim::delete_user(&org_id, &id).await {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+#[get("/{org_id}/scim/v2/Groups")]
+pub async fn list_groups(
+    path: web::Path<String>,
+    query: web::Query<ListQuery>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let org_id = path.into_inner();
+    if let Err(e) = scim::check_access(&org_id, get_user_id(&req)).await {
+        return Ok(e.into());
+    }
+    match scim::list_groups(
+        &org_id,
+        query.filter.as_deref(),
+        query.start_index,
+        query.count,
+    )
+    .await
+    {
+        Ok(groups) => Ok(scim_response(StatusCode::OK, &groups)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+#[post("/{org_id}/scim/v2/Groups")]
+pub async fn create_group(
+    path: web::Path<String>,
+    body: web::Bytes,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let org_id = path.into_inner();
+    if let Err(e) = scim::check_access(&org_id, get_user_id(&req)).await {
+        return Ok(e.into());
+    }
+    let group = match parse_body(&body) {
+        Ok(group) => group,
+        Err(e) => return Ok(e.into()),
+    };
+    match scim::create_group(&org_id, group).await {
+        Ok(group) => Ok(scim_response(StatusCode::CREATED, &group)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+#[get("/{org_id}/scim/v2/Groups/{id}")]
+pub async fn get_group(
+    path: web::Path<(String, String)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let (org_id, id) = path.into_inner();
+    if let Err(e) = scim::check_access(&org_id, get_user_id(&req)).await {
+        return Ok(e.into());
+    }
+    match scim::get_group(&org_id, &id).await {
+        Ok(group) => Ok(scim_response(StatusCode::OK, &group)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+#[put("/{org_id}/scim/v2/Groups/{id}")]
+pub async fn replace_group(
+    path: web::Path<(String, String)>,
+    body: web::Bytes,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let (org_id, id) = path.into_inner();
+    if let Err(e) = scim::check_access(&org_id, get_user_id(&req)).await {
+        return Ok(e.into());
+    }
+    let group = match parse_body(&body) {
+        Ok(group) => group,
+        Err(e) => return Ok(e.into()),
+    };
+    match scim::replace_group(&org_id, &id, group).await {
+        Ok(group) => Ok(scim_response(StatusCode::OK, &group)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+#[patch("/{org_id}/scim/v2/Groups/{id}")]
+pub async fn patch_group(
+    path: web::Path<(String, String)>,
+    body: web::Bytes,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let (org_id, id) = path.into_inner();
+    if let Err(e) = scim::check_access(&org_id, get_user_id(&req)).await {
+        return Ok(e.into());
+    }
+    let patch = match parse_body(&body) {
+        Ok(patch) => patch,
+        Err(e) => return Ok(e.into()),
+    };
+    match scim::patch_group(&org_id, &id, patch).await {
+        Ok(group) => Ok(scim_response(StatusCode::OK, &group)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+#[delete("/{org_id}/scim/v2/Groups/{id}")]
+pub async fn delete_group(
+    path: web::Path<(String, String)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let (org_id, id) = path.into_inner();
+    if let Err(e) = scim::check_access(&org_id, get_user_id(&req)).await {
+        return Ok(e.into());
+    }
+    match scim::delete_group(&org_id, &id).await {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+fn scim_response<T: Serialize>(status: StatusCode, body: &T) -> HttpResponse {
+    HttpResponse::build(status)
+        .content_type(SCIM_CONTENT_TYPE)
+        .json(body)
+}
+
+fn parse_body<T: DeserializeOwned>(body: &[u8]) -> Result<T, ScimError> {
+    json::from_slice(body).map_err(|e| ScimError::InvalidValue(e.to_string()))
+}
+
+fn get_user_id(req: &HttpRequest) -> &str {
+    req.headers()
+        .get("user_id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+}
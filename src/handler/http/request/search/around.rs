@@ -222,6 +222,16 @@ pub(crate) async fn around(
     resp.scan_size = resp_forward.scan_size + resp_backward.scan_size;
     resp.took = resp_forward.took + resp_backward.took;
     resp.cached_ratio = (resp_forward.cached_ratio + resp_backward.cached_ratio) / 2;
+    if let Some(masker) = SearchService::masking::get_masker(
+        org_id,
+        stream_type,
+        &[stream_name.to_string()],
+        user_id.as_deref(),
+    )
+    .await
+    {
+        masker.mask_response(&mut resp);
+    }
 
     let time = start.elapsed().as_secs_f64();
     http_report_metrics(start, org_id, stream_type, "200", "_around", "", "");
@@ -18,16 +18,17 @@ use infra::errors;
 
 use crate::{
     common::meta::http::HttpResponse as MetaHttpResponse, handler::http::router::ERROR_HEADER,
+    service::ingestion::flow_control::RETRY_AFTER_HEADER,
 };
 
 pub fn map_error_to_http_response(err: &errors::Error, trace_id: Option<String>) -> HttpResponse {
     match err {
         errors::Error::ErrorCode(code) => match code {
-            errors::ErrorCodes::SearchCancelQuery(_) | errors::ErrorCodes::RatelimitExceeded(_) => {
-                HttpResponse::TooManyRequests()
-                    .append_header((ERROR_HEADER, code.to_json()))
-                    .json(MetaHttpResponse::error_code_with_trace_id(code, trace_id))
-            }
+            errors::ErrorCodes::SearchCancelQuery(_)
+            | errors::ErrorCodes::RatelimitExceeded(_)
+            | errors::ErrorCodes::QuotaExceeded(_) => HttpResponse::TooManyRequests()
+                .append_header((ERROR_HEADER, code.to_json()))
+                .json(MetaHttpResponse::error_code_with_trace_id(code, trace_id)),
             errors::ErrorCodes::SearchTimeout(_) => HttpResponse::RequestTimeout()
                 .append_header((ERROR_HEADER, code.to_json()))
                 .json(MetaHttpResponse::error_code_with_trace_id(code, trace_id)),
@@ -47,6 +48,10 @@ pub fn map_error_to_http_response(err: &errors::Error, trace_id: Option<String>)
                 .append_header((ERROR_HEADER, code.to_json()))
                 .json(MetaHttpResponse::error_code_with_trace_id(code, trace_id)),
         },
+        errors::Error::IngestionThrottled { retry_after, .. } => HttpResponse::TooManyRequests()
+            .append_header((RETRY_AFTER_HEADER, retry_after.to_string()))
+            .append_header((ERROR_HEADER, err.to_string()))
+            .json(MetaHttpResponse::error(StatusCode::TOO_MANY_REQUESTS, err)),
         errors::Error::ResourceError(_) => HttpResponse::ServiceUnavailable()
             .append_header((ERROR_HEADER, err.to_string()))
             .json(MetaHttpResponse::error(
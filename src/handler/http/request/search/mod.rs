@@ -31,7 +31,6 @@ use config::{
 use error_utils::map_error_to_http_response;
 use hashbrown::HashMap;
 use tracing::{Instrument, Span};
-#[cfg(feature = "enterprise")]
 use utils::check_stream_permissions;
 
 #[cfg(feature = "enterprise")]
@@ -50,6 +49,7 @@ use crate::{
         },
     },
     service::{
+        api_tokens,
         db::enrichment_table,
         metadata::distinct_values::DISTINCT_STREAM_PREFIX,
         search as SearchService,
@@ -59,6 +59,7 @@ use crate::{
 
 pub(crate) mod around;
 pub(crate) mod error_utils;
+pub mod multi_orgs;
 pub mod multi_streams;
 pub mod query_manager;
 pub mod saved_view;
@@ -278,12 +279,14 @@ pub async fn search(
         }
 
         // Check permissions on stream
-        #[cfg(feature = "enterprise")]
         if let Some(res) =
             check_stream_permissions(&stream_name, &org_id, &user_id, &stream_type).await
         {
             return Ok(res);
         }
+        if !api_tokens::check_stream(&in_req, &stream_name) {
+            return Ok(MetaHttpResponse::forbidden("Unauthorized Access"));
+        }
     }
 
     #[cfg(feature = "enterprise")]
@@ -349,7 +352,7 @@ pub async fn search(
         &trace_id,
         &org_id,
         stream_type,
-        Some(user_id),
+        Some(user_id.clone()),
         &req,
         range_error,
         false,
@@ -358,6 +361,14 @@ pub async fn search(
     .await;
     match res {
         Ok(mut res) => {
+            SearchService::masking::mask_response(
+                &org_id,
+                stream_type,
+                &req.query.sql,
+                Some(&user_id),
+                &mut res,
+            )
+            .await;
             res.set_took(start.elapsed().as_millis() as usize);
             Ok(HttpResponse::Ok().json(res))
         }
@@ -1060,6 +1071,16 @@ async fn values_v1(
         .await
         .unwrap_or(Schema::empty());
 
+    // the values of masked fields are not listed, the patterns over all the
+    // fields are applied to the values of the others
+    let masker = SearchService::masking::get_masker(
+        org_id,
+        stream_type,
+        &[stream_name.to_string()],
+        Some(user_id),
+    )
+    .await;
+
     let mut query_results = Vec::with_capacity(fields.len());
     let sql_where = if where_str.is_empty() {
         "".to_string()
@@ -1069,7 +1090,9 @@ async fn values_v1(
     for field in &fields {
         let http_span = http_span.clone();
         // skip values for field which aren't part of the schema
-        if schema.field_with_name(field).is_err() {
+        if schema.field_with_name(field).is_err()
+            || masker.as_ref().is_some_and(|m| m.is_masked(field))
+        {
             continue;
         }
         let sql_where = if !sql_where.is_empty() && !keyword.is_empty() {
@@ -1231,6 +1254,9 @@ async fn values_v1(
     resp.total = fields.len();
     resp.hits = hit_values;
     resp.size = size;
+    if let Some(masker) = masker.as_ref() {
+        masker.mask_values_response(&mut resp);
+    }
     resp.took = start.elapsed().as_millis() as usize;
 
     let time = start.elapsed().as_secs_f64();
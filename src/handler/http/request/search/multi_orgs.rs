@@ -0,0 +1,285 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io::Error;
+
+use actix_web::{HttpRequest, HttpResponse, post, web};
+use config::{
+    get_config,
+    meta::{
+        search::{MultiOrgSearchPartitionRequest, MultiOrgSearchRequest},
+        sql::resolve_stream_names,
+        stream::StreamType,
+    },
+    utils::json,
+};
+use hashbrown::HashMap;
+use tracing::{Instrument, Span};
+
+use crate::{
+    common::{
+        meta::http::HttpResponse as MetaHttpResponse,
+        utils::{
+            auth::is_root_user,
+            http::{
+                get_or_create_trace_id, get_search_type_from_request, get_stream_type_from_request,
+            },
+        },
+    },
+    handler::http::request::search::{
+        error_utils::map_error_to_http_response, utils::check_stream_permissions,
+    },
+    service::{search::multi_orgs, self_reporting::http_report_metrics, users},
+};
+
+/// SearchMultiOrgs
+///
+/// #{"ratelimit_module":"Search", "ratelimit_module_operation":"get"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Search",
+    operation_id = "SearchMultiOrgs",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name of the user"),
+    ),
+    request_body(content = MultiOrgSearchRequest, description = "Search query and the organizations to run it in", content_type = "application/json", example = json!({
+        "orgs": ["tenant_a", "tenant_b"],
+        "query": {
+            "sql": "select * from k8s where match_all('redis timeout')",
+            "start_time": 1675182660872049i64,
+            "end_time": 1675185660872049i64,
+            "from": 0,
+            "size": 100
+        }
+    })),
+    responses(
+        (status = 200, description = "Success, each hit has the `_org` field", content_type = "application/json", body = SearchResponse),
+        (status = 400, description = "Failure", content_type = "application/json", body = HttpResponse),
+        (status = 403, description = "Forbidden", content_type = "application/json", body = HttpResponse),
+        (status = 500, description = "Failure", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[post("/{org_id}/_search_orgs")]
+pub async fn search_orgs(
+    org_id: web::Path<String>,
+    in_req: HttpRequest,
+    body: web::Bytes,
+) -> Result<HttpResponse, Error> {
+    let start = std::time::Instant::now();
+    let cfg = get_config();
+
+    let org_id = org_id.into_inner();
+    let http_span = if cfg.common.tracing_search_enabled {
+        tracing::info_span!("/api/{org_id}/_search_orgs", org_id = org_id.clone())
+    } else {
+        Span::none()
+    };
+    let trace_id = get_or_create_trace_id(in_req.headers(), &http_span);
+
+    let user_id = get_user_id(&in_req);
+    if !multi_orgs::can_search(user_id) {
+        return Ok(MetaHttpResponse::forbidden(
+            "Not allowed to search across organizations",
+        ));
+    }
+
+    let query = web::Query::<HashMap<String, String>>::from_query(in_req.query_string()).unwrap();
+    let stream_type = get_stream_type_from_request(&query).unwrap_or_default();
+    let search_type = match get_search_type_from_request(&query) {
+        Ok(v) => v,
+        Err(e) => return Ok(MetaHttpResponse::bad_request(e)),
+    };
+
+    let mut req: MultiOrgSearchRequest = match json::from_slice(&body) {
+        Ok(v) => v,
+        Err(e) => return Ok(MetaHttpResponse::bad_request(e)),
+    };
+    if let Err(e) = req.request.decode() {
+        return Ok(MetaHttpResponse::bad_request(e));
+    }
+    if let Ok(sql) =
+        config::utils::query_select_utils::replace_o2_custom_patterns(&req.request.query.sql)
+    {
+        req.request.query.sql = sql;
+    }
+    req.request.search_type = search_type;
+    if let Some(res) = check_access(user_id, stream_type, &req.orgs, &req.request.query.sql).await {
+        return Ok(res);
+    }
+
+    let res = multi_orgs::search(&trace_id, user_id, stream_type, &req)
+        .instrument(http_span)
+        .await;
+    match res {
+        Ok(res) => {
+            http_report_metrics(start, &org_id, stream_type, "200", "_search_orgs", "", "");
+            Ok(HttpResponse::Ok().json(res))
+        }
+        Err(err) => {
+            http_report_metrics(start, &org_id, stream_type, "500", "_search_orgs", "", "");
+            log::error!("[trace_id {trace_id}] search_orgs error: {:?}", err);
+            Ok(map_error_to_http_response(&err, Some(trace_id)))
+        }
+    }
+}
+
+/// SearchMultiOrgsPartition
+///
+/// #{"ratelimit_module":"Search", "ratelimit_module_operation":"get"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Search",
+    operation_id = "SearchMultiOrgsPartition",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name of the user"),
+    ),
+    request_body(content = MultiOrgSearchPartitionRequest, description = "Search query and the organizations to run it in", content_type = "application/json", example = json!({
+        "orgs": ["tenant_a", "tenant_b"],
+        "sql": "select * from k8s ",
+        "start_time": 1675182660872049i64,
+        "end_time": 1675185660872049i64
+    })),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = SearchPartitionResponse),
+        (status = 400, description = "Failure", content_type = "application/json", body = HttpResponse),
+        (status = 403, description = "Forbidden", content_type = "application/json", body = HttpResponse),
+        (status = 500, description = "Failure", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[post("/{org_id}/_search_orgs_partition")]
+pub async fn search_orgs_partition(
+    org_id: web::Path<String>,
+    in_req: HttpRequest,
+    body: web::Bytes,
+) -> Result<HttpResponse, Error> {
+    let start = std::time::Instant::now();
+    let cfg = get_config();
+
+    let org_id = org_id.into_inner();
+    let http_span = if cfg.common.tracing_search_enabled {
+        tracing::info_span!(
+            "/api/{org_id}/_search_orgs_partition",
+            org_id = org_id.clone()
+        )
+    } else {
+        Span::none()
+    };
+    let trace_id = get_or_create_trace_id(in_req.headers(), &http_span);
+
+    let user_id = get_user_id(&in_req);
+    if !multi_orgs::can_search(user_id) {
+        return Ok(MetaHttpResponse::forbidden(
+            "Not allowed to search across organizations",
+        ));
+    }
+
+    let query = web::Query::<HashMap<String, String>>::from_query(in_req.query_string()).unwrap();
+    let stream_type = get_stream_type_from_request(&query).unwrap_or_default();
+
+    let mut req: MultiOrgSearchPartitionRequest = match json::from_slice(&body) {
+        Ok(v) => v,
+        Err(e) => return Ok(MetaHttpResponse::bad_request(e)),
+    };
+    if let Ok(sql) = config::utils::query_select_utils::replace_o2_custom_patterns(&req.request.sql)
+    {
+        req.request.sql = sql;
+    }
+    if let Err(e) = req.request.decode() {
+        return Ok(MetaHttpResponse::bad_request(e));
+    }
+    if let Some(res) = check_access(user_id, stream_type, &req.orgs, &req.request.sql).await {
+        return Ok(res);
+    }
+
+    let res = multi_orgs::search_partition_multi(&trace_id, user_id, stream_type, &req)
+        .instrument(http_span)
+        .await;
+    match res {
+        Ok(res) => {
+            http_report_metrics(
+                start,
+                &org_id,
+                stream_type,
+                "200",
+                "_search_orgs_partition",
+                "",
+                "",
+            );
+            Ok(HttpResponse::Ok().json(res))
+        }
+        Err(err) => {
+            http_report_metrics(
+                start,
+                &org_id,
+                stream_type,
+                "500",
+                "_search_orgs_partition",
+                "",
+                "",
+            );
+            log::error!(
+                "[trace_id {trace_id}] search_orgs_partition error: {:?}",
+                err
+            );
+            Ok(map_error_to_http_response(&err, Some(trace_id)))
+        }
+    }
+}
+
+/// Checks that a user who isn't root is a member of every org and can read
+/// the streams of the query there, being allowed to search across orgs
+/// doesn't grant access to the orgs themselves.
+async fn check_access(
+    user_id: &str,
+    stream_type: StreamType,
+    orgs: &[String],
+    sql: &str,
+) -> Option<HttpResponse> {
+    if is_root_user(user_id) {
+        return None;
+    }
+    let stream_names = match resolve_stream_names(sql) {
+        Ok(v) => v,
+        Err(e) => return Some(MetaHttpResponse::bad_request(e)),
+    };
+    for org_id in orgs.iter().map(|org| org.trim()) {
+        if users::get_user(Some(org_id), user_id).await.is_none() {
+            return Some(MetaHttpResponse::forbidden(format!(
+                "Not a member of the organization {org_id}"
+            )));
+        }
+        for stream_name in stream_names.iter() {
+            if let Some(res) =
+                check_stream_permissions(stream_name, org_id, user_id, &stream_type).await
+            {
+                return Some(res);
+            }
+        }
+    }
+    None
+}
+
+fn get_user_id(req: &HttpRequest) -> &str {
+    req.headers()
+        .get("user_id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+}
@@ -219,6 +219,17 @@ pub async fn search_multi(
         }
 
         // Check permissions on stream
+        #[cfg(not(feature = "enterprise"))]
+        if let Some(res) = crate::handler::http::request::search::utils::check_stream_permissions(
+            &stream_name,
+            &org_id,
+            user_id,
+            &stream_type,
+        )
+        .await
+        {
+            return Ok(res);
+        }
         #[cfg(feature = "enterprise")]
         {
             use o2_openfga::meta::mapping::OFGA_MODELS;
@@ -336,6 +347,14 @@ pub async fn search_multi(
 
         match search_res {
             Ok(mut res) => {
+                SearchService::masking::mask_response(
+                    &org_id,
+                    stream_type,
+                    &req.query.sql,
+                    Some(user_id),
+                    &mut res,
+                )
+                .await;
                 let time = start.elapsed().as_secs_f64();
                 metrics::HTTP_RESPONSE_TIME
                     .with_label_values(&[
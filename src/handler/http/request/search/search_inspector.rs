@@ -28,8 +28,6 @@ use serde::{Deserialize, Serialize};
 use serde_json;
 use tracing::{Instrument, Span};
 
-#[cfg(feature = "enterprise")]
-use crate::handler::http::request::search::utils::check_stream_permissions;
 #[cfg(feature = "enterprise")]
 use crate::service::search::sql::get_cipher_key_names;
 use crate::{
@@ -40,7 +38,7 @@ use crate::{
             stream::get_settings_max_query_range,
         },
     },
-    handler::http::request::search::error_utils,
+    handler::http::request::search::{error_utils, utils::check_stream_permissions},
     service::{
         search::inspector::{SearchInspectorFields, extract_search_inspector_fields},
         self_reporting::http_report_metrics,
@@ -190,7 +188,6 @@ pub async fn get_search_profile(
     }
 
     // Check permissions on stream
-    #[cfg(feature = "enterprise")]
     if let Some(res) = check_stream_permissions(&stream_name, &org_id, &user_id, &stream_type).await
     {
         return Ok(res);
@@ -435,13 +435,20 @@ pub async fn get_job_result(
         if let Some(res) = check_permissions(&model, &org_id, &user_id).await {
             return Ok(res);
         }
+        let masker = crate::service::search::masking::get_masker(
+            &org_id,
+            StreamType::from(model.stream_type.as_str()),
+            &json::from_str::<Vec<String>>(&model.stream_names).unwrap_or_default(),
+            Some(&user_id),
+        )
+        .await;
 
         if let Some(msg) = model.error_message {
             Ok(MetaHttpResponse::ok(format!(
                 "job_id: {job_id} error: {msg}",
             )))
         } else if model.status == 1 && model.partition_num != Some(1) {
-            let response = get_partition_result(&model, from, size).await;
+            let response = get_partition_result(&model, from, size, masker.as_ref()).await;
             Ok(response)
         } else if model.result_path.is_none() || model.cluster.is_none() {
             Ok(MetaHttpResponse::not_found(format!(
@@ -454,7 +461,11 @@ pub async fn get_job_result(
             if let Err(e) = response {
                 return Ok(MetaHttpResponse::internal_error(e));
             }
-            Ok(HttpResponse::Ok().json(response.unwrap()))
+            let mut response = response.unwrap();
+            if let Some(masker) = masker.as_ref() {
+                masker.mask_response(&mut response);
+            }
+            Ok(HttpResponse::Ok().json(response))
         }
     }
 
@@ -654,7 +665,12 @@ async fn cancel_job_inner(
 }
 
 #[cfg(feature = "enterprise")]
-async fn get_partition_result(job: &JobModel, from: i64, size: i64) -> HttpResponse {
+async fn get_partition_result(
+    job: &JobModel,
+    from: i64,
+    size: i64,
+    masker: Option<&crate::service::search::masking::Masker>,
+) -> HttpResponse {
     let req: Result<Request, serde_json::Error> = json::from_str(&job.payload);
     if let Err(e) = req {
         return MetaHttpResponse::internal_error(e);
@@ -675,7 +691,10 @@ async fn get_partition_result(job: &JobModel, from: i64, size: i64) -> HttpRespo
     if let Err(e) = response {
         return MetaHttpResponse::internal_error(e);
     }
-    let response = response.unwrap();
+    let mut response = response.unwrap();
+    if let Some(masker) = masker {
+        masker.mask_response(&mut response);
+    }
     apply_pagination(response, from, size)
 }
 #[cfg(feature = "enterprise")]
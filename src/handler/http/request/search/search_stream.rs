@@ -44,14 +44,16 @@ use crate::{
     },
     handler::http::request::search::{
         build_search_request_per_field, error_utils::map_error_to_http_response,
+        utils::check_stream_permissions,
+    },
+    service::{
+        api_tokens,
+        search::{masking::get_masker, search_stream::process_search_stream_request},
+        setup_tracing_with_trace_id,
     },
-    service::{search::search_stream::process_search_stream_request, setup_tracing_with_trace_id},
 };
 #[cfg(feature = "enterprise")]
-use crate::{
-    handler::http::request::search::utils::check_stream_permissions,
-    service::search::search_stream::AuditContext, service::self_reporting::audit,
-};
+use crate::{service::search::search_stream::AuditContext, service::self_reporting::audit};
 /// Search HTTP2 streaming endpoint
 ///
 /// #{"ratelimit_module":"Search", "ratelimit_module_operation":"get"}#
@@ -248,11 +250,13 @@ pub async fn search_http2_stream(
     };
 
     // Check permissions for each stream
-    #[cfg(feature = "enterprise")]
     for stream_name in stream_names.iter() {
-        if let Some(res) =
+        let res = if api_tokens::check_stream(&in_req, stream_name) {
             check_stream_permissions(stream_name, &org_id, &user_id, &stream_type).await
-        {
+        } else {
+            Some(MetaHttpResponse::forbidden("Unauthorized Access"))
+        };
+        if let Some(res) = res {
             // Add audit before closing
             #[cfg(feature = "enterprise")]
             {
@@ -335,6 +339,8 @@ pub async fn search_http2_stream(
     #[cfg(not(feature = "enterprise"))]
     let audit_ctx = None;
 
+    let masker = get_masker(&org_id, stream_type, &stream_names, Some(&user_id)).await;
+
     // Spawn the search task in a separate task
     actix_web::rt::spawn(process_search_stream_request(
         org_id.clone(),
@@ -354,7 +360,12 @@ pub async fn search_http2_stream(
     // Return streaming response
     let stream = tokio_stream::wrappers::ReceiverStream::new(rx).flat_map(move |result| {
         let chunks_iter = match result {
-            Ok(v) => v.to_chunks(),
+            Ok(mut v) => {
+                if let Some(masker) = &masker {
+                    masker.mask_stream_response(&mut v, false);
+                }
+                v.to_chunks()
+            }
             Err(err) => {
                 log::error!(
                     "[HTTP2_STREAM] trace_id: {} Error in stream: {}",
@@ -589,11 +600,13 @@ pub async fn values_http2_stream(
     let stream_names = vec![values_req.stream_name.clone()];
 
     // Check permissions for each stream
-    #[cfg(feature = "enterprise")]
     for stream_name in stream_names.iter() {
-        if let Some(res) =
+        let res = if api_tokens::check_stream(&in_req, stream_name) {
             check_stream_permissions(stream_name, &org_id, &user_id, &stream_type).await
-        {
+        } else {
+            Some(MetaHttpResponse::forbidden("Unauthorized Access"))
+        };
+        if let Some(res) = res {
             // Add audit before closing
             #[cfg(feature = "enterprise")]
             {
@@ -638,6 +651,8 @@ pub async fn values_http2_stream(
     #[cfg(not(feature = "enterprise"))]
     let audit_ctx = None;
 
+    let masker = get_masker(&org_id, stream_type, &stream_names, Some(&user_id)).await;
+
     // Spawn the search task to process the request
     actix_web::rt::spawn(process_search_stream_request(
         org_id.clone(),
@@ -657,7 +672,12 @@ pub async fn values_http2_stream(
     // Return streaming response
     let stream = tokio_stream::wrappers::ReceiverStream::new(rx).flat_map(move |result| {
         let chunks_iter = match result {
-            Ok(v) => v.to_chunks(),
+            Ok(mut v) => {
+                if let Some(masker) = &masker {
+                    masker.mask_stream_response(&mut v, true);
+                }
+                v.to_chunks()
+            }
             Err(err) => {
                 log::error!(
                     "[HTTP2_STREAM] trace_id: {} Error in stream: {}",
@@ -13,6 +13,15 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+#[cfg(not(feature = "enterprise"))]
+use {
+    crate::{common::meta::http::HttpResponse as MetaHttpResponse, service::grants},
+    actix_web::HttpResponse,
+    config::meta::{
+        grant::{GrantLevel, GrantResource},
+        stream::StreamType,
+    },
+};
 #[cfg(feature = "enterprise")]
 use {
     crate::{
@@ -65,3 +74,26 @@ pub async fn check_stream_permissions(
     }
     None
 }
+
+// Check the stream grants of the user
+#[cfg(not(feature = "enterprise"))]
+pub async fn check_stream_permissions(
+    stream_name: &str,
+    org_id: &str,
+    user_id: &str,
+    stream_type: &StreamType,
+) -> Option<HttpResponse> {
+    if !grants::is_allowed(
+        org_id,
+        user_id,
+        GrantResource::Stream,
+        Some(*stream_type),
+        stream_name,
+        GrantLevel::Read,
+    )
+    .await
+    {
+        return Some(MetaHttpResponse::forbidden("Unauthorized Access"));
+    }
+    None
+}
@@ -212,7 +212,7 @@ pub async fn zo_config() -> Result<HttpResponse, Error> {
     #[cfg(feature = "enterprise")]
     let sso_enabled = dex_cfg.dex_enabled;
     #[cfg(not(feature = "enterprise"))]
-    let sso_enabled = false;
+    let sso_enabled = get_config().oidc.enabled;
     #[cfg(feature = "enterprise")]
     let native_login_enabled = dex_cfg.native_login_enabled;
     #[cfg(not(feature = "enterprise"))]
@@ -586,7 +586,13 @@ pub async fn redirect(req: HttpRequest) -> Result<HttpResponse, Error> {
             }
 
             // store session_id in cluster co-ordinator
-            let _ = crate::service::session::set_session(&session_id, &access_token).await;
+            let _ = crate::service::session::create_session(
+                &session_id,
+                &access_token,
+                &audit_message.user_email,
+                &crate::service::session::SessionClient::from_request(&req),
+            )
+            .await;
 
             let access_token = format!("session {session_id}");
 
@@ -640,6 +646,73 @@ pub async fn dex_login() -> Result<HttpResponse, Error> {
     Ok(HttpResponse::Ok().json(login_data.url))
 }
 
+/// Returns the authorization url of the OIDC provider, like `dex_login`.
+#[cfg(not(feature = "enterprise"))]
+#[get("/oidc_login")]
+pub async fn oidc_login() -> Result<HttpResponse, Error> {
+    match crate::service::oidc::login_url().await {
+        Ok(url) => Ok(HttpResponse::Ok().json(url)),
+        Err(crate::service::oidc::OidcError::Disabled) => {
+            Ok(MetaHttpResponse::not_found("OIDC login is not enabled"))
+        }
+        Err(e) => Ok(MetaHttpResponse::internal_error(e)),
+    }
+}
+
+/// The redirect url of the OIDC provider, it starts a session for the user
+/// and redirects to the UI.
+#[cfg(not(feature = "enterprise"))]
+#[get("/oidc_callback")]
+pub async fn oidc_callback(req: HttpRequest) -> Result<HttpResponse, Error> {
+    use crate::{handler::http::auth::validator::ID_TOKEN_HEADER, service::oidc::OidcError};
+
+    let query = web::Query::<HashMap<String, String>>::from_query(req.query_string()).unwrap();
+    let (Some(code), Some(state)) = (query.get("code"), query.get("state")) else {
+        return Ok(MetaHttpResponse::bad_request("no code or state in request"));
+    };
+
+    let client = crate::service::session::SessionClient::from_request(&req);
+    let (user, session_id) = match crate::service::oidc::login(code, state, &client).await {
+        Ok(res) => res,
+        Err(e @ (OidcError::InfraError(_) | OidcError::Provider(_))) => {
+            log::error!("[OIDC] login failed: {e}");
+            return Ok(MetaHttpResponse::internal_error(e));
+        }
+        Err(e) => {
+            log::warn!("[OIDC] login rejected: {e}");
+            return Ok(HttpResponse::Unauthorized().json(e.to_string()));
+        }
+    };
+
+    let cfg = get_config();
+    let id_token = json::to_string(&json::json!({
+        "email": user.email,
+        "name": format!("{} {}", user.first_name, user.last_name).trim(),
+        "family_name": user.last_name,
+        "given_name": user.first_name,
+        "is_valid": true,
+    }))
+    .unwrap();
+    let login_url = format!(
+        "{}#id_token={}.{}",
+        cfg.oidc.ui_redirect_url,
+        ID_TOKEN_HEADER,
+        base64::encode(&id_token)
+    );
+    let auth_cookie = prepare_empty_cookie(
+        "auth_tokens",
+        &AuthTokens {
+            access_token: format!("session {session_id}"),
+            refresh_token: "".to_string(),
+        },
+        &cfg,
+    );
+    Ok(HttpResponse::Found()
+        .append_header((header::LOCATION, login_url))
+        .cookie(auth_cookie)
+        .finish())
+}
+
 #[cfg(feature = "enterprise")]
 #[get("/dex_refresh")]
 async fn refresh_token_with_dex(req: actix_web::HttpRequest) -> HttpResponse {
@@ -658,6 +731,7 @@ async fn refresh_token_with_dex(req: actix_web::HttpRequest) -> HttpResponse {
             trace_id: None,
         },
     };
+    let mut user_email = String::new();
     let token = if let Some(cookie) = req.cookie("auth_tokens") {
         let decoded_cookie = config::utils::base64::decode(cookie.value()).unwrap_or_default();
         let auth_tokens: AuthTokens = json::from_str(&decoded_cookie).unwrap_or_default();
@@ -665,9 +739,13 @@ async fn refresh_token_with_dex(req: actix_web::HttpRequest) -> HttpResponse {
         // remove old session id from cluster co-ordinator
 
         let access_token = auth_tokens.access_token;
-        if access_token.starts_with("session") {
-            crate::service::session::remove_session(access_token.strip_prefix("session ").unwrap())
-                .await;
+        if let Some(session_id) = access_token.strip_prefix("session ") {
+            // the new session belongs to the same user
+            let handle = crate::service::session::session_handle(session_id);
+            if let Ok(info) = crate::service::db::session::get_info(&handle).await {
+                user_email = info.user_email;
+            }
+            crate::service::session::remove_session(session_id).await;
         }
 
         auth_tokens.refresh_token
@@ -689,7 +767,13 @@ async fn refresh_token_with_dex(req: actix_web::HttpRequest) -> HttpResponse {
             }
 
             // store session_id in cluster co-ordinator
-            let _ = crate::service::session::set_session(&session_id, &access_token).await;
+            let _ = crate::service::session::create_session(
+                &session_id,
+                &access_token,
+                &user_email,
+                &crate::service::session::SessionClient::from_request(&req),
+            )
+            .await;
 
             let access_token = format!("session {session_id}");
 
@@ -428,6 +428,34 @@ async fn list(org_id: web::Path<String>, req: HttpRequest) -> impl Responder {
         indices.retain(|s| s.name.contains(keyword));
     }
 
+    // filter by the stream grants of the user
+    #[cfg(not(feature = "enterprise"))]
+    {
+        use config::meta::grant::{GrantLevel, GrantResource};
+
+        let user_id = req
+            .headers()
+            .get("user_id")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        let mut allowed = Vec::with_capacity(indices.len());
+        for stream in indices {
+            if crate::service::grants::is_allowed(
+                &org_id,
+                user_id,
+                GrantResource::Stream,
+                Some(stream.stream_type),
+                &stream.name,
+                GrantLevel::Read,
+            )
+            .await
+            {
+                allowed.push(stream);
+            }
+        }
+        indices = allowed;
+    }
+
     // sort by
     let mut sort = "name".to_string();
     if let Some(s) = query.get("sort") {
@@ -557,3 +585,94 @@ async fn delete_stream_cache(
         ))),
     }
 }
+
+/// StreamVerify
+///
+/// Compares the file_list of the stream with the object storage and reports
+/// the orphan, missing and mismatched files.
+///
+/// #{"ratelimit_module":"Streams", "ratelimit_module_operation":"update"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Streams",
+    operation_id = "StreamVerify",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("stream_name" = String, Path, description = "Stream name"),
+        ("type" = String, Query, description = "Stream type"),
+        ("start_time" = i64, Query, description = "Start time in microseconds, default is the verifier lookback window"),
+        ("end_time" = i64, Query, description = "End time in microseconds"),
+        ("sample" = usize, Query, description = "Only verify this number of random partitions, 0 means all"),
+        ("repair" = bool, Query, description = "Repair the discrepancies"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = VerifyReport),
+        (status = 400, description = "Failure", content_type = "application/json", body = HttpResponse),
+        (status = 500, description = "Failure", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[post("/{org_id}/streams/{stream_name}/verify")]
+async fn verify(
+    path: web::Path<(String, String)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let (org_id, mut stream_name) = path.into_inner();
+    if !config::get_config().common.skip_formatting_stream_name {
+        stream_name = format_stream_name(&stream_name);
+    }
+    let query = web::Query::<HashMap<String, String>>::from_query(req.query_string()).unwrap();
+    let stream_type = get_stream_type_from_request(&query).unwrap_or_default();
+    let (default_start, default_end) = crate::service::compact::verify::default_time_range();
+    let start_time = query
+        .get("start_time")
+        .and_then(|v| v.parse::<i64>().ok())
+        .unwrap_or(default_start);
+    let end_time = query
+        .get("end_time")
+        .and_then(|v| v.parse::<i64>().ok())
+        .unwrap_or(default_end);
+    if start_time >= end_time {
+        return Ok(HttpResponse::BadRequest().json(MetaHttpResponse::error(
+            http::StatusCode::BAD_REQUEST,
+            "start_time should be less than end_time",
+        )));
+    }
+    let sample = query
+        .get("sample")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or_default();
+    let repair = query
+        .get("repair")
+        .and_then(|v| v.parse::<bool>().ok())
+        .unwrap_or_default();
+
+    let deleted_files = match crate::service::compact::verify::list_deleted_files().await {
+        Ok(v) => v,
+        Err(e) => {
+            return Ok(
+                HttpResponse::InternalServerError().json(MetaHttpResponse::error(
+                    http::StatusCode::INTERNAL_SERVER_ERROR,
+                    e,
+                )),
+            );
+        }
+    };
+    match crate::service::compact::verify::verify_stream(
+        &org_id,
+        stream_type,
+        &stream_name,
+        (start_time, end_time),
+        sample,
+        repair,
+        &deleted_files,
+    )
+    .await
+    {
+        Ok(report) => Ok(HttpResponse::Ok().json(report)),
+        Err(e) => Ok(HttpResponse::BadRequest()
+            .json(MetaHttpResponse::error(http::StatusCode::BAD_REQUEST, e))),
+    }
+}
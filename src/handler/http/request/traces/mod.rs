@@ -32,11 +32,15 @@ use crate::{
         utils::http::{get_or_create_trace_id, get_use_cache_from_request},
     },
     handler::http::request::{
-        CONTENT_TYPE_JSON, CONTENT_TYPE_PROTO, search::error_utils::map_error_to_http_response,
+        CONTENT_TYPE_JSON, CONTENT_TYPE_PROTO,
+        search::{error_utils::map_error_to_http_response, utils::check_stream_permissions},
     },
     service::{search as SearchService, traces},
 };
 
+const CONTENT_TYPE_THRIFT: &str = "application/x-thrift";
+const CONTENT_TYPE_THRIFT_BINARY: &str = "application/vnd.apache.thrift.binary";
+
 /// TracesIngest
 #[utoipa::path(
     context_path = "/api",
@@ -94,6 +98,100 @@ async fn handle_req(
     }
 }
 
+/// ZipkinSpansIngest
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Traces",
+    operation_id = "PostZipkinSpans",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    request_body(content = String, description = "Zipkin v2 spans in json or ListOfSpans in protobuf", content_type = "application/json"),
+    responses(
+        (status = 202, description = "Accepted"),
+        (status = 400, description = "Failure", content_type = "application/json", body = HttpResponse),
+        (status = 500, description = "Failure", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[post("/{org_id}/zipkin/api/v2/spans")]
+pub async fn zipkin_spans_write(
+    org_id: web::Path<String>,
+    req: HttpRequest,
+    body: web::Bytes,
+) -> Result<HttpResponse, Error> {
+    let org_id = org_id.into_inner();
+    let content_type = req
+        .headers()
+        .get("Content-Type")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or(CONTENT_TYPE_JSON);
+    let in_stream_name = req
+        .headers()
+        .get(&get_config().grpc.stream_header_key)
+        .and_then(|header| header.to_str().ok());
+    if content_type.eq(CONTENT_TYPE_PROTO) {
+        traces::zipkin::ingest_proto(&org_id, body, in_stream_name).await
+    } else if content_type.starts_with(CONTENT_TYPE_JSON) {
+        traces::zipkin::ingest_json(&org_id, body, in_stream_name).await
+    } else {
+        Ok(
+            HttpResponse::BadRequest().json(meta::http::HttpResponse::error(
+                http::StatusCode::BAD_REQUEST,
+                "Bad Request",
+            )),
+        )
+    }
+}
+
+/// JaegerTracesIngest
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Traces",
+    operation_id = "PostJaegerTraces",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    request_body(content = String, description = "Jaeger Batch in thrift binary protocol", content_type = "application/x-thrift"),
+    responses(
+        (status = 202, description = "Accepted"),
+        (status = 400, description = "Failure", content_type = "application/json", body = HttpResponse),
+        (status = 500, description = "Failure", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[post("/{org_id}/jaeger/api/traces")]
+pub async fn jaeger_traces_write(
+    org_id: web::Path<String>,
+    req: HttpRequest,
+    body: web::Bytes,
+) -> Result<HttpResponse, Error> {
+    let org_id = org_id.into_inner();
+    let content_type = req
+        .headers()
+        .get("Content-Type")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or(CONTENT_TYPE_THRIFT);
+    let in_stream_name = req
+        .headers()
+        .get(&get_config().grpc.stream_header_key)
+        .and_then(|header| header.to_str().ok());
+    if content_type.eq(CONTENT_TYPE_THRIFT) || content_type.eq(CONTENT_TYPE_THRIFT_BINARY) {
+        traces::jaeger::ingest_thrift(&org_id, body, in_stream_name).await
+    } else {
+        Ok(
+            HttpResponse::BadRequest().json(meta::http::HttpResponse::error(
+                http::StatusCode::BAD_REQUEST,
+                "Bad Request",
+            )),
+        )
+    }
+}
+
 /// GetLatestTraces
 ///
 /// #{"ratelimit_module":"Traces", "ratelimit_module_operation":"list"}#
@@ -161,7 +259,12 @@ pub async fn get_latest_traces(
     let query = web::Query::<HashMap<String, String>>::from_query(in_req.query_string()).unwrap();
 
     // Check permissions on stream
-
+    #[cfg(not(feature = "enterprise"))]
+    if let Some(res) =
+        check_stream_permissions(&stream_name, &org_id, &user_id, &StreamType::Traces).await
+    {
+        return Ok(res);
+    }
     #[cfg(feature = "enterprise")]
     {
         use o2_openfga::meta::mapping::OFGA_MODELS;
@@ -380,6 +483,13 @@ pub async fn get_latest_traces(
     req.query.start_time = start_time;
     req.query.end_time = end_time;
     let mut traces_service_name: HashMap<String, HashMap<String, u16>> = HashMap::new();
+    let masker = SearchService::masking::get_masker(
+        &org_id,
+        stream_type,
+        std::slice::from_ref(&stream_name),
+        user_id.as_deref(),
+    )
+    .await;
 
     loop {
         let search_res = SearchService::cache::search(
@@ -424,23 +534,25 @@ pub async fn get_latest_traces(
         };
 
         let resp_size = resp_search.hits.len() as i64;
-        for item in resp_search.hits {
+        for mut item in resp_search.hits {
             let trace_id = item.get("trace_id").unwrap().as_str().unwrap().to_string();
             let trace_start_time = json::get_int_value(item.get("start_time").unwrap());
             let trace_end_time = json::get_int_value(item.get("end_time").unwrap());
             let duration = json::get_int_value(item.get("duration").unwrap());
+            // the ids and the times are only used to group the spans
+            if let Some(masker) = masker.as_ref()
+                && let json::Value::Object(record) = &mut item
+            {
+                masker.mask_record(record);
+            }
             let service_name = item
                 .get("service_name")
-                .unwrap()
-                .as_str()
-                .unwrap()
-                .to_string();
+                .map(json::get_string_value)
+                .unwrap_or_default();
             let span_status = item
                 .get("span_status")
-                .unwrap()
-                .as_str()
-                .unwrap()
-                .to_string();
+                .map(json::get_string_value)
+                .unwrap_or_default();
             let trace = traces_data.get_mut(&trace_id).unwrap();
             if trace.first_event.is_null() {
                 trace.first_event = item.clone();
@@ -516,6 +628,238 @@ pub async fn get_latest_traces(
     Ok(HttpResponse::Ok().json(resp))
 }
 
+/// SearchTracesByStructure
+///
+/// #{"ratelimit_module":"Traces", "ratelimit_module_operation":"list"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Traces",
+    operation_id = "SearchTracesByStructure",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("stream_name" = String, Path, description = "Stream name"),
+        ("q" = String, Query, description = "TraceQL query, eg: { resource.service.name = \"A\" && status = error } >> { duration > 2s }"),
+        ("size" = Option<usize>, Query, description = "max number of traces, default 20"),
+        ("start_time" = i64, Query, description = "start time"),
+        ("end_time" = i64, Query, description = "end time"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = TraceQLResponse),
+        (status = 400, description = "Failure", content_type = "application/json", body = HttpResponse),
+        (status = 500, description = "Failure", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[get("/{org_id}/{stream_name}/traces/search")]
+pub async fn search_traces_by_structure(
+    path: web::Path<(String, String)>,
+    in_req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let cfg = get_config();
+    let (org_id, stream_name) = path.into_inner();
+    let http_span = if cfg.common.tracing_search_enabled {
+        tracing::info_span!(
+            "/api/{org_id}/{stream_name}/traces/search",
+            org_id = org_id.clone(),
+            stream_name = stream_name.clone()
+        )
+    } else {
+        Span::none()
+    };
+    let trace_id = get_or_create_trace_id(in_req.headers(), &http_span);
+    let user_id = in_req
+        .headers()
+        .get("user_id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_string();
+    let query = web::Query::<HashMap<String, String>>::from_query(in_req.query_string()).unwrap();
+
+    // Check permissions on stream
+    #[cfg(not(feature = "enterprise"))]
+    if let Some(res) =
+        check_stream_permissions(&stream_name, &org_id, &user_id, &StreamType::Traces).await
+    {
+        return Ok(res);
+    }
+    #[cfg(feature = "enterprise")]
+    {
+        use o2_openfga::meta::mapping::OFGA_MODELS;
+
+        use crate::{
+            common::utils::auth::{AuthExtractor, is_root_user},
+            service::users::get_user,
+        };
+        if !is_root_user(&user_id) {
+            let user: config::meta::user::User = get_user(Some(&org_id), &user_id).await.unwrap();
+            let stream_type_str = StreamType::Traces.as_str();
+
+            if !crate::handler::http::auth::validator::check_permissions(
+                &user_id,
+                AuthExtractor {
+                    auth: "".to_string(),
+                    method: "GET".to_string(),
+                    o2_type: format!(
+                        "{}:{}",
+                        OFGA_MODELS
+                            .get(stream_type_str)
+                            .map_or(stream_type_str, |model| model.key),
+                        stream_name
+                    ),
+                    org_id: org_id.clone(),
+                    bypass_check: false,
+                    parent_id: "".to_string(),
+                },
+                user.role,
+                user.is_external,
+            )
+            .await
+            {
+                return Ok(MetaHttpResponse::forbidden("Unauthorized Access"));
+            }
+        }
+    }
+
+    let expr = match query.get("q").map(|v| traces::traceql::parse(v)) {
+        Some(Ok(expr)) => expr,
+        Some(Err(e)) => return Ok(MetaHttpResponse::bad_request(format!("invalid query: {e}"))),
+        None => return Ok(MetaHttpResponse::bad_request("q is empty")),
+    };
+    let size = query
+        .get("size")
+        .map_or(20, |v| v.parse::<usize>().unwrap_or(20))
+        .clamp(1, 1000);
+    let mut start_time = query
+        .get("start_time")
+        .map_or(0, |v| v.parse::<i64>().unwrap_or(0));
+    if start_time == 0 {
+        return Ok(MetaHttpResponse::bad_request("start_time is empty"));
+    }
+    let end_time = query
+        .get("end_time")
+        .map_or(0, |v| v.parse::<i64>().unwrap_or(0));
+    if end_time == 0 {
+        return Ok(MetaHttpResponse::bad_request("end_time is empty"));
+    }
+    let max_query_range = crate::common::utils::stream::get_max_query_range(
+        std::slice::from_ref(&stream_name),
+        org_id.as_str(),
+        &user_id,
+        StreamType::Traces,
+    )
+    .await;
+    if max_query_range > 0 && (end_time - start_time) > max_query_range * 3600 * 1_000_000 {
+        start_time = end_time - max_query_range * 3600 * 1_000_000;
+    }
+
+    match traces::traceql::search(
+        &trace_id,
+        &org_id,
+        &stream_name,
+        Some(user_id),
+        &expr,
+        (start_time, end_time),
+        size,
+    )
+    .instrument(http_span)
+    .await
+    {
+        Ok(resp) => Ok(HttpResponse::Ok().json(resp)),
+        Err(err) => {
+            log::error!("search traces by structure error: {:?}", err);
+            Ok(map_error_to_http_response(&err, Some(trace_id)))
+        }
+    }
+}
+
+/// GetServiceGraph
+///
+/// #{"ratelimit_module":"Traces", "ratelimit_module_operation":"list"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Traces",
+    operation_id = "GetServiceGraph",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("stream_name" = Option<String>, Query, description = "Traces stream name, default all streams"),
+        ("start_time" = i64, Query, description = "start time"),
+        ("end_time" = i64, Query, description = "end time"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = ServiceGraph),
+        (status = 400, description = "Failure", content_type = "application/json", body = HttpResponse),
+        (status = 500, description = "Failure", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[get("/{org_id}/traces/service_graph")]
+pub async fn get_service_graph(
+    org_id: web::Path<String>,
+    in_req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let org_id = org_id.into_inner();
+    let http_span = if get_config().common.tracing_search_enabled {
+        tracing::info_span!(
+            "/api/{org_id}/traces/service_graph",
+            org_id = org_id.clone()
+        )
+    } else {
+        Span::none()
+    };
+    let trace_id = get_or_create_trace_id(in_req.headers(), &http_span);
+    let query = web::Query::<HashMap<String, String>>::from_query(in_req.query_string()).unwrap();
+    let start_time = query
+        .get("start_time")
+        .map_or(0, |v| v.parse::<i64>().unwrap_or(0));
+    if start_time == 0 {
+        return Ok(MetaHttpResponse::bad_request("start_time is empty"));
+    }
+    let end_time = query
+        .get("end_time")
+        .map_or(0, |v| v.parse::<i64>().unwrap_or(0));
+    if end_time == 0 {
+        return Ok(MetaHttpResponse::bad_request("end_time is empty"));
+    }
+    let stream_name = query
+        .get("stream_name")
+        .map(|v| v.as_str())
+        .filter(|v| !v.is_empty());
+
+    let user_email = in_req
+        .headers()
+        .get("user_id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    if let Some(stream_name) = stream_name
+        && let Some(res) =
+            check_stream_permissions(stream_name, &org_id, user_email, &StreamType::Traces).await
+    {
+        return Ok(res);
+    }
+
+    match traces::service_graph::query(
+        &trace_id,
+        &org_id,
+        user_email,
+        stream_name,
+        start_time,
+        end_time,
+    )
+    .instrument(http_span)
+    .await
+    {
+        Ok(graph) => Ok(HttpResponse::Ok().json(graph)),
+        Err(err) => {
+            log::error!("get traces service graph error: {:?}", err);
+            Ok(map_error_to_http_response(&err, Some(trace_id)))
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct TraceResponseItem {
     trace_id: String,
@@ -48,10 +48,11 @@ use crate::{
         },
         utils::auth::{UserEmail, generate_presigned_url},
     },
-    service::users,
+    service::{session, users},
 };
 
 pub mod service_accounts;
+pub mod sessions;
 
 /// ListUsers
 ///
@@ -257,6 +258,20 @@ fn _prepare_cookie<'a, T: Serialize + ?Sized, E: Into<cookie::Expiration>>(
     }
     auth_cookie
 }
+
+/// Opens a session for a native login, returns the access token of the login
+/// cookie.
+async fn create_login_session(req: &HttpRequest, user_email: &str) -> Option<String> {
+    let session_id = config::ider::uuid();
+    session::create_native_session(
+        &session_id,
+        user_email,
+        &session::SessionClient::from_request(req),
+    )
+    .await?;
+    Some(format!("session {session_id}"))
+}
+
 /// RemoveUserFromOrganization
 ///
 /// #{"ratelimit_module":"Users", "ratelimit_module_operation":"delete"}#
@@ -376,10 +391,16 @@ pub async fn authentication(
             return unauthorized_error(resp);
         }
     }
-    match crate::handler::http::auth::validator::validate_user(&auth.name, &auth.password).await {
+    let user_email = match crate::handler::http::auth::validator::validate_user(
+        &auth.name,
+        &auth.password,
+    )
+    .await
+    {
         Ok(v) => {
             if v.is_valid {
                 resp.status = true;
+                v.user_email
             } else {
                 #[cfg(feature = "enterprise")]
                 audit_unauthorized_error(audit_message).await;
@@ -395,10 +416,11 @@ pub async fn authentication(
     if resp.status {
         let cfg = get_config();
 
-        let access_token = format!(
-            "Basic {}",
-            base64::encode(&format!("{}:{}", auth.name, auth.password))
-        );
+        let Some(access_token) = create_login_session(&_req, &user_email).await else {
+            return Ok(meta::http::HttpResponse::internal_error(
+                "Failed to create the login session",
+            ));
+        };
         let tokens = json::to_string(&AuthTokens {
             access_token,
             refresh_token: "".to_string(),
@@ -623,10 +645,11 @@ pub async fn get_auth(_req: HttpRequest) -> Result<HttpResponse, Error> {
             });
             let cookie_name = "auth_tokens";
             let auth_cookie = if req_ts == 0 {
-                let access_token = format!(
-                    "Basic {}",
-                    base64::encode(&format!("{}:{}", &name, &password))
-                );
+                let Some(access_token) = create_login_session(&_req, &name).await else {
+                    return Ok(meta::http::HttpResponse::internal_error(
+                        "Failed to create the login session",
+                    ));
+                };
                 let tokens = AuthTokens {
                     access_token,
                     refresh_token: "".to_string(),
@@ -0,0 +1,145 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io::Error;
+
+use actix_web::{HttpRequest, HttpResponse, delete, get, http, web};
+
+use crate::{
+    common::meta::http::HttpResponse as MetaHttpResponse,
+    service::session::{self, SessionError},
+};
+
+impl From<SessionError> for HttpResponse {
+    fn from(value: SessionError) -> Self {
+        match value {
+            SessionError::InfraError(err) => MetaHttpResponse::internal_error(err),
+            SessionError::NotFound(_) => MetaHttpResponse::not_found(value),
+            SessionError::Forbidden(_) => MetaHttpResponse::forbidden(value),
+            error => MetaHttpResponse::bad_request(error),
+        }
+    }
+}
+
+/// ListUserSessions
+///
+/// #{"ratelimit_module":"Users", "ratelimit_module_operation":"list"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Users",
+    operation_id = "ListUserSessions",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("email_id" = String, Path, description = "User's email id"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = Vec<SessionInfo>),
+        (status = 403, description = "Forbidden", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[get("/{org_id}/users/{email_id}/sessions")]
+pub async fn list(
+    path: web::Path<(String, String)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let (org_id, email_id) = path.into_inner();
+    let email_id = email_id.trim().to_lowercase();
+    match session::list(&org_id, get_user_id(&req), &email_id).await {
+        Ok(sessions) => Ok(HttpResponse::Ok().json(sessions)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+/// RevokeUserSession
+///
+/// #{"ratelimit_module":"Users", "ratelimit_module_operation":"delete"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Users",
+    operation_id = "RevokeUserSession",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("email_id" = String, Path, description = "User's email id"),
+        ("handle" = String, Path, description = "Session handle, as listed by ListUserSessions"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = HttpResponse),
+        (status = 404, description = "NotFound", content_type = "application/json", body = HttpResponse),
+        (status = 403, description = "Forbidden", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[delete("/{org_id}/users/{email_id}/sessions/{handle}")]
+pub async fn revoke(
+    path: web::Path<(String, String, String)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let (org_id, email_id, handle) = path.into_inner();
+    let email_id = email_id.trim().to_lowercase();
+    match session::revoke(&org_id, get_user_id(&req), &email_id, &handle).await {
+        Ok(()) => Ok(HttpResponse::Ok().json(MetaHttpResponse::message(
+            http::StatusCode::OK,
+            "Session revoked successfully",
+        ))),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+/// RevokeAllUserSessions
+///
+/// #{"ratelimit_module":"Users", "ratelimit_module_operation":"delete"}#
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Users",
+    operation_id = "RevokeAllUserSessions",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("email_id" = String, Path, description = "User's email id"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = HttpResponse),
+        (status = 403, description = "Forbidden", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[delete("/{org_id}/users/{email_id}/sessions")]
+pub async fn revoke_all(
+    path: web::Path<(String, String)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let (org_id, email_id) = path.into_inner();
+    let email_id = email_id.trim().to_lowercase();
+    match session::revoke_all(&org_id, get_user_id(&req), &email_id).await {
+        Ok(count) => Ok(HttpResponse::Ok().json(MetaHttpResponse::message(
+            http::StatusCode::OK,
+            format!("{count} sessions revoked"),
+        ))),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+fn get_user_id(req: &HttpRequest) -> &str {
+    req.headers()
+        .get("user_id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+}
@@ -0,0 +1,244 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Records the API requests into the audit stream of the `_meta` org.
+
+use actix_http::h1::Payload;
+use actix_web::{
+    HttpMessage,
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    web::BytesMut,
+};
+use actix_web_lab::middleware::Next;
+use config::{
+    get_config,
+    meta::self_reporting::audit::AuditData,
+    utils::{json, time::now_micros},
+};
+use futures::StreamExt;
+
+use crate::{
+    common::meta::ingestion::INGESTION_EP, handler::http::router::ERROR_HEADER,
+    service::self_reporting::audit,
+};
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Field names, lowercased, whose values never reach the audit stream.
+const SECRET_FIELDS: [&str; 9] = [
+    "password",
+    "passcode",
+    "secret",
+    "token",
+    "authorization",
+    "credential",
+    "private_key",
+    "access_key",
+    "api_key",
+];
+
+pub async fn audit_middleware(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let cfg = get_config();
+    let method = req.method().to_string();
+    let prefix = format!("{}/api/", cfg.common.base_uri);
+    let path = req
+        .path()
+        .strip_prefix(&prefix)
+        .unwrap_or_default()
+        .to_string();
+    if !cfg.common.audit_enabled || path.is_empty() || !should_audit(&method, &path) {
+        // Remove the error header from the response if it exists
+        let mut res = next.call(req).await?;
+        res.headers_mut().remove(ERROR_HEADER);
+        return Ok(res);
+    }
+
+    let path_columns = path.split('/').collect::<Vec<_>>();
+    let (org_id, resource, resource_id) = match path_columns[0] {
+        "organizations" => ("", path_columns[0], path_columns.get(1)),
+        org => (
+            org,
+            path_columns.get(1).copied().unwrap_or_default(),
+            path_columns.get(2),
+        ),
+    };
+    let mut audit_data = AuditData {
+        user_email: req
+            .headers()
+            .get("user_id")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string(),
+        org_id: org_id.to_string(),
+        http_method: method,
+        http_query_params: req.query_string().to_string(),
+        resource: resource.to_string(),
+        resource_id: resource_id.map(|id| id.to_string()).unwrap_or_default(),
+        ..Default::default()
+    };
+
+    let mut request_body = BytesMut::new();
+    let mut payload_stream = req.take_payload();
+    while let Some(chunk) = payload_stream.next().await {
+        request_body.extend_from_slice(&chunk?);
+    }
+    audit_data.http_body = summarize_body(&request_body, cfg.common.audit_body_max_size);
+    audit_data.http_path = path;
+
+    // Put the payload back into the req
+    let (_, mut payload) = Payload::create(true);
+    payload.unread_data(request_body.into());
+    req.set_payload(payload.into());
+
+    let mut res = next.call(req).await?;
+    // the response body can't be read here, the handlers report the error
+    // message through the error header which the client doesn't need
+    audit_data.error_msg = res
+        .response()
+        .headers()
+        .get(ERROR_HEADER)
+        .map(|v| v.to_str().unwrap_or_default().to_string());
+    res.headers_mut().remove(ERROR_HEADER);
+    audit_data.http_response_code = res.response().status().as_u16();
+    audit_data._timestamp = now_micros();
+    audit(audit_data).await;
+    Ok(res)
+}
+
+/// Checks the request against the audited methods and the include/exclude
+/// path rules. Ingestion and streaming endpoints are never audited.
+fn should_audit(method: &str, path: &str) -> bool {
+    let cfg = get_config();
+    let path_columns = path.split('/').collect::<Vec<_>>();
+    let second = path_columns.get(1).copied().unwrap_or_default();
+    if second == "ws"
+        || second.ends_with("_stream") // skip for http2 streams
+        || path.ends_with("ai/chat_stream")
+        || (method == "POST" && INGESTION_EP.contains(path_columns.last().unwrap()))
+    {
+        return false;
+    }
+    if !cfg
+        .common
+        .audit_methods
+        .split(',')
+        .any(|m| m.trim().eq_ignore_ascii_case(method))
+    {
+        return false;
+    }
+    let include = &cfg.common.audit_include_paths;
+    if !include.trim().is_empty() && !matches_any_rule(include, path) {
+        return false;
+    }
+    !matches_any_rule(&cfg.common.audit_exclude_paths, path)
+}
+
+/// Rules are comma separated path prefixes, `*` matches one path segment.
+fn matches_any_rule(rules: &str, path: &str) -> bool {
+    let path_columns = path.split('/').collect::<Vec<_>>();
+    rules
+        .split(',')
+        .map(|rule| rule.trim().trim_matches('/'))
+        .filter(|rule| !rule.is_empty())
+        .any(|rule| {
+            let rule_columns = rule.split('/').collect::<Vec<_>>();
+            rule_columns.len() <= path_columns.len()
+                && rule_columns
+                    .iter()
+                    .zip(path_columns.iter())
+                    .all(|(rule, column)| *rule == "*" || rule == column)
+        })
+}
+
+/// JSON bodies are kept with the secret fields redacted, other bodies are only
+/// described by their size.
+fn summarize_body(body: &[u8], max_size: usize) -> String {
+    if body.is_empty() {
+        return String::new();
+    }
+    let mut summary = match json::from_slice::<json::Value>(body) {
+        Ok(mut value) => {
+            redact(&mut value);
+            value.to_string()
+        }
+        Err(_) => format!("<{} bytes>", body.len()),
+    };
+    if summary.len() > max_size {
+        let mut end = max_size;
+        while !summary.is_char_boundary(end) {
+            end -= 1;
+        }
+        summary.truncate(end);
+        summary.push_str("...");
+    }
+    summary
+}
+
+fn redact(value: &mut json::Value) {
+    match value {
+        json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_lowercase();
+                if SECRET_FIELDS.iter().any(|field| key.contains(field)) {
+                    *value = json::Value::String(REDACTED.to_string());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        json::Value::Array(values) => values.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_any_rule() {
+        assert!(matches_any_rule(
+            "*/streams",
+            "default/streams/logs1/settings"
+        ));
+        assert!(matches_any_rule("users, default/alerts", "default/alerts"));
+        assert!(!matches_any_rule("*/streams", "default/alerts/a1"));
+        assert!(!matches_any_rule("*/streams/x/y", "default/streams"));
+        assert!(!matches_any_rule("", "default/streams"));
+    }
+
+    #[test]
+    fn test_summarize_body() {
+        let body = json::json!({
+            "name": "u1",
+            "password": "secret1",
+            "nested": [{"Api_Key": "k", "value": 1}],
+        });
+        let summary = summarize_body(body.to_string().as_bytes(), 1024);
+        let value: json::Value = json::from_str(&summary).unwrap();
+        assert_eq!(value["name"], "u1");
+        assert_eq!(value["password"], REDACTED);
+        assert_eq!(value["nested"][0]["Api_Key"], REDACTED);
+        assert_eq!(value["nested"][0]["value"], 1);
+
+        assert_eq!(summarize_body(b"\x00\x01binary", 1024), "<8 bytes>");
+        assert_eq!(summarize_body(b"", 1024), "");
+        assert_eq!(summarize_body(br#""abcdef""#, 4), r#""abc..."#);
+    }
+}
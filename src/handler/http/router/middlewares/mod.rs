@@ -13,11 +13,15 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+#[cfg(not(feature = "enterprise"))]
+mod audit;
 mod check_keep_alive;
 mod compress;
 mod encoding;
 mod slow_log;
 
+#[cfg(not(feature = "enterprise"))]
+pub use audit::audit_middleware;
 pub use check_keep_alive::check_keep_alive;
 pub use compress::Compress;
 pub use slow_log::SlowLog;
@@ -18,14 +18,13 @@ use std::{rc::Rc, str::FromStr};
 use actix_cors::Cors;
 use actix_web::{
     HttpRequest, HttpResponse,
-    body::MessageBody,
-    dev::{Service, ServiceRequest, ServiceResponse},
+    dev::{Service, ServiceResponse},
     get,
     http::header,
     middleware, web,
 };
 use actix_web_httpauth::middleware::HttpAuthentication;
-use actix_web_lab::middleware::{Next, from_fn};
+use actix_web_lab::middleware::from_fn;
 use config::get_config;
 use futures::FutureExt;
 use utoipa::OpenApi;
@@ -34,7 +33,8 @@ use utoipa_swagger_ui::SwaggerUi;
 use {
     crate::{common::meta::ingestion::INGESTION_EP, service::self_reporting::audit},
     actix_http::h1::Payload,
-    actix_web::{HttpMessage, web::BytesMut},
+    actix_web::{HttpMessage, body::MessageBody, dev::ServiceRequest, web::BytesMut},
+    actix_web_lab::middleware::Next,
     base64::{Engine as _, engine::general_purpose},
     config::utils::time::now_micros,
     futures::StreamExt,
@@ -44,6 +44,8 @@ use {
     },
 };
 
+#[cfg(not(feature = "enterprise"))]
+use self::middlewares::audit_middleware;
 use super::request::*;
 use crate::{
     common::meta::{middleware_data::RumExtraData, proxy::PathParamProxyURL},
@@ -162,16 +164,6 @@ async fn audit_middleware(
     }
 }
 
-#[cfg(not(feature = "enterprise"))]
-async fn audit_middleware(
-    req: ServiceRequest,
-    next: Next<impl MessageBody>,
-) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
-    let mut res = next.call(req).await?;
-    res.headers_mut().remove(ERROR_HEADER);
-    Ok(res)
-}
-
 #[get("/metrics")]
 async fn get_metrics() -> Result<HttpResponse, actix_web::Error> {
     let body = if config::get_config().common.prometheus_enabled {
@@ -329,6 +321,8 @@ pub fn get_config_routes(svc: &mut web::ServiceConfig) {
         web::scope("/config")
             .wrap(cors.clone())
             .service(status::zo_config)
+            .service(status::oidc_login)
+            .service(status::oidc_callback)
             .service(status::logout)
             .service(web::scope("/reload").service(status::config_reload)),
     );
@@ -377,6 +371,9 @@ pub fn get_service_routes(svc: &mut web::ServiceConfig) {
         .service(users::add_user_to_org)
         .service(users::list_invitations)
         .service(users::list_roles)
+        .service(users::sessions::list)
+        .service(users::sessions::revoke)
+        .service(users::sessions::revoke_all)
         .service(organization::org::organizations)
         .service(organization::settings::get)
         .service(organization::settings::create)
@@ -416,7 +413,11 @@ pub fn get_service_routes(svc: &mut web::ServiceConfig) {
         .service(logs::loki::loki_push)
         .service(traces::traces_write)
         .service(traces::otlp_traces_write)
+        .service(traces::zipkin_spans_write)
+        .service(traces::jaeger_traces_write)
+        .service(traces::get_service_graph)
         .service(traces::get_latest_traces)
+        .service(traces::search_traces_by_structure)
         .service(metrics::ingest::json)
         .service(metrics::ingest::otlp_metrics_write)
         .service(promql::remote_write)
@@ -520,7 +521,23 @@ pub fn get_service_routes(svc: &mut web::ServiceConfig) {
         .service(syslog::delete_route)
         .service(syslog::update_route)
         .service(syslog::toggle_state)
+        .service(grants::list)
+        .service(grants::save)
+        .service(grants::delete)
+        .service(api_tokens::list)
+        .service(api_tokens::create)
+        .service(api_tokens::revoke)
+        .service(quotas::get)
+        .service(quotas::set)
+        .service(quotas::delete)
+        .service(quotas::usage)
+        .service(encryption::status)
+        .service(encryption::rotate)
         .service(enrichment_table::save_enrichment_table)
+        .service(enrichment_table::save_enrichment_table_source)
+        .service(enrichment_table::get_enrichment_table_source)
+        .service(enrichment_table::delete_enrichment_table_source)
+        .service(enrichment_table::refresh_enrichment_table_source)
         .service(logs::ingest::handle_kinesis_request)
         .service(logs::ingest::handle_gcp_request)
         .service(organization::org::create_org)
@@ -541,6 +558,7 @@ pub fn get_service_routes(svc: &mut web::ServiceConfig) {
         .service(authz::fga::delete_group)
         .service(clusters::list_clusters)
         .service(pipeline::save_pipeline)
+        .service(pipeline::dry_run_pipeline)
         .service(pipeline::update_pipeline)
         .service(pipeline::list_pipelines)
         .service(pipeline::list_streams_with_pipeline)
@@ -549,7 +567,10 @@ pub fn get_service_routes(svc: &mut web::ServiceConfig) {
         .service(search::multi_streams::search_multi)
         .service(search::multi_streams::_search_partition_multi)
         .service(search::multi_streams::around_multi)
+        .service(search::multi_orgs::search_orgs)
+        .service(search::multi_orgs::search_orgs_partition)
         .service(stream::delete_stream_cache)
+        .service(stream::verify)
         .service(short_url::shorten)
         .service(short_url::retrieve)
         .service(service_accounts::list)
@@ -559,6 +580,21 @@ pub fn get_service_routes(svc: &mut web::ServiceConfig) {
         .service(service_accounts::get_api_token)
         .service(ws::websocket);
 
+    #[cfg(not(feature = "enterprise"))]
+    let service = service
+        .service(scim::list_users)
+        .service(scim::create_user)
+        .service(scim::get_user)
+        .service(scim::replace_user)
+        .service(scim::patch_user)
+        .service(scim::delete_user)
+        .service(scim::list_groups)
+        .service(scim::create_group)
+        .service(scim::get_group)
+        .service(scim::replace_group)
+        .service(scim::patch_group)
+        .service(scim::delete_group);
+
     #[cfg(feature = "enterprise")]
     let service = service
         .service(search::search_job::submit_job)
@@ -29,6 +29,9 @@ use crate::{common::meta, handler::http::request};
         request::users::update,
         request::users::delete,
         request::users::add_user_to_org,
+        request::users::sessions::list,
+        request::users::sessions::revoke,
+        request::users::sessions::revoke_all,
         request::organization::org::organizations,
         request::organization::org::create_org,
         request::organization::org::rename_org,
@@ -46,12 +49,17 @@ use crate::{common::meta, handler::http::request};
         request::stream::update_settings,
         request::stream::delete_fields,
         request::stream::delete,
+        request::stream::verify,
         request::logs::ingest::bulk,
         request::logs::ingest::multi,
         request::logs::ingest::json,
         request::logs::loki::loki_push,
         request::traces::traces_write,
+        request::traces::zipkin_spans_write,
+        request::traces::jaeger_traces_write,
         request::traces::get_latest_traces,
+        request::traces::get_service_graph,
+        request::traces::search_traces_by_structure,
         request::metrics::ingest::json,
         request::promql::remote_write,
         request::promql::query_get,
@@ -62,11 +70,17 @@ use crate::{common::meta, handler::http::request};
         request::promql::label_values,
         request::promql::format_query_get,
         request::enrichment_table::save_enrichment_table,
+        request::enrichment_table::save_enrichment_table_source,
+        request::enrichment_table::get_enrichment_table_source,
+        request::enrichment_table::delete_enrichment_table_source,
+        request::enrichment_table::refresh_enrichment_table_source,
         request::rum::ingest::log,
         request::rum::ingest::data,
         request::rum::ingest::sessionreplay,
         request::search::search,
         request::search::search_partition,
+        request::search::multi_orgs::search_orgs,
+        request::search::multi_orgs::search_orgs_partition,
         request::search::around_v1,
         request::search::around_v2,
         request::search::values,
@@ -140,6 +154,18 @@ use crate::{common::meta, handler::http::request};
         request::syslog::update_route,
         request::syslog::list_routes,
         request::syslog::delete_route,
+        request::grants::list,
+        request::grants::save,
+        request::grants::delete,
+        request::api_tokens::list,
+        request::api_tokens::create,
+        request::api_tokens::revoke,
+        request::quotas::get,
+        request::quotas::set,
+        request::quotas::delete,
+        request::quotas::usage,
+        request::encryption::status,
+        request::encryption::rotate,
         request::clusters::list_clusters,
         request::short_url::shorten,
         request::short_url::retrieve,
@@ -152,6 +178,7 @@ use crate::{common::meta, handler::http::request};
         request::service_accounts::delete,
         request::service_accounts::get_api_token,
         request::pipeline::save_pipeline,
+        request::pipeline::dry_run_pipeline,
         request::pipeline::list_pipelines,
         request::pipeline::list_streams_with_pipeline,
         request::pipeline::delete_pipeline,
@@ -210,6 +237,30 @@ use crate::{common::meta, handler::http::request};
             config::meta::stream::StreamStats,
             config::meta::stream::PartitionTimeLevel,
             config::meta::stream::UpdateStreamSettings,
+            config::meta::stream::FileMeta,
+            config::meta::grant::Grant,
+            config::meta::grant::GrantResource,
+            config::meta::grant::GrantLevel,
+            config::meta::api_token::ApiToken,
+            config::meta::api_token::ApiTokenScope,
+            config::meta::api_token::CreateApiTokenRequest,
+            config::meta::api_token::CreateApiTokenResponse,
+            config::meta::session::SessionInfo,
+            config::meta::quota::OrgQuota,
+            config::meta::quota::QuotaLimit,
+            config::meta::quota::QuotaStatus,
+            config::meta::quota::QuotaUsage,
+            config::meta::quota::OrgQuotaUsage,
+            config::meta::encryption::EncryptionStatus,
+            config::meta::encryption::DataKeyInfo,
+            config::meta::encryption::KeyProvider,
+            crate::service::compact::verify::VerifyReport,
+            crate::service::compact::verify::VerifyMismatch,
+            crate::service::traces::service_graph::ServiceGraph,
+            crate::service::traces::service_graph::ServiceGraphNode,
+            crate::service::traces::service_graph::ServiceGraphEdge,
+            crate::service::traces::traceql::TraceQLResponse,
+            crate::service::traces::traceql::TraceQLTrace,
             config::meta::dashboards::Dashboard,
             config::meta::dashboards::v1::AxisItem,
             config::meta::dashboards::v1::Dashboard,
@@ -291,6 +342,8 @@ use crate::{common::meta, handler::http::request};
             config::meta::search::SearchEventContext,
             config::meta::search::SearchPartitionRequest,
             config::meta::search::SearchPartitionResponse,
+            config::meta::search::MultiOrgSearchRequest,
+            config::meta::search::MultiOrgSearchPartitionRequest,
             config::meta::search::SearchHistoryRequest,
             config::meta::search::CancelQueryResponse,
             config::meta::search::QueryStatusResponse,
@@ -299,6 +352,12 @@ use crate::{common::meta, handler::http::request};
             config::meta::search::ScanStats,
             config::meta::short_url::ShortenUrlRequest,
             config::meta::short_url::ShortenUrlResponse,
+            config::meta::enrichment_table::EnrichmentTableSource,
+            config::meta::enrichment_table::SourceKind,
+            config::meta::enrichment_table::SourceFormat,
+            config::meta::enrichment_table::RefreshStatus,
+            config::meta::enrichment_table::EnrichmentTableSourceStatus,
+            config::meta::enrichment_table::EnrichmentTableSourceResponse,
             config::meta::user::UserRole,
             meta::ingestion::RecordStatus,
             meta::ingestion::StreamStatus,
@@ -365,6 +424,10 @@ use crate::{common::meta, handler::http::request};
         (name = "Metrics", description = "Metrics data ingestion operations"),
         (name = "Traces", description = "Traces data ingestion operations"),
         (name = "Syslog Routes", description = "Syslog Routes retrieval & management operations"),
+        (name = "Grants", description = "Stream, dashboard folder and alert grants of users"),
+        (name = "ApiTokens", description = "Scoped and expiring API tokens of users and service accounts"),
+        (name = "Quotas", description = "Data volume quotas of the organizations and their usage"),
+        (name = "Encryption", description = "Data keys of the encryption at rest of the organizations"),
         (name = "Clusters", description = "Super cluster operations"),
         (name = "Short Url", description = "Short Url Service"),
         (name = "Ratelimit", description = "Ratelimit operations"),
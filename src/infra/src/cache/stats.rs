@@ -59,19 +59,7 @@ pub fn incr_stream_stats(key: &str, val: &FileMeta) -> Result<(), anyhow::Error>
         return Ok(());
     }
 
-    // eg: files/default/logs/olympics/2022/10/03/10/6982652937134804993_1.parquet
-    let columns = key.split('/').collect::<Vec<&str>>();
-    if columns.len() < 9 {
-        return Err(anyhow::anyhow!(
-            "[incr_stream_stats] Invalid file path: {}",
-            key
-        ));
-    }
-    // let _ = columns[0];
-    let org_id = columns[1];
-    let stream_type = columns[2];
-    let stream_name = columns[3];
-    let key = format!("{org_id}/{stream_type}/{stream_name}");
+    let key = stream_key_from_file(key)?;
     let mut stats = STATS.entry(key).or_default();
     if stats.doc_time_min > val.min_ts || stats.doc_time_min == 0 {
         stats.doc_time_min = val.min_ts;
@@ -88,6 +76,40 @@ pub fn incr_stream_stats(key: &str, val: &FileMeta) -> Result<(), anyhow::Error>
     Ok(())
 }
 
+/// Removes a file from the stats of its stream, the time range of the stream
+/// is kept.
+#[inline]
+pub fn decr_stream_stats(key: &str, val: &FileMeta) -> Result<(), anyhow::Error> {
+    if val.records == 0 {
+        return Ok(());
+    }
+
+    let key = stream_key_from_file(key)?;
+    let Some(mut stats) = STATS.get_mut(&key) else {
+        return Ok(());
+    };
+    stats.doc_num = (stats.doc_num - val.records).max(0);
+    stats.file_num = (stats.file_num - 1).max(0);
+    stats.storage_size = (stats.storage_size - val.original_size as f64).max(0.0);
+    stats.compressed_size = (stats.compressed_size - val.compressed_size as f64).max(0.0);
+    stats.index_size = (stats.index_size - val.index_size as f64).max(0.0);
+
+    Ok(())
+}
+
+fn stream_key_from_file(key: &str) -> Result<String, anyhow::Error> {
+    // eg: files/default/logs/olympics/2022/10/03/10/6982652937134804993_1.parquet
+    let columns = key.split('/').collect::<Vec<&str>>();
+    if columns.len() < 9 {
+        return Err(anyhow::anyhow!("[stream_stats] Invalid file path: {}", key));
+    }
+    // let _ = columns[0];
+    let org_id = columns[1];
+    let stream_type = columns[2];
+    let stream_name = columns[3];
+    Ok(format!("{org_id}/{stream_type}/{stream_name}"))
+}
+
 #[inline]
 pub fn get_stream_stats_len() -> usize {
     STATS.len()
@@ -128,4 +150,32 @@ mod tests {
         let stats = get_stream_stats("nexus", "default", StreamType::Logs);
         assert_eq!(stats.doc_num, 5000);
     }
+
+    #[test]
+    fn test_incr_decr_stream_stats() {
+        let key = "files/stats_test/logs/default/2022/10/03/10/6982652937134804993_1.parquet";
+        let meta = FileMeta {
+            min_ts: 100,
+            max_ts: 200,
+            records: 10,
+            original_size: 1000,
+            compressed_size: 100,
+            ..Default::default()
+        };
+        incr_stream_stats(key, &meta).unwrap();
+        incr_stream_stats(key, &meta).unwrap();
+        decr_stream_stats(key, &meta).unwrap();
+        let stats = get_stream_stats("stats_test", "default", StreamType::Logs);
+        assert_eq!(stats.doc_num, 10);
+        assert_eq!(stats.file_num, 1);
+        assert_eq!(stats.compressed_size, 100.0);
+        assert_eq!(stats.doc_time_min, 100);
+
+        decr_stream_stats(key, &meta).unwrap();
+        decr_stream_stats(key, &meta).unwrap();
+        let stats = get_stream_stats("stats_test", "default", StreamType::Logs);
+        assert_eq!(stats.doc_num, 0);
+        assert_eq!(stats.file_num, 0);
+        assert!(decr_stream_stats("files/invalid", &meta).is_err());
+    }
 }
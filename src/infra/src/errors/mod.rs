@@ -85,6 +85,12 @@ pub enum Error {
     ResourceError(String),
     #[error("Error# {0}")]
     IngestionError(String),
+    #[error("Error# {message}")]
+    IngestionThrottled {
+        reason: String,
+        message: String,
+        retry_after: u64,
+    },
     #[error("Error# {0}")]
     WalFileError(String),
     #[error("Error# {0}")]
@@ -194,6 +200,7 @@ pub enum ErrorCodes {
     SearchTimeout(String),
     InvalidParams(String),
     RatelimitExceeded(String),
+    QuotaExceeded(String),
 }
 
 impl From<sea_orm::DbErr> for Error {
@@ -248,6 +255,7 @@ impl ErrorCodes {
             ErrorCodes::SearchTimeout(_) => 20010,
             ErrorCodes::InvalidParams(_) => 20011,
             ErrorCodes::RatelimitExceeded(_) => 20012,
+            ErrorCodes::QuotaExceeded(_) => 20013,
         }
     }
 
@@ -274,6 +282,7 @@ impl ErrorCodes {
             ErrorCodes::SearchTimeout(_) => "Search query timed out".to_string(),
             ErrorCodes::InvalidParams(_) => "Invalid parameters".to_string(),
             ErrorCodes::RatelimitExceeded(_) => "Ratelimit exceeded".to_string(),
+            ErrorCodes::QuotaExceeded(_) => "Quota exceeded".to_string(),
         }
     }
 
@@ -292,6 +301,7 @@ impl ErrorCodes {
             ErrorCodes::SearchTimeout(msg) => msg.to_owned(),
             ErrorCodes::InvalidParams(msg) => msg.to_owned(),
             ErrorCodes::RatelimitExceeded(msg) => msg.to_owned(),
+            ErrorCodes::QuotaExceeded(msg) => msg.to_owned(),
         }
     }
 
@@ -310,6 +320,7 @@ impl ErrorCodes {
             ErrorCodes::SearchTimeout(msg) => msg.to_owned(),
             ErrorCodes::InvalidParams(msg) => msg.to_owned(),
             ErrorCodes::RatelimitExceeded(msg) => msg.to_owned(),
+            ErrorCodes::QuotaExceeded(msg) => msg.to_owned(),
         }
     }
 
@@ -359,6 +370,7 @@ impl ErrorCodes {
             20008 => Ok(ErrorCodes::SearchSQLExecuteError(message)),
             20009 => Ok(ErrorCodes::SearchCancelQuery(message)),
             20010 => Ok(ErrorCodes::SearchTimeout(message)),
+            20013 => Ok(ErrorCodes::QuotaExceeded(message)),
             _ => Ok(ErrorCodes::ServerInternalError(json.to_string())),
         }
     }
@@ -63,6 +63,7 @@ CREATE TABLE IF NOT EXISTS pipeline
     derived_stream  TEXT,
     nodes           TEXT,
     edges           TEXT,
+    dead_letter_stream VARCHAR(256),
     created_at      TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 );
             "#,
@@ -70,6 +71,22 @@ CREATE TABLE IF NOT EXISTS pipeline
         .execute(&pool)
         .await?;
 
+        // add the columns which were introduced after the table was created
+        let has_column = sqlx::query_scalar::<_, i64>(
+            "SELECT count(*) FROM INFORMATION_SCHEMA.COLUMNS WHERE table_name='pipeline' AND column_name='dead_letter_stream';",
+        )
+        .fetch_one(&pool)
+        .await?;
+        if has_column == 0
+            && let Err(e) =
+                sqlx::query("ALTER TABLE pipeline ADD COLUMN dead_letter_stream VARCHAR(256);")
+                    .execute(&pool)
+                    .await
+            && !e.to_string().contains("Duplicate column name")
+        {
+            return Err(e.into());
+        }
+
         Ok(())
     }
 
@@ -118,8 +135,8 @@ CREATE TABLE IF NOT EXISTS pipeline
                 );
                 sqlx::query(
                     r#"
-INSERT IGNORE INTO pipeline (id, version, enabled, name, description, org, source_type, stream_org, stream_name, stream_type, nodes, edges)
-    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
+INSERT IGNORE INTO pipeline (id, version, enabled, name, description, org, source_type, stream_org, stream_name, stream_type, nodes, edges, dead_letter_stream)
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
                     "#,
                 )
                 .bind(&pipeline.id)
@@ -134,6 +151,7 @@ INSERT IGNORE INTO pipeline (id, version, enabled, name, description, org, sourc
                 .bind(stream_type)
                 .bind(json::to_string(&pipeline.nodes).expect("Serializing pipeline nodes error"))
                 .bind(json::to_string(&pipeline.edges).expect("Serializing pipeline edges error"))
+                .bind(&pipeline.dead_letter_stream)
                 .execute(&mut *tx)
                 .await
             }
@@ -145,8 +163,8 @@ INSERT IGNORE INTO pipeline (id, version, enabled, name, description, org, sourc
                 );
                 sqlx::query(
                     r#"
-INSERT IGNORE INTO pipeline (id, version, enabled, name, description, org, source_type, derived_stream, nodes, edges)
-    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
+INSERT IGNORE INTO pipeline (id, version, enabled, name, description, org, source_type, derived_stream, nodes, edges, dead_letter_stream)
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
                     "#,
                 )
                 .bind(&pipeline.id)
@@ -159,6 +177,7 @@ INSERT IGNORE INTO pipeline (id, version, enabled, name, description, org, sourc
                 .bind(derived_stream_str)
                 .bind(json::to_string(&pipeline.nodes).expect("Serializing pipeline nodes error"))
                 .bind(json::to_string(&pipeline.edges).expect("Serializing pipeline edges error"))
+                .bind(&pipeline.dead_letter_stream)
                 .execute(&mut *tx)
                 .await
             }
@@ -192,7 +211,7 @@ INSERT IGNORE INTO pipeline (id, version, enabled, name, description, org, sourc
                 sqlx::query(
                     r#"
 UPDATE pipeline
-    SET version = ?, enabled = ?, name = ?, description = ?, org = ?, source_type = ?, stream_org = ?, stream_name = ?, stream_type = ?, nodes = ?, edges = ?
+    SET version = ?, enabled = ?, name = ?, description = ?, org = ?, source_type = ?, stream_org = ?, stream_name = ?, stream_type = ?, nodes = ?, edges = ?, dead_letter_stream = ?
     WHERE id =?;
                     "#,
                 )
@@ -207,6 +226,7 @@ UPDATE pipeline
                 .bind(stream_type)
                 .bind(json::to_string(&pipeline.nodes).expect("Serializing pipeline nodes error"))
                 .bind(json::to_string(&pipeline.edges).expect("Serializing pipeline edges error"))
+                .bind(&pipeline.dead_letter_stream)
                 .bind(&pipeline.id)
                 .execute(&mut *tx)
                 .await
@@ -220,7 +240,7 @@ UPDATE pipeline
                 sqlx::query(
                     r#"
 UPDATE pipeline
-    SET version = ?, enabled = ?, name = ?, description = ?, org = ?, source_type = ?, derived_stream = ?, nodes = ?, edges = ?
+    SET version = ?, enabled = ?, name = ?, description = ?, org = ?, source_type = ?, derived_stream = ?, nodes = ?, edges = ?, dead_letter_stream = ?
     WHERE id = ?;
                     "#,
                 )
@@ -233,6 +253,7 @@ UPDATE pipeline
                 .bind(derived_stream_str)
                 .bind(json::to_string(&pipeline.nodes).expect("Serializing pipeline nodes error"))
                 .bind(json::to_string(&pipeline.edges).expect("Serializing pipeline edges error"))
+                .bind(&pipeline.dead_letter_stream)
                 .bind(&pipeline.id)
                 .execute(&mut *tx)
                 .await
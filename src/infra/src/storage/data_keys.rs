@@ -0,0 +1,233 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-org data keys of the encryption at rest. A data key is a random
+//! AES-256 key, stored in the cipher table and wrapped either by the master
+//! key of the table or by the KMS plugin. Keys are never deleted, the files
+//! keep the version of the key they were written with.
+
+use std::sync::Arc;
+
+use base64::{Engine, prelude::BASE64_STANDARD};
+use config::{
+    get_config,
+    meta::encryption::{DataKeyEntry, DataKeyInfo, KeyProvider},
+    utils::{json, time::now_micros},
+};
+use hashbrown::HashMap;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use rand::RngCore;
+
+use crate::{
+    errors,
+    table::cipher::{self, CipherEntry, EntryKind, ListFilter},
+};
+
+pub const KEY_LEN: usize = 32;
+
+/// Seconds the active version of an org is cached, a rotation on another
+/// node is picked up after it.
+pub const ACTIVE_KEY_TTL: i64 = 60;
+
+pub type DataKey = Arc<[u8; KEY_LEN]>;
+
+/// org_id/version -> key
+static KEYS: Lazy<RwLock<HashMap<String, DataKey>>> = Lazy::new(Default::default);
+
+/// org_id -> (active version, loaded at)
+static ACTIVE: Lazy<RwLock<HashMap<String, (u32, i64)>>> = Lazy::new(Default::default);
+
+/// Returns the version and the key new files of an org are encrypted with,
+/// the first key of the org is created on demand.
+pub async fn active_key(org_id: &str) -> Result<(u32, DataKey), anyhow::Error> {
+    let now = now_micros();
+    let cached = ACTIVE.read().get(org_id).copied();
+    if let Some((version, loaded_at)) = cached
+        && now - loaded_at < ACTIVE_KEY_TTL * 1_000_000
+        && let Some(key) = KEYS.read().get(&format!("{org_id}/{version}"))
+    {
+        return Ok((version, key.clone()));
+    }
+
+    let version = match load(org_id).await? {
+        Some(version) => version,
+        None => create(org_id, 1, "").await?,
+    };
+    Ok((version, get_key(org_id, version).await?))
+}
+
+/// Returns the key of a version, e.g. of the header of a file.
+pub async fn get_key(org_id: &str, version: u32) -> Result<DataKey, anyhow::Error> {
+    let cache_key = format!("{org_id}/{version}");
+    if let Some(key) = KEYS.read().get(&cache_key) {
+        return Ok(key.clone());
+    }
+    load(org_id).await?;
+    KEYS.read()
+        .get(&cache_key)
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("data key {version} of org {org_id} not found"))
+}
+
+/// Creates a new version of the data key of an org, new files are encrypted
+/// with it and the compactors rewrite the older files.
+pub async fn rotate(org_id: &str, created_by: &str) -> Result<u32, anyhow::Error> {
+    let version = load(org_id).await?.unwrap_or_default() + 1;
+    create(org_id, version, created_by).await
+}
+
+pub async fn list(org_id: &str) -> Result<Vec<DataKeyInfo>, anyhow::Error> {
+    let mut keys = list_entries(org_id)
+        .await?
+        .into_iter()
+        .filter_map(|entry| {
+            let data: DataKeyEntry = json::from_str(&entry.data).ok()?;
+            Some(DataKeyInfo {
+                version: data.version,
+                provider: data.provider,
+                created_at: data.created_at,
+                created_by: entry.created_by,
+            })
+        })
+        .collect::<Vec<_>>();
+    keys.sort_by_key(|k| k.version);
+    Ok(keys)
+}
+
+/// Caches all the keys of an org and returns the active version.
+async fn load(org_id: &str) -> Result<Option<u32>, anyhow::Error> {
+    let mut active = None;
+    for entry in list_entries(org_id).await? {
+        let data: DataKeyEntry = json::from_str(&entry.data)?;
+        let cache_key = format!("{org_id}/{}", data.version);
+        if !KEYS.read().contains_key(&cache_key) {
+            let key = unwrap_key(org_id, &data).await?;
+            KEYS.write().insert(cache_key, key);
+        }
+        active = active.max(Some(data.version));
+    }
+    if let Some(version) = active {
+        ACTIVE
+            .write()
+            .insert(org_id.to_string(), (version, now_micros()));
+    }
+    Ok(active)
+}
+
+async fn list_entries(org_id: &str) -> Result<Vec<CipherEntry>, anyhow::Error> {
+    let filter = ListFilter {
+        org: Some(org_id.to_string()),
+        kind: Some(EntryKind::DataKey),
+    };
+    Ok(cipher::list_filtered(filter, None).await?)
+}
+
+async fn create(org_id: &str, version: u32, created_by: &str) -> Result<u32, anyhow::Error> {
+    let mut key = [0u8; KEY_LEN];
+    rand::thread_rng().fill_bytes(&mut key);
+    let data = wrap_key(org_id, version, &key).await?;
+    let entry = CipherEntry {
+        org: org_id.to_string(),
+        created_at: data.created_at,
+        created_by: created_by.to_string(),
+        name: DataKeyEntry::name(version),
+        data: json::to_string(&data)?,
+        kind: EntryKind::DataKey,
+    };
+    match cipher::add(entry).await {
+        Ok(()) => {}
+        // another node created the same version first
+        Err(errors::Error::DbError(errors::DbError::UniqueViolation)) => {
+            return load(org_id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("data key of org {org_id} not found"));
+        }
+        Err(e) => return Err(e.into()),
+    }
+    log::info!("[ENCRYPTION] created data key {version} of org {org_id}");
+    KEYS.write()
+        .insert(format!("{org_id}/{version}"), Arc::new(key));
+    ACTIVE
+        .write()
+        .insert(org_id.to_string(), (version, now_micros()));
+    Ok(version)
+}
+
+async fn wrap_key(
+    org_id: &str,
+    version: u32,
+    key: &[u8; KEY_LEN],
+) -> Result<DataKeyEntry, anyhow::Error> {
+    let plaintext = BASE64_STANDARD.encode(key);
+    let (provider, key) = if get_config().encryption.kms_url.is_empty() {
+        (KeyProvider::Local, plaintext)
+    } else {
+        let res = call_kms("wrap", org_id, "plaintext", &plaintext, "ciphertext").await?;
+        (KeyProvider::Kms, res)
+    };
+    Ok(DataKeyEntry {
+        version,
+        provider,
+        key,
+        created_at: now_micros(),
+    })
+}
+
+async fn unwrap_key(org_id: &str, data: &DataKeyEntry) -> Result<DataKey, anyhow::Error> {
+    let plaintext = match data.provider {
+        KeyProvider::Local => data.key.clone(),
+        KeyProvider::Kms => {
+            call_kms("unwrap", org_id, "ciphertext", &data.key, "plaintext").await?
+        }
+    };
+    let key: [u8; KEY_LEN] = BASE64_STANDARD
+        .decode(plaintext)?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("invalid data key {} of org {org_id}", data.version))?;
+    Ok(Arc::new(key))
+}
+
+/// Calls the KMS plugin, e.g. `POST /wrap {"key_id": org_id, "plaintext": ..}`
+/// which returns `{"ciphertext": ..}`.
+async fn call_kms(
+    action: &str,
+    org_id: &str,
+    field: &str,
+    value: &str,
+    result_field: &str,
+) -> Result<String, anyhow::Error> {
+    let cfg = get_config();
+    let body = json::json!({ "key_id": org_id, field: value });
+    let mut req = reqwest::Client::new()
+        .post(format!("{}/{action}", cfg.encryption.kms_url))
+        .header("Content-Type", "application/json")
+        .body(body.to_string());
+    if !cfg.encryption.kms_token.is_empty() {
+        req = req.bearer_auth(&cfg.encryption.kms_token);
+    }
+    let resp = req.send().await?;
+    if !resp.status().is_success() {
+        return Err(anyhow::anyhow!(
+            "KMS {action} of org {org_id} failed: {}",
+            resp.status()
+        ));
+    }
+    let resp: json::Value = json::from_slice(&resp.bytes().await?)?;
+    resp.get(result_field)
+        .and_then(|v| v.as_str())
+        .map(String::from)
+        .ok_or_else(|| anyhow::anyhow!("KMS {action} response has no {result_field}"))
+}
@@ -0,0 +1,537 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Encryption at rest of the stream files. The parquet and index files of
+//! the streams are encrypted with AES-256-CTR by the data key of their org,
+//! CTR keeps the range reads of the parquet footers and pages possible.
+//!
+//! An encrypted file is `MAGIC | key version (u32 BE) | IV | ciphertext`, the
+//! files without the header are read as they are, so the encryption can be
+//! enabled on an existing deployment.
+//!
+//! The encryption only keeps the data confidential, it doesn't protect its
+//! integrity: CTR has no authentication tag and the files carry no MAC, so a
+//! change of the ciphertext in the bucket is not detected and decrypts to
+//! altered data, and a file written without the header is read as plaintext.
+//! Write access to the bucket must be restricted to the cluster.
+
+use std::{fmt, ops::Range};
+
+use aes::Aes256;
+use async_trait::async_trait;
+use bytes::Bytes;
+use ctr::cipher::{KeyIvInit, StreamCipher, StreamCipherSeek};
+use futures::{StreamExt, stream::BoxStream};
+use hashlink::lru_cache::LruCache;
+use object_store::{
+    Error, GetOptions, GetRange, GetResult, GetResultPayload, ListResult, MultipartUpload,
+    ObjectMeta, PutMultipartOpts, PutOptions, PutPayload, PutResult, Result, UploadPart,
+    path::Path,
+};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use rand::RngCore;
+
+use crate::storage::{ObjectStoreExt, data_keys};
+
+type Aes256Ctr = ctr::Ctr128BE<Aes256>;
+
+pub const MAGIC: &[u8; 4] = b"O2E1";
+pub const IV_LEN: usize = 16;
+pub const HEADER_LEN: usize = MAGIC.len() + 4 + IV_LEN;
+
+const HEADER_CACHE_SIZE: usize = 10_000;
+
+/// path -> (e_tag, header), the e_tag tells a rewritten file apart
+static HEADERS: Lazy<Mutex<LruCache<String, (String, Option<Header>)>>> =
+    Lazy::new(|| Mutex::new(LruCache::new(HEADER_CACHE_SIZE)));
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Header {
+    pub version: u32,
+    pub iv: [u8; IV_LEN],
+}
+
+impl Header {
+    fn new(version: u32) -> Self {
+        let mut iv = [0u8; IV_LEN];
+        rand::thread_rng().fill_bytes(&mut iv);
+        Self { version, iv }
+    }
+
+    pub fn encode(&self) -> [u8; HEADER_LEN] {
+        let mut buf = [0u8; HEADER_LEN];
+        buf[..4].copy_from_slice(MAGIC);
+        buf[4..8].copy_from_slice(&self.version.to_be_bytes());
+        buf[8..].copy_from_slice(&self.iv);
+        buf
+    }
+
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if data.len() < HEADER_LEN || &data[..4] != MAGIC {
+            return None;
+        }
+        Some(Self {
+            version: u32::from_be_bytes(data[4..8].try_into().ok()?),
+            iv: data[8..HEADER_LEN].try_into().ok()?,
+        })
+    }
+
+    fn cipher(&self, key: &[u8; data_keys::KEY_LEN], offset: usize) -> Aes256Ctr {
+        let mut cipher = Aes256Ctr::new(key.into(), (&self.iv).into());
+        cipher.seek(offset as u64);
+        cipher
+    }
+}
+
+/// Encrypts the stream files written through the inner store and decrypts
+/// them on read, the other files are passed through.
+pub struct EncryptedStore {
+    inner: &'static dyn ObjectStoreExt,
+}
+
+impl EncryptedStore {
+    pub fn new_store(inner: &'static dyn ObjectStoreExt) -> Box<dyn ObjectStoreExt> {
+        Box::new(Self { inner })
+    }
+
+    async fn header(&self, account: &str, location: &Path) -> Result<(String, Option<Header>)> {
+        if let Some(cached) = HEADERS.lock().get(location.as_ref()) {
+            return Ok(cached.clone());
+        }
+        let (e_tag, header) = read_header(self.inner, account, location).await?;
+        let e_tag = e_tag.unwrap_or_default();
+        if !e_tag.is_empty() {
+            HEADERS
+                .lock()
+                .insert(location.to_string(), (e_tag.clone(), header));
+        }
+        Ok((e_tag, header))
+    }
+
+    async fn get_range_opts(
+        &self,
+        org_id: &str,
+        account: &str,
+        location: &Path,
+        options: GetOptions,
+        range: GetRange,
+    ) -> Result<GetResult> {
+        let mut retried = false;
+        loop {
+            let (e_tag, header) = self.header(account, location).await?;
+            let Some(header) = header else {
+                return self.inner.get_opts(account, location, options).await;
+            };
+            let mut opts = options.clone();
+            opts.range = Some(match &range {
+                GetRange::Bounded(r) => (r.start + HEADER_LEN..r.end + HEADER_LEN).into(),
+                GetRange::Offset(o) => GetRange::Offset(o + HEADER_LEN),
+                GetRange::Suffix(n) => GetRange::Suffix(*n),
+            });
+            if opts.if_match.is_none() && !e_tag.is_empty() {
+                opts.if_match = Some(e_tag);
+            }
+            let result = match self.inner.get_opts(account, location, opts).await {
+                Ok(result) => result,
+                // the file was rewritten with another key since the header was cached
+                Err(Error::Precondition { .. }) if !retried => {
+                    HEADERS.lock().remove(location.as_ref());
+                    retried = true;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            let mut meta = result.meta.clone();
+            let attributes = result.attributes.clone();
+            let raw_range = result.range.clone();
+            let data = result.bytes().await?;
+            // a suffix longer than the data also returns the header
+            let skip = HEADER_LEN.saturating_sub(raw_range.start).min(data.len());
+            let start = raw_range.start.max(HEADER_LEN) - HEADER_LEN;
+            let data = decrypt(org_id, &header, start, &data[skip..]).await?;
+            meta.size = meta.size.saturating_sub(HEADER_LEN);
+            return Ok(bytes_result(meta, attributes, start, data));
+        }
+    }
+}
+
+impl fmt::Display for EncryptedStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EncryptedStore({})", self.inner)
+    }
+}
+
+impl fmt::Debug for EncryptedStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EncryptedStore({:?})", self.inner)
+    }
+}
+
+#[async_trait]
+impl ObjectStoreExt for EncryptedStore {
+    fn get_account(&self, file: &str) -> Option<String> {
+        self.inner.get_account(file)
+    }
+
+    async fn put(&self, account: &str, location: &Path, payload: PutPayload) -> Result<PutResult> {
+        self.put_opts(account, location, payload, PutOptions::default())
+            .await
+    }
+
+    async fn put_opts(
+        &self,
+        account: &str,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> Result<PutResult> {
+        let Some(org_id) = file_org(location) else {
+            return self.inner.put_opts(account, location, payload, opts).await;
+        };
+        let (version, key) = data_keys::active_key(org_id).await.map_err(store_error)?;
+        let header = Header::new(version);
+        let mut buf = Vec::with_capacity(HEADER_LEN + payload.content_length());
+        buf.extend_from_slice(&header.encode());
+        for chunk in payload.iter() {
+            buf.extend_from_slice(chunk);
+        }
+        header
+            .cipher(&key, 0)
+            .apply_keystream(&mut buf[HEADER_LEN..]);
+        let result = self
+            .inner
+            .put_opts(account, location, buf.into(), opts)
+            .await?;
+        cache_header(location, result.e_tag.clone(), header);
+        Ok(result)
+    }
+
+    async fn put_multipart(
+        &self,
+        account: &str,
+        location: &Path,
+    ) -> Result<Box<dyn MultipartUpload>> {
+        self.put_multipart_opts(account, location, PutMultipartOpts::default())
+            .await
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        account: &str,
+        location: &Path,
+        opts: PutMultipartOpts,
+    ) -> Result<Box<dyn MultipartUpload>> {
+        let Some(org_id) = file_org(location) else {
+            return self.inner.put_multipart_opts(account, location, opts).await;
+        };
+        let (version, key) = data_keys::active_key(org_id).await.map_err(store_error)?;
+        let header = Header::new(version);
+        let inner = self
+            .inner
+            .put_multipart_opts(account, location, opts)
+            .await?;
+        HEADERS.lock().remove(location.as_ref());
+        Ok(Box::new(EncryptedUpload {
+            inner,
+            cipher: header.cipher(&key, 0),
+            header: Some(header.encode()),
+        }))
+    }
+
+    async fn get(&self, account: &str, location: &Path) -> Result<GetResult> {
+        self.get_opts(account, location, GetOptions::default())
+            .await
+    }
+
+    async fn get_opts(
+        &self,
+        account: &str,
+        location: &Path,
+        options: GetOptions,
+    ) -> Result<GetResult> {
+        let Some(org_id) = file_org(location) else {
+            return self.inner.get_opts(account, location, options).await;
+        };
+        if let Some(range) = options.range.clone() {
+            return self
+                .get_range_opts(org_id, account, location, options, range)
+                .await;
+        }
+        if options.head {
+            let mut result = self.inner.get_opts(account, location, options).await?;
+            if self.header(account, location).await?.1.is_some() {
+                result.meta.size = result.meta.size.saturating_sub(HEADER_LEN);
+                result.range = 0..result.meta.size;
+            }
+            return Ok(result);
+        }
+
+        // the whole file, the header comes with it
+        let result = self.inner.get_opts(account, location, options).await?;
+        let mut meta = result.meta.clone();
+        let attributes = result.attributes.clone();
+        let data = result.bytes().await?;
+        let Some(header) = Header::decode(&data) else {
+            return Ok(bytes_result(meta, attributes, 0, data));
+        };
+        cache_header(location, meta.e_tag.clone(), header);
+        let data = decrypt(org_id, &header, 0, &data[HEADER_LEN..]).await?;
+        meta.size = data.len();
+        Ok(bytes_result(meta, attributes, 0, data))
+    }
+
+    async fn get_range(
+        &self,
+        account: &str,
+        location: &Path,
+        range: Range<usize>,
+    ) -> Result<Bytes> {
+        if file_org(location).is_none() {
+            return self.inner.get_range(account, location, range).await;
+        }
+        let options = GetOptions {
+            range: Some(range.into()),
+            ..Default::default()
+        };
+        self.get_opts(account, location, options)
+            .await?
+            .bytes()
+            .await
+    }
+
+    async fn get_ranges(
+        &self,
+        account: &str,
+        location: &Path,
+        ranges: &[Range<usize>],
+    ) -> Result<Vec<Bytes>> {
+        if file_org(location).is_none() {
+            return self.inner.get_ranges(account, location, ranges).await;
+        }
+        futures::future::try_join_all(
+            ranges
+                .iter()
+                .map(|range| self.get_range(account, location, range.clone())),
+        )
+        .await
+    }
+
+    async fn head(&self, account: &str, location: &Path) -> Result<ObjectMeta> {
+        let mut meta = self.inner.head(account, location).await?;
+        if file_org(location).is_some() && self.header(account, location).await?.1.is_some() {
+            meta.size = meta.size.saturating_sub(HEADER_LEN);
+        }
+        Ok(meta)
+    }
+
+    async fn delete(&self, account: &str, location: &Path) -> Result<()> {
+        HEADERS.lock().remove(location.as_ref());
+        self.inner.delete(account, location).await
+    }
+
+    fn delete_stream<'a>(
+        &'a self,
+        account: &str,
+        locations: BoxStream<'a, Result<Path>>,
+    ) -> BoxStream<'a, Result<Path>> {
+        self.inner.delete_stream(account, locations)
+    }
+
+    fn list(&self, account: &str, prefix: Option<&Path>) -> BoxStream<'_, Result<ObjectMeta>> {
+        self.inner.list(account, prefix)
+    }
+
+    fn list_with_offset(
+        &self,
+        account: &str,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'_, Result<ObjectMeta>> {
+        self.inner.list_with_offset(account, prefix, offset)
+    }
+
+    async fn list_with_delimiter(
+        &self,
+        account: &str,
+        prefix: Option<&Path>,
+    ) -> Result<ListResult> {
+        self.inner.list_with_delimiter(account, prefix).await
+    }
+
+    async fn copy(&self, account: &str, from: &Path, to: &Path) -> Result<()> {
+        HEADERS.lock().remove(to.as_ref());
+        self.inner.copy(account, from, to).await
+    }
+
+    async fn rename(&self, account: &str, from: &Path, to: &Path) -> Result<()> {
+        HEADERS.lock().remove(from.as_ref());
+        HEADERS.lock().remove(to.as_ref());
+        self.inner.rename(account, from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, account: &str, from: &Path, to: &Path) -> Result<()> {
+        self.inner.copy_if_not_exists(account, from, to).await
+    }
+
+    async fn rename_if_not_exists(&self, account: &str, from: &Path, to: &Path) -> Result<()> {
+        HEADERS.lock().remove(from.as_ref());
+        self.inner.rename_if_not_exists(account, from, to).await
+    }
+}
+
+/// Encrypts the parts of a multipart upload in the order they are put, the
+/// header is prepended to the first part.
+struct EncryptedUpload {
+    inner: Box<dyn MultipartUpload>,
+    cipher: Aes256Ctr,
+    header: Option<[u8; HEADER_LEN]>,
+}
+
+impl fmt::Debug for EncryptedUpload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EncryptedUpload({:?})", self.inner)
+    }
+}
+
+#[async_trait]
+impl MultipartUpload for EncryptedUpload {
+    fn put_part(&mut self, data: PutPayload) -> UploadPart {
+        let mut buf = Vec::with_capacity(HEADER_LEN + data.content_length());
+        if let Some(header) = self.header.take() {
+            buf.extend_from_slice(&header);
+        }
+        let start = buf.len();
+        for chunk in data.iter() {
+            buf.extend_from_slice(chunk);
+        }
+        self.cipher.apply_keystream(&mut buf[start..]);
+        self.inner.put_part(buf.into())
+    }
+
+    async fn complete(&mut self) -> Result<PutResult> {
+        self.inner.complete().await
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        self.inner.abort().await
+    }
+}
+
+/// Reads the encryption header of a file from the store, a file without it
+/// is not encrypted. Returns the e_tag of the file along with the header.
+pub async fn read_header(
+    store: &dyn ObjectStoreExt,
+    account: &str,
+    location: &Path,
+) -> Result<(Option<String>, Option<Header>)> {
+    let options = GetOptions {
+        range: Some((0..HEADER_LEN).into()),
+        ..Default::default()
+    };
+    let result = store.get_opts(account, location, options).await?;
+    let e_tag = result.meta.e_tag.clone();
+    let data = result.bytes().await?;
+    Ok((e_tag, Header::decode(&data)))
+}
+
+/// Returns the org of a stream file, only the stream files are encrypted.
+/// eg: files/default/logs/olympics/2023/08/21/08/a.parquet
+pub fn file_org(location: &Path) -> Option<&str> {
+    let mut parts = location.as_ref().split('/');
+    if parts.next()? != "files" {
+        return None;
+    }
+    parts.next().filter(|org| !org.is_empty())
+}
+
+fn cache_header(location: &Path, e_tag: Option<String>, header: Header) {
+    match e_tag {
+        Some(e_tag) => HEADERS
+            .lock()
+            .insert(location.to_string(), (e_tag, Some(header))),
+        None => HEADERS.lock().remove(location.as_ref()),
+    };
+}
+
+async fn decrypt(org_id: &str, header: &Header, offset: usize, data: &[u8]) -> Result<Bytes> {
+    let key = data_keys::get_key(org_id, header.version)
+        .await
+        .map_err(store_error)?;
+    let mut buf = data.to_vec();
+    header.cipher(&key, offset).apply_keystream(&mut buf);
+    Ok(buf.into())
+}
+
+fn bytes_result(
+    meta: ObjectMeta,
+    attributes: object_store::Attributes,
+    start: usize,
+    data: Bytes,
+) -> GetResult {
+    let range = start..start + data.len();
+    GetResult {
+        payload: GetResultPayload::Stream(futures::stream::once(async move { Ok(data) }).boxed()),
+        meta,
+        range,
+        attributes,
+    }
+}
+
+fn store_error(e: anyhow::Error) -> Error {
+    Error::Generic {
+        store: "encryption",
+        source: e.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header() {
+        let header = Header::new(3);
+        let encoded = header.encode();
+        assert_eq!(Header::decode(&encoded), Some(header));
+        assert_eq!(Header::decode(b"PAR1 plain parquet file content"), None);
+        assert_eq!(Header::decode(&encoded[..HEADER_LEN - 1]), None);
+    }
+
+    #[test]
+    fn test_cipher_seek() {
+        let key = [7u8; data_keys::KEY_LEN];
+        let header = Header::new(1);
+        let plain = (0..255u8).collect::<Vec<_>>();
+        let mut data = plain.clone();
+        header.cipher(&key, 0).apply_keystream(&mut data);
+        assert_ne!(data, plain);
+        // a range decrypts on its own
+        let mut part = data[100..150].to_vec();
+        header.cipher(&key, 100).apply_keystream(&mut part);
+        assert_eq!(part, &plain[100..150]);
+    }
+
+    #[test]
+    fn test_file_org() {
+        let path = Path::from("files/default/logs/app/2024/01/01/00/a.parquet");
+        assert_eq!(file_org(&path), Some("default"));
+        assert_eq!(
+            file_org(&Path::from("file_list/2024/01/01/00/a.json.zst")),
+            None
+        );
+        assert_eq!(file_org(&Path::from("meta/default/a.json")), None);
+    }
+}
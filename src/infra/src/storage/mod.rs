@@ -29,6 +29,8 @@ use once_cell::sync::Lazy;
 use parquet::file::metadata::ParquetMetaDataReader;
 
 pub mod accounts;
+pub mod data_keys;
+pub mod encryption;
 mod local;
 mod remote;
 pub mod wal;
@@ -37,7 +39,17 @@ pub use remote::test_config as test_remote_config;
 
 pub const CONCURRENT_REQUESTS: usize = 1000;
 
-static MULTI_ACCOUNTS: Lazy<Box<dyn ObjectStoreExt>> = Lazy::new(accounts::default);
+static ACCOUNTS: Lazy<Box<dyn ObjectStoreExt>> = Lazy::new(accounts::default);
+
+/// The store the files are read and written through, the stream files are
+/// encrypted by it when the encryption at rest is enabled.
+static MULTI_ACCOUNTS: Lazy<&'static dyn ObjectStoreExt> = Lazy::new(|| {
+    if get_config().encryption.at_rest_enabled {
+        Box::leak(encryption::EncryptedStore::new_store(&**ACCOUNTS))
+    } else {
+        &**ACCOUNTS
+    }
+});
 
 // Create a wrapper trait that extends ObjectStore
 #[async_trait]
@@ -131,6 +143,13 @@ pub async fn head(account: &str, file: &str) -> Result<ObjectMeta> {
     MULTI_ACCOUNTS.head(account, &file.into()).await
 }
 
+/// Returns the version of the data key a file is encrypted with, `None` for
+/// a file which is not encrypted.
+pub async fn get_key_version(account: &str, file: &str) -> Result<Option<u32>> {
+    let (_, header) = encryption::read_header(&**ACCOUNTS, account, &file.into()).await?;
+    Ok(header.map(|h| h.version))
+}
+
 pub async fn get_bytes(account: &str, file: &str) -> Result<bytes::Bytes> {
     let data = get(account, file).await?;
     data.bytes().await
@@ -157,6 +176,19 @@ pub async fn put(account: &str, file: &str, data: bytes::Bytes) -> Result<()> {
     Ok(())
 }
 
+/// Writes a file in a single request with the given options, e.g. a
+/// conditional put. Large files are not split into a multipart upload.
+pub async fn put_opts(
+    account: &str,
+    file: &str,
+    data: bytes::Bytes,
+    opts: PutOptions,
+) -> Result<PutResult> {
+    MULTI_ACCOUNTS
+        .put_opts(account, &file.into(), data.into(), opts)
+        .await
+}
+
 pub async fn put_multipart(account: &str, file: &str, data: bytes::Bytes) -> Result<()> {
     let path = Path::from(file);
     let upload = MULTI_ACCOUNTS.put_multipart(account, &path).await?;
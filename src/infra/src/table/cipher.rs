@@ -29,6 +29,8 @@ use crate::{
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EntryKind {
     CipherKey,
+    /// Data key of the encryption at rest of an org
+    DataKey,
 }
 
 // DBKey to set cipher keys
@@ -105,6 +107,7 @@ impl std::fmt::Display for EntryKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::CipherKey => write!(f, "cipher_key"),
+            Self::DataKey => write!(f, "data_key"),
         }
     }
 }
@@ -114,6 +117,7 @@ impl TryFrom<String> for EntryKind {
     fn try_from(value: String) -> Result<Self, Self::Error> {
         match value.as_str() {
             "cipher_key" => Ok(Self::CipherKey),
+            "data_key" => Ok(Self::DataKey),
             _ => Err(errors::Error::NotImplemented),
         }
     }
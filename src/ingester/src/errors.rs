@@ -120,4 +120,8 @@ pub enum Error {
     ExternalError {
         source: Box<dyn std::error::Error + Send + Sync>,
     },
+    #[snafu(display("WalReplicationError: {}", message))]
+    WalReplicationError {
+        message: String,
+    },
 }
@@ -29,6 +29,7 @@ use crate::{
     entry::PersistStat,
     errors::{DeleteFileSnafu, RenameFileSnafu, Result, TokioMpscSendSnafu, WriteDataSnafu},
     memtable::MemTable,
+    replication,
     rwmap::RwIndexMap,
     writer::WriterKey,
 };
@@ -93,6 +94,14 @@ impl Immutable {
         fs::remove_file(wal_path)
             .await
             .context(DeleteFileSnafu { path: wal_path })?;
+        // the data is persisted, the peers can drop the replica
+        if let Err(e) = replication::release(wal_path) {
+            log::error!(
+                "[INGESTER:MEM] release wal replica {} error: {}",
+                wal_path.display(),
+                e
+            );
+        }
         // 4. rename the tmp files to parquet files
         for (path, stat) in paths {
             persist_stat += stat;
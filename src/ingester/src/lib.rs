@@ -18,6 +18,7 @@ pub mod errors;
 mod immutable;
 mod memtable;
 mod partition;
+mod replication;
 mod rwmap;
 mod stream;
 mod wal;
@@ -30,6 +31,12 @@ use config::RwAHashMap;
 pub use entry::Entry;
 pub use immutable::read_from_immutable;
 use once_cell::sync::Lazy;
+pub use replication::{
+    WalReplicator, clean_replicas as clean_wal_replicas,
+    list_replica_sources as list_wal_replica_sources, recover_replicas as recover_wal_replicas,
+    release_replica as release_wal_replica, set_replicator as set_wal_replicator,
+    write_replica as write_wal_replica,
+};
 use snafu::ResultExt;
 use tokio::sync::{Mutex, mpsc};
 pub use writer::{
@@ -187,13 +187,14 @@ impl Partition {
                     batch_num: data.len(),
                 };
                 // write into parquet buf
+                let settings = infra::schema::unwrap_stream_settings(self.schema.as_ref());
                 let bloom_filter_fields =
                     if self.schema.fields().len() >= cfg.limit.file_move_fields_limit {
-                        let settings = infra::schema::unwrap_stream_settings(self.schema.as_ref());
                         infra::schema::get_stream_setting_bloom_filter_fields(&settings)
                     } else {
                         vec![]
                     };
+                let parquet_settings = settings.and_then(|s| s.parquet_settings);
 
                 let batches = data
                     .iter()
@@ -220,6 +221,7 @@ impl Partition {
                     &file_meta,
                     true,
                     compression,
+                    parquet_settings.as_ref(),
                 );
 
                 writer
@@ -0,0 +1,539 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// WAL replication:
+// 1. the source ingester ships every wal entry to the peers before (sync) or after (async) it
+//    writes the local wal file, and tells the peers when the wal file is rotated (sealed)
+// 2. the peer writes the entries into
+//    `{wal_dir}/replicas/{source_node}/{idx}/{org}/{stype}/{id}.wal`
+// 3. when the source ingester is gone, the peer chosen by the consistent hash moves the replicas
+//    into its own wal dir and replays them, the data then goes through the normal persist & upload
+// 4. when the source ingester persists a wal file it tells the peers to drop the replica, so a
+//    failover doesn't replay data which was already persisted
+// 5. replicas which were not released (e.g. the release was lost) are deleted after the retention
+//    time
+
+use std::{
+    fs::{create_dir_all, remove_file},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use config::{RwAHashMap, get_config, metrics, utils::time::now_micros};
+use once_cell::sync::{Lazy, OnceCell};
+use snafu::ResultExt;
+use tokio::sync::{Mutex, mpsc};
+use wal::Writer as WalWriter;
+
+use crate::{WAL_DIR_DEFAULT_PREFIX, errors::*, wal::wal_scan_files};
+
+pub const WAL_REPLICA_DIR: &str = "replicas";
+
+/// Transport used to ship wal entries to the peer ingesters.
+#[async_trait]
+pub trait WalReplicator: Send + Sync {
+    /// Ships the entries of the wal file, `file_path` is relative to the wal dir.
+    async fn replicate(
+        &self,
+        file_path: &str,
+        entries: Vec<Vec<u8>>,
+        sealed: bool,
+    ) -> std::result::Result<(), String>;
+
+    /// Tells the peers the wal file was persisted by the source ingester and
+    /// the replica can be dropped.
+    async fn release(&self, file_path: &str) -> std::result::Result<(), String>;
+}
+
+struct ReplicationTask {
+    file_path: String,
+    entries: Vec<Vec<u8>>,
+    sealed: bool,
+    persisted: bool,
+}
+
+static REPLICATOR: OnceCell<Arc<dyn WalReplicator>> = OnceCell::new();
+static REPLICATION_QUEUE: OnceCell<mpsc::Sender<ReplicationTask>> = OnceCell::new();
+
+// replica writers, key: source_node/file_path
+static REPLICA_WRITERS: Lazy<RwAHashMap<String, Arc<Mutex<ReplicaWriter>>>> =
+    Lazy::new(Default::default);
+
+struct ReplicaWriter {
+    wal: WalWriter,
+    updated_at: i64,
+}
+
+/// Registers the transport and starts the async replication queue. Only the
+/// first registration takes effect.
+pub fn set_replicator(replicator: Arc<dyn WalReplicator>) {
+    if REPLICATOR.set(replicator.clone()).is_err() {
+        return;
+    }
+    let (tx, mut rx) =
+        mpsc::channel::<ReplicationTask>(get_config().limit.wal_replication_queue_size);
+    _ = REPLICATION_QUEUE.set(tx);
+    tokio::task::spawn(async move {
+        while let Some(task) = rx.recv().await {
+            let (org_id, stream_type) = split_file_path(&task.file_path);
+            let size = task.entries.iter().map(|e| e.len()).sum::<usize>();
+            let ret = if task.persisted {
+                replicator.release(&task.file_path).await
+            } else {
+                replicator
+                    .replicate(&task.file_path, task.entries, task.sealed)
+                    .await
+            };
+            if let Err(e) = ret {
+                log::error!(
+                    "[INGESTER:REPLICATION] replicate wal file {} error: {}",
+                    task.file_path,
+                    e
+                );
+                metrics::INGEST_WAL_REPLICATION_ERRORS
+                    .with_label_values(&[org_id, stream_type])
+                    .inc();
+                continue;
+            }
+            metrics::INGEST_WAL_REPLICATION_BYTES
+                .with_label_values(&[org_id, stream_type])
+                .inc_by(size as u64);
+        }
+        log::info!("[INGESTER:REPLICATION] replication queue closed");
+    });
+}
+
+#[inline]
+pub(crate) fn is_enabled() -> bool {
+    get_config().common.wal_replication_enabled && REPLICATOR.get().is_some()
+}
+
+#[inline]
+fn is_sync_mode() -> bool {
+    get_config().common.wal_replication_mode == "sync"
+}
+
+/// Sync mode: ships the entries and waits for the peers, it is called before
+/// writing the local wal so a failed replication fails the write.
+pub(crate) async fn replicate_sync(wal_path: &Path, entries: &[Vec<u8>]) -> Result<()> {
+    if !is_enabled() || !is_sync_mode() {
+        return Ok(());
+    }
+    let file_path = get_relative_path(wal_path)?;
+    let (org_id, stream_type) = split_file_path(&file_path);
+    let size = entries.iter().map(|e| e.len()).sum::<usize>();
+    let replicator = REPLICATOR.get().unwrap();
+    if let Err(e) = replicator
+        .replicate(&file_path, entries.to_vec(), false)
+        .await
+    {
+        metrics::INGEST_WAL_REPLICATION_ERRORS
+            .with_label_values(&[org_id, stream_type])
+            .inc();
+        return Err(Error::WalReplicationError { message: e });
+    }
+    metrics::INGEST_WAL_REPLICATION_BYTES
+        .with_label_values(&[org_id, stream_type])
+        .inc_by(size as u64);
+    Ok(())
+}
+
+/// Async mode: queues the entries after they were written to the local wal,
+/// the entries are dropped when the queue is full.
+pub(crate) fn replicate_async(wal_path: &Path, entries: Vec<Vec<u8>>) -> Result<()> {
+    if !is_enabled() || is_sync_mode() {
+        return Ok(());
+    }
+    enqueue(wal_path, entries, false, false)
+}
+
+/// Tells the peers the wal file was rotated. Sync mode waits for the peers like
+/// the entries do, async mode queues it behind the entries to keep the order.
+pub(crate) async fn seal(wal_path: &Path) -> Result<()> {
+    if !is_enabled() {
+        return Ok(());
+    }
+    if is_sync_mode() {
+        let file_path = get_relative_path(wal_path)?;
+        return REPLICATOR
+            .get()
+            .unwrap()
+            .replicate(&file_path, vec![], true)
+            .await
+            .map_err(|message| Error::WalReplicationError { message });
+    }
+    enqueue(wal_path, vec![], true, false)
+}
+
+/// Tells the peers the wal file was persisted, it goes through the queue in
+/// both modes so a slow peer doesn't block the persist job.
+pub(crate) fn release(wal_path: &Path) -> Result<()> {
+    if !is_enabled() {
+        return Ok(());
+    }
+    enqueue(wal_path, vec![], false, true)
+}
+
+fn enqueue(wal_path: &Path, entries: Vec<Vec<u8>>, sealed: bool, persisted: bool) -> Result<()> {
+    let Some(queue) = REPLICATION_QUEUE.get() else {
+        return Ok(());
+    };
+    let file_path = get_relative_path(wal_path)?;
+    if let Err(e) = queue.try_send(ReplicationTask {
+        file_path: file_path.clone(),
+        entries,
+        sealed,
+        persisted,
+    }) {
+        let (org_id, stream_type) = split_file_path(&file_path);
+        log::error!(
+            "[INGESTER:REPLICATION] replication queue is full, drop entries of {}: {}",
+            file_path,
+            e
+        );
+        metrics::INGEST_WAL_REPLICATION_ERRORS
+            .with_label_values(&[org_id, stream_type])
+            .inc();
+    }
+    Ok(())
+}
+
+/// Writes the entries received from a source ingester into the replica wal file.
+pub async fn write_replica(
+    source_node: &str,
+    file_path: &str,
+    entries: Vec<Vec<u8>>,
+    sealed: bool,
+) -> Result<()> {
+    validate_file_path(source_node, file_path)?;
+    let key = format!("{source_node}/{file_path}");
+    let writer = REPLICA_WRITERS.read().await.get(&key).cloned();
+    let writer = match writer {
+        Some(w) => w,
+        // the replica was already sealed or cleaned, nothing to write
+        None if entries.is_empty() => return Ok(()),
+        None => {
+            let mut w = REPLICA_WRITERS.write().await;
+            match w.get(&key) {
+                Some(v) => v.clone(),
+                None => {
+                    let v = Arc::new(Mutex::new(open_replica(source_node, file_path)?));
+                    w.insert(key.clone(), v.clone());
+                    v
+                }
+            }
+        }
+    };
+
+    let mut replica = writer.lock().await;
+    for entry in entries.iter() {
+        if entry.is_empty() {
+            continue;
+        }
+        replica.wal.write(entry).context(WalSnafu)?;
+    }
+    replica.wal.sync().context(WalSnafu)?;
+    replica.updated_at = now_micros();
+    drop(replica);
+
+    if sealed {
+        REPLICA_WRITERS.write().await.remove(&key);
+    }
+    Ok(())
+}
+
+/// Drops the replica of a wal file which was persisted by the source ingester.
+pub async fn release_replica(source_node: &str, file_path: &str) -> Result<()> {
+    validate_file_path(source_node, file_path)?;
+    let key = format!("{source_node}/{file_path}");
+    REPLICA_WRITERS.write().await.remove(&key);
+    let path = get_replica_dir().join(&key);
+    if path.exists() {
+        remove_file(&path).context(DeleteFileSnafu { path: path.clone() })?;
+    }
+    // the entries kept aside when the replica was reopened are persisted too
+    let Some(prefix) = backup_prefix(&path) else {
+        return Ok(());
+    };
+    let Some(dir) = path.parent() else {
+        return Ok(());
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Ok(());
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let name = entry.file_name();
+        if name
+            .to_str()
+            .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(".wal"))
+        {
+            let path = entry.path();
+            remove_file(&path).context(DeleteFileSnafu { path })?;
+        }
+    }
+    Ok(())
+}
+
+/// The backups of a replica are named `{id}.{now_micros}.wal`, next to the
+/// replica `{id}.wal`, so that the release of the wal file removes them.
+fn backup_prefix(path: &Path) -> Option<String> {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .map(|stem| format!("{stem}."))
+}
+
+fn open_replica(source_node: &str, file_path: &str) -> Result<ReplicaWriter> {
+    let path = get_replica_dir().join(source_node).join(file_path);
+    // the wal writer truncates the file, keep the entries received before a restart
+    if path.exists()
+        && let Some(prefix) = backup_prefix(&path)
+    {
+        let mut backup = path.clone();
+        backup.set_file_name(format!("{prefix}{}.wal", now_micros()));
+        std::fs::rename(&path, &backup).context(RenameFileSnafu { path: path.clone() })?;
+    }
+    let (wal, _) = WalWriter::new(path, 0, get_config().limit.wal_write_buffer_size, None)
+        .context(WalSnafu)?;
+    Ok(ReplicaWriter {
+        wal,
+        updated_at: now_micros(),
+    })
+}
+
+/// Lists the source ingesters which have replicas on this node.
+pub async fn list_replica_sources() -> Vec<String> {
+    let Ok(dirs) = std::fs::read_dir(get_replica_dir()) else {
+        return vec![];
+    };
+    dirs.filter_map(|d| d.ok())
+        .filter(|d| d.path().is_dir())
+        .filter_map(|d| d.file_name().to_str().map(|s| s.to_string()))
+        .collect()
+}
+
+/// Moves the replicas of a dead source ingester into the local wal dir and
+/// replays them, returns the number of recovered wal files.
+pub async fn recover_replicas(source_node: &str) -> Result<usize> {
+    validate_file_path(source_node, "0/org/stype/0.wal")?;
+    // close the open writers of the source node
+    let prefix = format!("{source_node}/");
+    REPLICA_WRITERS
+        .write()
+        .await
+        .retain(|k, _| !k.starts_with(&prefix));
+
+    let source_dir = get_replica_dir().join(source_node);
+    let wal_dir = PathBuf::from(&get_config().common.data_wal_dir).join(WAL_DIR_DEFAULT_PREFIX);
+    let files = wal_scan_files(&source_dir, "wal").await.unwrap_or_default();
+    let mut recovered = Vec::with_capacity(files.len());
+    for file in files {
+        let Ok(relative) = file.strip_prefix(&source_dir) else {
+            continue;
+        };
+        let mut target = wal_dir.join(relative);
+        if target.exists() {
+            target.set_file_name(format!("{}.wal", now_micros()));
+        }
+        if let Some(parent) = target.parent() {
+            create_dir_all(parent).context(OpenDirSnafu {
+                path: parent.to_path_buf(),
+            })?;
+        }
+        std::fs::rename(&file, &target).context(RenameFileSnafu { path: file.clone() })?;
+        log::warn!(
+            "[INGESTER:REPLICATION] recover wal replica {} of node {} to {}",
+            relative.display(),
+            source_node,
+            target.display()
+        );
+        recovered.push(target);
+    }
+    let num = recovered.len();
+    crate::wal::replay_wal_files(wal_dir, recovered).await?;
+    _ = std::fs::remove_dir_all(&source_dir);
+    Ok(num)
+}
+
+/// Deletes the replicas of an alive source ingester which haven't been updated
+/// for the retention time.
+pub async fn clean_replicas(source_node: &str, retention: Duration) -> Result<usize> {
+    validate_file_path(source_node, "0/org/stype/0.wal")?;
+    let expired_at = now_micros() - retention.as_micros() as i64;
+    let prefix = format!("{source_node}/");
+    let mut w = REPLICA_WRITERS.write().await;
+    let mut expired_keys = Vec::new();
+    for (key, writer) in w.iter() {
+        if key.starts_with(&prefix) && writer.lock().await.updated_at < expired_at {
+            expired_keys.push(key.clone());
+        }
+    }
+    for key in expired_keys {
+        w.remove(&key);
+    }
+    let open_files = w
+        .keys()
+        .filter(|k| k.starts_with(&prefix))
+        .map(|k| get_replica_dir().join(k))
+        .collect::<Vec<_>>();
+    drop(w);
+
+    let source_dir = get_replica_dir().join(source_node);
+    let files = wal_scan_files(&source_dir, "wal").await.unwrap_or_default();
+    let mut deleted = 0;
+    for file in files {
+        if open_files.contains(&file) {
+            continue;
+        }
+        let modified = std::fs::metadata(&file)
+            .and_then(|m| m.modified())
+            .unwrap_or(SystemTime::now());
+        if modified.elapsed().unwrap_or_default() < retention {
+            continue;
+        }
+        remove_file(&file).context(DeleteFileSnafu { path: file.clone() })?;
+        deleted += 1;
+    }
+    Ok(deleted)
+}
+
+fn get_replica_dir() -> PathBuf {
+    PathBuf::from(&get_config().common.data_wal_dir).join(WAL_REPLICA_DIR)
+}
+
+// the relative path of a local wal file: {idx}/{org}/{stype}/{id}.wal
+fn get_relative_path(wal_path: &Path) -> Result<String> {
+    let wal_dir = PathBuf::from(&get_config().common.data_wal_dir).join(WAL_DIR_DEFAULT_PREFIX);
+    let path = wal_path
+        .strip_prefix(&wal_dir)
+        .map_err(|_| Error::WalReplicationError {
+            message: format!("wal file {} is not in the wal dir", wal_path.display()),
+        })?;
+    Ok(path.to_string_lossy().replace('\\', "/"))
+}
+
+fn split_file_path(file_path: &str) -> (&str, &str) {
+    let columns = file_path.split('/').collect::<Vec<_>>();
+    if columns.len() < 4 {
+        return ("", "");
+    }
+    (columns[1], columns[2])
+}
+
+fn validate_file_path(source_node: &str, file_path: &str) -> Result<()> {
+    let valid_name =
+        |v: &str| !v.is_empty() && v != "." && v != ".." && !v.contains('/') && !v.contains('\\');
+    let columns = file_path.split('/').collect::<Vec<_>>();
+    if !valid_name(source_node)
+        || columns.len() != 4
+        || !columns.iter().all(|c| valid_name(c))
+        || !file_path.ends_with(".wal")
+    {
+        return Err(Error::WalReplicationError {
+            message: format!("invalid wal replica path: {source_node}/{file_path}"),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_file_path() {
+        assert!(validate_file_path("node1", "0/default/logs/7099303408192061440.wal").is_ok());
+        assert!(validate_file_path("node1", "0/default/logs/../../x.wal").is_err());
+        assert!(validate_file_path("..", "0/default/logs/1.wal").is_err());
+        assert!(validate_file_path("node1", "default/logs/1.wal").is_err());
+        assert!(validate_file_path("node1", "0/default/logs/1.parquet").is_err());
+    }
+
+    #[test]
+    fn test_split_file_path() {
+        assert_eq!(
+            split_file_path("0/default/logs/7099303408192061440.wal"),
+            ("default", "logs")
+        );
+        assert_eq!(split_file_path("invalid"), ("", ""));
+    }
+
+    #[tokio::test]
+    async fn test_write_and_clean_replica() {
+        let source = format!("test-node-{}", now_micros());
+        let file_path = "0/default/logs/1.wal";
+        write_replica(&source, file_path, vec![b"entry".to_vec()], false)
+            .await
+            .unwrap();
+        write_replica(&source, file_path, vec![], true)
+            .await
+            .unwrap();
+        assert!(list_replica_sources().await.contains(&source));
+
+        let path = get_replica_dir().join(&source).join(file_path);
+        let mut reader = wal::Reader::from_path(&path).unwrap();
+        assert_eq!(reader.read_entry().unwrap(), Some(b"entry".to_vec()));
+
+        // not expired yet
+        assert_eq!(
+            clean_replicas(&source, Duration::from_secs(3600))
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(clean_replicas(&source, Duration::ZERO).await.unwrap(), 1);
+        _ = std::fs::remove_dir_all(get_replica_dir().join(&source));
+    }
+
+    #[tokio::test]
+    async fn test_release_replica() {
+        let source = format!("test-node-{}", now_micros());
+        let file_path = "0/default/logs/2.wal";
+        write_replica(&source, file_path, vec![b"entry".to_vec()], false)
+            .await
+            .unwrap();
+        let path = get_replica_dir().join(&source).join(file_path);
+        assert!(path.exists());
+
+        release_replica(&source, file_path).await.unwrap();
+        assert!(!path.exists());
+        assert!(
+            !REPLICA_WRITERS
+                .read()
+                .await
+                .contains_key(&format!("{source}/{file_path}"))
+        );
+        // releasing twice is fine
+        release_replica(&source, file_path).await.unwrap();
+
+        // the backup of a reopened replica is released with it
+        write_replica(&source, file_path, vec![b"entry".to_vec()], false)
+            .await
+            .unwrap();
+        REPLICA_WRITERS
+            .write()
+            .await
+            .remove(&format!("{source}/{file_path}"));
+        write_replica(&source, file_path, vec![b"entry".to_vec()], false)
+            .await
+            .unwrap();
+        let dir = path.parent().unwrap();
+        assert_eq!(std::fs::read_dir(dir).unwrap().count(), 2);
+        release_replica(&source, file_path).await.unwrap();
+        assert_eq!(std::fs::read_dir(dir).unwrap().count(), 0);
+        _ = std::fs::remove_dir_all(get_replica_dir().join(&source));
+    }
+}
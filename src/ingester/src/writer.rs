@@ -39,6 +39,7 @@ use crate::{
     errors::*,
     immutable::{IMMUTABLES, Immutable},
     memtable::MemTable,
+    replication,
     rwmap::RwMap,
 };
 
@@ -281,6 +282,10 @@ impl Writer {
         writer_clone
     }
 
+    pub fn get_org_id(&self) -> &str {
+        &self.key.org_id
+    }
+
     pub fn get_key_str(&self) -> String {
         format!("{}/{}", self.key.org_id, self.key.stream_type)
     }
@@ -363,13 +368,17 @@ impl Writer {
         metrics::INGEST_WAL_LOCK_TIME
             .with_label_values(&[&self.key.org_id])
             .observe(wal_lock_time);
-        for entry in bytes_entries {
+        // replicate while holding the wal lock to keep the order of entries on the peers
+        let wal_path = wal.path().clone();
+        replication::replicate_sync(&wal_path, &bytes_entries).await?;
+        for entry in bytes_entries.iter() {
             if entry.is_empty() {
                 continue;
             }
-            wal.write(&entry).context(WalSnafu)?;
+            wal.write(entry).context(WalSnafu)?;
             tokio::task::coop::consume_budget().await;
         }
+        replication::replicate_async(&wal_path, bytes_entries)?;
         drop(wal);
 
         // write into memtable
@@ -442,6 +451,13 @@ impl Writer {
         .context(WalSnafu)?;
         wal.sync().context(WalSnafu)?; // sync wal before rotation
         let old_wal = std::mem::replace(&mut *wal, new_wal);
+        if let Err(e) = replication::seal(old_wal.path()).await {
+            log::error!(
+                "[INGESTER:MEM] seal wal replica {} error: {}",
+                old_wal.path().display(),
+                e
+            );
+        }
         drop(wal);
 
         // rotation memtable
@@ -59,6 +59,12 @@ pub async fn run() -> Result<(), anyhow::Error> {
     tokio::task::spawn(async move { run_check_running_jobs().await });
     tokio::task::spawn(async move { run_clean_done_jobs().await });
     tokio::task::spawn(async move { run_compactor_pending_jobs_metric().await });
+    if cfg.compact.verify_enabled {
+        tokio::task::spawn(async move { run_verify().await });
+    }
+    if cfg.encryption.at_rest_enabled {
+        tokio::task::spawn(async move { run_rekey().await });
+    }
 
     Ok(())
 }
@@ -108,6 +114,34 @@ async fn run_compactor_pending_jobs_metric() -> Result<(), anyhow::Error> {
     }
 }
 
+/// Verify file_list with the object storage
+async fn run_verify() -> Result<(), anyhow::Error> {
+    loop {
+        tokio::time::sleep(tokio::time::Duration::from_secs(
+            get_config().compact.verify_interval,
+        ))
+        .await;
+        log::debug!("[COMPACTOR::JOB] Running file_list verification");
+        if let Err(e) = compact::run_verify().await {
+            log::error!("[COMPACTOR::JOB] run file_list verification error: {e}");
+        }
+    }
+}
+
+/// Rewrite the files with the active data keys of the orgs
+async fn run_rekey() -> Result<(), anyhow::Error> {
+    loop {
+        tokio::time::sleep(tokio::time::Duration::from_secs(
+            get_config().compact.interval,
+        ))
+        .await;
+        log::debug!("[COMPACTOR::JOB] Running data key rotation");
+        if let Err(e) = compact::run_rekey().await {
+            log::error!("[COMPACTOR::JOB] run data key rotation error: {e}");
+        }
+    }
+}
+
 /// Generate merging jobs
 async fn run_generate_job() -> Result<(), anyhow::Error> {
     loop {
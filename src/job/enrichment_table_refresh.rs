@@ -0,0 +1,42 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use config::{
+    cluster::{LOCAL_NODE, is_offline},
+    meta::enrichment_table::MIN_REFRESH_INTERVAL,
+};
+use tokio::time;
+
+use crate::service::enrichment_table::source;
+
+pub async fn run() -> Result<(), anyhow::Error> {
+    if !LOCAL_NODE.is_ingester() {
+        return Ok(());
+    }
+
+    let mut interval = time::interval(time::Duration::from_secs(MIN_REFRESH_INTERVAL));
+    interval.tick().await; // trigger the first run
+    loop {
+        if is_offline() {
+            break;
+        }
+        interval.tick().await;
+        if let Err(e) = source::run_scheduled_refresh().await {
+            log::error!("[ENRICHMENT_TABLE] scheduled refresh error: {}", e);
+        }
+    }
+    log::info!("job::enrichment_table_refresh is stopped");
+    Ok(())
+}
@@ -114,6 +114,7 @@ pub(crate) async fn write_parquet_index_to_disk(
             &file_meta,
             true,
             None,
+            None,
         );
         writer.write(&batch).await?;
         writer.close().await?;
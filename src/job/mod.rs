@@ -34,17 +34,22 @@ mod alert_manager;
 #[cfg(feature = "enterprise")]
 mod cipher;
 mod compactor;
+mod enrichment_table_refresh;
 mod file_downloader;
 mod file_list_dump;
 pub(crate) mod files;
 mod flatten_compactor;
 pub mod metrics;
 mod mmdb_downloader;
+mod pipeline_stats;
 mod promql;
 mod promql_self_consume;
+mod quotas;
+mod sessions;
 mod stats;
 pub(crate) mod syslog_server;
 mod telemetry;
+mod wal_replication;
 
 pub use file_downloader::{download_from_node, queue_download};
 pub use file_list_dump::FILE_LIST_SCHEMA;
@@ -168,15 +173,21 @@ pub async fn init() -> Result<(), anyhow::Error> {
     tokio::task::spawn(async move { db::alerts::realtime_triggers::watch().await });
     tokio::task::spawn(async move { db::alerts::alert::watch().await });
     tokio::task::spawn(async move { db::organization::org_settings_watch().await });
+    #[cfg(not(feature = "enterprise"))]
+    tokio::task::spawn(async move { db::grants::watch().await });
+    #[cfg(not(feature = "enterprise"))]
+    tokio::task::spawn(async move { db::grants::watch_restrictions().await });
+    tokio::task::spawn(async move { db::api_tokens::watch().await });
+    tokio::task::spawn(async move { db::quotas::watch().await });
 
     // pipeline not used on compactors
     if LOCAL_NODE.is_ingester() || LOCAL_NODE.is_querier() || LOCAL_NODE.is_alert_manager() {
         tokio::task::spawn(async move { db::pipeline::watch().await });
     }
 
-    #[cfg(feature = "enterprise")]
     if LOCAL_NODE.is_ingester() || LOCAL_NODE.is_querier() {
         tokio::task::spawn(async move { db::session::watch().await });
+        tokio::task::spawn(async move { db::session::watch_info().await });
     }
     if LOCAL_NODE.is_ingester() || LOCAL_NODE.is_querier() || LOCAL_NODE.is_alert_manager() {
         tokio::task::spawn(async move { db::enrichment_table::watch().await });
@@ -213,11 +224,16 @@ pub async fn init() -> Result<(), anyhow::Error> {
     db::syslog::cache_syslog_settings()
         .await
         .expect("syslog settings cache failed");
+    #[cfg(not(feature = "enterprise"))]
+    db::grants::cache().await.expect("grants cache failed");
+    db::api_tokens::cache()
+        .await
+        .expect("api tokens cache failed");
+    db::quotas::cache().await.expect("quotas cache failed");
 
     infra_file_list::create_table_index().await?;
     infra_file_list::LOCAL_CACHE.create_table_index().await?;
 
-    #[cfg(feature = "enterprise")]
     if LOCAL_NODE.is_ingester() || LOCAL_NODE.is_querier() || LOCAL_NODE.is_alert_manager() {
         db::session::cache()
             .await
@@ -230,6 +246,10 @@ pub async fn init() -> Result<(), anyhow::Error> {
         if let Err(e) = std::fs::create_dir_all(&cfg.common.data_wal_dir) {
             log::error!("Failed to create wal dir: {}", e);
         }
+        // buffer again the spans which tail sampling didn't decide before the restart
+        if let Err(e) = crate::service::traces::tail_sampling::replay().await {
+            log::error!("Failed to replay tail sampling wal: {}", e);
+        }
     }
 
     tokio::task::spawn(async move { files::run().await });
@@ -240,6 +260,11 @@ pub async fn init() -> Result<(), anyhow::Error> {
     tokio::task::spawn(async move { promql::run().await });
     tokio::task::spawn(async move { alert_manager::run().await });
     tokio::task::spawn(async move { file_downloader::run().await });
+    tokio::task::spawn(async move { wal_replication::run().await });
+    tokio::task::spawn(async move { enrichment_table_refresh::run().await });
+    tokio::task::spawn(async move { pipeline_stats::run().await });
+    tokio::task::spawn(async move { quotas::run().await });
+    tokio::task::spawn(async move { sessions::run().await });
 
     if LOCAL_NODE.is_compactor() {
         tokio::task::spawn(async move { file_list_dump::run().await });
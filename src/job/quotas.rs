@@ -0,0 +1,43 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use config::cluster::{LOCAL_NODE, is_offline};
+use tokio::time;
+
+use crate::service::quotas;
+
+/// Seconds between two syncs of the org quota usage
+const SYNC_INTERVAL: u64 = 30;
+
+pub async fn run() -> Result<(), anyhow::Error> {
+    // ingesters and queriers enforce the quotas, compactors notify
+    if !LOCAL_NODE.is_ingester() && !LOCAL_NODE.is_querier() && !LOCAL_NODE.is_compactor() {
+        return Ok(());
+    }
+
+    let mut interval = time::interval(time::Duration::from_secs(SYNC_INTERVAL));
+    interval.tick().await; // trigger the first run
+    loop {
+        if is_offline() {
+            break;
+        }
+        interval.tick().await;
+        if let Err(e) = quotas::sync().await {
+            log::error!("[QUOTA] sync usage error: {}", e);
+        }
+    }
+    log::info!("job::quotas is stopped");
+    Ok(())
+}
@@ -0,0 +1,46 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use config::cluster::{LOCAL_NODE, is_offline};
+use tokio::time;
+
+use crate::service::ingestion::wal_replication;
+
+pub async fn run() -> Result<(), anyhow::Error> {
+    let cfg = config::get_config();
+    if !LOCAL_NODE.is_ingester() || !cfg.common.wal_replication_enabled {
+        return Ok(());
+    }
+
+    ingester::set_wal_replicator(Arc::new(wal_replication::GrpcWalReplicator));
+
+    let mut interval = time::interval(time::Duration::from_secs(
+        cfg.limit.wal_replication_check_interval,
+    ));
+    interval.tick().await; // trigger the first run
+    loop {
+        if is_offline() {
+            break;
+        }
+        interval.tick().await;
+        if let Err(e) = wal_replication::check_replicas().await {
+            log::error!("[WAL_REPLICATION] check replicas error: {}", e);
+        }
+    }
+    log::info!("job::wal_replication is stopped");
+    Ok(())
+}
@@ -61,8 +61,8 @@ use openobserve::{
     },
     job, migration, router,
     service::{
-        cluster_info::ClusterInfoService, db, metadata, node::NodeService, search::SEARCH_SERVER,
-        self_reporting, tls::http_tls_config,
+        cluster_info::ClusterInfoService, db, metadata, node::NodeService, pipeline,
+        search::SEARCH_SERVER, self_reporting, tls::http_tls_config, traces,
     },
 };
 use opentelemetry::{KeyValue, global, trace::TracerProvider};
@@ -306,6 +306,10 @@ async fn main() -> Result<(), anyhow::Error> {
             // shutdown meter provider
             let _ = meter_provider.shutdown();
 
+            // decide the pending traces of tail sampling
+            _ = traces::tail_sampling::close().await;
+            // flush pipeline node stats
+            _ = pipeline::stats::flush().await;
             // flush distinct values
             _ = metadata::close().await;
             // flush WAL cache to disk
@@ -2017,6 +2017,32 @@ pub struct IngestionResponse {
     #[prost(string, tag = "2")]
     pub message: ::prost::alloc::string::String,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WalReplicationRequest {
+    /// name of the ingester which owns the wal file
+    #[prost(string, tag = "1")]
+    pub source_node: ::prost::alloc::string::String,
+    /// wal file path relative to the wal dir
+    #[prost(string, tag = "2")]
+    pub file_path: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", repeated, tag = "3")]
+    pub entries: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// the wal file is rotated, no more entries
+    #[prost(bool, tag = "4")]
+    pub sealed: bool,
+    /// the wal file is persisted, drop the replica
+    #[prost(bool, tag = "5")]
+    pub persisted: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WalReplicationResponse {
+    #[prost(int32, tag = "1")]
+    pub status_code: i32,
+    #[prost(string, tag = "2")]
+    pub message: ::prost::alloc::string::String,
+}
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum IngestionType {
@@ -2162,6 +2188,31 @@ pub mod ingest_client {
             req.extensions_mut().insert(GrpcMethod::new("cluster.Ingest", "Ingest"));
             self.inner.unary(req, path, codec).await
         }
+        pub async fn replicate_wal(
+            &mut self,
+            request: impl tonic::IntoRequest<super::WalReplicationRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::WalReplicationResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/cluster.Ingest/ReplicateWal",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("cluster.Ingest", "ReplicateWal"));
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -2178,6 +2229,13 @@ pub mod ingest_server {
             tonic::Response<super::IngestionResponse>,
             tonic::Status,
         >;
+        async fn replicate_wal(
+            &self,
+            request: tonic::Request<super::WalReplicationRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::WalReplicationResponse>,
+            tonic::Status,
+        >;
     }
     #[derive(Debug)]
     pub struct IngestServer<T: Ingest> {
@@ -2302,6 +2360,50 @@ pub mod ingest_server {
                     };
                     Box::pin(fut)
                 }
+                "/cluster.Ingest/ReplicateWal" => {
+                    #[allow(non_camel_case_types)]
+                    struct ReplicateWalSvc<T: Ingest>(pub Arc<T>);
+                    impl<T: Ingest> tonic::server::UnaryService<super::WalReplicationRequest>
+                    for ReplicateWalSvc<T> {
+                        type Response = super::WalReplicationResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::WalReplicationRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Ingest>::replicate_wal(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ReplicateWalSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
@@ -433,6 +433,23 @@ async fn prepare_alert(
         }
     }
 
+    // the alert runs its query with the access of the user who saved it
+    #[cfg(not(feature = "enterprise"))]
+    if let Some(user_email) = alert.last_edited_by.as_deref() {
+        crate::service::grants::check_query_streams(
+            org_id,
+            user_email,
+            stream_type,
+            Some(stream_name),
+            &alert.query_condition,
+        )
+        .await
+        .map_err(|e| match e {
+            crate::service::grants::GrantError::ForbiddenStream(_) => AlertError::PermissionDenied,
+            e => AlertError::ResolveStreamNameError(e.into()),
+        })?;
+    }
+
     // Commented intentionally - in case the alert period is big and there
     // is huge amount of data within the time period, the below can timeout and return error.
     // // test the alert
@@ -892,6 +909,29 @@ impl AlertExt for Alert {
         start_time: Option<i64>,
         evaluation_timestamp: i64,
     ) -> Result<(String, String), AlertError> {
+        // alerts run without a user so all the masking rules of the stream apply
+        let masked_rows;
+        let rows: &[Map<String, Value>] = match crate::service::search::masking::get_masker(
+            &self.org_id,
+            self.stream_type,
+            &[self.stream_name.clone()],
+            None,
+        )
+        .await
+        {
+            Some(masker) if !rows.is_empty() => {
+                masked_rows = rows
+                    .iter()
+                    .map(|row| {
+                        let mut row = row.clone();
+                        masker.mask_record(&mut row);
+                        row
+                    })
+                    .collect::<Vec<_>>();
+                &masked_rows
+            }
+            _ => rows,
+        };
         let mut err_message = "".to_string();
         let mut success_message = "".to_string();
         let mut no_of_error = 0;
@@ -1009,6 +1049,20 @@ async fn send_notification(
     }
 }
 
+/// Sends a message which is not rendered from a template, e.g. the quota
+/// notifications, `title` is the email subject and the sns alert name.
+pub(crate) async fn send_to_destination(
+    dest_type: &DestinationType,
+    title: &str,
+    msg: String,
+) -> Result<String, anyhow::Error> {
+    match dest_type {
+        DestinationType::Http(endpoint) => send_http_notification(endpoint, msg).await,
+        DestinationType::Email(email) => send_email_notification(title, email, msg).await,
+        DestinationType::Sns(aws_sns) => send_sns_notification(title, aws_sns, msg).await,
+    }
+}
+
 async fn send_http_notification(endpoint: &Endpoint, msg: String) -> Result<String, anyhow::Error> {
     #[cfg(feature = "enterprise")]
     let msg = if endpoint.action_id.is_some() {
@@ -0,0 +1,348 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use actix_web::{HttpRequest, http::Method};
+use config::{
+    ider,
+    meta::{
+        api_token::{ApiToken, ApiTokenScope, CreateApiTokenRequest},
+        user::UserRole,
+    },
+    utils::{rand::generate_random_string, time::now_micros},
+};
+
+use crate::{
+    common::{
+        infra::config::API_TOKENS,
+        meta::ingestion::INGESTION_EP,
+        utils::auth::{V2_API_PREFIX, is_root_user},
+    },
+    service::{db, users},
+};
+
+/// Length of the random part of a token.
+const SECRET_LEN: usize = 40;
+/// The last used time is persisted at most once per minute per token.
+const LAST_USED_INTERVAL: i64 = 60 * 1_000_000;
+
+/// Search and read endpoints which a query token can POST to.
+const QUERY_EP: [&str; 12] = [
+    "_search",
+    "_search_multi",
+    "_search_partition",
+    "_search_stream",
+    "_values_stream",
+    "_around",
+    "_values",
+    "query",
+    "query_range",
+    "query_exemplars",
+    "series",
+    "format_query",
+];
+
+/// Org level resources which hold credentials or manage access, only a full
+/// token can use them, even to read.
+const CREDENTIAL_EP: [&str; 6] = [
+    "passcode",
+    "rumtoken",
+    "service_accounts",
+    "api_tokens",
+    "users",
+    "grants",
+];
+
+/// Org level endpoints whose streams are only known from the body, the
+/// handlers check them against the streams of the token.
+const BODY_STREAM_EP: [&str; 4] = ["_bulk", "_search", "_search_stream", "_values_stream"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum ApiTokenError {
+    #[error("Invalid token: {0}")]
+    InvalidToken(String),
+    #[error("Token with ID {0} not found")]
+    NotFound(String),
+    #[error("Not allowed to manage the tokens of {0}")]
+    Forbidden(String),
+    #[error("{0}")]
+    InfraError(#[from] anyhow::Error),
+}
+
+/// Root and the org admins manage the tokens of everyone in the org, e.g. the
+/// tokens of the service accounts.
+pub async fn can_manage(org_id: &str, user_email: &str) -> bool {
+    is_root_user(user_email)
+        || users::get_user(Some(org_id), user_email)
+            .await
+            .is_some_and(|user| matches!(user.role, UserRole::Admin | UserRole::Root))
+}
+
+/// Lists the tokens of the user, org admins see all the tokens of the org.
+pub async fn list(org_id: &str, user_email: &str) -> Result<Vec<ApiToken>, ApiTokenError> {
+    let is_admin = can_manage(org_id, user_email).await;
+    Ok(db::api_tokens::list(org_id)
+        .await?
+        .into_iter()
+        .filter(|token| is_admin || token.user_email == user_email)
+        .map(|mut token| {
+            token.token_hash.clear();
+            token
+        })
+        .collect())
+}
+
+/// Creates a token and returns it along with its secret, which is not kept.
+pub async fn create(
+    org_id: &str,
+    user_email: &str,
+    req: CreateApiTokenRequest,
+) -> Result<(ApiToken, String), ApiTokenError> {
+    let owner = req
+        .user_email
+        .map(|email| email.trim().to_lowercase())
+        .unwrap_or_else(|| user_email.to_string());
+    if owner != user_email && !can_manage(org_id, user_email).await {
+        return Err(ApiTokenError::Forbidden(owner));
+    }
+    if !is_root_user(&owner) && users::get_user(Some(org_id), &owner).await.is_none() {
+        return Err(ApiTokenError::InvalidToken(format!(
+            "user {owner} is not a member of the organization"
+        )));
+    }
+
+    let now = now_micros();
+    let secret = generate_random_string(SECRET_LEN);
+    let mut token = ApiToken {
+        id: ider::generate(),
+        name: req.name.trim().to_string(),
+        user_email: owner,
+        scope: req.scope,
+        streams: req.streams.iter().map(|s| s.trim().to_string()).collect(),
+        token_hash: sha256::digest(secret.as_str()),
+        created_at: now,
+        expires_at: req.expires_at,
+        last_used_at: None,
+        revoked: false,
+    };
+    token.validate(now).map_err(ApiTokenError::InvalidToken)?;
+    db::api_tokens::set(org_id, &token).await?;
+
+    let secret = ApiToken::format(&token.id, &secret);
+    token.token_hash.clear();
+    Ok((token, secret))
+}
+
+/// Revokes a token, the token is kept to show when it was last used.
+pub async fn revoke(org_id: &str, user_email: &str, id: &str) -> Result<(), ApiTokenError> {
+    let Ok(mut token) = db::api_tokens::get(org_id, id).await else {
+        return Err(ApiTokenError::NotFound(id.to_string()));
+    };
+    if token.user_email != user_email && !can_manage(org_id, user_email).await {
+        return Err(ApiTokenError::Forbidden(token.user_email));
+    }
+    token.revoked = true;
+    db::api_tokens::set(org_id, &token).await?;
+    Ok(())
+}
+
+/// Returns the active token of the org for a token string.
+pub fn find(org_id: &str, token: &str) -> Option<ApiToken> {
+    let (id, secret) = ApiToken::parse(token)?;
+    let token = API_TOKENS.get(&format!("{org_id}/{id}"))?.value().clone();
+    (token.token_hash == sha256::digest(secret) && token.is_active(now_micros())).then_some(token)
+}
+
+/// Returns the token of a request by the path after `/api/`.
+pub fn from_request(path: &str, token: &str) -> Option<ApiToken> {
+    let path_columns = split_path(path);
+    find(path_columns.first()?, token)
+}
+
+/// Validates a token of `user_email` for a request, `path` is the path after
+/// `/api/`. The token must be active and its scope must cover the request.
+pub async fn validate(user_email: &str, token: &str, method: &Method, path: &str) -> bool {
+    let path_columns = split_path(path);
+    let Some(org_id) = path_columns.first() else {
+        return false;
+    };
+    let Some(token) = find(org_id, token) else {
+        return false;
+    };
+    if token.user_email != user_email || !check_request(&token, method, &path_columns) {
+        return false;
+    }
+    touch(org_id, token).await;
+    true
+}
+
+/// Checks a stream whose name comes from the body against the token the
+/// request was authenticated with, if any.
+pub fn check_stream(req: &HttpRequest, stream_name: &str) -> bool {
+    req.extensions()
+        .get::<ApiToken>()
+        .is_none_or(|token| token.allows_stream(stream_name))
+}
+
+fn split_path(path: &str) -> Vec<&str> {
+    let mut path_columns = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>();
+    if path_columns.first() == Some(&V2_API_PREFIX) {
+        path_columns.remove(0);
+    }
+    path_columns
+}
+
+fn check_request(token: &ApiToken, method: &Method, path_columns: &[&str]) -> bool {
+    let is_ingest = path_columns.get(1) != Some(&"streams")
+        && path_columns
+            .last()
+            .is_some_and(|ep| INGESTION_EP.contains(ep));
+    let allowed = match token.scope {
+        ApiTokenScope::Full => true,
+        _ if path_columns
+            .get(1)
+            .is_some_and(|resource| CREDENTIAL_EP.contains(resource)) =>
+        {
+            false
+        }
+        ApiTokenScope::Ingest => is_ingest,
+        ApiTokenScope::Query => {
+            !is_ingest
+                && (*method == Method::GET
+                    || path_columns.last().is_some_and(|ep| QUERY_EP.contains(ep)))
+        }
+    };
+    if !allowed || token.streams.is_empty() {
+        return allowed;
+    }
+    match (path_columns.get(1), path_columns.get(2)) {
+        (Some(&"streams"), Some(stream_name)) => token.allows_stream(stream_name),
+        (Some(endpoint), None) => BODY_STREAM_EP.contains(endpoint),
+        (Some(stream_name), Some(endpoint))
+            if INGESTION_EP.contains(endpoint)
+                || endpoint.starts_with("_values")
+                || endpoint.starts_with("_around") =>
+        {
+            token.allows_stream(stream_name)
+        }
+        // the streams of the other endpoints are unknown
+        _ => false,
+    }
+}
+
+async fn touch(org_id: &str, mut token: ApiToken) {
+    let now = now_micros();
+    if token
+        .last_used_at
+        .is_some_and(|t| now - t < LAST_USED_INTERVAL)
+    {
+        return;
+    }
+    token.last_used_at = Some(now);
+    if let Some(mut cached) = API_TOKENS.get_mut(&format!("{org_id}/{}", token.id)) {
+        cached.last_used_at = token.last_used_at;
+    }
+    if let Err(e) = db::api_tokens::set(org_id, &token).await {
+        log::error!(
+            "[API_TOKEN] failed to save the last used time of {}: {e}",
+            token.id
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(scope: ApiTokenScope, streams: &[&str]) -> ApiToken {
+        ApiToken {
+            id: "1".to_string(),
+            name: "agent".to_string(),
+            user_email: "agent@example.com".to_string(),
+            scope,
+            streams: streams.iter().map(|s| s.to_string()).collect(),
+            token_hash: String::new(),
+            created_at: 0,
+            expires_at: None,
+            last_used_at: None,
+            revoked: false,
+        }
+    }
+
+    fn check(token: &ApiToken, method: Method, path: &str) -> bool {
+        check_request(token, &method, &split_path(path))
+    }
+
+    #[test]
+    fn test_check_request_scope() {
+        let ingest = token(ApiTokenScope::Ingest, &[]);
+        assert!(check(&ingest, Method::POST, "default/app/_json"));
+        assert!(check(&ingest, Method::POST, "default/_bulk"));
+        assert!(!check(&ingest, Method::POST, "default/_search"));
+        assert!(!check(&ingest, Method::GET, "default/streams"));
+
+        let query = token(ApiTokenScope::Query, &[]);
+        assert!(check(&query, Method::POST, "default/_search"));
+        assert!(check(&query, Method::GET, "default/streams"));
+        assert!(check(&query, Method::GET, "default/app/_values"));
+        assert!(!check(&query, Method::POST, "default/app/_json"));
+        assert!(!check(&query, Method::DELETE, "default/streams/app"));
+
+        // credentials are only readable with a full token
+        for path in [
+            "default/passcode",
+            "default/rumtoken",
+            "default/service_accounts/sa@example.com",
+            "default/api_tokens",
+            "default/users",
+            "v2/default/users/a@example.com/sessions",
+            "default/grants",
+        ] {
+            assert!(!check(&query, Method::GET, path), "{path}");
+            assert!(!check(&ingest, Method::GET, path), "{path}");
+        }
+
+        let full = token(ApiTokenScope::Full, &[]);
+        assert!(check(&full, Method::DELETE, "default/streams/app"));
+        assert!(check(&full, Method::GET, "default/passcode"));
+    }
+
+    #[test]
+    fn test_check_request_streams() {
+        let ingest = token(ApiTokenScope::Ingest, &["k8s_*"]);
+        assert!(check(&ingest, Method::POST, "default/k8s_logs/_json"));
+        assert!(!check(&ingest, Method::POST, "default/payments/_json"));
+        // the streams of bulk requests are checked while ingesting
+        assert!(check(&ingest, Method::POST, "default/_bulk"));
+        // otlp requests name the stream in a header
+        assert!(!check(&ingest, Method::POST, "default/v1/logs"));
+
+        let query = token(ApiTokenScope::Query, &["k8s_*"]);
+        assert!(check(
+            &query,
+            Method::GET,
+            "default/streams/k8s_logs/schema"
+        ));
+        assert!(!check(
+            &query,
+            Method::GET,
+            "default/streams/payments/schema"
+        ));
+        assert!(check(&query, Method::GET, "v2/default/k8s_logs/_values"));
+        assert!(!check(&query, Method::GET, "default/dashboards"));
+    }
+}
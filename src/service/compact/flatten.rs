@@ -181,16 +181,22 @@ pub async fn generate_file(file: &FileKey) -> Result<(), anyhow::Error> {
         .await
         .unwrap_or_default();
     let bloom_filter_fields = stream_setting.bloom_filter_fields;
+    let parquet_settings = stream_setting.parquet_settings;
     let new_file = format!(
         "files{}/{}",
         get_config().common.column_all,
         file.key.strip_prefix("files/").unwrap()
     );
     let new_schema = new_batches.first().unwrap().schema();
-    let new_data =
-        write_recordbatch_to_parquet(new_schema, &new_batches, &bloom_filter_fields, &file.meta)
-            .await
-            .map_err(|e| anyhow::anyhow!("write_recordbatch_to_parquet error: {}", e))?;
+    let new_data = write_recordbatch_to_parquet(
+        new_schema,
+        &new_batches,
+        &bloom_filter_fields,
+        &file.meta,
+        parquet_settings.as_ref(),
+    )
+    .await
+    .map_err(|e| anyhow::anyhow!("write_recordbatch_to_parquet error: {}", e))?;
     // upload filee
     storage::put(&file.account, &new_file, new_data.into()).await?;
     // delete from queue
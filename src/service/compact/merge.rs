@@ -794,6 +794,9 @@ pub async fn merge_files(
     let latest_schema = infra::schema::get(org_id, stream_name, stream_type).await?;
     let stream_settings = infra::schema::unwrap_stream_settings(&latest_schema);
     let bloom_filter_fields = get_stream_setting_bloom_filter_fields(&stream_settings);
+    let parquet_settings = stream_settings
+        .as_ref()
+        .and_then(|s| s.parquet_settings.clone());
     let full_text_search_fields = get_stream_setting_fts_fields(&stream_settings);
     let index_fields = get_stream_setting_index_fields(&stream_settings);
     let (defined_schema_fields, need_original, index_original_data, index_all_values) =
@@ -908,6 +911,7 @@ pub async fn merge_files(
                     latest_schema,
                     tables,
                     &bloom_filter_fields,
+                    parquet_settings.as_ref(),
                     &new_file_meta,
                     false,
                 )
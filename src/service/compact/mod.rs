@@ -37,10 +37,89 @@ use crate::{common::infra::cluster::get_node_from_consistent_hash, service::db};
 pub mod deleted;
 pub mod flatten;
 pub mod merge;
+pub mod rekey;
 pub mod retention;
 pub mod stats;
+pub mod verify;
 pub mod worker;
 
+/// compactor file_list verification run steps:
+/// 1. check the recent hours of the streams which belong to this node
+/// 2. report the discrepancies and repair them if auto repair is enabled
+pub async fn run_verify() -> Result<(), anyhow::Error> {
+    let cfg = get_config();
+    let time_range = verify::default_time_range();
+    let deleted_files = verify::list_deleted_files().await?;
+    let orgs = db::schema::list_organizations_from_cache().await;
+    for org_id in orgs {
+        for stream_type in [StreamType::Logs, StreamType::Metrics, StreamType::Traces] {
+            let streams = db::schema::list_streams_from_cache(&org_id, stream_type).await;
+            for stream_name in streams {
+                let Some(node_name) =
+                    get_node_from_consistent_hash(&stream_name, &Role::Compactor, None).await
+                else {
+                    continue; // no compactor node
+                };
+                if LOCAL_NODE.name.ne(&node_name) {
+                    continue; // not this node
+                }
+                if let Err(e) = verify::verify_stream(
+                    &org_id,
+                    stream_type,
+                    &stream_name,
+                    time_range,
+                    cfg.compact.verify_sample_partitions,
+                    cfg.compact.verify_auto_repair,
+                    &deleted_files,
+                )
+                .await
+                {
+                    log::error!(
+                        "[COMPACTOR] verify: verify_stream [{}/{}/{}] error: {}",
+                        org_id,
+                        stream_type,
+                        stream_name,
+                        e
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rewrites the files which are not encrypted with the active data key of
+/// their org, for the streams owned by this node. All the stream types are
+/// encrypted, including the enrichment tables.
+pub async fn run_rekey() -> Result<(), anyhow::Error> {
+    let orgs = db::schema::list_organizations_from_cache().await;
+    for org_id in orgs {
+        for stream_type in ALL_STREAM_TYPES {
+            let streams = db::schema::list_streams_from_cache(&org_id, stream_type).await;
+            for stream_name in streams {
+                let Some(node_name) =
+                    get_node_from_consistent_hash(&stream_name, &Role::Compactor, None).await
+                else {
+                    continue; // no compactor node
+                };
+                if LOCAL_NODE.name.ne(&node_name) {
+                    continue; // not this node
+                }
+                if let Err(e) = rekey::rekey_stream(&org_id, stream_type, &stream_name).await {
+                    log::error!(
+                        "[COMPACTOR] rekey: rekey_stream [{}/{}/{}] error: {}",
+                        org_id,
+                        stream_type,
+                        stream_name,
+                        e
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 /// compactor retention run steps:
 pub async fn run_retention() -> Result<(), anyhow::Error> {
     let cfg = get_config();
@@ -0,0 +1,160 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Rewrites the files of a stream which are not encrypted with the active
+//! data key of the org, e.g. after a key rotation or after the encryption at
+//! rest was enabled on an existing deployment.
+
+use chrono::Duration;
+use config::{
+    meta::stream::StreamType,
+    utils::{inverted_index::convert_parquet_idx_file_name_to_tantivy_file, time::now_micros},
+};
+use infra::{
+    file_list as infra_file_list,
+    storage::{self, data_keys},
+};
+use object_store::{PutMode, PutOptions, UpdateVersion};
+
+use crate::service::db;
+
+/// Rewrites the files of the stream day by day, returns the number of the
+/// rewritten files.
+///
+/// The rewritten files keep their names and sizes, so the file_list is not
+/// changed. A file is only written back if it was not changed or deleted
+/// since it was read, a merged file is never brought back.
+pub async fn rekey_stream(
+    org_id: &str,
+    stream_type: StreamType,
+    stream_name: &str,
+) -> Result<usize, anyhow::Error> {
+    // the files written before the encryption was enabled are encrypted
+    // with the first key of the org
+    data_keys::active_key(org_id).await?;
+    let keys = data_keys::list(org_id).await?;
+    let Some(active) = keys.last() else {
+        return Ok(0);
+    };
+    // the other nodes pick up a new key after the ttl, the files written
+    // with the old key until then would be missed
+    let ttl = Duration::try_seconds(data_keys::ACTIVE_KEY_TTL * 2)
+        .unwrap()
+        .num_microseconds()
+        .unwrap();
+    let now = now_micros();
+    if now - active.created_at < ttl {
+        return Ok(0);
+    }
+    if db::compact::rekey::get_version(org_id, stream_type, stream_name).await >= active.version {
+        return Ok(0);
+    }
+
+    let stream_settings = infra::schema::get_settings(org_id, stream_name, stream_type)
+        .await
+        .unwrap_or_default();
+    let partition_time_level = infra::schema::unwrap_partition_time_level(
+        stream_settings.partition_time_level,
+        stream_type,
+    );
+    let stats = infra::cache::stats::get_stream_stats(org_id, stream_name, stream_type);
+    let day = Duration::try_days(1).unwrap().num_microseconds().unwrap();
+    let mut start = stats.doc_time_min.max(1);
+    let mut rewritten = 0;
+    while stats.doc_time_min > 0 && start < now {
+        let end = (start + day).min(now);
+        let files = infra_file_list::query(
+            org_id,
+            stream_type,
+            stream_name,
+            partition_time_level,
+            Some((start, end - 1)),
+            None,
+        )
+        .await?;
+        for file in files {
+            if rekey_file(&file.account, &file.key, &file.key, active.version).await? {
+                rewritten += 1;
+            }
+            if file.meta.index_size > 0
+                && let Some(ttv_file) = convert_parquet_idx_file_name_to_tantivy_file(&file.key)
+                && rekey_file(&file.account, &ttv_file, &file.key, active.version).await?
+            {
+                rewritten += 1;
+            }
+        }
+        start = end;
+    }
+
+    db::compact::rekey::set_version(org_id, stream_type, stream_name, active.version).await?;
+    if rewritten > 0 {
+        log::info!(
+            "[COMPACTOR] rekey: rewrote {rewritten} files of [{org_id}/{stream_type}/{stream_name}] with data key {}",
+            active.version
+        );
+    }
+    Ok(rewritten)
+}
+
+/// Rewrites a file if it's not encrypted with `version`, the store encrypts
+/// it with the active key.
+///
+/// The put is conditional on the version which was read, so a file deleted
+/// by the compactor meanwhile is not written again. The stores without
+/// conditional puts skip the files which are no longer in the file_list
+/// instead, `list_key` is the data file the file belongs to.
+async fn rekey_file(
+    account: &str,
+    file: &str,
+    list_key: &str,
+    version: u32,
+) -> Result<bool, anyhow::Error> {
+    let current = match storage::get_key_version(account, file).await {
+        Ok(v) => v,
+        Err(object_store::Error::NotFound { .. }) => return Ok(false),
+        Err(e) => return Err(e.into()),
+    };
+    if current.is_some_and(|v| v >= version) {
+        return Ok(false);
+    }
+    let result = match storage::get(account, file).await {
+        Ok(v) => v,
+        Err(object_store::Error::NotFound { .. }) => return Ok(false),
+        Err(e) => return Err(e.into()),
+    };
+    let mode = PutMode::Update(UpdateVersion {
+        e_tag: result.meta.e_tag.clone(),
+        version: result.meta.version.clone(),
+    });
+    let data = result.bytes().await?;
+    let opts = PutOptions {
+        mode,
+        ..Default::default()
+    };
+    match storage::put_opts(account, file, data.clone(), opts).await {
+        Ok(_) => Ok(true),
+        // changed or deleted since it was read
+        Err(object_store::Error::Precondition { .. })
+        | Err(object_store::Error::NotFound { .. }) => Ok(false),
+        Err(object_store::Error::NotImplemented) => {
+            if !infra_file_list::contains(list_key).await? {
+                return Ok(false); // merged or deleted meanwhile
+            }
+            storage::put(account, file, data).await?;
+            Ok(true)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
@@ -0,0 +1,469 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{TimeZone, Utc};
+use config::{
+    get_config,
+    meta::stream::{FileKey, FileMeta, PartitionTimeLevel, StreamType},
+    metrics,
+};
+use infra::{file_list as infra_file_list, storage};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VerifyMismatch {
+    pub file: String,
+    pub file_list: FileMeta,
+    pub storage: FileMeta,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VerifyReport {
+    pub org_id: String,
+    pub stream_type: StreamType,
+    pub stream_name: String,
+    pub start_time: i64,
+    pub end_time: i64,
+    /// the partitions (hours or days) which were checked
+    pub partitions: usize,
+    pub storage_files: usize,
+    pub file_list_files: usize,
+    /// files in the object storage without a file_list record
+    pub orphan_files: Vec<String>,
+    /// file_list records without an object in the storage
+    pub missing_files: Vec<String>,
+    /// files whose parquet footer doesn't match the file_list record
+    pub mismatched_files: Vec<VerifyMismatch>,
+    pub repaired_files: usize,
+    pub errors: Vec<String>,
+}
+
+impl VerifyReport {
+    pub fn has_discrepancies(&self) -> bool {
+        !self.orphan_files.is_empty()
+            || !self.missing_files.is_empty()
+            || !self.mismatched_files.is_empty()
+    }
+}
+
+/// Compares the file_list of the stream with the object storage in the given
+/// time range, and repairs the discrepancies if `repair` is true:
+///
+/// - orphan files are added to file_list with the meta read from the parquet footer
+/// - records of the missing files are removed from file_list
+/// - records which don't match the parquet footer are replaced
+///
+/// The cached stats of the stream follow the repaired records right away.
+///
+/// The time range is aligned to the partition time level of the stream, only
+/// `sample` random partitions are checked when it is greater than 0.
+/// `deleted_files` comes from [`list_deleted_files`], it is loaded once per run.
+pub async fn verify_stream(
+    org_id: &str,
+    stream_type: StreamType,
+    stream_name: &str,
+    time_range: (i64, i64),
+    sample: usize,
+    repair: bool,
+    deleted_files: &HashSet<String>,
+) -> Result<VerifyReport, anyhow::Error> {
+    let stream_settings = infra::schema::get_settings(org_id, stream_name, stream_type)
+        .await
+        .unwrap_or_default();
+    let partition_time_level = infra::schema::unwrap_partition_time_level(
+        stream_settings.partition_time_level,
+        stream_type,
+    );
+    let mut partitions = generate_partitions(time_range, partition_time_level);
+    if sample > 0 && partitions.len() > sample {
+        partitions.shuffle(&mut rand::thread_rng());
+        partitions.truncate(sample);
+        partitions.sort();
+    }
+
+    let mut report = VerifyReport {
+        org_id: org_id.to_string(),
+        stream_type,
+        stream_name: stream_name.to_string(),
+        start_time: time_range.0,
+        end_time: time_range.1,
+        partitions: partitions.len(),
+        ..Default::default()
+    };
+    if partitions.is_empty() {
+        return Ok(report);
+    }
+
+    let stream_prefix = format!("files/{org_id}/{stream_type}/{stream_name}/");
+
+    for (start, end) in partitions {
+        let prefix = format!(
+            "{stream_prefix}{}",
+            format_partition_key(start, partition_time_level)
+        );
+        let account = storage::get_account(&prefix).unwrap_or_default();
+        let storage_files = storage::list(&account, &prefix)
+            .await?
+            .into_iter()
+            .filter(|f| f.ends_with(".parquet"))
+            .collect::<Vec<_>>();
+        let file_list_files = infra_file_list::query(
+            org_id,
+            stream_type,
+            stream_name,
+            partition_time_level,
+            Some((start, end - 1)),
+            None,
+        )
+        .await?
+        .into_iter()
+        .filter(|f| f.key.starts_with(&prefix))
+        .collect::<Vec<_>>();
+        report.storage_files += storage_files.len();
+        report.file_list_files += file_list_files.len();
+
+        let diff = diff_files(&storage_files, file_list_files, deleted_files);
+        for file in diff.orphans {
+            verify_orphan_file(&mut report, &account, &file, repair).await;
+        }
+        for file in diff.missing {
+            verify_missing_file(&mut report, file, repair).await;
+        }
+        for file in diff.matched {
+            verify_file_meta(&mut report, file, repair).await;
+        }
+    }
+
+    if report.has_discrepancies() {
+        log::warn!(
+            "[COMPACTOR:VERIFY] [{}/{}/{}] orphan files: {}, missing files: {}, mismatched files: {}, repaired: {}",
+            org_id,
+            stream_type,
+            stream_name,
+            report.orphan_files.len(),
+            report.missing_files.len(),
+            report.mismatched_files.len(),
+            report.repaired_files
+        );
+    }
+    Ok(report)
+}
+
+/// Lists the files which are pending deletion. The merged files are kept in the
+/// storage until the deletion delay, they are not orphans.
+pub async fn list_deleted_files() -> Result<HashSet<String>, anyhow::Error> {
+    Ok(infra_file_list::list_deleted()
+        .await?
+        .into_iter()
+        .map(|f| f.file)
+        .collect())
+}
+
+async fn verify_orphan_file(report: &mut VerifyReport, account: &str, file: &str, repair: bool) {
+    // the file may be added to file_list after we listed it
+    match infra_file_list::contains(file).await {
+        Ok(true) => return,
+        Ok(false) => {}
+        Err(e) => {
+            report.errors.push(format!("check file {file} error: {e}"));
+            return;
+        }
+    }
+    report.orphan_files.push(file.to_string());
+    inc_discrepancy(report, "orphan");
+    if !repair {
+        return;
+    }
+    let file_meta = match storage::get_file_meta(account, file).await {
+        Ok(v) => v,
+        Err(e) => {
+            report.errors.push(format!("read file {file} error: {e}"));
+            return;
+        }
+    };
+    match infra_file_list::add(account, file, &file_meta).await {
+        Ok(_) => {
+            update_stats(file, None, Some(&file_meta));
+            inc_repaired(report, "orphan")
+        }
+        Err(e) => report.errors.push(format!("add file {file} error: {e}")),
+    }
+}
+
+async fn verify_missing_file(report: &mut VerifyReport, mut file: FileKey, repair: bool) {
+    // the file may be uploaded after we listed the storage
+    if storage::head(&file.account, &file.key).await.is_ok() {
+        return;
+    }
+    report.missing_files.push(file.key.clone());
+    inc_discrepancy(report, "missing");
+    if !repair {
+        return;
+    }
+    file.deleted = true;
+    match infra_file_list::batch_process(&[file.clone()]).await {
+        Ok(_) => {
+            update_stats(&file.key, Some(&file.meta), None);
+            inc_repaired(report, "missing")
+        }
+        Err(e) => report
+            .errors
+            .push(format!("remove file {} error: {e}", file.key)),
+    }
+}
+
+async fn verify_file_meta(report: &mut VerifyReport, file: FileKey, repair: bool) {
+    let file_meta = match storage::get_file_meta(&file.account, &file.key).await {
+        Ok(v) => v,
+        Err(e) => {
+            report
+                .errors
+                .push(format!("read file {} error: {e}", file.key));
+            return;
+        }
+    };
+    if !is_meta_mismatch(&file.meta, &file_meta) {
+        return;
+    }
+    report.mismatched_files.push(VerifyMismatch {
+        file: file.key.clone(),
+        file_list: file.meta.clone(),
+        storage: file_meta.clone(),
+    });
+    inc_discrepancy(report, "mismatch");
+    if !repair {
+        return;
+    }
+    let key = file.key.clone();
+    let new_file = FileKey {
+        id: 0,
+        meta: FileMeta {
+            index_size: file.meta.index_size,
+            flattened: file.meta.flattened,
+            ..file_meta
+        },
+        deleted: false,
+        ..file.clone()
+    };
+    let old_file = FileKey {
+        deleted: true,
+        ..file
+    };
+    let ret = match infra_file_list::batch_process(&[old_file.clone()]).await {
+        Ok(_) => infra_file_list::batch_add(&[new_file.clone()]).await,
+        Err(e) => Err(e),
+    };
+    match ret {
+        Ok(_) => {
+            update_stats(&key, Some(&old_file.meta), Some(&new_file.meta));
+            inc_repaired(report, "mismatch")
+        }
+        Err(e) => report.errors.push(format!("replace file {key} error: {e}")),
+    }
+}
+
+/// Applies a repaired file to the cached stats of its stream, the stats in the
+/// database are updated from the file_list records by the stats job.
+fn update_stats(key: &str, removed: Option<&FileMeta>, added: Option<&FileMeta>) {
+    if let Some(meta) = removed
+        && let Err(e) = infra::cache::stats::decr_stream_stats(key, meta)
+    {
+        log::error!("[COMPACTOR] verify: decr_stream_stats {key} error: {e}");
+    }
+    if let Some(meta) = added
+        && let Err(e) = infra::cache::stats::incr_stream_stats(key, meta)
+    {
+        log::error!("[COMPACTOR] verify: incr_stream_stats {key} error: {e}");
+    }
+}
+
+fn inc_discrepancy(report: &VerifyReport, kind: &str) {
+    metrics::COMPACT_VERIFY_DISCREPANCIES
+        .with_label_values(&[&report.org_id, report.stream_type.as_str(), kind])
+        .inc();
+}
+
+fn inc_repaired(report: &mut VerifyReport, kind: &str) {
+    report.repaired_files += 1;
+    metrics::COMPACT_VERIFY_REPAIRED
+        .with_label_values(&[&report.org_id, report.stream_type.as_str(), kind])
+        .inc();
+}
+
+#[derive(Debug, Default)]
+struct FilesDiff {
+    orphans: Vec<String>,
+    missing: Vec<FileKey>,
+    matched: Vec<FileKey>,
+}
+
+fn diff_files(
+    storage_files: &[String],
+    file_list_files: Vec<FileKey>,
+    deleted_files: &HashSet<String>,
+) -> FilesDiff {
+    let mut diff = FilesDiff::default();
+    let mut file_list_files = file_list_files
+        .into_iter()
+        .map(|f| (f.key.clone(), f))
+        .collect::<HashMap<_, _>>();
+    for file in storage_files {
+        match file_list_files.remove(file) {
+            Some(f) => diff.matched.push(f),
+            None if deleted_files.contains(file) => {}
+            None => diff.orphans.push(file.to_string()),
+        }
+    }
+    diff.missing = file_list_files.into_values().collect();
+    diff.orphans.sort();
+    diff.missing.sort_by(|a, b| a.key.cmp(&b.key));
+    diff.matched.sort_by(|a, b| a.key.cmp(&b.key));
+    diff
+}
+
+fn is_meta_mismatch(file_list: &FileMeta, storage: &FileMeta) -> bool {
+    file_list.records != storage.records
+        || file_list.min_ts != storage.min_ts
+        || file_list.max_ts != storage.max_ts
+        || file_list.compressed_size != storage.compressed_size
+}
+
+/// Splits the time range into the partitions of the stream, returns the
+/// `[start, end)` of every partition in microseconds.
+fn generate_partitions(
+    time_range: (i64, i64),
+    partition_time_level: PartitionTimeLevel,
+) -> Vec<(i64, i64)> {
+    let step = match partition_time_level {
+        PartitionTimeLevel::Daily => PartitionTimeLevel::Daily.duration(),
+        _ => PartitionTimeLevel::Hourly.duration(),
+    } * 1_000_000;
+    let (start, end) = time_range;
+    let mut partitions = Vec::new();
+    let mut t = start - start.rem_euclid(step);
+    while t < end {
+        partitions.push((t, t + step));
+        t += step;
+    }
+    partitions
+}
+
+fn format_partition_key(ts: i64, partition_time_level: PartitionTimeLevel) -> String {
+    let t = Utc.timestamp_nanos(ts * 1000);
+    match partition_time_level {
+        PartitionTimeLevel::Daily => t.format("%Y/%m/%d/00/").to_string(),
+        _ => t.format("%Y/%m/%d/%H/").to_string(),
+    }
+}
+
+/// The default time range of the verifier, the recent hours are skipped
+/// because the files of them may still be written or merged.
+pub fn default_time_range() -> (i64, i64) {
+    let cfg = get_config();
+    let hour = PartitionTimeLevel::Hourly.duration() * 1_000_000;
+    let now = config::utils::time::now_micros();
+    let end = now - now.rem_euclid(hour) - cfg.compact.delete_files_delay_hours.max(1) * hour;
+    let start = end - cfg.compact.verify_lookback_hours * hour;
+    (start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_key(key: &str) -> FileKey {
+        FileKey::new(
+            0,
+            "".to_string(),
+            key.to_string(),
+            FileMeta::default(),
+            false,
+        )
+    }
+
+    #[test]
+    fn test_diff_files() {
+        let storage_files = vec![
+            "files/default/logs/app/2025/01/01/00/1.parquet".to_string(),
+            "files/default/logs/app/2025/01/01/00/2.parquet".to_string(),
+            "files/default/logs/app/2025/01/01/00/3.parquet".to_string(),
+        ];
+        let file_list_files = vec![
+            file_key("files/default/logs/app/2025/01/01/00/1.parquet"),
+            file_key("files/default/logs/app/2025/01/01/00/4.parquet"),
+        ];
+        let deleted_files =
+            HashSet::from(["files/default/logs/app/2025/01/01/00/3.parquet".to_string()]);
+        let diff = diff_files(&storage_files, file_list_files, &deleted_files);
+        assert_eq!(
+            diff.orphans,
+            vec!["files/default/logs/app/2025/01/01/00/2.parquet"]
+        );
+        assert_eq!(diff.missing.len(), 1);
+        assert_eq!(
+            diff.missing[0].key,
+            "files/default/logs/app/2025/01/01/00/4.parquet"
+        );
+        assert_eq!(diff.matched.len(), 1);
+    }
+
+    #[test]
+    fn test_is_meta_mismatch() {
+        let meta = FileMeta {
+            min_ts: 1,
+            max_ts: 2,
+            records: 3,
+            original_size: 100,
+            compressed_size: 10,
+            ..Default::default()
+        };
+        assert!(!is_meta_mismatch(&meta, &meta.clone()));
+        let storage = FileMeta {
+            index_size: 5,
+            flattened: true,
+            ..meta.clone()
+        };
+        assert!(!is_meta_mismatch(&meta, &storage));
+        let storage = FileMeta {
+            records: 4,
+            ..meta.clone()
+        };
+        assert!(is_meta_mismatch(&meta, &storage));
+    }
+
+    #[test]
+    fn test_generate_partitions() {
+        // 2025-01-01T00:30:00Z ~ 2025-01-01T02:00:00Z
+        let start = 1735691400000000;
+        let end = 1735696800000000;
+        let partitions = generate_partitions((start, end), PartitionTimeLevel::Hourly);
+        assert_eq!(partitions.len(), 2);
+        assert_eq!(partitions[0].0, 1735689600000000);
+        assert_eq!(
+            format_partition_key(partitions[1].0, PartitionTimeLevel::Hourly),
+            "2025/01/01/01/"
+        );
+        let partitions = generate_partitions((start, end), PartitionTimeLevel::Daily);
+        assert_eq!(partitions.len(), 1);
+        assert_eq!(
+            format_partition_key(partitions[0].0, PartitionTimeLevel::Daily),
+            "2025/01/01/00/"
+        );
+    }
+}
@@ -0,0 +1,100 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use config::{meta::api_token::ApiToken, utils::json};
+
+use crate::{common::infra::config::API_TOKENS, service::db};
+
+const API_TOKENS_KEY: &str = "/api_tokens/";
+
+#[tracing::instrument(name = "service:db:api_tokens:list")]
+pub async fn list(org_id: &str) -> Result<Vec<ApiToken>, anyhow::Error> {
+    Ok(db::list(&format!("{API_TOKENS_KEY}{org_id}/"))
+        .await?
+        .values()
+        .filter_map(|val| json::from_slice(val).ok())
+        .collect())
+}
+
+#[tracing::instrument(name = "service:db:api_tokens:set", skip_all)]
+pub async fn set(org_id: &str, token: &ApiToken) -> Result<(), anyhow::Error> {
+    Ok(db::put(
+        &format!("{API_TOKENS_KEY}{org_id}/{}", token.id),
+        json::to_vec(token).unwrap().into(),
+        db::NEED_WATCH,
+        None,
+    )
+    .await?)
+}
+
+#[tracing::instrument(name = "service:db:api_tokens:get")]
+pub async fn get(org_id: &str, id: &str) -> Result<ApiToken, anyhow::Error> {
+    let val = db::get(&format!("{API_TOKENS_KEY}{org_id}/{id}")).await?;
+    Ok(json::from_slice(&val)?)
+}
+
+pub async fn watch() -> Result<(), anyhow::Error> {
+    let cluster_coordinator = db::get_coordinator().await;
+    let mut events = cluster_coordinator.watch(API_TOKENS_KEY).await?;
+    let events = Arc::get_mut(&mut events).unwrap();
+    log::info!("Start watching api tokens");
+    loop {
+        let ev = match events.recv().await {
+            Some(ev) => ev,
+            None => {
+                log::error!("watch_api_tokens: event channel closed");
+                break;
+            }
+        };
+        match ev {
+            db::Event::Put(ev) => {
+                let item_key = ev.key.strip_prefix(API_TOKENS_KEY).unwrap();
+                let item_value: ApiToken = match db::get(&ev.key).await {
+                    Ok(val) => match json::from_slice(&val) {
+                        Ok(val) => val,
+                        Err(e) => {
+                            log::error!("Error getting value: {}", e);
+                            continue;
+                        }
+                    },
+                    Err(e) => {
+                        log::error!("Error getting value: {}", e);
+                        continue;
+                    }
+                };
+                API_TOKENS.insert(item_key.to_owned(), item_value);
+            }
+            db::Event::Delete(ev) => {
+                let item_key = ev.key.strip_prefix(API_TOKENS_KEY).unwrap();
+                API_TOKENS.remove(item_key);
+            }
+            db::Event::Empty => {}
+        }
+    }
+    Ok(())
+}
+
+pub async fn cache() -> Result<(), anyhow::Error> {
+    let ret = db::list(API_TOKENS_KEY).await?;
+    for (key, item_value) in ret {
+        let item_key = key.strip_prefix(API_TOKENS_KEY).unwrap();
+        let json_val: ApiToken = json::from_slice(&item_value)?;
+        API_TOKENS.insert(item_key.to_owned(), json_val);
+    }
+    log::info!("API tokens Cached");
+    Ok(())
+}
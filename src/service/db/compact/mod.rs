@@ -17,5 +17,6 @@ pub mod downsampling;
 pub mod file_list;
 pub mod files;
 pub mod organization;
+pub mod rekey;
 pub mod retention;
 pub mod stats;
@@ -0,0 +1,54 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use config::meta::stream::StreamType;
+
+use crate::service::db;
+
+fn mk_key(org_id: &str, stream_type: StreamType, stream_name: &str) -> String {
+    format!("/compact/rekey/{org_id}/{stream_type}/{stream_name}")
+}
+
+/// Returns the data key version all the files of the stream were rewritten
+/// with, 0 if none.
+pub async fn get_version(org_id: &str, stream_type: StreamType, stream_name: &str) -> u32 {
+    let key = mk_key(org_id, stream_type, stream_name);
+    match db::get(&key).await {
+        Ok(ret) => String::from_utf8_lossy(&ret).parse().unwrap_or_default(),
+        Err(_) => 0,
+    }
+}
+
+pub async fn set_version(
+    org_id: &str,
+    stream_type: StreamType,
+    stream_name: &str,
+    version: u32,
+) -> Result<(), anyhow::Error> {
+    let key = mk_key(org_id, stream_type, stream_name);
+    db::put(&key, version.to_string().into(), db::NO_NEED_WATCH, None).await?;
+    Ok(())
+}
+
+pub async fn delete(
+    org_id: &str,
+    stream_type: StreamType,
+    stream_name: &str,
+) -> Result<(), anyhow::Error> {
+    let key = mk_key(org_id, stream_type, stream_name);
+    db::delete_if_exists(&key, false, db::NO_NEED_WATCH)
+        .await
+        .map_err(Into::into)
+}
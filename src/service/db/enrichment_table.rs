@@ -16,7 +16,10 @@
 use std::sync::Arc;
 
 use config::{
-    meta::stream::{EnrichmentTableMetaStreamStats, StreamType},
+    meta::{
+        enrichment_table::{EnrichmentTableSource, EnrichmentTableSourceStatus},
+        stream::{EnrichmentTableMetaStreamStats, StreamType},
+    },
     utils::{
         json,
         time::{BASE_TIME, now_micros},
@@ -33,6 +36,8 @@ use crate::{
 /// Will no longer be used as we are using the meta stream stats to store start, end time and size
 pub const ENRICHMENT_TABLE_SIZE_KEY: &str = "/enrichment_table_size";
 pub const ENRICHMENT_TABLE_META_STREAM_STATS_KEY: &str = "/enrichment_table_meta_stream_stats";
+pub const ENRICHMENT_TABLE_SOURCE_KEY: &str = "/enrichment_table_source";
+pub const ENRICHMENT_TABLE_SOURCE_STATUS_KEY: &str = "/enrichment_table_source_status";
 
 pub async fn get(org_id: &str, name: &str) -> Result<Vec<vrl::value::Value>, anyhow::Error> {
     let start_time = get_start_time(org_id, name).await;
@@ -193,6 +198,85 @@ pub async fn delete_meta_table_stats(org_id: &str, name: &str) -> Result<(), inf
     .await
 }
 
+pub async fn get_source(
+    org_id: &str,
+    name: &str,
+) -> Result<Option<EnrichmentTableSource>, anyhow::Error> {
+    match db_service::get(&format!("{ENRICHMENT_TABLE_SOURCE_KEY}/{org_id}/{name}")).await {
+        Ok(val) => Ok(Some(json::from_slice(&val)?)),
+        Err(infra::errors::Error::DbError(infra::errors::DbError::KeyNotExists(_))) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn set_source(org_id: &str, source: &EnrichmentTableSource) -> Result<(), anyhow::Error> {
+    Ok(db_service::put(
+        &format!("{ENRICHMENT_TABLE_SOURCE_KEY}/{org_id}/{}", source.name),
+        json::to_vec(source)?.into(),
+        false,
+        None,
+    )
+    .await?)
+}
+
+pub async fn delete_source(org_id: &str, name: &str) -> Result<(), anyhow::Error> {
+    db_service::delete(
+        &format!("{ENRICHMENT_TABLE_SOURCE_KEY}/{org_id}/{name}"),
+        false,
+        false,
+        None,
+    )
+    .await?;
+    db_service::delete(
+        &format!("{ENRICHMENT_TABLE_SOURCE_STATUS_KEY}/{org_id}/{name}"),
+        false,
+        false,
+        None,
+    )
+    .await?;
+    Ok(())
+}
+
+/// Returns the sources of all the organizations as (org_id, source).
+pub async fn list_sources() -> Result<Vec<(String, EnrichmentTableSource)>, anyhow::Error> {
+    let prefix = format!("{ENRICHMENT_TABLE_SOURCE_KEY}/");
+    let mut sources = Vec::new();
+    for (key, val) in db_service::list(&prefix).await? {
+        let Some((org_id, _)) = key.strip_prefix(&prefix).and_then(|k| k.split_once('/')) else {
+            continue;
+        };
+        match json::from_slice::<EnrichmentTableSource>(&val) {
+            Ok(source) => sources.push((org_id.to_string(), source)),
+            Err(e) => log::error!("Error parsing enrichment table source {key}: {e}"),
+        }
+    }
+    Ok(sources)
+}
+
+pub async fn get_source_status(org_id: &str, name: &str) -> EnrichmentTableSourceStatus {
+    db_service::get(&format!(
+        "{ENRICHMENT_TABLE_SOURCE_STATUS_KEY}/{org_id}/{name}"
+    ))
+    .await
+    .ok()
+    .and_then(|val| json::from_slice(&val).ok())
+    .unwrap_or_default()
+}
+
+pub async fn set_source_status(
+    org_id: &str,
+    name: &str,
+    status: &EnrichmentTableSourceStatus,
+) -> Result<(), anyhow::Error> {
+    Ok(db_service::put(
+        &format!("{ENRICHMENT_TABLE_SOURCE_STATUS_KEY}/{org_id}/{name}"),
+        json::to_vec(status)?.into(),
+        false,
+        None,
+    )
+    .await?)
+}
+
 pub async fn notify_update(org_id: &str, name: &str) -> Result<(), infra::errors::Error> {
     let cluster_coordinator = infra_db::get_coordinator().await;
     let key: String = format!(
@@ -244,11 +328,7 @@ pub async fn watch() -> Result<(), anyhow::Error> {
                 );
                 ENRICHMENT_TABLES.insert(
                     item_key.to_owned(),
-                    StreamTable {
-                        org_id: org_id.to_string(),
-                        stream_name: stream_name.to_string(),
-                        data,
-                    },
+                    StreamTable::new(org_id, stream_name, data),
                 );
             }
             infra_db::Event::Delete(ev) => {
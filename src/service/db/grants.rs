@@ -0,0 +1,181 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use config::{
+    meta::grant::{Grant, GrantResource},
+    utils::json,
+};
+
+use crate::{
+    common::infra::config::{GRANT_RESTRICTIONS, GRANTS},
+    service::db,
+};
+
+const GRANTS_KEY: &str = "/grants/";
+const RESTRICTIONS_KEY: &str = "/grant_restrictions/";
+
+#[tracing::instrument(name = "service:db:grants:list")]
+pub async fn list(org_id: &str) -> Result<Vec<Grant>, anyhow::Error> {
+    Ok(db::list(&format!("{GRANTS_KEY}{org_id}/"))
+        .await?
+        .values()
+        .filter_map(|val| json::from_slice(val).ok())
+        .collect())
+}
+
+#[tracing::instrument(name = "service:db:grants:set", skip_all)]
+pub async fn set(org_id: &str, grant: &Grant) -> Result<(), anyhow::Error> {
+    Ok(db::put(
+        &format!("{GRANTS_KEY}{org_id}/{}", grant.id),
+        json::to_vec(grant).unwrap().into(),
+        db::NEED_WATCH,
+        None,
+    )
+    .await?)
+}
+
+#[tracing::instrument(name = "service:db:grants:get")]
+pub async fn get(org_id: &str, id: &str) -> Result<Grant, anyhow::Error> {
+    let val = db::get(&format!("{GRANTS_KEY}{org_id}/{id}")).await?;
+    Ok(json::from_slice(&val)?)
+}
+
+#[tracing::instrument(name = "service:db:grants:delete")]
+pub async fn delete(org_id: &str, id: &str) -> Result<(), anyhow::Error> {
+    Ok(db::delete(
+        &format!("{GRANTS_KEY}{org_id}/{id}"),
+        false,
+        db::NEED_WATCH,
+        None,
+    )
+    .await?)
+}
+
+/// Saves the kinds of resources a subject is restricted on.
+#[tracing::instrument(name = "service:db:grants:set_restrictions", skip_all)]
+pub async fn set_restrictions(
+    org_id: &str,
+    subject: &str,
+    restrictions: &[GrantResource],
+) -> Result<(), anyhow::Error> {
+    Ok(db::put(
+        &format!("{RESTRICTIONS_KEY}{org_id}/{subject}"),
+        json::to_vec(restrictions).unwrap().into(),
+        db::NEED_WATCH,
+        None,
+    )
+    .await?)
+}
+
+pub async fn watch_restrictions() -> Result<(), anyhow::Error> {
+    let cluster_coordinator = db::get_coordinator().await;
+    let mut events = cluster_coordinator.watch(RESTRICTIONS_KEY).await?;
+    let events = Arc::get_mut(&mut events).unwrap();
+    log::info!("Start watching grant restrictions");
+    loop {
+        let ev = match events.recv().await {
+            Some(ev) => ev,
+            None => {
+                log::error!("watch_grant_restrictions: event channel closed");
+                break;
+            }
+        };
+        match ev {
+            db::Event::Put(ev) => {
+                let item_key = ev.key.strip_prefix(RESTRICTIONS_KEY).unwrap();
+                let item_value: Vec<GrantResource> = match db::get(&ev.key).await {
+                    Ok(val) => match json::from_slice(&val) {
+                        Ok(val) => val,
+                        Err(e) => {
+                            log::error!("Error getting value: {}", e);
+                            continue;
+                        }
+                    },
+                    Err(e) => {
+                        log::error!("Error getting value: {}", e);
+                        continue;
+                    }
+                };
+                GRANT_RESTRICTIONS.insert(item_key.to_owned(), item_value);
+            }
+            db::Event::Delete(ev) => {
+                let item_key = ev.key.strip_prefix(RESTRICTIONS_KEY).unwrap();
+                GRANT_RESTRICTIONS.remove(item_key);
+            }
+            db::Event::Empty => {}
+        }
+    }
+    Ok(())
+}
+
+pub async fn watch() -> Result<(), anyhow::Error> {
+    let cluster_coordinator = db::get_coordinator().await;
+    let mut events = cluster_coordinator.watch(GRANTS_KEY).await?;
+    let events = Arc::get_mut(&mut events).unwrap();
+    log::info!("Start watching grants");
+    loop {
+        let ev = match events.recv().await {
+            Some(ev) => ev,
+            None => {
+                log::error!("watch_grants: event channel closed");
+                break;
+            }
+        };
+        match ev {
+            db::Event::Put(ev) => {
+                let item_key = ev.key.strip_prefix(GRANTS_KEY).unwrap();
+                let item_value: Grant = match db::get(&ev.key).await {
+                    Ok(val) => match json::from_slice(&val) {
+                        Ok(val) => val,
+                        Err(e) => {
+                            log::error!("Error getting value: {}", e);
+                            continue;
+                        }
+                    },
+                    Err(e) => {
+                        log::error!("Error getting value: {}", e);
+                        continue;
+                    }
+                };
+                GRANTS.insert(item_key.to_owned(), item_value);
+            }
+            db::Event::Delete(ev) => {
+                let item_key = ev.key.strip_prefix(GRANTS_KEY).unwrap();
+                GRANTS.remove(item_key);
+            }
+            db::Event::Empty => {}
+        }
+    }
+    Ok(())
+}
+
+pub async fn cache() -> Result<(), anyhow::Error> {
+    let ret = db::list(GRANTS_KEY).await?;
+    for (key, item_value) in ret {
+        let item_key = key.strip_prefix(GRANTS_KEY).unwrap();
+        let json_val: Grant = json::from_slice(&item_value)?;
+        GRANTS.insert(item_key.to_owned(), json_val);
+    }
+    let ret = db::list(RESTRICTIONS_KEY).await?;
+    for (key, item_value) in ret {
+        let item_key = key.strip_prefix(RESTRICTIONS_KEY).unwrap();
+        let json_val: Vec<GrantResource> = json::from_slice(&item_value)?;
+        GRANT_RESTRICTIONS.insert(item_key.to_owned(), json_val);
+    }
+    log::info!("Grants Cached");
+    Ok(())
+}
@@ -22,12 +22,15 @@ use {
 };
 
 pub mod alerts;
+pub mod api_tokens;
 pub mod compact;
 pub mod dashboards;
 pub mod distinct_values;
 pub mod enrichment_table;
 pub mod file_list;
 pub mod functions;
+#[cfg(not(feature = "enterprise"))]
+pub mod grants;
 #[cfg(feature = "enterprise")]
 pub mod keys;
 pub mod kv;
@@ -38,9 +41,12 @@ pub mod ofga;
 pub mod org_users;
 pub mod organization;
 pub mod pipeline;
+pub mod quotas;
 pub mod saved_view;
 pub mod scheduler;
 pub mod schema;
+#[cfg(not(feature = "enterprise"))]
+pub mod scim;
 pub mod search_job;
 pub mod session;
 pub mod short_url;
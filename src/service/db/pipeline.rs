@@ -54,6 +54,11 @@ pub enum PipelineError {
     InvalidDerivedStream(String),
     #[error("Error deleting previous DerivedStream: {0}")]
     DeleteDerivedStream(String),
+    #[error("Pipeline dry run failed: {0}")]
+    DryRun(String),
+    // forbidden
+    #[error("Pipeline dry run can't read stream {0}")]
+    DryRunForbidden(String),
 }
 
 /// Stores a new pipeline to database.
@@ -320,6 +325,7 @@ pub async fn watch() -> Result<(), anyhow::Error> {
             }
             db::Event::Delete(ev) => {
                 let pipeline_id = ev.key.strip_prefix(PIPELINES_WATCH_PREFIX).unwrap();
+                crate::service::pipeline::stats::forget(pipeline_id);
                 if let Some(removed) = PIPELINE_STREAM_MAPPING.write().await.remove(pipeline_id) {
                     if STREAM_EXECUTABLE_PIPELINES
                         .write()
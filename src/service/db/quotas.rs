@@ -0,0 +1,180 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use config::{
+    cluster::LOCAL_NODE,
+    meta::quota::{DailyUsage, OrgQuota},
+    utils::json,
+};
+use hashbrown::HashMap;
+
+use crate::{common::infra::config::ORG_QUOTAS, service::db};
+
+const QUOTAS_KEY: &str = "/quotas/";
+const USAGE_KEY: &str = "/quota_usage/";
+const ALERTS_KEY: &str = "/quota_alerts/";
+
+#[tracing::instrument(name = "service:db:quotas:get")]
+pub async fn get(org_id: &str) -> Result<OrgQuota, anyhow::Error> {
+    let val = db::get(&format!("{QUOTAS_KEY}{org_id}")).await?;
+    Ok(json::from_slice(&val)?)
+}
+
+#[tracing::instrument(name = "service:db:quotas:set", skip_all)]
+pub async fn set(org_id: &str, quota: &OrgQuota) -> Result<(), anyhow::Error> {
+    Ok(db::put(
+        &format!("{QUOTAS_KEY}{org_id}"),
+        json::to_vec(quota).unwrap().into(),
+        db::NEED_WATCH,
+        None,
+    )
+    .await?)
+}
+
+#[tracing::instrument(name = "service:db:quotas:delete")]
+pub async fn delete(org_id: &str) -> Result<(), anyhow::Error> {
+    Ok(db::delete(
+        &format!("{QUOTAS_KEY}{org_id}"),
+        false,
+        db::NEED_WATCH,
+        None,
+    )
+    .await?)
+}
+
+/// Adds the usage of this node to its entry of the day.
+pub async fn add_usage(day: u32, org_id: &str, usage: &DailyUsage) -> Result<(), anyhow::Error> {
+    let key = format!("{USAGE_KEY}{day}/{org_id}/{}", LOCAL_NODE.uuid);
+    let mut saved: DailyUsage = match db::get(&key).await {
+        Ok(val) => json::from_slice(&val).unwrap_or_default(),
+        Err(_) => DailyUsage::default(),
+    };
+    saved.add(usage);
+    Ok(db::put(&key, json::to_vec(&saved)?.into(), db::NO_NEED_WATCH, None).await?)
+}
+
+/// Returns the usage of the day summed over all the nodes, org_id -> usage
+pub async fn list_usage(day: u32) -> Result<HashMap<String, DailyUsage>, anyhow::Error> {
+    let prefix = format!("{USAGE_KEY}{day}/");
+    let mut result: HashMap<String, DailyUsage> = HashMap::new();
+    for (key, val) in db::list(&prefix).await? {
+        let Some((org_id, _node)) = key
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.split_once('/'))
+        else {
+            continue;
+        };
+        if let Ok(usage) = json::from_slice::<DailyUsage>(&val) {
+            result.entry(org_id.to_string()).or_default().add(&usage);
+        }
+    }
+    Ok(result)
+}
+
+/// Removes the usage entries of the days before `day`.
+pub async fn prune_usage(day: u32) -> Result<(), anyhow::Error> {
+    let mut old_days = db::list_keys(USAGE_KEY)
+        .await?
+        .into_iter()
+        .filter_map(|key| {
+            key.strip_prefix(USAGE_KEY)
+                .and_then(|rest| rest.split('/').next())
+                .and_then(|d| d.parse::<u32>().ok())
+        })
+        .filter(|d| *d < day)
+        .collect::<Vec<_>>();
+    old_days.sort_unstable();
+    old_days.dedup();
+    for old_day in old_days {
+        db::delete_if_exists(&format!("{USAGE_KEY}{old_day}/"), true, db::NO_NEED_WATCH).await?;
+    }
+    Ok(())
+}
+
+/// Returns the last notified state of a quota, e.g. `20250131/warning`.
+pub async fn get_alert_state(org_id: &str, quota_name: &str) -> Option<String> {
+    db::get(&format!("{ALERTS_KEY}{org_id}/{quota_name}"))
+        .await
+        .ok()
+        .map(|val| String::from_utf8_lossy(&val).to_string())
+}
+
+pub async fn set_alert_state(
+    org_id: &str,
+    quota_name: &str,
+    state: &str,
+) -> Result<(), anyhow::Error> {
+    Ok(db::put(
+        &format!("{ALERTS_KEY}{org_id}/{quota_name}"),
+        state.to_string().into(),
+        db::NO_NEED_WATCH,
+        None,
+    )
+    .await?)
+}
+
+pub async fn watch() -> Result<(), anyhow::Error> {
+    let cluster_coordinator = db::get_coordinator().await;
+    let mut events = cluster_coordinator.watch(QUOTAS_KEY).await?;
+    let events = Arc::get_mut(&mut events).unwrap();
+    log::info!("Start watching org quotas");
+    loop {
+        let ev = match events.recv().await {
+            Some(ev) => ev,
+            None => {
+                log::error!("watch_quotas: event channel closed");
+                break;
+            }
+        };
+        match ev {
+            db::Event::Put(ev) => {
+                let item_key = ev.key.strip_prefix(QUOTAS_KEY).unwrap();
+                let item_value: OrgQuota = match db::get(&ev.key).await {
+                    Ok(val) => match json::from_slice(&val) {
+                        Ok(val) => val,
+                        Err(e) => {
+                            log::error!("Error getting value: {}", e);
+                            continue;
+                        }
+                    },
+                    Err(e) => {
+                        log::error!("Error getting value: {}", e);
+                        continue;
+                    }
+                };
+                ORG_QUOTAS.insert(item_key.to_owned(), item_value);
+            }
+            db::Event::Delete(ev) => {
+                let item_key = ev.key.strip_prefix(QUOTAS_KEY).unwrap();
+                ORG_QUOTAS.remove(item_key);
+            }
+            db::Event::Empty => {}
+        }
+    }
+    Ok(())
+}
+
+pub async fn cache() -> Result<(), anyhow::Error> {
+    let ret = db::list(QUOTAS_KEY).await?;
+    for (key, item_value) in ret {
+        let item_key = key.strip_prefix(QUOTAS_KEY).unwrap();
+        let json_val: OrgQuota = json::from_slice(&item_value)?;
+        ORG_QUOTAS.insert(item_key.to_owned(), json_val);
+    }
+    log::info!("Org quotas Cached");
+    Ok(())
+}
@@ -414,11 +414,7 @@ pub async fn watch() -> Result<(), anyhow::Error> {
                         .unwrap();
                     ENRICHMENT_TABLES.insert(
                         item_key.to_owned(),
-                        StreamTable {
-                            org_id: org_id.to_string(),
-                            stream_name: stream_name.to_string(),
-                            data,
-                        },
+                        StreamTable::new(org_id, stream_name, data),
                     );
                 }
 
@@ -470,6 +466,11 @@ pub async fn watch() -> Result<(), anyhow::Error> {
                 {
                     log::error!("[Schema:watch] del_offset: {}", e);
                 }
+                if let Err(e) =
+                    super::compact::rekey::delete(org_id, stream_type, stream_name).await
+                {
+                    log::error!("[Schema:watch] del rekey version: {}", e);
+                }
 
                 if stream_type.eq(&StreamType::EnrichmentTables) && is_local_disk_storage() {
                     let data_dir = format!(
@@ -584,11 +585,7 @@ pub async fn cache_enrichment_tables() -> Result<(), anyhow::Error> {
         }
         tables.insert(
             schema_key.to_owned(),
-            StreamTable {
-                org_id: org_id.to_string(),
-                stream_name: stream_name.to_string(),
-                data: vec![],
-            },
+            StreamTable::new(org_id, stream_name, vec![]),
         );
     }
     drop(r);
@@ -613,14 +610,7 @@ pub async fn cache_enrichment_tables() -> Result<(), anyhow::Error> {
     // fill data
     for (key, tbl) in tables {
         let data = super::enrichment_table::get(&tbl.org_id, &tbl.stream_name).await?;
-        ENRICHMENT_TABLES.insert(
-            key,
-            StreamTable {
-                org_id: tbl.org_id,
-                stream_name: tbl.stream_name,
-                data,
-            },
-        );
+        ENRICHMENT_TABLES.insert(key, StreamTable::new(&tbl.org_id, &tbl.stream_name, data));
     }
     log::info!("EnrichmentTables Cached");
     Ok(())
@@ -0,0 +1,98 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use config::{
+    meta::scim::{ScimGroup, ScimUser},
+    utils::json,
+};
+
+use crate::service::db;
+
+const SCIM_USERS_KEY: &str = "/scim_users/";
+const SCIM_GROUPS_KEY: &str = "/scim_groups/";
+
+#[tracing::instrument(name = "service:db:scim:list_users")]
+pub async fn list_users(org_id: &str) -> Result<Vec<ScimUser>, anyhow::Error> {
+    list(&format!("{SCIM_USERS_KEY}{org_id}/")).await
+}
+
+#[tracing::instrument(name = "service:db:scim:get_user")]
+pub async fn get_user(org_id: &str, id: &str) -> Result<ScimUser, anyhow::Error> {
+    let val = db::get(&format!("{SCIM_USERS_KEY}{org_id}/{id}")).await?;
+    Ok(json::from_slice(&val)?)
+}
+
+#[tracing::instrument(name = "service:db:scim:set_user", skip_all)]
+pub async fn set_user(org_id: &str, user: &ScimUser) -> Result<(), anyhow::Error> {
+    Ok(db::put(
+        &format!("{SCIM_USERS_KEY}{org_id}/{}", user.id),
+        json::to_vec(user).unwrap().into(),
+        db::NO_NEED_WATCH,
+        None,
+    )
+    .await?)
+}
+
+#[tracing::instrument(name = "service:db:scim:delete_user")]
+pub async fn delete_user(org_id: &str, id: &str) -> Result<(), anyhow::Error> {
+    Ok(db::delete(
+        &format!("{SCIM_USERS_KEY}{org_id}/{id}"),
+        false,
+        db::NO_NEED_WATCH,
+        None,
+    )
+    .await?)
+}
+
+#[tracing::instrument(name = "service:db:scim:list_groups")]
+pub async fn list_groups(org_id: &str) -> Result<Vec<ScimGroup>, anyhow::Error> {
+    list(&format!("{SCIM_GROUPS_KEY}{org_id}/")).await
+}
+
+#[tracing::instrument(name = "service:db:scim:get_group")]
+pub async fn get_group(org_id: &str, id: &str) -> Result<ScimGroup, anyhow::Error> {
+    let val = db::get(&format!("{SCIM_GROUPS_KEY}{org_id}/{id}")).await?;
+    Ok(json::from_slice(&val)?)
+}
+
+#[tracing::instrument(name = "service:db:scim:set_group", skip_all)]
+pub async fn set_group(org_id: &str, group: &ScimGroup) -> Result<(), anyhow::Error> {
+    Ok(db::put(
+        &format!("{SCIM_GROUPS_KEY}{org_id}/{}", group.id),
+        json::to_vec(group).unwrap().into(),
+        db::NO_NEED_WATCH,
+        None,
+    )
+    .await?)
+}
+
+#[tracing::instrument(name = "service:db:scim:delete_group")]
+pub async fn delete_group(org_id: &str, id: &str) -> Result<(), anyhow::Error> {
+    Ok(db::delete(
+        &format!("{SCIM_GROUPS_KEY}{org_id}/{id}"),
+        false,
+        db::NO_NEED_WATCH,
+        None,
+    )
+    .await?)
+}
+
+async fn list<T: serde::de::DeserializeOwned>(prefix: &str) -> Result<Vec<T>, anyhow::Error> {
+    Ok(db::list(prefix)
+        .await?
+        .values()
+        .filter_map(|val| json::from_slice(val).ok())
+        .collect())
+}
@@ -15,15 +15,16 @@
 
 use std::sync::Arc;
 
-use config::utils::json;
+use config::{meta::session::SessionInfo, utils::json};
 
 use crate::{
-    common::infra::config::USER_SESSIONS,
+    common::infra::config::{USER_SESSION_INFOS, USER_SESSIONS},
     service::db::{self},
 };
 
 // DBKey to set settings for an org
 pub const USER_SESSION_KEY: &str = "/user_sessions/";
+pub const USER_SESSION_INFO_KEY: &str = "/user_session_info/";
 
 pub async fn get(session_id: &str) -> Result<String, anyhow::Error> {
     match USER_SESSIONS.get(session_id) {
@@ -122,6 +123,108 @@ pub async fn cache() -> Result<(), anyhow::Error> {
         }
         USER_SESSIONS.insert(session_id.to_owned(), json_val);
     }
+
+    let ret = db::list_values(USER_SESSION_INFO_KEY).await?;
+    for item_value in ret {
+        match json::from_slice::<SessionInfo>(&item_value) {
+            Ok(info) => {
+                USER_SESSION_INFOS.insert(info.handle.clone(), info);
+            }
+            Err(e) => {
+                log::error!("Error deserializing session info: {}", e);
+            }
+        }
+    }
     log::info!("User Sessions Cached");
     Ok(())
 }
+
+pub async fn get_info(handle: &str) -> Result<SessionInfo, anyhow::Error> {
+    let val = db::get(&format!("{USER_SESSION_INFO_KEY}{handle}")).await?;
+    Ok(json::from_slice(&val)?)
+}
+
+/// Saves the info of a session, the other nodes are only notified of new
+/// sessions, not of the updates of the last seen time.
+pub async fn set_info(info: &SessionInfo, need_watch: bool) -> Result<(), anyhow::Error> {
+    db::put(
+        &format!("{USER_SESSION_INFO_KEY}{}", info.handle),
+        json::to_vec(info).unwrap().into(),
+        need_watch,
+        None,
+    )
+    .await?;
+    USER_SESSION_INFOS.insert(info.handle.clone(), info.clone());
+    Ok(())
+}
+
+pub async fn delete_info(handle: &str) -> Result<(), anyhow::Error> {
+    db::delete_if_exists(
+        &format!("{USER_SESSION_INFO_KEY}{handle}"),
+        false,
+        db::NEED_WATCH,
+    )
+    .await?;
+    USER_SESSION_INFOS.remove(handle);
+    Ok(())
+}
+
+/// Returns the ids of all the sessions.
+pub async fn list_ids() -> Result<Vec<String>, anyhow::Error> {
+    Ok(db::list_keys(USER_SESSION_KEY)
+        .await?
+        .into_iter()
+        .filter_map(|key| {
+            key.strip_prefix(USER_SESSION_KEY)
+                .map(|id| id.trim_end_matches('/').to_string())
+        })
+        .collect())
+}
+
+pub async fn list_info() -> Result<Vec<SessionInfo>, anyhow::Error> {
+    let ret = db::list_values(USER_SESSION_INFO_KEY).await?;
+    Ok(ret
+        .iter()
+        .filter_map(|v| json::from_slice(v).ok())
+        .collect())
+}
+
+pub async fn watch_info() -> Result<(), anyhow::Error> {
+    let key = USER_SESSION_INFO_KEY;
+    let cluster_coordinator = db::get_coordinator().await;
+    let mut events = cluster_coordinator.watch(key).await?;
+    let events = Arc::get_mut(&mut events).unwrap();
+    log::info!("Start watching user session info");
+    loop {
+        let ev = match events.recv().await {
+            Some(ev) => ev,
+            None => {
+                log::error!("watch_user_session_info: event channel closed");
+                return Ok(());
+            }
+        };
+        match ev {
+            db::Event::Put(ev) => {
+                let item_value: SessionInfo = match db::get(&ev.key).await {
+                    Ok(val) => match json::from_slice(&val) {
+                        Ok(val) => val,
+                        Err(e) => {
+                            log::error!("Error getting value: {}", e);
+                            continue;
+                        }
+                    },
+                    Err(e) => {
+                        log::error!("Error getting value: {}", e);
+                        continue;
+                    }
+                };
+                USER_SESSION_INFOS.insert(item_value.handle.clone(), item_value);
+            }
+            db::Event::Delete(ev) => {
+                let item_key = ev.key.strip_prefix(key).unwrap();
+                USER_SESSION_INFOS.remove(item_key);
+            }
+            db::Event::Empty => {}
+        }
+    }
+}
@@ -0,0 +1,67 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use config::{
+    get_config,
+    meta::{encryption::EncryptionStatus, user::UserRole},
+};
+use infra::storage::data_keys;
+
+use crate::{common::utils::auth::is_root_user, service::users};
+
+#[derive(Debug, thiserror::Error)]
+pub enum EncryptionError {
+    #[error("Encryption at rest is not enabled")]
+    Disabled,
+    #[error("Not allowed to manage the data keys of {0}")]
+    Forbidden(String),
+    #[error("{0}")]
+    InfraError(#[from] anyhow::Error),
+}
+
+/// Returns the data keys of an org to root and the org admins, the keys
+/// themselves are never returned.
+pub async fn status(org_id: &str, user_email: &str) -> Result<EncryptionStatus, EncryptionError> {
+    if !is_root_user(user_email)
+        && !users::get_user(Some(org_id), user_email)
+            .await
+            .is_some_and(|user| matches!(user.role, UserRole::Admin | UserRole::Root))
+    {
+        return Err(EncryptionError::Forbidden(org_id.to_string()));
+    }
+    if !get_config().encryption.at_rest_enabled {
+        return Ok(EncryptionStatus::default());
+    }
+    let keys = data_keys::list(org_id).await?;
+    Ok(EncryptionStatus {
+        enabled: true,
+        active_version: keys.last().map(|k| k.version),
+        keys,
+    })
+}
+
+/// Rotates the data key of an org, the new files are encrypted with the new
+/// key and the compactors rewrite the existing files with it.
+pub async fn rotate(org_id: &str, user_email: &str) -> Result<EncryptionStatus, EncryptionError> {
+    if !is_root_user(user_email) {
+        return Err(EncryptionError::Forbidden(org_id.to_string()));
+    }
+    if !get_config().encryption.at_rest_enabled {
+        return Err(EncryptionError::Disabled);
+    }
+    let version = data_keys::rotate(org_id, user_email).await?;
+    log::info!("[ENCRYPTION] {user_email} rotated the data key of {org_id} to {version}");
+    status(org_id, user_email).await
+}
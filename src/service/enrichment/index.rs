@@ -0,0 +1,553 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Lookup indexes of the enrichment tables.
+//!
+//! The indexes only return the candidate rows of a lookup, the caller still
+//! checks all the conditions on them, so a hash collision or a condition which
+//! isn't covered by the index never changes the result. The indexes are built
+//! once per loaded table and shared by all the clones of it.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    net::IpAddr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use config::{get_config, metrics, utils::time::parse_str_to_time};
+use ipnetwork::IpNetwork;
+use parking_lot::RwLock;
+use vector_enrichment::{Case, Condition, IndexHandle};
+use vrl::value::Value;
+
+/// Memory of the indexes of all the enrichment tables.
+static INDEX_MEMORY: AtomicUsize = AtomicUsize::new(0);
+
+/// Rough overhead of a hash map entry and its row list.
+const ENTRY_OVERHEAD: usize = 64;
+
+/// Suffix of the condition fields which match by network containment.
+const CONTAINS_SUFFIX: &str = "|contains";
+
+#[derive(Debug)]
+pub struct TableIndexes {
+    org_id: String,
+    table_name: String,
+    inner: RwLock<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    indexes: Vec<Index>,
+    // built on the first date range lookup of the field, None if it doesn't fit
+    dates: HashMap<String, Option<Vec<(i64, usize)>>>,
+    memory: usize,
+}
+
+#[derive(Debug)]
+struct Index {
+    case: Case,
+    fields: Vec<String>,
+    // None if the index doesn't fit in memory or no field can be indexed
+    kind: Option<IndexKind>,
+}
+
+#[derive(Debug)]
+enum IndexKind {
+    /// Rows by the values of the key fields, the network containment fields
+    /// of a multi field index are left out of the key.
+    Exact {
+        key_fields: Vec<String>,
+        rows: HashMap<Vec<u8>, Vec<usize>>,
+    },
+    /// Rows of a single network containment field by the network, an IP
+    /// address matches the rows of all the networks containing it.
+    Network {
+        field: String,
+        networks: HashMap<(bool, u8, u128), Vec<usize>>,
+        prefixes: BTreeSet<(bool, u8)>,
+    },
+}
+
+impl TableIndexes {
+    pub fn new(org_id: &str, table_name: &str) -> Self {
+        Self {
+            org_id: org_id.to_string(),
+            table_name: table_name.to_string(),
+            inner: RwLock::new(Inner::default()),
+        }
+    }
+
+    /// Returns the handle of the index of the fields, building it if needed.
+    pub fn add_index(&self, data: &[Value], case: Case, fields: &[&str]) -> IndexHandle {
+        let fields = fields.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+        let mut inner = self.inner.write();
+        if let Some(pos) = inner
+            .indexes
+            .iter()
+            .position(|idx| idx.case == case && idx.fields == fields)
+        {
+            return IndexHandle(pos);
+        }
+        let kind = build_index(data, case, &fields).and_then(|kind| {
+            let size = index_memory(&kind);
+            self.reserve(&mut inner, size).then_some(kind)
+        });
+        if kind.is_none() {
+            log::warn!(
+                "[ENRICHMENT] org: {}, table: {} lookups on fields {:?} are not indexed",
+                self.org_id,
+                self.table_name,
+                fields
+            );
+        }
+        inner.indexes.push(Index { case, fields, kind });
+        IndexHandle(inner.indexes.len() - 1)
+    }
+
+    pub fn index_fields(&self) -> Vec<(Case, Vec<String>)> {
+        self.inner
+            .read()
+            .indexes
+            .iter()
+            .map(|idx| (idx.case, idx.fields.clone()))
+            .collect()
+    }
+
+    /// Returns the candidate rows of the lookup in the table order, or None if
+    /// no index can be used and the table needs to be scanned.
+    pub fn find_rows(
+        &self,
+        data: &[Value],
+        case: Case,
+        conditions: &[Condition],
+        index: Option<IndexHandle>,
+    ) -> Option<Vec<usize>> {
+        if let Some(IndexHandle(pos)) = index
+            && let Some(rows) = self.find_by_index(pos, case, conditions)
+        {
+            return Some(rows);
+        }
+        let (field, from, to) = conditions.iter().find_map(|cond| match cond {
+            Condition::BetweenDates { field, from, to } => Some((
+                field.to_string(),
+                from.timestamp_micros(),
+                to.timestamp_micros(),
+            )),
+            _ => None,
+        })?;
+        self.find_by_dates(data, &field, from, to)
+    }
+
+    fn find_by_index(
+        &self,
+        pos: usize,
+        case: Case,
+        conditions: &[Condition],
+    ) -> Option<Vec<usize>> {
+        let inner = self.inner.read();
+        let index = inner.indexes.get(pos)?;
+        if index.case != case {
+            return None;
+        }
+        let mut rows = match index.kind.as_ref()? {
+            IndexKind::Exact { key_fields, rows } => {
+                let mut key = Vec::new();
+                for field in key_fields {
+                    let value = equals_value(conditions, field)?;
+                    let Some(value_key) = value_key(value, case) else {
+                        // can't match any row
+                        return Some(vec![]);
+                    };
+                    push_key(&mut key, &value_key);
+                }
+                rows.get(&key).cloned().unwrap_or_default()
+            }
+            IndexKind::Network {
+                field,
+                networks,
+                prefixes,
+            } => {
+                let value = equals_value(conditions, field)?;
+                let mut found = Vec::new();
+                if let Some(ip) = value.as_str().and_then(|v| v.parse::<IpAddr>().ok()) {
+                    for (is_v6, prefix) in prefixes.iter() {
+                        if *is_v6 == ip.is_ipv6()
+                            && let Some(v) =
+                                networks.get(&(*is_v6, *prefix, network_bits(ip, *prefix)))
+                        {
+                            found.extend(v);
+                        }
+                    }
+                }
+                found
+            }
+        };
+        rows.sort_unstable();
+        rows.dedup();
+        Some(rows)
+    }
+
+    fn find_by_dates(&self, data: &[Value], field: &str, from: i64, to: i64) -> Option<Vec<usize>> {
+        if !self.inner.read().dates.contains_key(field) {
+            let mut inner = self.inner.write();
+            if !inner.dates.contains_key(field) {
+                let index = build_date_index(data, field);
+                let size = index.len() * std::mem::size_of::<(i64, usize)>() + ENTRY_OVERHEAD;
+                let index = self.reserve(&mut inner, size).then_some(index);
+                inner.dates.insert(field.to_string(), index);
+            }
+        }
+        let inner = self.inner.read();
+        let index = inner.dates.get(field)?.as_ref()?;
+        let start = index.partition_point(|(ts, _)| *ts < from);
+        let end = index.partition_point(|(ts, _)| *ts <= to);
+        let mut rows = index[start..end.max(start)]
+            .iter()
+            .map(|(_, row)| *row)
+            .collect::<Vec<_>>();
+        rows.sort_unstable();
+        Some(rows)
+    }
+
+    /// Accounts the memory of a new index, returns false if it's over the limit.
+    fn reserve(&self, inner: &mut Inner, size: usize) -> bool {
+        let limit = get_config().limit.enrichment_table_index_max_size * 1024 * 1024;
+        if INDEX_MEMORY.fetch_add(size, Ordering::Relaxed) + size > limit {
+            INDEX_MEMORY.fetch_sub(size, Ordering::Relaxed);
+            return false;
+        }
+        inner.memory += size;
+        metrics::ENRICHMENT_TABLE_INDEX_BYTES
+            .with_label_values(&[&self.org_id, &self.table_name])
+            .add(size as i64);
+        true
+    }
+}
+
+impl Drop for TableIndexes {
+    fn drop(&mut self) {
+        let memory = self.inner.get_mut().memory;
+        if memory > 0 {
+            INDEX_MEMORY.fetch_sub(memory, Ordering::Relaxed);
+            metrics::ENRICHMENT_TABLE_INDEX_BYTES
+                .with_label_values(&[&self.org_id, &self.table_name])
+                .sub(memory as i64);
+        }
+    }
+}
+
+fn equals_value<'a>(conditions: &'a [Condition], name: &str) -> Option<&'a Value> {
+    conditions.iter().find_map(|cond| match cond {
+        Condition::Equals { field, value } if *field == name => Some(value),
+        _ => None,
+    })
+}
+
+/// The key of a value, the values which are equal under the case have the
+/// same key. Only strings can match case insensitively.
+fn value_key(value: &Value, case: Case) -> Option<Vec<u8>> {
+    match (value, case) {
+        (Value::Bytes(v), Case::Sensitive) => Some([b"s", &v[..]].concat()),
+        (Value::Bytes(v), Case::Insensitive) => Some([b"s", &v.to_ascii_lowercase()[..]].concat()),
+        (_, Case::Insensitive) => None,
+        (v, Case::Sensitive) => Some(format!("v{v}").into_bytes()),
+    }
+}
+
+fn push_key(key: &mut Vec<u8>, value_key: &[u8]) {
+    key.extend_from_slice(&(value_key.len() as u32).to_le_bytes());
+    key.extend_from_slice(value_key);
+}
+
+fn row_field<'a>(row: &'a Value, field: &str) -> Option<&'a Value> {
+    match row {
+        Value::Object(map) => map.get(field),
+        _ => None,
+    }
+}
+
+/// Returns the column of a network containment condition like
+/// `{"subnet|contains": .client_ip}`, which matches the rows whose network
+/// contains the address. The column names never contain the separator, so a
+/// plain equals condition is never taken for it.
+pub fn contains_field(field: &str) -> Option<&str> {
+    field.strip_suffix(CONTAINS_SUFFIX)
+}
+
+/// Parses a network like `10.0.0.0/8`, a plain address is a single host
+/// network.
+pub fn parse_network(value: &Value) -> Option<IpNetwork> {
+    value.as_str()?.parse::<IpNetwork>().ok()
+}
+
+fn network_bits(ip: IpAddr, prefix: u8) -> u128 {
+    let (bits, len) = match ip {
+        IpAddr::V4(v) => (u32::from(v) as u128, 32),
+        IpAddr::V6(v) => (u128::from(v), 128),
+    };
+    if prefix == 0 {
+        return 0;
+    }
+    let shift = len - prefix.min(len) as u32;
+    (bits >> shift) << shift
+}
+
+fn build_index(data: &[Value], case: Case, fields: &[String]) -> Option<IndexKind> {
+    if let [field] = fields
+        && let Some(column) = contains_field(field)
+    {
+        let mut networks: HashMap<(bool, u8, u128), Vec<usize>> = HashMap::new();
+        let mut prefixes = BTreeSet::new();
+        for (i, row) in data.iter().enumerate() {
+            if let Some(network) = row_field(row, column).and_then(parse_network) {
+                let is_v6 = network.is_ipv6();
+                let prefix = network.prefix();
+                prefixes.insert((is_v6, prefix));
+                networks
+                    .entry((is_v6, prefix, network_bits(network.network(), prefix)))
+                    .or_default()
+                    .push(i);
+            }
+        }
+        return Some(IndexKind::Network {
+            field: field.clone(),
+            networks,
+            prefixes,
+        });
+    }
+
+    let key_fields = fields
+        .iter()
+        .filter(|f| contains_field(f).is_none())
+        .cloned()
+        .collect::<Vec<_>>();
+    if key_fields.is_empty() {
+        return None;
+    }
+    let mut rows: HashMap<Vec<u8>, Vec<usize>> = HashMap::new();
+    'rows: for (i, row) in data.iter().enumerate() {
+        let mut key = Vec::new();
+        for field in key_fields.iter() {
+            // rows without the field never match an equals condition
+            let Some(value_key) = row_field(row, field).and_then(|v| value_key(v, case)) else {
+                continue 'rows;
+            };
+            push_key(&mut key, &value_key);
+        }
+        rows.entry(key).or_default().push(i);
+    }
+    Some(IndexKind::Exact { key_fields, rows })
+}
+
+fn build_date_index(data: &[Value], field: &str) -> Vec<(i64, usize)> {
+    let mut index = data
+        .iter()
+        .enumerate()
+        .filter_map(|(i, row)| {
+            let value = row_field(row, field)?.as_str()?;
+            let ts = parse_str_to_time(&value).ok()?;
+            Some((ts.timestamp_micros(), i))
+        })
+        .collect::<Vec<_>>();
+    index.sort_unstable();
+    index
+}
+
+fn index_memory(kind: &IndexKind) -> usize {
+    let rows_memory = |rows: &HashMap<Vec<u8>, Vec<usize>>| {
+        rows.iter()
+            .map(|(k, v)| k.len() + v.len() * std::mem::size_of::<usize>() + ENTRY_OVERHEAD)
+            .sum::<usize>()
+    };
+    match kind {
+        IndexKind::Exact { rows, .. } => rows_memory(rows),
+        IndexKind::Network { networks, .. } => networks
+            .values()
+            .map(|v| v.len() * std::mem::size_of::<usize>() + ENTRY_OVERHEAD)
+            .sum::<usize>(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use vrl::value::ObjectMap;
+
+    use super::*;
+
+    fn row(pairs: &[(&str, Value)]) -> Value {
+        Value::Object(
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string().into(), v.clone()))
+                .collect::<ObjectMap>(),
+        )
+    }
+
+    fn data() -> Vec<Value> {
+        vec![
+            row(&[
+                ("host", "web-1".into()),
+                ("env", "prod".into()),
+                ("subnet", "10.0.0.0/8".into()),
+                ("created", "2024-01-01T00:00:00Z".into()),
+            ]),
+            row(&[
+                ("host", "Web-1".into()),
+                ("env", "dev".into()),
+                ("subnet", "10.1.0.0/16".into()),
+                ("created", "2024-02-01T00:00:00Z".into()),
+            ]),
+            row(&[
+                ("host", "db-1".into()),
+                ("env", "prod".into()),
+                ("subnet", "192.168.1.1".into()),
+                ("created", "2024-03-01T00:00:00Z".into()),
+            ]),
+            row(&[("host", Value::Integer(1))]),
+        ]
+    }
+
+    fn equals<'a>(field: &'a str, value: Value) -> Condition<'a> {
+        Condition::Equals { field, value }
+    }
+
+    #[test]
+    fn test_exact_index() {
+        let data = data();
+        let indexes = TableIndexes::new("org", "assets");
+        let handle = indexes.add_index(&data, Case::Sensitive, &["host", "env"]);
+        assert_eq!(
+            indexes.add_index(&data, Case::Sensitive, &["host", "env"]),
+            handle
+        );
+        let find = |conditions: &[Condition]| {
+            indexes.find_rows(&data, Case::Sensitive, conditions, Some(handle))
+        };
+        assert_eq!(
+            find(&[equals("host", "web-1".into()), equals("env", "prod".into())]),
+            Some(vec![0])
+        );
+        assert_eq!(
+            find(&[equals("host", "web-2".into()), equals("env", "prod".into())]),
+            Some(vec![])
+        );
+        // a condition of the index is missing
+        assert_eq!(find(&[equals("host", "web-1".into())]), None);
+
+        let handle = indexes.add_index(&data, Case::Insensitive, &["host"]);
+        assert_eq!(
+            indexes.find_rows(
+                &data,
+                Case::Insensitive,
+                &[equals("host", "WEB-1".into())],
+                Some(handle)
+            ),
+            Some(vec![0, 1])
+        );
+        assert_eq!(indexes.index_fields().len(), 2);
+
+        let handle = indexes.add_index(&data, Case::Sensitive, &["host"]);
+        assert_eq!(
+            indexes.find_rows(
+                &data,
+                Case::Sensitive,
+                &[equals("host", Value::Integer(1))],
+                Some(handle)
+            ),
+            Some(vec![3])
+        );
+    }
+
+    #[test]
+    fn test_network_index() {
+        let data = data();
+        let indexes = TableIndexes::new("org", "assets");
+        let handle = indexes.add_index(&data, Case::Sensitive, &["subnet|contains"]);
+        let find = |ip: &str| {
+            indexes.find_rows(
+                &data,
+                Case::Sensitive,
+                &[equals("subnet|contains", ip.into())],
+                Some(handle),
+            )
+        };
+        assert_eq!(find("10.1.2.3"), Some(vec![0, 1]));
+        assert_eq!(find("10.2.0.1"), Some(vec![0]));
+        assert_eq!(find("192.168.1.1"), Some(vec![2]));
+        assert_eq!(find("172.16.0.1"), Some(vec![]));
+        assert_eq!(find("::1"), Some(vec![]));
+
+        // a plain equals on the network column is an exact match
+        let handle = indexes.add_index(&data, Case::Sensitive, &["subnet"]);
+        assert_eq!(
+            indexes.find_rows(
+                &data,
+                Case::Sensitive,
+                &[equals("subnet", "10.1.2.3".into())],
+                Some(handle)
+            ),
+            Some(vec![])
+        );
+        assert_eq!(
+            indexes.find_rows(
+                &data,
+                Case::Sensitive,
+                &[equals("subnet", "10.0.0.0/8".into())],
+                Some(handle)
+            ),
+            Some(vec![0])
+        );
+    }
+
+    #[test]
+    fn test_date_index() {
+        let data = data();
+        let indexes = TableIndexes::new("org", "assets");
+        let conditions = [Condition::BetweenDates {
+            field: "created",
+            from: Utc.with_ymd_and_hms(2024, 1, 15, 0, 0, 0).unwrap(),
+            to: Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap(),
+        }];
+        assert_eq!(
+            indexes.find_rows(&data, Case::Sensitive, &conditions, None),
+            Some(vec![1, 2])
+        );
+        assert_eq!(
+            indexes.find_rows(
+                &data,
+                Case::Sensitive,
+                &[equals("host", "db-1".into())],
+                None
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_network_bits() {
+        let ip = "10.1.2.3".parse().unwrap();
+        assert_eq!(network_bits(ip, 8), 10 << 24);
+        assert_eq!(
+            network_bits(ip, 32),
+            u32::from_be_bytes([10, 1, 2, 3]) as u128
+        );
+        assert_eq!(network_bits(ip, 0), 0);
+        let ip = "2001:db8::1".parse().unwrap();
+        assert_eq!(network_bits(ip, 128), 0x2001_0db8 << 96 | 1);
+        assert_eq!(network_bits(ip, 32), 0x2001_0db8 << 96);
+    }
+}
@@ -13,11 +13,17 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use config::utils::time::parse_str_to_time;
 use vector_enrichment::{Case, IndexHandle, Table};
 use vrl::value::{ObjectMap, Value};
 
+use self::index::TableIndexes;
+
+pub mod index;
+
 #[derive(Clone)]
 pub struct StreamTableConfig {}
 
@@ -25,9 +31,20 @@ pub struct StreamTableConfig {}
 pub struct StreamTable {
     pub org_id: String,
     pub stream_name: String,
-    pub data: Vec<vrl::value::Value>,
+    pub data: Arc<Vec<vrl::value::Value>>,
+    indexes: Arc<TableIndexes>,
+}
+
+impl StreamTable {
+    pub fn new(org_id: &str, stream_name: &str, data: Vec<vrl::value::Value>) -> Self {
+        Self {
+            org_id: org_id.to_string(),
+            stream_name: stream_name.to_string(),
+            data: Arc::new(data),
+            indexes: Arc::new(TableIndexes::new(org_id, stream_name)),
+        }
+    }
 }
-impl StreamTable {}
 
 #[async_trait]
 impl Table for StreamTable {
@@ -36,9 +53,9 @@ impl Table for StreamTable {
         case: vector_enrichment::Case,
         conditions: &[vector_enrichment::Condition],
         select: Option<&[String]>,
-        _index: Option<vector_enrichment::IndexHandle>,
+        index: Option<vector_enrichment::IndexHandle>,
     ) -> Result<ObjectMap, String> {
-        let resp = get_data(self, conditions, select, case);
+        let resp = get_data(self, conditions, select, case, index, Some(1));
         let record = if resp.is_empty() {
             ObjectMap::new()
         } else {
@@ -53,22 +70,22 @@ impl Table for StreamTable {
         case: vector_enrichment::Case,
         conditions: &[vector_enrichment::Condition],
         select: Option<&[String]>,
-        _index: Option<vector_enrichment::IndexHandle>,
+        index: Option<vector_enrichment::IndexHandle>,
     ) -> Result<Vec<ObjectMap>, String> {
-        let resp = get_data(self, conditions, select, case);
+        let resp = get_data(self, conditions, select, case, index, None);
         Ok(resp)
     }
 
     fn add_index(
         &mut self,
-        _case: vector_enrichment::Case,
-        _fields: &[&str],
+        case: vector_enrichment::Case,
+        fields: &[&str],
     ) -> Result<vector_enrichment::IndexHandle, String> {
-        Ok(IndexHandle(1))
+        Ok(self.indexes.add_index(&self.data, case, fields))
     }
 
     fn index_fields(&self) -> Vec<(vector_enrichment::Case, Vec<String>)> {
-        Vec::new()
+        self.indexes.index_fields()
     }
 
     fn needs_reload(&self) -> bool {
@@ -81,66 +98,35 @@ fn get_data(
     condition: &[vector_enrichment::Condition],
     select: Option<&[String]>,
     case: vector_enrichment::Case,
+    index: Option<IndexHandle>,
+    limit: Option<usize>,
 ) -> Vec<ObjectMap> {
     let mut resp = vec![];
-    let filtered: Vec<&vrl::value::Value> = table
-        .data
-        .iter()
-        .filter(|v| {
-            if let vrl::value::Value::Object(map) = v {
-                // Default to false for empty conditions array
-                if condition.is_empty() {
-                    return false;
-                }
-
-                // Check that ALL conditions match (AND logic)
-                condition.iter().all(|cond| match cond {
-                    vector_enrichment::Condition::Equals { field, value } => match case {
-                        Case::Insensitive => {
-                            if let Some(Value::Bytes(bytes1)) = map.get(field.to_owned()) {
-                                if let Value::Bytes(bytes2) = value {
-                                    match (std::str::from_utf8(bytes1), std::str::from_utf8(bytes2))
-                                    {
-                                        (Ok(s1), Ok(s2)) => s1.eq_ignore_ascii_case(s2),
-                                        (Err(_), Err(_)) => bytes1 == bytes2,
-                                        _ => false,
-                                    }
-                                } else {
-                                    false
-                                }
-                            } else {
-                                false
-                            }
-                        }
-                        Case::Sensitive => {
-                            if let Some(v) = map.get(field.to_owned()) {
-                                v.clone() == value.clone()
-                            } else {
-                                false
-                            }
-                        }
-                    },
-                    vector_enrichment::Condition::BetweenDates { field, from, to } => {
-                        if let Some(v) = map.get(field.to_owned()) {
-                            if let Some(v) = v.as_str() {
-                                if let Ok(v) = parse_str_to_time(&v) {
-                                    v >= *from && v <= *to
-                                } else {
-                                    false
-                                }
-                            } else {
-                                false
-                            }
-                        } else {
-                            false
-                        }
-                    }
-                })
-            } else {
-                false
-            }
-        })
-        .collect();
+    // Default to false for empty conditions array
+    if condition.is_empty() {
+        return resp;
+    }
+    let is_match = |v: &&vrl::value::Value| {
+        if let vrl::value::Value::Object(map) = v {
+            // Check that ALL conditions match (AND logic)
+            condition
+                .iter()
+                .all(|cond| match_condition(map, cond, case))
+        } else {
+            false
+        }
+    };
+    let limit = limit.unwrap_or(usize::MAX);
+    let filtered: Vec<&vrl::value::Value> =
+        match table.indexes.find_rows(&table.data, case, condition, index) {
+            Some(rows) => rows
+                .into_iter()
+                .filter_map(|i| table.data.get(i))
+                .filter(is_match)
+                .take(limit)
+                .collect(),
+            None => table.data.iter().filter(is_match).take(limit).collect(),
+        };
 
     match select {
         Some(val) => {
@@ -171,3 +157,45 @@ fn get_data(
 
     resp
 }
+
+fn match_condition(map: &ObjectMap, cond: &vector_enrichment::Condition, case: Case) -> bool {
+    match cond {
+        vector_enrichment::Condition::Equals { field, value } => {
+            if let Some(column) = index::contains_field(field) {
+                // the network of the row, like 10.0.0.0/8, contains the address
+                return map
+                    .get(column)
+                    .and_then(index::parse_network)
+                    .zip(value.as_str().and_then(|ip| ip.parse().ok()))
+                    .is_some_and(|(network, ip)| network.contains(ip));
+            }
+            let Some(v) = map.get(field.to_owned()) else {
+                return false;
+            };
+            match case {
+                Case::Insensitive => {
+                    if let (Value::Bytes(bytes1), Value::Bytes(bytes2)) = (v, value) {
+                        match (std::str::from_utf8(bytes1), std::str::from_utf8(bytes2)) {
+                            (Ok(s1), Ok(s2)) => s1.eq_ignore_ascii_case(s2),
+                            (Err(_), Err(_)) => bytes1 == bytes2,
+                            _ => false,
+                        }
+                    } else {
+                        false
+                    }
+                }
+                Case::Sensitive => v == value,
+            }
+        }
+        vector_enrichment::Condition::BetweenDates { field, from, to } => {
+            if let Some(v) = map.get(field.to_owned())
+                && let Some(v) = v.as_str()
+                && let Ok(v) = parse_str_to_time(&v)
+            {
+                v >= *from && v <= *to
+            } else {
+                false
+            }
+        }
+    }
+}
@@ -54,6 +54,18 @@ use crate::{
 };
 
 pub mod geoip;
+pub mod source;
+
+/// How a save treats the existing rows of the table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SaveMode {
+    Append,
+    /// Deletes the table before writing the new rows.
+    Replace,
+    /// Writes the new rows next to the existing ones and then moves the start
+    /// time of the table to them, so the table never has partial data.
+    Swap,
+}
 
 pub async fn save_enrichment_data(
     org_id: &str,
@@ -61,6 +73,31 @@ pub async fn save_enrichment_data(
     payload: Vec<json::Map<String, json::Value>>,
     append_data: bool,
 ) -> Result<HttpResponse, Error> {
+    let mode = if append_data {
+        SaveMode::Append
+    } else {
+        SaveMode::Replace
+    };
+    save_data(org_id, table_name, payload, mode).await
+}
+
+/// Replaces the rows of the table once all the new rows are written, the
+/// table keeps the previous rows if the save fails.
+pub async fn swap_enrichment_data(
+    org_id: &str,
+    table_name: &str,
+    payload: Vec<json::Map<String, json::Value>>,
+) -> Result<HttpResponse, Error> {
+    save_data(org_id, table_name, payload, SaveMode::Swap).await
+}
+
+async fn save_data(
+    org_id: &str,
+    table_name: &str,
+    payload: Vec<json::Map<String, json::Value>>,
+    mode: SaveMode,
+) -> Result<HttpResponse, Error> {
+    let append_data = mode == SaveMode::Append;
     let start = std::time::Instant::now();
     let started_at = Utc::now().timestamp_micros();
     let cfg = get_config();
@@ -152,7 +189,7 @@ pub async fn save_enrichment_data(
     )
     .await;
 
-    if stream_schema.has_fields && !append_data {
+    if stream_schema.has_fields && mode == SaveMode::Replace {
         delete_enrichment_table(org_id, stream_name, StreamType::EnrichmentTables).await;
         stream_schema_map.remove(stream_name);
     }
@@ -162,8 +199,9 @@ pub async fn save_enrichment_data(
     let timestamp = Utc::now().timestamp_micros();
     for mut json_record in payload {
         let timestamp = match json_record.get(TIMESTAMP_COL_NAME) {
-            Some(v) => v.as_i64().unwrap_or(timestamp),
-            None => timestamp,
+            // the rows of a swap are only visible after its start time
+            Some(v) if mode != SaveMode::Swap => v.as_i64().unwrap_or(timestamp),
+            _ => timestamp,
         };
         json_record.insert(
             TIMESTAMP_COL_NAME.to_string(),
@@ -253,6 +291,7 @@ pub async fn save_enrichment_data(
     let mut enrich_meta_stats = db::enrichment_table::get_meta_table_stats(org_id, stream_name)
         .await
         .unwrap_or_default();
+    let previous_start_time = enrich_meta_stats.start_time;
 
     if !append_data {
         enrich_meta_stats.start_time = started_at;
@@ -265,8 +304,23 @@ pub async fn save_enrichment_data(
     enrich_meta_stats.size = total_expected_size_in_bytes as i64;
     // The stream_stats table takes some time to update, so we need to update the enrichment table
     // size in the meta table to avoid exceeding the `ZO_ENRICHMENT_TABLE_LIMIT`.
-    let _ =
+    let ret =
         db::enrichment_table::update_meta_table_stats(org_id, stream_name, enrich_meta_stats).await;
+    if mode == SaveMode::Swap {
+        // the start time is the switch to the new rows
+        if let Err(e) = ret {
+            return Ok(HttpResponse::InternalServerError()
+                .append_header((
+                    ERROR_HEADER,
+                    format!("Error swapping enrichment table: {e}"),
+                ))
+                .json(MetaHttpResponse::error(
+                    http::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Error swapping enrichment table: {e}"),
+                )));
+        }
+        delete_previous_rows(org_id, stream_name, previous_start_time, started_at).await;
+    }
 
     // notify update
     if !schema.fields().is_empty()
@@ -302,6 +356,39 @@ pub async fn save_enrichment_data(
     )))
 }
 
+/// Deletes the days of the rows which a swap replaced, the rows of the day of
+/// the swap are only hidden by the start time of the table.
+async fn delete_previous_rows(org_id: &str, stream_name: &str, from: i64, to: i64) {
+    if from <= 0 {
+        return;
+    }
+    let (Some(from), Some(to)) = (
+        chrono::DateTime::from_timestamp_micros(from),
+        chrono::DateTime::from_timestamp_micros(to),
+    ) else {
+        return;
+    };
+    let (from, to) = (
+        from.format("%Y-%m-%d").to_string(),
+        to.format("%Y-%m-%d").to_string(),
+    );
+    if from >= to {
+        return;
+    }
+    if let Err(e) = db::compact::retention::delete_stream(
+        org_id,
+        StreamType::EnrichmentTables,
+        stream_name,
+        Some((&from, &to)),
+    )
+    .await
+    {
+        log::error!(
+            "Error deleting the previous rows of enrichment table {org_id}/{stream_name}: {e}"
+        );
+    }
+}
+
 async fn delete_enrichment_table(org_id: &str, stream_name: &str, stream_type: StreamType) {
     log::info!("deleting enrichment table  {stream_name}");
     // delete stream schema
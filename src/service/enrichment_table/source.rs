@@ -0,0 +1,572 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Enrichment tables loaded from an external source.
+//!
+//! A refresh downloads and validates the whole data, writes it next to the
+//! current rows and only then switches the table to it, so a failed refresh
+//! keeps the previous data. The scheduled refreshes of a table run on the
+//! ingester which owns it on the consistent hash ring.
+//!
+//! The url sources can't reach private, loopback or link local addresses
+//! unless the host is in `ZO_ENRICHMENT_TABLE_SOURCE_ALLOWED_HOSTS`, and the
+//! object storage sources only read the files of their organization.
+
+use std::{
+    collections::{HashMap, HashSet},
+    net::{IpAddr, SocketAddr},
+};
+
+use actix_web::http::StatusCode;
+use bytes::{Bytes, BytesMut};
+use config::{
+    SIZE_IN_MB,
+    cluster::LOCAL_NODE,
+    get_config,
+    meta::{
+        cluster::Role,
+        enrichment_table::{
+            EnrichmentTableSource, EnrichmentTableSourceStatus, RefreshStatus, SourceFormat,
+            SourceKind,
+        },
+        grant::{GrantLevel, GrantResource},
+        search::{Query, Request},
+        sql::resolve_stream_names,
+    },
+    utils::{
+        arrow::record_batches_to_json_rows,
+        flatten::{self, format_key},
+        json,
+        time::now_micros,
+    },
+};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+use crate::{
+    common::{infra::cluster, utils::auth::is_root_user},
+    service::{db, grants, search as SearchService, users},
+};
+
+/// The tables which are being refreshed, as `org_id/name`.
+static REFRESHING: Lazy<Mutex<HashSet<String>>> = Lazy::new(Default::default);
+
+/// Refreshes the sources which are due and owned by this ingester.
+pub async fn run_scheduled_refresh() -> Result<(), anyhow::Error> {
+    let now = now_micros();
+    for (org_id, source) in db::enrichment_table::list_sources().await? {
+        if source.refresh_interval == 0 {
+            continue;
+        }
+        let owner = cluster::get_node_from_consistent_hash(
+            &format!("{org_id}/{}", source.name),
+            &Role::Ingester,
+            None,
+        )
+        .await;
+        if owner.is_some_and(|name| name != LOCAL_NODE.name) {
+            continue;
+        }
+        let status = db::enrichment_table::get_source_status(&org_id, &source.name).await;
+        if now - status.last_refresh_at < source.refresh_interval as i64 * 1_000_000 {
+            continue;
+        }
+        refresh(&org_id, &source).await;
+    }
+    Ok(())
+}
+
+/// Loads the source into the table and saves the status of the refresh.
+pub async fn refresh(org_id: &str, source: &EnrichmentTableSource) -> EnrichmentTableSourceStatus {
+    let key = format!("{org_id}/{}", source.name);
+    if !REFRESHING.lock().insert(key.clone()) {
+        let mut status = db::enrichment_table::get_source_status(org_id, &source.name).await;
+        status.error = Some("a refresh of the table is already running".to_string());
+        return status;
+    }
+    let start = std::time::Instant::now();
+    let mut status = db::enrichment_table::get_source_status(org_id, &source.name).await;
+    status.last_refresh_at = now_micros();
+    match load(org_id, source).await {
+        Ok(rows) => {
+            status.status = RefreshStatus::Success;
+            status.last_success_at = status.last_refresh_at;
+            status.rows = rows;
+            status.error = None;
+        }
+        Err(e) => {
+            log::error!(
+                "[ENRICHMENT_TABLE] org: {org_id}, table: {} refresh error: {e}",
+                source.name
+            );
+            status.status = RefreshStatus::Failed;
+            status.error = Some(e.to_string());
+        }
+    }
+    status.took = start.elapsed().as_millis() as usize;
+    if let Err(e) = db::enrichment_table::set_source_status(org_id, &source.name, &status).await {
+        log::error!(
+            "[ENRICHMENT_TABLE] org: {org_id}, table: {} save refresh status error: {e}",
+            source.name
+        );
+    }
+    REFRESHING.lock().remove(&key);
+    status
+}
+
+async fn load(org_id: &str, source: &EnrichmentTableSource) -> Result<usize, anyhow::Error> {
+    // the sources saved before a check was added
+    source.validate().map_err(|e| anyhow::anyhow!(e))?;
+    let records = match &source.source {
+        SourceKind::Query {
+            sql,
+            stream_type,
+            lookback,
+        } => {
+            // the query runs with the access of the user who saved the source
+            let user_id = &source.updated_by;
+            if user_id.is_empty() {
+                anyhow::bail!("source has no owner, save it again to refresh it");
+            }
+            if !is_root_user(user_id) && users::get_user(Some(org_id), user_id).await.is_none() {
+                anyhow::bail!(
+                    "user {user_id} who saved the source is not a member of the organization"
+                );
+            }
+            for stream_name in resolve_stream_names(sql)? {
+                if !grants::is_allowed(
+                    org_id,
+                    user_id,
+                    GrantResource::Stream,
+                    Some(*stream_type),
+                    &stream_name,
+                    GrantLevel::Read,
+                )
+                .await
+                {
+                    anyhow::bail!(
+                        "user {user_id} who saved the source can't read stream {stream_name}"
+                    );
+                }
+            }
+            let end_time = now_micros();
+            let req = Request {
+                query: Query {
+                    sql: sql.clone(),
+                    from: 0,
+                    size: -1,
+                    start_time: end_time - lookback * 1_000_000,
+                    end_time,
+                    quick_mode: false,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let mut resp =
+                SearchService::search("", org_id, *stream_type, Some(user_id.to_string()), &req)
+                    .await?;
+            // the table is readable by everyone who can read enrichment tables
+            SearchService::masking::mask_response(
+                org_id,
+                *stream_type,
+                sql,
+                Some(user_id),
+                &mut resp,
+            )
+            .await;
+            resp.hits
+                .into_iter()
+                .filter_map(|hit| match hit {
+                    json::Value::Object(record) => Some(record),
+                    _ => None,
+                })
+                .collect()
+        }
+        _ => {
+            let data = download(org_id, &source.source).await?;
+            let format = source
+                .get_format()
+                .ok_or_else(|| anyhow::anyhow!("unknown format of the source"))?;
+            parse(format, data)?
+        }
+    };
+    let records = normalize(records)?;
+    validate(source, &records)?;
+    let rows = records.len();
+
+    let resp = super::swap_enrichment_data(org_id, &source.name, records).await?;
+    if resp.status() != StatusCode::OK {
+        let body = actix_web::body::to_bytes(resp.into_body())
+            .await
+            .unwrap_or_default();
+        anyhow::bail!("save data error: {}", String::from_utf8_lossy(&body));
+    }
+    Ok(rows)
+}
+
+async fn download(org_id: &str, source: &SourceKind) -> Result<Bytes, anyhow::Error> {
+    let cfg = get_config();
+    let max_size = cfg.limit.enrichment_table_max_size * SIZE_IN_MB as usize;
+    let data = match source {
+        SourceKind::Url { url, headers } => {
+            let allowed_hosts = cfg
+                .limit
+                .enrichment_table_source_allowed_hosts
+                .split(',')
+                .map(|h| h.trim())
+                .filter(|h| !h.is_empty())
+                .collect::<Vec<_>>();
+            download_url(url, headers, &allowed_hosts, max_size).await?
+        }
+        SourceKind::ObjectStorage { path } => {
+            infra::storage::get_bytes("", &format!("enrichment_tables/{org_id}/{path}")).await?
+        }
+        SourceKind::Query { .. } => anyhow::bail!("query source can't be downloaded"),
+    };
+    if data.len() > max_size {
+        anyhow::bail!("source exceeds the enrichment table limit");
+    }
+    Ok(data)
+}
+
+async fn download_url(
+    url: &str,
+    headers: &HashMap<String, String>,
+    allowed_hosts: &[&str],
+    max_size: usize,
+) -> Result<Bytes, anyhow::Error> {
+    let parsed = url::Url::parse(url)?;
+    let host = parsed
+        .host()
+        .ok_or_else(|| anyhow::anyhow!("url {url} has no host"))?;
+    let port = parsed.port_or_known_default().unwrap_or(80);
+    let mut builder = reqwest::ClientBuilder::new()
+        .connect_timeout(std::time::Duration::from_secs(
+            get_config().limit.http_request_timeout,
+        ))
+        // a redirect could point to an address which isn't checked
+        .redirect(reqwest::redirect::Policy::none());
+    let host_name = host.to_string();
+    if !allowed_hosts
+        .iter()
+        .any(|h| h.eq_ignore_ascii_case(host_name.trim_matches(['[', ']'])))
+    {
+        let addrs = match host {
+            url::Host::Domain(domain) => {
+                let addrs = tokio::net::lookup_host((domain, port))
+                    .await?
+                    .collect::<Vec<_>>();
+                // connect to the checked addresses only
+                builder = builder.resolve_to_addrs(domain, &addrs);
+                addrs
+            }
+            url::Host::Ipv4(ip) => vec![SocketAddr::new(ip.into(), port)],
+            url::Host::Ipv6(ip) => vec![SocketAddr::new(ip.into(), port)],
+        };
+        if addrs.is_empty() {
+            anyhow::bail!("host {host_name} has no address");
+        }
+        if let Some(addr) = addrs.iter().find(|addr| !is_public_ip(addr.ip())) {
+            anyhow::bail!(
+                "host {host_name} resolves to the non public address {}, add the host to ZO_ENRICHMENT_TABLE_SOURCE_ALLOWED_HOSTS to allow it",
+                addr.ip()
+            );
+        }
+    }
+    let client = builder.build()?;
+    let mut req = client.get(url);
+    for (name, value) in headers {
+        req = req.header(name, value);
+    }
+    let mut resp = req.send().await?;
+    if !resp.status().is_success() {
+        anyhow::bail!("download {url} error: status {}", resp.status());
+    }
+    if resp
+        .content_length()
+        .is_some_and(|len| len as usize > max_size)
+    {
+        anyhow::bail!("source exceeds the enrichment table limit");
+    }
+    // the content length is only a hint, the body is capped while reading it
+    let mut data = BytesMut::new();
+    while let Some(chunk) = resp.chunk().await? {
+        if data.len() + chunk.len() > max_size {
+            anyhow::bail!("source exceeds the enrichment table limit");
+        }
+        data.extend_from_slice(&chunk);
+    }
+    Ok(data.freeze())
+}
+
+/// Whether the address is reachable on the internet, the private, loopback,
+/// link local and other special ranges are not.
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || a == 0
+                // shared address space 100.64.0.0/10
+                || (a == 100 && b & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_ip(ip.into()),
+            None => {
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+fn parse(
+    format: SourceFormat,
+    data: Bytes,
+) -> Result<Vec<json::Map<String, json::Value>>, anyhow::Error> {
+    let objects = |values: Vec<json::Value>| {
+        values
+            .into_iter()
+            .map(|v| match v {
+                json::Value::Object(record) => Ok(record),
+                _ => Err(anyhow::anyhow!("every row must be a JSON object")),
+            })
+            .collect::<Result<Vec<_>, _>>()
+    };
+    match format {
+        SourceFormat::Json => match json::from_slice::<json::Value>(&data)? {
+            json::Value::Array(values) => objects(values),
+            json::Value::Object(record) => Ok(vec![record]),
+            _ => anyhow::bail!("JSON source must be an array of objects"),
+        },
+        SourceFormat::Ndjson => objects(
+            data.split(|b| *b == b'\n')
+                .filter(|line| !line.trim_ascii().is_empty())
+                .map(json::from_slice::<json::Value>)
+                .collect::<Result<Vec<_>, _>>()?,
+        ),
+        SourceFormat::Csv => {
+            let mut rdr = csv::Reader::from_reader(data.as_ref());
+            let headers = rdr.headers()?.clone();
+            let mut records = Vec::new();
+            for record in rdr.records() {
+                let record = record?;
+                records.push(
+                    headers
+                        .iter()
+                        .zip(record.iter())
+                        .map(|(header, field)| {
+                            (header.trim().to_string(), json::Value::String(field.into()))
+                        })
+                        .collect(),
+                );
+            }
+            Ok(records)
+        }
+        SourceFormat::Parquet => {
+            let reader = ParquetRecordBatchReaderBuilder::try_new(data)?.build()?;
+            let batches = reader.collect::<Result<Vec<_>, _>>()?;
+            record_batches_to_json_rows(&batches.iter().collect::<Vec<_>>())
+        }
+    }
+}
+
+/// Flattens the nested values, the columns of the table are the flattened
+/// keys.
+fn normalize(
+    records: Vec<json::Map<String, json::Value>>,
+) -> Result<Vec<json::Map<String, json::Value>>, anyhow::Error> {
+    records
+        .into_iter()
+        .map(
+            |record| match flatten::flatten(json::Value::Object(record))? {
+                json::Value::Object(record) => Ok(record),
+                _ => Err(anyhow::anyhow!("every row must be a JSON object")),
+            },
+        )
+        .collect()
+}
+
+fn validate(
+    source: &EnrichmentTableSource,
+    records: &[json::Map<String, json::Value>],
+) -> Result<(), anyhow::Error> {
+    if records.is_empty() {
+        anyhow::bail!("source has no rows");
+    }
+    for column in source.required_columns.iter() {
+        let mut column = column.trim().to_string();
+        format_key(&mut column);
+        if let Some(pos) = records
+            .iter()
+            .position(|r| r.get(&column).is_none_or(|v| v.is_null()))
+        {
+            anyhow::bail!("row {} misses the required column {column}", pos + 1);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use arrow::{
+        array::{Int64Array, RecordBatch, StringArray},
+        datatypes::{DataType, Field, Schema},
+    };
+    use parquet::arrow::ArrowWriter;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+
+    fn source(required_columns: &[&str]) -> EnrichmentTableSource {
+        EnrichmentTableSource {
+            name: "assets".to_string(),
+            source: SourceKind::ObjectStorage {
+                path: "assets.csv".to_string(),
+            },
+            format: None,
+            refresh_interval: 3600,
+            required_columns: required_columns.iter().map(|c| c.to_string()).collect(),
+            created_at: 0,
+            updated_at: 0,
+            updated_by: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let csv = Bytes::from("host,owner\nweb-1,team-a\ndb-1,team-b\n");
+        let records = parse(SourceFormat::Csv, csv).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1]["owner"], "team-b");
+
+        let data = Bytes::from(r#"[{"host":"web-1","port":80},{"host":"db-1","port":5432}]"#);
+        let records = parse(SourceFormat::Json, data).unwrap();
+        assert_eq!(records[1]["port"], 5432);
+
+        let data = Bytes::from("{\"host\":\"web-1\"}\n\n{\"host\":\"db-1\"}\n");
+        assert_eq!(parse(SourceFormat::Ndjson, data).unwrap().len(), 2);
+        assert!(parse(SourceFormat::Ndjson, Bytes::from("[1]\n")).is_err());
+        assert!(parse(SourceFormat::Json, Bytes::from("\"x\"")).is_err());
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("host", DataType::Utf8, false),
+            Field::new("port", DataType::Int64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec!["web-1", "db-1"])),
+                Arc::new(Int64Array::from(vec![80, 5432])),
+            ],
+        )
+        .unwrap();
+        let mut buf = Vec::new();
+        let mut writer = ArrowWriter::try_new(&mut buf, schema, None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        let records = parse(SourceFormat::Parquet, Bytes::from(buf)).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["host"], "web-1");
+        assert_eq!(records[1]["port"], 5432);
+    }
+
+    #[test]
+    fn test_normalize_and_validate() {
+        let records = normalize(vec![
+            json::json!({"Host": "web-1", "owner": {"team": "a"}})
+                .as_object()
+                .unwrap()
+                .clone(),
+        ])
+        .unwrap();
+        assert_eq!(records[0]["owner_team"], "a");
+        assert_eq!(records[0]["host"], "web-1");
+        assert!(validate(&source(&["Host", "owner_team"]), &records).is_ok());
+        assert!(validate(&source(&["ip"]), &records).is_err());
+        assert!(validate(&source(&[]), &[]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_download_url() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0; 1024];
+                let _ = socket.read(&mut buf).await.unwrap();
+                let body = "host,owner\nweb-1,team-a\n";
+                let resp = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: text/csv\r\ncontent-length: {}\r\n\r\n{body}",
+                    body.len()
+                );
+                socket.write_all(resp.as_bytes()).await.unwrap();
+            }
+        });
+        let url = format!("http://{addr}/assets.csv");
+        let headers = HashMap::new();
+        let data = download_url(&url, &headers, &["127.0.0.1"], SIZE_IN_MB as usize)
+            .await
+            .unwrap();
+        let records = parse(SourceFormat::Csv, data).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0]["owner"], "team-a");
+
+        // loopback isn't allowed by default
+        assert!(
+            download_url(&url, &headers, &[], SIZE_IN_MB as usize)
+                .await
+                .is_err()
+        );
+        // over the size limit
+        assert!(
+            download_url(&url, &headers, &["127.0.0.1"], 10)
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_is_public_ip() {
+        for (ip, public) in [
+            ("8.8.8.8", true),
+            ("10.1.2.3", false),
+            ("127.0.0.1", false),
+            ("169.254.169.254", false),
+            ("100.64.0.1", false),
+            ("0.0.0.0", false),
+            ("2001:4860:4860::8888", true),
+            ("::1", false),
+            ("fe80::1", false),
+            ("fd00::1", false),
+            ("::ffff:169.254.169.254", false),
+        ] {
+            assert_eq!(is_public_ip(ip.parse().unwrap()), public, "{ip}");
+        }
+    }
+}
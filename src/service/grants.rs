@@ -0,0 +1,353 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+
+use actix_web::{http::Method, web};
+use config::{
+    ider,
+    meta::{
+        alerts::{QueryCondition, QueryType},
+        grant::{self, Grant, GrantLevel, GrantResource},
+        sql::resolve_stream_names,
+        stream::StreamType,
+        user::UserRole,
+    },
+    utils::schema::format_stream_name,
+};
+
+use crate::{
+    common::{
+        infra::config::{GRANT_RESTRICTIONS, GRANTS},
+        meta::ingestion::INGESTION_EP,
+        utils::auth::{V2_API_PREFIX, is_root_user},
+    },
+    service::{db, users},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum GrantError {
+    #[error("Invalid grant: {0}")]
+    InvalidGrant(String),
+    #[error("Grant with ID {0} not found")]
+    NotFound(String),
+    #[error("Forbidden to access stream: {0}")]
+    ForbiddenStream(String),
+    #[error("{0}")]
+    InfraError(#[from] anyhow::Error),
+}
+
+pub async fn list(org_id: &str) -> Result<Vec<Grant>, GrantError> {
+    Ok(db::grants::list(org_id).await?)
+}
+
+pub async fn save(org_id: &str, mut grant: Grant) -> Result<Grant, GrantError> {
+    grant.validate().map_err(GrantError::InvalidGrant)?;
+    grant.subject = grant.subject.trim().to_lowercase();
+    grant.pattern = grant.pattern.trim().to_string();
+    if users::get_user(Some(org_id), &grant.subject)
+        .await
+        .is_none()
+    {
+        return Err(GrantError::InvalidGrant(format!(
+            "user {} is not a member of the organization",
+            grant.subject
+        )));
+    }
+    // the subject stays restricted on the resource after its last grant is
+    // deleted, so it's saved before the grant
+    let mut restrictions = get_restrictions(org_id, &grant.subject);
+    if !restrictions.contains(&grant.resource) {
+        restrictions.push(grant.resource);
+        db::grants::set_restrictions(org_id, &grant.subject, &restrictions).await?;
+        GRANT_RESTRICTIONS.insert(format!("{org_id}/{}", grant.subject), restrictions);
+    }
+    grant.id = ider::generate();
+    db::grants::set(org_id, &grant).await?;
+    Ok(grant)
+}
+
+pub async fn delete(org_id: &str, id: &str) -> Result<(), GrantError> {
+    if db::grants::get(org_id, id).await.is_err() {
+        return Err(GrantError::NotFound(id.to_string()));
+    }
+    db::grants::delete(org_id, id).await?;
+    Ok(())
+}
+
+/// Only root and the org admins manage the grants of an org.
+pub async fn can_manage(org_id: &str, user_email: &str) -> bool {
+    is_root_user(user_email)
+        || users::get_user(Some(org_id), user_email)
+            .await
+            .is_some_and(|user| matches!(user.role, UserRole::Admin | UserRole::Root))
+}
+
+/// Checks the access of a user to an object of the org. Root and org admins
+/// are never restricted by grants.
+pub async fn is_allowed(
+    org_id: &str,
+    user_email: &str,
+    resource: GrantResource,
+    stream_type: Option<StreamType>,
+    name: &str,
+    level: GrantLevel,
+) -> bool {
+    let grants = get_user_grants(org_id, user_email);
+    let restrictions = get_restrictions(org_id, user_email);
+    if (grants.is_empty() && restrictions.is_empty()) || can_manage(org_id, user_email).await {
+        return true;
+    }
+    grant::is_allowed(&grants, &restrictions, resource, stream_type, name, level)
+}
+
+/// Returns the names the user can access out of `names`.
+pub async fn filter_allowed(
+    org_id: &str,
+    user_email: &str,
+    resource: GrantResource,
+    stream_type: Option<StreamType>,
+    names: Vec<String>,
+    level: GrantLevel,
+) -> Vec<String> {
+    let mut allowed = Vec::with_capacity(names.len());
+    for name in names {
+        if is_allowed(org_id, user_email, resource, stream_type, &name, level).await {
+            allowed.push(name);
+        }
+    }
+    allowed
+}
+
+/// Checks that the user can ingest into all the streams, for ingestion whose
+/// streams are only known from the body.
+pub async fn check_ingest_streams<'a>(
+    org_id: &str,
+    user_email: &str,
+    stream_type: StreamType,
+    stream_names: impl IntoIterator<Item = &'a str>,
+) -> Result<(), GrantError> {
+    for stream_name in stream_names {
+        if !is_allowed(
+            org_id,
+            user_email,
+            GrantResource::Stream,
+            Some(stream_type),
+            stream_name,
+            GrantLevel::Ingest,
+        )
+        .await
+        {
+            return Err(GrantError::ForbiddenStream(stream_name.to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the user can read all the streams a scheduled query reads: the
+/// streams of its SQL, the metrics of its PromQL, or `stream_name` for custom
+/// conditions.
+pub async fn check_query_streams(
+    org_id: &str,
+    user_email: &str,
+    stream_type: StreamType,
+    stream_name: Option<&str>,
+    query: &QueryCondition,
+) -> Result<(), GrantError> {
+    let stream_names = match query.query_type {
+        QueryType::Custom => stream_name.map(String::from).into_iter().collect(),
+        QueryType::SQL => match query.sql.as_deref() {
+            Some(sql) if !sql.is_empty() => resolve_stream_names(sql)?,
+            _ => vec![],
+        },
+        QueryType::PromQL => match query.promql.as_deref() {
+            Some(promql) if !promql.is_empty() => {
+                let ast = promql_parser::parser::parse(promql).map_err(|e| anyhow::anyhow!(e))?;
+                let mut visitor = super::promql::name_visitor::MetricNameVisitor::default();
+                promql_parser::util::walk_expr(&mut visitor, &ast)
+                    .map_err(|e| anyhow::anyhow!(e))?;
+                visitor.name.into_iter().collect()
+            }
+            _ => vec![],
+        },
+    };
+    for stream_name in stream_names {
+        if !is_allowed(
+            org_id,
+            user_email,
+            GrantResource::Stream,
+            Some(stream_type),
+            &stream_name,
+            GrantLevel::Read,
+        )
+        .await
+        {
+            return Err(GrantError::ForbiddenStream(stream_name));
+        }
+    }
+    Ok(())
+}
+
+/// Checks a request against the grants of the user by the object in its path.
+/// `path` is the path after `/api/`. Requests whose objects are only known from
+/// the body, like searches and bulk ingestion, are checked by their handlers.
+/// `stream_header` is the stream name header that OTLP ingestion reads the
+/// stream from.
+pub async fn check_request(
+    user_email: &str,
+    method: &Method,
+    path: &str,
+    query_string: &str,
+    stream_header: Option<&str>,
+) -> bool {
+    let mut path_columns = path.split('/').collect::<Vec<_>>();
+    let is_v2 = path_columns.first() == Some(&V2_API_PREFIX);
+    if is_v2 {
+        path_columns.remove(0);
+    }
+    if path_columns.len() < 2 {
+        return true;
+    }
+    let org_id = path_columns[0];
+    if get_user_grants(org_id, user_email).is_empty()
+        && get_restrictions(org_id, user_email).is_empty()
+    {
+        return true;
+    }
+    let query = web::Query::<HashMap<String, String>>::from_query(query_string)
+        .map(|q| q.into_inner())
+        .unwrap_or_default();
+    let level = if method == Method::GET {
+        GrantLevel::Read
+    } else {
+        GrantLevel::Admin
+    };
+    let stream_type = query.get("type").map(|t| StreamType::from(t.as_str()));
+    let (resource, name, level) = match (path_columns[1], path_columns.get(2)) {
+        ("streams", Some(stream_name)) => (GrantResource::Stream, stream_name.to_string(), level),
+        ("dashboards", _) => {
+            // searching without a folder goes through all the folders
+            let folder = match query.get("folder") {
+                Some(folder) => folder.to_string(),
+                None if query.is_empty() => "default".to_string(),
+                None => "*".to_string(),
+            };
+            (GrantResource::DashboardFolder, folder, level)
+        }
+        ("folders", Some(&"dashboards")) if is_v2 => match path_columns.get(3) {
+            Some(folder_id) if *folder_id != "name" => {
+                (GrantResource::DashboardFolder, folder_id.to_string(), level)
+            }
+            _ => return true,
+        },
+        ("folders", Some(folder_id))
+            if !is_v2 && *folder_id != "name" && *folder_id != "dashboards" =>
+        {
+            (GrantResource::DashboardFolder, folder_id.to_string(), level)
+        }
+        ("alerts", Some(alert_id)) if is_v2 && *alert_id != "move" => {
+            let Ok(alert_id) = alert_id.parse() else {
+                return true;
+            };
+            let Ok(alert) = super::alerts::alert::get_by_id_db(org_id, alert_id).await else {
+                return true;
+            };
+            (GrantResource::Alert, alert.name, level)
+        }
+        // otlp ingestion takes the stream from a header, the metrics are
+        // checked by their handlers with the names in the body
+        ("v1", Some(&"metrics")) | ("ingest", Some(&"metrics")) => return true,
+        ("v1", Some(&"logs")) | ("v1", Some(&"traces")) | ("traces", None)
+            if *method == Method::POST =>
+        {
+            let stream_type = if path_columns.get(2) == Some(&"logs") {
+                StreamType::Logs
+            } else {
+                StreamType::Traces
+            };
+            return check_otlp_ingest(org_id, user_email, stream_type, stream_header).await;
+        }
+        (stream_name, Some(&"traces")) if *method == Method::GET => {
+            return is_allowed(
+                org_id,
+                user_email,
+                GrantResource::Stream,
+                Some(StreamType::Traces),
+                stream_name,
+                GrantLevel::Read,
+            )
+            .await;
+        }
+        (stream_name, Some(endpoint))
+            if *method == Method::POST && INGESTION_EP.contains(endpoint) =>
+        {
+            (
+                GrantResource::Stream,
+                stream_name.to_string(),
+                GrantLevel::Ingest,
+            )
+        }
+        (stream_name, Some(endpoint))
+            if endpoint.starts_with("_values") || endpoint.starts_with("_around") =>
+        {
+            (
+                GrantResource::Stream,
+                stream_name.to_string(),
+                GrantLevel::Read,
+            )
+        }
+        _ => return true,
+    };
+    is_allowed(org_id, user_email, resource, stream_type, &name, level).await
+}
+
+/// Checks that the user can ingest into the stream of an OTLP request, which
+/// is taken from the stream name header.
+pub async fn check_otlp_ingest(
+    org_id: &str,
+    user_email: &str,
+    stream_type: StreamType,
+    stream_header: Option<&str>,
+) -> bool {
+    is_allowed(
+        org_id,
+        user_email,
+        GrantResource::Stream,
+        Some(stream_type),
+        &otlp_stream_name(stream_header),
+        GrantLevel::Ingest,
+    )
+    .await
+}
+
+fn otlp_stream_name(stream_header: Option<&str>) -> String {
+    stream_header.map_or_else(|| "default".to_string(), format_stream_name)
+}
+
+fn get_restrictions(org_id: &str, user_email: &str) -> Vec<GrantResource> {
+    GRANT_RESTRICTIONS
+        .get(&format!("{org_id}/{user_email}"))
+        .map(|r| r.value().clone())
+        .unwrap_or_default()
+}
+
+fn get_user_grants(org_id: &str, user_email: &str) -> Vec<Grant> {
+    let prefix = format!("{org_id}/");
+    GRANTS
+        .iter()
+        .filter(|entry| entry.key().starts_with(&prefix) && entry.value().subject == user_email)
+        .map(|entry| entry.value().clone())
+        .collect()
+}
@@ -0,0 +1,411 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Ingestion flow control.
+//!
+//! Instead of failing hard when the ingester is overloaded, requests are
+//! rejected with `429 Too Many Requests` and a `Retry-After` header on HTTP, or
+//! `RESOURCE_EXHAUSTED` with a `google.rpc.RetryInfo` detail on gRPC, so the
+//! shippers back off and retry the same batch later.
+//!
+//! Two things are checked:
+//! 1. a token bucket per organization, charged with the ingested bytes
+//! 2. the memory pressure of the ingester, the retry delay depends on the number of memtables
+//!    waiting to be persisted
+
+use std::time::{Duration, Instant};
+
+use actix_web::{HttpResponse, http};
+use config::{get_config, meta::otlp::OtlpRequestType, metrics};
+use hashbrown::HashMap;
+use infra::errors::{Error, Result};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use prost::Message;
+use tonic::{Code, Status, metadata::MetadataValue};
+
+use crate::common::meta::http::HttpResponse as MetaHttpResponse;
+
+pub const RETRY_AFTER_HEADER: &str = "Retry-After";
+pub const PROTOCOL_HTTP: &str = "http";
+pub const PROTOCOL_GRPC: &str = "grpc";
+
+const REASON_RATE_LIMIT: &str = "rate_limit";
+const REASON_MEMTABLE: &str = "memtable";
+const REASON_MEMORY: &str = "memory";
+
+const RETRY_INFO_TYPE_URL: &str = "type.googleapis.com/google.rpc.RetryInfo";
+
+static ORG_BUCKETS: Lazy<Mutex<HashMap<String, TokenBucket>>> = Lazy::new(Default::default);
+
+// a full bucket is the same as no bucket, so the idle orgs are dropped from time to time
+static LAST_EVICTION: Lazy<Mutex<Instant>> = Lazy::new(|| Mutex::new(Instant::now()));
+const EVICTION_INTERVAL: Duration = Duration::from_secs(60);
+
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(burst: f64) -> Self {
+        Self {
+            tokens: burst,
+            updated_at: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self, rate: f64, burst: f64, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(burst);
+        self.updated_at = now;
+    }
+
+    /// Whether the bucket is refilled up to the burst size at `now`
+    fn is_full(&self, rate: f64, burst: f64, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens + elapsed * rate >= burst
+    }
+
+    /// Returns the seconds to wait until the bucket is not in debt
+    fn wait_secs(&self, rate: f64) -> u64 {
+        if self.tokens >= 0.0 {
+            0
+        } else {
+            (-self.tokens / rate).ceil() as u64
+        }
+    }
+}
+
+/// Checks if the organization is allowed to ingest now.
+pub fn check(org_id: &str) -> Result<()> {
+    check_rate_limit(org_id)?;
+    check_memory_pressure()
+}
+
+/// Charges the ingested bytes to the bucket of the organization. The bucket may
+/// go into debt, the following requests are throttled until it's refilled.
+pub fn consume(org_id: &str, bytes: usize) {
+    let cfg = get_config();
+    if cfg.limit.ingest_org_rate_limit == 0 {
+        return;
+    }
+    let rate = cfg.limit.ingest_org_rate_limit as f64;
+    let burst = cfg.limit.ingest_org_rate_burst as f64;
+    let mut buckets = ORG_BUCKETS.lock();
+    let bucket = buckets
+        .entry(org_id.to_string())
+        .or_insert_with(|| TokenBucket::new(burst));
+    let now = Instant::now();
+    bucket.refill(rate, burst, now);
+    bucket.tokens -= bytes as f64;
+
+    let mut last_eviction = LAST_EVICTION.lock();
+    if now.saturating_duration_since(*last_eviction) >= EVICTION_INTERVAL {
+        evict_full_buckets(&mut buckets, rate, burst, now);
+        *last_eviction = now;
+    }
+}
+
+fn evict_full_buckets(
+    buckets: &mut HashMap<String, TokenBucket>,
+    rate: f64,
+    burst: f64,
+    now: Instant,
+) {
+    buckets.retain(|_, bucket| !bucket.is_full(rate, burst, now));
+}
+
+fn check_rate_limit(org_id: &str) -> Result<()> {
+    let cfg = get_config();
+    if cfg.limit.ingest_org_rate_limit == 0 {
+        return Ok(());
+    }
+    let rate = cfg.limit.ingest_org_rate_limit as f64;
+    let burst = cfg.limit.ingest_org_rate_burst as f64;
+    let mut buckets = ORG_BUCKETS.lock();
+    let Some(bucket) = buckets.get_mut(org_id) else {
+        return Ok(());
+    };
+    bucket.refill(rate, burst, Instant::now());
+    let wait = bucket.wait_secs(rate);
+    if wait == 0 {
+        return Ok(());
+    }
+    Err(throttled(
+        REASON_RATE_LIMIT,
+        format!("Ingestion rate limit exceeded for this organization [{org_id}]"),
+        clamp_retry_after(wait),
+    ))
+}
+
+fn check_memory_pressure() -> Result<()> {
+    let (reason, message) = if let Err(e) = ingester::check_memory_circuit_breaker() {
+        (REASON_MEMORY, e.to_string())
+    } else if let Err(e) = ingester::check_memtable_size() {
+        (REASON_MEMTABLE, e.to_string())
+    } else {
+        return Ok(());
+    };
+    Err(throttled(reason, message, get_retry_after_by_queue_depth()))
+}
+
+// the memtables waiting to be persisted are flushed by `mem_dump_thread_num` threads every
+// `mem_persist_interval` seconds, the deeper the queue the longer the shippers should wait
+fn get_retry_after_by_queue_depth() -> u64 {
+    let cfg = get_config();
+    let pending = metrics::INGEST_MEMTABLE_FILES
+        .with_label_values(&[])
+        .get()
+        .max(0) as u64;
+    let rounds = pending
+        .div_ceil(cfg.limit.mem_dump_thread_num.max(1) as u64)
+        .max(1);
+    clamp_retry_after(rounds * cfg.limit.mem_persist_interval)
+}
+
+fn clamp_retry_after(secs: u64) -> u64 {
+    let cfg = get_config();
+    secs.clamp(
+        cfg.limit.ingest_retry_after_min,
+        cfg.limit.ingest_retry_after_max,
+    )
+}
+
+fn throttled(reason: &str, message: String, retry_after: u64) -> Error {
+    Error::IngestionThrottled {
+        reason: reason.to_string(),
+        message,
+        retry_after,
+    }
+}
+
+#[inline]
+pub fn get_protocol(req_type: OtlpRequestType) -> &'static str {
+    match req_type {
+        OtlpRequestType::Grpc => PROTOCOL_GRPC,
+        _ => PROTOCOL_HTTP,
+    }
+}
+
+#[inline]
+pub fn record_throttled(org_id: &str, protocol: &str, reason: &str) {
+    metrics::INGEST_THROTTLED_REQUESTS
+        .with_label_values(&[org_id, protocol, reason])
+        .inc();
+}
+
+/// Builds the `429 Too Many Requests` response of a throttled request, returns
+/// `None` if the error is not caused by flow control.
+pub fn http_response(org_id: &str, protocol: &str, err: &Error) -> Option<HttpResponse> {
+    let Error::IngestionThrottled {
+        reason,
+        retry_after,
+        ..
+    } = err
+    else {
+        return None;
+    };
+    record_throttled(org_id, protocol, reason);
+    Some(
+        HttpResponse::TooManyRequests()
+            .insert_header((RETRY_AFTER_HEADER, retry_after.to_string()))
+            .json(MetaHttpResponse::error(
+                http::StatusCode::TOO_MANY_REQUESTS,
+                err,
+            )),
+    )
+}
+
+/// Builds the `RESOURCE_EXHAUSTED` status of a throttled request, returns
+/// `None` if the error is not caused by flow control.
+pub fn grpc_status(org_id: &str, err: &Error) -> Option<Status> {
+    let Error::IngestionThrottled {
+        reason,
+        retry_after,
+        ..
+    } = err
+    else {
+        return None;
+    };
+    record_throttled(org_id, PROTOCOL_GRPC, reason);
+    Some(resource_exhausted(err.to_string(), *retry_after))
+}
+
+/// Converts the throttled response of the handlers shared by http and grpc to a
+/// `RESOURCE_EXHAUSTED` status, the request was already counted in metrics.
+pub fn http_response_to_grpc_status(resp: &HttpResponse) -> Option<Status> {
+    if resp.status() != http::StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    let retry_after = resp
+        .headers()
+        .get(RETRY_AFTER_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(get_config().limit.ingest_retry_after_min);
+    Some(resource_exhausted(
+        "Ingestion is throttled, please retry later".to_string(),
+        retry_after,
+    ))
+}
+
+fn resource_exhausted(message: String, retry_after: u64) -> Status {
+    let details = RpcStatus {
+        code: Code::ResourceExhausted as i32,
+        message: message.clone(),
+        details: vec![RpcAny {
+            type_url: RETRY_INFO_TYPE_URL.to_string(),
+            value: RetryInfo {
+                retry_delay: Some(RpcDuration {
+                    seconds: retry_after as i64,
+                    nanos: 0,
+                }),
+            }
+            .encode_to_vec(),
+        }],
+    };
+    let mut status = Status::with_details(
+        Code::ResourceExhausted,
+        message,
+        details.encode_to_vec().into(),
+    );
+    let metadata = status.metadata_mut();
+    if let Ok(v) = MetadataValue::try_from(retry_after.to_string()) {
+        metadata.insert("retry-after", v);
+    }
+    if let Ok(v) = MetadataValue::try_from((retry_after * 1000).to_string()) {
+        metadata.insert("grpc-retry-pushback-ms", v);
+    }
+    status
+}
+
+// google.rpc.Status, the payload of `grpc-status-details-bin`
+#[derive(Clone, PartialEq, Message)]
+struct RpcStatus {
+    #[prost(int32, tag = "1")]
+    code: i32,
+    #[prost(string, tag = "2")]
+    message: String,
+    #[prost(message, repeated, tag = "3")]
+    details: Vec<RpcAny>,
+}
+
+// google.protobuf.Any
+#[derive(Clone, PartialEq, Message)]
+struct RpcAny {
+    #[prost(string, tag = "1")]
+    type_url: String,
+    #[prost(bytes = "vec", tag = "2")]
+    value: Vec<u8>,
+}
+
+// google.rpc.RetryInfo
+#[derive(Clone, PartialEq, Message)]
+struct RetryInfo {
+    #[prost(message, optional, tag = "1")]
+    retry_delay: Option<RpcDuration>,
+}
+
+// google.protobuf.Duration
+#[derive(Clone, PartialEq, Message)]
+struct RpcDuration {
+    #[prost(int64, tag = "1")]
+    seconds: i64,
+    #[prost(int32, tag = "2")]
+    nanos: i32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket {
+            tokens: 100.0,
+            updated_at: now,
+        };
+        bucket.tokens -= 150.0;
+        assert_eq!(bucket.wait_secs(10.0), 5);
+        bucket.refill(10.0, 100.0, now + Duration::from_secs(5));
+        assert_eq!(bucket.wait_secs(10.0), 0);
+        // never over the burst size
+        bucket.refill(10.0, 100.0, now + Duration::from_secs(100));
+        assert_eq!(bucket.tokens, 100.0);
+    }
+
+    #[test]
+    fn test_evict_full_buckets() {
+        let now = Instant::now();
+        let mut buckets = HashMap::new();
+        buckets.insert(
+            "idle".to_string(),
+            TokenBucket {
+                tokens: 50.0,
+                updated_at: now,
+            },
+        );
+        buckets.insert(
+            "busy".to_string(),
+            TokenBucket {
+                tokens: -500.0,
+                updated_at: now,
+            },
+        );
+        evict_full_buckets(&mut buckets, 10.0, 100.0, now + Duration::from_secs(4));
+        assert_eq!(buckets.len(), 2);
+        evict_full_buckets(&mut buckets, 10.0, 100.0, now + Duration::from_secs(10));
+        assert!(!buckets.contains_key("idle"));
+        assert!(buckets.contains_key("busy"));
+    }
+
+    #[test]
+    fn test_http_response() {
+        let err = throttled(REASON_RATE_LIMIT, "throttled".to_string(), 7);
+        let resp = http_response("default", PROTOCOL_HTTP, &err).unwrap();
+        assert_eq!(resp.status(), http::StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(
+            resp.headers().get(RETRY_AFTER_HEADER).unwrap(),
+            &http::header::HeaderValue::from_static("7")
+        );
+        let status = http_response_to_grpc_status(&resp).unwrap();
+        assert_eq!(status.code(), Code::ResourceExhausted);
+
+        let err = Error::IngestionError("bad request".to_string());
+        assert!(http_response("default", PROTOCOL_HTTP, &err).is_none());
+    }
+
+    #[test]
+    fn test_grpc_status() {
+        let err = throttled(REASON_MEMTABLE, "throttled".to_string(), 3);
+        let status = grpc_status("default", &err).unwrap();
+        assert_eq!(status.code(), Code::ResourceExhausted);
+        assert_eq!(
+            status
+                .metadata()
+                .get("retry-after")
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "3"
+        );
+        let details = RpcStatus::decode(status.details()).unwrap();
+        assert_eq!(details.details[0].type_url, RETRY_INFO_TYPE_URL);
+        let info = RetryInfo::decode(details.details[0].value.as_slice()).unwrap();
+        assert_eq!(info.retry_delay.unwrap().seconds, 3);
+    }
+}
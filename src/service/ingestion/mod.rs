@@ -59,11 +59,14 @@ use crate::{
         alerts::alert::AlertExt,
         db::{self, alerts::alert::scheduler_key},
         logs::bulk::TRANSFORM_FAILED,
+        quotas,
     },
 };
 
+pub mod flow_control;
 pub mod grpc;
 pub mod ingestion_service;
+pub mod wal_replication;
 
 pub type TriggerAlertData = Vec<(Alert, Vec<Map<String, Value>>)>;
 
@@ -396,6 +399,8 @@ pub async fn write_file(
         return Err(Error::IngestionError(e.to_string()));
     }
 
+    flow_control::consume(writer.get_org_id(), entries_size);
+
     req_stats.size += entries_size as f64 / SIZE_IN_MB;
     req_stats.records += entries_records as i64;
     Ok(req_stats)
@@ -419,6 +424,9 @@ pub fn check_ingestion_allowed(
         )));
     }
 
+    // check the ingestion and storage quotas of the org
+    quotas::check_ingestion(org_id).map_err(Error::IngestionError)?;
+
     // check if we are allowed to ingest
     if let Some(stream_name) = stream_name
         && db::compact::retention::is_deleting_stream(org_id, stream_type, stream_name, None)
@@ -428,11 +436,8 @@ pub fn check_ingestion_allowed(
         )));
     }
 
-    // check memory circuit breaker
-    ingester::check_memory_circuit_breaker().map_err(|e| Error::ResourceError(e.to_string()))?;
-
-    // check memtable
-    ingester::check_memtable_size().map_err(|e| Error::ResourceError(e.to_string()))?;
+    // check rate limit, memory circuit breaker and memtable
+    flow_control::check(org_id)?;
 
     Ok(())
 }
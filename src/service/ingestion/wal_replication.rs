@@ -0,0 +1,168 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use async_trait::async_trait;
+use config::{
+    cluster::LOCAL_NODE,
+    get_config,
+    meta::cluster::{Role, get_internal_grpc_token},
+};
+use proto::cluster_rpc;
+use tonic::{Request, codec::CompressionEncoding, metadata::MetadataValue};
+
+use crate::{common::infra::cluster, service::grpc::get_cached_channel};
+
+/// Ships wal entries to the next `wal_replication_factor` ingesters on the
+/// consistent hash ring, keyed by the local node name.
+///
+/// The replicas are dropped once the source ingester persists the wal file. The
+/// release is best effort, a replica whose release was lost may be replayed
+/// after a failover, so duplicates are still possible in that case.
+///
+/// Without any peer (e.g. a single ingester) there is nothing to replicate to
+/// and the writes go on unreplicated.
+pub struct GrpcWalReplicator;
+
+#[async_trait]
+impl ingester::WalReplicator for GrpcWalReplicator {
+    async fn replicate(
+        &self,
+        file_path: &str,
+        entries: Vec<Vec<u8>>,
+        sealed: bool,
+    ) -> Result<(), String> {
+        send_to_peers(cluster_rpc::WalReplicationRequest {
+            source_node: LOCAL_NODE.name.clone(),
+            file_path: file_path.to_string(),
+            entries,
+            sealed,
+            persisted: false,
+        })
+        .await
+    }
+
+    async fn release(&self, file_path: &str) -> Result<(), String> {
+        send_to_peers(cluster_rpc::WalReplicationRequest {
+            source_node: LOCAL_NODE.name.clone(),
+            file_path: file_path.to_string(),
+            entries: vec![],
+            sealed: true,
+            persisted: true,
+        })
+        .await
+    }
+}
+
+async fn send_to_peers(req: cluster_rpc::WalReplicationRequest) -> Result<(), String> {
+    let peers = get_replica_peers(&LOCAL_NODE.name).await;
+    if peers.is_empty() {
+        log::debug!(
+            "[WAL_REPLICATION] no peer ingester available, skip replicating {}",
+            req.file_path
+        );
+        return Ok(());
+    }
+    let tasks = peers
+        .into_iter()
+        .map(|peer| send_to_peer(peer, req.clone()))
+        .collect::<Vec<_>>();
+    let errors = futures::future::join_all(tasks)
+        .await
+        .into_iter()
+        .filter_map(|r| r.err())
+        .collect::<Vec<_>>();
+    if !errors.is_empty() {
+        return Err(errors.join("; "));
+    }
+    Ok(())
+}
+
+/// Returns the ingesters which hold the replicas of the source node, the first
+/// one is responsible for recovering them when the source node is gone.
+pub async fn get_replica_peers(source_node: &str) -> Vec<String> {
+    let factor = get_config().limit.wal_replication_factor;
+    cluster::get_nodes_from_consistent_hash(source_node, &Role::Ingester, factor + 1)
+        .await
+        .into_iter()
+        .filter(|name| name != source_node)
+        .take(factor)
+        .collect()
+}
+
+async fn send_to_peer(peer: String, req: cluster_rpc::WalReplicationRequest) -> Result<(), String> {
+    let cfg = get_config();
+    let Some(node) = cluster::get_cached_node_by_name(&peer).await else {
+        return Err(format!("peer {peer} not found"));
+    };
+    let token: MetadataValue<_> = get_internal_grpc_token()
+        .parse()
+        .map_err(|_| "invalid token".to_string())?;
+    let channel = get_cached_channel(&node.grpc_addr)
+        .await
+        .map_err(|e| format!("peer {peer} connect error: {e}"))?;
+    let mut client = cluster_rpc::ingest_client::IngestClient::with_interceptor(
+        channel,
+        move |mut req: Request<()>| {
+            req.metadata_mut().insert("authorization", token.clone());
+            Ok(req)
+        },
+    );
+    client = client
+        .send_compressed(CompressionEncoding::Gzip)
+        .accept_compressed(CompressionEncoding::Gzip)
+        .max_decoding_message_size(cfg.grpc.max_message_size * 1024 * 1024)
+        .max_encoding_message_size(cfg.grpc.max_message_size * 1024 * 1024);
+    let res = client
+        .replicate_wal(req)
+        .await
+        .map_err(|e| format!("peer {peer} response error: {e}"))?
+        .into_inner();
+    if res.status_code != 200 {
+        return Err(format!("peer {peer} response error: {}", res.message));
+    }
+    Ok(())
+}
+
+/// Recovers the replicas of the ingesters which are gone and cleans the
+/// replicas which are not needed anymore.
+pub async fn check_replicas() -> Result<(), anyhow::Error> {
+    let retention = std::time::Duration::from_secs(get_config().limit.wal_replication_retention);
+    let online_nodes = cluster::get_cached_online_ingester_nodes()
+        .await
+        .unwrap_or_default();
+    for source in ingester::list_wal_replica_sources().await {
+        let is_online = online_nodes.iter().any(|n| n.name == source);
+        // only the first peer of the source node recovers the replicas, other peers
+        // keep them until the retention to avoid replaying the same data twice
+        if !is_online && get_replica_peers(&source).await.first() == Some(&LOCAL_NODE.name) {
+            let num = ingester::recover_wal_replicas(&source).await?;
+            log::warn!(
+                "[WAL_REPLICATION] node {} is gone, recovered {} wal files",
+                source,
+                num
+            );
+            continue;
+        }
+        let num = ingester::clean_wal_replicas(&source, retention).await?;
+        if num > 0 {
+            log::info!(
+                "[WAL_REPLICATION] cleaned {} expired wal replicas of node {}",
+                num,
+                source
+            );
+        }
+    }
+    Ok(())
+}
@@ -24,6 +24,7 @@ use config::{
     ALL_VALUES_COL_NAME, BLOCKED_STREAMS, ID_COL_NAME, ORIGINAL_DATA_COL_NAME, TIMESTAMP_COL_NAME,
     get_config,
     meta::{
+        api_token::ApiToken,
         self_reporting::usage::UsageType,
         stream::{StreamParams, StreamType},
     },
@@ -57,6 +58,7 @@ pub async fn ingest(
     org_id: &str,
     body: web::Bytes,
     user_email: &str,
+    token: Option<&ApiToken>,
 ) -> Result<BulkResponse> {
     let start = std::time::Instant::now();
     let started_at = Utc::now().timestamp_micros();
@@ -83,6 +85,7 @@ pub async fn ingest(
     let mut doc_id = None;
 
     let mut blocked_stream_warnings: HashMap<String, bool> = HashMap::new();
+    let mut stream_ingest_allowed: HashMap<String, bool> = HashMap::new();
 
     let mut stream_executable_pipelines: HashMap<String, Option<ExecutablePipeline>> =
         HashMap::new();
@@ -153,6 +156,50 @@ pub async fn ingest(
                 continue; // skip
             }
 
+            // check the streams of the api token and the stream grants of the user
+            let allowed = match stream_ingest_allowed.get(&stream_name) {
+                Some(allowed) => *allowed,
+                None => {
+                    let allowed = token.is_none_or(|token| token.allows_stream(&stream_name));
+                    #[cfg(not(feature = "enterprise"))]
+                    let allowed = allowed
+                        && crate::service::grants::is_allowed(
+                            org_id,
+                            user_email,
+                            config::meta::grant::GrantResource::Stream,
+                            Some(StreamType::Logs),
+                            &stream_name,
+                            config::meta::grant::GrantLevel::Ingest,
+                        )
+                        .await;
+                    stream_ingest_allowed.insert(stream_name.clone(), allowed);
+                    allowed
+                }
+            };
+            if !allowed {
+                let err_msg = format!("Forbidden to ingest into stream: {stream_name}");
+                bulk_res.errors = true;
+                let err = BulkResponseError::new(
+                    err_msg.to_string(),
+                    stream_name.clone(),
+                    err_msg,
+                    "0".to_string(),
+                );
+                let mut item = HashMap::new();
+                item.insert(
+                    action.clone(),
+                    BulkResponseItem::new_failed(
+                        stream_name.clone(),
+                        doc_id.clone().unwrap_or_default(),
+                        err,
+                        None,
+                        stream_name.clone(),
+                    ),
+                );
+                bulk_res.items.push(item);
+                continue; // skip
+            }
+
             let mut streams = vec![StreamParams {
                 org_id: org_id.to_owned().into(),
                 stream_type: StreamType::Logs,
@@ -31,7 +31,7 @@ use crate::{
         ingestion::{IngestionRequest, IngestionResponse},
         loki::{LokiError, LokiPushRequest},
     },
-    service::logs,
+    service::{ingestion::flow_control, logs},
 };
 
 pub enum LokiRequest {
@@ -52,6 +52,22 @@ pub async fn handle_request(
         }
     };
 
+    // check the stream grants of the user before ingesting anything
+    #[cfg(not(feature = "enterprise"))]
+    crate::service::grants::check_ingest_streams(
+        org_id,
+        user_email,
+        config::meta::stream::StreamType::Logs,
+        streams_data.keys().map(|name| name.as_str()),
+    )
+    .await
+    .map_err(|e| match e {
+        crate::service::grants::GrantError::ForbiddenStream(stream_name) => {
+            LokiError::Forbidden { stream_name }
+        }
+        e => LokiError::from(anyhow::anyhow!(e)),
+    })?;
+
     for (stream_name, records) in streams_data {
         logs::ingest::ingest(
             thread_id,
@@ -69,6 +85,18 @@ pub async fn handle_request(
                 org_id,
                 e
             );
+            if let infra::errors::Error::IngestionThrottled {
+                reason,
+                message,
+                retry_after,
+            } = &e
+            {
+                flow_control::record_throttled(org_id, flow_control::PROTOCOL_HTTP, reason);
+                return LokiError::Throttled {
+                    message: message.clone(),
+                    retry_after: *retry_after,
+                };
+            }
             LokiError::from(anyhow::anyhow!(
                 "Stream {} ingestion failed: {:?}",
                 stream_name,
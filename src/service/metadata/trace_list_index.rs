@@ -216,6 +216,9 @@ impl TraceListIndex {
                 extended_retention_days: vec![],
                 index_all_values: false,
                 index_original_data: false,
+                parquet_settings: None,
+                tail_sampling: None,
+                masking: None,
             };
 
             stream::save_stream_settings(org_id, STREAM_NAME, StreamType::Metadata, settings)
@@ -13,7 +13,11 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::{collections::HashMap, io::BufReader, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    io::BufReader,
+    sync::Arc,
+};
 
 use actix_web::{http, web};
 use anyhow::{Result, anyhow};
@@ -56,10 +60,13 @@ use crate::{
     },
 };
 
-pub async fn ingest(org_id: &str, body: web::Bytes) -> Result<IngestionResponse> {
+pub async fn ingest(org_id: &str, user_email: &str, body: web::Bytes) -> Result<IngestionResponse> {
     // check system resource
     if let Err(e) = check_ingestion_allowed(org_id, StreamType::Metrics, None) {
         log::error!("Metrics ingestion error: {e}");
+        if matches!(e, infra::errors::Error::IngestionThrottled { .. }) {
+            return Err(e.into());
+        }
         return Ok(IngestionResponse {
             code: http::StatusCode::SERVICE_UNAVAILABLE.into(),
             status: vec![],
@@ -88,6 +95,25 @@ pub async fn ingest(org_id: &str, body: web::Bytes) -> Result<IngestionResponse>
     let mut json_data_by_stream: HashMap<String, Vec<(json::Value, String)>> = HashMap::new();
 
     let reader: Vec<json::Value> = json::from_slice(&body)?;
+
+    // check the stream grants of the user before ingesting anything
+    #[cfg(feature = "enterprise")]
+    let _ = user_email;
+    #[cfg(not(feature = "enterprise"))]
+    {
+        let stream_names = reader
+            .iter()
+            .filter_map(|record| record.get(NAME_LABEL)?.as_str())
+            .map(format_stream_name)
+            .collect::<HashSet<_>>();
+        crate::service::grants::check_ingest_streams(
+            org_id,
+            user_email,
+            StreamType::Metrics,
+            stream_names.iter().map(|name| name.as_str()),
+        )
+        .await?;
+    }
     for record in reader.into_iter() {
         // JSON Flattening
         let mut record = flatten::flatten(record)?;
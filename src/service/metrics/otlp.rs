@@ -47,7 +47,7 @@ use crate::{
         alerts::alert::AlertExt,
         db, format_stream_name,
         ingestion::{
-            TriggerAlertData, check_ingestion_allowed, evaluate_trigger,
+            TriggerAlertData, check_ingestion_allowed, evaluate_trigger, flow_control,
             grpc::{get_exemplar_val, get_metric_val, get_val},
             write_file,
         },
@@ -58,7 +58,11 @@ use crate::{
     },
 };
 
-pub async fn otlp_proto(org_id: &str, body: web::Bytes) -> Result<HttpResponse, std::io::Error> {
+pub async fn otlp_proto(
+    org_id: &str,
+    user_email: &str,
+    body: web::Bytes,
+) -> Result<HttpResponse, std::io::Error> {
     let request = match ExportMetricsServiceRequest::decode(body) {
         Ok(v) => v,
         Err(e) => {
@@ -73,6 +77,12 @@ pub async fn otlp_proto(org_id: &str, body: web::Bytes) -> Result<HttpResponse,
             )));
         }
     };
+    #[cfg(not(feature = "enterprise"))]
+    if let Err(e) = check_grants(org_id, user_email, &request).await {
+        return Ok(MetaHttpResponse::forbidden(e));
+    }
+    #[cfg(feature = "enterprise")]
+    let _ = user_email;
     match handle_otlp_request(org_id, request, OtlpRequestType::HttpProtobuf).await {
         Ok(v) => Ok(v),
         Err(e) => {
@@ -86,7 +96,11 @@ pub async fn otlp_proto(org_id: &str, body: web::Bytes) -> Result<HttpResponse,
     }
 }
 
-pub async fn otlp_json(org_id: &str, body: web::Bytes) -> Result<HttpResponse, std::io::Error> {
+pub async fn otlp_json(
+    org_id: &str,
+    user_email: &str,
+    body: web::Bytes,
+) -> Result<HttpResponse, std::io::Error> {
     let request = match serde_json::from_slice::<ExportMetricsServiceRequest>(body.as_ref()) {
         Ok(req) => req,
         Err(e) => {
@@ -97,6 +111,12 @@ pub async fn otlp_json(org_id: &str, body: web::Bytes) -> Result<HttpResponse, s
             )));
         }
     };
+    #[cfg(not(feature = "enterprise"))]
+    if let Err(e) = check_grants(org_id, user_email, &request).await {
+        return Ok(MetaHttpResponse::forbidden(e));
+    }
+    #[cfg(feature = "enterprise")]
+    let _ = user_email;
     match handle_otlp_request(org_id, request, OtlpRequestType::HttpJson).await {
         Ok(v) => Ok(v),
         Err(e) => {
@@ -106,6 +126,30 @@ pub async fn otlp_json(org_id: &str, body: web::Bytes) -> Result<HttpResponse, s
     }
 }
 
+/// Checks that the user can ingest into the streams of all the metrics of the
+/// request.
+#[cfg(not(feature = "enterprise"))]
+pub async fn check_grants(
+    org_id: &str,
+    user_email: &str,
+    request: &ExportMetricsServiceRequest,
+) -> Result<(), crate::service::grants::GrantError> {
+    let stream_names = request
+        .resource_metrics
+        .iter()
+        .flat_map(|resource_metric| resource_metric.scope_metrics.iter())
+        .flat_map(|scope_metric| scope_metric.metrics.iter())
+        .map(|metric| format_stream_name(&metric.name))
+        .collect::<HashSet<_>>();
+    crate::service::grants::check_ingest_streams(
+        org_id,
+        user_email,
+        StreamType::Metrics,
+        stream_names.iter().map(|name| name.as_str()),
+    )
+    .await
+}
+
 pub async fn handle_otlp_request(
     org_id: &str,
     request: ExportMetricsServiceRequest,
@@ -115,10 +159,13 @@ pub async fn handle_otlp_request(
     if let Err(e) = check_ingestion_allowed(org_id, StreamType::Metrics, None) {
         log::error!("Metrics ingestion error: {e}");
         return Ok(
-            HttpResponse::ServiceUnavailable().json(MetaHttpResponse::error(
-                http::StatusCode::SERVICE_UNAVAILABLE,
-                e,
-            )),
+            flow_control::http_response(org_id, flow_control::get_protocol(req_type), &e)
+                .unwrap_or_else(|| {
+                    HttpResponse::ServiceUnavailable().json(MetaHttpResponse::error(
+                        http::StatusCode::SERVICE_UNAVAILABLE,
+                        e,
+                    ))
+                }),
         );
     }
 
@@ -65,6 +65,7 @@ use crate::{
 
 pub async fn remote_write(
     org_id: &str,
+    user_email: &str,
     body: web::Bytes,
 ) -> std::result::Result<(), anyhow::Error> {
     // check system resource
@@ -100,6 +101,35 @@ pub async fn remote_write(
     let request = prometheus_rpc::WriteRequest::decode(bytes::Bytes::from(decoded))
         .map_err(|e| anyhow::anyhow!("Invalid protobuf: {}", e.to_string()))?;
 
+    // check the stream grants of the user before ingesting anything
+    #[cfg(feature = "enterprise")]
+    let _ = user_email;
+    #[cfg(not(feature = "enterprise"))]
+    {
+        let metadata_names = request
+            .metadata
+            .iter()
+            .map(|item| format_stream_name(&item.metric_family_name))
+            .collect::<HashSet<_>>();
+        let series_names = request
+            .timeseries
+            .iter()
+            .flat_map(|event| event.labels.iter())
+            .filter(|label| label.name == NAME_LABEL)
+            .map(|label| label.value.as_str())
+            .collect::<HashSet<_>>();
+        crate::service::grants::check_ingest_streams(
+            org_id,
+            user_email,
+            StreamType::Metrics,
+            metadata_names
+                .iter()
+                .map(|name| name.as_str())
+                .chain(series_names),
+        )
+        .await?;
+    }
+
     // records buffer
     let mut json_data_by_stream: HashMap<String, Vec<(json::Value, i64)>> = HashMap::new();
 
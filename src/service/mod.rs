@@ -18,10 +18,12 @@ use infra::errors::Result;
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 pub mod alerts;
+pub mod api_tokens;
 pub mod cluster_info;
 pub mod compact;
 pub mod dashboards;
 pub mod db;
+pub mod encryption;
 pub mod enrichment;
 pub mod enrichment_table;
 pub mod exporter;
@@ -29,6 +31,8 @@ pub mod file_list;
 pub mod file_list_dump;
 pub mod folders;
 pub mod functions;
+#[cfg(not(feature = "enterprise"))]
+pub mod grants;
 pub mod grpc;
 pub mod ingestion;
 pub mod kv;
@@ -36,14 +40,20 @@ pub mod logs;
 pub mod metadata;
 pub mod metrics;
 pub mod node;
+#[cfg(not(feature = "enterprise"))]
+pub mod oidc;
+pub mod org_bundle;
 #[cfg(feature = "cloud")]
 pub mod org_usage;
 pub mod organization;
 pub mod pipeline;
 pub mod promql;
+pub mod quotas;
 #[cfg(feature = "enterprise")]
 pub mod ratelimit;
 pub mod schema;
+#[cfg(not(feature = "enterprise"))]
+pub mod scim;
 pub mod search;
 pub mod websocket_events;
 
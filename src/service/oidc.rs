@@ -0,0 +1,576 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! OpenID Connect login of the open source build. The users log in at any
+//! OIDC provider with the authorization code flow and PKCE, the groups in
+//! their ID token are mapped to org roles, and they get a session signed by
+//! this cluster.
+
+use std::collections::HashMap;
+
+use base64::Engine;
+use config::{
+    get_config, ider,
+    meta::user::{DBUser, UserOrg, UserRole},
+    utils::{
+        json::{self, Value},
+        rand::generate_random_string,
+        time::now,
+    },
+};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, decode_header, encode,
+    jwk::JwkSet,
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::{
+    common::utils::auth::is_root_user,
+    handler::http::auth::validator::PKCE_STATE_ORG,
+    service::{
+        db, kv, organization,
+        session::{self, SessionClient},
+        users,
+    },
+};
+
+/// Issuer of the sessions signed by the cluster.
+const SESSION_ISSUER: &str = "openobserve";
+
+static PROVIDER: Lazy<RwLock<Option<Provider>>> = Lazy::new(|| RwLock::new(None));
+
+#[derive(Debug, thiserror::Error)]
+pub enum OidcError {
+    #[error("OIDC login is not enabled")]
+    Disabled,
+    #[error("Invalid login state")]
+    InvalidState,
+    #[error("Provider error: {0}")]
+    Provider(String),
+    #[error("Invalid ID token: {0}")]
+    InvalidToken(String),
+    #[error("User {0} has no access to any organization")]
+    NoAccess(String),
+    #[error("Service accounts are not allowed to login")]
+    ServiceAccount,
+    #[error("User {0} is not managed by the provider")]
+    NativeUser(String),
+    #[error("{0}")]
+    InfraError(#[from] anyhow::Error),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Clone, Debug)]
+struct Provider {
+    metadata: ProviderMetadata,
+    jwks: JwkSet,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LoginState {
+    code_verifier: String,
+    nonce: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SessionClaims {
+    sub: String,
+    iss: String,
+    exp: i64,
+}
+
+/// The user of a verified ID token.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OidcUser {
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub groups: Vec<String>,
+}
+
+/// Returns the authorization url of the provider and keeps the PKCE verifier
+/// of the login under its state.
+pub async fn login_url() -> Result<String, OidcError> {
+    let cfg = get_config();
+    if !cfg.oidc.enabled {
+        return Err(OidcError::Disabled);
+    }
+    let provider = get_provider(false).await?;
+    let state = generate_random_string(32);
+    let login_state = LoginState {
+        code_verifier: generate_random_string(64),
+        nonce: generate_random_string(32),
+    };
+    kv::set(
+        PKCE_STATE_ORG,
+        &state,
+        json::to_vec(&login_state).unwrap().into(),
+    )
+    .await?;
+
+    let url = url::Url::parse_with_params(
+        &provider.metadata.authorization_endpoint,
+        &[
+            ("response_type", "code"),
+            ("client_id", cfg.oidc.client_id.as_str()),
+            ("redirect_uri", cfg.oidc.redirect_url.as_str()),
+            ("scope", cfg.oidc.scopes.as_str()),
+            ("state", state.as_str()),
+            ("nonce", login_state.nonce.as_str()),
+            (
+                "code_challenge",
+                pkce_challenge(&login_state.code_verifier).as_str(),
+            ),
+            ("code_challenge_method", "S256"),
+        ],
+    )
+    .map_err(|e| OidcError::Provider(e.to_string()))?;
+    Ok(url.to_string())
+}
+
+/// Completes a login: exchanges the code, verifies the ID token and
+/// provisions the user. Returns the user and the id of the new session.
+pub async fn login(
+    code: &str,
+    state: &str,
+    client: &SessionClient,
+) -> Result<(OidcUser, String), OidcError> {
+    let cfg = get_config();
+    if !cfg.oidc.enabled {
+        return Err(OidcError::Disabled);
+    }
+    let login_state: LoginState = match kv::get(PKCE_STATE_ORG, state).await {
+        Ok(val) => {
+            let _ = kv::delete(PKCE_STATE_ORG, state).await;
+            json::from_slice(&val).map_err(|_| OidcError::InvalidState)?
+        }
+        Err(_) => return Err(OidcError::InvalidState),
+    };
+
+    let provider = get_provider(false).await?;
+    let form = url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs([
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", cfg.oidc.redirect_url.as_str()),
+            ("client_id", cfg.oidc.client_id.as_str()),
+            ("client_secret", cfg.oidc.client_secret.as_str()),
+            ("code_verifier", login_state.code_verifier.as_str()),
+        ])
+        .finish();
+    let resp = reqwest::Client::new()
+        .post(&provider.metadata.token_endpoint)
+        .header(
+            reqwest::header::CONTENT_TYPE,
+            "application/x-www-form-urlencoded",
+        )
+        .body(form)
+        .send()
+        .await
+        .map_err(|e| OidcError::Provider(e.to_string()))?;
+    let status = resp.status();
+    let body = resp
+        .bytes()
+        .await
+        .map_err(|e| OidcError::Provider(e.to_string()))?;
+    if !status.is_success() {
+        return Err(OidcError::Provider(format!(
+            "token exchange failed with {status}: {}",
+            String::from_utf8_lossy(&body)
+        )));
+    }
+    let tokens: TokenResponse =
+        json::from_slice(&body).map_err(|e| OidcError::Provider(e.to_string()))?;
+
+    let claims = verify_id_token(&tokens.id_token, &login_state.nonce).await?;
+    let user = parse_claims(&claims, &cfg.oidc.email_claim, &cfg.oidc.groups_claim)?;
+    provision(&user).await?;
+    let session_id = create_session(&user.email, client).await?;
+    Ok((user, session_id))
+}
+
+/// Returns the email of the user of a session token signed by the cluster.
+pub fn verify_session_token(token: &str) -> Option<String> {
+    let cfg = get_config();
+    if !cfg.oidc.enabled {
+        return None;
+    }
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.set_issuer(&[SESSION_ISSUER]);
+    validation.set_required_spec_claims(&["exp", "iss", "sub"]);
+    decode::<SessionClaims>(
+        token,
+        &DecodingKey::from_secret(cfg.oidc.session_secret.as_bytes()),
+        &validation,
+    )
+    .ok()
+    .map(|data| data.claims.sub)
+}
+
+async fn create_session(user_email: &str, client: &SessionClient) -> Result<String, OidcError> {
+    let cfg = get_config();
+    let claims = SessionClaims {
+        sub: user_email.to_string(),
+        iss: SESSION_ISSUER.to_string(),
+        exp: now().timestamp() + cfg.auth.cookie_max_age,
+    };
+    let token = encode(
+        &Header::new(Algorithm::HS256),
+        &claims,
+        &EncodingKey::from_secret(cfg.oidc.session_secret.as_bytes()),
+    )
+    .map_err(|e| OidcError::InfraError(e.into()))?;
+    let session_id = ider::uuid();
+    session::create_session(&session_id, &token, user_email, client)
+        .await
+        .ok_or_else(|| OidcError::InfraError(anyhow::anyhow!("failed to save the session")))?;
+    Ok(session_id)
+}
+
+async fn get_provider(refresh: bool) -> Result<Provider, OidcError> {
+    if !refresh && let Some(provider) = PROVIDER.read().await.as_ref() {
+        return Ok(provider.clone());
+    }
+    let cfg = get_config();
+    let metadata: ProviderMetadata = fetch_json(&format!(
+        "{}/.well-known/openid-configuration",
+        cfg.oidc.issuer_url
+    ))
+    .await?;
+    let jwks: JwkSet = fetch_json(&metadata.jwks_uri).await?;
+    let provider = Provider { metadata, jwks };
+    *PROVIDER.write().await = Some(provider.clone());
+    Ok(provider)
+}
+
+async fn fetch_json<T: serde::de::DeserializeOwned>(url: &str) -> Result<T, OidcError> {
+    let resp = reqwest::get(url)
+        .await
+        .map_err(|e| OidcError::Provider(e.to_string()))?;
+    if !resp.status().is_success() {
+        return Err(OidcError::Provider(format!(
+            "{url} responded with {}",
+            resp.status()
+        )));
+    }
+    let body = resp
+        .bytes()
+        .await
+        .map_err(|e| OidcError::Provider(e.to_string()))?;
+    json::from_slice(&body).map_err(|e| OidcError::Provider(e.to_string()))
+}
+
+async fn verify_id_token(id_token: &str, nonce: &str) -> Result<HashMap<String, Value>, OidcError> {
+    let header = decode_header(id_token).map_err(|e| OidcError::InvalidToken(e.to_string()))?;
+    if matches!(
+        header.alg,
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+    ) {
+        return Err(OidcError::InvalidToken(
+            "symmetric signatures are not accepted".to_string(),
+        ));
+    }
+    let kid = header.kid.unwrap_or_default();
+    let mut provider = get_provider(false).await?;
+    if provider.jwks.find(&kid).is_none() {
+        // the provider rotated its keys
+        provider = get_provider(true).await?;
+    }
+    let Some(jwk) = provider.jwks.find(&kid) else {
+        return Err(OidcError::InvalidToken(format!("unknown key id {kid}")));
+    };
+    let key = DecodingKey::from_jwk(jwk).map_err(|e| OidcError::InvalidToken(e.to_string()))?;
+
+    let cfg = get_config();
+    let mut validation = Validation::new(header.alg);
+    validation.set_audience(&[cfg.oidc.client_id.as_str()]);
+    validation.set_issuer(&[provider.metadata.issuer.as_str()]);
+    let claims = decode::<HashMap<String, Value>>(id_token, &key, &validation)
+        .map_err(|e| OidcError::InvalidToken(e.to_string()))?
+        .claims;
+    if claims.get("nonce").and_then(|v| v.as_str()) != Some(nonce) {
+        return Err(OidcError::InvalidToken("nonce mismatch".to_string()));
+    }
+    Ok(claims)
+}
+
+fn parse_claims(
+    claims: &HashMap<String, Value>,
+    email_claim: &str,
+    groups_claim: &str,
+) -> Result<OidcUser, OidcError> {
+    let claim = |name: &str| {
+        claims
+            .get(name)
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let email = claim(email_claim).trim().to_lowercase();
+    if email.is_empty() {
+        return Err(OidcError::InvalidToken(format!(
+            "missing {email_claim} claim"
+        )));
+    }
+    // the email is the identity of the user here, some providers send the flag
+    // as a string
+    let verified = match claims.get("email_verified") {
+        Some(Value::Bool(verified)) => *verified,
+        Some(Value::String(verified)) => verified.eq_ignore_ascii_case("true"),
+        _ => false,
+    };
+    if !verified {
+        return Err(OidcError::InvalidToken(format!(
+            "email {email} is not verified"
+        )));
+    }
+    let groups = match claims.get(groups_claim) {
+        Some(Value::Array(groups)) => groups
+            .iter()
+            .filter_map(|g| g.as_str().map(String::from))
+            .collect(),
+        Some(Value::String(group)) => vec![group.to_string()],
+        _ => vec![],
+    };
+    let (first_name, last_name) = match (claim("given_name"), claim("family_name")) {
+        (first, last) if first.is_empty() && last.is_empty() => (claim("name"), last),
+        names => names,
+    };
+    Ok(OidcUser {
+        email,
+        first_name,
+        last_name,
+        groups,
+    })
+}
+
+/// Parses a role of the mapping, only the roles a user can log in with.
+pub fn parse_role(role: &str) -> Option<UserRole> {
+    match role.trim() {
+        "admin" => Some(UserRole::Admin),
+        "editor" => Some(UserRole::Editor),
+        "viewer" => Some(UserRole::Viewer),
+        "user" => Some(UserRole::User),
+        _ => None,
+    }
+}
+
+/// Maps groups to org roles with `group=org:role` entries, a user in several
+/// groups of an org gets the highest of their roles. Users without any mapped
+/// group get the default role in the default org, if there is one.
+pub fn map_groups(
+    groups: &[String],
+    mapping: &str,
+    default_org: &str,
+    default_role: &str,
+) -> HashMap<String, UserRole> {
+    let mut roles: HashMap<String, UserRole> = HashMap::new();
+    for entry in mapping.split(',').filter(|e| !e.trim().is_empty()) {
+        let Some((group, org_role)) = entry.split_once('=') else {
+            log::warn!("[OIDC] invalid group mapping {entry}");
+            continue;
+        };
+        let Some((org, role)) = org_role.split_once(':') else {
+            log::warn!("[OIDC] invalid group mapping {entry}");
+            continue;
+        };
+        let Some(role) = parse_role(role) else {
+            log::warn!("[OIDC] invalid role in group mapping {entry}");
+            continue;
+        };
+        if !groups.iter().any(|g| g == group.trim()) {
+            continue;
+        }
+        let org = org.trim().to_string();
+        match roles.get(&org) {
+            Some(current) if *current >= role => {}
+            _ => {
+                roles.insert(org, role);
+            }
+        }
+    }
+    if roles.is_empty()
+        && !default_org.is_empty()
+        && let Some(role) = parse_role(default_role)
+    {
+        roles.insert(default_org.to_string(), role);
+    }
+    roles
+}
+
+/// Creates the user or syncs the orgs and roles of the user with the groups.
+/// Only the memberships of users created by the provider are removed. A native
+/// user of the same email is only taken over when the operator allows it, the
+/// root user never.
+async fn provision(user: &OidcUser) -> Result<(), OidcError> {
+    let cfg = get_config();
+    let roles = map_groups(
+        &user.groups,
+        &cfg.oidc.group_role_mapping,
+        &cfg.oidc.default_org,
+        &cfg.oidc.default_role,
+    );
+    if roles.is_empty() {
+        return Err(OidcError::NoAccess(user.email.clone()));
+    }
+    for org_id in roles.keys() {
+        organization::check_and_create_org(org_id).await?;
+    }
+
+    let Some(db_user) = db::user::get_user_by_email(&user.email).await else {
+        let db_user = DBUser {
+            email: user.email.clone(),
+            first_name: user.first_name.clone(),
+            last_name: user.last_name.clone(),
+            password: String::new(),
+            salt: String::new(),
+            organizations: roles
+                .into_iter()
+                .map(|(name, role)| UserOrg {
+                    name,
+                    token: String::new(),
+                    rum_token: None,
+                    role,
+                })
+                .collect(),
+            is_external: true,
+            password_ext: None,
+        };
+        users::create_new_user(db_user).await?;
+        return Ok(());
+    };
+    if db_user
+        .organizations
+        .iter()
+        .any(|org| org.role == UserRole::ServiceAccount)
+    {
+        return Err(OidcError::ServiceAccount);
+    }
+    if !db_user.is_external && (!cfg.oidc.link_native_users || is_root_user(&user.email)) {
+        log::warn!(
+            "[OIDC] refused the login of {} over the native user",
+            user.email
+        );
+        return Err(OidcError::NativeUser(user.email.clone()));
+    }
+
+    for (org_id, role) in roles.iter() {
+        match db_user.organizations.iter().find(|org| &org.name == org_id) {
+            Some(org) if org.role == UserRole::Root || &org.role == role => {}
+            Some(org) => {
+                db::org_users::update(
+                    org_id,
+                    &user.email,
+                    role.clone(),
+                    &org.token,
+                    org.rum_token.clone(),
+                )
+                .await?;
+            }
+            None => {
+                db::org_users::add(
+                    org_id,
+                    &user.email,
+                    role.clone(),
+                    &generate_random_string(16),
+                    Some(format!("rum{}", generate_random_string(16))),
+                )
+                .await?;
+            }
+        }
+    }
+    if db_user.is_external {
+        for org in db_user.organizations.iter() {
+            if !roles.contains_key(&org.name) && org.role != UserRole::Root {
+                db::org_users::remove(&org.name, &user.email).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn pkce_challenge(code_verifier: &str) -> String {
+    let digest = hex::decode(sha256::digest(code_verifier)).unwrap();
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_groups() {
+        let mapping = "sre=default:admin, dev=default:editor,dev=payments:viewer,bad=x:owner";
+        let groups = vec!["dev".to_string(), "sre".to_string()];
+        let roles = map_groups(&groups, mapping, "default", "viewer");
+        assert_eq!(roles.get("default"), Some(&UserRole::Admin));
+        assert_eq!(roles.get("payments"), Some(&UserRole::Viewer));
+        assert!(!roles.contains_key("x"));
+
+        let roles = map_groups(&[], mapping, "default", "viewer");
+        assert_eq!(roles.len(), 1);
+        assert_eq!(roles.get("default"), Some(&UserRole::Viewer));
+
+        assert!(map_groups(&[], mapping, "default", "").is_empty());
+    }
+
+    #[test]
+    fn test_parse_claims() {
+        let claims: HashMap<String, Value> = json::from_value(json::json!({
+            "email": "Jane@Example.com",
+            "email_verified": true,
+            "name": "Jane Doe",
+            "groups": ["sre", 1],
+        }))
+        .unwrap();
+        let user = parse_claims(&claims, "email", "groups").unwrap();
+        assert_eq!(user.email, "jane@example.com");
+        assert_eq!(user.first_name, "Jane Doe");
+        assert_eq!(user.groups, vec!["sre".to_string()]);
+        assert!(parse_claims(&claims, "upn", "groups").is_err());
+
+        for verified in [json::json!(false), json::json!("false"), Value::Null] {
+            let mut claims = claims.clone();
+            claims.insert("email_verified".to_string(), verified);
+            assert!(parse_claims(&claims, "email", "groups").is_err());
+        }
+        let mut claims = claims.clone();
+        claims.insert("email_verified".to_string(), json::json!("true"));
+        assert!(parse_claims(&claims, "email", "groups").is_ok());
+    }
+
+    #[test]
+    fn test_pkce_challenge() {
+        // RFC 7636 appendix B
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGMSstw-cM"
+        );
+    }
+}
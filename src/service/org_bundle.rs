@@ -0,0 +1,989 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Organization bundles: export all the metadata of an org, and optionally the
+//! data files referenced from `file_list`, into a directory which can be
+//! imported into an org of another cluster.
+//!
+//! The bundle directory contains a `manifest.json` with the metadata and a
+//! `files/` tree with the object storage files, keyed by their original path.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    str::FromStr,
+};
+
+use arrow_schema::Schema;
+use config::{
+    ider,
+    meta::{
+        alerts::alert::{Alert, ListAlertsParams},
+        dashboards::{Dashboard, ListDashboardsParams},
+        destinations::{Destination, Template},
+        folder::{DEFAULT_FOLDER, Folder, FolderType},
+        function::Transform,
+        pipeline::Pipeline,
+        stream::{FileKey, FileMeta, StreamType},
+    },
+    utils::{json, time::now_micros},
+};
+use infra::{
+    db::{ORM_CLIENT, connect_to_orm},
+    file_list as infra_file_list, storage, table,
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{
+    common::{
+        meta::{
+            authz::Authz,
+            saved_view::{CreateViewRequest, UpdateViewRequest},
+        },
+        utils::auth::set_ownership,
+    },
+    service::{alerts, dashboards, db, folders, organization, pipeline},
+};
+
+/// Version of the manifest format, bumped on incompatible changes.
+pub const BUNDLE_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const FILES_DIR: &str = "files";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrgBundle {
+    pub version: u32,
+    /// The org the bundle was exported from
+    pub org_id: String,
+    pub created_at: i64,
+    #[serde(default)]
+    pub folders: Vec<BundleFolder>,
+    #[serde(default)]
+    pub dashboards: Vec<BundleDashboard>,
+    #[serde(default)]
+    pub alerts: Vec<BundleAlert>,
+    #[serde(default)]
+    pub destinations: Vec<Destination>,
+    #[serde(default)]
+    pub templates: Vec<Template>,
+    #[serde(default)]
+    pub functions: Vec<Transform>,
+    #[serde(default)]
+    pub pipelines: Vec<Pipeline>,
+    #[serde(default)]
+    pub views: Vec<BundleView>,
+    /// Latest schema of each stream, the stream settings are kept in its
+    /// metadata
+    #[serde(default)]
+    pub streams: Vec<BundleStream>,
+    /// Data files copied into the `files/` directory of the bundle
+    #[serde(default)]
+    pub files: Vec<BundleFile>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BundleFolder {
+    pub folder_id: String,
+    pub folder_type: FolderType,
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BundleDashboard {
+    pub folder_id: String,
+    pub dashboard: Dashboard,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BundleAlert {
+    pub folder_id: String,
+    pub alert: Alert,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BundleView {
+    pub view_name: String,
+    pub data: json::Value,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BundleStream {
+    pub stream_type: StreamType,
+    pub stream_name: String,
+    pub schema: Schema,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BundleFile {
+    pub key: String,
+    pub meta: FileMeta,
+}
+
+/// What to do when an object of the bundle already exists in the target org.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConflictMode {
+    /// keep the existing object
+    #[default]
+    Skip,
+    /// replace the existing object with the one of the bundle
+    Overwrite,
+    /// abort the import
+    Fail,
+}
+
+impl FromStr for ConflictMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "skip" => Ok(ConflictMode::Skip),
+            "overwrite" => Ok(ConflictMode::Overwrite),
+            "fail" => Ok(ConflictMode::Fail),
+            _ => Err(anyhow::anyhow!(
+                "invalid conflict mode: {s}, should be one of skip, overwrite, fail"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub files: usize,
+    /// objects which failed to import, the import goes on with the others
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    Create,
+    Update,
+    Skip,
+}
+
+impl ImportReport {
+    fn record(&mut self, action: &Action) {
+        match action {
+            Action::Create => self.created += 1,
+            Action::Update => self.updated += 1,
+            Action::Skip => self.skipped += 1,
+        }
+    }
+
+    fn error(&mut self, what: String, e: impl std::fmt::Display) {
+        log::error!("[ORG_BUNDLE] failed to import {what}: {e}");
+        self.errors.push(format!("{what}: {e}"));
+    }
+}
+
+fn resolve(mode: ConflictMode, exists: bool, what: &str) -> Result<Action, anyhow::Error> {
+    match (exists, mode) {
+        (false, _) => Ok(Action::Create),
+        (true, ConflictMode::Skip) => Ok(Action::Skip),
+        (true, ConflictMode::Overwrite) => Ok(Action::Update),
+        (true, ConflictMode::Fail) => Err(anyhow::anyhow!("{what} already exists")),
+    }
+}
+
+/// Exports the metadata of `org_id` into `dir`. The data files of the streams
+/// are only copied when `with_data` is set, except for enrichment tables which
+/// are useless without them.
+pub async fn export(org_id: &str, dir: &Path, with_data: bool) -> Result<OrgBundle, anyhow::Error> {
+    let mut bundle = OrgBundle {
+        version: BUNDLE_VERSION,
+        org_id: org_id.to_string(),
+        created_at: now_micros(),
+        folders: vec![],
+        dashboards: vec![],
+        alerts: vec![],
+        destinations: vec![],
+        templates: vec![],
+        functions: vec![],
+        pipelines: vec![],
+        views: vec![],
+        streams: vec![],
+        files: vec![],
+    };
+
+    for folder_type in [FolderType::Dashboards, FolderType::Alerts] {
+        for folder in table::folders::list_folders(org_id, folder_type).await? {
+            bundle.folders.push(BundleFolder {
+                folder_id: folder.folder_id,
+                folder_type,
+                name: folder.name,
+                description: folder.description,
+            });
+        }
+    }
+    for (folder, dashboard) in table::dashboards::list(ListDashboardsParams::new(org_id)).await? {
+        bundle.dashboards.push(BundleDashboard {
+            folder_id: folder.folder_id,
+            dashboard,
+        });
+    }
+    for (folder, alert) in
+        alerts::alert::list_with_folders_db(ListAlertsParams::new(org_id)).await?
+    {
+        bundle.alerts.push(BundleAlert {
+            folder_id: folder.folder_id,
+            alert,
+        });
+    }
+    bundle.destinations = alerts::destinations::list(org_id, None, None).await?;
+    // the templates of the default org are shared with all the orgs
+    bundle.templates = alerts::templates::list(org_id, None)
+        .await?
+        .into_iter()
+        .filter(|t| t.org_id == org_id)
+        .collect();
+    bundle.functions = db::functions::list(org_id).await?;
+    bundle.pipelines = db::pipeline::list_by_org(org_id).await?;
+    for view in db::saved_view::get_views_list_only(org_id).await?.views {
+        let view = db::saved_view::get_view(org_id, &view.view_id).await?;
+        bundle.views.push(BundleView {
+            view_name: view.view_name,
+            data: view.data,
+        });
+    }
+
+    let files_dir = dir.join(FILES_DIR);
+    for stream in db::schema::list(org_id, None, true).await? {
+        if with_data || stream.stream_type == StreamType::EnrichmentTables {
+            let files = export_files(
+                org_id,
+                stream.stream_type,
+                &stream.stream_name,
+                &stream.schema,
+                &files_dir,
+            )
+            .await?;
+            bundle.files.extend(files);
+        }
+        bundle.streams.push(BundleStream {
+            stream_type: stream.stream_type,
+            stream_name: stream.stream_name,
+            schema: stream.schema,
+        });
+    }
+
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(dir.join(MANIFEST_FILE), json::to_vec(&bundle)?)?;
+    Ok(bundle)
+}
+
+async fn export_files(
+    org_id: &str,
+    stream_type: StreamType,
+    stream_name: &str,
+    schema: &Schema,
+    files_dir: &Path,
+) -> Result<Vec<BundleFile>, anyhow::Error> {
+    let settings = infra::schema::unwrap_stream_settings(schema).unwrap_or_default();
+    let partition_time_level =
+        infra::schema::unwrap_partition_time_level(settings.partition_time_level, stream_type);
+    // the whole time range, the data can be older than the stream when it was
+    // ingested with a past timestamp or newer than now
+    let stats = infra::cache::stats::get_stream_stats(org_id, stream_name, stream_type);
+    let end = stats.doc_time_max.max(now_micros());
+    let files = infra_file_list::query(
+        org_id,
+        stream_type,
+        stream_name,
+        partition_time_level,
+        Some((1, end)),
+        None,
+    )
+    .await?;
+
+    let mut exported = Vec::with_capacity(files.len());
+    for file in files {
+        let data = storage::get_bytes(&file.account, &file.key).await?;
+        let path = files_dir.join(&file.key);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, data)?;
+        exported.push(BundleFile {
+            key: file.key,
+            meta: file.meta,
+        });
+    }
+    Ok(exported)
+}
+
+/// Reads the manifest of the bundle in `dir`.
+pub fn read_manifest(dir: &Path) -> Result<OrgBundle, anyhow::Error> {
+    let data = std::fs::read(dir.join(MANIFEST_FILE))?;
+    let bundle: OrgBundle = json::from_slice(&data)?;
+    if bundle.version != BUNDLE_VERSION {
+        return Err(anyhow::anyhow!(
+            "unsupported bundle version: {}, expected {BUNDLE_VERSION}",
+            bundle.version
+        ));
+    }
+    Ok(bundle)
+}
+
+/// Imports the bundle in `dir` into `org_id`, or into the org it was exported
+/// from when `org_id` is not given.
+///
+/// Dashboards, alerts, pipelines, destinations and templates get new ids in
+/// the target org, the references between dashboards are remapped to them.
+/// With [`ConflictMode::Fail`] the import stops at the first existing object,
+/// the objects imported before it are kept.
+pub async fn import(
+    dir: &Path,
+    org_id: Option<&str>,
+    mode: ConflictMode,
+) -> Result<ImportReport, anyhow::Error> {
+    let bundle = read_manifest(dir)?;
+    let org_id = org_id.unwrap_or(&bundle.org_id).to_string();
+    organization::check_and_create_org(&org_id).await?;
+
+    let mut report = ImportReport::default();
+    let importer = Importer {
+        dir,
+        from: &bundle.org_id,
+        to: &org_id,
+        mode,
+    };
+    // streams and functions first, alerts and pipelines depend on them
+    importer.streams(&bundle, &mut report).await?;
+    importer.files(&bundle, &mut report).await?;
+    importer.functions(&bundle, &mut report).await?;
+    importer.templates(&bundle, &mut report).await?;
+    importer.destinations(&bundle, &mut report).await?;
+    let folder_ids = importer.folders(&bundle, &mut report).await?;
+    importer
+        .dashboards(&bundle, &folder_ids, &mut report)
+        .await?;
+    importer.alerts(&bundle, &folder_ids, &mut report).await?;
+    importer.pipelines(&bundle, &mut report).await?;
+    importer.views(&bundle, &mut report).await?;
+
+    for stream in bundle
+        .streams
+        .iter()
+        .filter(|s| s.stream_type == StreamType::EnrichmentTables)
+    {
+        if let Err(e) = db::enrichment_table::notify_update(&org_id, &stream.stream_name).await {
+            report.error(format!("enrichment table {}", stream.stream_name), e);
+        }
+    }
+    Ok(report)
+}
+
+struct Importer<'a> {
+    dir: &'a Path,
+    from: &'a str,
+    to: &'a str,
+    mode: ConflictMode,
+}
+
+impl Importer<'_> {
+    /// Moves an object of the source org into the target org.
+    fn retarget<T: Serialize + DeserializeOwned>(&self, item: &T) -> Result<T, anyhow::Error> {
+        let mut value = json::to_value(item)?;
+        if self.from != self.to {
+            replace_org(&mut value, self.from, self.to);
+        }
+        Ok(json::from_value(value)?)
+    }
+
+    async fn streams(
+        &self,
+        bundle: &OrgBundle,
+        report: &mut ImportReport,
+    ) -> Result<(), anyhow::Error> {
+        let existing = db::schema::list(self.to, None, false)
+            .await?
+            .into_iter()
+            .map(|s| (s.stream_type, s.stream_name))
+            .collect::<HashSet<_>>();
+        for stream in bundle.streams.iter() {
+            let what = format!("stream {}/{}", stream.stream_type, stream.stream_name);
+            let exists = existing.contains(&(stream.stream_type, stream.stream_name.clone()));
+            let action = resolve(self.mode, exists, &what)?;
+            let ret = match action {
+                Action::Skip => Ok(()),
+                Action::Create => db::schema::merge(
+                    self.to,
+                    &stream.stream_name,
+                    stream.stream_type,
+                    &stream.schema,
+                    None,
+                )
+                .await
+                .map(|_| ()),
+                Action::Update => self.update_stream(stream).await,
+            };
+            match ret {
+                Ok(()) => report.record(&action),
+                Err(e) => report.error(what, e),
+            }
+        }
+        Ok(())
+    }
+
+    async fn update_stream(&self, stream: &BundleStream) -> Result<(), anyhow::Error> {
+        db::schema::merge(
+            self.to,
+            &stream.stream_name,
+            stream.stream_type,
+            &stream.schema,
+            None,
+        )
+        .await?;
+        if let Some(settings) = stream.schema.metadata().get("settings") {
+            let metadata = HashMap::from([("settings".to_string(), settings.to_string())]);
+            db::schema::update_setting(self.to, &stream.stream_name, stream.stream_type, metadata)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Copies the data files into the object storage of the target org. The
+    /// files are immutable, so a file which is already registered is always
+    /// kept unless the conflict mode is [`ConflictMode::Fail`].
+    async fn files(
+        &self,
+        bundle: &OrgBundle,
+        report: &mut ImportReport,
+    ) -> Result<(), anyhow::Error> {
+        let mut added = Vec::new();
+        for file in bundle.files.iter() {
+            if !is_bundle_file_key(&file.key, &bundle.org_id) {
+                report.error(
+                    format!("file {}", file.key),
+                    anyhow::anyhow!("invalid file key"),
+                );
+                continue;
+            }
+            let key = retarget_file_key(&file.key, self.from, self.to);
+            if infra_file_list::contains(&key).await? {
+                resolve(self.mode, true, &format!("file {key}"))?;
+                report.skipped += 1;
+                continue;
+            }
+            let data = match std::fs::read(self.dir.join(FILES_DIR).join(&file.key)) {
+                Ok(data) => data,
+                Err(e) => {
+                    report.error(format!("file {}", file.key), e);
+                    continue;
+                }
+            };
+            let account = storage::get_account(&key).unwrap_or_default();
+            if let Err(e) = storage::put(&account, &key, data.into()).await {
+                report.error(format!("file {key}"), e);
+                continue;
+            }
+            // the tantivy index files are not part of the bundle, the imported
+            // files are searched without their index
+            let mut meta = file.meta.clone();
+            meta.index_size = 0;
+            added.push(FileKey::new(0, account, key, meta, false));
+        }
+        for chunk in added.chunks(1000) {
+            infra_file_list::batch_add(chunk).await?;
+            report.files += chunk.len();
+        }
+        Ok(())
+    }
+
+    async fn functions(
+        &self,
+        bundle: &OrgBundle,
+        report: &mut ImportReport,
+    ) -> Result<(), anyhow::Error> {
+        for func in bundle.functions.iter() {
+            let what = format!("function {}", func.name);
+            let exists = db::functions::get(self.to, &func.name).await.is_ok();
+            let action = resolve(self.mode, exists, &what)?;
+            if action == Action::Skip {
+                report.record(&action);
+                continue;
+            }
+            match db::functions::set(self.to, &func.name, func).await {
+                Ok(()) => {
+                    if action == Action::Create {
+                        set_ownership(self.to, "functions", Authz::new(&func.name)).await;
+                    }
+                    report.record(&action);
+                }
+                Err(e) => report.error(what, e),
+            }
+        }
+        Ok(())
+    }
+
+    async fn templates(
+        &self,
+        bundle: &OrgBundle,
+        report: &mut ImportReport,
+    ) -> Result<(), anyhow::Error> {
+        for template in bundle.templates.iter() {
+            let what = format!("template {}", template.name);
+            let exists = alerts::templates::get(self.to, &template.name)
+                .await
+                .is_ok();
+            let action = resolve(self.mode, exists, &what)?;
+            let mut template = self.retarget(template)?;
+            template.id = None;
+            let ret = match action {
+                Action::Skip => Ok(()),
+                Action::Create => alerts::templates::save("", template, true)
+                    .await
+                    .map(|_| ()),
+                Action::Update => {
+                    let name = template.name.clone();
+                    alerts::templates::save(&name, template, false)
+                        .await
+                        .map(|_| ())
+                }
+            };
+            match ret {
+                Ok(()) => report.record(&action),
+                Err(e) => report.error(what, e),
+            }
+        }
+        Ok(())
+    }
+
+    async fn destinations(
+        &self,
+        bundle: &OrgBundle,
+        report: &mut ImportReport,
+    ) -> Result<(), anyhow::Error> {
+        for destination in bundle.destinations.iter() {
+            let what = format!("destination {}", destination.name);
+            let exists = alerts::destinations::get(self.to, &destination.name)
+                .await
+                .is_ok();
+            let action = resolve(self.mode, exists, &what)?;
+            let mut destination = self.retarget(destination)?;
+            destination.id = None;
+            let ret = match action {
+                Action::Skip => Ok(()),
+                Action::Create => alerts::destinations::save("", destination, true)
+                    .await
+                    .map(|_| ()),
+                Action::Update => {
+                    let name = destination.name.clone();
+                    alerts::destinations::save(&name, destination, false)
+                        .await
+                        .map(|_| ())
+                }
+            };
+            match ret {
+                Ok(()) => report.record(&action),
+                Err(e) => report.error(what, e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Folders are matched by name, an existing folder is reused whatever the
+    /// conflict mode. Returns the ids of the source folders mapped to the ids
+    /// of the target folders.
+    async fn folders(
+        &self,
+        bundle: &OrgBundle,
+        report: &mut ImportReport,
+    ) -> Result<HashMap<String, String>, anyhow::Error> {
+        let mut ids = HashMap::new();
+        for folder in bundle.folders.iter() {
+            if folder.folder_id == DEFAULT_FOLDER {
+                ids.insert(folder.folder_id.clone(), DEFAULT_FOLDER.to_string());
+                continue;
+            }
+            if let Ok(existing) =
+                folders::get_folder_by_name(self.to, &folder.name, folder.folder_type).await
+            {
+                ids.insert(folder.folder_id.clone(), existing.folder_id);
+                report.skipped += 1;
+                continue;
+            }
+            let new_folder = Folder {
+                folder_id: String::new(),
+                name: folder.name.clone(),
+                description: folder.description.clone(),
+            };
+            match folders::save_folder(self.to, new_folder, folder.folder_type, false).await {
+                Ok(saved) => {
+                    ids.insert(folder.folder_id.clone(), saved.folder_id);
+                    report.created += 1;
+                }
+                Err(e) => report.error(format!("folder {}", folder.name), e),
+            }
+        }
+        Ok(ids)
+    }
+
+    async fn dashboards(
+        &self,
+        bundle: &OrgBundle,
+        folder_ids: &HashMap<String, String>,
+        report: &mut ImportReport,
+    ) -> Result<(), anyhow::Error> {
+        let existing = table::dashboards::list(ListDashboardsParams::new(self.to))
+            .await?
+            .into_iter()
+            .filter_map(|(folder, dashboard)| {
+                let title = dashboard.title()?.to_string();
+                Some(((folder.folder_id, title), dashboard))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let mut ids = folder_ids.clone();
+        let mut saved = Vec::new();
+        for item in bundle.dashboards.iter() {
+            let title = item.dashboard.title().unwrap_or_default().to_string();
+            let what = format!("dashboard {title}");
+            let folder_id = map_id(folder_ids, &item.folder_id);
+            let current = existing.get(&(folder_id.clone(), title));
+            let action = resolve(self.mode, current.is_some(), &what)?;
+            let dashboard = self.retarget(&item.dashboard)?;
+            let ret = match (&action, current) {
+                (Action::Skip, Some(current)) => Ok(current.clone()),
+                (Action::Update, Some(current)) => {
+                    let id = current.dashboard_id().unwrap_or_default();
+                    dashboards::update_dashboard(
+                        self.to,
+                        id,
+                        &folder_id,
+                        dashboard,
+                        Some(&current.hash),
+                    )
+                    .await
+                }
+                _ => dashboards::create_dashboard(self.to, &folder_id, dashboard).await,
+            };
+            match ret {
+                Ok(dashboard) => {
+                    if let (Some(old_id), Some(new_id)) =
+                        (item.dashboard.dashboard_id(), dashboard.dashboard_id())
+                    {
+                        ids.insert(old_id.to_string(), new_id.to_string());
+                    }
+                    if action != Action::Skip {
+                        saved.push((folder_id, dashboard));
+                    }
+                    report.record(&action);
+                }
+                Err(e) => report.error(what, e),
+            }
+        }
+
+        // the drilldowns of a dashboard can link to other dashboards by id
+        for (folder_id, dashboard) in saved {
+            let mut value = json::to_value(&dashboard)?;
+            if !remap_ids(&mut value, &ids) {
+                continue;
+            }
+            let remapped: Dashboard = json::from_value(value)?;
+            let id = dashboard.dashboard_id().unwrap_or_default();
+            if let Err(e) = dashboards::update_dashboard(
+                self.to,
+                id,
+                &folder_id,
+                remapped,
+                Some(&dashboard.hash),
+            )
+            .await
+            {
+                report.error(format!("dashboard links of {id}"), e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn alerts(
+        &self,
+        bundle: &OrgBundle,
+        folder_ids: &HashMap<String, String>,
+        report: &mut ImportReport,
+    ) -> Result<(), anyhow::Error> {
+        let conn = ORM_CLIENT.get_or_init(connect_to_orm).await;
+        for item in bundle.alerts.iter() {
+            let alert = &item.alert;
+            let what = format!(
+                "alert {}/{}/{}",
+                alert.stream_type, alert.stream_name, alert.name
+            );
+            let current = alerts::alert::get_by_name(
+                self.to,
+                alert.stream_type,
+                &alert.stream_name,
+                &alert.name,
+            )
+            .await?;
+            let action = resolve(self.mode, current.is_some(), &what)?;
+            let mut alert = self.retarget(alert)?;
+            let ret = match action {
+                Action::Skip => Ok(()),
+                Action::Create => {
+                    alert.id = None;
+                    let folder_id = map_id(folder_ids, &item.folder_id);
+                    alerts::alert::create(conn, self.to, &folder_id, alert)
+                        .await
+                        .map(|_| ())
+                }
+                Action::Update => {
+                    alert.id = current.and_then(|a| a.id);
+                    alerts::alert::update(conn, self.to, None, alert)
+                        .await
+                        .map(|_| ())
+                }
+            };
+            match ret {
+                Ok(()) => report.record(&action),
+                Err(e) => report.error(what, e),
+            }
+        }
+        Ok(())
+    }
+
+    async fn pipelines(
+        &self,
+        bundle: &OrgBundle,
+        report: &mut ImportReport,
+    ) -> Result<(), anyhow::Error> {
+        let existing = db::pipeline::list_by_org(self.to)
+            .await?
+            .into_iter()
+            .map(|p| (p.name.clone(), p))
+            .collect::<HashMap<_, _>>();
+        for pipeline in bundle.pipelines.iter() {
+            let what = format!("pipeline {}", pipeline.name);
+            let current = existing.get(&pipeline.name);
+            let action = resolve(self.mode, current.is_some(), &what)?;
+            let mut pipeline = self.retarget(pipeline)?;
+            pipeline.org = self.to.to_string();
+            let ret = match (&action, current) {
+                (Action::Skip, _) => Ok(()),
+                (Action::Update, Some(current)) => {
+                    pipeline.id = current.id.clone();
+                    pipeline.version = current.version;
+                    pipeline::update_pipeline(pipeline).await
+                }
+                _ => {
+                    pipeline.id = ider::generate();
+                    pipeline::save_pipeline(pipeline).await
+                }
+            };
+            match ret {
+                Ok(()) => report.record(&action),
+                Err(e) => report.error(what, e),
+            }
+        }
+        Ok(())
+    }
+
+    async fn views(
+        &self,
+        bundle: &OrgBundle,
+        report: &mut ImportReport,
+    ) -> Result<(), anyhow::Error> {
+        let existing = db::saved_view::get_views_list_only(self.to)
+            .await?
+            .views
+            .into_iter()
+            .map(|v| (v.view_name, v.view_id))
+            .collect::<HashMap<_, _>>();
+        for view in bundle.views.iter() {
+            let what = format!("saved view {}", view.view_name);
+            let current = existing.get(&view.view_name);
+            let action = resolve(self.mode, current.is_some(), &what)?;
+            let ret = match (&action, current) {
+                (Action::Skip, _) => Ok(()),
+                (Action::Update, Some(view_id)) => {
+                    let req = UpdateViewRequest {
+                        data: view.data.clone(),
+                        view_name: view.view_name.clone(),
+                    };
+                    db::saved_view::update_view(self.to, view_id, &req)
+                        .await
+                        .map(|_| ())
+                }
+                _ => {
+                    let req = CreateViewRequest {
+                        data: view.data.clone(),
+                        view_name: view.view_name.clone(),
+                    };
+                    db::saved_view::set_view(self.to, &req).await.map(|_| ())
+                }
+            };
+            match ret {
+                Ok(()) => report.record(&action),
+                Err(e) => report.error(what, e),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn map_id(ids: &HashMap<String, String>, id: &str) -> String {
+    ids.get(id).cloned().unwrap_or_else(|| id.to_string())
+}
+
+/// Replaces the org of the `org_id` and `org` fields of an exported object.
+fn replace_org(value: &mut json::Value, from: &str, to: &str) {
+    match value {
+        json::Value::Object(map) => {
+            for (k, v) in map.iter_mut() {
+                if (k == "org_id" || k == "org") && v.as_str() == Some(from) {
+                    *v = json::Value::String(to.to_string());
+                } else {
+                    replace_org(v, from, to);
+                }
+            }
+        }
+        json::Value::Array(items) => {
+            for v in items.iter_mut() {
+                replace_org(v, from, to);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replaces the source ids found in the strings of `value` with the target
+/// ones. Returns true when anything was replaced.
+fn remap_ids(value: &mut json::Value, ids: &HashMap<String, String>) -> bool {
+    match value {
+        json::Value::String(s) => {
+            let mut changed = false;
+            for (old, new) in ids.iter() {
+                if old != new && s.contains(old.as_str()) {
+                    *s = s.replace(old.as_str(), new);
+                    changed = true;
+                }
+            }
+            changed
+        }
+        json::Value::Object(map) => map
+            .values_mut()
+            .fold(false, |changed, v| remap_ids(v, ids) || changed),
+        json::Value::Array(items) => items
+            .iter_mut()
+            .fold(false, |changed, v| remap_ids(v, ids) || changed),
+        _ => false,
+    }
+}
+
+/// The key of a bundle file is also its path in the bundle directory, only the
+/// data files of the exported org are accepted so that a crafted manifest can
+/// not read outside of the bundle.
+fn is_bundle_file_key(key: &str, org_id: &str) -> bool {
+    key.starts_with(&format!("files/{org_id}/"))
+        && key
+            .split('/')
+            .all(|part| !part.is_empty() && part != "." && part != "..")
+        && Path::new(key)
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_)))
+}
+
+fn retarget_file_key(key: &str, from: &str, to: &str) -> String {
+    match key.strip_prefix(&format!("files/{from}/")) {
+        Some(rest) => format!("files/{to}/{rest}"),
+        None => key.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conflict_mode() {
+        assert_eq!(
+            ConflictMode::from_str("Overwrite").unwrap(),
+            ConflictMode::Overwrite
+        );
+        assert!(ConflictMode::from_str("merge").is_err());
+        assert_eq!(
+            resolve(ConflictMode::Fail, false, "x").unwrap(),
+            Action::Create
+        );
+        assert_eq!(
+            resolve(ConflictMode::Skip, true, "x").unwrap(),
+            Action::Skip
+        );
+        assert!(resolve(ConflictMode::Fail, true, "x").is_err());
+    }
+
+    #[test]
+    fn test_is_bundle_file_key() {
+        assert!(is_bundle_file_key(
+            "files/src/logs/default/2025/01/01/00/a.parquet",
+            "src"
+        ));
+        assert!(!is_bundle_file_key(
+            "files/other/logs/default/2025/01/01/00/a.parquet",
+            "src"
+        ));
+        assert!(!is_bundle_file_key("files/src/../../etc/passwd", "src"));
+        assert!(!is_bundle_file_key("files/src/./a.parquet", "src"));
+        assert!(!is_bundle_file_key("files/src//a.parquet", "src"));
+        assert!(!is_bundle_file_key("/files/src/a.parquet", "src"));
+    }
+
+    #[test]
+    fn test_replace_org() {
+        let mut value = json::json!({
+            "org": "src",
+            "source": {"org_id": "src", "stream_name": "src"},
+            "nodes": [{"data": {"org_id": "other"}}]
+        });
+        replace_org(&mut value, "src", "dst");
+        assert_eq!(value["org"], "dst");
+        assert_eq!(value["source"]["org_id"], "dst");
+        assert_eq!(value["source"]["stream_name"], "src");
+        assert_eq!(value["nodes"][0]["data"]["org_id"], "other");
+    }
+
+    #[test]
+    fn test_remap_ids() {
+        let ids = HashMap::from([("old".to_string(), "new".to_string())]);
+        let mut value = json::json!({
+            "dashboardId": "old",
+            "panels": [{"drilldown": "/dashboards/view?dashboard=old&folder=f"}]
+        });
+        assert!(remap_ids(&mut value, &ids));
+        assert_eq!(value["dashboardId"], "new");
+        assert_eq!(
+            value["panels"][0]["drilldown"],
+            "/dashboards/view?dashboard=new&folder=f"
+        );
+        assert!(!remap_ids(&mut value, &ids));
+    }
+
+    #[test]
+    fn test_retarget_file_key() {
+        assert_eq!(
+            retarget_file_key("files/src/logs/app/2025/01/01/00/a.parquet", "src", "dst"),
+            "files/dst/logs/app/2025/01/01/00/a.parquet"
+        );
+        assert_eq!(
+            retarget_file_key("files/srcx/logs/app/a.parquet", "src", "dst"),
+            "files/srcx/logs/app/a.parquet"
+        );
+    }
+}
@@ -21,12 +21,16 @@ use chrono::Utc;
 use config::{
     meta::{
         function::{Transform, VRLResultResolver},
-        pipeline::{Pipeline, components::NodeData},
+        pipeline::{
+            DryRunDestination, DryRunError, DryRunNodeResult, DryRunRecord, Pipeline,
+            PipelineDryRunResponse, PipelineNodeStats, components::NodeData,
+        },
         self_reporting::error::{ErrorData, ErrorSource, PipelineError},
         stream::{StreamParams, StreamType},
     },
     utils::{
         flatten,
+        grok::Pattern,
         json::{self, Value, get_string_value},
         schema::format_stream_name,
     },
@@ -37,6 +41,11 @@ use o2_enterprise::enterprise::pipeline::pipeline_wal_writer::get_pipeline_wal_w
 use once_cell::sync::Lazy;
 use tokio::sync::mpsc::{Receiver, Sender, channel};
 
+use super::{
+    dead_letter,
+    nodes::{self, Output},
+    stats,
+};
 use crate::{
     common::infra::config::QUERY_FUNCTIONS,
     service::{
@@ -91,7 +100,9 @@ pub struct ExecutablePipeline {
     source_node_id: String,
     sorted_nodes: Vec<String>,
     vrl_map: HashMap<String, (VRLResultResolver, bool)>,
+    pattern_map: HashMap<String, Pattern>,
     node_map: HashMap<String, ExecutableNode>,
+    dead_letter_stream: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -158,6 +169,24 @@ impl ExecutablePipeline {
                 return Err(e);
             }
         };
+        let pattern_map = match nodes::compile_patterns(pipeline) {
+            Ok(pattern_map) => pattern_map,
+            Err(e) => {
+                let pipeline_error = PipelineError {
+                    pipeline_id: pipeline.id.to_string(),
+                    pipeline_name: pipeline.name.to_string(),
+                    error: Some(format!("Init error: failed to compile parse pattern: {e}")),
+                    node_errors: HashMap::new(),
+                };
+                publish_error(ErrorData {
+                    _timestamp: Utc::now().timestamp_micros(),
+                    stream_params: pipeline.get_source_stream_params(),
+                    error_source: ErrorSource::Pipeline(pipeline_error),
+                })
+                .await;
+                return Err(e);
+            }
+        };
         let sorted_nodes = match topological_sort(&node_map) {
             Ok(sorted) => sorted,
             Err(e) => {
@@ -187,6 +216,8 @@ impl ExecutablePipeline {
             node_map,
             sorted_nodes,
             vrl_map,
+            pattern_map,
+            dead_letter_stream: pipeline.dead_letter_stream.clone(),
         })
     }
 
@@ -196,6 +227,106 @@ impl ExecutablePipeline {
         records: Vec<Value>,
         stream_name: Option<String>,
     ) -> Result<HashMap<StreamParams, Vec<(usize, Value)>>> {
+        self.execute(org_id, records, stream_name, None)
+            .await
+            .map(|(results, _)| results)
+    }
+
+    /// Runs the records through the pipeline without writing them anywhere.
+    /// Errors are returned instead of being published, and neither node
+    /// stats nor dead letters are recorded.
+    pub async fn dry_run(
+        &self,
+        org_id: &str,
+        records: Vec<Value>,
+    ) -> Result<PipelineDryRunResponse> {
+        let input_count = records.len();
+        let (tap_sender, mut tap_receiver) = channel::<(String, usize, Value)>(input_count.max(1));
+        let tap_task = tokio::spawn(async move {
+            let mut node_records: HashMap<String, Vec<(usize, Value)>> = HashMap::new();
+            while let Some((node_id, idx, record)) = tap_receiver.recv().await {
+                node_records.entry(node_id).or_default().push((idx, record));
+            }
+            node_records
+        });
+        let (results, errors) = self
+            .execute(org_id, records, None, Some(tap_sender))
+            .await?;
+        let node_records = tap_task.await.map_err(|e| {
+            log::error!("[Pipeline] dry run collecting job failed: {}", e);
+            anyhow!("[Pipeline] dry run collecting job failed: {}", e)
+        })?;
+
+        let dry_run_record = |(idx, record): (usize, Value)| DryRunRecord {
+            // records generated by a function have no single input record
+            input_index: (idx < input_count).then_some(idx),
+            record,
+        };
+        let mut response = PipelineDryRunResponse {
+            input_count,
+            ..Default::default()
+        };
+        for (node_id, records) in node_records {
+            let node = self.node_map.get(&node_id).unwrap();
+            if let NodeData::RemoteStream(remote_stream) = &node.node_data {
+                response.destinations.push(DryRunDestination {
+                    stream: None,
+                    remote_destination: Some(remote_stream.destination_name.to_string()),
+                    count: records.len(),
+                    input_indexes: records
+                        .iter()
+                        .filter_map(|(idx, _)| (*idx < input_count).then_some(*idx))
+                        .collect(),
+                });
+            }
+            response.nodes.insert(
+                node_id,
+                DryRunNodeResult {
+                    node_type: node.node_type(),
+                    count: records.len(),
+                    records: records.into_iter().map(dry_run_record).collect(),
+                },
+            );
+        }
+        for (stream_params, records) in results {
+            response.destinations.push(DryRunDestination {
+                stream: Some(stream_params),
+                remote_destination: None,
+                count: records.len(),
+                input_indexes: records
+                    .iter()
+                    .filter_map(|(idx, _)| (*idx < input_count).then_some(*idx))
+                    .collect(),
+            });
+        }
+        response.errors = errors
+            .into_iter()
+            .map(|(node_id, node_type, error)| DryRunError {
+                node_id,
+                node_type,
+                error,
+            })
+            .collect();
+        Ok(response)
+    }
+
+    /// Executes the pipeline DAG. When `tap` is set the execution is a dry run:
+    /// every record entering a node is also sent to the tap as
+    /// `(node_id, idx, record)`, remote destinations are not written and the
+    /// errors are returned as `(node_id, node_type, error)` instead of being
+    /// published.
+    #[allow(clippy::type_complexity)]
+    async fn execute(
+        &self,
+        org_id: &str,
+        records: Vec<Value>,
+        stream_name: Option<String>,
+        tap: Option<Sender<(String, usize, Value)>>,
+    ) -> Result<(
+        HashMap<StreamParams, Vec<(usize, Value)>>,
+        Vec<(String, String, String)>,
+    )> {
+        let dry_run = tap.is_some();
         let batch_size = records.len();
         let pipeline_name = self.name.clone();
         log::debug!(
@@ -204,7 +335,7 @@ impl ExecutablePipeline {
             batch_size
         );
         if batch_size == 0 {
-            return Ok(HashMap::default());
+            return Ok((HashMap::default(), Vec::new()));
         }
 
         // result_channel
@@ -212,7 +343,8 @@ impl ExecutablePipeline {
             channel::<(usize, StreamParams, Value)>(batch_size);
 
         // error_channel
-        let (error_sender, mut error_receiver) = channel::<(String, String, String)>(batch_size);
+        let (error_sender, mut error_receiver) =
+            channel::<(String, String, String, Option<Value>)>(batch_size);
 
         let mut node_senders = HashMap::new();
         let mut node_receivers = HashMap::new();
@@ -223,13 +355,46 @@ impl ExecutablePipeline {
             node_receivers.insert(node_id.to_string(), receiver);
         }
 
+        // in a dry run every node receives its records through a relay which
+        // copies them to the tap
+        if let Some(tap) = &tap {
+            for node_id in &self.sorted_nodes {
+                let (relay_sender, mut relay_receiver) =
+                    channel::<(usize, Value, bool)>(batch_size);
+                let node_sender = node_senders
+                    .insert(node_id.to_string(), relay_sender)
+                    .unwrap();
+                let tap = tap.clone();
+                let node_id = node_id.to_string();
+                tokio::spawn(async move {
+                    while let Some(item) = relay_receiver.recv().await {
+                        _ = tap.send((node_id.clone(), item.0, item.1.clone())).await;
+                        if node_sender.send(item).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        }
+
+        // the function nodes keep a copy of their input for the dead-letter stream
+        let keep_input = !dry_run && self.dead_letter_stream.is_some();
+
         // Spawn tasks for each node
         let mut node_tasks = Vec::new();
         for (idx, node_id) in self.sorted_nodes.iter().enumerate() {
             let pl_id_cp = self.id.to_string();
             let org_id_cp = org_id.to_string();
             let node = self.node_map.get(node_id).unwrap().clone();
-            let node_receiver = node_receivers.remove(node_id).unwrap();
+            let mut node_receiver = node_receivers.remove(node_id).unwrap();
+            if dry_run && matches!(node.node_data, NodeData::RemoteStream(_)) {
+                // records reaching a remote destination are only recorded by the tap
+                node_tasks.push(tokio::spawn(async move {
+                    while node_receiver.recv().await.is_some() {}
+                    Ok(())
+                }));
+                continue;
+            }
             let child_senders: Vec<_> = node
                 .children
                 .iter()
@@ -238,6 +403,7 @@ impl ExecutablePipeline {
             let result_sender_cp = node.children.is_empty().then_some(result_sender.clone());
             let error_sender_cp = error_sender.clone();
             let vrl_runtime = self.vrl_map.get(node_id).cloned();
+            let pattern = self.pattern_map.get(node_id).cloned();
             let pipeline_name = pipeline_name.clone();
             let stream_name = stream_name.clone();
 
@@ -252,10 +418,13 @@ impl ExecutablePipeline {
                     node_receiver,
                     child_senders,
                     vrl_runtime,
+                    pattern,
                     result_sender_cp,
                     error_sender_cp,
                     pipeline_name,
                     stream_name,
+                    dry_run,
+                    keep_input,
                 )
                 .await
             });
@@ -280,19 +449,45 @@ impl ExecutablePipeline {
 
         // task to collect errors
         let mut pipeline_error = PipelineError::new(&self.id, &self.name);
+        let (pl_id, pl_name) = (self.id.to_string(), self.name.to_string());
+        let need_dead_letter = !dry_run && self.dead_letter_stream.is_some();
         let error_task = tokio::spawn(async move {
             log::debug!("[Pipeline]: starts error collecting job");
             let mut count = 0;
-            while let Some((node_id, node_type, error)) = error_receiver.recv().await {
+            // node_id -> (node_type, error count)
+            let mut node_errors: HashMap<String, (String, u64)> = HashMap::new();
+            let mut dead_letters = Vec::new();
+            let mut errors = Vec::new();
+            while let Some((node_id, node_type, error, record)) = error_receiver.recv().await {
+                if dry_run {
+                    errors.push((node_id, node_type, error));
+                    count += 1;
+                    continue;
+                }
+                if need_dead_letter {
+                    dead_letters.push(dead_letter::build_record(
+                        &pl_id,
+                        &pl_name,
+                        &node_id,
+                        &node_type,
+                        &error,
+                        record.as_ref(),
+                    ));
+                }
+                node_errors
+                    .entry(node_id.clone())
+                    .or_insert_with(|| (node_type.clone(), 0))
+                    .1 += 1;
                 pipeline_error.add_node_error(node_id, node_type, error);
                 count += 1;
             }
             log::debug!("[Pipeline]: collected {count} errors");
-            if count > 0 {
+            let pipeline_error = if count > 0 {
                 Some(pipeline_error)
             } else {
                 None
-            }
+            };
+            (pipeline_error, node_errors, dead_letters, errors)
         });
 
         // Send records to the source node to begin processing
@@ -310,6 +505,7 @@ impl ExecutablePipeline {
             }
         }
         drop(source_sender);
+        drop(tap);
         drop(result_sender);
         drop(error_sender);
         drop(node_senders);
@@ -320,11 +516,32 @@ impl ExecutablePipeline {
             log::error!("[Pipeline] node processing jobs failed: {}", e);
         }
 
+        let (pipeline_errors, node_errors, dead_letters, errors) =
+            error_task.await.map_err(|e| {
+                log::error!("[Pipeline] error collecting job failed: {}", e);
+                anyhow!("[Pipeline] error collecting job failed: {}", e)
+            })?;
+        let results = result_task.await.map_err(|e| {
+            log::error!("[Pipeline] result collecting job failed: {}", e);
+            anyhow!("[Pipeline] result collecting job failed: {}", e)
+        })?;
+        if dry_run {
+            return Ok((results, errors));
+        }
+
+        for (node_id, (node_type, errors)) in node_errors {
+            let node_stats = PipelineNodeStats {
+                errors,
+                ..Default::default()
+            };
+            stats::record(org_id, &self.id, &node_id, &node_type, &node_stats);
+        }
+        if let Some(dead_letter_stream) = &self.dead_letter_stream {
+            dead_letter::send(org_id, dead_letter_stream, dead_letters);
+        }
+
         // Publish errors if received any
-        if let Some(pipeline_errors) = error_task.await.map_err(|e| {
-            log::error!("[Pipeline] error collecting job failed: {}", e);
-            anyhow!("[Pipeline] error collecting job failed: {}", e)
-        })? {
+        if let Some(pipeline_errors) = pipeline_errors {
             let stream_params = self.get_source_stream_params();
             let error_data = ErrorData {
                 _timestamp: Utc::now().timestamp_micros(),
@@ -335,12 +552,7 @@ impl ExecutablePipeline {
             publish_error(error_data).await;
         }
 
-        let results = result_task.await.map_err(|e| {
-            log::error!("[Pipeline] result collecting job failed: {}", e);
-            anyhow!("[Pipeline] result collecting job failed: {}", e)
-        })?;
-
-        Ok(results)
+        Ok((results, errors))
     }
 
     pub fn get_all_destination_streams(&self) -> Vec<StreamParams> {
@@ -399,6 +611,11 @@ impl std::fmt::Display for ExecutableNode {
             NodeData::Function(_) => write!(f, "function"),
             NodeData::Condition(_) => write!(f, "condition"),
             NodeData::RemoteStream(_) => write!(f, "remote_stream"),
+            NodeData::Parse(_) => write!(f, "parse"),
+            NodeData::FieldTransform(_) => write!(f, "field_transform"),
+            NodeData::Sample(_) => write!(f, "sample"),
+            NodeData::RateLimit(_) => write!(f, "rate_limit"),
+            NodeData::HashRoute(_) => write!(f, "hash_route"),
         }
     }
 }
@@ -502,13 +719,17 @@ async fn process_node(
     mut receiver: Receiver<(usize, Value, bool)>,
     mut child_senders: Vec<Sender<(usize, Value, bool)>>,
     vrl_runtime: Option<(VRLResultResolver, bool)>,
+    pattern: Option<Pattern>,
     result_sender: Option<Sender<(usize, StreamParams, Value)>>,
-    error_sender: Sender<(String, String, String)>,
+    error_sender: Sender<(String, String, String, Option<Value>)>,
     pipeline_name: String,
     stream_name: Option<String>,
+    dry_run: bool,
+    keep_input: bool,
 ) -> Result<()> {
     let cfg = config::get_config();
     let mut count: usize = 0;
+    let mut processed: u64 = 0;
     match &node.node_data {
         NodeData::Stream(stream_params) => {
             if node.children.is_empty() {
@@ -520,6 +741,7 @@ async fn process_node(
                 // send received results directly via `result_sender` for collection
                 let result_sender = result_sender.unwrap();
                 while let Some((idx, mut record, flattened)) = receiver.recv().await {
+                    processed += 1;
                     if !flattened {
                         record = match flatten::flatten_with_level(
                             record,
@@ -529,7 +751,7 @@ async fn process_node(
                             Err(e) => {
                                 let err_msg = format!("LeafNode error with flattening: {e}");
                                 if let Err(send_err) = error_sender
-                                    .send((node.id.to_string(), node.node_type(), err_msg))
+                                    .send((node.id.to_string(), node.node_type(), err_msg, None))
                                     .await
                                 {
                                     log::error!(
@@ -565,7 +787,12 @@ async fn process_node(
                                 };
                                 log::warn!("{err_msg}");
                                 if let Err(send_err) = error_sender
-                                    .send((node.id.to_string(), node.node_type(), err_msg))
+                                    .send((
+                                        node.id.to_string(),
+                                        node.node_type(),
+                                        err_msg,
+                                        Some(record),
+                                    ))
                                     .await
                                 {
                                     log::error!(
@@ -595,6 +822,7 @@ async fn process_node(
                 log::debug!("[Pipeline]: source node {node_idx} starts processing");
                 // source stream node: send received record to all its children
                 while let Some(item) = receiver.recv().await {
+                    processed += 1;
                     send_to_children(&mut child_senders, item, "StreamNode").await;
                     count += 1;
                 }
@@ -607,6 +835,7 @@ async fn process_node(
         NodeData::Condition(condition_params) => {
             log::debug!("[Pipeline]: cond node {node_idx} starts processing");
             while let Some((idx, mut record, mut flattened)) = receiver.recv().await {
+                processed += 1;
                 // value must be flattened before condition params can take effect
                 if !flattened {
                     record = match flatten::flatten_with_level(
@@ -617,7 +846,7 @@ async fn process_node(
                         Err(e) => {
                             let err_msg = format!("ConditionNode error with flattening: {e}");
                             if let Err(send_err) = error_sender
-                                .send((node.id.to_string(), node.node_type(), err_msg))
+                                .send((node.id.to_string(), node.node_type(), err_msg, None))
                                 .await
                             {
                                 log::error!(
@@ -653,8 +882,13 @@ async fn process_node(
             let mut runtime = crate::service::ingestion::init_functions_runtime();
             let stream_name = stream_name.unwrap_or("pipeline".to_string());
             let mut result_array_records = Vec::new();
+            let mut input_records = Vec::new();
             while let Some((idx, mut record, mut flattened)) = receiver.recv().await {
+                processed += 1;
                 if let Some((vrl_runtime, is_result_array_vrl)) = &vrl_runtime {
+                    // the dead-letter stream gets the record which failed, not the
+                    // output of the function
+                    let input = keep_input.then(|| record.clone());
                     if func_params.after_flatten && !flattened {
                         record = match flatten::flatten_with_level(
                             record,
@@ -664,7 +898,7 @@ async fn process_node(
                             Err(e) => {
                                 let err_msg = format!("FunctionNode error with flattening: {e}");
                                 if let Err(send_err) = error_sender
-                                    .send((node.id.to_string(), node.node_type(), err_msg))
+                                    .send((node.id.to_string(), node.node_type(), err_msg, input))
                                     .await
                                 {
                                     log::error!(
@@ -689,7 +923,7 @@ async fn process_node(
                             (res, Some(error)) => {
                                 let err_msg = format!("FunctionNode error: {error}");
                                 if let Err(send_err) = error_sender
-                                    .send((node.id.to_string(), node.node_type(), err_msg))
+                                    .send((node.id.to_string(), node.node_type(), err_msg, input))
                                     .await
                                 {
                                     log::error!(
@@ -709,6 +943,7 @@ async fn process_node(
                         )
                         .await;
                     } else {
+                        input_records.extend(input);
                         result_array_records.push(record);
                     }
                 }
@@ -727,8 +962,9 @@ async fn process_node(
                     (res, None) => res,
                     (res, Some(error)) => {
                         let err_msg = format!("FunctionNode error: {error}");
+                        let input = keep_input.then(|| json::Value::Array(input_records));
                         if let Err(send_err) = error_sender
-                            .send((node.id.to_string(), node.node_type(), err_msg))
+                            .send((node.id.to_string(), node.node_type(), err_msg, input))
                             .await
                         {
                             log::error!(
@@ -753,10 +989,101 @@ async fn process_node(
             }
             log::debug!("[Pipeline]: func node {node_idx} done processing {count} records");
         }
+        node_data @ (NodeData::Parse(_)
+        | NodeData::FieldTransform(_)
+        | NodeData::Sample(_)
+        | NodeData::RateLimit(_)
+        | NodeData::HashRoute(_)) => {
+            let node_type = node.node_type();
+            log::debug!("[Pipeline]: {node_type} node {node_idx} starts processing");
+            // dry runs must not use up the limits of the running pipeline
+            let limiter_prefix = if dry_run {
+                format!("dry_run/{pipeline_id}/{}", node.id)
+            } else {
+                format!("{pipeline_id}/{}", node.id)
+            };
+            while let Some((idx, mut record, flattened)) = receiver.recv().await {
+                processed += 1;
+                // built-in transform nodes work on flattened field names
+                if !flattened {
+                    record = match flatten::flatten_with_level(
+                        record,
+                        cfg.limit.ingest_flatten_level,
+                    ) {
+                        Ok(flattened) => flattened,
+                        Err(e) => {
+                            let err_msg = format!("TransformNode error with flattening: {e}");
+                            if let Err(send_err) = error_sender
+                                .send((node.id.to_string(), node_type.clone(), err_msg, None))
+                                .await
+                            {
+                                log::error!(
+                                    "[Pipeline] {} : TransformNode failed sending errors for collection caused by: {send_err}",
+                                    pipeline_name
+                                );
+                                break;
+                            }
+                            continue;
+                        }
+                    };
+                }
+                match nodes::apply(
+                    &limiter_prefix,
+                    node_data,
+                    pattern.as_ref(),
+                    child_senders.len(),
+                    record,
+                ) {
+                    Output::Forward(record, flattened) => {
+                        send_to_children(
+                            &mut child_senders,
+                            (idx, record, flattened),
+                            "TransformNode",
+                        )
+                        .await;
+                        count += 1;
+                    }
+                    Output::Route(child, record) => {
+                        if let Err(send_err) = child_senders[child].send((idx, record, true)).await
+                        {
+                            log::error!(
+                                "[Pipeline]: TransformNode errors sending record to its children caused by: {send_err}"
+                            );
+                            break;
+                        }
+                        count += 1;
+                    }
+                    Output::Drop => {}
+                    Output::Error(record, error) => {
+                        let err_msg = format!("TransformNode error: {error}");
+                        if let Err(send_err) = error_sender
+                            .send((
+                                node.id.to_string(),
+                                node_type.clone(),
+                                err_msg,
+                                Some(record.clone()),
+                            ))
+                            .await
+                        {
+                            log::error!(
+                                "[Pipeline] {} : TransformNode failed sending errors for collection caused by: {send_err}",
+                                pipeline_name
+                            );
+                            break;
+                        }
+                        send_to_children(&mut child_senders, (idx, record, true), "TransformNode")
+                            .await;
+                        count += 1;
+                    }
+                }
+            }
+            log::debug!("[Pipeline]: {node_type} node {node_idx} done processing {count} records");
+        }
         NodeData::Query(_) => {
             // source node for Scheduled pipeline. Directly send to children nodes
             log::debug!("[Pipeline]: query node {node_idx} starts processing");
             while let Some(item) = receiver.recv().await {
+                processed += 1;
                 send_to_children(&mut child_senders, item, "QueryNode").await;
                 count += 1;
             }
@@ -776,6 +1103,7 @@ async fn process_node(
                 + chrono::Duration::try_hours(cfg.limit.ingest_allowed_in_future).unwrap())
             .timestamp_micros();
             while let Some((_, mut record, flattened)) = receiver.recv().await {
+                processed += 1;
                 // handle timestamp before sending to remote_write service
                 if !flattened {
                     record = match flatten::flatten_with_level(
@@ -786,7 +1114,7 @@ async fn process_node(
                         Err(e) => {
                             let err_msg = format!("DestinationNode error with flattening: {e}");
                             if let Err(send_err) = error_sender
-                                .send((node.id.to_string(), node.node_type(), err_msg))
+                                .send((node.id.to_string(), node.node_type(), err_msg, None))
                                 .await
                             {
                                 log::error!(
@@ -804,7 +1132,7 @@ async fn process_node(
                 {
                     let err_msg = format!("DestinationNode error handling timestamp: {e}");
                     if let Err(send_err) = error_sender
-                        .send((node.id.to_string(), node.node_type(), err_msg))
+                        .send((node.id.to_string(), node.node_type(), err_msg, Some(record)))
                         .await
                     {
                         log::error!(
@@ -821,14 +1149,14 @@ async fn process_node(
 
             if !records.is_empty() {
                 let mut remote_stream = remote_stream.clone();
-                remote_stream.org_id = org_id.into();
+                remote_stream.org_id = org_id.clone().into();
                 let writer = get_pipeline_wal_writer(&pipeline_id, remote_stream).await?;
                 if let Err(e) = writer.write_wal(records).await {
                     let err_msg = format!(
                         "DestinationNode error persisting data to be ingested externally: {e}"
                     );
                     if let Err(send_err) = error_sender
-                        .send((node.id.to_string(), node.node_type(), err_msg))
+                        .send((node.id.to_string(), node.node_type(), err_msg, None))
                         .await
                     {
                         log::error!(
@@ -845,7 +1173,7 @@ async fn process_node(
             let err_msg = "[Pipeline]: remote destination is not supported in open source version. Records dropped".to_string();
             log::error!("{err_msg}");
             if let Err(send_err) = error_sender
-                .send((node.id.to_string(), node.node_type(), err_msg))
+                .send((node.id.to_string(), node.node_type(), err_msg, None))
                 .await
             {
                 log::error!(
@@ -855,6 +1183,24 @@ async fn process_node(
         }
     }
 
+    if dry_run {
+        return Ok(());
+    }
+
+    // records which the node received but didn't pass on
+    let node_stats = PipelineNodeStats {
+        processed,
+        dropped: processed.saturating_sub(count as u64),
+        ..Default::default()
+    };
+    stats::record(
+        &org_id,
+        &pipeline_id,
+        &node.id,
+        &node.node_type(),
+        &node_stats,
+    );
+
     // all cloned senders dropped when function goes out of scope -> close the channel
 
     Ok(())
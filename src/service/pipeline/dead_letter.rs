@@ -0,0 +1,153 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Writes the records which failed in a pipeline node to the dead-letter
+//! stream of the pipeline. The records are handed over to a background task so
+//! that the ingestion of the dead-letter stream never runs inside the pipeline
+//! which produced them.
+
+use chrono::Utc;
+use config::{
+    TIMESTAMP_COL_NAME,
+    cluster::LOCAL_NODE,
+    meta::stream::StreamType,
+    utils::json::{self, Value},
+};
+use once_cell::sync::Lazy;
+use proto::cluster_rpc;
+use tokio::sync::mpsc;
+
+use crate::{common::meta::ingestion::IngestionRequest, service};
+
+/// Number of batches which can wait for the background task, the batches
+/// above it are dropped.
+const QUEUE_SIZE: usize = 1024;
+
+struct DeadLetterBatch {
+    org_id: String,
+    stream_name: String,
+    records: Vec<Value>,
+}
+
+static QUEUE: Lazy<mpsc::Sender<DeadLetterBatch>> = Lazy::new(|| {
+    let (sender, receiver) = mpsc::channel(QUEUE_SIZE);
+    tokio::task::spawn(async move { run(receiver).await });
+    sender
+});
+
+/// Builds the dead-letter record of a failed record.
+pub fn build_record(
+    pipeline_id: &str,
+    pipeline_name: &str,
+    node_id: &str,
+    node_type: &str,
+    error: &str,
+    record: Option<&Value>,
+) -> Value {
+    json::json!({
+        TIMESTAMP_COL_NAME: Utc::now().timestamp_micros(),
+        "pipeline_id": pipeline_id,
+        "pipeline_name": pipeline_name,
+        "node_id": node_id,
+        "node_type": node_type,
+        "error": error,
+        "record": record.map(|r| r.to_string()).unwrap_or_default(),
+    })
+}
+
+/// Queues the records to be written to the dead-letter stream.
+pub fn send(org_id: &str, stream_name: &str, records: Vec<Value>) {
+    if records.is_empty() {
+        return;
+    }
+    let count = records.len();
+    if let Err(e) = QUEUE.try_send(DeadLetterBatch {
+        org_id: org_id.to_string(),
+        stream_name: stream_name.to_string(),
+        records,
+    }) {
+        log::error!(
+            "[Pipeline] dead-letter queue is full, dropped {count} records for {org_id}/{stream_name}: {e}"
+        );
+    }
+}
+
+async fn run(mut receiver: mpsc::Receiver<DeadLetterBatch>) {
+    while let Some(batch) = receiver.recv().await {
+        if let Err(e) = write(&batch).await {
+            log::error!(
+                "[Pipeline] failed to write {} records to dead-letter stream {}/{}: {e}",
+                batch.records.len(),
+                batch.org_id,
+                batch.stream_name
+            );
+        }
+    }
+}
+
+async fn write(batch: &DeadLetterBatch) -> Result<(), anyhow::Error> {
+    if LOCAL_NODE.is_ingester() {
+        let bytes = bytes::Bytes::from(json::to_vec(&batch.records)?);
+        let resp = service::logs::ingest::ingest(
+            0,
+            &batch.org_id,
+            &batch.stream_name,
+            IngestionRequest::JSON(&bytes),
+            "",
+            None,
+        )
+        .await?;
+        if resp.code != 200 {
+            return Err(anyhow::anyhow!("{}", resp.error.unwrap_or_default()));
+        }
+    } else {
+        // call gRPC ingestion service
+        let req = cluster_rpc::IngestionRequest {
+            org_id: batch.org_id.clone(),
+            stream_name: batch.stream_name.clone(),
+            stream_type: StreamType::Logs.to_string(),
+            data: Some(cluster_rpc::IngestionData::from(batch.records.clone())),
+            ingestion_type: Some(cluster_rpc::IngestionType::Json.into()),
+            metadata: None,
+        };
+        let resp = service::ingestion::ingestion_service::ingest(req).await?;
+        if resp.status_code != 200 {
+            return Err(anyhow::anyhow!("{}", resp.message));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_record() {
+        let record = json::json!({"log": "x"});
+        let dead_letter = build_record(
+            "pl1",
+            "my_pipeline",
+            "n1",
+            "function",
+            "boom",
+            Some(&record),
+        );
+        assert_eq!(dead_letter["node_id"], "n1");
+        assert_eq!(dead_letter["error"], "boom");
+        assert_eq!(dead_letter["record"], r#"{"log":"x"}"#);
+        assert!(dead_letter[TIMESTAMP_COL_NAME].as_i64().unwrap() > 0);
+    }
+}
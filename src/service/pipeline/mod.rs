@@ -13,19 +13,34 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use config::meta::{
-    pipeline::{Pipeline, PipelineList, components::PipelineSource},
-    search::SearchEventType,
-    stream::ListStreamParams,
+use config::{
+    ider,
+    meta::{
+        alerts::QueryType,
+        pipeline::{
+            DRY_RUN_MAX_SIZE, Pipeline, PipelineDryRunRequest, PipelineDryRunResponse,
+            PipelineList, components::PipelineSource,
+        },
+        search::{Query, Request, SearchEventType},
+        stream::ListStreamParams,
+    },
+    utils::json::Value,
 };
 
-use super::db::pipeline::{self, PipelineError};
+use super::{
+    alerts::derived_streams::DerivedStreamExt,
+    db::pipeline::{self, PipelineError},
+    search as SearchService,
+};
 use crate::common::{
     meta::authz::Authz,
     utils::auth::{remove_ownership, set_ownership},
 };
 
 pub mod batch_execution;
+mod dead_letter;
+mod nodes;
+pub mod stats;
 
 #[tracing::instrument(skip(pipeline))]
 pub async fn save_pipeline(mut pipeline: Pipeline) -> Result<(), PipelineError> {
@@ -159,8 +174,10 @@ pub async fn list_pipelines(
                     .unwrap()
                     .contains(&format!("pipeline:_all_{org_id}"))
         })
-        .collect();
-    Ok(PipelineList { list })
+        .collect::<Vec<_>>();
+    let ids = list.iter().map(|pl| pl.id.as_str()).collect::<Vec<_>>();
+    let stats = stats::get(&ids).await;
+    Ok(PipelineList { list, stats })
 }
 
 #[tracing::instrument]
@@ -203,6 +220,159 @@ pub async fn enable_pipeline(
     Ok(())
 }
 
+/// Runs the pipeline over the given records, or over the records its source
+/// produced in the given time range, without writing anything.
+pub async fn dry_run_pipeline(
+    org_id: &str,
+    user_email: &str,
+    req: PipelineDryRunRequest,
+) -> Result<PipelineDryRunResponse, PipelineError> {
+    let PipelineDryRunRequest {
+        mut pipeline,
+        mut records,
+        start_time,
+        end_time,
+        size,
+    } = req;
+    pipeline.org = org_id.to_string();
+    pipeline
+        .validate()
+        .map_err(|e| PipelineError::InvalidPipeline(e.to_string()))?;
+
+    let size = size.clamp(1, DRY_RUN_MAX_SIZE);
+    if records.is_empty() {
+        if start_time <= 0 || end_time <= start_time {
+            return Err(PipelineError::DryRun(
+                "either records or a valid time range must be given".to_string(),
+            ));
+        }
+        records = read_source(org_id, user_email, &pipeline, start_time, end_time, size).await?;
+    }
+    records.truncate(size);
+
+    let executable = batch_execution::ExecutablePipeline::new(&pipeline)
+        .await
+        .map_err(|e| PipelineError::DryRun(e.to_string()))?;
+    executable
+        .dry_run(org_id, records)
+        .await
+        .map_err(|e| PipelineError::DryRun(e.to_string()))
+}
+
+/// Reads the records of the pipeline source in the time range: the realtime
+/// source stream is searched, the scheduled query is evaluated like the
+/// scheduler does.
+async fn read_source(
+    org_id: &str,
+    user_email: &str,
+    pipeline: &Pipeline,
+    start_time: i64,
+    end_time: i64,
+    size: usize,
+) -> Result<Vec<Value>, PipelineError> {
+    match &pipeline.source {
+        PipelineSource::Realtime(stream_params) => {
+            #[cfg(not(feature = "enterprise"))]
+            if !crate::service::grants::is_allowed(
+                org_id,
+                user_email,
+                config::meta::grant::GrantResource::Stream,
+                Some(stream_params.stream_type),
+                &stream_params.stream_name,
+                config::meta::grant::GrantLevel::Read,
+            )
+            .await
+            {
+                return Err(PipelineError::DryRunForbidden(
+                    stream_params.stream_name.to_string(),
+                ));
+            }
+            let req = Request {
+                query: Query {
+                    sql: format!("SELECT * FROM \"{}\"", stream_params.stream_name),
+                    from: 0,
+                    size: size as i64,
+                    start_time,
+                    end_time,
+                    ..Default::default()
+                },
+                search_type: Some(SearchEventType::Other),
+                ..Default::default()
+            };
+            let user_id = Some(user_email.to_string());
+            let stream_type = stream_params.stream_type;
+            let mut resp = SearchService::search(&ider::uuid(), org_id, stream_type, user_id, &req)
+                .await
+                .map_err(|e| PipelineError::DryRun(e.to_string()))?;
+            SearchService::masking::mask_response(
+                org_id,
+                stream_type,
+                &req.query.sql,
+                Some(user_email),
+                &mut resp,
+            )
+            .await;
+            Ok(resp.hits)
+        }
+        PipelineSource::Scheduled(derived_stream) => {
+            let mut derived_stream = derived_stream.clone();
+            derived_stream.org_id = org_id.to_string();
+            #[cfg(not(feature = "enterprise"))]
+            crate::service::grants::check_query_streams(
+                org_id,
+                user_email,
+                derived_stream.stream_type,
+                None,
+                &derived_stream.query_condition,
+            )
+            .await
+            .map_err(|e| match e {
+                crate::service::grants::GrantError::ForbiddenStream(stream_name) => {
+                    PipelineError::DryRunForbidden(stream_name)
+                }
+                e => PipelineError::DryRun(e.to_string()),
+            })?;
+            // the scheduler runs the query without a user, the masking rules of
+            // the user apply to the records shown to them
+            let query = &derived_stream.query_condition;
+            let masker = match query.sql.as_deref() {
+                Some(sql) if query.query_type == QueryType::SQL => {
+                    let masker = SearchService::masking::get_query_masker(
+                        org_id,
+                        derived_stream.stream_type,
+                        sql,
+                        Some(user_email),
+                    )
+                    .await;
+                    if let Some(masker) = masker.as_ref() {
+                        masker
+                            .check_query(sql, query.vrl_function.is_some())
+                            .map_err(PipelineError::DryRun)?;
+                    }
+                    masker
+                }
+                _ => None,
+            };
+            let module_key = derived_stream.get_scheduler_module_key(&pipeline.name, &pipeline.id);
+            let results = derived_stream
+                .evaluate((Some(start_time), end_time), &module_key, None)
+                .await
+                .map_err(|e| PipelineError::DryRun(e.to_string()))?;
+            let mut records = results
+                .data
+                .unwrap_or_default()
+                .into_iter()
+                .take(size)
+                .map(Value::Object)
+                .collect::<Vec<_>>();
+            if let Some(masker) = masker.as_ref() {
+                masker.mask_hits(&mut records);
+            }
+            Ok(records)
+        }
+    }
+}
+
 #[tracing::instrument]
 pub async fn delete_pipeline(pipeline_id: &str) -> Result<(), PipelineError> {
     let Ok(existing_pipeline) = pipeline::get_by_id(pipeline_id).await else {
@@ -222,6 +392,7 @@ pub async fn delete_pipeline(pipeline_id: &str) -> Result<(), PipelineError> {
     }
 
     pipeline::delete(pipeline_id).await?;
+    stats::delete(pipeline_id).await;
     remove_ownership(
         &existing_pipeline.org,
         "pipelines",
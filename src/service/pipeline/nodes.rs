@@ -0,0 +1,399 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Built-in transform nodes which run without VRL: parse, field transform,
+//! sample, rate limit and hash route. All of them work on flattened records.
+
+use std::collections::HashMap;
+
+use anyhow::{Result, anyhow};
+use config::{
+    meta::pipeline::{
+        Pipeline,
+        components::{
+            FieldAction, FieldTransformParams, NodeData, ParseFormat, ParseParams, SampleMode,
+        },
+    },
+    utils::{
+        grok::Pattern,
+        hash::{Sum64, gxhash},
+        json::{self, Map, Value, get_string_value},
+    },
+};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+
+/// Number of limiter keys above which the expired windows are pruned.
+const RATE_LIMIT_PRUNE_THRESHOLD: usize = 100_000;
+
+/// pipeline_id/node_id/key -> (window index, count)
+static RATE_LIMITS: Lazy<Mutex<HashMap<String, (u64, u64)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// What a transform node does with a record.
+#[derive(Debug, PartialEq)]
+pub enum Output {
+    /// Send to all the children, the bool tells if the record is still flattened
+    Forward(Value, bool),
+    /// Send to the child at the index only
+    Route(usize, Value),
+    Drop,
+    /// Send to all the children unchanged, and report the error
+    Error(Value, String),
+}
+
+/// Compiles the grok and regex patterns of all the ParseNodes of the pipeline.
+/// Returns a map of node_id -> Pattern
+pub fn compile_patterns(pipeline: &Pipeline) -> Result<HashMap<String, Pattern>> {
+    let mut patterns = HashMap::new();
+    for node in &pipeline.nodes {
+        if let NodeData::Parse(params) = &node.data {
+            let pattern = match (params.format, params.pattern.as_deref()) {
+                (ParseFormat::Grok, Some(pattern)) => Pattern::grok(pattern),
+                (ParseFormat::Regex, Some(pattern)) => Pattern::regex(pattern),
+                _ => continue,
+            }
+            .map_err(|e| anyhow!("ParseNode {}: {e}", node.id))?;
+            patterns.insert(node.get_node_id(), pattern);
+        }
+    }
+    Ok(patterns)
+}
+
+/// Applies a transform node to a flattened record.
+pub fn apply(
+    limiter_prefix: &str,
+    node_data: &NodeData,
+    pattern: Option<&Pattern>,
+    num_children: usize,
+    record: Value,
+) -> Output {
+    let Value::Object(mut map) = record else {
+        return Output::Error(record, "record is not an object".to_string());
+    };
+    match node_data {
+        NodeData::Parse(params) => match parse(params, pattern, &mut map) {
+            Ok(()) => Output::Forward(Value::Object(map), false),
+            Err(_) if params.ignore_failure => Output::Forward(Value::Object(map), true),
+            Err(e) => Output::Error(Value::Object(map), e),
+        },
+        NodeData::FieldTransform(params) => {
+            transform_fields(params, &mut map);
+            Output::Forward(Value::Object(map), true)
+        }
+        NodeData::Sample(params) => {
+            let keep = match params.mode {
+                SampleMode::Random => rand::random::<f64>() * 100.0 < params.percent,
+                SampleMode::Hash => {
+                    hash_key(&params.key_fields, &map) % 10000 < (params.percent * 100.0) as u64
+                }
+            };
+            if keep {
+                Output::Forward(Value::Object(map), true)
+            } else {
+                Output::Drop
+            }
+        }
+        NodeData::RateLimit(params) => {
+            let key = format!("{limiter_prefix}/{}", join_key(&params.key_fields, &map));
+            let now = chrono::Utc::now().timestamp() as u64;
+            if allow(key, now / params.window, params.limit) {
+                Output::Forward(Value::Object(map), true)
+            } else {
+                Output::Drop
+            }
+        }
+        NodeData::HashRoute(params) => {
+            if num_children == 0 {
+                return Output::Drop;
+            }
+            let child = hash_key(&params.key_fields, &map) % num_children as u64;
+            Output::Route(child as usize, Value::Object(map))
+        }
+        _ => Output::Forward(Value::Object(map), true),
+    }
+}
+
+fn parse(
+    params: &ParseParams,
+    pattern: Option<&Pattern>,
+    record: &mut Map<String, Value>,
+) -> Result<(), String> {
+    let text = match record.get(&params.field) {
+        Some(Value::String(text)) => text,
+        Some(_) => return Err(format!("field {} is not a string", params.field)),
+        None => return Err(format!("field {} not found", params.field)),
+    };
+    let parsed = match params.format {
+        ParseFormat::Json => match json::from_str::<Value>(text) {
+            Ok(Value::Object(parsed)) => parsed,
+            Ok(_) => return Err(format!("field {} is not a json object", params.field)),
+            Err(e) => return Err(format!("field {} is not valid json: {e}", params.field)),
+        },
+        ParseFormat::Logfmt => parse_logfmt(text),
+        ParseFormat::Grok | ParseFormat::Regex => {
+            let Some(pattern) = pattern else {
+                return Err("pattern is not compiled".to_string());
+            };
+            match pattern.parse(text) {
+                Some(parsed) => parsed,
+                None => return Err(format!("field {} doesn't match the pattern", params.field)),
+            }
+        }
+    };
+    if params.drop_source {
+        record.remove(&params.field);
+    }
+    let prefix = params.target_prefix.as_deref().unwrap_or_default();
+    for (key, value) in parsed {
+        if prefix.is_empty() {
+            record.insert(key, value);
+        } else {
+            record.insert(format!("{prefix}{key}"), value);
+        }
+    }
+    Ok(())
+}
+
+/// Parses `key=value key2="quoted value"` pairs, a key without value gets `true`.
+fn parse_logfmt(text: &str) -> Map<String, Value> {
+    let mut out = Map::new();
+    let mut chars = text.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let mut key = String::new();
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '=') {
+            key.push(c);
+        }
+        if key.is_empty() {
+            // skip a stray `=` or stop at the end
+            if chars.next().is_none() {
+                break;
+            }
+            continue;
+        }
+        if chars.next_if_eq(&'=').is_none() {
+            out.insert(key, Value::Bool(true));
+            continue;
+        }
+        let mut value = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => {
+                        if let Some(escaped) = chars.next() {
+                            value.push(escaped);
+                        }
+                    }
+                    '"' => break,
+                    c => value.push(c),
+                }
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                value.push(c);
+            }
+        }
+        out.insert(key, Value::String(value));
+    }
+    out
+}
+
+fn transform_fields(params: &FieldTransformParams, record: &mut Map<String, Value>) {
+    match params.action {
+        FieldAction::Drop => {
+            for field in params.fields.iter() {
+                record.remove(field);
+            }
+        }
+        FieldAction::Keep => record.retain(|k, _| params.fields.contains(k)),
+        FieldAction::Rename => {
+            for (from, to) in params.renames.iter() {
+                if let Some(value) = record.remove(from) {
+                    record.insert(to.to_string(), value);
+                }
+            }
+        }
+    }
+}
+
+fn join_key(fields: &[String], record: &Map<String, Value>) -> String {
+    fields
+        .iter()
+        .map(|f| record.get(f).map(get_string_value).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\u{1f}")
+}
+
+fn hash_key(fields: &[String], record: &Map<String, Value>) -> u64 {
+    gxhash::new().sum64(&join_key(fields, record))
+}
+
+fn allow(key: String, window: u64, limit: u64) -> bool {
+    let mut limits = RATE_LIMITS.lock();
+    if limits.len() > RATE_LIMIT_PRUNE_THRESHOLD {
+        limits.retain(|_, (w, _)| *w >= window);
+    }
+    let entry = limits.entry(key).or_insert((window, 0));
+    if entry.0 != window {
+        *entry = (window, 0);
+    }
+    if entry.1 >= limit {
+        return false;
+    }
+    entry.1 += 1;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use config::meta::pipeline::components::{HashRouteParams, RateLimitParams, SampleParams};
+
+    use super::*;
+
+    fn parse_params(format: ParseFormat, pattern: Option<&str>) -> ParseParams {
+        ParseParams {
+            field: "log".to_string(),
+            format,
+            pattern: pattern.map(|p| p.to_string()),
+            target_prefix: None,
+            drop_source: true,
+            ignore_failure: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_logfmt() {
+        let parsed = parse_logfmt(r#"level=info msg="user \"a\" logged in" took=12ms  debug"#);
+        assert_eq!(
+            Value::Object(parsed),
+            json::json!({
+                "level": "info",
+                "msg": "user \"a\" logged in",
+                "took": "12ms",
+                "debug": true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_apply_parse() {
+        let node = NodeData::Parse(parse_params(ParseFormat::Json, None));
+        let record = json::json!({"log": r#"{"a": {"b": 1}}"#, "c": 2});
+        assert_eq!(
+            apply("p/n", &node, None, 1, record),
+            Output::Forward(json::json!({"a": {"b": 1}, "c": 2}), false)
+        );
+
+        let mut params = parse_params(ParseFormat::Grok, Some("%{WORD:method} %{INT:code:int}"));
+        params.target_prefix = Some("http_".to_string());
+        let pattern = Pattern::grok(params.pattern.as_deref().unwrap()).unwrap();
+        let node = NodeData::Parse(params);
+        let record = json::json!({"log": "GET 200"});
+        assert_eq!(
+            apply("p/n", &node, Some(&pattern), 1, record),
+            Output::Forward(json::json!({"http_method": "GET", "http_code": 200}), false)
+        );
+        let record = json::json!({"log": "nope"});
+        assert!(matches!(
+            apply("p/n", &node, Some(&pattern), 1, record),
+            Output::Error(..)
+        ));
+    }
+
+    #[test]
+    fn test_apply_field_transform() {
+        let record = json::json!({"a": 1, "b": 2, "c": 3});
+        let node = NodeData::FieldTransform(FieldTransformParams {
+            action: FieldAction::Keep,
+            fields: vec!["a".to_string(), "c".to_string()],
+            renames: HashMap::new(),
+        });
+        assert_eq!(
+            apply("p/n", &node, None, 1, record.clone()),
+            Output::Forward(json::json!({"a": 1, "c": 3}), true)
+        );
+        let node = NodeData::FieldTransform(FieldTransformParams {
+            action: FieldAction::Rename,
+            fields: vec![],
+            renames: HashMap::from([("a".to_string(), "x".to_string())]),
+        });
+        assert_eq!(
+            apply("p/n", &node, None, 1, record),
+            Output::Forward(json::json!({"x": 1, "b": 2, "c": 3}), true)
+        );
+    }
+
+    #[test]
+    fn test_apply_sample_and_route() {
+        let record = json::json!({"service": "api"});
+        let sample = |percent| {
+            NodeData::Sample(SampleParams {
+                mode: SampleMode::Hash,
+                percent,
+                key_fields: vec!["service".to_string()],
+            })
+        };
+        assert_eq!(
+            apply("p/n", &sample(0.0), None, 1, record.clone()),
+            Output::Drop
+        );
+        assert!(matches!(
+            apply("p/n", &sample(100.0), None, 1, record.clone()),
+            Output::Forward(..)
+        ));
+
+        let node = NodeData::HashRoute(HashRouteParams {
+            key_fields: vec!["service".to_string()],
+        });
+        let Output::Route(first, _) = apply("p/n", &node, None, 3, record.clone()) else {
+            panic!("expected a route");
+        };
+        assert!(first < 3);
+        assert_eq!(
+            apply("p/n", &node, None, 3, record.clone()),
+            Output::Route(first, record)
+        );
+    }
+
+    #[test]
+    fn test_apply_rate_limit() {
+        let node = NodeData::RateLimit(RateLimitParams {
+            key_fields: vec!["service".to_string()],
+            limit: 1,
+            window: u64::MAX,
+        });
+        let prefix = "test_apply_rate_limit/n";
+        let api = json::json!({"service": "api"});
+        assert!(matches!(
+            apply(prefix, &node, None, 1, api.clone()),
+            Output::Forward(..)
+        ));
+        assert_eq!(apply(prefix, &node, None, 1, api), Output::Drop);
+        let web = json::json!({"service": "web"});
+        assert!(matches!(
+            apply(prefix, &node, None, 1, web),
+            Output::Forward(..)
+        ));
+    }
+
+    #[test]
+    fn test_allow() {
+        let key = "test_allow/n/k".to_string();
+        assert!(allow(key.clone(), 1, 1));
+        assert!(!allow(key.clone(), 1, 1));
+        assert!(allow(key, 2, 1));
+    }
+}
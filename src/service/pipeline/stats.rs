@@ -0,0 +1,185 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-node record counters of the pipelines. Every node of the cluster keeps
+//! the counters since its last flush in memory and adds them to its own db
+//! entry, the pipeline list API sums the entries of all the nodes. A deleted
+//! pipeline is forgotten by every node, so no node writes its entry again.
+
+use std::collections::{HashMap, HashSet};
+
+use config::{cluster::LOCAL_NODE, meta::pipeline::PipelineNodeStats, metrics, utils::json};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+
+use crate::service::db;
+
+const STATS_KEY: &str = "/pipeline_stats/";
+
+/// pipeline_id -> node_id -> stats since the last flush
+static PENDING: Lazy<Mutex<HashMap<String, HashMap<String, PipelineNodeStats>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// The pipelines deleted since the last flush, whose entries of this node are
+/// removed by the next flush
+static DELETED: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// Records the stats of one node for one batch.
+pub fn record(
+    org_id: &str,
+    pipeline_id: &str,
+    node_id: &str,
+    node_type: &str,
+    stats: &PipelineNodeStats,
+) {
+    if stats.is_empty() {
+        return;
+    }
+    for (status, value) in [
+        ("processed", stats.processed),
+        ("error", stats.errors),
+        ("dropped", stats.dropped),
+    ] {
+        if value > 0 {
+            metrics::PIPELINE_NODE_RECORDS
+                .with_label_values(&[org_id, pipeline_id, node_id, node_type, status])
+                .inc_by(value);
+        }
+    }
+    PENDING
+        .lock()
+        .entry(pipeline_id.to_string())
+        .or_default()
+        .entry(node_id.to_string())
+        .or_default()
+        .add(stats);
+}
+
+/// Adds the pending stats of this node to its db entries.
+pub async fn flush() -> Result<(), anyhow::Error> {
+    let deleted = std::mem::take(&mut *DELETED.lock());
+    let mut pending = std::mem::take(&mut *PENDING.lock());
+    for pipeline_id in deleted {
+        // a flush which raced with the delete could have written the entry again
+        pending.remove(&pipeline_id);
+        let key = format!("{STATS_KEY}{pipeline_id}/{}", LOCAL_NODE.uuid);
+        db::delete_if_exists(&key, false, db::NO_NEED_WATCH).await?;
+    }
+    for (pipeline_id, node_stats) in pending {
+        let key = format!("{STATS_KEY}{pipeline_id}/{}", LOCAL_NODE.uuid);
+        let mut saved: HashMap<String, PipelineNodeStats> = match db::get(&key).await {
+            Ok(val) => json::from_slice(&val).unwrap_or_default(),
+            Err(_) => HashMap::new(),
+        };
+        for (node_id, stats) in node_stats {
+            saved.entry(node_id).or_default().add(&stats);
+        }
+        db::put(&key, json::to_vec(&saved)?.into(), db::NO_NEED_WATCH, None).await?;
+    }
+    Ok(())
+}
+
+/// Returns the stats of the given pipelines summed over all the nodes,
+/// pipeline_id -> node_id -> stats
+pub async fn get(pipeline_ids: &[&str]) -> HashMap<String, HashMap<String, PipelineNodeStats>> {
+    let mut result: HashMap<String, HashMap<String, PipelineNodeStats>> = HashMap::new();
+    let entries = match db::list(STATS_KEY).await {
+        Ok(entries) => entries,
+        Err(e) => {
+            log::error!("[Pipeline] failed to list pipeline stats: {e}");
+            return result;
+        }
+    };
+    for (key, val) in entries {
+        let Some((pipeline_id, _node)) = key
+            .strip_prefix(STATS_KEY)
+            .and_then(|rest| rest.split_once('/'))
+        else {
+            continue;
+        };
+        if !pipeline_ids.contains(&pipeline_id) {
+            continue;
+        }
+        let Ok(node_stats) = json::from_slice::<HashMap<String, PipelineNodeStats>>(&val) else {
+            continue;
+        };
+        let pipeline_stats = result.entry(pipeline_id.to_string()).or_default();
+        for (node_id, stats) in node_stats {
+            pipeline_stats.entry(node_id).or_default().add(&stats);
+        }
+    }
+    result
+}
+
+/// Drops the pending stats of a pipeline deleted on any node of the cluster.
+pub fn forget(pipeline_id: &str) {
+    PENDING.lock().remove(pipeline_id);
+    DELETED.lock().insert(pipeline_id.to_string());
+}
+
+/// Removes the stats of a deleted pipeline.
+pub async fn delete(pipeline_id: &str) {
+    forget(pipeline_id);
+    let prefix = format!("{STATS_KEY}{pipeline_id}/");
+    if let Err(e) = db::delete_if_exists(&prefix, true, db::NO_NEED_WATCH).await {
+        log::error!("[Pipeline] failed to delete stats of pipeline {pipeline_id}: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let stats = PipelineNodeStats {
+            processed: 10,
+            errors: 2,
+            dropped: 3,
+        };
+        record("default", "test_record_pl", "n1", "function", &stats);
+        record("default", "test_record_pl", "n1", "function", &stats);
+        record(
+            "default",
+            "test_record_pl",
+            "n2",
+            "stream",
+            &PipelineNodeStats::default(),
+        );
+        let pending = PENDING.lock();
+        let node_stats = pending.get("test_record_pl").unwrap();
+        assert_eq!(node_stats.len(), 1);
+        assert_eq!(
+            node_stats.get("n1").unwrap(),
+            &PipelineNodeStats {
+                processed: 20,
+                errors: 4,
+                dropped: 6,
+            }
+        );
+    }
+
+    #[test]
+    fn test_forget() {
+        let stats = PipelineNodeStats {
+            processed: 1,
+            ..Default::default()
+        };
+        record("default", "test_forget_pl", "n1", "function", &stats);
+        forget("test_forget_pl");
+        assert!(!PENDING.lock().contains_key("test_forget_pl"));
+        assert!(DELETED.lock().contains("test_forget_pl"));
+    }
+}
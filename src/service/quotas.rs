@@ -0,0 +1,429 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-org data volume quotas. Every node counts the ingested and scanned bytes
+//! of the orgs with a quota and adds them to its own db entry of the UTC day on
+//! each sync, then reloads the totals of the cluster. The daily limits are thus
+//! enforced with the delay of one sync, the concurrent queries are counted per
+//! querier.
+
+use std::collections::HashMap;
+
+use chrono::{Datelike, Utc};
+use config::{
+    cluster::LOCAL_NODE,
+    meta::{
+        destinations::{DestinationType, Module},
+        quota::{DailyUsage, OrgQuota, OrgQuotaUsage, QuotaLimit, QuotaStatus, QuotaUsage},
+    },
+    utils::json,
+};
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, RwLock};
+
+use crate::{
+    common::{infra::config::ORG_QUOTAS, utils::auth::is_root_user},
+    service::{alerts::alert::send_to_destination, db},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum QuotaError {
+    #[error("No quota is set for organization {0}")]
+    NotFound(String),
+    #[error("Invalid quota: {0}")]
+    InvalidQuota(String),
+    #[error("Only root can change the quotas of {0}")]
+    Forbidden(String),
+    #[error("{0}")]
+    InfraError(#[from] anyhow::Error),
+}
+
+#[derive(Default)]
+struct Totals {
+    day: u32,
+    /// org_id -> usage of the day in the cluster
+    usage: HashMap<String, DailyUsage>,
+    /// org_id -> compressed bytes of all the streams
+    stored_bytes: HashMap<String, u64>,
+}
+
+/// (day, org_id) -> usage of this node since the last sync
+static PENDING: Lazy<Mutex<HashMap<(u32, String), DailyUsage>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// The cluster usage as of the last sync
+static TOTALS: Lazy<RwLock<Totals>> = Lazy::new(Default::default);
+
+/// org_id -> searches running on this node
+static RUNNING: Lazy<Mutex<HashMap<String, u64>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub async fn get(org_id: &str) -> Result<OrgQuota, QuotaError> {
+    db::quotas::get(org_id)
+        .await
+        .map_err(|_| QuotaError::NotFound(org_id.to_string()))
+}
+
+/// Sets the quota of an org, only root does so the org admins can't raise
+/// their own budget.
+pub async fn set(org_id: &str, user_email: &str, quota: OrgQuota) -> Result<OrgQuota, QuotaError> {
+    if !is_root_user(user_email) {
+        return Err(QuotaError::Forbidden(org_id.to_string()));
+    }
+    quota.validate().map_err(QuotaError::InvalidQuota)?;
+    for name in quota.alert_destinations.iter() {
+        if db::alerts::destinations::get(org_id, name).await.is_err() {
+            return Err(QuotaError::InvalidQuota(format!(
+                "alert destination {name} not found"
+            )));
+        }
+    }
+    db::quotas::set(org_id, &quota).await?;
+    Ok(quota)
+}
+
+pub async fn delete(org_id: &str, user_email: &str) -> Result<(), QuotaError> {
+    if !is_root_user(user_email) {
+        return Err(QuotaError::Forbidden(org_id.to_string()));
+    }
+    get(org_id).await?;
+    db::quotas::delete(org_id).await?;
+    Ok(())
+}
+
+/// Counts the bytes ingested into an org.
+pub fn record_ingestion(org_id: &str, bytes: u64) {
+    record(org_id, |usage| usage.ingested_bytes += bytes);
+}
+
+/// Counts the bytes scanned by a search of an org.
+pub fn record_query(org_id: &str, bytes: u64) {
+    record(org_id, |usage| usage.query_scanned_bytes += bytes);
+}
+
+fn record(org_id: &str, add: impl FnOnce(&mut DailyUsage)) {
+    if !ORG_QUOTAS.contains_key(org_id) {
+        return;
+    }
+    add(PENDING
+        .lock()
+        .entry((today(), org_id.to_string()))
+        .or_default());
+}
+
+/// Rejects the ingestion of an org above its hard limits.
+pub fn check_ingestion(org_id: &str) -> Result<(), String> {
+    let Some(quota) = ORG_QUOTAS.get(org_id).map(|q| q.value().clone()) else {
+        return Ok(());
+    };
+    let (usage, stored_bytes) = current_usage(org_id);
+    check_limit(
+        org_id,
+        "bytes ingested today",
+        &quota.ingested_bytes_per_day,
+        usage.ingested_bytes,
+    )?;
+    check_limit(org_id, "bytes stored", &quota.stored_bytes, stored_bytes)
+}
+
+/// Counts a running search of an org, it is released when dropped.
+#[must_use]
+pub struct QueryPermit {
+    org_id: Option<String>,
+}
+
+impl Drop for QueryPermit {
+    fn drop(&mut self) {
+        let Some(org_id) = self.org_id.take() else {
+            return;
+        };
+        let mut running = RUNNING.lock();
+        if let Some(count) = running.get_mut(&org_id) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                running.remove(&org_id);
+            }
+        }
+    }
+}
+
+/// Admits a search of an org unless it is above its hard limits.
+pub fn admit_query(org_id: &str) -> Result<QueryPermit, String> {
+    let Some(quota) = ORG_QUOTAS.get(org_id).map(|q| q.value().clone()) else {
+        return Ok(QueryPermit { org_id: None });
+    };
+    let (usage, _) = current_usage(org_id);
+    check_limit(
+        org_id,
+        "bytes scanned today",
+        &quota.query_scanned_bytes_per_day,
+        usage.query_scanned_bytes,
+    )?;
+    let mut running = RUNNING.lock();
+    let count = running.entry(org_id.to_string()).or_default();
+    if let Some(hard) = quota.concurrent_queries.hard
+        && *count >= hard
+    {
+        return Err(format!(
+            "Quota exceeded for organization [{org_id}]: {count} of {hard} concurrent queries running"
+        ));
+    }
+    *count += 1;
+    Ok(QueryPermit {
+        org_id: Some(org_id.to_string()),
+    })
+}
+
+fn check_limit(org_id: &str, name: &str, limit: &QuotaLimit, usage: u64) -> Result<(), String> {
+    match limit.hard {
+        Some(hard) if usage >= hard => Err(format!(
+            "Quota exceeded for organization [{org_id}]: {usage} of {hard} {name}"
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the usage of the day and the stored bytes of an org, including
+/// what this node counted since the last sync.
+fn current_usage(org_id: &str) -> (DailyUsage, u64) {
+    let day = today();
+    let mut usage = DailyUsage::default();
+    let mut stored_bytes = 0;
+    {
+        let totals = TOTALS.read();
+        if totals.day == day
+            && let Some(total) = totals.usage.get(org_id)
+        {
+            usage.add(total);
+        }
+        if let Some(bytes) = totals.stored_bytes.get(org_id) {
+            stored_bytes = *bytes;
+        }
+    }
+    if let Some(pending) = PENDING.lock().get(&(day, org_id.to_string())) {
+        usage.add(pending);
+    }
+    (usage, stored_bytes)
+}
+
+/// Returns the usage of an org against its quota, the concurrent queries are
+/// the ones running on this node.
+pub fn usage(org_id: &str) -> OrgQuotaUsage {
+    let quota = ORG_QUOTAS
+        .get(org_id)
+        .map(|q| q.value().clone())
+        .unwrap_or_default();
+    let (usage, stored_bytes) = current_usage(org_id);
+    let running = RUNNING.lock().get(org_id).copied().unwrap_or_default();
+    OrgQuotaUsage {
+        day: today(),
+        ingested_bytes_per_day: QuotaUsage::new(
+            usage.ingested_bytes,
+            &quota.ingested_bytes_per_day,
+        ),
+        stored_bytes: QuotaUsage::new(stored_bytes, &quota.stored_bytes),
+        query_scanned_bytes_per_day: QuotaUsage::new(
+            usage.query_scanned_bytes,
+            &quota.query_scanned_bytes_per_day,
+        ),
+        concurrent_queries: QuotaUsage::new(running, &quota.concurrent_queries),
+    }
+}
+
+/// Flushes the usage of this node and reloads the usage of the cluster. The
+/// compactors also prune the old days and notify the orgs near their limits.
+pub async fn sync() -> Result<(), anyhow::Error> {
+    let pending = std::mem::take(&mut *PENDING.lock());
+    let mut pending = pending.into_iter();
+    while let Some(((day, org_id), usage)) = pending.next() {
+        if let Err(e) = db::quotas::add_usage(day, &org_id, &usage).await {
+            // keep the usage which was not written for the next sync
+            let mut current = PENDING.lock();
+            for (key, usage) in std::iter::once(((day, org_id), usage)).chain(pending) {
+                current.entry(key).or_default().add(&usage);
+            }
+            return Err(e);
+        }
+    }
+
+    let day = today();
+    let usage = db::quotas::list_usage(day).await?.into_iter().collect();
+    let mut stored_bytes: HashMap<String, u64> = HashMap::new();
+    for item in infra::cache::stats::get_stats().iter() {
+        // the stats key is org_id/stream_type/stream_name
+        let Some((org_id, _)) = item.key().split_once('/') else {
+            continue;
+        };
+        if ORG_QUOTAS.contains_key(org_id) {
+            *stored_bytes.entry(org_id.to_string()).or_default() +=
+                item.value().compressed_size as u64;
+        }
+    }
+    *TOTALS.write() = Totals {
+        day,
+        usage,
+        stored_bytes,
+    };
+
+    if LOCAL_NODE.is_compactor() {
+        db::quotas::prune_usage(day).await?;
+        notify(day).await;
+    }
+    Ok(())
+}
+
+/// Notifies the alert destinations of the quotas once per status change, the
+/// daily quotas once per day and status.
+async fn notify(day: u32) {
+    let quotas = ORG_QUOTAS
+        .iter()
+        .filter(|q| !q.value().alert_destinations.is_empty())
+        .map(|q| (q.key().clone(), q.value().clone()))
+        .collect::<Vec<_>>();
+    for (org_id, quota) in quotas {
+        let (usage, stored_bytes) = current_usage(&org_id);
+        for (name, limit, value, period) in [
+            (
+                "ingested_bytes_per_day",
+                &quota.ingested_bytes_per_day,
+                usage.ingested_bytes,
+                day.to_string(),
+            ),
+            (
+                "stored_bytes",
+                &quota.stored_bytes,
+                stored_bytes,
+                "all".to_string(),
+            ),
+            (
+                "query_scanned_bytes_per_day",
+                &quota.query_scanned_bytes_per_day,
+                usage.query_scanned_bytes,
+                day.to_string(),
+            ),
+        ] {
+            let status = limit.status(value);
+            let state = format!("{period}/{status}");
+            let last_state = db::quotas::get_alert_state(&org_id, name).await;
+            if last_state.as_deref() == Some(state.as_str())
+                || (status == QuotaStatus::Ok && last_state.is_none())
+            {
+                continue;
+            }
+            if status != QuotaStatus::Ok {
+                let msg = QuotaUsage::new(value, limit);
+                send_alerts(&org_id, name, &msg, &quota.alert_destinations).await;
+            }
+            if let Err(e) = db::quotas::set_alert_state(&org_id, name, &state).await {
+                log::error!("[QUOTA] failed to save the alert state of {org_id}/{name}: {e}");
+            }
+        }
+    }
+}
+
+async fn send_alerts(org_id: &str, quota_name: &str, usage: &QuotaUsage, destinations: &[String]) {
+    let title = format!(
+        "Quota {} for organization {org_id}: {quota_name}",
+        usage.status
+    );
+    for name in destinations {
+        let dest_type = match db::alerts::destinations::get(org_id, name).await {
+            Ok(dest) => match dest.module {
+                Module::Alert {
+                    destination_type, ..
+                } => destination_type,
+                _ => continue,
+            },
+            Err(e) => {
+                log::error!("[QUOTA] alert destination {org_id}/{name} not found: {e}");
+                continue;
+            }
+        };
+        let msg = match &dest_type {
+            DestinationType::Http(_) => json::json!({
+                "org_id": org_id,
+                "quota": quota_name,
+                "status": usage.status,
+                "usage": usage.usage,
+                "soft_limit": usage.limit.soft,
+                "hard_limit": usage.limit.hard,
+            })
+            .to_string(),
+            _ => format!(
+                "{title}, usage {} of the soft limit {:?} and the hard limit {:?}",
+                usage.usage, usage.limit.soft, usage.limit.hard
+            ),
+        };
+        if let Err(e) = send_to_destination(&dest_type, &title, msg).await {
+            log::error!("[QUOTA] failed to notify {org_id}/{name}: {e}");
+        }
+    }
+}
+
+/// The UTC day as a number, e.g. 20250131
+fn today() -> u32 {
+    let now = Utc::now();
+    now.year() as u32 * 10000 + now.month() * 100 + now.day()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admit_query() {
+        let org_id = "test_admit_query_org";
+        ORG_QUOTAS.insert(
+            org_id.to_string(),
+            OrgQuota {
+                concurrent_queries: QuotaLimit {
+                    soft: None,
+                    hard: Some(1),
+                },
+                ..Default::default()
+            },
+        );
+        let permit = admit_query(org_id).unwrap();
+        assert!(admit_query(org_id).is_err());
+        drop(permit);
+        assert!(admit_query(org_id).is_ok());
+        assert!(RUNNING.lock().get(org_id).is_none());
+        assert!(admit_query("test_admit_query_other").is_ok());
+        ORG_QUOTAS.remove(org_id);
+    }
+
+    #[test]
+    fn test_check_ingestion() {
+        let org_id = "test_check_ingestion_org";
+        ORG_QUOTAS.insert(
+            org_id.to_string(),
+            OrgQuota {
+                ingested_bytes_per_day: QuotaLimit {
+                    soft: Some(50),
+                    hard: Some(100),
+                },
+                ..Default::default()
+            },
+        );
+        record_ingestion(org_id, 60);
+        assert!(check_ingestion(org_id).is_ok());
+        assert_eq!(
+            usage(org_id).ingested_bytes_per_day.status,
+            QuotaStatus::Warning
+        );
+        record_ingestion(org_id, 40);
+        assert!(check_ingestion(org_id).is_err());
+        ORG_QUOTAS.remove(org_id);
+    }
+}
@@ -0,0 +1,662 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! SCIM 2.0 provisioning (RFC 7644) of the open source build. The identity
+//! provider pushes the users and groups of an org, the groups get their roles
+//! from the OIDC group mapping and deactivated users lose access to the org.
+
+use std::collections::HashSet;
+
+use config::{
+    get_config, ider,
+    meta::{
+        scim::{
+            GROUP_SCHEMA, ScimGroup, ScimListResponse, ScimMember, ScimMeta, ScimPatchOp,
+            ScimPatchOperation, ScimUser, USER_SCHEMA,
+        },
+        user::{DBUser, UserOrg, UserRole},
+    },
+    utils::{
+        json::{self, Value},
+        rand::generate_random_string,
+    },
+};
+
+use crate::{
+    common::utils::auth::{is_root_user, is_valid_email},
+    service::{db, grants, oidc, users},
+};
+
+/// Page size of the list requests without a count.
+const DEFAULT_COUNT: usize = 100;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ScimError {
+    #[error("SCIM provisioning is not enabled")]
+    Disabled,
+    #[error("Only admins can provision users")]
+    Forbidden,
+    #[error("Resource {0} not found")]
+    NotFound(String),
+    #[error("{0} already exists")]
+    Conflict(String),
+    #[error("Unsupported filter: {0}")]
+    InvalidFilter(String),
+    #[error("Invalid value: {0}")]
+    InvalidValue(String),
+    #[error("{0}")]
+    InfraError(#[from] anyhow::Error),
+}
+
+/// Only root and the org admins provision, usually with an API token.
+pub async fn check_access(org_id: &str, user_email: &str) -> Result<(), ScimError> {
+    if !get_config().oidc.scim_enabled {
+        return Err(ScimError::Disabled);
+    }
+    if !grants::can_manage(org_id, user_email).await {
+        return Err(ScimError::Forbidden);
+    }
+    Ok(())
+}
+
+pub async fn list_users(
+    org_id: &str,
+    filter: Option<&str>,
+    start_index: Option<usize>,
+    count: Option<usize>,
+) -> Result<ScimListResponse<ScimUser>, ScimError> {
+    let filter = filter
+        .map(|f| parse_filter(f, &["id", "username", "externalid"]))
+        .transpose()?;
+    let groups = db::scim::list_groups(org_id).await?;
+    let mut users = db::scim::list_users(org_id)
+        .await?
+        .into_iter()
+        .filter(|user| {
+            filter
+                .as_ref()
+                .is_none_or(|(attr, value)| match attr.as_str() {
+                    "externalid" => user.external_id.as_deref() == Some(value.as_str()),
+                    "username" => user.user_name.eq_ignore_ascii_case(value),
+                    _ => user.id.eq_ignore_ascii_case(value),
+                })
+        })
+        .map(|user| user_resource(user, &groups))
+        .collect::<Vec<_>>();
+    users.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(ScimListResponse::new(
+        users,
+        start_index.unwrap_or(1),
+        count.unwrap_or(DEFAULT_COUNT),
+    ))
+}
+
+pub async fn get_user(org_id: &str, id: &str) -> Result<ScimUser, ScimError> {
+    let user = find_user(org_id, id).await?;
+    let groups = db::scim::list_groups(org_id).await?;
+    Ok(user_resource(user, &groups))
+}
+
+pub async fn create_user(org_id: &str, mut user: ScimUser) -> Result<ScimUser, ScimError> {
+    let email = user.email();
+    if !is_valid_email(&email) {
+        return Err(ScimError::InvalidValue(format!(
+            "{email} is not a valid email"
+        )));
+    }
+    if is_root_user(&email) || db::scim::get_user(org_id, &email).await.is_ok() {
+        return Err(ScimError::Conflict(email));
+    }
+    user.id = email;
+    let groups = db::scim::list_groups(org_id).await?;
+    save_user(org_id, user, &groups).await
+}
+
+pub async fn replace_user(
+    org_id: &str,
+    id: &str,
+    mut user: ScimUser,
+) -> Result<ScimUser, ScimError> {
+    let existing = find_user(org_id, id).await?;
+    if user.email() != existing.id {
+        return Err(ScimError::InvalidValue(
+            "the email of a user can't be changed".to_string(),
+        ));
+    }
+    user.id = existing.id;
+    let groups = db::scim::list_groups(org_id).await?;
+    save_user(org_id, user, &groups).await
+}
+
+pub async fn patch_user(org_id: &str, id: &str, patch: ScimPatchOp) -> Result<ScimUser, ScimError> {
+    let mut user = find_user(org_id, id).await?;
+    for op in patch.operations.iter() {
+        patch_user_attributes(&mut user, op)?;
+    }
+    let groups = db::scim::list_groups(org_id).await?;
+    save_user(org_id, user, &groups).await
+}
+
+/// Removes the user from the org and its groups, the user itself is kept as
+/// it may be a member of other orgs.
+pub async fn delete_user(org_id: &str, id: &str) -> Result<(), ScimError> {
+    let mut user = find_user(org_id, id).await?;
+    for mut group in db::scim::list_groups(org_id).await? {
+        if group.members.iter().any(|m| m.value == user.id) {
+            group.members.retain(|m| m.value != user.id);
+            db::scim::set_group(org_id, &group).await?;
+        }
+    }
+    user.active = false;
+    sync_user(org_id, &user, &[]).await?;
+    db::scim::delete_user(org_id, &user.id).await?;
+    Ok(())
+}
+
+pub async fn list_groups(
+    org_id: &str,
+    filter: Option<&str>,
+    start_index: Option<usize>,
+    count: Option<usize>,
+) -> Result<ScimListResponse<ScimGroup>, ScimError> {
+    let filter = filter
+        .map(|f| parse_filter(f, &["id", "displayname", "externalid"]))
+        .transpose()?;
+    let mut groups = db::scim::list_groups(org_id)
+        .await?
+        .into_iter()
+        .filter(|group| {
+            filter
+                .as_ref()
+                .is_none_or(|(attr, value)| match attr.as_str() {
+                    "externalid" => group.external_id.as_deref() == Some(value.as_str()),
+                    "displayname" => group.display_name.eq_ignore_ascii_case(value),
+                    _ => group.id == *value,
+                })
+        })
+        .map(group_resource)
+        .collect::<Vec<_>>();
+    groups.sort_by(|a, b| a.display_name.cmp(&b.display_name));
+    Ok(ScimListResponse::new(
+        groups,
+        start_index.unwrap_or(1),
+        count.unwrap_or(DEFAULT_COUNT),
+    ))
+}
+
+pub async fn get_group(org_id: &str, id: &str) -> Result<ScimGroup, ScimError> {
+    Ok(group_resource(find_group(org_id, id).await?))
+}
+
+pub async fn create_group(org_id: &str, mut group: ScimGroup) -> Result<ScimGroup, ScimError> {
+    group.id = ider::generate();
+    save_group(org_id, group, vec![]).await
+}
+
+pub async fn replace_group(
+    org_id: &str,
+    id: &str,
+    mut group: ScimGroup,
+) -> Result<ScimGroup, ScimError> {
+    let existing = find_group(org_id, id).await?;
+    group.id = existing.id;
+    save_group(org_id, group, existing.members).await
+}
+
+pub async fn patch_group(
+    org_id: &str,
+    id: &str,
+    patch: ScimPatchOp,
+) -> Result<ScimGroup, ScimError> {
+    let mut group = find_group(org_id, id).await?;
+    let old_members = group.members.clone();
+    for op in patch.operations.iter() {
+        patch_group_attributes(&mut group, op)?;
+    }
+    save_group(org_id, group, old_members).await
+}
+
+/// Deletes the group, its members get the roles of their other groups.
+pub async fn delete_group(org_id: &str, id: &str) -> Result<(), ScimError> {
+    let group = find_group(org_id, id).await?;
+    db::scim::delete_group(org_id, &group.id).await?;
+    sync_members(org_id, group.members).await
+}
+
+async fn find_user(org_id: &str, id: &str) -> Result<ScimUser, ScimError> {
+    db::scim::get_user(org_id, &id.to_lowercase())
+        .await
+        .map_err(|_| ScimError::NotFound(id.to_string()))
+}
+
+async fn find_group(org_id: &str, id: &str) -> Result<ScimGroup, ScimError> {
+    db::scim::get_group(org_id, id)
+        .await
+        .map_err(|_| ScimError::NotFound(id.to_string()))
+}
+
+async fn save_user(
+    org_id: &str,
+    mut user: ScimUser,
+    groups: &[ScimGroup],
+) -> Result<ScimUser, ScimError> {
+    user.schemas = vec![USER_SCHEMA.to_string()];
+    user.groups.clear();
+    user.meta = None;
+    sync_user(org_id, &user, groups).await?;
+    db::scim::set_user(org_id, &user).await?;
+    Ok(user_resource(user, groups))
+}
+
+/// Saves a group and syncs the roles of its old and new members.
+async fn save_group(
+    org_id: &str,
+    mut group: ScimGroup,
+    old_members: Vec<ScimMember>,
+) -> Result<ScimGroup, ScimError> {
+    group.display_name = group.display_name.trim().to_string();
+    if group.display_name.is_empty() {
+        return Err(ScimError::InvalidValue(
+            "displayName is required".to_string(),
+        ));
+    }
+    if db::scim::list_groups(org_id)
+        .await?
+        .iter()
+        .any(|g| g.id != group.id && g.display_name.eq_ignore_ascii_case(&group.display_name))
+    {
+        return Err(ScimError::Conflict(group.display_name));
+    }
+    let mut seen = HashSet::new();
+    group.members.retain_mut(|m| {
+        m.value = m.value.trim().to_lowercase();
+        seen.insert(m.value.clone())
+    });
+    group.schemas = vec![GROUP_SCHEMA.to_string()];
+    group.meta = None;
+    db::scim::set_group(org_id, &group).await?;
+
+    let members = old_members
+        .into_iter()
+        .chain(group.members.iter().cloned())
+        .collect();
+    sync_members(org_id, members).await?;
+    Ok(group_resource(group))
+}
+
+async fn sync_members(org_id: &str, members: Vec<ScimMember>) -> Result<(), ScimError> {
+    let groups = db::scim::list_groups(org_id).await?;
+    let ids = members.into_iter().map(|m| m.value).collect::<HashSet<_>>();
+    for id in ids {
+        // the provider may push groups before their members
+        let Ok(user) = db::scim::get_user(org_id, &id).await else {
+            continue;
+        };
+        sync_user(org_id, &user, &groups).await?;
+    }
+    Ok(())
+}
+
+/// The role of a user in the org, the highest role of the groups of the user
+/// by the group mapping, or the default role.
+fn role_of(org_id: &str, user_id: &str, groups: &[ScimGroup]) -> Option<UserRole> {
+    let cfg = get_config();
+    let names = groups
+        .iter()
+        .filter(|g| g.members.iter().any(|m| m.value == user_id))
+        .map(|g| g.display_name.clone())
+        .collect::<Vec<_>>();
+    oidc::map_groups(&names, &cfg.oidc.group_role_mapping, "", "")
+        .remove(org_id)
+        .or_else(|| oidc::parse_role(&cfg.oidc.default_role))
+}
+
+/// Creates the user or syncs its membership of the org. Inactive users and
+/// users without a role lose access to the org, root is never changed.
+async fn sync_user(org_id: &str, user: &ScimUser, groups: &[ScimGroup]) -> Result<(), ScimError> {
+    let role = if user.active {
+        role_of(org_id, &user.id, groups)
+    } else {
+        None
+    };
+    let Some(db_user) = db::user::get_user_by_email(&user.id).await else {
+        if let Some(role) = role {
+            users::create_new_user(DBUser {
+                email: user.id.clone(),
+                first_name: user.name.given_name.clone(),
+                last_name: user.name.family_name.clone(),
+                password: String::new(),
+                salt: String::new(),
+                organizations: vec![UserOrg {
+                    name: org_id.to_string(),
+                    token: String::new(),
+                    rum_token: None,
+                    role,
+                }],
+                is_external: true,
+                password_ext: None,
+            })
+            .await?;
+        }
+        return Ok(());
+    };
+    if db_user
+        .organizations
+        .iter()
+        .any(|org| org.role == UserRole::ServiceAccount)
+    {
+        return Err(ScimError::Conflict(format!("service account {}", user.id)));
+    }
+
+    let (first_name, last_name) = (&user.name.given_name, &user.name.family_name);
+    if !(first_name.is_empty() && last_name.is_empty())
+        && (db_user.first_name != *first_name || db_user.last_name != *last_name)
+    {
+        db::user::update(
+            &user.id,
+            first_name,
+            last_name,
+            &db_user.password,
+            db_user.password_ext.clone(),
+        )
+        .await?;
+    }
+
+    match (
+        db_user.organizations.iter().find(|org| org.name == org_id),
+        role,
+    ) {
+        (Some(org), _) if org.role == UserRole::Root => {}
+        (Some(org), Some(role)) if org.role != role => {
+            db::org_users::update(org_id, &user.id, role, &org.token, org.rum_token.clone())
+                .await?;
+        }
+        (Some(_), None) => db::org_users::remove(org_id, &user.id).await?,
+        (None, Some(role)) => {
+            db::org_users::add(
+                org_id,
+                &user.id,
+                role,
+                &generate_random_string(16),
+                Some(format!("rum{}", generate_random_string(16))),
+            )
+            .await?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn user_resource(mut user: ScimUser, groups: &[ScimGroup]) -> ScimUser {
+    user.groups = groups
+        .iter()
+        .filter(|g| g.members.iter().any(|m| m.value == user.id))
+        .map(|g| ScimMember {
+            value: g.id.clone(),
+            display: Some(g.display_name.clone()),
+        })
+        .collect();
+    user.meta = Some(ScimMeta {
+        resource_type: "User".to_string(),
+    });
+    user
+}
+
+fn group_resource(mut group: ScimGroup) -> ScimGroup {
+    group.meta = Some(ScimMeta {
+        resource_type: "Group".to_string(),
+    });
+    group
+}
+
+/// Parses the `attribute eq "value"` filters the providers look users and
+/// groups up with, the attribute is lowercased.
+fn parse_filter(filter: &str, attributes: &[&str]) -> Result<(String, String), ScimError> {
+    let invalid = || ScimError::InvalidFilter(filter.to_string());
+    let mut parts = filter.trim().splitn(3, ' ');
+    let (Some(attr), Some(op), Some(value)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+    let attr = attr.to_lowercase();
+    if !op.eq_ignore_ascii_case("eq") || !attributes.contains(&attr.as_str()) {
+        return Err(invalid());
+    }
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .ok_or_else(invalid)?;
+    Ok((attr, value.to_string()))
+}
+
+fn patch_user_attributes(user: &mut ScimUser, op: &ScimPatchOperation) -> Result<(), ScimError> {
+    if !matches!(op.op.to_lowercase().as_str(), "add" | "replace") {
+        return Err(ScimError::InvalidValue(format!(
+            "unsupported operation {} on a user",
+            op.op
+        )));
+    }
+    let value = op.value.clone().unwrap_or_default();
+    let Some(path) = &op.path else {
+        // the value holds the attributes
+        let Value::Object(attributes) = value else {
+            return Err(ScimError::InvalidValue(
+                "an operation without a path needs an object value".to_string(),
+            ));
+        };
+        for (path, value) in attributes {
+            set_user_attribute(user, &path, value)?;
+        }
+        return Ok(());
+    };
+    set_user_attribute(user, path, value)
+}
+
+fn set_user_attribute(user: &mut ScimUser, path: &str, value: Value) -> Result<(), ScimError> {
+    let as_string = |value: &Value| value.as_str().unwrap_or_default().to_string();
+    match path.trim().to_lowercase().as_str() {
+        "active" => {
+            user.active = match &value {
+                Value::Bool(active) => *active,
+                // some providers send the booleans as strings
+                Value::String(active) => active.to_lowercase().parse().map_err(|_| {
+                    ScimError::InvalidValue(format!("active must be a boolean, got {active}"))
+                })?,
+                _ => {
+                    return Err(ScimError::InvalidValue(
+                        "active must be a boolean".to_string(),
+                    ));
+                }
+            }
+        }
+        "name" => {
+            user.name = json::from_value(value)
+                .map_err(|e| ScimError::InvalidValue(format!("invalid name: {e}")))?
+        }
+        "name.givenname" => user.name.given_name = as_string(&value),
+        "name.familyname" => user.name.family_name = as_string(&value),
+        "externalid" => user.external_id = value.as_str().map(String::from),
+        path => log::debug!("[SCIM] ignoring the user attribute {path}"),
+    }
+    Ok(())
+}
+
+fn patch_group_attributes(group: &mut ScimGroup, op: &ScimPatchOperation) -> Result<(), ScimError> {
+    let kind = op.op.to_lowercase();
+    let path = op.path.as_deref().map(|p| p.trim().to_lowercase());
+    let members = || -> Result<Vec<ScimMember>, ScimError> {
+        match op.value.clone() {
+            Some(Value::Array(members)) => json::from_value(Value::Array(members)),
+            Some(member @ Value::Object(_)) => json::from_value(member).map(|m| vec![m]),
+            _ => return Ok(vec![]),
+        }
+        .map_err(|e| ScimError::InvalidValue(format!("invalid members: {e}")))
+    };
+    match (kind.as_str(), path.as_deref()) {
+        ("add", Some("members")) => {
+            for member in members()? {
+                if !group.members.iter().any(|m| m.value == member.value) {
+                    group.members.push(member);
+                }
+            }
+        }
+        ("replace", Some("members")) => group.members = members()?,
+        ("remove", Some("members")) if op.value.is_none() => group.members.clear(),
+        ("remove", Some("members")) => {
+            let removed = members()?
+                .into_iter()
+                .map(|m| m.value.to_lowercase())
+                .collect::<HashSet<_>>();
+            group
+                .members
+                .retain(|m| !removed.contains(&m.value.to_lowercase()));
+        }
+        ("remove", Some(path)) if path.starts_with("members[") => {
+            // members[value eq "id"]
+            let filter = path
+                .strip_prefix("members[")
+                .and_then(|f| f.strip_suffix(']'))
+                .unwrap_or_default();
+            let (_, value) = parse_filter(filter, &["value"])?;
+            group.members.retain(|m| m.value.to_lowercase() != value);
+        }
+        ("add" | "replace", Some("displayname")) => {
+            group.display_name = op
+                .value
+                .as_ref()
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string()
+        }
+        ("add" | "replace", Some("externalid")) => {
+            group.external_id = op.value.as_ref().and_then(|v| v.as_str()).map(String::from)
+        }
+        ("add" | "replace", None) => {
+            // the value holds the attributes
+            let Some(Value::Object(attributes)) = op.value.clone() else {
+                return Err(ScimError::InvalidValue(
+                    "an operation without a path needs an object value".to_string(),
+                ));
+            };
+            for (path, value) in attributes {
+                patch_group_attributes(
+                    group,
+                    &ScimPatchOperation {
+                        op: kind.clone(),
+                        path: Some(path),
+                        value: Some(value),
+                    },
+                )?;
+            }
+        }
+        _ => {
+            return Err(ScimError::InvalidValue(format!(
+                "unsupported operation {} on {}",
+                op.op,
+                op.path.as_deref().unwrap_or("a group")
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn op(op: &str, path: Option<&str>, value: Value) -> ScimPatchOperation {
+        ScimPatchOperation {
+            op: op.to_string(),
+            path: path.map(String::from),
+            value: Some(value),
+        }
+    }
+
+    #[test]
+    fn test_parse_filter() {
+        assert_eq!(
+            parse_filter(r#"userName eq "Jane@Example.com""#, &["username"]).unwrap(),
+            ("username".to_string(), "Jane@Example.com".to_string())
+        );
+        assert!(parse_filter(r#"userName co "jane""#, &["username"]).is_err());
+        assert!(parse_filter(r#"title eq "sre""#, &["username"]).is_err());
+        assert!(parse_filter("userName eq jane", &["username"]).is_err());
+    }
+
+    #[test]
+    fn test_patch_user() {
+        let mut user = ScimUser {
+            id: "jane@example.com".to_string(),
+            user_name: "jane@example.com".to_string(),
+            active: true,
+            ..Default::default()
+        };
+        patch_user_attributes(
+            &mut user,
+            &op("Replace", None, json::json!({"active": "False"})),
+        )
+        .unwrap();
+        assert!(!user.active);
+        patch_user_attributes(
+            &mut user,
+            &op("replace", Some("name.givenName"), json::json!("Jane")),
+        )
+        .unwrap();
+        assert_eq!(user.name.given_name, "Jane");
+        assert!(
+            patch_user_attributes(&mut user, &op("remove", Some("active"), Value::Null)).is_err()
+        );
+    }
+
+    #[test]
+    fn test_patch_group() {
+        let mut group = ScimGroup {
+            id: "1".to_string(),
+            display_name: "sre".to_string(),
+            ..Default::default()
+        };
+        let member = |value: &str| json::json!({"value": value});
+        patch_group_attributes(
+            &mut group,
+            &op(
+                "add",
+                Some("members"),
+                json::json!([member("a@example.com"), member("b@example.com")]),
+            ),
+        )
+        .unwrap();
+        assert_eq!(group.members.len(), 2);
+        patch_group_attributes(
+            &mut group,
+            &op(
+                "Remove",
+                Some(r#"members[value eq "a@example.com"]"#),
+                Value::Null,
+            ),
+        )
+        .unwrap();
+        assert_eq!(group.members.len(), 1);
+        assert_eq!(group.members[0].value, "b@example.com");
+        patch_group_attributes(
+            &mut group,
+            &op("replace", None, json::json!({"displayName": "oncall"})),
+        )
+        .unwrap();
+        assert_eq!(group.display_name, "oncall");
+        assert!(
+            patch_group_attributes(&mut group, &op("move", Some("members"), Value::Null)).is_err()
+        );
+    }
+}
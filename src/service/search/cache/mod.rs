@@ -68,6 +68,8 @@ pub async fn search(
     let start = std::time::Instant::now();
     let started_at = Utc::now().timestamp_micros();
     let cfg = get_config();
+    // a cached result must not skip the masking check of the query
+    SearchService::masking::check_request(org_id, stream_type, in_req, user_id.as_deref()).await?;
     // result cache can be enable only when its from the start
     let use_cache = if in_req.query.from == 0 {
         in_req.use_cache
@@ -21,7 +21,7 @@ use config::{
     PARQUET_BATCH_SIZE, TIMESTAMP_COL_NAME, get_config,
     meta::{
         search::{Session as SearchSession, StorageType},
-        stream::{FileKey, FileMeta, StreamType},
+        stream::{FileKey, FileMeta, ParquetSettings, StreamType},
     },
     utils::{parquet::new_parquet_writer, schema_ext::SchemaExt},
 };
@@ -92,6 +92,7 @@ pub async fn merge_parquet_files(
     schema: Arc<Schema>,
     tables: Vec<Arc<dyn TableProvider>>,
     bloom_filter_fields: &[String],
+    parquet_settings: Option<&ParquetSettings>,
     metadata: &FileMeta,
     is_ingester: bool,
 ) -> Result<(Arc<Schema>, MergeParquetResult)> {
@@ -106,6 +107,7 @@ pub async fn merge_parquet_files(
                 schema,
                 tables,
                 bloom_filter_fields,
+                parquet_settings,
                 rule,
                 metadata,
             )
@@ -186,6 +188,7 @@ pub async fn merge_parquet_files(
         metadata,
         false,
         compression,
+        parquet_settings,
     );
 
     // calculate the new file meta records
@@ -242,6 +245,7 @@ pub async fn merge_parquet_files_with_downsampling(
     schema: Arc<Schema>,
     tables: Vec<Arc<dyn TableProvider>>,
     bloom_filter_fields: &[String],
+    parquet_settings: Option<&ParquetSettings>,
     rule: &DownsamplingRule,
     metadata: &FileMeta,
 ) -> Result<(Arc<Schema>, MergeParquetResult)> {
@@ -291,6 +295,7 @@ pub async fn merge_parquet_files_with_downsampling(
         &metadata,
         false,
         None,
+        parquet_settings,
     );
     let mut batch_stream = execute_stream(physical_plan, ctx.task_ctx())?;
     let (tx, mut rx) = tokio::sync::mpsc::channel::<RecordBatch>(2);
@@ -344,6 +349,7 @@ pub async fn merge_parquet_files_with_downsampling(
                 &metadata,
                 false,
                 None,
+                parquet_settings,
             );
         }
         if let Err(e) = writer.write(&batch).await {
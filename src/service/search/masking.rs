@@ -0,0 +1,528 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Query-time masking of the search results by the masking rules of the
+//! streams. The results are masked right before they leave the server, the
+//! result cache always keeps the raw values so that every role gets its own
+//! view of the same cached data.
+//!
+//! The results are masked by column name, so a query may only use a masked
+//! column as a plain projection: an alias, a function, a filter or a VRL
+//! function could carry the raw value out under another name. Such queries
+//! are rejected before they run.
+
+use std::{borrow::Cow, ops::ControlFlow};
+
+use config::{
+    TIMESTAMP_COL_NAME,
+    meta::{
+        search::{Request, Response, StreamResponses},
+        stream::{MaskingAction, MaskingRule, StreamType},
+    },
+    utils::{
+        json::{Map, Value},
+        sql::resolve_stream_names,
+    },
+};
+use hashbrown::{HashMap, HashSet};
+use infra::errors::{Error, ErrorCodes};
+use regex::Regex;
+use sqlparser::{
+    ast::{Expr, Query, SelectItem, SetExpr, Visit, Visitor},
+    dialect::PostgreSqlDialect,
+    parser::Parser,
+};
+
+use crate::{common::utils::auth::is_root_user, service::users};
+
+const MASK_CHAR: char = '*';
+
+struct CompiledRule {
+    fields: HashSet<String>,
+    pattern: Option<Regex>,
+    action: MaskingAction,
+    keep_last: usize,
+}
+
+/// The masking rules which apply to one user on the queried streams.
+pub struct Masker {
+    rules: Vec<CompiledRule>,
+}
+
+impl Masker {
+    /// Compiles the rules which don't exempt `role`, `None` when nothing is
+    /// masked. Without a role, e.g. for alerts, all the rules apply.
+    pub fn new(rules: &[MaskingRule], role: Option<&str>) -> Option<Self> {
+        let rules = rules
+            .iter()
+            .filter(|rule| role.is_none_or(|role| !rule.exempt_roles.iter().any(|r| r == role)))
+            .map(|rule| {
+                // an invalid pattern drops the whole values instead of nothing
+                let (pattern, action) = match rule.pattern.as_ref() {
+                    Some(pattern) => match Regex::new(pattern) {
+                        Ok(re) => (Some(re), rule.action),
+                        Err(e) => {
+                            log::error!("[MASKING] invalid pattern {pattern}: {e}");
+                            let all = rule
+                                .fields
+                                .is_empty()
+                                .then(|| Regex::new("(?s).+").unwrap());
+                            (all, MaskingAction::Drop)
+                        }
+                    },
+                    None => (None, rule.action),
+                };
+                CompiledRule {
+                    fields: rule.fields.iter().cloned().collect(),
+                    pattern,
+                    action,
+                    keep_last: rule.keep_last,
+                }
+            })
+            .collect::<Vec<_>>();
+        (!rules.is_empty()).then_some(Self { rules })
+    }
+
+    /// Whether the values of the field are masked by a rule of the field. A
+    /// pattern rule without fields masks the strings of every field but the
+    /// timestamp.
+    pub fn is_masked(&self, field: &str) -> bool {
+        self.rules.iter().any(|rule| {
+            rule.fields.contains(field) || (rule.fields.is_empty() && field != TIMESTAMP_COL_NAME)
+        })
+    }
+
+    /// Rejects a query which uses a masked field anywhere but as a plain
+    /// projection, or which has a VRL function while fields are masked.
+    pub fn check_query(&self, sql: &str, has_vrl: bool) -> Result<(), String> {
+        if has_vrl {
+            return Err("VRL functions can't be used on streams with masked fields".to_string());
+        }
+        let statements =
+            Parser::parse_sql(&PostgreSqlDialect {}, sql).map_err(|e| e.to_string())?;
+        let mut visitor = MaskedFieldVisitor {
+            masker: self,
+            uses: HashMap::new(),
+        };
+        let _ = statements.visit(&mut visitor);
+        match visitor
+            .uses
+            .into_iter()
+            .find(|(_, (plain, all))| all > plain)
+        {
+            Some((field, _)) => Err(format!(
+                "masked field {field} can only be selected as is, without an alias, a function or a filter"
+            )),
+            None => Ok(()),
+        }
+    }
+
+    pub fn mask_response(&self, res: &mut Response) {
+        self.mask_hits(&mut res.hits);
+    }
+
+    /// Masks a response of a values query, the top values of `field` are
+    /// in `zo_sql_key`.
+    pub fn mask_values_response(&self, res: &mut Response) {
+        for hit in res.hits.iter_mut() {
+            let Value::Object(hit) = hit else {
+                continue;
+            };
+            let Some(field) = hit.get("field").and_then(|v| v.as_str()).map(String::from) else {
+                continue;
+            };
+            let Some(Value::Array(values)) = hit.get_mut("values") else {
+                continue;
+            };
+            for value in values.iter_mut() {
+                if let Value::Object(value) = value
+                    && let Some(key) = value.get_mut("zo_sql_key")
+                {
+                    let mut record = Map::new();
+                    record.insert(field.clone(), key.take());
+                    self.mask_record(&mut record);
+                    *key = record.remove(&field).unwrap_or(Value::Null);
+                }
+            }
+        }
+    }
+
+    pub fn mask_search_response(&self, res: &mut Response, is_values: bool) {
+        if is_values {
+            self.mask_values_response(res);
+        } else {
+            self.mask_response(res);
+        }
+    }
+
+    /// Masks a response of the HTTP/2 search stream.
+    pub fn mask_stream_response(&self, res: &mut StreamResponses, is_values: bool) {
+        match res {
+            StreamResponses::SearchResponse { results, .. }
+            | StreamResponses::SearchResponseMetadata { results, .. } => {
+                self.mask_search_response(results, is_values)
+            }
+            StreamResponses::SearchResponseHits { hits } if !is_values => self.mask_hits(hits),
+            _ => {}
+        }
+    }
+
+    pub fn mask_hits(&self, hits: &mut [Value]) {
+        for hit in hits.iter_mut() {
+            if let Value::Object(record) = hit {
+                self.mask_record(record);
+            }
+        }
+    }
+
+    pub fn mask_record(&self, record: &mut Map<String, Value>) {
+        for rule in self.rules.iter() {
+            if rule.fields.is_empty() {
+                for value in record.values_mut() {
+                    rule.mask_string(value);
+                }
+                continue;
+            }
+            for field in rule.fields.iter() {
+                match (rule.pattern.is_some(), rule.action) {
+                    (false, MaskingAction::Drop) => {
+                        record.remove(field);
+                    }
+                    _ => {
+                        if let Some(value) = record.get_mut(field) {
+                            rule.mask_value(value);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Counts the plain projections and all the uses of each masked field.
+struct MaskedFieldVisitor<'a> {
+    masker: &'a Masker,
+    uses: HashMap<String, (usize, usize)>,
+}
+
+impl MaskedFieldVisitor<'_> {
+    fn masked_field(&self, expr: &Expr) -> Option<String> {
+        let name = match expr {
+            Expr::Identifier(ident) => &ident.value,
+            Expr::CompoundIdentifier(idents) => &idents.last()?.value,
+            _ => return None,
+        };
+        self.masker.is_masked(name).then(|| name.to_string())
+    }
+}
+
+impl Visitor for MaskedFieldVisitor<'_> {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        if let SetExpr::Select(select) = query.body.as_ref() {
+            for item in select.projection.iter() {
+                if let SelectItem::UnnamedExpr(expr) = item
+                    && let Some(field) = self.masked_field(expr)
+                {
+                    self.uses.entry(field).or_default().0 += 1;
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+        if let Some(field) = self.masked_field(expr) {
+            self.uses.entry(field).or_default().1 += 1;
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl CompiledRule {
+    /// Masks the whole value or the matches of the pattern.
+    fn mask_value(&self, value: &mut Value) {
+        if self.pattern.is_some() {
+            self.mask_string(value);
+            return;
+        }
+        let raw = match value {
+            Value::Null => return,
+            Value::String(s) => Cow::Borrowed(s.as_str()),
+            v => Cow::Owned(v.to_string()),
+        };
+        *value = Value::String(self.mask_str(&raw));
+    }
+
+    /// Masks the matches of the pattern in a string value.
+    fn mask_string(&self, value: &mut Value) {
+        let (Some(pattern), Value::String(s)) = (self.pattern.as_ref(), &*value) else {
+            return;
+        };
+        let masked = match pattern.replace_all(s, |caps: &regex::Captures| match self.action {
+            MaskingAction::Drop => String::new(),
+            _ => self.mask_str(&caps[0]),
+        }) {
+            Cow::Owned(masked) => masked,
+            Cow::Borrowed(_) => return,
+        };
+        *value = Value::String(masked);
+    }
+
+    fn mask_str(&self, s: &str) -> String {
+        match self.action {
+            MaskingAction::Hash => sha256::digest(s),
+            MaskingAction::Partial => {
+                let len = s.chars().count();
+                let keep = self.keep_last.min(len);
+                std::iter::repeat_n(MASK_CHAR, len - keep)
+                    .chain(s.chars().skip(len - keep))
+                    .collect()
+            }
+            MaskingAction::Drop => String::new(),
+        }
+    }
+}
+
+/// Returns the masker of the user for the streams, `None` when nothing is
+/// masked. Root users see the raw values, a user without a role in the org
+/// gets all the rules.
+pub async fn get_masker(
+    org_id: &str,
+    stream_type: StreamType,
+    stream_names: &[String],
+    user_id: Option<&str>,
+) -> Option<Masker> {
+    let mut rules = Vec::new();
+    for stream_name in stream_names {
+        if let Some(masking) = infra::schema::get_settings(org_id, stream_name, stream_type)
+            .await
+            .and_then(|s| s.masking)
+            && masking.enabled
+        {
+            rules.extend(masking.rules);
+        }
+    }
+    if rules.is_empty() {
+        return None;
+    }
+    let role = match user_id {
+        Some(user_id) if is_root_user(user_id) => return None,
+        Some(user_id) => users::get_user(Some(org_id), user_id)
+            .await
+            .map(|user| user.role.to_string())
+            .unwrap_or_default(),
+        None => return Masker::new(&rules, None),
+    };
+    Masker::new(&rules, Some(&role))
+}
+
+/// Returns the masker of the user for the streams of the SQL.
+pub async fn get_query_masker(
+    org_id: &str,
+    stream_type: StreamType,
+    sql: &str,
+    user_id: Option<&str>,
+) -> Option<Masker> {
+    match resolve_stream_names(sql) {
+        Ok(stream_names) => get_masker(org_id, stream_type, &stream_names, user_id).await,
+        Err(e) => {
+            // the query has already run so the sql can't be invalid here
+            log::error!("[MASKING] failed to resolve the streams of the query: {e}");
+            None
+        }
+    }
+}
+
+/// Rejects a query of the user which could read the raw values of a masked
+/// field, see [`Masker::check_query`]. Internal queries without a user are
+/// not checked.
+pub async fn check_request(
+    org_id: &str,
+    stream_type: StreamType,
+    req: &Request,
+    user_id: Option<&str>,
+) -> Result<(), Error> {
+    if user_id.is_none() {
+        return Ok(());
+    }
+    let Ok(stream_names) = resolve_stream_names(&req.query.sql) else {
+        // the query is rejected with a better message when it is planned
+        return Ok(());
+    };
+    let has_vrl = req
+        .query
+        .query_fn
+        .as_ref()
+        .is_some_and(|f| !f.trim().is_empty())
+        || req.query.action_id.is_some();
+    match get_masker(org_id, stream_type, &stream_names, user_id).await {
+        Some(masker) => masker
+            .check_query(&req.query.sql, has_vrl)
+            .map_err(|e| Error::ErrorCode(ErrorCodes::SearchSQLNotValid(e))),
+        None => Ok(()),
+    }
+}
+
+/// Masks the hits of the response of a query on the streams of the SQL.
+pub async fn mask_response(
+    org_id: &str,
+    stream_type: StreamType,
+    sql: &str,
+    user_id: Option<&str>,
+    res: &mut Response,
+) {
+    if let Some(masker) = get_query_masker(org_id, stream_type, sql, user_id).await {
+        masker.mask_response(res);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use config::utils::json;
+
+    use super::*;
+
+    fn rule(fields: &[&str], pattern: Option<&str>, action: MaskingAction) -> MaskingRule {
+        MaskingRule {
+            fields: fields.iter().map(|f| f.to_string()).collect(),
+            pattern: pattern.map(|p| p.to_string()),
+            action,
+            keep_last: 4,
+            exempt_roles: vec!["admin".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_masker() {
+        let rules = vec![
+            rule(&["email"], None, MaskingAction::Hash),
+            rule(&["card"], None, MaskingAction::Partial),
+            rule(&["token"], None, MaskingAction::Drop),
+            rule(&[], Some(r"secret=\w+"), MaskingAction::Drop),
+        ];
+        assert!(Masker::new(&rules, Some("admin")).is_none());
+
+        let masker = Masker::new(&rules, Some("viewer")).unwrap();
+        let mut hits = vec![json::json!({
+            "email": "a@b.com",
+            "card": 4111111111111234_i64,
+            "token": "abc",
+            "log": "login secret=xyz ok",
+        })];
+        masker.mask_hits(&mut hits);
+        let hit = &hits[0];
+        assert_eq!(hit["email"], sha256::digest("a@b.com"));
+        assert_eq!(hit["card"], "************1234");
+        assert!(hit.get("token").is_none());
+        assert_eq!(hit["log"], "login  ok");
+
+        // alerts have no role
+        assert!(Masker::new(&rules, None).is_some());
+    }
+
+    #[test]
+    fn test_mask_pattern_in_field() {
+        let rules = vec![rule(
+            &["msg"],
+            Some(r"\d{4}-\d{4}-\d{4}-\d{4}"),
+            MaskingAction::Partial,
+        )];
+        let masker = Masker::new(&rules, Some("viewer")).unwrap();
+        let mut record =
+            json::json!({"msg": "paid with 4111-1111-1111-1234", "other": "1111-1111-1111-1111"})
+                .as_object()
+                .unwrap()
+                .clone();
+        masker.mask_record(&mut record);
+        assert_eq!(record["msg"], "paid with ***************1234");
+        assert_eq!(record["other"], "1111-1111-1111-1111");
+    }
+
+    #[test]
+    fn test_invalid_pattern_fails_closed() {
+        let rules = vec![
+            rule(&["msg"], Some("(unclosed"), MaskingAction::Partial),
+            rule(&[], Some("[unclosed"), MaskingAction::Drop),
+        ];
+        let masker = Masker::new(&rules, Some("viewer")).unwrap();
+        let mut record = json::json!({"msg": "card 1234", "other": "secret"})
+            .as_object()
+            .unwrap()
+            .clone();
+        masker.mask_record(&mut record);
+        assert!(record.get("msg").is_none());
+        assert_eq!(record["other"], "");
+    }
+
+    #[test]
+    fn test_check_query() {
+        let rules = vec![rule(&["email"], None, MaskingAction::Hash)];
+        let masker = Masker::new(&rules, Some("viewer")).unwrap();
+        for sql in [
+            "SELECT * FROM t",
+            "SELECT email, name FROM t WHERE name = 'a'",
+            "SELECT t.email FROM t",
+            "SELECT email FROM (SELECT email FROM t)",
+        ] {
+            assert!(masker.check_query(sql, false).is_ok(), "{sql}");
+        }
+        for sql in [
+            "SELECT email AS e FROM t",
+            "SELECT lower(email) FROM t",
+            "SELECT * FROM t WHERE email = 'a@b.com'",
+            "SELECT name FROM t ORDER BY email",
+            "SELECT e FROM (SELECT email AS e FROM t)",
+        ] {
+            assert!(masker.check_query(sql, false).is_err(), "{sql}");
+        }
+        assert!(masker.check_query("SELECT * FROM t", true).is_err());
+
+        // rules of patterns over all the fields mask the values of any column
+        let rules = vec![rule(&[], Some(r"\d+"), MaskingAction::Drop)];
+        let masker = Masker::new(&rules, Some("viewer")).unwrap();
+        assert!(masker.check_query("SELECT * FROM t", false).is_ok());
+        assert!(
+            masker
+                .check_query("SELECT * FROM t WHERE _timestamp > 0", false)
+                .is_ok()
+        );
+        assert!(masker.check_query("SELECT * FROM t", true).is_err());
+        assert!(
+            masker
+                .check_query("SELECT lower(a) AS b FROM t", false)
+                .is_err()
+        );
+        assert!(masker.is_masked("a"));
+        assert!(!masker.is_masked(TIMESTAMP_COL_NAME));
+    }
+
+    #[test]
+    fn test_mask_values_response() {
+        let rules = vec![rule(&["email"], None, MaskingAction::Drop)];
+        let masker = Masker::new(&rules, Some("viewer")).unwrap();
+        let mut res = Response {
+            hits: vec![json::json!({
+                "field": "email",
+                "values": [{"zo_sql_key": "a@b.com", "zo_sql_num": 3}],
+            })],
+            ..Default::default()
+        };
+        masker.mask_values_response(&mut res);
+        assert_eq!(res.hits[0]["values"][0]["zo_sql_key"], Value::Null);
+        assert_eq!(res.hits[0]["values"][0]["zo_sql_num"], 3);
+    }
+}
@@ -87,6 +87,8 @@ pub(crate) mod grpc;
 pub(crate) mod grpc_search;
 pub(crate) mod index;
 pub(crate) mod inspector;
+pub(crate) mod masking;
+pub(crate) mod multi_orgs;
 pub(crate) mod partition;
 pub(crate) mod request;
 pub(crate) mod search_stream;
@@ -127,6 +129,12 @@ pub async fn search(
     let started_at = now_micros();
     let cfg = get_config();
 
+    masking::check_request(org_id, stream_type, in_req, user_id.as_deref()).await?;
+
+    // the permit is held until the search is done
+    let _quota_permit = crate::service::quotas::admit_query(org_id)
+        .map_err(|e| Error::ErrorCode(ErrorCodes::QuotaExceeded(e)))?;
+
     let trace_id = if trace_id.is_empty() {
         if cfg.common.tracing_enabled || cfg.common.tracing_search_enabled {
             let ctx = tracing::Span::current().context();
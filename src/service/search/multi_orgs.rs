@@ -0,0 +1,344 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Searches across orgs, e.g. an incident query over all the tenants of a
+//! platform team. The same query runs in each of the selected orgs, the hits
+//! are tagged with the org and merged in the order of the query.
+
+use std::collections::HashSet;
+
+use chrono::Utc;
+use config::{
+    TIMESTAMP_COL_NAME, get_config,
+    meta::{
+        search::{self, MultiOrgSearchPartitionRequest, MultiOrgSearchRequest, ORG_FIELD},
+        self_reporting::usage::{RequestStats, UsageType},
+        sql::resolve_stream_names,
+        stream::StreamType,
+    },
+    utils::{json, sql::is_aggregate_query},
+};
+use infra::errors::Error;
+use proto::cluster_rpc::SearchQuery;
+
+use crate::{
+    common::utils::auth::is_root_user,
+    service::{
+        db,
+        search::{
+            cache::{self, cacher::get_ts_col_order_by, result_utils::get_ts_value},
+            masking, search_partition,
+            sql::Sql,
+        },
+        self_reporting::report_request_usage_stats,
+    },
+};
+
+/// Root and the users of `ZO_CROSS_ORG_SEARCH_USERS` can search across orgs.
+pub fn can_search(user_id: &str) -> bool {
+    is_root_user(user_id)
+        || get_config()
+            .auth
+            .cross_org_search_users
+            .split(',')
+            .any(|user| !user.trim().is_empty() && user.trim().eq_ignore_ascii_case(user_id))
+}
+
+/// Runs the query in each org, the orgs which fail are reported in the
+/// function errors of a partial response. Each org returns its first
+/// `from + size` hits, the page is taken after they're merged.
+pub async fn search(
+    trace_id: &str,
+    user_id: &str,
+    stream_type: StreamType,
+    req: &MultiOrgSearchRequest,
+) -> Result<search::Response, Error> {
+    let start = std::time::Instant::now();
+    let orgs = check_orgs(&req.orgs).await?;
+    let stream_name = resolve_stream_names(&req.request.query.sql)
+        .map_err(|e| Error::Message(e.to_string()))?
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+
+    let from = req.request.query.from.max(0);
+    let size = req.request.query.size;
+    let mut org_req = req.request.clone();
+    org_req.query.from = 0;
+    if size > 0 {
+        org_req.query.size = from + size;
+    }
+
+    let mut responses = Vec::with_capacity(orgs.len());
+    let mut errors = Vec::new();
+    for org_id in orgs.iter() {
+        let org_start = std::time::Instant::now();
+        let started_at = Utc::now().timestamp_micros();
+        match cache::search(
+            trace_id,
+            org_id,
+            stream_type,
+            Some(user_id.to_string()),
+            &org_req,
+            String::new(),
+            false,
+        )
+        .await
+        {
+            Ok(mut res) => {
+                masking::mask_response(
+                    org_id,
+                    stream_type,
+                    &req.request.query.sql,
+                    Some(user_id),
+                    &mut res,
+                )
+                .await;
+                let req_stats = RequestStats {
+                    records: res.hits.len() as i64,
+                    response_time: org_start.elapsed().as_secs_f64(),
+                    size: res.scan_size as f64,
+                    request_body: Some(req.request.query.sql.clone()),
+                    user_email: Some(user_id.to_string()),
+                    min_ts: Some(req.request.query.start_time),
+                    max_ts: Some(req.request.query.end_time),
+                    cached_ratio: Some(res.cached_ratio),
+                    search_type: req.request.search_type,
+                    search_event_context: req.request.search_event_context.clone(),
+                    trace_id: Some(trace_id.to_string()),
+                    took_wait_in_queue: Some(res.took_detail.wait_in_queue),
+                    work_group: res.work_group.clone(),
+                    ..Default::default()
+                };
+                let num_fn = req.request.query.query_fn.is_some() as u16;
+                report_request_usage_stats(
+                    req_stats,
+                    org_id,
+                    &stream_name,
+                    stream_type,
+                    UsageType::Search,
+                    num_fn,
+                    started_at,
+                )
+                .await;
+                tag_hits(&mut res.hits, org_id);
+                responses.push(res);
+            }
+            Err(e) => {
+                log::error!("[trace_id {trace_id}] multi_orgs: search in org {org_id} error: {e}");
+                if responses.is_empty() && errors.len() + 1 == orgs.len() {
+                    return Err(e); // failed in all the orgs
+                }
+                errors.push(format!("{org_id}: {e}"));
+            }
+        }
+    }
+
+    let sort_by = ts_column_order_by(&orgs[0], stream_type, &req.request).await;
+    let mut res = merge_responses(responses, sort_by, from, size);
+    if !errors.is_empty() {
+        res.is_partial = true;
+        res.function_error.extend(errors);
+    }
+    res.set_trace_id(trace_id.to_string());
+    res.set_took(start.elapsed().as_millis() as usize);
+    Ok(res)
+}
+
+/// Partitions the query like the org with the most data does, the partitions
+/// cover the whole time range in every org.
+pub async fn search_partition_multi(
+    trace_id: &str,
+    user_id: &str,
+    stream_type: StreamType,
+    req: &MultiOrgSearchPartitionRequest,
+) -> Result<search::SearchPartitionResponse, Error> {
+    let orgs = check_orgs(&req.orgs).await?;
+    let mut res: Option<search::SearchPartitionResponse> = None;
+    let mut records = 0;
+    for org_id in orgs.iter() {
+        let resp = search_partition(
+            trace_id,
+            org_id,
+            Some(user_id),
+            stream_type,
+            &req.request,
+            false,
+            true,
+        )
+        .await?;
+        records += resp.records;
+        if res
+            .as_ref()
+            .is_none_or(|res| resp.partitions.len() > res.partitions.len())
+        {
+            res = Some(resp);
+        }
+    }
+    let mut res = res.unwrap_or_default();
+    res.records = records;
+    Ok(res)
+}
+
+/// Deduplicates the orgs and checks they exist.
+async fn check_orgs(orgs: &[String]) -> Result<Vec<String>, Error> {
+    let known = db::schema::list_organizations_from_cache()
+        .await
+        .into_iter()
+        .collect::<HashSet<_>>();
+    let mut seen = HashSet::new();
+    let mut checked = Vec::with_capacity(orgs.len());
+    for org_id in orgs.iter().map(|org| org.trim()) {
+        if !seen.insert(org_id) {
+            continue;
+        }
+        if !known.contains(org_id) {
+            return Err(Error::Message(format!(
+                "organization {org_id} not found or has no streams"
+            )));
+        }
+        checked.push(org_id.to_string());
+    }
+    if checked.is_empty() {
+        return Err(Error::Message("no organization to search".to_string()));
+    }
+    Ok(checked)
+}
+
+fn tag_hits(hits: &mut [json::Value], org_id: &str) {
+    for hit in hits.iter_mut() {
+        if let Some(hit) = hit.as_object_mut() {
+            hit.insert(
+                ORG_FIELD.to_string(),
+                json::Value::String(org_id.to_string()),
+            );
+        }
+    }
+}
+
+/// Returns the timestamp column of the results and whether it's sorted in
+/// descending order, the results of the orgs are merged by it.
+async fn ts_column_order_by(
+    org_id: &str,
+    stream_type: StreamType,
+    req: &search::Request,
+) -> Option<(String, bool)> {
+    let query: SearchQuery = req.query.clone().into();
+    let sql = Sql::new(&query, org_id, stream_type, req.search_type)
+        .await
+        .ok()?;
+    let is_aggregate = is_aggregate_query(&req.query.sql).unwrap_or_default();
+    get_ts_col_order_by(&sql, TIMESTAMP_COL_NAME, is_aggregate)
+}
+
+fn merge_responses(
+    responses: Vec<search::Response>,
+    sort_by: Option<(String, bool)>,
+    from: i64,
+    size: i64,
+) -> search::Response {
+    let mut res = search::Response::default();
+    for resp in responses {
+        res.total += resp.total;
+        res.file_count += resp.file_count;
+        res.scan_size += resp.scan_size;
+        res.idx_scan_size += resp.idx_scan_size;
+        res.scan_records += resp.scan_records;
+        res.cached_ratio = res.cached_ratio.max(resp.cached_ratio);
+        for column in resp.columns {
+            if !res.columns.contains(&column) {
+                res.columns.push(column);
+            }
+        }
+        if res.histogram_interval.is_none() {
+            res.histogram_interval = resp.histogram_interval;
+        }
+        if res.order_by.is_none() {
+            res.order_by = resp.order_by;
+        }
+        res.is_partial |= resp.is_partial;
+        res.function_error.extend(resp.function_error);
+        res.hits.extend(resp.hits);
+    }
+
+    if let Some((ts_column, is_descending)) = sort_by {
+        if is_descending {
+            res.hits
+                .sort_by_key(|hit| std::cmp::Reverse(get_ts_value(&ts_column, hit)));
+        } else {
+            res.hits.sort_by_key(|hit| get_ts_value(&ts_column, hit));
+        }
+    }
+    res.hits.drain(..(from as usize).min(res.hits.len()));
+    if size > 0 && res.hits.len() > size as usize {
+        res.hits.truncate(size as usize);
+    }
+    res.size = res.hits.len() as i64;
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(org_id: &str, timestamps: &[i64]) -> search::Response {
+        let mut res = search::Response::default();
+        res.hits = timestamps
+            .iter()
+            .map(|ts| json::json!({ TIMESTAMP_COL_NAME: ts }))
+            .collect();
+        res.total = timestamps.len();
+        tag_hits(&mut res.hits, org_id);
+        res
+    }
+
+    #[test]
+    fn test_merge_responses() {
+        let res = merge_responses(
+            vec![response("a", &[5, 3, 1]), response("b", &[4, 2])],
+            Some((TIMESTAMP_COL_NAME.to_string(), true)),
+            0,
+            4,
+        );
+        assert_eq!(res.total, 5);
+        assert_eq!(res.size, 4);
+        let hits = res
+            .hits
+            .iter()
+            .map(|hit| {
+                (
+                    hit[ORG_FIELD].as_str().unwrap(),
+                    hit[TIMESTAMP_COL_NAME].as_i64().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(hits, vec![("a", 5), ("b", 4), ("a", 3), ("b", 2)]);
+
+        // the second page of two hits
+        let res = merge_responses(
+            vec![response("a", &[5, 3, 1]), response("b", &[4, 2])],
+            Some((TIMESTAMP_COL_NAME.to_string(), true)),
+            2,
+            2,
+        );
+        assert_eq!(res.size, 2);
+        let hits = res
+            .hits
+            .iter()
+            .map(|hit| hit[TIMESTAMP_COL_NAME].as_i64().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(hits, vec![3, 2]);
+    }
+}
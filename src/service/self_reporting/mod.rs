@@ -36,6 +36,8 @@ use o2_enterprise::enterprise::common::auditor;
 use proto::cluster_rpc;
 use tokio::sync::oneshot;
 
+use crate::service::quotas;
+
 mod ingestion;
 mod queues;
 
@@ -90,6 +92,11 @@ pub async fn report_request_usage_stats(
             .with_label_values(&[org_id, stream_type.as_str()])
             .inc_by((stats.size * SIZE_IN_MB) as u64);
     }
+    match event {
+        UsageEvent::Ingestion => quotas::record_ingestion(org_id, (stats.size * SIZE_IN_MB) as u64),
+        UsageEvent::Search => quotas::record_query(org_id, (stats.size * SIZE_IN_MB) as u64),
+        _ => {}
+    }
 
     if !get_config().common.usage_enabled {
         return;
@@ -247,7 +254,6 @@ pub async fn publish_error(error_data: ErrorData) {
 
 pub async fn flush() {
     // flush audit data
-    #[cfg(feature = "enterprise")]
     flush_audit().await;
 
     let cfg = get_config();
@@ -307,6 +313,37 @@ pub async fn flush_audit() {
     auditor::flush_audit(META_ORG_ID, publish_audit).await;
 }
 
+/// Queues an audited request to be ingested into the audit stream of the
+/// `_meta` org.
+#[cfg(not(feature = "enterprise"))]
+pub async fn audit(audit_data: config::meta::self_reporting::audit::AuditData) {
+    if !get_config().common.audit_enabled {
+        return;
+    }
+
+    if let Err(e) = queues::AUDIT_QUEUE
+        .enqueue(ReportingData::Audit(Box::new(audit_data)))
+        .await
+    {
+        log::error!("[SELF-REPORTING] Failed to send audit data to background ingesting job: {e}");
+    }
+}
+
+#[cfg(not(feature = "enterprise"))]
+pub async fn flush_audit() {
+    if !get_config().common.audit_enabled {
+        return;
+    }
+
+    // the audit queue has a single consumer
+    let (res_sender, res_receiver) = oneshot::channel();
+    if let Err(e) = queues::AUDIT_QUEUE.shutdown(res_sender).await {
+        log::error!("[SELF-REPORTING] Error shutting down AUDIT_QUEUE: {e}");
+    }
+    // wait for flush ingestion job
+    res_receiver.await.ok();
+}
+
 #[cfg(feature = "enterprise")]
 async fn publish_audit(
     req: cluster_rpc::IngestionRequest,
@@ -20,6 +20,7 @@ use config::{
     meta::{
         self_reporting::{
             ReportingData, ReportingMessage, ReportingQueue, ReportingRunner,
+            audit::{AUDIT_STREAM, AuditData},
             error::ErrorData,
             usage::{ERROR_STREAM, TRIGGERS_USAGE_STREAM, TriggerData},
         },
@@ -39,6 +40,9 @@ pub(super) static USAGE_QUEUE: Lazy<Arc<ReportingQueue>> =
 pub(super) static ERROR_QUEUE: Lazy<Arc<ReportingQueue>> =
     Lazy::new(|| Arc::new(initialize_error_queue()));
 
+pub(super) static AUDIT_QUEUE: Lazy<Arc<ReportingQueue>> =
+    Lazy::new(|| Arc::new(initialize_audit_queue()));
+
 fn initialize_usage_queue() -> ReportingQueue {
     let cfg = get_config();
     let timeout = time::Duration::from_secs(
@@ -89,6 +93,23 @@ fn initialize_error_queue() -> ReportingQueue {
     ReportingQueue::new(msg_sender)
 }
 
+/// The audit records are ingested by a single job so that they keep the order
+/// of the requests.
+fn initialize_audit_queue() -> ReportingQueue {
+    let cfg = get_config();
+    let timeout = time::Duration::from_secs(cfg.common.audit_publish_interval.max(1));
+    let batch_size = cfg.common.audit_batch_size.max(1);
+
+    let (msg_sender, msg_receiver) =
+        mpsc::channel::<ReportingMessage>(cfg.common.audit_queue_size.max(batch_size));
+    let msg_receiver = Arc::new(Mutex::new(msg_receiver));
+    tokio::task::spawn(async move {
+        self_reporting_ingest_job(0, msg_receiver, batch_size, timeout).await
+    });
+
+    ReportingQueue::new(msg_sender)
+}
+
 async fn self_reporting_ingest_job(
     thread_id: usize,
     msg_receiver: Arc<Mutex<mpsc::Receiver<ReportingMessage>>>,
@@ -144,15 +165,16 @@ async fn ingest_buffered_data(thread_id: usize, buffered: Vec<ReportingData>) {
         buffered.len()
     );
 
-    let (usages, triggers, errors) = buffered.into_iter().fold(
-        (Vec::new(), Vec::new(), Vec::new()),
-        |(mut usages, mut triggers, mut errors), item| {
+    let (usages, triggers, errors, audits) = buffered.into_iter().fold(
+        (Vec::new(), Vec::new(), Vec::new(), Vec::new()),
+        |(mut usages, mut triggers, mut errors, mut audits), item| {
             match item {
                 ReportingData::Usage(usage) => usages.push(*usage),
                 ReportingData::Trigger(trigger) => triggers.push(json::to_value(*trigger).unwrap()),
                 ReportingData::Error(error) => errors.push(json::to_value(*error).unwrap()),
+                ReportingData::Audit(audit) => audits.push(json::to_value(*audit).unwrap()),
             }
-            (usages, triggers, errors)
+            (usages, triggers, errors, audits)
         },
     );
 
@@ -221,4 +243,23 @@ async fn ingest_buffered_data(thread_id: usize, buffered: Vec<ReportingData>) {
             }
         }
     }
+
+    if !audits.is_empty() {
+        let audit_stream = StreamParams::new(META_ORG_ID, AUDIT_STREAM, StreamType::Logs);
+        if super::ingestion::ingest_reporting_data(audits.clone(), audit_stream)
+            .await
+            .is_err()
+        {
+            // on error in ingesting audit data, push back the data. The job
+            // is the only reader of the queue, so it must not wait for room.
+            for audit_json in audits {
+                let audit: AuditData = json::from_value(audit_json).unwrap();
+                if let Err(e) = AUDIT_QUEUE.try_enqueue(ReportingData::Audit(Box::new(audit))) {
+                    log::error!(
+                        "[SELF-REPORTING] Error in pushing back un-ingested AuditData to AuditQueue: {e}"
+                    );
+                }
+            }
+        }
+    }
 }
@@ -13,7 +13,79 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use super::db;
+use std::collections::HashMap;
+
+use actix_web::{HttpRequest, http::header};
+use config::{
+    get_config,
+    meta::{session::SessionInfo, user::UserRole},
+    utils::{base64, json, time::now_micros},
+};
+
+use super::{db, users};
+use crate::common::{
+    infra::config::{ORGANIZATION_SETTING, USER_SESSION_INFOS, USER_SESSIONS},
+    meta::user::AuthTokens,
+    utils::auth::is_root_user,
+};
+
+/// The last seen time is persisted at most once per minute per session.
+const LAST_SEEN_INTERVAL: i64 = 60 * 1_000_000;
+
+/// Prefix of the value of the sessions opened by a native login, the value
+/// only has the user and never the credentials.
+const NATIVE_SESSION_PREFIX: &str = "native ";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SessionError {
+    #[error("Session {0} not found")]
+    NotFound(String),
+    #[error("Not allowed to manage the sessions of {0}")]
+    Forbidden(String),
+    #[error("Session expired after being idle")]
+    Idle,
+    #[error("Session expired")]
+    Expired,
+    #[error("Too many sessions, the oldest ones are logged out")]
+    TooMany,
+    #[error("{0}")]
+    InfraError(#[from] anyhow::Error),
+}
+
+/// The client which opened a session.
+#[derive(Clone, Debug, Default)]
+pub struct SessionClient {
+    pub ip: String,
+    pub user_agent: String,
+}
+
+impl SessionClient {
+    pub fn from_request(req: &HttpRequest) -> Self {
+        let conn = req.connection_info();
+        let ip = if req.headers().contains_key("X-Forwarded-For")
+            || req.headers().contains_key("Forwarded")
+        {
+            conn.realip_remote_addr()
+        } else {
+            conn.peer_addr()
+        };
+        let user_agent = req
+            .headers()
+            .get(header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        Self {
+            ip: ip.unwrap_or_default().to_string(),
+            user_agent: user_agent.to_string(),
+        }
+    }
+}
+
+/// Returns the handle of a session, the id is the credential in the login
+/// cookie and is never shown.
+pub fn session_handle(session_id: &str) -> String {
+    sha256::digest(session_id)
+}
 
 pub async fn get_session(session_id: &str) -> Option<String> {
     db::session::get(session_id).await.ok()
@@ -23,6 +95,343 @@ pub async fn set_session(session_id: &str, val: &str) -> Option<()> {
     db::session::set(session_id, val).await.ok()
 }
 
+/// Stores a new session of the user along with the client which opened it.
+pub async fn create_session(
+    session_id: &str,
+    val: &str,
+    user_email: &str,
+    client: &SessionClient,
+) -> Option<()> {
+    set_session(session_id, val).await?;
+    let now = now_micros();
+    let info = SessionInfo {
+        handle: session_handle(session_id),
+        user_email: user_email.to_string(),
+        created_at: now,
+        expires_at: session_expires_at(now),
+        last_seen_at: now,
+        ip: client.ip.clone(),
+        user_agent: client.user_agent.clone(),
+    };
+    // the session would never expire without its info
+    if let Err(e) = db::session::set_info(&info, db::NEED_WATCH).await {
+        log::error!(
+            "[SESSION] failed to save the info of session {}: {e}",
+            info.handle
+        );
+        let _ = db::session::delete(session_id).await;
+        return None;
+    }
+    Some(())
+}
+
+/// Stores a new session of a user logged in with a password, the session
+/// is resolved to the user by [`get_native_user`].
+pub async fn create_native_session(
+    session_id: &str,
+    user_email: &str,
+    client: &SessionClient,
+) -> Option<()> {
+    let val = format!("{NATIVE_SESSION_PREFIX}{user_email}");
+    create_session(session_id, &val, user_email, client).await
+}
+
+/// Returns the user of a session opened by a native login, none when the
+/// session doesn't exist, has expired or was opened by an OIDC login.
+pub async fn get_native_user(session_id: &str) -> Option<String> {
+    let val = get_session(session_id).await?;
+    let user_email = val.strip_prefix(NATIVE_SESSION_PREFIX)?.to_string();
+    let handle = session_handle(session_id);
+    let info = match USER_SESSION_INFOS.get(&handle) {
+        Some(info) => info.value().clone(),
+        None => db::session::get_info(&handle).await.ok()?,
+    };
+    if info.is_expired(now_micros()) {
+        remove_session(session_id).await;
+        return None;
+    }
+    Some(user_email)
+}
+
+/// Returns true when the value of a session belongs to a native login.
+pub fn is_native_session(val: &str) -> bool {
+    val.starts_with(NATIVE_SESSION_PREFIX)
+}
+
+/// Sessions expire with the login cookie.
+fn session_expires_at(now: i64) -> i64 {
+    now + get_config().auth.cookie_max_age * 1_000_000
+}
+
+/// Logs out the sessions which are past their expiry time, returns how many
+/// there were.
+pub async fn remove_expired_sessions() -> Result<usize, anyhow::Error> {
+    let now = now_micros();
+    let handles = db::session::list_info()
+        .await?
+        .into_iter()
+        .filter(|s| s.is_expired(now))
+        .map(|s| s.handle)
+        .collect::<Vec<_>>();
+    if !handles.is_empty() {
+        remove_sessions(&handles).await?;
+    }
+    Ok(handles.len())
+}
+
 pub async fn remove_session(session_id: &str) {
     let _ = db::session::delete(session_id).await;
+    let _ = db::session::delete_info(&session_handle(session_id)).await;
+}
+
+/// Returns the ids of the sessions by their handle, out of the given handles.
+async fn find_session_ids(handles: &[String]) -> Result<HashMap<String, String>, anyhow::Error> {
+    let mut ids = HashMap::with_capacity(handles.len());
+    for id in USER_SESSIONS.iter().map(|s| s.key().clone()) {
+        let handle = session_handle(&id);
+        if handles.contains(&handle) {
+            ids.insert(handle, id);
+        }
+    }
+    // the sessions which aren't in the cache of this node yet
+    if ids.len() < handles.len() {
+        for id in db::session::list_ids().await? {
+            let handle = session_handle(&id);
+            if handles.contains(&handle) {
+                ids.insert(handle, id);
+            }
+        }
+    }
+    Ok(ids)
+}
+
+/// Logs out the sessions with the given handles.
+async fn remove_sessions(handles: &[String]) -> Result<(), anyhow::Error> {
+    let ids = find_session_ids(handles).await?;
+    for handle in handles.iter() {
+        if let Some(id) = ids.get(handle) {
+            let _ = db::session::delete(id).await;
+        }
+        let _ = db::session::delete_info(handle).await;
+    }
+    Ok(())
+}
+
+/// Returns the session id of the login cookie of the request.
+pub fn session_id_from_request(req: &HttpRequest) -> Option<String> {
+    let cookie = req.cookie("auth_tokens")?;
+    let val = base64::decode_raw(cookie.value()).ok()?;
+    let tokens: AuthTokens = json::from_slice(&val).ok()?;
+    tokens
+        .access_token
+        .strip_prefix("session ")
+        .map(|id| id.to_string())
+}
+
+/// Enforces the session policies of the org on a request made with a session
+/// of `user_email` and records the activity of the session. A session which
+/// breaks a policy is logged out.
+pub async fn check_session(
+    session_id: &str,
+    user_email: &str,
+    org_id: &str,
+) -> Result<(), SessionError> {
+    let now = now_micros();
+    let handle = session_handle(session_id);
+    let cached = USER_SESSION_INFOS.get(&handle).map(|v| v.value().clone());
+    let is_new = cached.is_none();
+    // sessions opened before their info was recorded
+    let mut info = cached.unwrap_or_else(|| SessionInfo {
+        handle: handle.clone(),
+        created_at: now,
+        expires_at: session_expires_at(now),
+        last_seen_at: now,
+        ..Default::default()
+    });
+    if info.is_expired(now) {
+        log::info!("[SESSION] session {handle} of {user_email} expired, logging out");
+        remove_session(session_id).await;
+        return Err(SessionError::Expired);
+    }
+    let need_save = is_new || info.user_email.is_empty();
+    info.user_email = user_email.to_string();
+
+    let (idle_timeout, max_sessions) = ORGANIZATION_SETTING
+        .read()
+        .await
+        .get(&format!(
+            "{}/{org_id}",
+            db::organization::ORG_SETTINGS_KEY_PREFIX
+        ))
+        .map(|s| (s.session_idle_timeout, s.max_concurrent_sessions))
+        .unwrap_or_default();
+
+    if info.is_idle(idle_timeout, now) {
+        // the session may have been seen more recently by another node
+        if let Ok(stored) = db::session::get_info(&handle).await {
+            info.last_seen_at = info.last_seen_at.max(stored.last_seen_at);
+        }
+        if info.is_idle(idle_timeout, now) {
+            log::info!("[SESSION] session {handle} of {user_email} is idle, logging out");
+            remove_session(session_id).await;
+            return Err(SessionError::Idle);
+        }
+    }
+
+    if max_sessions > 0 {
+        let mut sessions = USER_SESSION_INFOS
+            .iter()
+            .filter(|s| s.user_email == user_email)
+            .map(|s| (s.created_at, s.handle.clone()))
+            .collect::<Vec<_>>();
+        sessions.sort_by(|a, b| b.cmp(a));
+        if let Some(pos) = sessions.iter().position(|(_, h)| *h == handle)
+            && pos >= max_sessions as usize
+        {
+            log::info!("[SESSION] too many sessions of {user_email}, logging out {handle}");
+            remove_session(session_id).await;
+            return Err(SessionError::TooMany);
+        }
+    }
+
+    if !need_save && now - info.last_seen_at < LAST_SEEN_INTERVAL {
+        return Ok(());
+    }
+    info.last_seen_at = now;
+    if let Err(e) = db::session::set_info(&info, is_new).await {
+        log::error!("[SESSION] failed to save the last seen time of {handle}: {e}");
+    }
+    Ok(())
+}
+
+/// Users manage their own sessions, root and the org admins manage the
+/// sessions of the members of the org.
+async fn can_manage(org_id: &str, initiator: &str, user_email: &str) -> bool {
+    if initiator == user_email || is_root_user(initiator) {
+        return true;
+    }
+    users::get_user(Some(org_id), initiator)
+        .await
+        .is_some_and(|user| matches!(user.role, UserRole::Admin | UserRole::Root))
+        && users::get_user(Some(org_id), user_email).await.is_some()
+}
+
+/// Lists the sessions of the user, the most recent first.
+pub async fn list(
+    org_id: &str,
+    initiator: &str,
+    user_email: &str,
+) -> Result<Vec<SessionInfo>, SessionError> {
+    if !can_manage(org_id, initiator, user_email).await {
+        return Err(SessionError::Forbidden(user_email.to_string()));
+    }
+    let mut sessions = list_user_sessions(user_email).await?;
+    sessions.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(sessions)
+}
+
+/// Logs out one session of the user by its handle.
+pub async fn revoke(
+    org_id: &str,
+    initiator: &str,
+    user_email: &str,
+    handle: &str,
+) -> Result<(), SessionError> {
+    if !can_manage(org_id, initiator, user_email).await {
+        return Err(SessionError::Forbidden(user_email.to_string()));
+    }
+    match db::session::get_info(handle).await {
+        Ok(info) if info.user_email == user_email => {
+            remove_sessions(&[info.handle]).await?;
+            Ok(())
+        }
+        _ => Err(SessionError::NotFound(handle.to_string())),
+    }
+}
+
+/// Logs out all the sessions of the user, returns how many there were.
+pub async fn revoke_all(
+    org_id: &str,
+    initiator: &str,
+    user_email: &str,
+) -> Result<usize, SessionError> {
+    if !can_manage(org_id, initiator, user_email).await {
+        return Err(SessionError::Forbidden(user_email.to_string()));
+    }
+    Ok(revoke_user_sessions(user_email).await?)
+}
+
+/// Logs out all the sessions of the user, e.g. after a password or role
+/// change.
+pub async fn revoke_user_sessions(user_email: &str) -> Result<usize, anyhow::Error> {
+    let handles = list_user_sessions(user_email)
+        .await?
+        .into_iter()
+        .map(|s| s.handle)
+        .collect::<Vec<_>>();
+    if !handles.is_empty() {
+        remove_sessions(&handles).await?;
+        log::info!(
+            "[SESSION] logged out {} sessions of {user_email}",
+            handles.len()
+        );
+    }
+    Ok(handles.len())
+}
+
+async fn list_user_sessions(user_email: &str) -> Result<Vec<SessionInfo>, anyhow::Error> {
+    Ok(db::session::list_info()
+        .await?
+        .into_iter()
+        .filter(|s| s.user_email == user_email)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{cookie::Cookie, test::TestRequest};
+
+    use super::*;
+
+    fn request(access_token: &str) -> HttpRequest {
+        let tokens = json::to_string(&AuthTokens {
+            access_token: access_token.to_string(),
+            refresh_token: "".to_string(),
+        })
+        .unwrap();
+        TestRequest::default()
+            .cookie(Cookie::new("auth_tokens", base64::encode(&tokens)))
+            .insert_header((header::USER_AGENT, "curl/8.0"))
+            .to_http_request()
+    }
+
+    #[test]
+    fn test_session_id_from_request() {
+        assert_eq!(
+            session_id_from_request(&request("session abc")).as_deref(),
+            Some("abc")
+        );
+        assert!(session_id_from_request(&request("Basic YTpi")).is_none());
+        assert!(session_id_from_request(&TestRequest::default().to_http_request()).is_none());
+    }
+
+    #[test]
+    fn test_is_native_session() {
+        assert!(is_native_session("native a@b.com"));
+        assert!(!is_native_session("eyJhbGciOiJIUzI1NiJ9.e30.sig"));
+    }
+
+    #[test]
+    fn test_session_handle() {
+        let handle = session_handle("abc");
+        assert_eq!(handle, session_handle("abc"));
+        assert_ne!(handle, "abc");
+        assert_ne!(handle, session_handle("abd"));
+    }
+
+    #[test]
+    fn test_session_client() {
+        let client = SessionClient::from_request(&request("session abc"));
+        assert_eq!(client.user_agent, "curl/8.0");
+    }
 }
@@ -200,6 +200,7 @@ pub fn stream_res(
         total_fields: mappings.len(),
         schema: mappings,
         uds_schema: None,
+        compression_ratio: stats.compression_ratio(),
         stats,
         settings,
         metrics_meta,
@@ -333,6 +334,50 @@ pub async fn save_stream_settings(
         }
     }
 
+    if let Some(parquet_settings) = settings.parquet_settings.as_ref() {
+        if let Err(e) = parquet_settings.validate() {
+            return Ok(HttpResponse::BadRequest().json(MetaHttpResponse::error(
+                http::StatusCode::BAD_REQUEST,
+                format!("invalid parquet settings: {e}"),
+            )));
+        }
+        // the inverted index maps segments to row groups with the fixed row group size
+        if cfg.common.inverted_index_enabled
+            && parquet_settings
+                .max_row_group_size
+                .is_some_and(|v| v != config::PARQUET_MAX_ROW_GROUP_SIZE)
+        {
+            return Ok(HttpResponse::BadRequest().json(MetaHttpResponse::error(
+                http::StatusCode::BAD_REQUEST,
+                "max_row_group_size can't be changed when inverted index is enabled",
+            )));
+        }
+    }
+
+    if let Some(tail_sampling) = settings.tail_sampling.as_ref() {
+        if tail_sampling.enabled && stream_type != StreamType::Traces {
+            return Ok(HttpResponse::BadRequest().json(MetaHttpResponse::error(
+                http::StatusCode::BAD_REQUEST,
+                "tail sampling is only supported for traces streams",
+            )));
+        }
+        if let Err(e) = tail_sampling.validate() {
+            return Ok(HttpResponse::BadRequest().json(MetaHttpResponse::error(
+                http::StatusCode::BAD_REQUEST,
+                format!("invalid tail sampling settings: {e}"),
+            )));
+        }
+    }
+
+    if let Some(masking) = settings.masking.as_ref()
+        && let Err(e) = masking.validate()
+    {
+        return Ok(HttpResponse::BadRequest().json(MetaHttpResponse::error(
+            http::StatusCode::BAD_REQUEST,
+            format!("invalid masking settings: {e}"),
+        )));
+    }
+
     let mut metadata = schema.metadata.clone();
     metadata.insert("settings".to_string(), json::to_string(&settings).unwrap());
     if !metadata.contains_key("created_at") {
@@ -585,6 +630,19 @@ pub async fn update_stream_settings(
             if let Some(partition_time_level) = new_settings.partition_time_level {
                 settings.partition_time_level = Some(partition_time_level);
             }
+
+            if let Some(parquet_settings) = new_settings.parquet_settings {
+                settings.parquet_settings =
+                    (!parquet_settings.is_empty()).then_some(parquet_settings);
+            }
+
+            if let Some(tail_sampling) = new_settings.tail_sampling {
+                settings.tail_sampling = Some(tail_sampling);
+            }
+
+            if let Some(masking) = new_settings.masking {
+                settings.masking = (!masking.rules.is_empty()).then_some(masking);
+            }
             save_stream_settings(org_id, stream_name, stream_type, settings).await
         }
         None => Ok(HttpResponse::BadRequest().json(MetaHttpResponse::error(
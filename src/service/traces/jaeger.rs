@@ -0,0 +1,676 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Jaeger thrift span ingestion, the batches which the jaeger clients post to
+//! the collector `/api/traces` endpoint are decoded with the thrift binary
+//! protocol and converted to the otlp model.
+//!
+//! ref: https://github.com/jaegertracing/jaeger-idl/blob/main/thrift/jaeger.thrift
+
+use std::io::Error;
+
+use actix_web::{HttpResponse, http, web};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use config::meta::otlp::OtlpRequestType;
+use opentelemetry_proto::tonic::{
+    collector::trace::v1::ExportTraceServiceRequest,
+    common::v1::{AnyValue, KeyValue, any_value::Value},
+    resource::v1::Resource,
+    trace::v1::{
+        ResourceSpans, ScopeSpans, Status,
+        span::{Event, Link, SpanKind},
+        status::StatusCode,
+    },
+};
+
+use crate::common::meta::http::HttpResponse as MetaHttpResponse;
+
+const REF_TYPE_CHILD_OF: i32 = 0;
+const REF_TYPE_FOLLOWS_FROM: i32 = 1;
+const ATTR_REF_TYPE: &str = "opentracing.ref_type";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TagValue {
+    String(String),
+    Double(f64),
+    Bool(bool),
+    Long(i64),
+    Binary(Vec<u8>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tag {
+    pub key: String,
+    pub value: TagValue,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Log {
+    pub timestamp: i64, // microseconds
+    pub fields: Vec<Tag>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SpanRef {
+    pub ref_type: i32,
+    pub trace_id_low: i64,
+    pub trace_id_high: i64,
+    pub span_id: i64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Span {
+    pub trace_id_low: i64,
+    pub trace_id_high: i64,
+    pub span_id: i64,
+    pub parent_span_id: i64,
+    pub operation_name: String,
+    pub references: Vec<SpanRef>,
+    pub flags: i32,
+    pub start_time: i64, // microseconds
+    pub duration: i64,   // microseconds
+    pub tags: Vec<Tag>,
+    pub logs: Vec<Log>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Process {
+    pub service_name: String,
+    pub tags: Vec<Tag>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Batch {
+    pub process: Process,
+    pub spans: Vec<Span>,
+}
+
+pub async fn ingest_thrift(
+    org_id: &str,
+    body: web::Bytes,
+    in_stream_name: Option<&str>,
+) -> Result<HttpResponse, Error> {
+    let batch = match thrift::decode_batch(body.as_ref()) {
+        Ok(v) => v,
+        Err(e) => {
+            log::error!("[TRACES:JAEGER] Invalid thrift: org_id: {org_id}, error: {e}");
+            return Ok(HttpResponse::BadRequest().json(MetaHttpResponse::error(
+                http::StatusCode::BAD_REQUEST,
+                format!("Invalid thrift: {e}"),
+            )));
+        }
+    };
+    let request = to_otlp(batch);
+    let resp = super::handle_otlp_request(
+        org_id,
+        request,
+        OtlpRequestType::HttpProtobuf,
+        in_stream_name,
+    )
+    .await?;
+    Ok(super::accepted_response(resp))
+}
+
+/// Converts a jaeger batch to an otlp request.
+///
+/// The first `CHILD_OF` reference (or the first `FOLLOWS_FROM` reference when
+/// there is no `CHILD_OF` one) becomes the parent of the span, the other
+/// references are kept as links with the `opentracing.ref_type` attribute.
+pub fn to_otlp(batch: Batch) -> ExportTraceServiceRequest {
+    let mut resource_attrs = vec![string_attr(super::SERVICE_NAME, batch.process.service_name)];
+    resource_attrs.extend(batch.process.tags.into_iter().map(tag_to_attr));
+
+    let spans = batch.spans.into_iter().map(convert_span).collect();
+    ExportTraceServiceRequest {
+        resource_spans: vec![ResourceSpans {
+            resource: Some(Resource {
+                attributes: resource_attrs,
+                dropped_attributes_count: 0,
+            }),
+            scope_spans: vec![ScopeSpans {
+                scope: None,
+                spans,
+                schema_url: "".to_string(),
+            }],
+            schema_url: "".to_string(),
+        }],
+    }
+}
+
+fn convert_span(span: Span) -> opentelemetry_proto::tonic::trace::v1::Span {
+    let trace_id = trace_id_bytes(span.trace_id_high, span.trace_id_low);
+
+    let parent_idx = span
+        .references
+        .iter()
+        .position(|r| r.ref_type == REF_TYPE_CHILD_OF)
+        .or_else(|| {
+            span.references
+                .iter()
+                .position(|r| r.ref_type == REF_TYPE_FOLLOWS_FROM)
+        });
+    let parent_span_id = match parent_idx {
+        Some(idx) => span.references[idx].span_id,
+        None => span.parent_span_id,
+    };
+    let links = span
+        .references
+        .iter()
+        .enumerate()
+        .filter(|(idx, r)| Some(*idx) != parent_idx && r.span_id != 0)
+        .map(|(_, r)| Link {
+            trace_id: trace_id_bytes(r.trace_id_high, r.trace_id_low),
+            span_id: r.span_id.to_be_bytes().to_vec(),
+            trace_state: "".to_string(),
+            attributes: vec![string_attr(
+                ATTR_REF_TYPE,
+                if r.ref_type == REF_TYPE_FOLLOWS_FROM {
+                    "follows_from".to_string()
+                } else {
+                    "child_of".to_string()
+                },
+            )],
+            dropped_attributes_count: 0,
+            flags: 0,
+        })
+        .collect();
+
+    let mut kind = SpanKind::Internal;
+    let mut status = None;
+    let mut attributes = Vec::with_capacity(span.tags.len());
+    for tag in span.tags {
+        match (tag.key.as_str(), &tag.value) {
+            ("span.kind", TagValue::String(v)) => {
+                kind = match v.as_str() {
+                    "client" => SpanKind::Client,
+                    "server" => SpanKind::Server,
+                    "producer" => SpanKind::Producer,
+                    "consumer" => SpanKind::Consumer,
+                    _ => SpanKind::Internal,
+                };
+                continue;
+            }
+            ("error", TagValue::Bool(true)) => {
+                status = Some(Status {
+                    message: "".to_string(),
+                    code: StatusCode::Error as i32,
+                });
+            }
+            _ => {}
+        }
+        attributes.push(tag_to_attr(tag));
+    }
+
+    let events = span
+        .logs
+        .into_iter()
+        .map(|log| {
+            let mut name = "log".to_string();
+            let mut attributes = Vec::with_capacity(log.fields.len());
+            for field in log.fields {
+                if let ("event", TagValue::String(v)) = (field.key.as_str(), &field.value) {
+                    name = v.clone();
+                    continue;
+                }
+                attributes.push(tag_to_attr(field));
+            }
+            Event {
+                time_unix_nano: log.timestamp as u64 * 1000,
+                name,
+                attributes,
+                dropped_attributes_count: 0,
+            }
+        })
+        .collect();
+
+    let start_time = span.start_time as u64 * 1000;
+    opentelemetry_proto::tonic::trace::v1::Span {
+        trace_id,
+        span_id: span.span_id.to_be_bytes().to_vec(),
+        trace_state: "".to_string(),
+        parent_span_id: if parent_span_id != 0 {
+            parent_span_id.to_be_bytes().to_vec()
+        } else {
+            vec![]
+        },
+        flags: span.flags as u32,
+        name: span.operation_name,
+        kind: kind as i32,
+        start_time_unix_nano: start_time,
+        end_time_unix_nano: start_time + span.duration as u64 * 1000,
+        attributes,
+        dropped_attributes_count: 0,
+        events,
+        dropped_events_count: 0,
+        links,
+        dropped_links_count: 0,
+        status,
+    }
+}
+
+fn trace_id_bytes(high: i64, low: i64) -> Vec<u8> {
+    let mut id = Vec::with_capacity(16);
+    id.extend_from_slice(&high.to_be_bytes());
+    id.extend_from_slice(&low.to_be_bytes());
+    id
+}
+
+fn tag_to_attr(tag: Tag) -> KeyValue {
+    let value = match tag.value {
+        TagValue::String(v) => Value::StringValue(v),
+        TagValue::Double(v) => Value::DoubleValue(v),
+        TagValue::Bool(v) => Value::BoolValue(v),
+        TagValue::Long(v) => Value::IntValue(v),
+        TagValue::Binary(v) => Value::StringValue(BASE64_STANDARD.encode(v)),
+    };
+    KeyValue {
+        key: tag.key,
+        value: Some(AnyValue { value: Some(value) }),
+    }
+}
+
+fn string_attr(key: &str, value: String) -> KeyValue {
+    KeyValue {
+        key: key.to_string(),
+        value: Some(AnyValue {
+            value: Some(Value::StringValue(value)),
+        }),
+    }
+}
+
+/// A minimal decoder of the thrift binary protocol for the jaeger model.
+mod thrift {
+    use super::{Batch, Log, Process, Span, SpanRef, Tag, TagValue};
+
+    const TYPE_STOP: u8 = 0;
+    const TYPE_BOOL: u8 = 2;
+    const TYPE_BYTE: u8 = 3;
+    const TYPE_DOUBLE: u8 = 4;
+    const TYPE_I16: u8 = 6;
+    const TYPE_I32: u8 = 8;
+    const TYPE_I64: u8 = 10;
+    const TYPE_STRING: u8 = 11;
+    const TYPE_STRUCT: u8 = 12;
+    const TYPE_MAP: u8 = 13;
+    const TYPE_SET: u8 = 14;
+    const TYPE_LIST: u8 = 15;
+
+    const MAX_DEPTH: usize = 32;
+
+    pub(super) struct Reader<'a> {
+        buf: &'a [u8],
+        pos: usize,
+        depth: usize,
+    }
+
+    impl<'a> Reader<'a> {
+        pub(super) fn new(buf: &'a [u8]) -> Self {
+            Self {
+                buf,
+                pos: 0,
+                depth: 0,
+            }
+        }
+
+        fn read_exact(&mut self, n: usize) -> Result<&'a [u8], String> {
+            if self.buf.len() - self.pos < n {
+                return Err(format!("unexpected eof at {}", self.pos));
+            }
+            let v = &self.buf[self.pos..self.pos + n];
+            self.pos += n;
+            Ok(v)
+        }
+
+        fn read_u8(&mut self) -> Result<u8, String> {
+            Ok(self.read_exact(1)?[0])
+        }
+
+        fn read_bool(&mut self) -> Result<bool, String> {
+            Ok(self.read_u8()? != 0)
+        }
+
+        fn read_i16(&mut self) -> Result<i16, String> {
+            Ok(i16::from_be_bytes(self.read_exact(2)?.try_into().unwrap()))
+        }
+
+        fn read_i32(&mut self) -> Result<i32, String> {
+            Ok(i32::from_be_bytes(self.read_exact(4)?.try_into().unwrap()))
+        }
+
+        fn read_i64(&mut self) -> Result<i64, String> {
+            Ok(i64::from_be_bytes(self.read_exact(8)?.try_into().unwrap()))
+        }
+
+        fn read_double(&mut self) -> Result<f64, String> {
+            Ok(f64::from_bits(self.read_i64()? as u64))
+        }
+
+        fn read_size(&mut self) -> Result<usize, String> {
+            let size = self.read_i32()?;
+            if size < 0 || size as usize > self.buf.len() - self.pos {
+                return Err(format!("invalid size {size} at {}", self.pos));
+            }
+            Ok(size as usize)
+        }
+
+        fn read_binary(&mut self) -> Result<Vec<u8>, String> {
+            let len = self.read_size()?;
+            Ok(self.read_exact(len)?.to_vec())
+        }
+
+        fn read_string(&mut self) -> Result<String, String> {
+            String::from_utf8(self.read_binary()?).map_err(|e| e.to_string())
+        }
+
+        /// Returns the type and the id of the next field, `None` at the end of
+        /// the struct.
+        fn read_field_header(&mut self) -> Result<Option<(u8, i16)>, String> {
+            let ty = self.read_u8()?;
+            if ty == TYPE_STOP {
+                return Ok(None);
+            }
+            Ok(Some((ty, self.read_i16()?)))
+        }
+
+        fn read_list<T>(
+            &mut self,
+            f: impl Fn(&mut Self) -> Result<T, String>,
+        ) -> Result<Vec<T>, String> {
+            let elem_type = self.read_u8()?;
+            let size = self.read_size()?;
+            if elem_type != TYPE_STRUCT {
+                for _ in 0..size {
+                    self.skip(elem_type)?;
+                }
+                return Ok(vec![]);
+            }
+            let mut items = Vec::with_capacity(size);
+            for _ in 0..size {
+                items.push(self.read_struct(&f)?);
+            }
+            Ok(items)
+        }
+
+        fn read_struct<T>(
+            &mut self,
+            f: impl Fn(&mut Self) -> Result<T, String>,
+        ) -> Result<T, String> {
+            self.depth += 1;
+            if self.depth > MAX_DEPTH {
+                return Err("struct is nested too deep".to_string());
+            }
+            let ret = f(self);
+            self.depth -= 1;
+            ret
+        }
+
+        fn skip(&mut self, ty: u8) -> Result<(), String> {
+            match ty {
+                TYPE_BOOL | TYPE_BYTE => self.read_exact(1).map(|_| ()),
+                TYPE_I16 => self.read_exact(2).map(|_| ()),
+                TYPE_I32 => self.read_exact(4).map(|_| ()),
+                TYPE_DOUBLE | TYPE_I64 => self.read_exact(8).map(|_| ()),
+                TYPE_STRING => {
+                    let len = self.read_size()?;
+                    self.read_exact(len).map(|_| ())
+                }
+                TYPE_STRUCT => self.read_struct(|r| {
+                    while let Some((ty, _)) = r.read_field_header()? {
+                        r.skip(ty)?;
+                    }
+                    Ok(())
+                }),
+                TYPE_MAP => {
+                    let key_type = self.read_u8()?;
+                    let val_type = self.read_u8()?;
+                    let size = self.read_size()?;
+                    for _ in 0..size {
+                        self.skip(key_type)?;
+                        self.skip(val_type)?;
+                    }
+                    Ok(())
+                }
+                TYPE_SET | TYPE_LIST => {
+                    let elem_type = self.read_u8()?;
+                    let size = self.read_size()?;
+                    for _ in 0..size {
+                        self.skip(elem_type)?;
+                    }
+                    Ok(())
+                }
+                _ => Err(format!("unknown type {ty} at {}", self.pos)),
+            }
+        }
+    }
+
+    pub(super) fn decode_batch(buf: &[u8]) -> Result<Batch, String> {
+        let mut r = Reader::new(buf);
+        r.read_struct(read_batch)
+    }
+
+    fn read_batch(r: &mut Reader) -> Result<Batch, String> {
+        let mut batch = Batch::default();
+        while let Some((ty, id)) = r.read_field_header()? {
+            match (id, ty) {
+                (1, TYPE_STRUCT) => batch.process = r.read_struct(read_process)?,
+                (2, TYPE_LIST) => batch.spans = r.read_list(read_span)?,
+                _ => r.skip(ty)?,
+            }
+        }
+        Ok(batch)
+    }
+
+    fn read_process(r: &mut Reader) -> Result<Process, String> {
+        let mut process = Process::default();
+        while let Some((ty, id)) = r.read_field_header()? {
+            match (id, ty) {
+                (1, TYPE_STRING) => process.service_name = r.read_string()?,
+                (2, TYPE_LIST) => process.tags = r.read_list(read_tag)?,
+                _ => r.skip(ty)?,
+            }
+        }
+        Ok(process)
+    }
+
+    fn read_tag(r: &mut Reader) -> Result<Tag, String> {
+        let mut key = String::new();
+        let mut v_type = 0;
+        let (mut v_str, mut v_double, mut v_bool, mut v_long, mut v_binary) =
+            (String::new(), 0.0, false, 0, Vec::new());
+        while let Some((ty, id)) = r.read_field_header()? {
+            match (id, ty) {
+                (1, TYPE_STRING) => key = r.read_string()?,
+                (2, TYPE_I32) => v_type = r.read_i32()?,
+                (3, TYPE_STRING) => v_str = r.read_string()?,
+                (4, TYPE_DOUBLE) => v_double = r.read_double()?,
+                (5, TYPE_BOOL) => v_bool = r.read_bool()?,
+                (6, TYPE_I64) => v_long = r.read_i64()?,
+                (7, TYPE_STRING) => v_binary = r.read_binary()?,
+                _ => r.skip(ty)?,
+            }
+        }
+        let value = match v_type {
+            1 => TagValue::Double(v_double),
+            2 => TagValue::Bool(v_bool),
+            3 => TagValue::Long(v_long),
+            4 => TagValue::Binary(v_binary),
+            _ => TagValue::String(v_str),
+        };
+        Ok(Tag { key, value })
+    }
+
+    fn read_log(r: &mut Reader) -> Result<Log, String> {
+        let mut log = Log::default();
+        while let Some((ty, id)) = r.read_field_header()? {
+            match (id, ty) {
+                (1, TYPE_I64) => log.timestamp = r.read_i64()?,
+                (2, TYPE_LIST) => log.fields = r.read_list(read_tag)?,
+                _ => r.skip(ty)?,
+            }
+        }
+        Ok(log)
+    }
+
+    fn read_span_ref(r: &mut Reader) -> Result<SpanRef, String> {
+        let mut span_ref = SpanRef::default();
+        while let Some((ty, id)) = r.read_field_header()? {
+            match (id, ty) {
+                (1, TYPE_I32) => span_ref.ref_type = r.read_i32()?,
+                (2, TYPE_I64) => span_ref.trace_id_low = r.read_i64()?,
+                (3, TYPE_I64) => span_ref.trace_id_high = r.read_i64()?,
+                (4, TYPE_I64) => span_ref.span_id = r.read_i64()?,
+                _ => r.skip(ty)?,
+            }
+        }
+        Ok(span_ref)
+    }
+
+    fn read_span(r: &mut Reader) -> Result<Span, String> {
+        let mut span = Span::default();
+        while let Some((ty, id)) = r.read_field_header()? {
+            match (id, ty) {
+                (1, TYPE_I64) => span.trace_id_low = r.read_i64()?,
+                (2, TYPE_I64) => span.trace_id_high = r.read_i64()?,
+                (3, TYPE_I64) => span.span_id = r.read_i64()?,
+                (4, TYPE_I64) => span.parent_span_id = r.read_i64()?,
+                (5, TYPE_STRING) => span.operation_name = r.read_string()?,
+                (6, TYPE_LIST) => span.references = r.read_list(read_span_ref)?,
+                (7, TYPE_I32) => span.flags = r.read_i32()?,
+                (8, TYPE_I64) => span.start_time = r.read_i64()?,
+                (9, TYPE_I64) => span.duration = r.read_i64()?,
+                (10, TYPE_LIST) => span.tags = r.read_list(read_tag)?,
+                (11, TYPE_LIST) => span.logs = r.read_list(read_log)?,
+                _ => r.skip(ty)?,
+            }
+        }
+        Ok(span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes the test data with the thrift binary protocol
+    #[derive(Default)]
+    struct Writer(Vec<u8>);
+
+    impl Writer {
+        fn field(&mut self, ty: u8, id: i16) -> &mut Self {
+            self.0.push(ty);
+            self.0.extend_from_slice(&id.to_be_bytes());
+            self
+        }
+        fn stop(&mut self) -> &mut Self {
+            self.0.push(0);
+            self
+        }
+        fn i32(&mut self, id: i16, v: i32) -> &mut Self {
+            self.field(8, id);
+            self.0.extend_from_slice(&v.to_be_bytes());
+            self
+        }
+        fn i64(&mut self, id: i16, v: i64) -> &mut Self {
+            self.field(10, id);
+            self.0.extend_from_slice(&v.to_be_bytes());
+            self
+        }
+        fn string(&mut self, id: i16, v: &str) -> &mut Self {
+            self.field(11, id);
+            self.0.extend_from_slice(&(v.len() as i32).to_be_bytes());
+            self.0.extend_from_slice(v.as_bytes());
+            self
+        }
+        fn list(&mut self, id: i16, size: i32) -> &mut Self {
+            self.field(15, id);
+            self.0.push(12);
+            self.0.extend_from_slice(&size.to_be_bytes());
+            self
+        }
+        fn string_tag(&mut self, key: &str, v: &str) -> &mut Self {
+            self.string(1, key).i32(2, 0).string(3, v).stop()
+        }
+    }
+
+    fn encode_batch() -> Vec<u8> {
+        let mut w = Writer::default();
+        // process
+        w.field(12, 1).string(1, "frontend").list(2, 1);
+        w.string_tag("hostname", "host-1");
+        w.stop();
+        // spans
+        w.list(2, 1);
+        w.i64(1, 2).i64(2, 1).i64(3, 3).i64(4, 0);
+        w.string(5, "GET /api");
+        w.list(6, 2);
+        w.i32(1, 1).i64(2, 2).i64(3, 1).i64(4, 7).stop();
+        w.i32(1, 0).i64(2, 2).i64(3, 1).i64(4, 5).stop();
+        w.i32(7, 1).i64(8, 1556604172355737).i64(9, 1431);
+        w.list(10, 2);
+        w.string_tag("span.kind", "server");
+        w.string_tag("http.method", "GET");
+        w.list(11, 1);
+        w.i64(1, 1556604172355800).list(2, 1);
+        w.string_tag("event", "cache miss");
+        w.stop();
+        // unknown field is skipped
+        w.string(12, "unknown");
+        w.stop();
+        w.stop();
+        w.0
+    }
+
+    #[test]
+    fn test_decode_batch() {
+        let batch = thrift::decode_batch(&encode_batch()).unwrap();
+        assert_eq!(batch.process.service_name, "frontend");
+        assert_eq!(batch.process.tags.len(), 1);
+        assert_eq!(batch.spans.len(), 1);
+        let span = &batch.spans[0];
+        assert_eq!(span.trace_id_low, 2);
+        assert_eq!(span.trace_id_high, 1);
+        assert_eq!(span.operation_name, "GET /api");
+        assert_eq!(span.references.len(), 2);
+        assert_eq!(span.tags.len(), 2);
+        assert_eq!(span.logs[0].fields.len(), 1);
+    }
+
+    #[test]
+    fn test_decode_truncated_batch() {
+        let data = encode_batch();
+        assert!(thrift::decode_batch(&data[..data.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn test_to_otlp() {
+        let batch = thrift::decode_batch(&encode_batch()).unwrap();
+        let req = to_otlp(batch);
+        let rs = &req.resource_spans[0];
+        assert_eq!(rs.resource.as_ref().unwrap().attributes.len(), 2);
+        let span = &rs.scope_spans[0].spans[0];
+        assert_eq!(
+            hex::encode(&span.trace_id),
+            "00000000000000010000000000000002"
+        );
+        // the CHILD_OF reference is the parent, the FOLLOWS_FROM one is a link
+        assert_eq!(span.parent_span_id, 5_i64.to_be_bytes().to_vec());
+        assert_eq!(span.links.len(), 1);
+        assert_eq!(span.links[0].span_id, 7_i64.to_be_bytes().to_vec());
+        assert_eq!(span.kind, SpanKind::Server as i32);
+        assert_eq!(span.attributes.len(), 1);
+        assert_eq!(span.events[0].name, "cache miss");
+        assert_eq!(span.end_time_unix_nano - span.start_time_unix_nano, 1431000);
+    }
+}
@@ -36,7 +36,7 @@ use opentelemetry_proto::tonic::{
     collector::trace::v1::{
         ExportTracePartialSuccess, ExportTraceServiceRequest, ExportTraceServiceResponse,
     },
-    trace::v1::{Status, status::StatusCode},
+    trace::v1::{Status, span::SpanKind, status::StatusCode},
 };
 use prost::Message;
 use serde_json::Map;
@@ -52,7 +52,8 @@ use crate::{
         alerts::alert::AlertExt,
         format_stream_name,
         ingestion::{
-            TriggerAlertData, check_ingestion_allowed, evaluate_trigger, grpc::get_val, write_file,
+            TriggerAlertData, check_ingestion_allowed, evaluate_trigger, flow_control,
+            grpc::get_val, write_file,
         },
         logs::O2IngestJsonData,
         metadata::{
@@ -64,9 +65,17 @@ use crate::{
         pipeline::batch_execution::ExecutablePipelineTraceInputs,
         schema::{check_for_schema, stream_schema_exists},
         self_reporting::report_request_usage_stats,
+        traces::service_graph::ServiceGraphSpan,
     },
 };
 
+pub mod jaeger;
+pub mod red_metrics;
+pub mod service_graph;
+pub mod tail_sampling;
+pub mod traceql;
+pub mod zipkin;
+
 const SERVICE_NAME: &str = "service.name";
 const SERVICE: &str = "service";
 const PARENT_SPAN_ID: &str = "reference.parent_span_id";
@@ -155,10 +164,13 @@ pub async fn handle_otlp_request(
     if let Err(e) = check_ingestion_allowed(org_id, StreamType::Traces, None) {
         log::error!("[TRACES:OTLP] ingestion error: {e}");
         return Ok(
-            HttpResponse::ServiceUnavailable().json(MetaHttpResponse::error(
-                http::StatusCode::SERVICE_UNAVAILABLE,
-                e,
-            )),
+            flow_control::http_response(org_id, flow_control::get_protocol(req_type), &e)
+                .unwrap_or_else(|| {
+                    HttpResponse::ServiceUnavailable().json(MetaHttpResponse::error(
+                        http::StatusCode::SERVICE_UNAVAILABLE,
+                        e,
+                    ))
+                }),
         );
     }
 
@@ -575,10 +587,13 @@ pub async fn ingest_json(
     if let Err(e) = check_ingestion_allowed(org_id, StreamType::Traces, None) {
         log::error!("[TRACES:JSON] ingestion error: {e}");
         return Ok(
-            HttpResponse::ServiceUnavailable().json(MetaHttpResponse::error(
-                http::StatusCode::SERVICE_UNAVAILABLE,
-                e,
-            )),
+            flow_control::http_response(org_id, flow_control::get_protocol(req_type), &e)
+                .unwrap_or_else(|| {
+                    HttpResponse::ServiceUnavailable().json(MetaHttpResponse::error(
+                        http::StatusCode::SERVICE_UNAVAILABLE,
+                        e,
+                    ))
+                }),
         );
     }
 
@@ -700,6 +715,16 @@ fn get_span_status(status: Option<Status>) -> String {
     }
 }
 
+/// Zipkin and jaeger collectors respond `202 Accepted` with an empty body
+/// when the spans are accepted, other responses are kept as they are.
+fn accepted_response(resp: HttpResponse) -> HttpResponse {
+    if resp.status().is_success() {
+        HttpResponse::Accepted().finish()
+    } else {
+        resp
+    }
+}
+
 fn format_response(
     mut partial_success: ExportTracePartialSuccess,
     req_type: OtlpRequestType,
@@ -739,6 +764,19 @@ async fn write_traces_by_stream(
     json_data_by_stream: HashMap<String, O2IngestJsonData>,
 ) -> Result<(), Error> {
     for (traces_stream_name, (json_data, fn_num)) in json_data_by_stream {
+        // all the spans count for the metrics, not only the sampled ones
+        observe_spans(org_id, &traces_stream_name, &json_data).await;
+
+        // the kept traces are written by the sampler after the decision wait
+        if let Some(tail_sampling) =
+            infra::schema::get_settings(org_id, &traces_stream_name, StreamType::Traces)
+                .await
+                .and_then(|s| s.tail_sampling)
+                .filter(|s| s.enabled)
+        {
+            tail_sampling::add(org_id, &traces_stream_name, &tail_sampling, json_data).await?;
+            continue;
+        }
         let mut req_stats = match write_traces(org_id, &traces_stream_name, json_data).await {
             Ok(v) => v,
             Err(e) => {
@@ -971,6 +1009,96 @@ async fn write_traces(
     Ok(req_stats)
 }
 
+/// Sends the client and server spans to the service graph and counts the
+/// spans for the rate, errors and duration metrics. It runs before the tail
+/// sampling, so the metrics cover the dropped traces too.
+async fn observe_spans(
+    org_id: &str,
+    stream_name: &str,
+    json_data: &[(i64, json::Map<String, json::Value>)],
+) {
+    let cfg = get_config();
+    let red_metrics_dimensions = if cfg.common.traces_red_metrics_enabled {
+        Some(red_metrics::get_dimensions(org_id).await)
+    } else {
+        None
+    };
+    if !cfg.common.traces_service_graph_enabled && red_metrics_dimensions.is_none() {
+        return;
+    }
+
+    let mut service_graph_values = Vec::new();
+    let mut red_metrics_values = Vec::new();
+    for (_, record_val) in json_data {
+        if cfg.common.traces_service_graph_enabled {
+            let service_name = record_val
+                .get("service_name")
+                .map(json::get_string_value)
+                .unwrap_or_default();
+            let trace_id = record_val
+                .get("trace_id")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default();
+            if let Some(span) =
+                get_service_graph_span(stream_name, &service_name, trace_id, record_val)
+            {
+                service_graph_values.push(span);
+            }
+        }
+        if let Some(dimensions) = &red_metrics_dimensions
+            && let Some(item) = red_metrics::build_item(record_val, dimensions)
+        {
+            red_metrics_values.push(item);
+        }
+    }
+
+    if !service_graph_values.is_empty() {
+        service_graph::observe(org_id, service_graph_values).await;
+    }
+    if !red_metrics_values.is_empty() {
+        red_metrics::observe(org_id, red_metrics_values).await;
+    }
+}
+
+/// Returns the span for the service graph if it is a client or server span
+fn get_service_graph_span(
+    stream_name: &str,
+    service_name: &str,
+    trace_id: &str,
+    record_val: &json::Map<String, json::Value>,
+) -> Option<ServiceGraphSpan> {
+    let span_kind = record_val
+        .get("span_kind")
+        .map(json::get_string_value)?
+        .parse::<i32>()
+        .ok()?;
+    if ![
+        SpanKind::Client,
+        SpanKind::Server,
+        SpanKind::Producer,
+        SpanKind::Consumer,
+    ]
+    .contains(&SpanKind::try_from(span_kind).ok()?)
+    {
+        return None;
+    }
+    let get_str = |key: &str| record_val.get(key).map(json::get_string_value);
+    Some(ServiceGraphSpan {
+        stream_name: stream_name.to_string(),
+        trace_id: trace_id.to_string(),
+        span_id: get_str("span_id")?,
+        parent_span_id: get_str("reference_parent_span_id").unwrap_or_default(),
+        service_name: service_name.to_string(),
+        operation_name: get_str("operation_name").unwrap_or_default(),
+        span_kind,
+        duration: record_val
+            .get("duration")
+            .map(json::get_uint_value)
+            .unwrap_or_default(),
+        error: get_str("span_status").is_some_and(|v| v == "ERROR"),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use config::utils::json::json;
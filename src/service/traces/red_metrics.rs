@@ -0,0 +1,398 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Rate, errors and duration metrics generated from the ingested spans.
+//!
+//! The spans are aggregated in memory per organization and written as
+//! cumulative `calls_total` counters and `duration` histograms (unit
+//! milliseconds) into the metrics streams of the organization, so they can be
+//! used by PromQL and alerts. Every ingester writes its own series which are
+//! labeled with `service_instance_id`.
+
+use std::collections::HashMap;
+
+use config::{
+    cluster::LOCAL_NODE, get_config, meta::otlp::OtlpRequestType, metrics::SPAN_METRICS_BUCKET,
+    utils::json,
+};
+use once_cell::sync::Lazy;
+use opentelemetry_proto::tonic::{
+    collector::metrics::v1::ExportMetricsServiceRequest,
+    common::v1::{AnyValue, KeyValue, any_value},
+    metrics::v1::{
+        AggregationTemporality, Histogram, HistogramDataPoint, Metric, NumberDataPoint,
+        ResourceMetrics, ScopeMetrics, Sum, metric, number_data_point,
+    },
+    resource::v1::Resource,
+    trace::v1::span::SpanKind,
+};
+use tokio::{sync::RwLock, time};
+
+use crate::{
+    common::infra::config::ORGANIZATION_SETTING,
+    service::{db::organization::ORG_SETTINGS_KEY_PREFIX, metrics::otlp::handle_otlp_request},
+};
+
+pub const CALLS_METRIC_NAME: &str = "calls_total";
+pub const DURATION_METRIC_NAME: &str = "duration";
+
+/// Series which did not receive any span for this long are removed, a new
+/// span starts the series again from zero.
+const SERIES_IDLE_TTL: u64 = 3600 * 1_000_000_000; // nanoseconds
+
+static INSTANCE: Lazy<RedMetrics> = Lazy::new(RedMetrics::new);
+
+/// Sorted label pairs of a series.
+type Labels = Vec<(String, String)>;
+
+/// A span which is counted by the metrics.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanMetricsItem {
+    pub labels: Labels,
+    pub duration: f64, // milliseconds
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Series {
+    start_time: u64,
+    last_update: u64,
+    calls: u64,
+    duration_sum: f64,
+    duration_min: f64,
+    duration_max: f64,
+    // not cumulative, the last one is the overflow bucket
+    buckets: [u64; SPAN_METRICS_BUCKET.len() + 1],
+}
+
+impl Series {
+    fn new(now: u64) -> Self {
+        Self {
+            start_time: now,
+            last_update: now,
+            calls: 0,
+            duration_sum: 0.0,
+            duration_min: 0.0,
+            duration_max: 0.0,
+            buckets: [0; SPAN_METRICS_BUCKET.len() + 1],
+        }
+    }
+
+    fn observe(&mut self, duration: f64, now: u64) {
+        if self.calls == 0 || duration < self.duration_min {
+            self.duration_min = duration;
+        }
+        self.duration_max = self.duration_max.max(duration);
+        self.calls += 1;
+        self.duration_sum += duration;
+        self.last_update = now;
+        let idx = SPAN_METRICS_BUCKET
+            .iter()
+            .position(|bound| duration <= *bound)
+            .unwrap_or(SPAN_METRICS_BUCKET.len());
+        self.buckets[idx] += 1;
+    }
+}
+
+struct RedMetrics {
+    series: RwLock<HashMap<String, HashMap<Labels, Series>>>,
+}
+
+impl RedMetrics {
+    fn new() -> Self {
+        tokio::task::spawn(async move { run_flush().await });
+        Self {
+            series: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+/// Returns the span attributes which the organization added as labels.
+pub async fn get_dimensions(org_id: &str) -> Vec<String> {
+    let key = format!("{ORG_SETTINGS_KEY_PREFIX}/{org_id}");
+    ORGANIZATION_SETTING
+        .read()
+        .await
+        .get(&key)
+        .map(|setting| setting.red_metrics_dimensions.clone())
+        .unwrap_or_default()
+}
+
+/// Builds the metrics item of a flattened span record, the dimensions which
+/// the span doesn't have are skipped.
+pub fn build_item(
+    record_val: &json::Map<String, json::Value>,
+    dimensions: &[String],
+) -> Option<SpanMetricsItem> {
+    let get_str = |key: &str| record_val.get(key).map(json::get_string_value);
+    let span_kind = get_str("span_kind")?
+        .parse::<i32>()
+        .ok()
+        .and_then(|v| SpanKind::try_from(v).ok())
+        .unwrap_or(SpanKind::Unspecified);
+    let status_code = match get_str("span_status").as_deref() {
+        Some("OK") => "STATUS_CODE_OK",
+        Some("ERROR") => "STATUS_CODE_ERROR",
+        _ => "STATUS_CODE_UNSET",
+    };
+    let mut labels = vec![
+        ("service_name".to_string(), get_str("service_name")?),
+        ("span_name".to_string(), get_str("operation_name")?),
+        ("span_kind".to_string(), span_kind.as_str_name().to_string()),
+        ("status_code".to_string(), status_code.to_string()),
+    ];
+    for dim in dimensions {
+        if labels.iter().any(|(k, _)| k == dim) {
+            continue;
+        }
+        if let Some(val) = get_str(dim) {
+            labels.push((dim.to_string(), val));
+        }
+    }
+    labels.sort();
+    let duration = record_val
+        .get("duration")
+        .map(json::get_float_value)
+        .unwrap_or_default()
+        / 1000.0; // microseconds to milliseconds
+    Some(SpanMetricsItem { labels, duration })
+}
+
+/// Adds the spans to the series of the organization.
+pub async fn observe(org_id: &str, items: Vec<SpanMetricsItem>) {
+    let now = now_nanos();
+    let max_series = get_config().common.traces_red_metrics_max_series;
+    let mut series = INSTANCE.series.write().await;
+    let org_series = series.entry(org_id.to_string()).or_default();
+    let mut dropped = 0;
+    for item in items {
+        if let Some(s) = org_series.get_mut(&item.labels) {
+            s.observe(item.duration, now);
+            continue;
+        }
+        if max_series > 0 && org_series.len() >= max_series {
+            dropped += 1;
+            continue;
+        }
+        let mut s = Series::new(now);
+        s.observe(item.duration, now);
+        org_series.insert(item.labels, s);
+    }
+    if dropped > 0 {
+        log::warn!(
+            "[TRACES:RED_METRICS] org: {org_id} reached max series {max_series}, dropped {dropped} spans"
+        );
+    }
+}
+
+async fn run_flush() {
+    let mut interval = time::interval(time::Duration::from_secs(
+        get_config().common.traces_red_metrics_flush_interval,
+    ));
+    interval.tick().await; // the first tick is immediate
+    loop {
+        interval.tick().await;
+        flush().await;
+    }
+}
+
+async fn flush() {
+    let now = now_nanos();
+    let requests = {
+        let mut series = INSTANCE.series.write().await;
+        series.retain(|_, org_series| {
+            org_series.retain(|_, s| now.saturating_sub(s.last_update) < SERIES_IDLE_TTL);
+            !org_series.is_empty()
+        });
+        series
+            .iter()
+            .map(|(org_id, org_series)| (org_id.clone(), build_request(org_series, now)))
+            .collect::<Vec<_>>()
+    };
+    for (org_id, request) in requests {
+        if let Err(e) = handle_otlp_request(&org_id, request, OtlpRequestType::Grpc).await {
+            log::error!("[TRACES:RED_METRICS] org: {org_id} write span metrics error: {e}");
+        }
+    }
+}
+
+fn build_request(org_series: &HashMap<Labels, Series>, now: u64) -> ExportMetricsServiceRequest {
+    let mut calls = Vec::with_capacity(org_series.len());
+    let mut durations = Vec::with_capacity(org_series.len());
+    for (labels, s) in org_series {
+        let attributes = labels
+            .iter()
+            .map(|(k, v)| string_attribute(k, v))
+            .collect::<Vec<_>>();
+        calls.push(NumberDataPoint {
+            attributes: attributes.clone(),
+            start_time_unix_nano: s.start_time,
+            time_unix_nano: now,
+            value: Some(number_data_point::Value::AsInt(s.calls as i64)),
+            ..Default::default()
+        });
+        durations.push(HistogramDataPoint {
+            attributes,
+            start_time_unix_nano: s.start_time,
+            time_unix_nano: now,
+            count: s.calls,
+            sum: Some(s.duration_sum),
+            bucket_counts: s.buckets.to_vec(),
+            explicit_bounds: SPAN_METRICS_BUCKET.to_vec(),
+            min: Some(s.duration_min),
+            max: Some(s.duration_max),
+            ..Default::default()
+        });
+    }
+    let metrics = vec![
+        Metric {
+            name: CALLS_METRIC_NAME.to_string(),
+            description: "Number of spans".to_string(),
+            unit: "1".to_string(),
+            data: Some(metric::Data::Sum(Sum {
+                data_points: calls,
+                aggregation_temporality: AggregationTemporality::Cumulative as i32,
+                is_monotonic: true,
+            })),
+            ..Default::default()
+        },
+        Metric {
+            name: DURATION_METRIC_NAME.to_string(),
+            description: "Duration of spans".to_string(),
+            unit: "ms".to_string(),
+            data: Some(metric::Data::Histogram(Histogram {
+                data_points: durations,
+                aggregation_temporality: AggregationTemporality::Cumulative as i32,
+            })),
+            ..Default::default()
+        },
+    ];
+    ExportMetricsServiceRequest {
+        resource_metrics: vec![ResourceMetrics {
+            resource: Some(Resource {
+                attributes: vec![string_attribute("service.instance.id", &LOCAL_NODE.name)],
+                ..Default::default()
+            }),
+            scope_metrics: vec![ScopeMetrics {
+                metrics,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }],
+    }
+}
+
+fn string_attribute(key: &str, value: &str) -> KeyValue {
+    KeyValue {
+        key: key.to_string(),
+        value: Some(AnyValue {
+            value: Some(any_value::Value::StringValue(value.to_string())),
+        }),
+    }
+}
+
+fn now_nanos() -> u64 {
+    config::utils::time::now_micros() as u64 * 1000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(kind: i32, status: &str, duration: u64) -> json::Map<String, json::Value> {
+        json::json!({
+            "service_name": "frontend",
+            "operation_name": "GET /cart",
+            "span_kind": kind.to_string(),
+            "span_status": status,
+            "duration": duration,
+            "http_method": "GET",
+        })
+        .as_object()
+        .unwrap()
+        .clone()
+    }
+
+    #[test]
+    fn test_build_item() {
+        let item = build_item(
+            &record(2, "ERROR", 12_500),
+            &["http_method".to_string(), "missing".to_string()],
+        )
+        .unwrap();
+        assert_eq!(item.duration, 12.5);
+        assert_eq!(
+            item.labels,
+            vec![
+                ("http_method".to_string(), "GET".to_string()),
+                ("service_name".to_string(), "frontend".to_string()),
+                ("span_kind".to_string(), "SPAN_KIND_SERVER".to_string()),
+                ("span_name".to_string(), "GET /cart".to_string()),
+                ("status_code".to_string(), "STATUS_CODE_ERROR".to_string()),
+            ]
+        );
+
+        let mut val = record(1, "UNSET", 0);
+        val.remove("operation_name");
+        assert!(build_item(&val, &[]).is_none());
+    }
+
+    #[test]
+    fn test_series_observe() {
+        let mut s = Series::new(1);
+        s.observe(7.0, 2);
+        s.observe(0.1, 3);
+        s.observe(100_000.0, 4);
+        assert_eq!(s.calls, 3);
+        assert_eq!(s.last_update, 4);
+        assert_eq!(s.duration_min, 0.1);
+        assert_eq!(s.duration_max, 100_000.0);
+        assert_eq!(s.buckets[0], 1); // <= 0.1
+        assert_eq!(s.buckets[4], 1); // <= 10
+        assert_eq!(s.buckets[SPAN_METRICS_BUCKET.len()], 1); // overflow
+        assert_eq!(s.buckets.iter().sum::<u64>(), 3);
+    }
+
+    #[test]
+    fn test_build_request() {
+        let item = build_item(&record(3, "OK", 2000), &[]).unwrap();
+        let mut s = Series::new(10);
+        s.observe(item.duration, 20);
+        let org_series = HashMap::from([(item.labels, s)]);
+        let req = build_request(&org_series, 30);
+        let metrics = &req.resource_metrics[0].scope_metrics[0].metrics;
+        assert_eq!(metrics[0].name, CALLS_METRIC_NAME);
+        let Some(metric::Data::Sum(sum)) = &metrics[0].data else {
+            panic!("calls_total should be a sum");
+        };
+        assert!(sum.is_monotonic);
+        assert_eq!(sum.data_points[0].start_time_unix_nano, 10);
+        assert_eq!(sum.data_points[0].time_unix_nano, 30);
+        assert_eq!(
+            sum.data_points[0].value,
+            Some(number_data_point::Value::AsInt(1))
+        );
+        assert_eq!(metrics[1].name, DURATION_METRIC_NAME);
+        let Some(metric::Data::Histogram(hist)) = &metrics[1].data else {
+            panic!("duration should be a histogram");
+        };
+        assert_eq!(hist.data_points[0].count, 1);
+        assert_eq!(hist.data_points[0].sum, Some(2.0));
+        assert_eq!(
+            hist.data_points[0].bucket_counts.len(),
+            SPAN_METRICS_BUCKET.len() + 1
+        );
+        assert_eq!(hist.data_points[0].attributes.len(), 4);
+    }
+}
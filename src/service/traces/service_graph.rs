@@ -0,0 +1,769 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Service dependency graph derived from the client and server spans.
+//!
+//! A client span is paired with the server span whose parent is the client
+//! span, every paired call adds to the edge `(client, server, operation)`. The
+//! edges are written as cumulative `service_graph_requests_total` and
+//! `service_graph_failed_requests_total` counters and a
+//! `service_graph_request_duration` histogram (unit milliseconds) into the
+//! metrics streams of the organization, labeled with `service_instance_id`.
+//!
+//! The spans are paired in memory on the ingester which receives them, the two
+//! sides of a call are only paired when they land on the same ingester within
+//! `ZO_TRACES_SERVICE_GRAPH_WAIT_TIME`. Calls whose spans are routed to
+//! different ingesters are dropped, so the collectors should route the spans
+//! of a trace to the same ingester (e.g. load balancing by trace id) for a
+//! complete graph.
+
+use std::collections::{BTreeMap, HashMap};
+
+use config::{
+    cluster::LOCAL_NODE, get_config, meta::otlp::OtlpRequestType, utils::time::now_micros,
+};
+use infra::errors::{Error, Result};
+use once_cell::sync::Lazy;
+use opentelemetry_proto::tonic::{
+    collector::metrics::v1::ExportMetricsServiceRequest,
+    common::v1::{AnyValue, KeyValue, any_value},
+    metrics::v1::{
+        AggregationTemporality, Histogram, HistogramDataPoint, Metric, NumberDataPoint,
+        ResourceMetrics, ScopeMetrics, Sum, metric, number_data_point,
+    },
+    resource::v1::Resource,
+    trace::v1::span::SpanKind,
+};
+use serde::Serialize;
+use tokio::{sync::RwLock, time};
+use utoipa::ToSchema;
+
+use crate::service::{
+    metrics::otlp::handle_otlp_request,
+    promql::{
+        self, MetricsQueryRequest,
+        value::{InstantValue, LabelsExt, Value},
+    },
+};
+
+pub const REQUESTS_METRIC_NAME: &str = "service_graph_requests_total";
+pub const FAILED_REQUESTS_METRIC_NAME: &str = "service_graph_failed_requests_total";
+pub const DURATION_METRIC_NAME: &str = "service_graph_request_duration";
+
+/// Upper bounds of the latency histogram buckets, unit milliseconds.
+pub const LATENCY_BUCKETS: [u64; 11] = [5, 10, 25, 50, 100, 250, 500, 1000, 2500, 5000, 10000];
+
+/// Edges which did not receive any call for this long are removed, a new call
+/// starts the series again from zero.
+const SERIES_IDLE_TTL: u64 = 3600 * 1_000_000_000; // nanoseconds
+
+static INSTANCE: Lazy<GraphState> = Lazy::new(GraphState::new);
+
+struct GraphState {
+    state: RwLock<State>,
+}
+
+impl GraphState {
+    fn new() -> Self {
+        tokio::task::spawn(async move { run_flush().await });
+        Self {
+            state: RwLock::new(State::default()),
+        }
+    }
+}
+
+/// A client or server span which is used to build the edges of the graph.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ServiceGraphSpan {
+    pub stream_name: String,
+    pub trace_id: String,
+    pub span_id: String,
+    pub parent_span_id: String,
+    pub service_name: String,
+    pub operation_name: String,
+    pub span_kind: i32,
+    pub duration: u64, // microseconds
+    pub error: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CallSide {
+    Client,
+    Server,
+}
+
+impl ServiceGraphSpan {
+    fn side(&self) -> Option<CallSide> {
+        match SpanKind::try_from(self.span_kind).ok()? {
+            SpanKind::Client | SpanKind::Producer => Some(CallSide::Client),
+            SpanKind::Server | SpanKind::Consumer => Some(CallSide::Server),
+            _ => None,
+        }
+    }
+
+    /// The client span is paired with the server span whose parent is the
+    /// client span.
+    fn call_id(&self, side: CallSide) -> &str {
+        match side {
+            CallSide::Client => &self.span_id,
+            CallSide::Server => &self.parent_span_id,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct PendingCall {
+    client: Option<ServiceGraphSpan>,
+    server: Option<ServiceGraphSpan>,
+    created_at: i64,
+}
+
+#[derive(Debug, Default, Clone, Eq, Hash, PartialEq)]
+struct EdgeKey {
+    org_id: String,
+    stream_name: String,
+    client: String,
+    server: String,
+    operation: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct EdgeSeries {
+    start_time: u64,
+    last_update: u64,
+    requests: u64,
+    errors: u64,
+    duration_sum: f64, // milliseconds
+    // not cumulative, the last one is the overflow bucket
+    buckets: [u64; LATENCY_BUCKETS.len() + 1],
+}
+
+impl EdgeSeries {
+    fn new(now: u64) -> Self {
+        Self {
+            start_time: now,
+            last_update: now,
+            requests: 0,
+            errors: 0,
+            duration_sum: 0.0,
+            buckets: [0; LATENCY_BUCKETS.len() + 1],
+        }
+    }
+
+    fn observe(&mut self, duration: u64, error: bool, now: u64) {
+        let duration_ms = duration as f64 / 1000.0;
+        self.requests += 1;
+        self.duration_sum += duration_ms;
+        self.last_update = now;
+        if error {
+            self.errors += 1;
+        }
+        let idx = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| duration_ms <= *bound as f64)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.buckets[idx] += 1;
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    // (org_id, stream_name, trace_id, call_id) -> call
+    pending: HashMap<(String, String, String, String), PendingCall>,
+    edges: HashMap<EdgeKey, EdgeSeries>,
+}
+
+impl State {
+    fn add(
+        &mut self,
+        org_id: &str,
+        span: ServiceGraphSpan,
+        created_at: i64,
+        now: u64,
+        max_pending: usize,
+    ) {
+        let Some(side) = span.side() else {
+            return;
+        };
+        let call_id = span.call_id(side);
+        if call_id.is_empty() {
+            return;
+        }
+        let key = (
+            org_id.to_string(),
+            span.stream_name.clone(),
+            span.trace_id.clone(),
+            call_id.to_string(),
+        );
+        if !self.pending.contains_key(&key) && self.pending.len() >= max_pending {
+            log::debug!(
+                "[SERVICE_GRAPH] too many pending spans, drop span {}",
+                span.span_id
+            );
+            return;
+        }
+        let call = self
+            .pending
+            .entry(key.clone())
+            .or_insert_with(|| PendingCall {
+                created_at,
+                ..Default::default()
+            });
+        match side {
+            CallSide::Client => call.client = Some(span),
+            CallSide::Server => call.server = Some(span),
+        }
+        if call.client.is_none() || call.server.is_none() {
+            return;
+        }
+        let call = self.pending.remove(&key).unwrap();
+        let (client, server) = (call.client.unwrap(), call.server.unwrap());
+        // the latency is measured by the caller, it includes the network time
+        let duration = if client.duration > 0 {
+            client.duration
+        } else {
+            server.duration
+        };
+        self.edges
+            .entry(EdgeKey {
+                org_id: key.0,
+                stream_name: key.1,
+                client: client.service_name,
+                server: server.service_name,
+                operation: server.operation_name,
+            })
+            .or_insert_with(|| EdgeSeries::new(now))
+            .observe(duration, client.error || server.error, now);
+    }
+
+    /// Drops the calls whose other side didn't arrive in time.
+    fn expire(&mut self, before: i64) -> usize {
+        let len = self.pending.len();
+        self.pending.retain(|_, call| call.created_at >= before);
+        len - self.pending.len()
+    }
+}
+
+/// Pairs the client and server spans of the organization.
+pub async fn observe(org_id: &str, spans: Vec<ServiceGraphSpan>) {
+    let max_pending = get_config().common.traces_service_graph_max_pending_spans;
+    let now = now_nanos();
+    let created_at = now_micros();
+    let mut state = INSTANCE.state.write().await;
+    for span in spans {
+        state.add(org_id, span, created_at, now, max_pending);
+    }
+}
+
+async fn run_flush() {
+    let mut interval = time::interval(time::Duration::from_secs(
+        get_config().common.traces_service_graph_flush_interval,
+    ));
+    interval.tick().await; // the first tick is immediate
+    loop {
+        interval.tick().await;
+        flush().await;
+    }
+}
+
+async fn flush() {
+    let wait_time = get_config().common.traces_service_graph_wait_time as i64 * 1_000_000;
+    let now = now_nanos();
+    let requests = {
+        let mut state = INSTANCE.state.write().await;
+        let expired = state.expire(now_micros() - wait_time);
+        if expired > 0 {
+            log::debug!("[SERVICE_GRAPH] dropped {expired} unpaired calls");
+        }
+        state
+            .edges
+            .retain(|_, s| now.saturating_sub(s.last_update) < SERIES_IDLE_TTL);
+        let mut orgs: HashMap<&str, Vec<(&EdgeKey, &EdgeSeries)>> = HashMap::new();
+        for (key, series) in state.edges.iter() {
+            orgs.entry(&key.org_id).or_default().push((key, series));
+        }
+        orgs.into_iter()
+            .map(|(org_id, edges)| (org_id.to_string(), build_request(&edges, now)))
+            .collect::<Vec<_>>()
+    };
+    for (org_id, request) in requests {
+        if let Err(e) = handle_otlp_request(&org_id, request, OtlpRequestType::Grpc).await {
+            log::error!("[SERVICE_GRAPH] org: {org_id} write service graph metrics error: {e}");
+        }
+    }
+}
+
+fn build_request(edges: &[(&EdgeKey, &EdgeSeries)], now: u64) -> ExportMetricsServiceRequest {
+    let mut requests = Vec::with_capacity(edges.len());
+    let mut errors = Vec::with_capacity(edges.len());
+    let mut durations = Vec::with_capacity(edges.len());
+    for (key, s) in edges {
+        let attributes = vec![
+            string_attribute("trace_stream", &key.stream_name),
+            string_attribute("client", &key.client),
+            string_attribute("server", &key.server),
+            string_attribute("operation", &key.operation),
+        ];
+        let counter = |value: u64| NumberDataPoint {
+            attributes: attributes.clone(),
+            start_time_unix_nano: s.start_time,
+            time_unix_nano: now,
+            value: Some(number_data_point::Value::AsInt(value as i64)),
+            ..Default::default()
+        };
+        requests.push(counter(s.requests));
+        errors.push(counter(s.errors));
+        durations.push(HistogramDataPoint {
+            attributes,
+            start_time_unix_nano: s.start_time,
+            time_unix_nano: now,
+            count: s.requests,
+            sum: Some(s.duration_sum),
+            bucket_counts: s.buckets.to_vec(),
+            explicit_bounds: LATENCY_BUCKETS.iter().map(|b| *b as f64).collect(),
+            ..Default::default()
+        });
+    }
+    let counter_metric = |name: &str, description: &str, data_points| Metric {
+        name: name.to_string(),
+        description: description.to_string(),
+        unit: "1".to_string(),
+        data: Some(metric::Data::Sum(Sum {
+            data_points,
+            aggregation_temporality: AggregationTemporality::Cumulative as i32,
+            is_monotonic: true,
+        })),
+        ..Default::default()
+    };
+    let metrics = vec![
+        counter_metric(
+            REQUESTS_METRIC_NAME,
+            "Number of calls between two services",
+            requests,
+        ),
+        counter_metric(
+            FAILED_REQUESTS_METRIC_NAME,
+            "Number of failed calls between two services",
+            errors,
+        ),
+        Metric {
+            name: DURATION_METRIC_NAME.to_string(),
+            description: "Duration of calls between two services measured by the client"
+                .to_string(),
+            unit: "ms".to_string(),
+            data: Some(metric::Data::Histogram(Histogram {
+                data_points: durations,
+                aggregation_temporality: AggregationTemporality::Cumulative as i32,
+            })),
+            ..Default::default()
+        },
+    ];
+    ExportMetricsServiceRequest {
+        resource_metrics: vec![ResourceMetrics {
+            resource: Some(Resource {
+                attributes: vec![string_attribute("service.instance.id", &LOCAL_NODE.name)],
+                ..Default::default()
+            }),
+            scope_metrics: vec![ScopeMetrics {
+                metrics,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }],
+    }
+}
+
+fn string_attribute(key: &str, value: &str) -> KeyValue {
+    KeyValue {
+        key: key.to_string(),
+        value: Some(AnyValue {
+            value: Some(any_value::Value::StringValue(value.to_string())),
+        }),
+    }
+}
+
+fn now_nanos() -> u64 {
+    now_micros() as u64 * 1000
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ServiceGraph {
+    pub nodes: Vec<ServiceGraphNode>,
+    pub edges: Vec<ServiceGraphEdge>,
+}
+
+/// A service in the graph, the counters are the requests received by it.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ServiceGraphNode {
+    pub name: String,
+    pub requests: u64,
+    pub errors: u64,
+}
+
+/// Calls from `source` to `target`, the durations are in milliseconds and the
+/// percentiles are estimated from the latency buckets.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ServiceGraphEdge {
+    pub source: String,
+    pub target: String,
+    pub operation: String,
+    pub requests: u64,
+    pub errors: u64,
+    pub duration_avg: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+}
+
+// (client, server, operation)
+type EdgeLabels = (String, String, String);
+
+#[derive(Debug, Default)]
+struct EdgeValues {
+    requests: f64,
+    errors: f64,
+    duration_sum: f64,
+    // cumulative, keyed by the upper bound
+    buckets: Vec<(f64, f64)>,
+}
+
+/// Returns the service graph of the org for the given time range, optionally
+/// limited to one traces stream. The counters are the increase of the metrics
+/// in the time range.
+pub async fn query(
+    trace_id: &str,
+    org_id: &str,
+    user_email: &str,
+    stream_name: Option<&str>,
+    start_time: i64,
+    end_time: i64,
+) -> Result<ServiceGraph> {
+    let range = ((end_time - start_time) / 1_000_000).max(1);
+    let mut edges: BTreeMap<EdgeLabels, EdgeValues> = BTreeMap::new();
+    for (metric, by_le) in [
+        (REQUESTS_METRIC_NAME.to_string(), false),
+        (FAILED_REQUESTS_METRIC_NAME.to_string(), false),
+        (format!("{DURATION_METRIC_NAME}_sum"), false),
+        (format!("{DURATION_METRIC_NAME}_bucket"), true),
+    ] {
+        let req = MetricsQueryRequest {
+            query: build_promql(&metric, stream_name, range, by_le),
+            start: end_time,
+            end: end_time,
+            step: promql::micros(promql::MINIMAL_INTERVAL),
+            query_exemplars: false,
+            no_cache: None,
+        };
+        let resp = promql::search::search(trace_id, org_id, &req, user_email, 0).await?;
+        for value in instant_values(resp)? {
+            let labels = (
+                value.labels.get_value("client"),
+                value.labels.get_value("server"),
+                value.labels.get_value("operation"),
+            );
+            let edge = edges.entry(labels).or_default();
+            let v = value.sample.value;
+            if metric == REQUESTS_METRIC_NAME {
+                edge.requests = v;
+            } else if metric == FAILED_REQUESTS_METRIC_NAME {
+                edge.errors = v;
+            } else if by_le {
+                let le = value
+                    .labels
+                    .get_value("le")
+                    .parse::<f64>()
+                    .unwrap_or(f64::INFINITY);
+                edge.buckets.push((le, v));
+            } else {
+                edge.duration_sum = v;
+            }
+        }
+    }
+    Ok(build_graph(edges))
+}
+
+fn build_promql(metric: &str, stream_name: Option<&str>, range: i64, by_le: bool) -> String {
+    let selector = match stream_name {
+        Some(name) => format!(
+            "{metric}{{trace_stream=\"{}\"}}",
+            name.replace('\\', "\\\\").replace('"', "\\\"")
+        ),
+        None => metric.to_string(),
+    };
+    let by = if by_le {
+        "client, server, operation, le"
+    } else {
+        "client, server, operation"
+    };
+    format!("sum by ({by}) (increase({selector}[{range}s]))")
+}
+
+fn instant_values(value: Value) -> Result<Vec<InstantValue>> {
+    match value {
+        Value::Vector(values) => Ok(values),
+        Value::Matrix(values) => Ok(values
+            .into_iter()
+            .filter_map(|v| {
+                v.samples.last().map(|sample| InstantValue {
+                    labels: v.labels.clone(),
+                    sample: sample.clone(),
+                })
+            })
+            .collect()),
+        Value::None => Ok(vec![]),
+        v => Err(Error::Message(format!(
+            "unexpected service graph query response: {v:?}"
+        ))),
+    }
+}
+
+fn build_graph(edges: BTreeMap<EdgeLabels, EdgeValues>) -> ServiceGraph {
+    let mut nodes: BTreeMap<String, ServiceGraphNode> = BTreeMap::new();
+    let mut graph_edges = Vec::with_capacity(edges.len());
+    for ((source, target, operation), mut values) in edges {
+        let requests = values.requests.round() as u64;
+        if requests == 0 {
+            continue;
+        }
+        values.buckets.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let buckets = LATENCY_BUCKETS
+            .iter()
+            .map(|bound| {
+                values
+                    .buckets
+                    .iter()
+                    .find(|(le, _)| *le == *bound as f64)
+                    .map(|(_, count)| count.round() as u64)
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+        let edge = ServiceGraphEdge {
+            source,
+            target,
+            operation,
+            requests,
+            errors: values.errors.round() as u64,
+            duration_avg: values.duration_sum / requests as f64,
+            p50: estimate_percentile(&buckets, requests, 0.5),
+            p95: estimate_percentile(&buckets, requests, 0.95),
+            p99: estimate_percentile(&buckets, requests, 0.99),
+        };
+        nodes
+            .entry(edge.source.clone())
+            .or_insert_with(|| ServiceGraphNode {
+                name: edge.source.clone(),
+                ..Default::default()
+            });
+        let target = nodes
+            .entry(edge.target.clone())
+            .or_insert_with(|| ServiceGraphNode {
+                name: edge.target.clone(),
+                ..Default::default()
+            });
+        target.requests += edge.requests;
+        target.errors += edge.errors;
+        graph_edges.push(edge);
+    }
+    ServiceGraph {
+        nodes: nodes.into_values().collect(),
+        edges: graph_edges,
+    }
+}
+
+/// Estimates the percentile in milliseconds by linear interpolation inside
+/// the cumulative bucket which contains the rank. Requests slower than the
+/// last bucket are reported as the last bound.
+fn estimate_percentile(buckets: &[u64], total: u64, quantile: f64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    let rank = quantile * total as f64;
+    let mut prev_count = 0;
+    let mut prev_bound = 0;
+    for (bound, count) in LATENCY_BUCKETS.iter().zip(buckets) {
+        if *count as f64 >= rank {
+            let in_bucket = count.saturating_sub(prev_count);
+            if in_bucket == 0 {
+                return *bound as f64;
+            }
+            let fraction = (rank - prev_count as f64) / in_bucket as f64;
+            return prev_bound as f64 + (*bound - prev_bound) as f64 * fraction;
+        }
+        prev_count = *count;
+        prev_bound = *bound;
+    }
+    prev_bound as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(service: &str, kind: SpanKind, span_id: &str, parent: &str) -> ServiceGraphSpan {
+        ServiceGraphSpan {
+            stream_name: "default".to_string(),
+            trace_id: "t1".to_string(),
+            span_id: span_id.to_string(),
+            parent_span_id: parent.to_string(),
+            service_name: service.to_string(),
+            operation_name: format!("{service}_op"),
+            span_kind: kind as i32,
+            duration: 20_000,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_pair_client_and_server() {
+        let mut state = State::default();
+        // the server span may arrive before the client span
+        state.add(
+            "org",
+            span("backend", SpanKind::Server, "s2", "s1"),
+            0,
+            1,
+            10,
+        );
+        assert_eq!(state.pending.len(), 1);
+        let mut client = span("frontend", SpanKind::Client, "s1", "s0");
+        client.error = true;
+        state.add("org", client, 0, 2, 10);
+        assert!(state.pending.is_empty());
+        assert_eq!(state.edges.len(), 1);
+        let (key, series) = state.edges.iter().next().unwrap();
+        assert_eq!(key.client, "frontend");
+        assert_eq!(key.server, "backend");
+        assert_eq!(key.operation, "backend_op");
+        assert_eq!(series.requests, 1);
+        assert_eq!(series.errors, 1);
+        assert_eq!(series.duration_sum, 20.0);
+        // 20ms is in the bucket of 25ms
+        assert_eq!(series.buckets[..3], [0, 0, 1]);
+
+        // internal spans are ignored
+        state.add(
+            "org",
+            span("backend", SpanKind::Internal, "s3", "s2"),
+            0,
+            3,
+            10,
+        );
+        assert!(state.pending.is_empty());
+    }
+
+    #[test]
+    fn test_expire_and_max_pending() {
+        let mut state = State::default();
+        state.add("org", span("a", SpanKind::Client, "s1", ""), 10, 0, 1);
+        state.add("org", span("a", SpanKind::Client, "s2", ""), 20, 0, 1);
+        assert_eq!(state.pending.len(), 1);
+        assert_eq!(state.expire(5), 0);
+        assert_eq!(state.expire(15), 1);
+        assert!(state.pending.is_empty());
+    }
+
+    #[test]
+    fn test_build_request() {
+        let key = EdgeKey {
+            org_id: "org".to_string(),
+            stream_name: "default".to_string(),
+            client: "frontend".to_string(),
+            server: "cart".to_string(),
+            operation: "GET /cart".to_string(),
+        };
+        let mut series = EdgeSeries::new(10);
+        series.observe(3_000, false, 20);
+        series.observe(700_000, true, 20);
+        let req = build_request(&[(&key, &series)], 30);
+        let metrics = &req.resource_metrics[0].scope_metrics[0].metrics;
+        assert_eq!(metrics[0].name, REQUESTS_METRIC_NAME);
+        let Some(metric::Data::Sum(sum)) = &metrics[1].data else {
+            panic!("failed requests should be a sum");
+        };
+        assert_eq!(
+            sum.data_points[0].value,
+            Some(number_data_point::Value::AsInt(1))
+        );
+        assert_eq!(sum.data_points[0].start_time_unix_nano, 10);
+        let Some(metric::Data::Histogram(hist)) = &metrics[2].data else {
+            panic!("duration should be a histogram");
+        };
+        assert_eq!(hist.data_points[0].count, 2);
+        assert_eq!(hist.data_points[0].sum, Some(703.0));
+        assert_eq!(hist.data_points[0].bucket_counts[0], 1);
+        assert_eq!(hist.data_points[0].bucket_counts[7], 1); // <= 1000ms
+        assert_eq!(hist.data_points[0].attributes.len(), 4);
+    }
+
+    #[test]
+    fn test_build_promql() {
+        assert_eq!(
+            build_promql(REQUESTS_METRIC_NAME, None, 3600, false),
+            "sum by (client, server, operation) (increase(service_graph_requests_total[3600s]))"
+        );
+        assert_eq!(
+            build_promql("m_bucket", Some("it\"s"), 60, true),
+            "sum by (client, server, operation, le) (increase(m_bucket{trace_stream=\"it\\\"s\"}[60s]))"
+        );
+    }
+
+    #[test]
+    fn test_estimate_percentile() {
+        // 10 requests, all of them between 10ms and 25ms
+        let mut buckets = [10u64; LATENCY_BUCKETS.len()];
+        buckets[0] = 0;
+        buckets[1] = 0;
+        assert_eq!(estimate_percentile(&buckets, 10, 0.5), 17.5);
+        assert_eq!(estimate_percentile(&buckets, 10, 1.0), 25.0);
+        // slower than the last bucket
+        assert_eq!(
+            estimate_percentile(&[0; LATENCY_BUCKETS.len()], 10, 0.5),
+            10000.0
+        );
+        assert_eq!(estimate_percentile(&buckets, 0, 0.5), 0.0);
+    }
+
+    #[test]
+    fn test_build_graph() {
+        let mut buckets = LATENCY_BUCKETS
+            .iter()
+            .map(|bound| (*bound as f64, if *bound >= 10 { 4.0 } else { 0.0 }))
+            .collect::<Vec<_>>();
+        buckets.push((f64::INFINITY, 4.0));
+        let edges = BTreeMap::from([(
+            (
+                "frontend".to_string(),
+                "cart".to_string(),
+                "GET /cart".to_string(),
+            ),
+            EdgeValues {
+                requests: 4.0,
+                errors: 1.0,
+                duration_sum: 40.0,
+                buckets,
+            },
+        )]);
+        let graph = build_graph(edges);
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.nodes[0].name, "cart");
+        assert_eq!(graph.nodes[0].requests, 4);
+        assert_eq!(graph.nodes[0].errors, 1);
+        assert_eq!(graph.nodes[1].name, "frontend");
+        assert_eq!(graph.nodes[1].requests, 0);
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(graph.edges[0].duration_avg, 10.0);
+        assert_eq!(graph.edges[0].p50, 7.5);
+    }
+}
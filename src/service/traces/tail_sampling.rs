@@ -0,0 +1,847 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tail-based sampling of the traces streams.
+//!
+//! The spans are buffered per trace for the decision wait of the stream, then
+//! the policies are applied on the whole trace and only the kept traces are
+//! written. All the spans of a trace are routed to the same ingester by the
+//! consistent hash of the trace id, so the decision is made once per trace.
+//! Spans which arrive after the decision follow it while it is cached.
+//!
+//! The buffered spans are appended to a local WAL before the ingestion request
+//! is acknowledged, the WAL is segmented by time and a segment is deleted once
+//! all its traces are decided and the kept ones are written. The segments left
+//! by a crash are replayed on startup and the traces are decided again.
+
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use config::{
+    TIMESTAMP_COL_NAME,
+    cluster::LOCAL_NODE,
+    get_config,
+    meta::{
+        cluster::{Role, get_internal_grpc_token},
+        self_reporting::usage::UsageType,
+        stream::{StreamType, TailSamplingSettings},
+    },
+    metrics,
+    utils::{
+        hash::{Sum64, gxhash},
+        json,
+        time::now_micros,
+    },
+};
+use once_cell::sync::Lazy;
+use proto::cluster_rpc;
+use serde::{Deserialize, Serialize};
+use tokio::{sync::Mutex, time};
+use tonic::{Request, codec::CompressionEncoding, metadata::MetadataValue};
+
+use crate::{
+    common::infra::cluster,
+    service::{grpc::get_cached_channel, self_reporting::report_request_usage_stats},
+};
+
+/// The metadata key of the ingestion request which carries spans forwarded
+/// by another ingester.
+pub const FORWARD_METADATA_KEY: &str = "tail_sampling";
+
+/// The sub directory of the wal dir which keeps the buffered spans.
+const WAL_DIR: &str = "tail_sampling";
+
+/// A new WAL segment is started after this time.
+const WAL_SEGMENT_DURATION: Duration = Duration::from_secs(60);
+
+static SAMPLER: Lazy<Mutex<State>> = Lazy::new(|| {
+    tokio::task::spawn(async move { run_flush().await });
+    Mutex::new(State::default())
+});
+
+// serializes the flushes, a segment is only released after the kept traces
+// decided by the flush are written
+static FLUSH_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+type Span = (i64, json::Map<String, json::Value>);
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct TraceKey {
+    org_id: String,
+    stream_name: String,
+    trace_id: String,
+}
+
+struct PendingTrace {
+    first_seen: Instant,
+    spans: Vec<Span>,
+    // the WAL segments which hold the spans
+    segments: Vec<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WalEntry {
+    org_id: String,
+    stream_name: String,
+    spans: Vec<Span>,
+}
+
+/// The WAL of the buffered spans, the segments are named by their id.
+#[derive(Default)]
+struct SpanWal {
+    current: Option<(u64, wal::Writer, Instant)>,
+    segments: BTreeSet<u64>,
+}
+
+impl SpanWal {
+    /// Appends the entry and syncs it, returns the id of the segment.
+    fn append(&mut self, entry: &WalEntry) -> Result<u64, std::io::Error> {
+        let data = json::to_vec(entry)?;
+        if self
+            .current
+            .as_ref()
+            .is_some_and(|(_, _, created_at)| created_at.elapsed() >= WAL_SEGMENT_DURATION)
+        {
+            self.rotate();
+        }
+        if self.current.is_none() {
+            // the ids only grow, the replayed segments are older
+            let id = (now_micros() as u64).max(self.segments.last().map_or(0, |v| v + 1));
+            let (writer, _) = wal::Writer::new(
+                segment_path(id),
+                0,
+                get_config().limit.wal_write_buffer_size,
+                None,
+            )
+            .map_err(std::io::Error::other)?;
+            self.segments.insert(id);
+            self.current = Some((id, writer, Instant::now()));
+        }
+        let (id, writer, _) = self.current.as_mut().unwrap();
+        writer.write(&data).map_err(std::io::Error::other)?;
+        writer.sync().map_err(std::io::Error::other)?;
+        Ok(*id)
+    }
+
+    fn rotate(&mut self) {
+        if let Some((id, mut writer, _)) = self.current.take()
+            && let Err(e) = writer.close()
+        {
+            log::error!("[TRACES:TAIL_SAMPLING] close wal segment {id} error: {e}");
+        }
+    }
+
+    /// Deletes the segments which don't hold any pending span.
+    fn release(&mut self, live: &HashSet<u64>) {
+        let current = self.current.as_ref().map(|(id, ..)| *id);
+        let unused = self
+            .segments
+            .iter()
+            .filter(|id| Some(**id) != current && !live.contains(id))
+            .copied()
+            .collect::<Vec<_>>();
+        for id in unused {
+            let path = segment_path(id);
+            if let Err(e) = std::fs::remove_file(&path)
+                && e.kind() != std::io::ErrorKind::NotFound
+            {
+                log::error!(
+                    "[TRACES:TAIL_SAMPLING] delete wal segment {} error: {e}",
+                    path.display()
+                );
+                continue;
+            }
+            self.segments.remove(&id);
+        }
+    }
+}
+
+fn wal_dir() -> PathBuf {
+    PathBuf::from(&get_config().common.data_wal_dir).join(WAL_DIR)
+}
+
+fn segment_path(id: u64) -> PathBuf {
+    wal_dir().join(format!("{id}.wal"))
+}
+
+#[derive(Default)]
+struct State {
+    traces: HashMap<TraceKey, PendingTrace>,
+    wal: SpanWal,
+    settings: HashMap<(String, String), TailSamplingSettings>,
+    // the decisions are cached for the late spans, true means kept
+    decisions: HashMap<TraceKey, (bool, Instant)>,
+    rate_limiter: RateLimiter,
+    span_count: usize,
+}
+
+impl State {
+    fn insert_pending(&mut self, key: TraceKey, span: Span, segment: u64) {
+        let trace = self.traces.entry(key).or_insert_with(|| PendingTrace {
+            first_seen: Instant::now(),
+            spans: Vec::new(),
+            segments: Vec::new(),
+        });
+        trace.spans.push(span);
+        if !trace.segments.contains(&segment) {
+            trace.segments.push(segment);
+        }
+        self.span_count += 1;
+    }
+
+    fn live_segments(&self) -> HashSet<u64> {
+        self.traces
+            .values()
+            .flat_map(|trace| trace.segments.iter().copied())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Decision {
+    KeepError,
+    KeepLatency,
+    KeepSampled,
+    Drop,
+    DropRateLimited,
+}
+
+impl Decision {
+    fn is_kept(&self) -> bool {
+        matches!(
+            self,
+            Decision::KeepError | Decision::KeepLatency | Decision::KeepSampled
+        )
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Decision::KeepError => "keep_error",
+            Decision::KeepLatency => "keep_latency",
+            Decision::KeepSampled => "keep_sampled",
+            Decision::Drop => "drop",
+            Decision::DropRateLimited => "drop_rate_limited",
+        }
+    }
+}
+
+/// Counts the sampled traces per second for each root service.
+#[derive(Default)]
+struct RateLimiter {
+    counters: HashMap<(String, String, String), (u64, u64)>,
+}
+
+impl RateLimiter {
+    fn allow(&mut self, key: (String, String, String), limit: u64, now_secs: u64) -> bool {
+        if limit == 0 {
+            return true;
+        }
+        let (second, count) = self.counters.entry(key).or_insert((now_secs, 0));
+        if *second != now_secs {
+            *second = now_secs;
+            *count = 0;
+        }
+        if *count >= limit {
+            return false;
+        }
+        *count += 1;
+        true
+    }
+
+    fn clean(&mut self, now_secs: u64) {
+        self.counters.retain(|_, (second, _)| *second >= now_secs);
+    }
+}
+
+/// Buffers the spans of a stream which has tail sampling enabled, the spans
+/// of the traces owned by other ingesters are forwarded to them. It returns
+/// after the spans are in the WAL of the owner.
+pub async fn add(
+    org_id: &str,
+    stream_name: &str,
+    settings: &TailSamplingSettings,
+    json_data: Vec<Span>,
+) -> Result<(), std::io::Error> {
+    let mut local = Vec::with_capacity(json_data.len());
+    let mut remote: HashMap<String, Vec<json::Value>> = HashMap::new();
+    for (timestamp, record) in json_data {
+        let trace_id = get_trace_id(&record);
+        match cluster::get_node_from_consistent_hash(&trace_id, &Role::Ingester, None).await {
+            Some(node) if node != LOCAL_NODE.name => {
+                remote
+                    .entry(node)
+                    .or_default()
+                    .push(json::Value::Object(record));
+            }
+            _ => local.push((timestamp, record)),
+        }
+    }
+    for (node, records) in remote {
+        if let Err(e) = forward(&node, org_id, stream_name, &records).await {
+            // the decision of these traces may be split, which is better than losing them
+            log::error!(
+                "[TRACES:TAIL_SAMPLING] org: {org_id}, stream: {stream_name} forward spans error: {e}, buffering them locally"
+            );
+            local.extend(records.into_iter().filter_map(|v| match v {
+                json::Value::Object(record) => Some((get_timestamp(&record), record)),
+                _ => None,
+            }));
+        }
+    }
+    if !local.is_empty() {
+        buffer(org_id, stream_name, settings, local).await?;
+    }
+    Ok(())
+}
+
+/// Buffers the spans forwarded by another ingester, they are not forwarded
+/// again.
+pub async fn receive_forwarded(org_id: &str, stream_name: &str, data: &[u8]) -> Result<(), String> {
+    let records: Vec<json::Map<String, json::Value>> =
+        json::from_slice(data).map_err(|e| format!("invalid forwarded spans: {e}"))?;
+    let spans = records
+        .into_iter()
+        .map(|record| (get_timestamp(&record), record))
+        .collect::<Vec<_>>();
+    if spans.is_empty() {
+        return Ok(());
+    }
+    let settings = infra::schema::get_settings(org_id, stream_name, StreamType::Traces)
+        .await
+        .and_then(|s| s.tail_sampling)
+        .filter(|s| s.enabled);
+    match settings {
+        Some(settings) => buffer(org_id, stream_name, &settings, spans)
+            .await
+            .map_err(|e| e.to_string()),
+        // the sampling was disabled meanwhile
+        None => write_kept(org_id, stream_name, spans)
+            .await
+            .map_err(|e| e.to_string()),
+    }
+}
+
+/// Decides all the pending traces and writes the kept ones, it is called on
+/// shutdown.
+pub async fn close() -> Result<(), anyhow::Error> {
+    flush(true).await;
+    let mut state = SAMPLER.lock().await;
+    let live = state.live_segments();
+    state.wal.rotate();
+    state.wal.release(&live);
+    Ok(())
+}
+
+/// Buffers the spans left in the WAL by the previous run, the traces are
+/// decided again after the decision wait.
+pub async fn replay() -> Result<(), anyhow::Error> {
+    let Ok(dirs) = std::fs::read_dir(wal_dir()) else {
+        return Ok(());
+    };
+    let mut ids = dirs
+        .filter_map(|d| d.ok())
+        .filter_map(|d| {
+            d.path()
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| s.parse::<u64>().ok())
+        })
+        .collect::<Vec<_>>();
+    ids.sort();
+    let mut settings_cache: HashMap<(String, String), Option<TailSamplingSettings>> =
+        HashMap::new();
+    for id in ids {
+        let path = segment_path(id);
+        let mut reader = match wal::Reader::from_path(&path) {
+            Ok(v) => v,
+            Err(e) => {
+                log::error!(
+                    "[TRACES:TAIL_SAMPLING] open wal segment {} error: {e}",
+                    path.display()
+                );
+                continue;
+            }
+        };
+        let mut num = 0;
+        SAMPLER.lock().await.wal.segments.insert(id);
+        loop {
+            let entry = match reader.read_entry() {
+                Ok(Some(v)) => v,
+                Ok(None) => break,
+                Err(e) => {
+                    // a half written entry at the end of the segment was never acknowledged
+                    log::warn!(
+                        "[TRACES:TAIL_SAMPLING] read wal segment {} error: {e}",
+                        path.display()
+                    );
+                    break;
+                }
+            };
+            let Ok(entry) = json::from_slice::<WalEntry>(&entry) else {
+                continue;
+            };
+            num += entry.spans.len();
+            let stream_key = (entry.org_id.clone(), entry.stream_name.clone());
+            if !settings_cache.contains_key(&stream_key) {
+                let settings = infra::schema::get_settings(
+                    &entry.org_id,
+                    &entry.stream_name,
+                    StreamType::Traces,
+                )
+                .await
+                .and_then(|s| s.tail_sampling)
+                .filter(|s| s.enabled);
+                settings_cache.insert(stream_key.clone(), settings);
+            }
+            match settings_cache.get(&stream_key).cloned().flatten() {
+                Some(settings) => {
+                    let mut state = SAMPLER.lock().await;
+                    state.settings.insert(stream_key, settings);
+                    for (timestamp, record) in entry.spans {
+                        let key = TraceKey {
+                            org_id: entry.org_id.clone(),
+                            stream_name: entry.stream_name.clone(),
+                            trace_id: get_trace_id(&record),
+                        };
+                        state.insert_pending(key, (timestamp, record), id);
+                    }
+                }
+                // the sampling was disabled meanwhile
+                None => write_kept(&entry.org_id, &entry.stream_name, entry.spans).await?,
+            }
+        }
+        log::info!(
+            "[TRACES:TAIL_SAMPLING] replayed {num} spans from wal segment {}",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+async fn buffer(
+    org_id: &str,
+    stream_name: &str,
+    settings: &TailSamplingSettings,
+    spans: Vec<Span>,
+) -> Result<(), std::io::Error> {
+    let max_spans = get_config().common.traces_tail_sampling_max_spans;
+    let mut late_spans = Vec::new();
+    let over_limit = {
+        let mut state = SAMPLER.lock().await;
+        state.settings.insert(
+            (org_id.to_string(), stream_name.to_string()),
+            settings.clone(),
+        );
+        let mut pending = Vec::with_capacity(spans.len());
+        for (timestamp, record) in spans {
+            let key = TraceKey {
+                org_id: org_id.to_string(),
+                stream_name: stream_name.to_string(),
+                trace_id: get_trace_id(&record),
+            };
+            if let Some((kept, _)) = state.decisions.get(&key) {
+                if *kept {
+                    late_spans.push((timestamp, record));
+                }
+                continue;
+            }
+            pending.push((key, (timestamp, record)));
+        }
+        if !pending.is_empty() {
+            let entry = WalEntry {
+                org_id: org_id.to_string(),
+                stream_name: stream_name.to_string(),
+                spans: pending.iter().map(|(_, span)| span.clone()).collect(),
+            };
+            let segment = state.wal.append(&entry)?;
+            for (key, span) in pending {
+                state.insert_pending(key, span, segment);
+            }
+        }
+        state.span_count > max_spans
+    };
+    write_kept(org_id, stream_name, late_spans).await?;
+    if over_limit {
+        flush(false).await;
+    }
+    Ok(())
+}
+
+async fn run_flush() {
+    let mut interval = time::interval(time::Duration::from_secs(1));
+    interval.tick().await; // the first tick is immediate
+    loop {
+        interval.tick().await;
+        flush(false).await;
+    }
+}
+
+/// Decides the traces which waited long enough, or all of them when `all` is
+/// set. The oldest traces are decided early when the buffer is over the limit.
+async fn flush(all: bool) {
+    let _flush_lock = FLUSH_LOCK.lock().await;
+    let cfg = get_config();
+    let decision_ttl = Duration::from_secs(cfg.common.traces_tail_sampling_decision_ttl);
+    let now = Instant::now();
+    let now_secs = now_micros() as u64 / 1_000_000;
+    let mut kept: HashMap<(String, String), Vec<Span>> = HashMap::new();
+    {
+        let mut state = SAMPLER.lock().await;
+        let state = &mut *state;
+        state
+            .decisions
+            .retain(|_, (_, decided_at)| now.duration_since(*decided_at) < decision_ttl);
+        state.rate_limiter.clean(now_secs);
+
+        let mut ready = Vec::new();
+        let mut waiting = Vec::new();
+        for (key, trace) in state.traces.iter() {
+            let wait = state
+                .settings
+                .get(&(key.org_id.clone(), key.stream_name.clone()))
+                .map_or(0, |s| s.decision_wait);
+            if all || now.duration_since(trace.first_seen) >= Duration::from_secs(wait) {
+                ready.push(key.clone());
+            } else {
+                waiting.push((trace.first_seen, trace.spans.len(), key.clone()));
+            }
+        }
+        let mut span_count = state.span_count
+            - ready
+                .iter()
+                .map(|key| state.traces[key].spans.len())
+                .sum::<usize>();
+        if span_count > cfg.common.traces_tail_sampling_max_spans {
+            waiting.sort_by_key(|(first_seen, ..)| *first_seen);
+            for (_, len, key) in waiting {
+                if span_count <= cfg.common.traces_tail_sampling_max_spans {
+                    break;
+                }
+                span_count -= len;
+                ready.push(key);
+            }
+        }
+
+        for key in ready {
+            let Some(trace) = state.traces.remove(&key) else {
+                continue;
+            };
+            state.span_count -= trace.spans.len();
+            let Some(settings) = state
+                .settings
+                .get(&(key.org_id.clone(), key.stream_name.clone()))
+            else {
+                continue;
+            };
+            let mut decision = evaluate(settings, &key.trace_id, &trace.spans);
+            if decision == Decision::KeepSampled {
+                let service_name = get_root_service_name(&trace.spans);
+                let limit = settings.service_rate_limit(&service_name);
+                let limiter_key = (key.org_id.clone(), key.stream_name.clone(), service_name);
+                if !state.rate_limiter.allow(limiter_key, limit, now_secs) {
+                    decision = Decision::DropRateLimited;
+                }
+            }
+            metrics::INGEST_TAIL_SAMPLING_TRACES
+                .with_label_values(&[&key.org_id, &key.stream_name, decision.as_str()])
+                .inc();
+            if decision.is_kept() {
+                kept.entry((key.org_id.clone(), key.stream_name.clone()))
+                    .or_default()
+                    .extend(trace.spans);
+            }
+            state.decisions.insert(key, (decision.is_kept(), now));
+        }
+    }
+    for ((org_id, stream_name), spans) in kept {
+        if let Err(e) = write_kept(&org_id, &stream_name, spans).await {
+            log::error!(
+                "[TRACES:TAIL_SAMPLING] org: {org_id}, stream: {stream_name} write traces error: {e}"
+            );
+        }
+    }
+
+    // the decided traces are written, their segments can be deleted
+    let mut state = SAMPLER.lock().await;
+    let live = state.live_segments();
+    state.wal.release(&live);
+}
+
+async fn write_kept(
+    org_id: &str,
+    stream_name: &str,
+    spans: Vec<Span>,
+) -> Result<(), std::io::Error> {
+    if spans.is_empty() {
+        return Ok(());
+    }
+    let started_at = now_micros();
+    let start = Instant::now();
+    let mut req_stats = super::write_traces(org_id, stream_name, spans).await?;
+    req_stats.response_time = start.elapsed().as_secs_f64();
+    report_request_usage_stats(
+        req_stats,
+        org_id,
+        stream_name,
+        StreamType::Traces,
+        UsageType::Traces,
+        0,
+        started_at,
+    )
+    .await;
+    Ok(())
+}
+
+async fn forward(
+    node_name: &str,
+    org_id: &str,
+    stream_name: &str,
+    records: &[json::Value],
+) -> Result<(), String> {
+    let cfg = get_config();
+    let Some(node) = cluster::get_cached_node_by_name(node_name).await else {
+        return Err(format!("node {node_name} not found"));
+    };
+    let token: MetadataValue<_> = get_internal_grpc_token()
+        .parse()
+        .map_err(|_| "invalid token".to_string())?;
+    let channel = get_cached_channel(&node.grpc_addr)
+        .await
+        .map_err(|e| format!("node {node_name} connect error: {e}"))?;
+    let mut client = cluster_rpc::ingest_client::IngestClient::with_interceptor(
+        channel,
+        move |mut req: Request<()>| {
+            req.metadata_mut().insert("authorization", token.clone());
+            Ok(req)
+        },
+    );
+    client = client
+        .send_compressed(CompressionEncoding::Gzip)
+        .accept_compressed(CompressionEncoding::Gzip)
+        .max_decoding_message_size(cfg.grpc.max_message_size * 1024 * 1024)
+        .max_encoding_message_size(cfg.grpc.max_message_size * 1024 * 1024);
+    let req = cluster_rpc::IngestionRequest {
+        org_id: org_id.to_string(),
+        stream_type: StreamType::Traces.to_string(),
+        stream_name: stream_name.to_string(),
+        data: Some(cluster_rpc::IngestionData {
+            data: json::to_vec(records).map_err(|e| e.to_string())?,
+        }),
+        ingestion_type: Some(cluster_rpc::IngestionType::Json.into()),
+        metadata: Some(cluster_rpc::IngestRequestMetadata {
+            data: HashMap::from([(FORWARD_METADATA_KEY.to_string(), "forward".to_string())]),
+        }),
+    };
+    let res = client
+        .ingest(req)
+        .await
+        .map_err(|e| format!("node {node_name} response error: {e}"))?
+        .into_inner();
+    if res.status_code != 200 {
+        return Err(format!("node {node_name} response error: {}", res.message));
+    }
+    Ok(())
+}
+
+/// Applies the policies in order: errors, latency and then the percentage.
+/// The rate limit of the sampled traces is applied by the caller.
+fn evaluate(settings: &TailSamplingSettings, trace_id: &str, spans: &[Span]) -> Decision {
+    if settings.keep_errors
+        && spans.iter().any(|(_, record)| {
+            record
+                .get("span_status")
+                .is_some_and(|v| json::get_string_value(v) == "ERROR")
+        })
+    {
+        return Decision::KeepError;
+    }
+    if settings.latency_threshold_ms > 0 {
+        let get_int = |record: &json::Map<String, json::Value>, key: &str| {
+            record.get(key).map(json::get_int_value).unwrap_or_default()
+        };
+        let start_time = spans.iter().map(|(_, r)| get_int(r, "start_time")).min();
+        let end_time = spans.iter().map(|(_, r)| get_int(r, "end_time")).max();
+        if let (Some(start_time), Some(end_time)) = (start_time, end_time)
+            && (end_time - start_time) / 1_000_000 > settings.latency_threshold_ms as i64
+        {
+            return Decision::KeepLatency;
+        }
+    }
+    // the hash of the trace id makes the sampling stable across the ingesters
+    let threshold = (settings.sample_percent * 100.0) as u64;
+    if gxhash::new().sum64(trace_id) % 10_000 < threshold {
+        Decision::KeepSampled
+    } else {
+        Decision::Drop
+    }
+}
+
+fn get_trace_id(record: &json::Map<String, json::Value>) -> String {
+    record
+        .get("trace_id")
+        .map(json::get_string_value)
+        .unwrap_or_default()
+}
+
+fn get_timestamp(record: &json::Map<String, json::Value>) -> i64 {
+    record
+        .get(TIMESTAMP_COL_NAME)
+        .map(json::get_int_value)
+        .unwrap_or_else(now_micros)
+}
+
+fn get_root_service_name(spans: &[Span]) -> String {
+    spans
+        .iter()
+        .find(|(_, record)| {
+            record
+                .get("reference_parent_span_id")
+                .is_none_or(|v| json::get_string_value(v).is_empty())
+        })
+        .or_else(|| spans.first())
+        .and_then(|(_, record)| record.get("service_name"))
+        .map(json::get_string_value)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(id: &str, parent: &str, service: &str, status: &str, start: i64, end: i64) -> Span {
+        let record = json::json!({
+            "trace_id": "t1",
+            "span_id": id,
+            "reference_parent_span_id": parent,
+            "service_name": service,
+            "span_status": status,
+            "start_time": start,
+            "end_time": end,
+        });
+        (start / 1000, record.as_object().unwrap().clone())
+    }
+
+    fn trace() -> Vec<Span> {
+        vec![
+            span("b", "a", "cart", "OK", 2_000_000, 3_000_000),
+            span("a", "", "frontend", "OK", 1_000_000, 5_000_000),
+        ]
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let mut settings = TailSamplingSettings {
+            enabled: true,
+            ..Default::default()
+        };
+        assert_eq!(evaluate(&settings, "t1", &trace()), Decision::Drop);
+
+        let mut spans = trace();
+        spans.push(span("c", "b", "cart", "ERROR", 2_000_000, 2_500_000));
+        assert_eq!(evaluate(&settings, "t1", &spans), Decision::KeepError);
+        settings.keep_errors = false;
+        assert_eq!(evaluate(&settings, "t1", &spans), Decision::Drop);
+
+        // the trace takes 4ms
+        settings.latency_threshold_ms = 3;
+        assert_eq!(evaluate(&settings, "t1", &trace()), Decision::KeepLatency);
+        settings.latency_threshold_ms = 4;
+        assert_eq!(evaluate(&settings, "t1", &trace()), Decision::Drop);
+
+        settings.sample_percent = 100.0;
+        assert_eq!(evaluate(&settings, "t1", &trace()), Decision::KeepSampled);
+    }
+
+    #[test]
+    fn test_evaluate_sample_percent() {
+        let settings = TailSamplingSettings {
+            enabled: true,
+            sample_percent: 10.0,
+            ..Default::default()
+        };
+        let kept = (0..10_000)
+            .filter(|i| {
+                evaluate(&settings, &format!("trace-{i}"), &trace()) == Decision::KeepSampled
+            })
+            .count();
+        assert!((800..1200).contains(&kept), "kept {kept}");
+        // the same trace always gets the same decision
+        let decision = evaluate(&settings, "trace-1", &trace());
+        for _ in 0..10 {
+            assert_eq!(evaluate(&settings, "trace-1", &trace()), decision);
+        }
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let key = || ("org".to_string(), "default".to_string(), "cart".to_string());
+        let mut limiter = RateLimiter::default();
+        assert!(limiter.allow(key(), 2, 100));
+        assert!(limiter.allow(key(), 2, 100));
+        assert!(!limiter.allow(key(), 2, 100));
+        assert!(limiter.allow(key(), 0, 100));
+        assert!(limiter.allow(key(), 2, 101));
+        limiter.clean(102);
+        assert!(limiter.counters.is_empty());
+    }
+
+    #[test]
+    fn test_span_wal() {
+        let mut span_wal = SpanWal::default();
+        let entry = WalEntry {
+            org_id: "org".to_string(),
+            stream_name: "default".to_string(),
+            spans: trace(),
+        };
+        let id = span_wal.append(&entry).unwrap();
+        assert_eq!(span_wal.append(&entry).unwrap(), id);
+        let mut reader = wal::Reader::from_path(segment_path(id)).unwrap();
+        let data = reader.read_entry().unwrap().unwrap();
+        let read: WalEntry = json::from_slice(&data).unwrap();
+        assert_eq!(read.spans, trace());
+
+        let mut state = State::default();
+        state.insert_pending(
+            TraceKey {
+                org_id: "org".to_string(),
+                stream_name: "default".to_string(),
+                trace_id: "t1".to_string(),
+            },
+            trace().remove(0),
+            id,
+        );
+        assert_eq!(state.live_segments(), HashSet::from([id]));
+
+        // the current segment is kept until it is rotated
+        span_wal.release(&HashSet::new());
+        assert!(segment_path(id).exists());
+        span_wal.rotate();
+        span_wal.release(&state.live_segments());
+        assert!(segment_path(id).exists());
+        span_wal.release(&HashSet::new());
+        assert!(!segment_path(id).exists());
+        assert!(span_wal.segments.is_empty());
+    }
+
+    #[test]
+    fn test_get_root_service_name() {
+        assert_eq!(get_root_service_name(&trace()), "frontend");
+        assert_eq!(get_root_service_name(&trace()[..1]), "cart");
+        assert_eq!(get_root_service_name(&[]), "");
+    }
+}
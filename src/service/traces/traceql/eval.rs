@@ -0,0 +1,287 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeSet, HashMap};
+
+use config::utils::json;
+
+use super::parser::{CompareOp, Condition, Expr, Filter, StructuralOp, Value};
+
+/// A span of a trace, the record is the flattened span of the traces stream.
+#[derive(Debug, Clone)]
+pub struct TraceSpan {
+    pub span_id: String,
+    pub parent_span_id: String,
+    pub record: json::Map<String, json::Value>,
+}
+
+impl TraceSpan {
+    pub fn new(record: json::Map<String, json::Value>) -> Self {
+        let get_str = |key: &str| {
+            record
+                .get(key)
+                .map(json::get_string_value)
+                .unwrap_or_default()
+        };
+        Self {
+            span_id: get_str("span_id"),
+            parent_span_id: get_str("reference_parent_span_id"),
+            record,
+        }
+    }
+}
+
+/// Returns the indexes of the matched spans of the trace, the trace matches if
+/// it is not empty.
+pub fn evaluate(expr: &Expr, spans: &[TraceSpan]) -> BTreeSet<usize> {
+    let parents = parent_indexes(spans);
+    eval_expr(expr, spans, &parents)
+}
+
+fn parent_indexes(spans: &[TraceSpan]) -> Vec<Option<usize>> {
+    let ids = spans
+        .iter()
+        .enumerate()
+        .map(|(i, span)| (span.span_id.as_str(), i))
+        .collect::<HashMap<_, _>>();
+    spans
+        .iter()
+        .map(|span| {
+            if span.parent_span_id.is_empty() {
+                None
+            } else {
+                ids.get(span.parent_span_id.as_str()).copied()
+            }
+        })
+        .collect()
+}
+
+fn eval_expr(expr: &Expr, spans: &[TraceSpan], parents: &[Option<usize>]) -> BTreeSet<usize> {
+    match expr {
+        Expr::Spanset(filter) => spans
+            .iter()
+            .enumerate()
+            .filter(|(_, span)| match_filter(filter, &span.record))
+            .map(|(i, _)| i)
+            .collect(),
+        Expr::And(l, r) => {
+            let left = eval_expr(l, spans, parents);
+            if left.is_empty() {
+                return left;
+            }
+            let right = eval_expr(r, spans, parents);
+            if right.is_empty() {
+                return right;
+            }
+            left.union(&right).copied().collect()
+        }
+        Expr::Or(l, r) => {
+            let mut left = eval_expr(l, spans, parents);
+            left.extend(eval_expr(r, spans, parents));
+            left
+        }
+        Expr::Structural(l, op, r) => {
+            let left = eval_expr(l, spans, parents);
+            if left.is_empty() {
+                return left;
+            }
+            eval_expr(r, spans, parents)
+                .into_iter()
+                .filter(|i| match op {
+                    StructuralOp::Child => parents[*i].is_some_and(|p| left.contains(&p)),
+                    StructuralOp::Descendant => {
+                        // the depth limit protects against cycles of broken traces
+                        let mut parent = parents[*i];
+                        let mut depth = 0;
+                        while let Some(p) = parent
+                            && depth < spans.len()
+                        {
+                            if left.contains(&p) {
+                                return true;
+                            }
+                            parent = parents[p];
+                            depth += 1;
+                        }
+                        false
+                    }
+                    StructuralOp::Sibling => left.iter().any(|j| {
+                        *j != *i
+                            && !spans[*i].parent_span_id.is_empty()
+                            && spans[*j].parent_span_id == spans[*i].parent_span_id
+                    }),
+                })
+                .collect()
+        }
+    }
+}
+
+fn match_filter(filter: &Filter, record: &json::Map<String, json::Value>) -> bool {
+    match filter {
+        Filter::True => true,
+        Filter::And(l, r) => match_filter(l, record) && match_filter(r, record),
+        Filter::Or(l, r) => match_filter(l, record) || match_filter(r, record),
+        Filter::Cond(cond) => match_condition(cond, record),
+    }
+}
+
+/// The spans which don't have the field never match.
+fn match_condition(cond: &Condition, record: &json::Map<String, json::Value>) -> bool {
+    let Some(val) = record.get(cond.field.column()) else {
+        return false;
+    };
+    if val.is_null() {
+        return false;
+    }
+    match &cond.value {
+        Value::Regex(re) => {
+            let matched = re.is_match(&json::get_string_value(val));
+            match cond.op {
+                CompareOp::Regex => matched,
+                CompareOp::NotRegex => !matched,
+                _ => false,
+            }
+        }
+        Value::Number(expected) => {
+            let actual = match val {
+                json::Value::Number(v) => v.as_f64(),
+                json::Value::String(v) => v.parse::<f64>().ok(),
+                _ => None,
+            };
+            match actual {
+                Some(actual) => compare(cond.op, actual.partial_cmp(expected)),
+                None => false,
+            }
+        }
+        Value::String(expected) => {
+            let actual = json::get_string_value(val);
+            compare(cond.op, Some(actual.as_str().cmp(expected.as_str())))
+        }
+        Value::Bool(expected) => {
+            let actual = match val {
+                json::Value::Bool(v) => Some(*v),
+                json::Value::String(v) => v.parse::<bool>().ok(),
+                _ => None,
+            };
+            match (actual, cond.op) {
+                (Some(actual), CompareOp::Eq) => actual == *expected,
+                (Some(actual), CompareOp::NotEq) => actual != *expected,
+                _ => false,
+            }
+        }
+    }
+}
+
+fn compare(op: CompareOp, ord: Option<std::cmp::Ordering>) -> bool {
+    use std::cmp::Ordering::*;
+    let Some(ord) = ord else {
+        return false;
+    };
+    match op {
+        CompareOp::Eq => ord == Equal,
+        CompareOp::NotEq => ord != Equal,
+        CompareOp::Gt => ord == Greater,
+        CompareOp::Gte => ord != Less,
+        CompareOp::Lt => ord == Less,
+        CompareOp::Lte => ord != Greater,
+        CompareOp::Regex | CompareOp::NotRegex => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::traces::traceql::parser::parse;
+
+    fn span(id: &str, parent: &str, service: &str, status: &str, duration: u64) -> TraceSpan {
+        TraceSpan::new(
+            json::json!({
+                "span_id": id,
+                "reference_parent_span_id": parent,
+                "service_name": service,
+                "operation_name": format!("{service} op"),
+                "span_status": status,
+                "span_kind": "2",
+                "duration": duration,
+                "http_status_code": "500",
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        )
+    }
+
+    // a -> b -> c, a -> d
+    fn trace() -> Vec<TraceSpan> {
+        vec![
+            span("a", "", "A", "ERROR", 5_000_000),
+            span("b", "a", "B", "OK", 1_000_000),
+            span("c", "b", "B", "OK", 3_000_000),
+            span("d", "a", "C", "UNSET", 100),
+        ]
+    }
+
+    fn eval(query: &str) -> Vec<usize> {
+        evaluate(&parse(query).unwrap(), &trace())
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_evaluate_spanset() {
+        assert_eq!(eval("{}"), vec![0, 1, 2, 3]);
+        assert_eq!(eval(r#"{ service.name = "B" }"#), vec![1, 2]);
+        assert_eq!(eval("{ status != ok }"), vec![0, 3]);
+        assert_eq!(eval("{ duration >= 3s }"), vec![0, 2]);
+        assert_eq!(eval(r#"{ name =~ "^(A|C) " }"#), vec![0, 3]);
+        assert_eq!(eval("{ .http.status_code >= 500 }"), vec![0, 1, 2, 3]);
+        assert!(eval(r#"{ .missing != "x" }"#).is_empty());
+    }
+
+    #[test]
+    fn test_evaluate_structural() {
+        let query = r#"{ service.name = "A" && status = error } >> { service.name = "B" && duration > 2s }"#;
+        assert_eq!(eval(query), vec![2]);
+        // c is not a child of a
+        let query = r#"{ service.name = "A" } > { service.name = "B" && duration > 2s }"#;
+        assert!(eval(query).is_empty());
+        assert_eq!(eval(r#"{ service.name = "A" } > {}"#), vec![1, 3]);
+        assert_eq!(eval(r#"{ service.name = "C" } ~ {}"#), vec![1]);
+        assert!(eval(r#"{ service.name = "C" } >> {}"#).is_empty());
+    }
+
+    #[test]
+    fn test_evaluate_logical() {
+        assert_eq!(
+            eval(r#"{ service.name = "A" } && { service.name = "C" }"#),
+            vec![0, 3]
+        );
+        assert!(eval(r#"{ service.name = "A" } && { service.name = "D" }"#).is_empty());
+        assert_eq!(
+            eval(r#"{ service.name = "D" } || { service.name = "C" }"#),
+            vec![3]
+        );
+    }
+
+    #[test]
+    fn test_evaluate_cycle() {
+        let spans = vec![
+            span("x", "", "X", "OK", 1),
+            span("a", "b", "A", "OK", 1),
+            span("b", "a", "B", "OK", 1),
+        ];
+        let expr = parse(r#"{ service.name = "X" } >> {}"#).unwrap();
+        assert!(evaluate(&expr, &spans).is_empty());
+    }
+}
@@ -0,0 +1,369 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Structural trace search, a TraceQL-like query is evaluated over the spans
+//! of each trace, e.g. the traces which have an error span of service A
+//! calling service B with a duration of more than 2 seconds:
+//!
+//! ```text
+//! { resource.service.name = "A" && status = error } >> { resource.service.name = "B" && duration > 2s }
+//! ```
+//!
+//! The candidate traces are read from the `trace_list_index` metadata stream,
+//! newest first, and narrowed by the services which the query requires.
+
+use std::collections::HashMap;
+
+use config::{
+    TIMESTAMP_COL_NAME,
+    meta::{
+        search::{Query, Request},
+        stream::StreamType,
+    },
+    utils::json,
+};
+use infra::errors::{Error, ErrorCodes, Result};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use self::eval::TraceSpan;
+pub use self::parser::{Expr, parse};
+use crate::service::search::{
+    self as SearchService,
+    masking::{self, Masker},
+};
+
+pub mod eval;
+pub mod parser;
+
+const TRACE_LIST_INDEX_STREAM: &str = "trace_list_index";
+
+/// Number of candidate traces which are evaluated together.
+const CANDIDATE_BATCH_SIZE: i64 = 100;
+
+/// Max number of candidate traces which are evaluated for a query.
+const MAX_CANDIDATES: i64 = 10_000;
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct TraceQLResponse {
+    pub took: usize,
+    pub traces: Vec<TraceQLTrace>,
+    /// Number of the candidate traces which were evaluated.
+    pub scanned_traces: usize,
+    /// The candidate limit was reached before finding enough traces.
+    pub is_partial: bool,
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct TraceQLTrace {
+    pub trace_id: String,
+    pub root_service_name: String,
+    pub root_span_name: String,
+    /// nanoseconds
+    pub start_time: i64,
+    /// nanoseconds
+    pub end_time: i64,
+    /// microseconds
+    pub duration: i64,
+    pub span_count: usize,
+    /// The matched spans.
+    #[schema(value_type = Vec<Object>)]
+    pub spans: Vec<json::Value>,
+}
+
+/// Returns up to `size` traces of the stream which match the query, newest
+/// first.
+pub async fn search(
+    trace_id: &str,
+    org_id: &str,
+    stream_name: &str,
+    user_id: Option<String>,
+    expr: &Expr,
+    (start_time, end_time): (i64, i64),
+    size: usize,
+) -> Result<TraceQLResponse> {
+    let start = std::time::Instant::now();
+    // the spans are matched on the raw values, a masked field can't be compared
+    let masker = masking::get_masker(
+        org_id,
+        StreamType::Traces,
+        &[stream_name.to_string()],
+        user_id.as_deref(),
+    )
+    .await;
+    if let Some(masker) = masker.as_ref()
+        && let Some(column) = expr.columns().into_iter().find(|c| masker.is_masked(c))
+    {
+        return Err(Error::ErrorCode(ErrorCodes::SearchSQLNotValid(format!(
+            "masked field {column} can't be used in a query"
+        ))));
+    }
+    let services = expr.required_services();
+    let mut resp = TraceQLResponse::default();
+    let mut from = 0;
+    while resp.traces.len() < size {
+        if from >= MAX_CANDIDATES {
+            resp.is_partial = true;
+            break;
+        }
+        let req = new_request(
+            build_candidates_sql(stream_name, &services),
+            from,
+            CANDIDATE_BATCH_SIZE,
+            start_time,
+            end_time,
+        );
+        let candidates = SearchService::search(
+            trace_id,
+            org_id,
+            StreamType::Metadata,
+            user_id.clone(),
+            &req,
+        )
+        .await?
+        .hits
+        .iter()
+        .filter_map(|hit| hit.get("trace_id").map(json::get_string_value))
+        .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            break;
+        }
+        let last_batch = (candidates.len() as i64) < CANDIDATE_BATCH_SIZE;
+        from += candidates.len() as i64;
+        resp.scanned_traces += candidates.len();
+
+        let req = new_request(
+            build_spans_sql(stream_name, &candidates),
+            0,
+            -1,
+            start_time,
+            end_time,
+        );
+        let hits =
+            SearchService::search(trace_id, org_id, StreamType::Traces, user_id.clone(), &req)
+                .await?
+                .hits;
+        let mut spans_by_trace: HashMap<String, Vec<TraceSpan>> = HashMap::new();
+        for hit in hits {
+            let json::Value::Object(record) = hit else {
+                continue;
+            };
+            let id = record
+                .get("trace_id")
+                .map(json::get_string_value)
+                .unwrap_or_default();
+            spans_by_trace
+                .entry(id)
+                .or_default()
+                .push(TraceSpan::new(record));
+        }
+        // keep the order of the candidates, the newest first
+        for id in candidates {
+            let Some(spans) = spans_by_trace.remove(&id) else {
+                continue;
+            };
+            if let Some(mut trace) = match_trace(expr, id, spans) {
+                if let Some(masker) = masker.as_ref() {
+                    mask_trace(masker, &mut trace);
+                }
+                resp.traces.push(trace);
+                if resp.traces.len() >= size {
+                    break;
+                }
+            }
+        }
+        if last_batch {
+            break;
+        }
+    }
+    resp.took = start.elapsed().as_millis() as usize;
+    Ok(resp)
+}
+
+fn new_request(sql: String, from: i64, size: i64, start_time: i64, end_time: i64) -> Request {
+    Request {
+        query: Query {
+            sql,
+            from,
+            size,
+            start_time,
+            end_time,
+            quick_mode: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\'', "''")
+}
+
+fn build_candidates_sql(stream_name: &str, services: &[String]) -> String {
+    let mut sql = format!(
+        "SELECT trace_id, MAX({TIMESTAMP_COL_NAME}) AS zo_sql_timestamp FROM \"{TRACE_LIST_INDEX_STREAM}\" WHERE stream_name = '{}'",
+        escape(stream_name)
+    );
+    if !services.is_empty() {
+        let services = services
+            .iter()
+            .map(|s| format!("'{}'", escape(s)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        sql.push_str(&format!(" AND service_name IN ({services})"));
+    }
+    sql.push_str(" GROUP BY trace_id");
+    if services.len() > 1 {
+        sql.push_str(&format!(
+            " HAVING COUNT(DISTINCT service_name) = {}",
+            services.len()
+        ));
+    }
+    sql.push_str(" ORDER BY zo_sql_timestamp DESC");
+    sql
+}
+
+fn build_spans_sql(stream_name: &str, trace_ids: &[String]) -> String {
+    let trace_ids = trace_ids
+        .iter()
+        .map(|id| escape(id))
+        .collect::<Vec<_>>()
+        .join("','");
+    format!(
+        "SELECT * FROM \"{stream_name}\" WHERE trace_id IN ('{trace_ids}') ORDER BY start_time ASC"
+    )
+}
+
+fn match_trace(expr: &Expr, trace_id: String, spans: Vec<TraceSpan>) -> Option<TraceQLTrace> {
+    let matched = eval::evaluate(expr, &spans);
+    if matched.is_empty() {
+        return None;
+    }
+    let get_int = |span: &TraceSpan, key: &str| {
+        span.record
+            .get(key)
+            .map(json::get_int_value)
+            .unwrap_or_default()
+    };
+    let get_str = |span: &TraceSpan, key: &str| {
+        span.record
+            .get(key)
+            .map(json::get_string_value)
+            .unwrap_or_default()
+    };
+    let start_time = spans
+        .iter()
+        .map(|span| get_int(span, "start_time"))
+        .min()
+        .unwrap_or_default();
+    let end_time = spans
+        .iter()
+        .map(|span| get_int(span, "end_time"))
+        .max()
+        .unwrap_or_default();
+    let root = spans
+        .iter()
+        .find(|span| span.parent_span_id.is_empty())
+        .or_else(|| spans.first());
+    Some(TraceQLTrace {
+        trace_id,
+        root_service_name: root.map(|s| get_str(s, "service_name")).unwrap_or_default(),
+        root_span_name: root
+            .map(|s| get_str(s, "operation_name"))
+            .unwrap_or_default(),
+        start_time,
+        end_time,
+        duration: (end_time - start_time) / 1000,
+        span_count: spans.len(),
+        spans: matched
+            .into_iter()
+            .map(|i| json::Value::Object(spans[i].record.clone()))
+            .collect(),
+    })
+}
+
+/// Masks the matched spans and the root names of the trace.
+fn mask_trace(masker: &Masker, trace: &mut TraceQLTrace) {
+    masker.mask_hits(&mut trace.spans);
+    let mut root = json::Map::new();
+    root.insert(
+        "service_name".to_string(),
+        json::Value::String(std::mem::take(&mut trace.root_service_name)),
+    );
+    root.insert(
+        "operation_name".to_string(),
+        json::Value::String(std::mem::take(&mut trace.root_span_name)),
+    );
+    masker.mask_record(&mut root);
+    let get_str = |key: &str| {
+        root.get(key)
+            .map(json::get_string_value)
+            .unwrap_or_default()
+    };
+    trace.root_service_name = get_str("service_name");
+    trace.root_span_name = get_str("operation_name");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_candidates_sql() {
+        assert_eq!(
+            build_candidates_sql("default", &[]),
+            "SELECT trace_id, MAX(_timestamp) AS zo_sql_timestamp FROM \"trace_list_index\" WHERE stream_name = 'default' GROUP BY trace_id ORDER BY zo_sql_timestamp DESC"
+        );
+        assert_eq!(
+            build_candidates_sql("default", &["A".to_string(), "B'".to_string()]),
+            "SELECT trace_id, MAX(_timestamp) AS zo_sql_timestamp FROM \"trace_list_index\" WHERE stream_name = 'default' AND service_name IN ('A', 'B''') GROUP BY trace_id HAVING COUNT(DISTINCT service_name) = 2 ORDER BY zo_sql_timestamp DESC"
+        );
+    }
+
+    #[test]
+    fn test_match_trace() {
+        let span = |id: &str, parent: &str, service: &str, start: i64, end: i64| {
+            TraceSpan::new(
+                json::json!({
+                    "span_id": id,
+                    "reference_parent_span_id": parent,
+                    "service_name": service,
+                    "operation_name": "op",
+                    "start_time": start,
+                    "end_time": end,
+                })
+                .as_object()
+                .unwrap()
+                .clone(),
+            )
+        };
+        let spans = vec![
+            span("b", "a", "B", 2_000_000, 3_000_000),
+            span("a", "", "A", 1_000_000, 5_000_000),
+        ];
+        let expr = parse(r#"{ service.name = "A" } > {}"#).unwrap();
+        let trace = match_trace(&expr, "t1".to_string(), spans.clone()).unwrap();
+        assert_eq!(trace.root_service_name, "A");
+        assert_eq!(trace.start_time, 1_000_000);
+        assert_eq!(trace.end_time, 5_000_000);
+        assert_eq!(trace.duration, 4000);
+        assert_eq!(trace.span_count, 2);
+        assert_eq!(trace.spans.len(), 1);
+        assert_eq!(trace.spans[0]["span_id"], "b");
+
+        let expr = parse(r#"{ service.name = "C" }"#).unwrap();
+        assert!(match_trace(&expr, "t1".to_string(), spans).is_none());
+    }
+}
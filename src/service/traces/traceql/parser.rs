@@ -0,0 +1,681 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use config::utils::flatten::format_key;
+use regex::Regex;
+
+/// A query over the spans of a trace.
+///
+/// ```text
+/// expr      := or_expr
+/// or_expr   := and_expr ("||" and_expr)*
+/// and_expr  := struct    ("&&" struct)*
+/// struct    := primary   ((">>" | ">" | "~") primary)*
+/// primary   := "{" filter? "}" | "(" expr ")"
+/// filter    := f_and ("||" f_and)*
+/// f_and     := f_primary ("&&" f_primary)*
+/// f_primary := "(" filter ")" | "true" | field op value
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Spanset(Filter),
+    /// Both sides match in the trace, returns the spans of both sides.
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    /// Returns the spans of the right side which have the relation to a span
+    /// of the left side.
+    Structural(Box<Expr>, StructuralOp, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StructuralOp {
+    /// `>`, the right span is a child of the left span
+    Child,
+    /// `>>`, the right span is a descendant of the left span
+    Descendant,
+    /// `~`, the spans have the same parent
+    Sibling,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    True,
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Cond(Condition),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub field: Field,
+    pub op: CompareOp,
+    pub value: Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Field {
+    ServiceName,
+    Name,
+    Status,
+    Kind,
+    /// microseconds
+    Duration,
+    /// The flattened column name of a span attribute.
+    Attribute(String),
+}
+
+impl Field {
+    /// The column of the field in the traces stream.
+    pub fn column(&self) -> &str {
+        match self {
+            Field::ServiceName => "service_name",
+            Field::Name => "operation_name",
+            Field::Status => "span_status",
+            Field::Kind => "span_kind",
+            Field::Duration => "duration",
+            Field::Attribute(name) => name.as_str(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompareOp {
+    Eq,
+    NotEq,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Regex,
+    NotRegex,
+}
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    String(String),
+    Number(f64),
+    Bool(bool),
+    Regex(Regex),
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Regex(a), Value::Regex(b)) => a.as_str() == b.as_str(),
+            _ => false,
+        }
+    }
+}
+
+impl Expr {
+    /// Services which must have spans in every matched trace, they are used to
+    /// narrow the candidate traces.
+    pub fn required_services(&self) -> Vec<String> {
+        let mut services = match self {
+            Expr::Spanset(filter) => filter.required_services(),
+            Expr::And(l, r) | Expr::Structural(l, _, r) => {
+                let mut services = l.required_services();
+                services.extend(r.required_services());
+                services
+            }
+            Expr::Or(l, r) => intersect(l.required_services(), r.required_services()),
+        };
+        services.sort();
+        services.dedup();
+        services
+    }
+
+    /// The columns which the conditions of the query compare.
+    pub fn columns(&self) -> Vec<&str> {
+        let mut columns = Vec::new();
+        self.collect_columns(&mut columns);
+        columns
+    }
+
+    fn collect_columns<'a>(&'a self, columns: &mut Vec<&'a str>) {
+        match self {
+            Expr::Spanset(filter) => filter.columns(columns),
+            Expr::And(l, r) | Expr::Or(l, r) | Expr::Structural(l, _, r) => {
+                l.collect_columns(columns);
+                r.collect_columns(columns);
+            }
+        }
+    }
+}
+
+impl Filter {
+    fn columns<'a>(&'a self, columns: &mut Vec<&'a str>) {
+        match self {
+            Filter::True => {}
+            Filter::And(l, r) | Filter::Or(l, r) => {
+                l.columns(columns);
+                r.columns(columns);
+            }
+            Filter::Cond(cond) => columns.push(cond.field.column()),
+        }
+    }
+
+    fn required_services(&self) -> Vec<String> {
+        match self {
+            Filter::True => vec![],
+            Filter::And(l, r) => {
+                let mut services = l.required_services();
+                services.extend(r.required_services());
+                services
+            }
+            Filter::Or(l, r) => intersect(l.required_services(), r.required_services()),
+            Filter::Cond(Condition {
+                field: Field::ServiceName,
+                op: CompareOp::Eq,
+                value: Value::String(name),
+            }) => vec![name.to_string()],
+            Filter::Cond(_) => vec![],
+        }
+    }
+}
+
+fn intersect(l: Vec<String>, r: Vec<String>) -> Vec<String> {
+    l.into_iter().filter(|v| r.contains(v)).collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
+    And,
+    Or,
+    Descendant,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Tilde,
+    Eq,
+    NotEq,
+    Regex,
+    NotRegex,
+    Str(String),
+    Num(f64),
+    /// microseconds
+    Duration(f64),
+    Ident(String),
+}
+
+pub fn parse(query: &str) -> Result<Expr, String> {
+    let tokens = tokenize(query)?;
+    if tokens.is_empty() {
+        return Err("query is empty".to_string());
+    }
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.parse_expr()?;
+    if let Some(token) = parser.peek() {
+        return Err(format!("unexpected token {token:?}"));
+    }
+    Ok(expr)
+}
+
+fn tokenize(query: &str) -> Result<Vec<Token>, String> {
+    let chars = query.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let (token, len) = match (c, next) {
+            _ if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('{', _) => (Token::LBrace, 1),
+            ('}', _) => (Token::RBrace, 1),
+            ('(', _) => (Token::LParen, 1),
+            (')', _) => (Token::RParen, 1),
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('>', Some('>')) => (Token::Descendant, 2),
+            ('>', Some('=')) => (Token::Gte, 2),
+            ('>', _) => (Token::Gt, 1),
+            ('<', Some('=')) => (Token::Lte, 2),
+            ('<', _) => (Token::Lt, 1),
+            ('~', _) => (Token::Tilde, 1),
+            ('=', Some('~')) => (Token::Regex, 2),
+            ('=', _) => (Token::Eq, 1),
+            ('!', Some('=')) => (Token::NotEq, 2),
+            ('!', Some('~')) => (Token::NotRegex, 2),
+            ('"', _) | ('\'', _) => {
+                let (value, len) = read_string(&chars[i..])?;
+                (Token::Str(value), len)
+            }
+            _ if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                read_number(&chars[i..])?
+            }
+            _ if c.is_alphabetic() || c == '_' || c == '.' => {
+                let len = chars[i..]
+                    .iter()
+                    .take_while(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '-'))
+                    .count();
+                (Token::Ident(chars[i..i + len].iter().collect()), len)
+            }
+            _ => return Err(format!("unexpected character '{c}' at {i}")),
+        };
+        tokens.push(token);
+        i += len;
+    }
+    Ok(tokens)
+}
+
+fn read_string(chars: &[char]) -> Result<(String, usize), String> {
+    let quote = chars[0];
+    let mut value = String::new();
+    let mut i = 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if i + 1 < chars.len() => {
+                value.push(chars[i + 1]);
+                i += 2;
+            }
+            c if c == quote => return Ok((value, i + 1)),
+            c => {
+                value.push(c);
+                i += 1;
+            }
+        }
+    }
+    Err("unterminated string".to_string())
+}
+
+fn read_number(chars: &[char]) -> Result<(Token, usize), String> {
+    let num_len = 1 + chars[1..]
+        .iter()
+        .take_while(|c| c.is_ascii_digit() || **c == '.')
+        .count();
+    let num = chars[..num_len].iter().collect::<String>();
+    let num = num
+        .parse::<f64>()
+        .map_err(|_| format!("invalid number {num}"))?;
+    let unit_len = chars[num_len..]
+        .iter()
+        .take_while(|c| c.is_alphabetic())
+        .count();
+    if unit_len == 0 {
+        return Ok((Token::Num(num), num_len));
+    }
+    let unit = chars[num_len..num_len + unit_len]
+        .iter()
+        .collect::<String>();
+    let micros = match unit.as_str() {
+        "ns" => num / 1000.0,
+        "us" | "µs" => num,
+        "ms" => num * 1000.0,
+        "s" => num * 1_000_000.0,
+        "m" => num * 60_000_000.0,
+        "h" => num * 3_600_000_000.0,
+        _ => return Err(format!("invalid duration unit {unit}")),
+    };
+    Ok((Token::Duration(micros), num_len + unit_len))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), String> {
+        match self.next() {
+            Some(t) if t == token => Ok(()),
+            Some(t) => Err(format!("expected {token:?}, found {t:?}")),
+            None => Err(format!("expected {token:?}, found end of query")),
+        }
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_and_expr()?;
+        while self.eat(&Token::Or) {
+            left = Expr::Or(Box::new(left), Box::new(self.parse_and_expr()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_and_expr(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_structural()?;
+        while self.eat(&Token::And) {
+            left = Expr::And(Box::new(left), Box::new(self.parse_structural()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_structural(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_primary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Gt) => StructuralOp::Child,
+                Some(Token::Descendant) => StructuralOp::Descendant,
+                Some(Token::Tilde) => StructuralOp::Sibling,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            left = Expr::Structural(Box::new(left), op, Box::new(self.parse_primary()?));
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::LBrace) => {
+                if self.eat(&Token::RBrace) {
+                    return Ok(Expr::Spanset(Filter::True));
+                }
+                let filter = self.parse_filter()?;
+                self.expect(Token::RBrace)?;
+                Ok(Expr::Spanset(filter))
+            }
+            Some(Token::LParen) => {
+                let expr = self.parse_expr()?;
+                self.expect(Token::RParen)?;
+                Ok(expr)
+            }
+            Some(t) => Err(format!("expected a spanset, found {t:?}")),
+            None => Err("expected a spanset, found end of query".to_string()),
+        }
+    }
+
+    fn parse_filter(&mut self) -> Result<Filter, String> {
+        let mut left = self.parse_filter_and()?;
+        while self.eat(&Token::Or) {
+            left = Filter::Or(Box::new(left), Box::new(self.parse_filter_and()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_filter_and(&mut self) -> Result<Filter, String> {
+        let mut left = self.parse_filter_primary()?;
+        while self.eat(&Token::And) {
+            left = Filter::And(Box::new(left), Box::new(self.parse_filter_primary()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_filter_primary(&mut self) -> Result<Filter, String> {
+        match self.next() {
+            Some(Token::LParen) => {
+                let filter = self.parse_filter()?;
+                self.expect(Token::RParen)?;
+                Ok(filter)
+            }
+            Some(Token::Ident(name)) if name == "true" => Ok(Filter::True),
+            Some(Token::Ident(name)) => {
+                let field = parse_field(&name)?;
+                let op = match self.next() {
+                    Some(Token::Eq) => CompareOp::Eq,
+                    Some(Token::NotEq) => CompareOp::NotEq,
+                    Some(Token::Gt) => CompareOp::Gt,
+                    Some(Token::Gte) => CompareOp::Gte,
+                    Some(Token::Lt) => CompareOp::Lt,
+                    Some(Token::Lte) => CompareOp::Lte,
+                    Some(Token::Regex) => CompareOp::Regex,
+                    Some(Token::NotRegex) => CompareOp::NotRegex,
+                    t => return Err(format!("expected an operator after {name}, found {t:?}")),
+                };
+                let value = self.next();
+                let value = parse_value(&field, op, value)?;
+                Ok(Filter::Cond(Condition { field, op, value }))
+            }
+            Some(t) => Err(format!("expected a condition, found {t:?}")),
+            None => Err("expected a condition, found end of query".to_string()),
+        }
+    }
+}
+
+fn parse_field(name: &str) -> Result<Field, String> {
+    let field = match name {
+        "name" | "span.name" => Field::Name,
+        "status" | "span.status" => Field::Status,
+        "kind" | "span.kind" => Field::Kind,
+        "duration" | "span.duration" => Field::Duration,
+        "resource.service.name" | ".service.name" | "service.name" | "service_name" => {
+            Field::ServiceName
+        }
+        _ => {
+            let attr = name
+                .strip_prefix("span.")
+                .or_else(|| name.strip_prefix('.'))
+                .unwrap_or(name);
+            if attr.is_empty() || attr.starts_with("resource.") {
+                return Err(format!("unsupported field {name}"));
+            }
+            let mut attr = attr.to_string();
+            format_key(&mut attr);
+            Field::Attribute(attr)
+        }
+    };
+    Ok(field)
+}
+
+fn parse_value(field: &Field, op: CompareOp, token: Option<Token>) -> Result<Value, String> {
+    let Some(token) = token else {
+        return Err("expected a value, found end of query".to_string());
+    };
+    if matches!(op, CompareOp::Regex | CompareOp::NotRegex) {
+        let Token::Str(pattern) = token else {
+            return Err(format!("expected a regex string, found {token:?}"));
+        };
+        return Regex::new(&pattern)
+            .map(Value::Regex)
+            .map_err(|e| format!("invalid regex {pattern}: {e}"));
+    }
+    match field {
+        Field::Status | Field::Kind => {
+            if !matches!(op, CompareOp::Eq | CompareOp::NotEq) {
+                return Err(format!("{} only supports = and !=", field.column()));
+            }
+            let (Token::Ident(v) | Token::Str(v)) = token else {
+                return Err(format!("expected a {}, found {token:?}", field.column()));
+            };
+            let v = v.to_lowercase();
+            let value = match (field, v.as_str()) {
+                (Field::Status, "error" | "ok" | "unset") => v.to_uppercase(),
+                (Field::Kind, "unspecified") => "0".to_string(),
+                (Field::Kind, "internal") => "1".to_string(),
+                (Field::Kind, "server") => "2".to_string(),
+                (Field::Kind, "client") => "3".to_string(),
+                (Field::Kind, "producer") => "4".to_string(),
+                (Field::Kind, "consumer") => "5".to_string(),
+                _ => return Err(format!("invalid {} {v}", field.column())),
+            };
+            Ok(Value::String(value))
+        }
+        Field::Duration => match token {
+            Token::Duration(v) | Token::Num(v) => Ok(Value::Number(v)),
+            t => Err(format!("expected a duration, found {t:?}")),
+        },
+        _ => match token {
+            Token::Str(v) => Ok(Value::String(v)),
+            Token::Num(v) => Ok(Value::Number(v)),
+            Token::Duration(v) => Ok(Value::Number(v)),
+            Token::Ident(v) if v == "true" || v == "false" => Ok(Value::Bool(v == "true")),
+            t => Err(format!("expected a value, found {t:?}")),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cond(field: Field, op: CompareOp, value: Value) -> Filter {
+        Filter::Cond(Condition { field, op, value })
+    }
+
+    #[test]
+    fn test_parse_structural() {
+        let expr = parse(
+            r#"{ resource.service.name = "A" && status = error } >> { service.name = "B" && duration > 2s }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            expr,
+            Expr::Structural(
+                Box::new(Expr::Spanset(Filter::And(
+                    Box::new(cond(
+                        Field::ServiceName,
+                        CompareOp::Eq,
+                        Value::String("A".to_string())
+                    )),
+                    Box::new(cond(
+                        Field::Status,
+                        CompareOp::Eq,
+                        Value::String("ERROR".to_string())
+                    )),
+                ))),
+                StructuralOp::Descendant,
+                Box::new(Expr::Spanset(Filter::And(
+                    Box::new(cond(
+                        Field::ServiceName,
+                        CompareOp::Eq,
+                        Value::String("B".to_string())
+                    )),
+                    Box::new(cond(
+                        Field::Duration,
+                        CompareOp::Gt,
+                        Value::Number(2_000_000.0)
+                    )),
+                ))),
+            )
+        );
+        assert_eq!(expr.required_services(), vec!["A", "B"]);
+    }
+
+    #[test]
+    fn test_parse_precedence() {
+        let expr = parse(r#"{ kind = server } > { .http.method = "GET" } || {}"#).unwrap();
+        let Expr::Or(left, right) = expr else {
+            panic!("|| should have the lowest precedence");
+        };
+        assert_eq!(*right, Expr::Spanset(Filter::True));
+        let Expr::Structural(parent, StructuralOp::Child, child) = *left else {
+            panic!("expected a child operator");
+        };
+        assert_eq!(
+            *parent,
+            Expr::Spanset(cond(
+                Field::Kind,
+                CompareOp::Eq,
+                Value::String("2".to_string())
+            ))
+        );
+        assert_eq!(
+            *child,
+            Expr::Spanset(cond(
+                Field::Attribute("http_method".to_string()),
+                CompareOp::Eq,
+                Value::String("GET".to_string())
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_values() {
+        let Expr::Spanset(Filter::Or(l, r)) =
+            parse(r#"{ (name =~ "GET .*" || .retry = true) }"#).unwrap()
+        else {
+            panic!("expected an or filter");
+        };
+        assert_eq!(
+            *l,
+            cond(
+                Field::Name,
+                CompareOp::Regex,
+                Value::Regex(Regex::new("GET .*").unwrap())
+            )
+        );
+        assert_eq!(
+            *r,
+            cond(
+                Field::Attribute("retry".to_string()),
+                CompareOp::Eq,
+                Value::Bool(true)
+            )
+        );
+        assert_eq!(
+            parse("{ duration <= 1.5ms && .http.status_code >= -1 }").unwrap(),
+            Expr::Spanset(Filter::And(
+                Box::new(cond(Field::Duration, CompareOp::Lte, Value::Number(1500.0))),
+                Box::new(cond(
+                    Field::Attribute("http_status_code".to_string()),
+                    CompareOp::Gte,
+                    Value::Number(-1.0)
+                )),
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("").is_err());
+        assert!(parse("{ status > error }").is_err());
+        assert!(parse("{ kind = remote }").is_err());
+        assert!(parse("{ duration > 2d }").is_err());
+        assert!(parse(r#"{ name = "a" "#).is_err());
+        assert!(parse(r#"{ name = "a }"#).is_err());
+        assert!(parse(r#"{ name =~ "(" }"#).is_err());
+        assert!(parse(r#"{ name = "a" } }"#).is_err());
+        assert!(parse(r#"{ resource.host = "a" }"#).is_err());
+    }
+
+    #[test]
+    fn test_required_services() {
+        let expr =
+            parse(r#"{ service.name = "A" } || { service.name = "A" && name = "x" }"#).unwrap();
+        assert_eq!(expr.required_services(), vec!["A"]);
+        let expr = parse(r#"{ service.name = "A" || service.name = "B" }"#).unwrap();
+        assert!(expr.required_services().is_empty());
+        let expr = parse(r#"{ service.name != "A" }"#).unwrap();
+        assert!(expr.required_services().is_empty());
+    }
+
+    #[test]
+    fn test_columns() {
+        let expr = parse(r#"{ service.name = "A" } > { name = "x" || duration > 2s }"#).unwrap();
+        assert_eq!(
+            expr.columns(),
+            vec!["service_name", "operation_name", "duration"]
+        );
+        assert!(parse("{}").unwrap().columns().is_empty());
+    }
+}
@@ -0,0 +1,486 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Zipkin v2 span ingestion, the spans are converted to the otlp model.
+//!
+//! ref: https://zipkin.io/zipkin-api/#/default/post_spans
+
+use std::{collections::HashMap, io::Error};
+
+use actix_web::{HttpResponse, http, web};
+use config::meta::otlp::OtlpRequestType;
+use opentelemetry::trace::{SpanId, TraceId};
+use opentelemetry_proto::tonic::{
+    collector::trace::v1::ExportTraceServiceRequest,
+    common::v1::{AnyValue, KeyValue, any_value::Value},
+    resource::v1::Resource,
+    trace::v1::{
+        ResourceSpans, ScopeSpans, Span, Status,
+        span::{Event, SpanKind},
+        status::StatusCode,
+    },
+};
+use prost::Message;
+use serde::Deserialize;
+
+use crate::common::meta::http::HttpResponse as MetaHttpResponse;
+
+const UNKNOWN_SERVICE: &str = "unknown_service";
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZipkinSpan {
+    pub trace_id: String,
+    #[serde(default)]
+    pub parent_id: Option<String>,
+    pub id: String,
+    #[serde(default)]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub timestamp: Option<u64>, // microseconds
+    #[serde(default)]
+    pub duration: Option<u64>, // microseconds
+    #[serde(default)]
+    pub local_endpoint: Option<ZipkinEndpoint>,
+    #[serde(default)]
+    pub remote_endpoint: Option<ZipkinEndpoint>,
+    #[serde(default)]
+    pub annotations: Vec<ZipkinAnnotation>,
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+    #[serde(default)]
+    pub debug: bool,
+    #[serde(default)]
+    pub shared: bool,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZipkinEndpoint {
+    #[serde(default)]
+    pub service_name: Option<String>,
+    #[serde(default)]
+    pub ipv4: Option<String>,
+    #[serde(default)]
+    pub ipv6: Option<String>,
+    #[serde(default)]
+    pub port: Option<i32>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ZipkinAnnotation {
+    pub timestamp: u64, // microseconds
+    pub value: String,
+}
+
+/// Messages of zipkin.proto3
+mod proto {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ListOfSpans {
+        #[prost(message, repeated, tag = "1")]
+        pub spans: Vec<Span>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Span {
+        #[prost(bytes = "vec", tag = "1")]
+        pub trace_id: Vec<u8>,
+        #[prost(bytes = "vec", tag = "2")]
+        pub parent_id: Vec<u8>,
+        #[prost(bytes = "vec", tag = "3")]
+        pub id: Vec<u8>,
+        #[prost(int32, tag = "4")]
+        pub kind: i32,
+        #[prost(string, tag = "5")]
+        pub name: String,
+        #[prost(fixed64, tag = "6")]
+        pub timestamp: u64,
+        #[prost(uint64, tag = "7")]
+        pub duration: u64,
+        #[prost(message, optional, tag = "8")]
+        pub local_endpoint: Option<Endpoint>,
+        #[prost(message, optional, tag = "9")]
+        pub remote_endpoint: Option<Endpoint>,
+        #[prost(message, repeated, tag = "10")]
+        pub annotations: Vec<Annotation>,
+        #[prost(map = "string, string", tag = "11")]
+        pub tags: std::collections::HashMap<String, String>,
+        #[prost(bool, tag = "12")]
+        pub debug: bool,
+        #[prost(bool, tag = "13")]
+        pub shared: bool,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Endpoint {
+        #[prost(string, tag = "1")]
+        pub service_name: String,
+        #[prost(bytes = "vec", tag = "2")]
+        pub ipv4: Vec<u8>,
+        #[prost(bytes = "vec", tag = "3")]
+        pub ipv6: Vec<u8>,
+        #[prost(int32, tag = "4")]
+        pub port: i32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Annotation {
+        #[prost(fixed64, tag = "1")]
+        pub timestamp: u64,
+        #[prost(string, tag = "2")]
+        pub value: String,
+    }
+}
+
+impl From<proto::Endpoint> for ZipkinEndpoint {
+    fn from(e: proto::Endpoint) -> Self {
+        let ipv4 = <[u8; 4]>::try_from(e.ipv4.as_slice())
+            .ok()
+            .map(|ip| std::net::Ipv4Addr::from(ip).to_string());
+        let ipv6 = <[u8; 16]>::try_from(e.ipv6.as_slice())
+            .ok()
+            .map(|ip| std::net::Ipv6Addr::from(ip).to_string());
+        Self {
+            service_name: (!e.service_name.is_empty()).then_some(e.service_name),
+            ipv4,
+            ipv6,
+            port: (e.port > 0).then_some(e.port),
+        }
+    }
+}
+
+impl From<proto::Span> for ZipkinSpan {
+    fn from(s: proto::Span) -> Self {
+        let kind = match s.kind {
+            1 => Some("CLIENT".to_string()),
+            2 => Some("SERVER".to_string()),
+            3 => Some("PRODUCER".to_string()),
+            4 => Some("CONSUMER".to_string()),
+            _ => None,
+        };
+        Self {
+            trace_id: hex::encode(s.trace_id),
+            parent_id: (!s.parent_id.is_empty()).then(|| hex::encode(s.parent_id)),
+            id: hex::encode(s.id),
+            kind,
+            name: (!s.name.is_empty()).then_some(s.name),
+            timestamp: (s.timestamp > 0).then_some(s.timestamp),
+            duration: (s.duration > 0).then_some(s.duration),
+            local_endpoint: s.local_endpoint.map(Into::into),
+            remote_endpoint: s.remote_endpoint.map(Into::into),
+            annotations: s
+                .annotations
+                .into_iter()
+                .map(|a| ZipkinAnnotation {
+                    timestamp: a.timestamp,
+                    value: a.value,
+                })
+                .collect(),
+            tags: s.tags,
+            debug: s.debug,
+            shared: s.shared,
+        }
+    }
+}
+
+pub async fn ingest_json(
+    org_id: &str,
+    body: web::Bytes,
+    in_stream_name: Option<&str>,
+) -> Result<HttpResponse, Error> {
+    let spans = match serde_json::from_slice::<Vec<ZipkinSpan>>(body.as_ref()) {
+        Ok(v) => v,
+        Err(e) => {
+            log::error!("[TRACES:ZIPKIN] Invalid json: org_id: {org_id}, error: {e}");
+            return Ok(bad_request(format!("Invalid json: {e}")));
+        }
+    };
+    ingest(org_id, spans, OtlpRequestType::HttpJson, in_stream_name).await
+}
+
+pub async fn ingest_proto(
+    org_id: &str,
+    body: web::Bytes,
+    in_stream_name: Option<&str>,
+) -> Result<HttpResponse, Error> {
+    let spans = match proto::ListOfSpans::decode(body) {
+        Ok(v) => v.spans.into_iter().map(ZipkinSpan::from).collect(),
+        Err(e) => {
+            log::error!("[TRACES:ZIPKIN] Invalid proto: org_id: {org_id}, error: {e}");
+            return Ok(bad_request(format!("Invalid proto: {e}")));
+        }
+    };
+    ingest(org_id, spans, OtlpRequestType::HttpProtobuf, in_stream_name).await
+}
+
+async fn ingest(
+    org_id: &str,
+    spans: Vec<ZipkinSpan>,
+    req_type: OtlpRequestType,
+    in_stream_name: Option<&str>,
+) -> Result<HttpResponse, Error> {
+    let request = match to_otlp(spans) {
+        Ok(v) => v,
+        Err(e) => {
+            log::error!("[TRACES:ZIPKIN] Invalid span: org_id: {org_id}, error: {e}");
+            return Ok(bad_request(e));
+        }
+    };
+    let resp = super::handle_otlp_request(org_id, request, req_type, in_stream_name).await?;
+    Ok(super::accepted_response(resp))
+}
+
+fn bad_request(message: impl ToString) -> HttpResponse {
+    HttpResponse::BadRequest().json(MetaHttpResponse::error(
+        http::StatusCode::BAD_REQUEST,
+        message,
+    ))
+}
+
+/// Converts zipkin spans to an otlp request, the spans are grouped by the
+/// service name of the local endpoint.
+pub fn to_otlp(spans: Vec<ZipkinSpan>) -> Result<ExportTraceServiceRequest, String> {
+    let mut services: Vec<(String, Vec<Span>)> = Vec::new();
+    for span in spans {
+        let service_name = span
+            .local_endpoint
+            .as_ref()
+            .and_then(|e| e.service_name.clone())
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| UNKNOWN_SERVICE.to_string());
+        let span = convert_span(span)?;
+        match services.iter_mut().find(|(name, _)| name == &service_name) {
+            Some((_, spans)) => spans.push(span),
+            None => services.push((service_name, vec![span])),
+        }
+    }
+    let resource_spans = services
+        .into_iter()
+        .map(|(service_name, spans)| ResourceSpans {
+            resource: Some(Resource {
+                attributes: vec![string_attr(super::SERVICE_NAME, service_name)],
+                dropped_attributes_count: 0,
+            }),
+            scope_spans: vec![ScopeSpans {
+                scope: None,
+                spans,
+                schema_url: "".to_string(),
+            }],
+            schema_url: "".to_string(),
+        })
+        .collect();
+    Ok(ExportTraceServiceRequest { resource_spans })
+}
+
+fn convert_span(span: ZipkinSpan) -> Result<Span, String> {
+    let trace_id = TraceId::from_hex(&span.trace_id)
+        .map_err(|_| format!("invalid trace id: {}", span.trace_id))?;
+    let span_id =
+        SpanId::from_hex(&span.id).map_err(|_| format!("invalid span id: {}", span.id))?;
+    let parent_span_id = match span.parent_id.as_deref() {
+        Some(id) if !id.is_empty() => SpanId::from_hex(id)
+            .map_err(|_| format!("invalid parent id: {id}"))?
+            .to_bytes()
+            .to_vec(),
+        _ => vec![],
+    };
+    let kind = match span.kind.as_deref() {
+        Some("CLIENT") => SpanKind::Client,
+        Some("SERVER") => SpanKind::Server,
+        Some("PRODUCER") => SpanKind::Producer,
+        Some("CONSUMER") => SpanKind::Consumer,
+        _ => SpanKind::Internal,
+    };
+    let start_time = span.timestamp.unwrap_or_default() * 1000;
+    let end_time = start_time + span.duration.unwrap_or_default() * 1000;
+
+    let mut attributes = Vec::with_capacity(span.tags.len() + 4);
+    let mut status = None;
+    for (key, value) in span.tags {
+        if key == "error" {
+            status = Some(Status {
+                message: value.clone(),
+                code: StatusCode::Error as i32,
+            });
+        }
+        attributes.push(string_attr(&key, value));
+    }
+    if let Some(local) = span.local_endpoint {
+        endpoint_attrs(&mut attributes, "net.host", local);
+    }
+    if let Some(remote) = span.remote_endpoint {
+        if let Some(name) = remote.service_name.clone().filter(|v| !v.is_empty()) {
+            attributes.push(string_attr("peer.service", name));
+        }
+        endpoint_attrs(&mut attributes, "net.peer", remote);
+    }
+    if span.debug {
+        attributes.push(bool_attr("zipkin.debug", true));
+    }
+    if span.shared {
+        attributes.push(bool_attr("zipkin.shared", true));
+    }
+
+    let events = span
+        .annotations
+        .into_iter()
+        .map(|a| Event {
+            time_unix_nano: a.timestamp * 1000,
+            name: a.value,
+            attributes: vec![],
+            dropped_attributes_count: 0,
+        })
+        .collect();
+
+    Ok(Span {
+        trace_id: trace_id.to_bytes().to_vec(),
+        span_id: span_id.to_bytes().to_vec(),
+        trace_state: "".to_string(),
+        parent_span_id,
+        flags: 0,
+        name: span.name.unwrap_or_default(),
+        kind: kind as i32,
+        start_time_unix_nano: start_time,
+        end_time_unix_nano: end_time,
+        attributes,
+        dropped_attributes_count: 0,
+        events,
+        dropped_events_count: 0,
+        links: vec![],
+        dropped_links_count: 0,
+        status,
+    })
+}
+
+fn endpoint_attrs(attributes: &mut Vec<KeyValue>, prefix: &str, endpoint: ZipkinEndpoint) {
+    if let Some(ip) = endpoint.ipv4.or(endpoint.ipv6) {
+        attributes.push(string_attr(&format!("{prefix}.ip"), ip));
+    }
+    if let Some(port) = endpoint.port {
+        attributes.push(KeyValue {
+            key: format!("{prefix}.port"),
+            value: Some(AnyValue {
+                value: Some(Value::IntValue(port as i64)),
+            }),
+        });
+    }
+}
+
+fn string_attr(key: &str, value: String) -> KeyValue {
+    KeyValue {
+        key: key.to_string(),
+        value: Some(AnyValue {
+            value: Some(Value::StringValue(value)),
+        }),
+    }
+}
+
+fn bool_attr(key: &str, value: bool) -> KeyValue {
+    KeyValue {
+        key: key.to_string(),
+        value: Some(AnyValue {
+            value: Some(Value::BoolValue(value)),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_to_otlp() {
+        let body = r#"[{
+            "traceId": "5af7183fb1d4cf5f",
+            "parentId": "6b221d5bc9e6496c",
+            "id": "352bff9a74ca9ad2",
+            "kind": "CLIENT",
+            "name": "get /api",
+            "timestamp": 1556604172355737,
+            "duration": 1431,
+            "localEndpoint": {"serviceName": "frontend", "ipv4": "192.168.99.1"},
+            "remoteEndpoint": {"serviceName": "backend", "ipv4": "172.19.0.2", "port": 8080},
+            "annotations": [{"timestamp": 1556604172355800, "value": "wire send"}],
+            "tags": {"http.method": "GET", "error": "timeout"}
+        }]"#;
+        let spans: Vec<ZipkinSpan> = serde_json::from_str(body).unwrap();
+        let req = to_otlp(spans).unwrap();
+        assert_eq!(req.resource_spans.len(), 1);
+        let rs = &req.resource_spans[0];
+        assert_eq!(
+            rs.resource.as_ref().unwrap().attributes[0],
+            string_attr(super::super::SERVICE_NAME, "frontend".to_string())
+        );
+        let span = &rs.scope_spans[0].spans[0];
+        assert_eq!(span.trace_id.len(), 16);
+        assert_eq!(
+            hex::encode(&span.trace_id),
+            "00000000000000005af7183fb1d4cf5f"
+        );
+        assert_eq!(hex::encode(&span.parent_span_id), "6b221d5bc9e6496c");
+        assert_eq!(span.kind, SpanKind::Client as i32);
+        assert_eq!(span.start_time_unix_nano, 1556604172355737000);
+        assert_eq!(span.end_time_unix_nano - span.start_time_unix_nano, 1431000);
+        assert_eq!(span.events[0].name, "wire send");
+        assert_eq!(span.status.as_ref().unwrap().code, StatusCode::Error as i32);
+        assert!(
+            span.attributes
+                .contains(&string_attr("peer.service", "backend".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_proto_to_otlp() {
+        let list = proto::ListOfSpans {
+            spans: vec![proto::Span {
+                trace_id: vec![1; 16],
+                id: vec![2; 8],
+                kind: 2,
+                name: "handle".to_string(),
+                timestamp: 1556604172355737,
+                duration: 10,
+                local_endpoint: Some(proto::Endpoint {
+                    service_name: "backend".to_string(),
+                    ipv4: vec![10, 0, 0, 1],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+        };
+        let list = proto::ListOfSpans::decode(list.encode_to_vec().as_slice()).unwrap();
+        let spans = list.spans.into_iter().map(ZipkinSpan::from).collect();
+        let req = to_otlp(spans).unwrap();
+        let span = &req.resource_spans[0].scope_spans[0].spans[0];
+        assert_eq!(span.trace_id, vec![1; 16]);
+        assert!(span.parent_span_id.is_empty());
+        assert_eq!(span.kind, SpanKind::Server as i32);
+        assert!(
+            span.attributes
+                .contains(&string_attr("net.host.ip", "10.0.0.1".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_invalid_span_id() {
+        let spans = vec![ZipkinSpan {
+            trace_id: "5af7183fb1d4cf5f".to_string(),
+            id: "xyz".to_string(),
+            ..Default::default()
+        }];
+        assert!(to_otlp(spans).is_err());
+    }
+}
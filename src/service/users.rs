@@ -294,6 +294,8 @@ pub async fn update_user(
         let mut new_user;
         let mut is_updated = false;
         let mut is_org_updated = false;
+        let mut is_password_updated = false;
+        let mut is_role_updated = false;
         let mut message = "";
         #[cfg(feature = "enterprise")]
         let mut custom_roles = vec![];
@@ -343,6 +345,7 @@ pub async fn update_user(
                         new_user.password_ext = Some(get_hash(&new_pass, password_ext_salt));
                         log::info!("Password self updated for user: {}", email);
                         is_updated = true;
+                        is_password_updated = true;
                     } else {
                         message = "Existing/old password mismatch, please provide valid existing password";
                         return Ok(HttpResponse::BadRequest().json(MetaHttpResponse::message(
@@ -365,6 +368,7 @@ pub async fn update_user(
                     log::info!("Password by root updated for user: {}", email);
 
                     is_updated = true;
+                    is_password_updated = true;
                 } else if user.new_password.is_some() {
                     message = "You are not authorised to change the password";
                 }
@@ -402,6 +406,7 @@ pub async fn update_user(
                             custom_roles.extend(new_org_role.custom_role.unwrap());
                         }
                         is_org_updated = true;
+                        is_role_updated = true;
                     }
                 }
                 if user.token.is_some() {
@@ -545,6 +550,13 @@ pub async fn update_user(
                     }
                 }
 
+                // the sessions opened with the old password or role are logged out
+                if (is_password_updated || is_role_updated)
+                    && let Err(e) = super::session::revoke_user_sessions(email).await
+                {
+                    log::error!("Error logging out the sessions of user {email}: {e}");
+                }
+
                 #[cfg(not(feature = "enterprise"))]
                 log::debug!("Role changed from {:?} to {:?}", old_role, new_role);
                 Ok(HttpResponse::Ok().json(MetaHttpResponse::message(
@@ -47,7 +47,7 @@ use crate::{
     },
     handler::http::request::ws::session::send_message,
     service::{
-        search::{self as SearchService, cache, sql::Sql},
+        search::{self as SearchService, cache, masking::Masker, sql::Sql},
         setup_tracing_with_trace_id,
         websocket_events::{WsServerEvents, calculate_progress_percentage},
     },
@@ -159,6 +159,28 @@ pub async fn handle_search_request(
             return Ok(());
         }
     }
+    #[cfg(not(feature = "enterprise"))]
+    for stream_name in stream_names.iter() {
+        if !crate::service::grants::is_allowed(
+            org_id,
+            user_id,
+            config::meta::grant::GrantResource::Stream,
+            Some(stream_type),
+            stream_name,
+            config::meta::grant::GrantLevel::Read,
+        )
+        .await
+        {
+            let err_res = WsServerEvents::error_response(
+                &Error::Message("Unauthorized Access".to_string()),
+                Some(req_id.to_string()),
+                Some(trace_id),
+                Default::default(),
+            );
+            send_message(req_id, err_res.to_json()).await?;
+            return Ok(());
+        }
+    }
 
     // handle search result size
     let req_size = if req.payload.query.size == 0 {
@@ -504,6 +526,7 @@ pub async fn handle_cache_responses_and_deltas(
                     req.fallback_order_by_col.clone(),
                     cache_order_by,
                     start_timer,
+                    user_id,
                 )
                 .await?;
                 cached_resp_iter.next();
@@ -543,6 +566,7 @@ pub async fn handle_cache_responses_and_deltas(
                 req.fallback_order_by_col.clone(),
                 cache_order_by,
                 start_timer,
+                user_id,
             )
             .await?;
         }
@@ -614,6 +638,10 @@ async fn process_delta(
         partitions.sort_by(|a, b| b[0].cmp(&a[0]));
     }
 
+    // the accumulated results keep the raw values for the result cache
+    let masker = get_masker(&req, user_id).await;
+    let is_values = req.values_event_context.is_some();
+
     for (idx, &[start_time, end_time]) in partitions.iter().enumerate() {
         let mut req = req.clone();
         req.payload.query.start_time = start_time;
@@ -682,6 +710,9 @@ async fn process_delta(
                 search_res.hits = top_k_values?;
             }
 
+            if let Some(masker) = &masker {
+                masker.mask_search_response(&mut search_res, is_values);
+            }
             let ws_search_res = WsServerEvents::SearchResponse {
                 trace_id: trace_id.clone(),
                 results: Box::new(search_res.clone()),
@@ -818,6 +849,7 @@ async fn send_cached_responses(
     fallback_order_by_col: Option<String>,
     cache_order_by: &OrderBy,
     start_timer: &mut Instant,
+    user_id: &str,
 ) -> Result<(), Error> {
     log::info!(
         "[WS_SEARCH]: Processing cached response for trace_id: {}",
@@ -857,6 +889,14 @@ async fn send_cached_responses(
     // reset start timer
     *start_timer = Instant::now();
 
+    // the accumulated results keep the raw values for the result cache
+    if let Some(masker) = get_masker(req, user_id).await {
+        masker.mask_search_response(
+            &mut cached.cached_response,
+            req.values_event_context.is_some(),
+        );
+    }
+
     // Send the cached response
     let ws_search_res = WsServerEvents::SearchResponse {
         trace_id: trace_id.to_string(),
@@ -967,6 +1007,10 @@ pub async fn do_partitioned_search(
         &partitions
     );
 
+    // the accumulated results keep the raw values for the result cache
+    let masker = get_masker(&req, user_id).await;
+    let is_values = req.values_event_context.is_some();
+
     for (idx, &[start_time, end_time]) in partitions.iter().enumerate() {
         let mut req = req.clone();
         req.payload.query.start_time = start_time;
@@ -1028,6 +1072,10 @@ pub async fn do_partitioned_search(
                 log::debug!("Top k values for partition {idx} took {:?}", duration);
             }
 
+            if let Some(masker) = &masker {
+                masker.mask_search_response(&mut search_res, is_values);
+            }
+
             // Send the cached response
             let ws_search_res = WsServerEvents::SearchResponse {
                 trace_id: trace_id.to_string(),
@@ -1123,6 +1171,18 @@ async fn send_partial_search_resp(
     Ok(())
 }
 
+/// Returns the masking of the results for the user, the results are masked
+/// right before they are sent.
+async fn get_masker(req: &SearchEventReq, user_id: &str) -> Option<Masker> {
+    SearchService::masking::get_query_masker(
+        &req.org_id,
+        req.stream_type,
+        &req.payload.query.sql,
+        Some(user_id),
+    )
+    .await
+}
+
 #[tracing::instrument(
     name = "service:websocket_events:search::write_results_to_cache",
     skip_all
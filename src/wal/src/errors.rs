@@ -86,6 +86,10 @@ pub enum Error {
     WriteQueueFull {
         idx: usize,
     },
+    #[snafu(display("Invalid file header: {}", message))]
+    InvalidHeader {
+        message: String,
+    },
     #[snafu(display("Provided path '{}' has no parent directory", path.display()))]
     NoParentDir {
         path: PathBuf,
@@ -59,6 +59,13 @@ impl Reader<BufReader<File>> {
             let header_len = buf
                 .read_u32::<BigEndian>()
                 .context(UnableToReadLengthSnafu)? as usize;
+            let file_len = f.get_ref().metadata().map(|m| m.len()).unwrap_or(u64::MAX);
+            ensure!(
+                header_len as u64 <= file_len,
+                InvalidHeaderSnafu {
+                    message: format!("header length {header_len} exceeds file size {file_len}"),
+                }
+            );
             let mut bytes = vec![0u8; header_len];
             f.read_exact(&mut bytes)
                 .context(UnableToReadArraySnafu { length: header_len })?;
@@ -106,28 +113,37 @@ impl Reader<BufReader<File>> {
         let mut cursor = 0;
 
         while cursor < bytes.len() {
-            // read key len
-            let key_len = u32::from_be_bytes(bytes[cursor..cursor + 4].try_into().unwrap());
-            cursor += 4;
-
-            // read key value
-            let key = String::from_utf8(bytes[cursor..cursor + key_len as usize].to_vec()).unwrap();
-            cursor += key_len as usize;
-
-            // read value len
-            let value_len = u32::from_be_bytes(bytes[cursor..cursor + 4].try_into().unwrap());
-            cursor += 4;
-
-            // read value value
-            let value =
-                String::from_utf8(bytes[cursor..cursor + value_len as usize].to_vec()).unwrap();
-            cursor += value_len as usize;
-
+            // read key
+            let key = Self::read_header_field(bytes, &mut cursor)?;
+            // read value
+            let value = Self::read_header_field(bytes, &mut cursor)?;
             header.insert(key, value);
         }
 
         Ok(header)
     }
+
+    // reads a length prefixed string, a truncated or corrupt header returns an error
+    fn read_header_field(bytes: &[u8], cursor: &mut usize) -> Result<String> {
+        let Some(len) = bytes.get(*cursor..*cursor + 4) else {
+            return InvalidHeaderSnafu {
+                message: format!("truncated field length at {}", *cursor),
+            }
+            .fail();
+        };
+        let len = u32::from_be_bytes(len.try_into().unwrap()) as usize;
+        *cursor += 4;
+        let Some(value) = bytes.get(*cursor..(*cursor).saturating_add(len)) else {
+            return InvalidHeaderSnafu {
+                message: format!("truncated field of length {len} at {}", *cursor),
+            }
+            .fail();
+        };
+        *cursor += len;
+        String::from_utf8(value.to_vec()).map_err(|e| Error::InvalidHeader {
+            message: e.to_string(),
+        })
+    }
 }
 
 impl<R> Reader<R>
@@ -71,6 +71,29 @@ fn test_wal_build() {
     assert!(reader.read_entry().unwrap().is_none());
 }
 
+#[test]
+fn test_wal_corrupt_header() {
+    let dir = tempdir().unwrap();
+    let dir = dir.path();
+    let mut header = wal::FileHeader::new();
+    header.insert("key1".into(), "value1".into());
+    let path = build_file_path(dir, "org", "stream", "1".to_string());
+    let (mut writer, _) = Writer::new(path.clone(), 1024_1024, 8 * 1024, Some(header)).unwrap();
+    writer.write(b"hello world").unwrap();
+    writer.close().unwrap();
+
+    // overwrite the length of the first header key
+    let mut data = std::fs::read(&path).unwrap();
+    let offset = wal::FILE_TYPE_IDENTIFIER_LEN + wal::WAL_FILE_HEADER_LEN;
+    data[offset..offset + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+    std::fs::write(&path, &data).unwrap();
+    assert!(Reader::from_path(&path).is_err());
+
+    // cut the file in the middle of the header
+    std::fs::write(&path, &data[..offset + 2]).unwrap();
+    assert!(Reader::from_path(&path).is_err());
+}
+
 #[test]
 fn test_position() {
     let entry_num = 100;